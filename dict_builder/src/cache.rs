@@ -0,0 +1,107 @@
+use std::{
+    collections::{hash_map::DefaultHasher, BTreeMap},
+    fs,
+    hash::{Hash, Hasher},
+    path::Path,
+};
+
+use crate::{DictConfig, WordEntry};
+
+/// Bumped whenever scoring changes, so that caches from older builders are ignored
+const CACHE_VERSION: u32 = 1;
+
+/// The output of a previous build, along with a hash of the inputs that produced it
+pub struct DictCache {
+    pub input_hash: u64,
+    pub entries: Vec<WordEntry>,
+}
+
+impl DictCache {
+    /// Reads a cache in the wordlist format, with the input hash on its first line.
+    /// Missing or unreadable caches are treated as absent.
+    pub fn load(path: &Path) -> Option<Self> {
+        let contents = fs::read_to_string(path).ok()?;
+        let mut lines = contents.lines();
+        let input_hash = lines.next()?.strip_prefix("# ")?.parse().ok()?;
+        let entries = lines
+            .map(|line| line.parse())
+            .collect::<Result<Vec<WordEntry>, _>>()
+            .ok()?;
+
+        Some(Self {
+            input_hash,
+            entries,
+        })
+    }
+
+    pub fn save(&self, path: &Path) {
+        if let Some(dir) = path.parent() {
+            _ = fs::create_dir_all(dir);
+        }
+
+        let mut contents = format!("# {}\n", self.input_hash);
+        for entry in &self.entries {
+            contents.push_str(&entry.to_string());
+            contents.push('\n');
+        }
+
+        if let Err(e) = fs::write(path, contents) {
+            println!(
+                "Couldn't write the dictionary cache to {}: {e}",
+                path.display()
+            );
+        }
+    }
+
+    /// The final substring score of each cached word
+    pub fn scores(&self) -> BTreeMap<String, usize> {
+        self.entries
+            .iter()
+            .map(|entry| (entry.word.clone(), entry.substring_score))
+            .collect()
+    }
+}
+
+fn hash_file(path: &Path, hasher: &mut DefaultHasher) {
+    path.file_name().hash(hasher);
+    if let Ok(contents) = fs::read(path) {
+        contents.hash(hasher);
+    }
+}
+
+/// Hashes the contents of every input file along with the filtering options,
+/// so that any change to them invalidates the cache.
+pub fn hash_inputs(config: &DictConfig) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    CACHE_VERSION.hash(&mut hasher);
+    config.max_scowl_size.hash(&mut hasher);
+    config.language.hash(&mut hasher);
+    config.letters.hash(&mut hasher);
+    config.categories.hash(&mut hasher);
+
+    for path in [
+        &config.word_frequencies,
+        &config.wordnik_wordlist,
+        &config.objectionable,
+    ] {
+        hash_file(path, &mut hasher);
+    }
+    "additions".hash(&mut hasher);
+    for path in &config.additions {
+        hash_file(path, &mut hasher);
+    }
+    "removals".hash(&mut hasher);
+    for path in &config.removals {
+        hash_file(path, &mut hasher);
+    }
+
+    let mut scowl_files: Vec<_> = fs::read_dir(&config.scowl_wordlists)
+        .map(|files| files.flatten().map(|f| f.path()).collect())
+        .unwrap_or_default();
+    scowl_files.sort();
+    for path in scowl_files {
+        hash_file(&path, &mut hasher);
+    }
+
+    hasher.finish()
+}
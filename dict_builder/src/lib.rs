@@ -0,0 +1,489 @@
+pub mod cache;
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+    fs::{self, read_dir, File},
+    io::{self, BufRead},
+    ops::AddAssign,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use cache::{hash_inputs, DictCache};
+use dashmap::DashMap;
+use rayon::iter::{
+    IndexedParallelIterator, IntoParallelIterator, IntoParallelRefIterator,
+    IntoParallelRefMutIterator, ParallelIterator,
+};
+
+/// This appears to be the best threashold to optimize for valid wordplay while exluding short, "invalid" words.
+pub const MAX_SCOWL_SIZE: usize = 70;
+
+/// Spelling categories of SCOWL lists that qualify for Truncate's English dictionary
+pub const ENGLISH_SCOWL_CATEGORIES: &[&str] = &[
+    "american",
+    "american_variant_1",
+    "american_variant_2",
+    "australian",
+    "australian_variant_1",
+    "australian_variant_2",
+    "british",
+    "british_variant_1",
+    "british_variant_2",
+    "british_z",
+    "british_z_variant_1",
+    "british_z_variant_2",
+    "canadian",
+    "canadian_variant_1",
+    "canadian_variant_2",
+    "english",
+    "variant_1",
+    "variant_2",
+    "variant_3",
+];
+
+pub const ENGLISH_LETTERS: &str = "abcdefghijklmnopqrstuvwxyz";
+
+/// Where the pipeline reads its word sources from, and how strictly it filters them.
+#[derive(Debug, Clone)]
+pub struct DictConfig {
+    pub word_frequencies: PathBuf,
+    pub wordnik_wordlist: PathBuf,
+    pub scowl_wordlists: PathBuf,
+    pub objectionable: PathBuf,
+    /// Files of words to add after filtering, one word per line
+    pub additions: Vec<PathBuf>,
+    /// Files of words to remove after the additions, one word per line
+    pub removals: Vec<PathBuf>,
+    pub max_scowl_size: usize,
+    /// Tag of the language being built, such as `en` or `fr`
+    pub language: String,
+    /// Lowercase letters that words are allowed to contain
+    pub letters: String,
+    /// Spelling categories of the word lists to include
+    pub categories: Vec<String>,
+    /// Where to keep the results of the previous build, to skip unchanged work
+    pub cache_file: Option<PathBuf>,
+    /// Ignores any cached results, rebuilding everything from scratch
+    pub force_rebuild: bool,
+}
+
+impl DictConfig {
+    /// Expects the same layout as this crate's `support_data` directory
+    pub fn from_support_dir(dir: impl AsRef<Path>) -> Self {
+        let dir = dir.as_ref();
+        Self {
+            word_frequencies: dir.join("en_word_freqs.txt"),
+            wordnik_wordlist: dir.join("wordnik_wordlist.txt"),
+            scowl_wordlists: dir.join("generated_scowl_wordlists/"),
+            objectionable: dir.join("objectionable.json"),
+            additions: vec![
+                dir.join("tranche_1_add.txt"),
+                dir.join("tranche_2_add.txt"),
+                dir.join("tranche_3_add.txt"),
+            ],
+            removals: vec![dir.join("tranche_3_del.txt")],
+            max_scowl_size: MAX_SCOWL_SIZE,
+            language: "en".to_string(),
+            letters: ENGLISH_LETTERS.to_string(),
+            categories: ENGLISH_SCOWL_CATEGORIES
+                .iter()
+                .map(|c| c.to_string())
+                .collect(),
+            cache_file: Some(dir.join(".dict_cache").join("en.txt")),
+            force_rebuild: false,
+        }
+    }
+
+    /// The file `main` writes this language's dictionary to,
+    /// keeping the English list at the path the client and server compile in
+    pub fn output_file_name(&self) -> String {
+        if self.language == "en" {
+            "final_wordlist.txt".to_string()
+        } else {
+            format!("final_wordlist_{}.txt", self.language)
+        }
+    }
+}
+
+impl Default for DictConfig {
+    fn default() -> Self {
+        Self::from_support_dir(PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("support_data"))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WordEntry {
+    pub word: String,
+    pub substring_score: usize,
+    pub frequency: f32,
+    pub objectionable: bool,
+}
+
+/// Formats the entry as a line of `final_wordlist.txt`
+impl fmt::Display for WordEntry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}{} {} {:.4}",
+            if self.objectionable { "*" } else { "" },
+            self.word,
+            self.substring_score,
+            self.frequency
+        )
+    }
+}
+
+/// Parses a line of `final_wordlist.txt`
+impl FromStr for WordEntry {
+    type Err = String;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let malformed = || format!("Malformed wordlist line: {line}");
+        let mut chunks = line.split(' ');
+
+        let word = chunks.next().ok_or_else(malformed)?;
+        let (word, objectionable) = match word.strip_prefix('*') {
+            Some(word) => (word, true),
+            None => (word, false),
+        };
+        let substring_score = chunks
+            .next()
+            .and_then(|s| s.parse().ok())
+            .ok_or_else(malformed)?;
+        let frequency = chunks
+            .next()
+            .and_then(|s| s.parse().ok())
+            .ok_or_else(malformed)?;
+
+        Ok(Self {
+            word: word.to_string(),
+            substring_score,
+            frequency,
+            objectionable,
+        })
+    }
+}
+
+pub type SourceSize = usize;
+
+/// Primary determiner for which lists do and do not qualify for inclusion in Truncate's validity dictionary.
+pub fn should_include_file(name: impl AsRef<str>, config: &DictConfig) -> (bool, SourceSize) {
+    // Currently, special files do not contain any extra Truncate words we desire.
+    if name.as_ref().starts_with("special") {
+        return (false, 0);
+    }
+
+    let (category, rest) = name
+        .as_ref()
+        .split_once('-')
+        .expect("SCOWL files are correctly named");
+    let (sub_category, size) = rest
+        .split_once('.')
+        .expect("SCOWL files are correctly named");
+
+    let size: usize = size.parse().expect("Scowl files are correctly named");
+    if size > config.max_scowl_size {
+        return (false, size);
+    }
+
+    // Early exclusion for various classes of word that will never be a valid Truncate word
+    match sub_category {
+        "words" => { /* allowed, continue */ }
+        "abbreviations" => return (false, size),
+        "contractions" => return (false, size),
+        "proper-names" => return (false, size),
+        "upper" => return (false, size),
+        other => panic!("Unknown SCOWL sub-category {other}"),
+    }
+
+    // Main filtering for spelling categories and their variants.
+    (config.categories.iter().any(|c| c == category), size)
+}
+
+/// Primary determiner for which words do and do not qualify for inclusion in Truncate's validity dictionary.
+pub fn should_include_word(word: &str, source_size: usize, letters: &str) -> bool {
+    let length = word.chars().count();
+    // One-letter words in Truncate can be a surprise, exclude them.
+    if length < 2 {
+        return false;
+    }
+    // Only the language's own letters are allowed —
+    // this also helps cut out proper names and words with punctuation
+    if !word.chars().all(|c| letters.contains(c)) {
+        return false;
+    }
+    // Super short words that are more obscure make Truncate less approachable (ex: xu, ai, ki)
+    if length < 3 && source_size > 60 {
+        return false;
+    }
+    true
+}
+
+fn read_lines(path: &Path) -> impl Iterator<Item = String> {
+    let file = File::open(path).unwrap_or_else(|_| panic!("{} should exist", path.display()));
+    io::BufReader::new(file).lines().map_while(Result::ok)
+}
+
+fn load_word_frequencies(path: &Path) -> BTreeMap<String, f32> {
+    println!("Loading word frequencies from file");
+    let frequency_lines = read_lines(path).collect::<Vec<_>>();
+
+    let mut frequency_lookup: BTreeMap<String, f32> = BTreeMap::new();
+
+    // Word frequencies are listed in order,
+    // so we can just use enumerate() for the rankings
+    let mut frequencies = frequency_lines
+        .into_par_iter()
+        .enumerate()
+        .map(|(i, wf)| {
+            let (word, _) = wf
+                .split_once(' ')
+                .expect("Word frequencies are well formed");
+            (word.to_string(), i as f32)
+        })
+        .collect::<Vec<_>>();
+
+    let total_words = frequencies.len() as f32;
+    frequencies.par_iter_mut().for_each(|(_, v)| {
+        *v = (total_words - *v) / total_words;
+    });
+
+    frequency_lookup.extend(frequencies);
+
+    println!("Recalculating word frequency counts");
+
+    frequency_lookup
+}
+
+fn load_word_files(paths: &[PathBuf]) -> BTreeSet<String> {
+    BTreeSet::from_iter(paths.iter().flat_map(|path| read_lines(path)))
+}
+
+fn load_objectionable(path: &Path) -> Vec<String> {
+    let input = fs::read(path).unwrap_or_else(|_| panic!("{} should exist", path.display()));
+    serde_json::from_slice(&input[..]).expect("objectionable.json should be the expected JSON")
+}
+
+fn load_scowl_wordlist(config: &DictConfig) -> BTreeSet<String> {
+    println!("Loading candidate wordlists");
+    let dir = &config.scowl_wordlists;
+    let files =
+        read_dir(dir).unwrap_or_else(|_| panic!("{} directory should exist", dir.display()));
+
+    let mut scowl_word_list: BTreeSet<String> = BTreeSet::new();
+
+    for file in files.flatten() {
+        let (included, source_size) =
+            should_include_file(file.file_name().to_string_lossy(), config);
+
+        if included {
+            println!("Processing {:?} into the word set", file.file_name());
+
+            let spelling_lines = read_lines(&file.path());
+            scowl_word_list.extend(
+                spelling_lines.filter(|w| should_include_word(w, source_size, &config.letters)),
+            );
+        } else {
+            println!(">> Skipping {:?}", file.file_name());
+        }
+    }
+
+    scowl_word_list
+}
+
+pub fn score_extension(target: &str, larger_word: &str) -> Option<usize> {
+    if larger_word <= target {
+        return None;
+    }
+    if larger_word.starts_with(target) || larger_word.ends_with(target) {
+        let diff = larger_word.chars().count() - target.chars().count();
+        if diff >= 5 {
+            return Some(1);
+        } else {
+            return Some((5 - diff).pow(2));
+        }
+    }
+    None
+}
+
+/// Scores how extensible each word is within the given set,
+/// then backpropagates each word's score into the words it extends to.
+pub fn score_words(
+    words: &BTreeSet<String>,
+    frequency_lookup: &BTreeMap<String, f32>,
+    objectionable: &[String],
+) -> Vec<WordEntry> {
+    println!("Calculating word substring counts");
+    let backprop_points: DashMap<&String, usize> = DashMap::new();
+
+    let mut scored_word_list = words
+        .par_iter()
+        .map(|word| {
+            let frequency = frequency_lookup.get(word).cloned().unwrap_or(0.0);
+            let links: Vec<_> = words
+                .iter()
+                .filter_map(|w| score_extension(word, w).map(|score| (w, score)))
+                .collect();
+            let substring_score: usize = links.iter().map(|(_, score)| score).sum();
+
+            for (word, _) in links.into_iter() {
+                backprop_points
+                    .entry(word)
+                    .or_default()
+                    .add_assign(substring_score);
+            }
+
+            (
+                word,
+                WordEntry {
+                    word: word.clone(),
+                    substring_score,
+                    frequency,
+                    objectionable: objectionable.contains(word),
+                },
+            )
+        })
+        .collect::<BTreeMap<_, _>>();
+
+    println!("Backpropagating word substring scores");
+    scored_word_list.iter_mut().for_each(|(word, entry)| {
+        if let Some(pts) = backprop_points.get(word) {
+            entry.substring_score += *pts;
+        }
+    });
+
+    scored_word_list.into_values().collect()
+}
+
+/// Scores words like `score_words`, but reuses the `cached_scores` of a previous word set
+/// for any word whose extensions and sub-words are unaffected by the words that changed.
+pub fn rescore_words(
+    words: &BTreeSet<String>,
+    frequency_lookup: &BTreeMap<String, f32>,
+    objectionable: &[String],
+    cached_scores: &BTreeMap<String, usize>,
+) -> Vec<WordEntry> {
+    let changed: Vec<&String> = words
+        .iter()
+        .filter(|w| !cached_scores.contains_key(*w))
+        .chain(cached_scores.keys().filter(|w| !words.contains(*w)))
+        .collect();
+    println!("{} words changed since the cached build", changed.len());
+
+    // Past this point, checking which words are affected costs more than it saves
+    if changed.len() > words.len() / 10 {
+        return score_words(words, frequency_lookup, objectionable);
+    }
+
+    let linked =
+        |a: &str, b: &str| score_extension(a, b).is_some() || score_extension(b, a).is_some();
+
+    // Words whose own extensions changed, or that extend a word that came or went
+    let touched: BTreeSet<&String> = words
+        .par_iter()
+        .filter(|w| {
+            !cached_scores.contains_key(*w)
+                || changed.iter().any(|c| linked(w.as_str(), c.as_str()))
+        })
+        .collect();
+    // Backpropagation carries changes in those words' scores into the words that extend them
+    let affected: BTreeSet<&String> = words
+        .par_iter()
+        .filter(|w| touched.contains(w) || touched.iter().any(|t| score_extension(t, w).is_some()))
+        .collect();
+    println!("Rescoring {} affected words", affected.len());
+
+    let own_scores: DashMap<String, usize> = DashMap::new();
+    let own_score = |word: &String| -> usize {
+        if let Some(score) = own_scores.get(word) {
+            return *score;
+        }
+        let score = words.iter().filter_map(|w| score_extension(word, w)).sum();
+        own_scores.insert(word.clone(), score);
+        score
+    };
+
+    words
+        .par_iter()
+        .map(|word| {
+            let substring_score = if affected.contains(word) {
+                own_score(word)
+                    + words
+                        .iter()
+                        .filter(|w| score_extension(w, word).is_some())
+                        .map(&own_score)
+                        .sum::<usize>()
+            } else {
+                cached_scores[word]
+            };
+
+            WordEntry {
+                word: word.clone(),
+                substring_score,
+                frequency: frequency_lookup.get(word).cloned().unwrap_or(0.0),
+                objectionable: objectionable.contains(word),
+            }
+        })
+        .collect()
+}
+
+/// Runs the whole pipeline for the configured language,
+/// returning the scored dictionary in alphabetical order.
+pub fn build_dictionary(config: DictConfig) -> Vec<WordEntry> {
+    let input_hash = config.cache_file.as_ref().map(|_| hash_inputs(&config));
+    let cache = match &config.cache_file {
+        Some(path) if !config.force_rebuild => DictCache::load(path),
+        _ => None,
+    };
+    if let Some(cache) = &cache {
+        if Some(cache.input_hash) == input_hash {
+            println!("Inputs are unchanged, reusing the cached dictionary");
+            return cache.entries.clone();
+        }
+    }
+
+    let frequency_lookup = load_word_frequencies(&config.word_frequencies);
+    let scowl_word_list = load_scowl_wordlist(&config);
+
+    // To help filter out less desired words from SCOWL, we require words to _also_ be in the Wordnik games set.
+    println!("Loading wordnik data from file");
+    let wordnik_word_list = BTreeSet::from_iter(read_lines(&config.wordnik_wordlist));
+    let mut final_wordlist: BTreeSet<String> = wordnik_word_list
+        .intersection(&scowl_word_list)
+        .cloned()
+        .collect();
+
+    println!("Loading additional data from files");
+    final_wordlist.extend(load_word_files(&config.additions));
+
+    println!("Loading removal data from files");
+    for removal in load_word_files(&config.removals) {
+        final_wordlist.remove(&removal);
+    }
+
+    println!("{} words in the total set.", final_wordlist.len());
+
+    let objectionable = load_objectionable(&config.objectionable);
+    let entries = match cache {
+        Some(cache) => rescore_words(
+            &final_wordlist,
+            &frequency_lookup,
+            &objectionable,
+            &cache.scores(),
+        ),
+        None => score_words(&final_wordlist, &frequency_lookup, &objectionable),
+    };
+
+    if let (Some(path), Some(input_hash)) = (&config.cache_file, input_hash) {
+        let cache = DictCache {
+            input_hash,
+            entries,
+        };
+        cache.save(path);
+        return cache.entries;
+    }
+
+    entries
+}
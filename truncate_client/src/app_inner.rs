@@ -1,5 +1,5 @@
 use eframe::egui;
-use epaint::vec2;
+use epaint::{emath::Align2, vec2};
 use instant::Duration;
 use truncate_core::{
     messages::{RoomCode, TruncateToken},
@@ -21,7 +21,10 @@ use crate::{
         tutorial::TutorialState,
     },
     utils::{
+        daily::get_puzzle_day,
         includes::{changelogs, ChangePriority, Tutorial},
+        sounds::play_queued_sounds,
+        text::TextHelper,
         urls::back_to_menu,
     },
 };
@@ -37,10 +40,13 @@ pub enum GameStatus {
     SinglePlayer(SinglePlayerState),
     PendingDaily,
     PendingJoin(RoomCode),
+    /// Waiting to watch a room, either from a spectator link or for a rematch to start
+    PendingSpectate(RoomCode),
     PendingCreate,
     PendingStart(Lobby),
     Active(ActiveGame),
-    Concluded(ActiveGame, u64),
+    /// Holds the winner, or `None` if the game was drawn
+    Concluded(ActiveGame, Option<u64>),
     PendingReplay,
     Replay(ReplayerState),
     HardError(Vec<String>),
@@ -54,6 +60,14 @@ pub struct AppInnerStorage {
 pub fn render(outer: &mut OuterApplication, ui: &mut egui::Ui, current_time: Duration) {
     handle_server_msg(outer, ui);
 
+    // Online games are timed by the server, so their timers follow its clock rather than ours
+    let offset = Duration::from_millis(outer.server_clock_offset.unsigned_abs());
+    let server_time = if outer.server_clock_offset >= 0 {
+        current_time + offset
+    } else {
+        current_time.saturating_sub(offset)
+    };
+
     if outer.log_frames {
         let ctx = ui.ctx().clone();
 
@@ -69,7 +83,16 @@ pub fn render(outer: &mut OuterApplication, ui: &mut egui::Ui, current_time: Dur
     // Block all further actions until we have a login token from the server,
     // or until the player accepts to play offline.
     // TODO: Daily puzzle is still inaccessible offline, thus needs a similar check.
-    if let (Some(waiting_for_login), None) = (&outer.started_login_at, &outer.logged_in_as) {
+    // Practice games and debug boards never need the server, so they skip straight past this.
+    let launching_offline = matches!(
+        outer.launched_code.as_deref(),
+        Some("PRACTICE" | "DEBUG_BOARD")
+    );
+    if let (Some(waiting_for_login), None, false) = (
+        &outer.started_login_at,
+        &outer.logged_in_as,
+        launching_offline,
+    ) {
         if (current_time - *waiting_for_login) < Duration::from_secs(5) {
             SplashUI::new(vec!["INITIALIZING".to_string()])
                 .animated(true)
@@ -101,6 +124,37 @@ pub fn render(outer: &mut OuterApplication, ui: &mut egui::Ui, current_time: Dur
         }
     }
 
+    // Players who have been here since before the day rolled over get a nudge towards the new puzzle,
+    // held back until their own local day has caught up with the announcement.
+    let mid_game = matches!(
+        outer.game_status,
+        GameStatus::Active(_) | GameStatus::PendingStart(_)
+    );
+    if let Some(day) = outer.new_daily {
+        if !mid_game && day > outer.launched_at_day && get_puzzle_day(current_time) >= day {
+            let area = egui::Area::new(egui::Id::new("new_daily_layer"))
+                .movable(false)
+                .order(egui::Order::Foreground)
+                .anchor(Align2::CENTER_BOTTOM, vec2(0.0, -10.0));
+
+            area.show(ui.ctx(), |ui| {
+                let prompt = TextHelper::heavy("NEW DAILY PUZZLE AVAILABLE", 10.0, None, ui);
+                if prompt
+                    .button(
+                        outer.theme.button_secondary,
+                        outer.theme.text,
+                        &outer.map_texture,
+                        ui,
+                    )
+                    .clicked()
+                {
+                    outer.new_daily = None;
+                    back_to_menu();
+                }
+            });
+        }
+    }
+
     let mut new_game_status = None;
     let loading_changelog = outer
         .launched_code
@@ -301,13 +355,14 @@ pub fn render(outer: &mut OuterApplication, ui: &mut egui::Ui, current_time: Dur
             }
 
             // Single player can talk to the server, e.g. to ask for word definitions and to persist data
-            for msg in sp.render(
+            let msgs = sp.render(
                 ui,
                 &outer.theme,
                 current_time,
                 &outer.backchannel,
                 &outer.logged_in_as,
-            ) {
+            );
+            for msg in sp.resolve_offline(msgs) {
                 send(msg);
             }
         }
@@ -351,6 +406,26 @@ pub fn render(outer: &mut OuterApplication, ui: &mut egui::Ui, current_time: Dur
                 back_to_menu();
             }
         }
+        GameStatus::PendingSpectate(room_code) => {
+            let splash = SplashUI::new(if let Some(error) = &outer.error {
+                vec![error.clone()]
+            } else {
+                vec![format!("SPECTATING {room_code}")]
+            })
+            .animated(outer.error.is_none())
+            .with_button(
+                "cancel",
+                "CANCEL".to_string(),
+                outer.theme.button_primary,
+                14.0,
+            );
+
+            let resp = splash.render(ui, &outer.theme, current_time, &outer.map_texture);
+
+            if resp.clicked == Some("cancel") {
+                back_to_menu();
+            }
+        }
         GameStatus::PendingCreate => {
             let splash = SplashUI::new(if let Some(error) = &outer.error {
                 vec![error.clone()]
@@ -377,13 +452,14 @@ pub fn render(outer: &mut OuterApplication, ui: &mut egui::Ui, current_time: Dur
             }
         }
         GameStatus::Active(game) => {
-            if let Some(msg) = game.render(ui, current_time, None) {
+            if let Some(msg) = game.render(ui, server_time, None) {
                 send(msg);
             }
+            play_queued_sounds(&mut game.depot.audio, &outer.backchannel);
         }
         GameStatus::Concluded(game, _winner) => {
-            if let Some(PlayerMessage::Rematch) = game.render(ui, current_time, None) {
-                send(PlayerMessage::Rematch);
+            if let Some(msg @ PlayerMessage::Rematch { .. }) = game.render(ui, server_time, None) {
+                send(msg);
             }
         }
         GameStatus::PendingReplay => {
@@ -11,6 +11,8 @@ use crate::app_inner::AppInnerStorage;
 use crate::utils::daily::get_puzzle_day;
 use crate::utils::includes::changelogs;
 use crate::utils::macros::current_time;
+use crate::utils::settings::Settings;
+use crate::utils::sounds::SoundEffect;
 use crate::{app_inner, utils::glyph_utils::Glypher};
 use eframe::egui::{self, Frame, Margin, TextureOptions};
 #[cfg(target_arch = "wasm32")]
@@ -20,7 +22,7 @@ use truncate_core::{
     board::Board,
     messages::{GameMessage, PlayerMessage},
     npc::scoring::NPCParams,
-    player::Player,
+    player::{Hand, Player},
     rules::GameRules,
 };
 
@@ -44,6 +46,23 @@ pub enum BackchannelMsg {
         next_player: usize,
         npc_params: NPCParams,
     },
+    /// Finds the top `count` moves for the next player in a given game state, best first
+    RankMoves {
+        board: Board,
+        rules: GameRules,
+        players: Vec<Player>,
+        next_player: usize,
+        count: usize,
+    },
+    /// Scores the current position for a player the way the NPC would,
+    /// returning the full `BoardScore` so its components can be shown
+    AnalyzePosition {
+        board: Board,
+        rules: GameRules,
+        players: Vec<Player>,
+        for_player: usize,
+        npc_params: NPCParams,
+    },
     /// Tells the outer host to add a given word to the NPC's known dictionaries
     Remember { word: String },
     /// Tells the outer host to forget all words learned via BackchannelMsg::Remember
@@ -55,6 +74,8 @@ pub enum BackchannelMsg {
     Copy { text: String, share: ShareType },
     /// Checks if any answer has been posted for a given message
     QueryFor { id: String },
+    /// Tells the outer host to play a sound effect
+    PlaySound { sound: SoundEffect },
 }
 
 #[derive(Serialize, Deserialize)]
@@ -121,6 +142,12 @@ pub struct OuterApplication {
     pub log_frames: bool,
     pub frames: debug::FrameHistory,
     pub event_dispatcher: EventDispatcher,
+    /// How many milliseconds the server's clock is ahead of ours, as of its last `ClockSync`
+    pub server_clock_offset: i64,
+    /// The latest daily puzzle the server has announced since we launched
+    pub new_daily: Option<u32>,
+    /// A board loaded by the native client's `--board` argument, with the hand to play it with
+    pub debug_board: Option<(Board, Option<Hand>)>,
 }
 
 impl OuterApplication {
@@ -241,11 +268,15 @@ impl OuterApplication {
             }
         }
 
-        let theme = if launched_at_day >= ART_CHANGE_DAY {
+        let settings = Settings::load();
+        let theme = if !settings.daytime {
+            Theme::night()
+        } else if launched_at_day >= ART_CHANGE_DAY {
             Theme::day()
         } else {
             Theme::old_day()
-        };
+        }
+        .with_palette(settings.colorblind);
 
         {
             use egui::FontFamily;
@@ -305,6 +336,9 @@ impl OuterApplication {
                 tx_player,
                 sent: vec![],
             },
+            server_clock_offset: 0,
+            new_daily: None,
+            debug_board: None,
         }
     }
 }
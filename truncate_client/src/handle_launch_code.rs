@@ -68,9 +68,24 @@ pub fn handle_launch_code(
                 ],
                 0,
                 board,
+                None,
                 outer.map_texture.clone(),
             )));
         }
+        "PRACTICE" => {
+            let seed = (current_time!().as_micros() % 243985691) as u32;
+            let rules_generation = GameRules::latest(Some(outer.launched_at_day)).0;
+            let practice_game = SinglePlayerState::practice(
+                ui.ctx(),
+                outer.map_texture.clone(),
+                outer.theme.clone(),
+                BoardSeed::new(seed),
+                rules_generation,
+                NPCPersonality::jet(),
+                outer.event_dispatcher.clone(),
+            );
+            return Some(GameStatus::SinglePlayer(practice_game));
+        }
         "DAILY_PUZZLE" => {
             let day = outer.launched_at_day;
             // let exact_current_day = get_puzzle_day(current_time!());
@@ -153,6 +168,21 @@ pub fn handle_launch_code(
             outer.log_frames = true;
             return Some(GameStatus::SinglePlayer(behemoth_game));
         }
+        "DEBUG_BOARD" => {
+            let (board, hand) = outer.debug_board.take()?;
+            let rules_generation = GameRules::latest(Some(outer.launched_at_day)).0;
+            let debug_game = SinglePlayerState::debug_board(
+                ui.ctx(),
+                outer.map_texture.clone(),
+                outer.theme.clone(),
+                board,
+                hand,
+                rules_generation,
+                NPCPersonality::jet(),
+                outer.event_dispatcher.clone(),
+            );
+            return Some(GameStatus::SinglePlayer(debug_game));
+        }
         _ => {}
     };
 
@@ -226,6 +256,22 @@ pub fn handle_launch_code(
         }
     }
 
+    if launch_code.starts_with("DAILY_PUZZLE:") {
+        let day = launch_code.split(':').nth(1).map(str::parse::<u32>);
+        if let Some(Ok(day)) = day {
+            if let Some(token) = &outer.logged_in_as {
+                send_to_server(PlayerMessage::ReplayDailyPuzzle(token.clone(), day));
+            }
+
+            return Some(GameStatus::PendingDaily);
+        } else {
+            return Some(GameStatus::HardError(vec![
+                "Sorry, that daily puzzle URL".to_string(),
+                "doesn't look right!".to_string(),
+            ]));
+        }
+    }
+
     if launch_code.starts_with("REPLAY:") {
         if let Some(id) = launch_code.split(':').skip(1).next() {
             send_to_server(PlayerMessage::LoadReplay(id.to_string()));
@@ -238,6 +284,18 @@ pub fn handle_launch_code(
         }
     }
 
+    if launch_code.starts_with("SPECTATE:") {
+        if let Some(room_code) = launch_code.split(':').nth(1).filter(|c| !c.is_empty()) {
+            send_to_server(PlayerMessage::Spectate(room_code.to_string()));
+            return Some(GameStatus::PendingSpectate(room_code.to_uppercase()));
+        } else {
+            return Some(GameStatus::HardError(vec![
+                "Sorry, that spectator URL".to_string(),
+                "doesn't look right!".to_string(),
+            ]));
+        }
+    }
+
     // No room code means we start a new game.
     if launch_code.is_empty() {
         send_to_server(PlayerMessage::NewGame {
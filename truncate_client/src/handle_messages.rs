@@ -1,4 +1,5 @@
 use eframe::egui;
+use epaint::TextureHandle;
 use truncate_core::{
     game::{self, GAME_COLOR_BLUE, GAME_COLOR_RED},
     generation,
@@ -15,6 +16,8 @@ use crate::{
     utils::{
         daily::{get_playable_daily_puzzle, get_raw_daily_puzzle},
         game_evals::get_main_dict,
+        macros::current_time,
+        Theme,
     },
 };
 
@@ -33,12 +36,16 @@ pub fn handle_server_msg(outer: &mut OuterApplication, ui: &mut egui::Ui) {
         match msg {
             GameMessage::Ping | GameMessage::Ack(_) | GameMessage::PleaseLogin => { /* handled at comms layer */
             }
-            GameMessage::JoinedLobby(player_index, id, players, board, token) => {
+            GameMessage::ClockSync { server_time } => {
+                outer.server_clock_offset = server_time as i64 - current_time!().as_millis() as i64;
+            }
+            GameMessage::JoinedLobby(player_index, id, players, board, rules, token) => {
                 // If we're already in a lobby, treat this as a lobby update
                 // (the websocket probably dropped and reconnected)
                 if let GameStatus::PendingStart(lobby) = &mut outer.game_status {
                     if lobby.room_code.to_uppercase() == id.to_uppercase() {
                         lobby.players = players;
+                        lobby.rules = Some(rules);
                         lobby.update_board(board, ui);
                         continue;
                     }
@@ -65,14 +72,16 @@ pub fn handle_server_msg(outer: &mut OuterApplication, ui: &mut egui::Ui) {
                     players,
                     player_index,
                     board,
+                    Some(rules),
                     outer.map_texture.clone(),
                 ))
             }
-            GameMessage::LobbyUpdate(_player_index, _id, players, board) => {
+            GameMessage::LobbyUpdate(_player_index, _id, players, board, rules) => {
                 match &mut outer.game_status {
                     GameStatus::PendingStart(editor_state) => {
                         // TODO: Assert that this message is for the correct lobby
-                        editor_state.players = players;
+                        editor_state.update_players(players, ui);
+                        editor_state.rules = Some(rules);
                         editor_state.update_board(board, ui);
                     }
                     _ => panic!("Game update hit an unknown state"),
@@ -89,6 +98,11 @@ pub fn handle_server_msg(outer: &mut OuterApplication, ui: &mut egui::Ui) {
                 game_ends_at,
                 paused,
                 remaining_turns,
+                read_only,
+                turn,
+                battle_delay,
+                challenge_mode,
+                hand_capacity,
             }) => {
                 // If we're already in a game, treat this as a game update
                 // (the websocket probably dropped and reconnected)
@@ -105,13 +119,18 @@ pub fn handle_server_msg(outer: &mut OuterApplication, ui: &mut egui::Ui) {
                             game_ends_at,
                             paused,
                             remaining_turns,
+                            read_only,
+                            turn,
+                            battle_delay,
+                            challenge_mode,
+                            hand_capacity,
                         };
                         game.apply_new_state(update);
                         continue;
                     }
                 }
 
-                outer.game_status = GameStatus::Active(ActiveGame::new(
+                let mut game = ActiveGame::new(
                     ui.ctx(),
                     room_code.to_uppercase(),
                     None,
@@ -121,12 +140,20 @@ pub fn handle_server_msg(outer: &mut OuterApplication, ui: &mut egui::Ui) {
                     next_player_number,
                     board,
                     hand,
+                    hand_capacity,
                     outer.map_texture.clone(),
                     outer.theme.clone(),
-                    GameLocation::Online,
+                    if read_only {
+                        GameLocation::Spectating
+                    } else {
+                        GameLocation::Online
+                    },
                     game_ends_at,
                     remaining_turns,
-                ));
+                );
+                game.depot.timing.battle_delay = battle_delay;
+                game.depot.gameplay.challenge_mode = challenge_mode;
+                outer.game_status = GameStatus::Active(game);
             }
             GameMessage::GameUpdate(state_message) => match &mut outer.game_status {
                 GameStatus::Active(game) => {
@@ -140,6 +167,15 @@ pub fn handle_server_msg(outer: &mut OuterApplication, ui: &mut egui::Ui) {
                     ])
                 }
             },
+            GameMessage::GameCatchUp(catch_up) => match &mut outer.game_status {
+                GameStatus::Active(game)
+                    if game.depot.gameplay.room_code.to_uppercase()
+                        == catch_up.room_code.to_uppercase() =>
+                {
+                    game.apply_catch_up(catch_up);
+                }
+                _ => { /* Only sent to players rejoining a game they're still showing */ }
+            },
             GameMessage::GameTimingUpdate(state_message) => match &mut outer.game_status {
                 GameStatus::Active(game) => {
                     game.apply_new_timing(state_message);
@@ -152,23 +188,85 @@ pub fn handle_server_msg(outer: &mut OuterApplication, ui: &mut egui::Ui) {
                     ])
                 }
             },
-            GameMessage::GameEnd(state_message, winner) => {
+            GameMessage::GameEnd(state_message, winner, end_reason) => {
+                // Spectators never held a seat, so any stored token belongs to the player's own game
                 #[cfg(target_arch = "wasm32")]
-                {
+                if !state_message.read_only {
                     let local_storage =
                         web_sys::window().unwrap().local_storage().unwrap().unwrap();
                     local_storage.remove_item("truncate_active_token").unwrap();
                 }
 
-                match &mut outer.game_status {
+                let finished_game = match &mut outer.game_status {
                     GameStatus::Active(game) => {
                         game.apply_new_state(state_message);
-                        game.depot.gameplay.winner = Some(winner as usize);
-                        outer.game_status = GameStatus::Concluded(game.clone(), winner);
+                        Some(game.clone())
                     }
-                    _ => {}
+                    // Spectators can arrive after the game has already finished
+                    GameStatus::PendingSpectate(_) => Some(finished_game_for_spectator(
+                        &outer.map_texture,
+                        &outer.theme,
+                        ui.ctx(),
+                        state_message,
+                    )),
+                    _ => None,
+                };
+
+                if let Some(mut game) = finished_game {
+                    game.depot.gameplay.winner = Some(winner as usize);
+                    game.depot.gameplay.end_reason = end_reason;
+                    outer.game_status = GameStatus::Concluded(game, Some(winner));
+                }
+            }
+            GameMessage::GameDraw(state_message) => {
+                #[cfg(target_arch = "wasm32")]
+                if !state_message.read_only {
+                    let local_storage =
+                        web_sys::window().unwrap().local_storage().unwrap().unwrap();
+                    local_storage.remove_item("truncate_active_token").unwrap();
+                }
+
+                let finished_game = match &mut outer.game_status {
+                    GameStatus::Active(game) => {
+                        game.apply_new_state(state_message);
+                        Some(game.clone())
+                    }
+                    GameStatus::PendingSpectate(_) => Some(finished_game_for_spectator(
+                        &outer.map_texture,
+                        &outer.theme,
+                        ui.ctx(),
+                        state_message,
+                    )),
+                    _ => None,
+                };
+
+                if let Some(mut game) = finished_game {
+                    game.depot.gameplay.drawn = true;
+                    outer.game_status = GameStatus::Concluded(game, None);
                 }
             }
+            GameMessage::SpectatingRematch(room_code) => {
+                let spectating = match &outer.game_status {
+                    GameStatus::Active(game) | GameStatus::Concluded(game, _) => {
+                        matches!(game.location, GameLocation::Spectating)
+                    }
+                    _ => false,
+                };
+                if !spectating {
+                    continue;
+                }
+
+                #[cfg(target_arch = "wasm32")]
+                {
+                    // Reloading the page should keep watching the latest game
+                    _ = web_sys::window()
+                        .unwrap()
+                        .location()
+                        .set_hash(format!("SPECTATE:{}", room_code.to_uppercase()).as_str());
+                }
+
+                outer.game_status = GameStatus::PendingSpectate(room_code.to_uppercase());
+            }
             GameMessage::GameError(_id, _num, err) => match &mut outer.game_status {
                 GameStatus::Active(game) => {
                     // assert_eq!(game.room_code, id);
@@ -177,6 +275,12 @@ pub fn handle_server_msg(outer: &mut OuterApplication, ui: &mut egui::Ui) {
                 }
                 _ => {}
             },
+            GameMessage::MoveRejected { reason } => match &mut outer.game_status {
+                GameStatus::Active(game) => {
+                    game.depot.gameplay.error_msg = Some(reason.to_string());
+                }
+                _ => {}
+            },
             GameMessage::GenericError(err) => {
                 outer.error = Some(err);
             }
@@ -199,6 +303,13 @@ pub fn handle_server_msg(outer: &mut OuterApplication, ui: &mut egui::Ui) {
                     _ => { /* Soft unreachable */ }
                 }
             }
+            GameMessage::ChatMessage { .. } => { /* TODO: Show chat within online games */ }
+            GameMessage::GameHistory(_) => { /* TODO: Show recent games on the main menu */ }
+            GameMessage::NewDailyAvailable { day } => {
+                outer.new_daily = Some(day);
+            }
+            GameMessage::DailyLeaderboard { .. } => { /* TODO: Show the leaderboard on the daily splash */
+            }
             GameMessage::LoggedInAs {
                 token: player_token,
                 unread_changelogs,
@@ -243,6 +354,13 @@ pub fn handle_server_msg(outer: &mut OuterApplication, ui: &mut egui::Ui) {
                     puzzle_game.best_game = Some(best_game);
                 }
 
+                if latest_puzzle_state.replay {
+                    puzzle_game.replay = true;
+                    if let HeaderType::Summary { title, .. } = &mut puzzle_game.header {
+                        *title = format!("Replaying Day {}", latest_puzzle_state.puzzle_day);
+                    }
+                }
+
                 let unplayed_puzzle = puzzle_game.clone();
 
                 match &mut puzzle_game.header {
@@ -281,12 +399,12 @@ pub fn handle_server_msg(outer: &mut OuterApplication, ui: &mut egui::Ui) {
                 let rules_generation = info
                     .as_ref()
                     .map(|(_, i)| i.rules_generation)
-                    .unwrap_or_else(|| GameRules::latest(Some(outer.launched_at_day)).0);
+                    .unwrap_or_else(|| GameRules::latest(Some(puzzle_state.puzzle_day)).0);
 
                 let mut game = game::Game::new(
                     9,
                     9,
-                    Some(seed.seed as u64),
+                    Some(seed.tile_seed()),
                     GameRules::generation(rules_generation),
                 );
                 if human_starts {
@@ -322,3 +440,28 @@ pub fn handle_server_msg(outer: &mut OuterApplication, ui: &mut egui::Ui) {
         }
     }
 }
+
+fn finished_game_for_spectator(
+    map_texture: &TextureHandle,
+    theme: &Theme,
+    ctx: &egui::Context,
+    state_message: GameStateMessage,
+) -> ActiveGame {
+    ActiveGame::new(
+        ctx,
+        state_message.room_code.to_uppercase(),
+        None,
+        None,
+        state_message.players,
+        state_message.player_number,
+        None,
+        state_message.board,
+        state_message.hand,
+        state_message.hand_capacity,
+        map_texture.clone(),
+        theme.clone(),
+        GameLocation::Spectating,
+        state_message.game_ends_at,
+        state_message.remaining_turns,
+    )
+}
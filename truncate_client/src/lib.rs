@@ -146,10 +146,7 @@ pub fn backchannel(msg: String) -> String {
             next_player,
             npc_params,
         } => {
-            let mut game = truncate_core::game::Game::new(9, 9, None, rules);
-            game.board = board;
-            game.player_turn_count = vec![0; players.len()];
-            game.players = players;
+            let mut game = truncate_core::game::Game::from_board(board, players, rules);
             game.next_player = Some(next_player);
 
             game.players[next_player].turn_starts_no_later_than = Some(
@@ -162,6 +159,36 @@ pub fn backchannel(msg: String) -> String {
 
             return serde_json::to_string(&best).expect("Resultant move should be serializable");
         }
+        BackchannelMsg::RankMoves {
+            board,
+            rules,
+            players,
+            next_player,
+            count,
+        } => {
+            let mut game = truncate_core::game::Game::from_board(board, players, rules);
+            game.next_player = Some(next_player);
+
+            let ranked = utils::game_evals::client_ranked_moves(&game, count);
+
+            return serde_json::to_string(&ranked).expect("Resultant moves should be serializable");
+        }
+        BackchannelMsg::AnalyzePosition {
+            board,
+            rules,
+            players,
+            for_player,
+            npc_params,
+        } => {
+            let mut game = truncate_core::game::Game::new(9, 9, None, rules);
+            game.board = board;
+            game.player_turn_count = vec![0; players.len()];
+            game.players = players;
+
+            let score = utils::game_evals::client_analyze_position(&game, for_player, &npc_params);
+
+            return serde_json::to_string(&score).expect("Board scores should be serializable");
+        }
         BackchannelMsg::Remember { word } => {
             utils::game_evals::remember(&word);
             return String::new();
@@ -176,5 +203,8 @@ pub fn backchannel(msg: String) -> String {
         BackchannelMsg::Copy { .. } => {
             unreachable!("Backchannel should not be passing through Copy")
         }
+        BackchannelMsg::PlaySound { .. } => {
+            unreachable!("Backchannel should not be passing through PlaySound")
+        }
     }
 }
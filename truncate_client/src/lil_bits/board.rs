@@ -3,6 +3,7 @@ use std::path::PathBuf;
 use epaint::{emath::Align2, pos2, vec2, Rect, Vec2};
 use instant::Duration;
 use truncate_core::{
+    bag::BLANK_TILE,
     board::{Board, Coordinate, Direction, Square},
     messages::PlayerMessage,
     player::Hand,
@@ -13,7 +14,7 @@ use eframe::egui::{self, Id, Order, Sense};
 use hashbrown::HashMap;
 
 use crate::utils::{
-    depot::TruncateDepot,
+    depot::{InteractionDepot, TruncateDepot},
     mapper::{MappedBoard, MappedTile, MappedTileVariant, MappedTiles},
 };
 
@@ -176,10 +177,11 @@ impl<'a> BoardUI<'a> {
                                                 if let Some((tile, _)) =
                                                     interactions.selected_tile_in_hand
                                                 {
-                                                    msg = Some(PlayerMessage::Place(
+                                                    msg = place_from_hand(
                                                         coord,
                                                         *hand.get(tile).unwrap(),
-                                                    ));
+                                                        interactions,
+                                                    );
 
                                                     interactions.selected_tile_in_hand = None;
                                                     interactions.selected_square_on_board = None;
@@ -199,10 +201,11 @@ impl<'a> BoardUI<'a> {
 
                                             if let Some(tile) = interactions.released_tile {
                                                 if tile.1 == coord {
-                                                    msg = Some(PlayerMessage::Place(
+                                                    msg = place_from_hand(
                                                         coord,
                                                         *hand.get(tile.0).unwrap(),
-                                                    ));
+                                                        interactions,
+                                                    );
                                                     interactions.selected_tile_in_hand = None;
                                                     interactions.selected_tile_on_board = None;
                                                     interactions.released_tile = None;
@@ -458,6 +461,7 @@ impl<'a> BoardUI<'a> {
             .interactable(false);
         texture_area.show(ui.ctx(), |ui| {
             ui.expand_to_include_rect(ui.available_rect_before_wrap());
+            mapped_board.animate_within(board_texture_dest, game_area);
             mapped_board.remap_texture(
                 ui.ctx(),
                 &depot.aesthetics,
@@ -468,6 +472,8 @@ impl<'a> BoardUI<'a> {
             );
             mapped_board.render_to_rect(board_texture_dest, Some(&depot.ui_state), ui);
         });
+        depot.regions.board_texture_rect = Some(board_texture_dest);
+        depot.regions.board_viewport_rect = Some(game_area);
 
         if !drag_underway {
             depot.interactions.dragging_tile_on_board = None;
@@ -527,7 +533,7 @@ impl<'a> BoardUI<'a> {
                 if zoom_delta != 1.0 {
                     depot.board_info.board_moved = true;
 
-                    depot.board_info.board_zoom *= zoom_delta;
+                    let zoom_delta = depot.board_info.zoom_by(zoom_delta);
                     let diff = board_pos.size() - board_pos.size() * zoom_delta;
                     board_pos.set_right(board_pos.right() - diff.x);
                     board_pos.set_bottom(board_pos.bottom() - diff.y);
@@ -575,7 +581,9 @@ impl<'a> BoardUI<'a> {
             };
 
             if capture_action {
-                depot.board_info.board_zoom *= (touch.zoom_delta - 1.0) * 0.25 + 1.0;
+                depot
+                    .board_info
+                    .zoom_by((touch.zoom_delta - 1.0) * 0.25 + 1.0);
                 depot.board_info.board_pan += touch.translation_delta;
                 depot.board_info.board_moved = true;
                 board_pos = board_pos.translate(touch.translation_delta);
@@ -608,6 +616,26 @@ impl<'a> BoardUI<'a> {
                 (bottom_overage * bounce).max(bounce).min(bottom_overage);
             bounced = true;
         }
+
+        // Once zoomed in far enough that the painted map covers the screen,
+        // don't allow panning past the edge of the map's buffer into nothingness.
+        let painted_pos =
+            board_pos.expand(depot.aesthetics.theme.grid_size * mapped_board.buffer() as f32);
+        if painted_pos.width() > game_area.width() {
+            if painted_pos.left() > game_area.left() {
+                depot.board_info.board_pan.x -= painted_pos.left() - game_area.left();
+            } else if painted_pos.right() < game_area.right() {
+                depot.board_info.board_pan.x += game_area.right() - painted_pos.right();
+            }
+        }
+        if painted_pos.height() > game_area.height() {
+            if painted_pos.top() > game_area.top() {
+                depot.board_info.board_pan.y -= painted_pos.top() - game_area.top();
+            } else if painted_pos.bottom() < game_area.bottom() {
+                depot.board_info.board_pan.y += game_area.bottom() - painted_pos.bottom();
+            }
+        }
+
         if bounced {
             // Paint at a high FPS while animating the board back to stasis.
             ui.ctx().request_repaint();
@@ -620,3 +648,17 @@ impl<'a> BoardUI<'a> {
         msg
     }
 }
+
+/// Places a tile from the hand, or holds a blank back until the player picks its letter
+fn place_from_hand(
+    coord: Coordinate,
+    tile: char,
+    interactions: &mut InteractionDepot,
+) -> Option<PlayerMessage> {
+    if tile == BLANK_TILE {
+        interactions.pending_blank = Some(coord);
+        None
+    } else {
+        Some(PlayerMessage::Place(coord, tile))
+    }
+}
@@ -2,7 +2,9 @@ use epaint::{emath::Align, vec2, Color32, Rect, TextureHandle, Vec2};
 
 use truncate_core::{
     board::{Board, Coordinate, Square},
+    boardfile::BoardFile,
     messages::PlayerMessage,
+    rules::GameRules,
 };
 
 use eframe::egui::{self, Id, Layout, Margin, RichText, Sense};
@@ -35,6 +37,7 @@ pub struct EditorUI<'a> {
     mapped_board: &'a mut MappedBoard,
     editing_mode: &'a mut BoardEditingMode,
     player_colors: &'a Vec<Color32>,
+    rules: Option<&'a GameRules>,
 }
 
 impl<'a> EditorUI<'a> {
@@ -43,12 +46,14 @@ impl<'a> EditorUI<'a> {
         mapped_board: &'a mut MappedBoard,
         editing_mode: &'a mut BoardEditingMode,
         player_colors: &'a Vec<Color32>,
+        rules: Option<&'a GameRules>,
     ) -> Self {
         Self {
             board,
             mapped_board,
             editing_mode,
             player_colors,
+            rules,
         }
     }
 }
@@ -110,6 +115,9 @@ impl<'a> EditorUI<'a> {
                     player_colors: self.player_colors.clone(),
                     destruction_tick: 0.0,
                     destruction_duration: 0.0,
+                    destruction_square_ripple: false,
+                    colorblind: false,
+                    settings_tick: 0,
                 };
                 self.mapped_board.remap_texture(
                     ui.ctx(),
@@ -122,6 +130,55 @@ impl<'a> EditorUI<'a> {
                 msg = Some(PlayerMessage::EditBoard(self.board.clone()));
             }
 
+            let text = TextHelper::heavy("COPY BOARD FILE", 10.0, None, ui);
+            if text
+                .button(Color32::WHITE, theme.text, map_texture, ui)
+                .clicked()
+            {
+                let mut file = BoardFile::new(self.board.clone(), self.player_colors.len());
+                if let Some(rules) = self.rules {
+                    file = file.rules(rules.clone());
+                }
+                ui.output_mut(|o| o.copied_text = file.to_string());
+            }
+
+            let import_id = ui.id().with("board_file_import");
+            let (mut import_text, import_error) = ui.data_mut(|d| {
+                d.get_temp::<(String, Option<String>)>(import_id)
+                    .unwrap_or_default()
+            });
+            ui.add(
+                egui::TextEdit::multiline(&mut import_text)
+                    .hint_text("Paste a .trboard file")
+                    .desired_rows(3),
+            );
+            let text = TextHelper::heavy("LOAD BOARD FILE", 10.0, None, ui);
+            let import_error = if text
+                .button(Color32::WHITE, theme.text, map_texture, ui)
+                .clicked()
+            {
+                match BoardFile::parse(&import_text) {
+                    Ok(file) if file.player_count != self.player_colors.len() => Some(format!(
+                        "This board is for {} players, but this game has {}",
+                        file.player_count,
+                        self.player_colors.len()
+                    )),
+                    Ok(file) => {
+                        *self.board = file.board;
+                        msg = Some(PlayerMessage::EditBoard(self.board.clone()));
+                        import_text.clear();
+                        None
+                    }
+                    Err(e) => Some(e.to_string()),
+                }
+            } else {
+                import_error
+            };
+            if let Some(error) = &import_error {
+                ui.label(RichText::new(error).color(theme.text));
+            }
+            ui.data_mut(|d| d.insert_temp(import_id, (import_text, import_error)));
+
             ui.label(RichText::new("Actions").color(Color32::WHITE));
 
             ui.add_space(28.0);
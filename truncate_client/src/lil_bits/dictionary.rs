@@ -8,7 +8,7 @@ use std::{collections::HashMap, f32};
 use truncate_core::{
     judge::Outcome,
     messages::PlayerMessage,
-    reporting::{BattleReport, BattleWord, WordMeaning},
+    reporting::{BattleReport, BattleWord, WordDefinition, WordMeaning},
 };
 
 use crate::utils::{
@@ -26,7 +26,7 @@ pub struct DictionaryUI {
     current_word: String,
     is_valid: bool,
     focus_in_n_frames: usize,
-    definitions: HashMap<String, Option<Vec<WordMeaning>>>,
+    definitions: HashMap<String, WordDefinition>,
 }
 
 impl DictionaryUI {
@@ -39,9 +39,9 @@ impl DictionaryUI {
         }
     }
 
-    pub fn load_definitions(&mut self, definitions: Vec<(String, Option<Vec<WordMeaning>>)>) {
-        for (word, def) in definitions {
-            self.definitions.insert(word, def);
+    pub fn load_definitions(&mut self, definitions: Vec<WordDefinition>) {
+        for def in definitions {
+            self.definitions.insert(def.word.clone(), def);
         }
     }
 
@@ -180,7 +180,10 @@ impl DictionaryUI {
                     None
                 };
 
-                loading_meaning.or(self.definitions.get(&self.current_word).cloned().flatten())
+                loading_meaning.or(self
+                    .definitions
+                    .get(&self.current_word)
+                    .and_then(|def| def.meanings.clone()))
             } else {
                 Some(vec![WordMeaning {
                     pos: "".to_string(),
@@ -198,12 +201,26 @@ impl DictionaryUI {
                     valid: Some(self.is_valid),
                 }],
                 outcome: Outcome::DefenderWins,
+                reason: None,
             };
 
             let desired_battle_width = ui.available_width().min(550.0);
 
             ui.add_space(20.0);
 
+            if self
+                .definitions
+                .get(&self.current_word)
+                .is_some_and(|def| self.is_valid && def.is_advanced())
+            {
+                TextHelper::heavy("ADVANCED WORD", 12.0, None, ui).paint(
+                    depot.aesthetics.theme.gold_medal,
+                    ui,
+                    true,
+                );
+                ui.add_space(8.0);
+            }
+
             let inset = (ui.available_width() - desired_battle_width) / 2.0;
             let (battle_band, _) = ui.allocate_exact_size(
                 vec2(ui.available_width(), ui.available_height()),
@@ -0,0 +1,88 @@
+use eframe::egui::{self, Id, Order, Sense};
+use epaint::{emath::Align2, pos2, vec2, Color32, Mesh, Rect, Shape, Stroke};
+
+use crate::utils::{depot::TruncateDepot, mapper::MappedBoard};
+
+pub struct MinimapUI<'a> {
+    mapped_board: &'a MappedBoard,
+    width: f32,
+}
+
+impl<'a> MinimapUI<'a> {
+    pub fn new(mapped_board: &'a MappedBoard) -> Self {
+        Self {
+            mapped_board,
+            width: 120.0,
+        }
+    }
+}
+
+impl<'a> MinimapUI<'a> {
+    /// Shows the whole map in the corner of the board's viewport while the map doesn't fit inside it.
+    /// Tapping the minimap recenters the board on that point.
+    pub fn render(self, ui: &mut egui::Ui, depot: &mut TruncateDepot) {
+        let (Some(map_rect), Some(viewport)) = (
+            depot.regions.board_texture_rect,
+            depot.regions.board_viewport_rect,
+        ) else {
+            return;
+        };
+        if viewport.contains_rect(map_rect) || !map_rect.is_positive() {
+            return;
+        }
+        let Some(textures) = self.mapped_board.overview_textures() else {
+            return;
+        };
+
+        let margin = 10.0;
+        let minimap_size = vec2(self.width, self.width * map_rect.aspect_ratio().recip());
+
+        let area = egui::Area::new(Id::new("minimap_layer"))
+            .movable(false)
+            .order(Order::Foreground)
+            .anchor(
+                Align2::RIGHT_TOP,
+                vec2(
+                    viewport.right() - ui.ctx().screen_rect().right() - margin,
+                    viewport.top() + margin,
+                ),
+            );
+
+        area.show(ui.ctx(), |ui| {
+            let (minimap_rect, response) = ui.allocate_exact_size(minimap_size, Sense::click());
+            let uv = Rect::from_min_max(pos2(0.0, 0.0), pos2(1.0, 1.0));
+
+            ui.painter().rect_filled(
+                minimap_rect.expand(2.0),
+                2.0,
+                depot.aesthetics.theme.water.gamma_multiply(0.8),
+            );
+            for texture in textures {
+                let mut mesh = Mesh::with_texture(texture.id());
+                mesh.add_rect_with_uv(minimap_rect, uv, Color32::WHITE);
+                ui.painter().add(Shape::mesh(mesh));
+            }
+
+            // Map the visible region of the board into minimap space
+            let scale = minimap_rect.width() / map_rect.width();
+            let to_minimap = |p: epaint::Pos2| minimap_rect.min + (p - map_rect.min) * scale;
+            let visible = Rect::from_min_max(to_minimap(viewport.min), to_minimap(viewport.max))
+                .intersect(minimap_rect);
+            ui.painter().rect_stroke(
+                visible,
+                0.0,
+                Stroke::new(2.0, depot.aesthetics.theme.ring_selected),
+            );
+
+            if let Some(tapped) = response
+                .interact_pointer_pos()
+                .filter(|_| response.clicked())
+            {
+                let target = map_rect.min + (tapped - minimap_rect.min) / scale;
+                depot.board_info.board_pan += viewport.center() - target;
+                depot.board_info.board_moved = true;
+                ui.ctx().request_repaint();
+            }
+        });
+    }
+}
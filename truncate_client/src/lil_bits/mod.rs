@@ -6,6 +6,7 @@ mod changes_splash;
 mod dictionary;
 mod hand;
 mod hand_square;
+mod minimap;
 pub mod result_modal;
 mod splash;
 mod timer;
@@ -18,6 +19,7 @@ pub use changes_splash::ChangelogSplashUI;
 pub use dictionary::DictionaryUI;
 pub use hand::HandUI;
 pub use hand_square::HandSquareUI;
+pub use minimap::MinimapUI;
 pub use result_modal::ResultModalUI;
 pub use splash::SplashUI;
 pub use timer::TimerUI;
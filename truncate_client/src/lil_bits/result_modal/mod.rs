@@ -33,7 +33,6 @@ pub struct ResultModalDaily {
     pub stats: DailyStats,
     graph: DailySplashGraph,
     daily_actions: DailyActions,
-    streak_length: usize,
     win_rate: f32,
 }
 
@@ -75,20 +74,6 @@ impl ResultModalUI {
         best_game: Option<&Game>,
         day: u32,
     ) -> Self {
-        let streak_length = stats
-            .days
-            .values()
-            .rev()
-            .enumerate()
-            .find_map(|(streak_length, day)| {
-                if day.attempts.iter().any(|a| a.won) {
-                    None
-                } else {
-                    Some(streak_length)
-                }
-            })
-            .unwrap_or_else(|| stats.days.len());
-
         let win_count = stats
             .days
             .values()
@@ -116,7 +101,6 @@ impl ResultModalUI {
                 stats,
                 graph,
                 daily_actions,
-                streak_length,
                 win_rate: win_count as f32 / attempted_day_count as f32,
             }),
         }
@@ -313,7 +297,7 @@ impl ResultModalUI {
 
                 match &mut self.contents {
                     ResultModalVariant::Daily(daily) => {
-                        let streak_string = format!("{} day streak", daily.streak_length);
+                        let streak_string = format!("{} day streak", daily.stats.current_streak);
                         let streak_text = TextHelper::heavy(&streak_string, 14.0, None, &mut ui);
 
                         let padding = streak_text.mesh_size().y / 2.0;
@@ -326,7 +310,11 @@ impl ResultModalUI {
                             ui,
                         );
 
-                        let wr_string = format!("{}% win rate", (daily.win_rate * 100.0) as usize);
+                        let wr_string = format!(
+                            "{}% win rate, best streak {}",
+                            (daily.win_rate * 100.0) as usize,
+                            daily.stats.longest_streak
+                        );
                         let wr_text = TextHelper::heavy(&wr_string, 12.0, None, &mut ui);
 
                         wr_text.paint_within(
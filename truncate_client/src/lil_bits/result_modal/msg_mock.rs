@@ -4,6 +4,7 @@ use epaint::{
     hex_color, vec2, Color32, TextureHandle, Vec2,
 };
 use truncate_core::{
+    emojification::{daily_share_header, DailyShareDetails},
     game::Game,
     messages::{DailyAttempt, DailyStats},
 };
@@ -33,12 +34,19 @@ impl ShareMessageMock {
         best_win: Option<&DailyAttempt>,
         latest_attempt: (u32, &DailyAttempt),
     ) -> Self {
-        let share_prefix =
-            ShareMessageMock::daily_share_message(day, first_win, best_win, latest_attempt);
-        let emoji_board = game
-            .board
-            .emojify(depot.gameplay.player_number as usize, game.winner);
-        let share_text = format!("{share_prefix}\n{emoji_board}");
+        let player = depot.gameplay.player_number as usize;
+        let emoji_board = game.board.emojify(player, game.winner);
+        let share_text = if first_win.is_some() {
+            let share_prefix =
+                ShareMessageMock::daily_share_message(day, first_win, best_win, latest_attempt);
+            format!("{share_prefix}\n{emoji_board}")
+        } else {
+            game.daily_share_text(DailyShareDetails {
+                day,
+                attempt: latest_attempt.0,
+                player,
+            })
+        };
 
         let _this_attempt = stats
             .days
@@ -149,11 +157,7 @@ impl ShareMessageMock {
     ) -> String {
         let plur = |num: u32| if num == 1 { "" } else { "s" };
 
-        let header = if matches!(option_env!("TR_ENV"), Some("outpost")) {
-            format!("-- Truncate Outpost Day #{day} --")
-        } else {
-            format!("Truncate Town Day #{day}")
-        };
+        let header = daily_share_header(day);
 
         let Some(first_win) = first_win else {
             return format!(
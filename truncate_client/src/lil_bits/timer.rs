@@ -1,16 +1,23 @@
 use time::Duration;
-use truncate_core::{messages::GamePlayerMessage, reporting::TimeChange};
+use truncate_core::{
+    messages::{GameEndReason, GamePlayerMessage},
+    reporting::TimeChange,
+};
 
 use eframe::egui::{self, Layout, Response, Sense};
-use epaint::{emath::Align, hex_color, vec2, Color32, Stroke};
+use epaint::{emath::Align, hex_color, pos2, vec2, Color32, Stroke};
 
 use crate::utils::{depot::TruncateDepot, text::TextHelper, Darken, Diaphanize};
 
+/// Running clocks flash and beep once they are down to this many seconds
+const LOW_TIME_WARNING_SECS: i64 = 10;
+
 pub struct TimerUI<'a> {
     player: &'a GamePlayerMessage,
     depot: &'a TruncateDepot,
     time_adjustment: isize,
     time: Duration,
+    ticking: bool,
     friend: bool,
     active: bool,
     right_align: bool,
@@ -32,6 +39,7 @@ impl<'a> TimerUI<'a> {
             player,
             depot,
             time: Duration::default(),
+            ticking: false,
             time_adjustment,
             friend: true,
             active: true,
@@ -56,8 +64,43 @@ impl<'a> TimerUI<'a> {
 }
 
 impl<'a> TimerUI<'a> {
+    /// The player's time as it stands now, and whether their clock is running down.
+    /// Clocks hold still while the game is paused, and stop ticking once it is over.
+    fn live_time(player: &GamePlayerMessage, depot: &TruncateDepot) -> Option<(Duration, bool)> {
+        let time = player.time_remaining?;
+        let game_over = depot.gameplay.winner.is_some() || depot.gameplay.drawn;
+
+        match player.turn_starts_no_later_than {
+            Some(next_turn) if !depot.timing.paused => {
+                let now = depot.timing.current_time.as_secs();
+                match now.checked_sub(next_turn) {
+                    Some(elapsed) => Some((time - Duration::seconds(elapsed as i64), !game_over)),
+                    None => Some((time, false)),
+                }
+            }
+            _ => match player.paused_turn_delta {
+                Some(paused_time_delta) => Some((
+                    time.saturating_add(Duration::seconds(paused_time_delta)),
+                    false,
+                )),
+                None => Some((time, false)),
+            },
+        }
+    }
+
+    /// The seconds left on the player's clock, if it is running and nearly out.
+    /// Clocks that reach zero stop warning, as only the server decides when time has run out.
+    pub fn low_time_warning(player: &GamePlayerMessage, depot: &TruncateDepot) -> Option<i64> {
+        let (time, ticking) = Self::live_time(player, depot)?;
+        let seconds = time.whole_seconds();
+        (ticking && (1..=LOW_TIME_WARNING_SECS).contains(&seconds)).then_some(seconds)
+    }
+
     fn get_time_color(&self) -> Color32 {
-        if self.depot.gameplay.winner == Some(self.player.index) {
+        let flash_on = (self.depot.timing.current_time.as_millis() / 500) % 2 == 0;
+        if flash_on && Self::low_time_warning(self.player, self.depot).is_some() {
+            hex_color!("#ff0000")
+        } else if self.depot.gameplay.winner == Some(self.player.index) {
             self.depot.aesthetics.theme.gold_medal
         } else if !self.active {
             hex_color!("#444444")
@@ -89,38 +132,13 @@ impl<'a> TimerUI<'a> {
     }
 
     fn calculate_time(&mut self) -> String {
-        match self.player.turn_starts_no_later_than {
-            Some(next_turn) => {
-                let now = self.depot.timing.current_time.as_secs();
-                let elapsed = now.checked_sub(next_turn);
-                if let Some(elapsed) = elapsed {
-                    if let Some(time) = self.player.time_remaining {
-                        self.time = time - Duration::seconds(elapsed as i64);
-                        format!("{}", TimerUI::human_time(self.time.whole_seconds(), false))
-                    } else {
-                        format!("")
-                    }
-                } else {
-                    if let Some(time) = self.player.time_remaining {
-                        self.time = time;
-                        format!("{}", TimerUI::human_time(self.time.whole_seconds(), false))
-                    } else {
-                        format!("")
-                    }
-                }
-            }
-            None => {
-                if let Some(mut time) = self.player.time_remaining {
-                    if let Some(paused_time_delta) = self.player.paused_turn_delta {
-                        time = time.saturating_add(Duration::seconds(paused_time_delta));
-                    }
-
-                    self.time = time;
-                    format!("{}", TimerUI::human_time(self.time.whole_seconds(), false))
-                } else {
-                    format!("")
-                }
+        match Self::live_time(self.player, self.depot) {
+            Some((time, ticking)) => {
+                self.time = time;
+                self.ticking = ticking;
+                TimerUI::human_time(self.time.whole_seconds(), false)
             }
+            None => String::new(),
         }
     }
 
@@ -130,9 +148,27 @@ impl<'a> TimerUI<'a> {
                 return "Victorious".into();
             }
             Some(_) => {
-                return "Defeated".into();
+                return match &self.depot.gameplay.end_reason {
+                    GameEndReason::Resigned { player, .. }
+                        if *player as usize == self.player.index =>
+                    {
+                        "Resigned".into()
+                    }
+                    GameEndReason::Forfeited { player }
+                        if *player as usize == self.player.index =>
+                    {
+                        "Forfeited".into()
+                    }
+                    GameEndReason::TimedOut { player } if *player as usize == self.player.index => {
+                        "Out of time".into()
+                    }
+                    _ => "Defeated".into(),
+                };
             }
-            _ => {}
+            None if self.depot.gameplay.drawn => {
+                return "Draw".into();
+            }
+            None => {}
         };
 
         if self.depot.timing.paused {
@@ -145,7 +181,11 @@ impl<'a> TimerUI<'a> {
                 let elapsed = now.checked_sub(next_turn);
                 if elapsed.is_some() {
                     if self.friend {
-                        return format!("Your turn!");
+                        return match self.player.remaining_swaps {
+                            Some(1) => format!("Your turn! 1 swap left"),
+                            Some(swaps) => format!("Your turn! {swaps} swaps left"),
+                            None => format!("Your turn!"),
+                        };
                     } else {
                         return format!("Playing");
                     }
@@ -289,12 +329,26 @@ impl<'a> TimerUI<'a> {
         let time_size = text.size();
 
         // Render the remaining time
-        if self.right_align {
-            text.paint_at(full_bar.left_bottom() + vec2(0.0, 10.0), timer_color, ui);
+        let time_pos = if self.right_align {
+            full_bar.left_bottom() + vec2(0.0, 10.0)
         } else {
-            let mut pos = full_bar.right_bottom() + vec2(0.0, 10.0);
-            pos.x -= time_size.x;
-            text.paint_at(pos, timer_color, ui);
+            full_bar.right_bottom() + vec2(-time_size.x, 10.0)
+        };
+        text.paint_at(time_pos, timer_color, ui);
+
+        // Mark whose clock is running with a dot that blinks each second
+        let blink_on = self.depot.timing.current_time.as_secs() % 2 == 0;
+        if self.ticking && blink_on {
+            let dot_x = if self.right_align {
+                time_pos.x + time_size.x + 8.0
+            } else {
+                time_pos.x - 8.0
+            };
+            ui.painter().circle_filled(
+                pos2(dot_x, time_pos.y + time_size.y / 2.0),
+                3.0,
+                timer_color,
+            );
         }
 
         let byline_string = self.calculate_byline();
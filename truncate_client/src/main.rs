@@ -13,11 +13,48 @@ use futures::channel::{mpsc, oneshot};
 use tokio::runtime::Builder;
 
 use app_outer::OuterApplication;
+use truncate_core::{board::Board, player::Hand};
+
+/// Reads the board layout from `path`, and the hand if one was given as a string of letters.
+/// Exits with a description of the problem if either can't be used.
+fn load_debug_board(path: &str, hand: Option<String>) -> (Board, Option<Hand>) {
+    let layout = std::fs::read_to_string(path).unwrap_or_else(|e| {
+        eprintln!("Couldn't read the board file {path}: {e}");
+        std::process::exit(1);
+    });
+    let board = Board::try_from_string(layout.trim()).unwrap_or_else(|e| {
+        eprintln!("Couldn't load the board in {path}: {e}");
+        std::process::exit(1);
+    });
+
+    let hand = hand.map(|letters| {
+        if letters.is_empty() || !letters.chars().all(|c| c.is_ascii_alphabetic()) {
+            eprintln!("The hand should be a string of letters, such as ABCDEFG");
+            std::process::exit(1);
+        }
+        Hand(letters.to_ascii_uppercase().chars().collect())
+    });
+
+    (board, hand)
+}
 
 fn main() {
-    let connect_addr = std::env::args()
-        .nth(1)
-        .unwrap_or_else(|| "wss://citadel.truncate.town".into());
+    let mut connect_addr = None;
+    let mut board_path = None;
+    let mut hand = None;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            // `--board <file> [--hand <letters>]` plays the given layout locally against the NPC
+            "--board" => board_path = args.next(),
+            "--hand" => hand = args.next(),
+            _ => connect_addr = Some(arg),
+        }
+    }
+    let connect_addr = connect_addr.unwrap_or_else(|| "wss://citadel.truncate.town".into());
+
+    let debug_board = board_path.map(|path| load_debug_board(&path, hand));
 
     let (tx_game, rx_game) = mpsc::channel(2048);
     let (tx_player, rx_player) = mpsc::channel(2048);
@@ -45,7 +82,10 @@ fn main() {
         options,
         Box::new(move |cc| {
             tx_context.send(cc.egui_ctx.clone()).unwrap();
-            Box::new(OuterApplication::new(cc, rx_game, tx_player, None))
+            let launch_code = debug_board.is_some().then(|| "DEBUG_BOARD".to_string());
+            let mut app = OuterApplication::new(cc, rx_game, tx_player, launch_code);
+            app.debug_board = debug_board;
+            Box::new(app)
         }),
     )
     .unwrap();
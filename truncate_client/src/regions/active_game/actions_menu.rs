@@ -1,6 +1,6 @@
 use epaint::{emath::Align2, vec2};
 
-use truncate_core::messages::PlayerMessage;
+use truncate_core::{board::Square, messages::PlayerMessage};
 
 use eframe::{
     egui::{self, Layout, Order, Sense},
@@ -9,7 +9,7 @@ use eframe::{
 
 use crate::{
     lil_bits::DictionaryUI,
-    utils::{text::TextHelper, urls::back_to_menu},
+    utils::{settings::Settings, text::TextHelper, urls::back_to_menu},
 };
 
 use super::{ActiveGame, GameLocation};
@@ -81,7 +81,9 @@ impl ActiveGame {
                         ui.add_space(menu_spacing);
                     }
 
-                    let text = if self.depot.audio.muted {
+                    let mut settings = self.depot.settings();
+
+                    let text = if settings.muted {
                         TextHelper::heavy("UNMUTE SOUNDS", 14.0, None, ui)
                     } else {
                         TextHelper::heavy("MUTE SOUNDS", 14.0, None, ui)
@@ -96,18 +98,70 @@ impl ActiveGame {
                         )
                         .clicked()
                     {
-                        self.depot.audio.muted = !self.depot.audio.muted;
+                        settings.muted = !settings.muted;
+                    }
+
+                    ui.add_space(menu_spacing);
+
+                    let text = if settings.colorblind {
+                        TextHelper::heavy("STANDARD COLORS", 14.0, None, ui)
+                    } else {
+                        TextHelper::heavy("COLORBLIND MODE", 14.0, None, ui)
+                    };
+
+                    if text
+                        .button(
+                            self.depot.aesthetics.theme.button_secondary,
+                            self.depot.aesthetics.theme.text,
+                            &self.depot.aesthetics.map_texture,
+                            ui,
+                        )
+                        .clicked()
+                    {
+                        settings.colorblind = !settings.colorblind;
+                    }
+
+                    ui.add_space(menu_spacing);
+
+                    let text = if settings.daytime {
+                        TextHelper::heavy("NIGHT MODE", 14.0, None, ui)
+                    } else {
+                        TextHelper::heavy("DAY MODE", 14.0, None, ui)
+                    };
+
+                    if text
+                        .button(
+                            self.depot.aesthetics.theme.button_secondary,
+                            self.depot.aesthetics.theme.text,
+                            &self.depot.aesthetics.map_texture,
+                            ui,
+                        )
+                        .clicked()
+                    {
+                        settings.daytime = !settings.daytime;
+                    }
 
-                        #[cfg(target_arch = "wasm32")]
+                    if settings != Settings::default() {
+                        ui.add_space(menu_spacing);
+
+                        let text = TextHelper::heavy("RESET SETTINGS", 14.0, None, ui);
+                        if text
+                            .button(
+                                self.depot.aesthetics.theme.button_secondary,
+                                self.depot.aesthetics.theme.text,
+                                &self.depot.aesthetics.map_texture,
+                                ui,
+                            )
+                            .clicked()
                         {
-                            let local_storage =
-                                web_sys::window().unwrap().local_storage().unwrap().unwrap();
-                            local_storage
-                                .set_item("truncate_muted", &self.depot.audio.muted.to_string())
-                                .unwrap();
+                            settings = Settings::default();
                         }
                     }
 
+                    if settings != self.depot.settings() {
+                        self.apply_settings(settings);
+                    }
+
                     if matches!(self.location, GameLocation::Online) {
                         ui.add_space(menu_spacing);
 
@@ -134,6 +188,93 @@ impl ActiveGame {
                         }
                     }
 
+                    if matches!(
+                        self.location,
+                        GameLocation::Online | GameLocation::Spectating
+                    ) {
+                        ui.add_space(menu_spacing);
+
+                        let text = TextHelper::heavy("COPY SPECTATOR LINK", 14.0, None, ui);
+                        if text
+                            .button(
+                                self.depot.aesthetics.theme.button_secondary,
+                                self.depot.aesthetics.theme.text,
+                                &self.depot.aesthetics.map_texture,
+                                ui,
+                            )
+                            .clicked()
+                        {
+                            #[cfg(target_arch = "wasm32")]
+                            {
+                                let host = web_sys::window()
+                                    .unwrap()
+                                    .location()
+                                    .host()
+                                    .unwrap_or_else(|_| "truncate.town".into());
+                                ui.output_mut(|o| {
+                                    o.copied_text = format!(
+                                        "https://{host}/join/?j=SPECTATE:{}",
+                                        &self.depot.gameplay.room_code
+                                    );
+                                });
+                            }
+                            self.depot.ui_state.actions_menu_open = false;
+                        }
+                    }
+
+                    if matches!(self.location, GameLocation::Local) {
+                        ui.add_space(menu_spacing);
+                        let text = if self.depot.ui_state.analysis_open {
+                            TextHelper::heavy("HIDE ANALYSIS", 14.0, None, ui)
+                        } else {
+                            TextHelper::heavy("SHOW ANALYSIS", 14.0, None, ui)
+                        };
+                        if text
+                            .button(
+                                self.depot.aesthetics.theme.button_secondary,
+                                self.depot.aesthetics.theme.text,
+                                &self.depot.aesthetics.map_texture,
+                                ui,
+                            )
+                            .clicked()
+                        {
+                            self.depot.ui_state.analysis_open = !self.depot.ui_state.analysis_open;
+                            self.depot.ui_state.actions_menu_open = false;
+                        }
+                    }
+
+                    // In challenge mode, an opponent's tile selected on the board can be challenged
+                    let challengeable = self
+                        .depot
+                        .interactions
+                        .selected_tile_on_board
+                        .filter(|(_, square)| {
+                            matches!(square, Square::Occupied { player, .. }
+                                if *player != self.depot.gameplay.player_number as usize)
+                        })
+                        .map(|(coord, _)| coord);
+                    if let (true, false, Some(coord)) = (
+                        self.depot.gameplay.challenge_mode,
+                        self.depot.interactions.view_only,
+                        challengeable,
+                    ) {
+                        ui.add_space(menu_spacing);
+                        let text = TextHelper::heavy("CHALLENGE WORD", 14.0, None, ui);
+                        if text
+                            .button(
+                                self.depot.aesthetics.theme.button_primary,
+                                self.depot.aesthetics.theme.text,
+                                &self.depot.aesthetics.map_texture,
+                                ui,
+                            )
+                            .clicked()
+                        {
+                            msg = Some(PlayerMessage::Challenge(coord));
+                            self.depot.interactions.selected_tile_on_board = None;
+                            self.depot.ui_state.actions_menu_open = false;
+                        }
+                    }
+
                     // TODO: Resigning is largely implented for multiplayer games as well, but we need to:
                     // - Resolve why the update isn't being sent from the server
                     // - Show the confirmation modal inside active_game (we only show it in single player)
@@ -151,7 +292,7 @@ impl ActiveGame {
                             )
                             .clicked()
                         {
-                            msg = Some(PlayerMessage::Resign);
+                            msg = Some(PlayerMessage::Resign(None));
                             self.depot.ui_state.actions_menu_open = false;
                         }
                     }
@@ -0,0 +1,82 @@
+use epaint::{emath::Align2, vec2};
+
+use truncate_core::messages::PlayerMessage;
+
+use eframe::egui::{self, Order};
+
+use crate::utils::text::TextHelper;
+
+use super::ActiveGame;
+
+impl ActiveGame {
+    /// Offers every letter for a blank that has been dropped on the board,
+    /// placing it as the lowercase form of the chosen letter
+    pub fn render_blank_picker(&mut self, ui: &mut egui::Ui) -> Option<PlayerMessage> {
+        let coord = self.depot.interactions.pending_blank?;
+        let mut msg = None;
+
+        let picker_alloc = ui.max_rect();
+        let area = egui::Area::new(egui::Id::new("blank_picker_layer"))
+            .movable(false)
+            .order(Order::Foreground)
+            .anchor(
+                Align2::LEFT_TOP,
+                vec2(picker_alloc.left(), picker_alloc.top()),
+            );
+
+        area.show(ui.ctx(), |ui| {
+            ui.painter().clone().rect_filled(
+                picker_alloc,
+                0.0,
+                self.depot.aesthetics.theme.water.gamma_multiply(0.9),
+            );
+
+            ui.allocate_ui_at_rect(picker_alloc.shrink2(vec2(10.0, 10.0)), |ui| {
+                TextHelper::heavy("PLAY BLANK AS", 14.0, None, ui).paint(
+                    self.depot.aesthetics.theme.text,
+                    ui,
+                    true,
+                );
+                ui.add_space(10.0);
+
+                ui.horizontal_wrapped(|ui| {
+                    for letter in 'A'..='Z' {
+                        let letter_text = letter.to_string();
+                        let text = TextHelper::heavy(&letter_text, 14.0, None, ui);
+                        if text
+                            .button(
+                                self.depot.aesthetics.theme.button_primary,
+                                self.depot.aesthetics.theme.text,
+                                &self.depot.aesthetics.map_texture,
+                                ui,
+                            )
+                            .clicked()
+                        {
+                            msg = Some(PlayerMessage::Place(coord, letter.to_ascii_lowercase()));
+                        }
+                    }
+                });
+                ui.add_space(10.0);
+
+                let text = TextHelper::heavy("CANCEL", 14.0, None, ui);
+                if text
+                    .button(
+                        self.depot.aesthetics.theme.button_secondary,
+                        self.depot.aesthetics.theme.text,
+                        &self.depot.aesthetics.map_texture,
+                        ui,
+                    )
+                    .clicked()
+                {
+                    self.depot.interactions.pending_blank = None;
+                }
+            });
+        });
+
+        if msg.is_some() {
+            self.depot.interactions.pending_blank = None;
+        }
+
+        msg
+    }
+}
@@ -115,7 +115,7 @@ impl ActiveGame {
 
                     ui.add_space(10.0);
 
-                    if self.depot.gameplay.winner.is_some() {
+                    if self.depot.gameplay.winner.is_some() || self.depot.gameplay.drawn {
                         if matches!(self.location, GameLocation::Online) {
                             let text = TextHelper::heavy("REMATCH", 12.0, None, ui);
                             if text
@@ -127,7 +127,22 @@ impl ActiveGame {
                                 )
                                 .clicked()
                             {
-                                msg = Some(PlayerMessage::Rematch);
+                                msg = Some(PlayerMessage::Rematch { mirror: false });
+                            }
+
+                            ui.add_space(10.0);
+
+                            let text = TextHelper::heavy("MIRRORED REMATCH", 12.0, None, ui);
+                            if text
+                                .centered_button(
+                                    self.depot.aesthetics.theme.button_primary,
+                                    self.depot.aesthetics.theme.text,
+                                    &self.depot.aesthetics.map_texture,
+                                    ui,
+                                )
+                                .clicked()
+                            {
+                                msg = Some(PlayerMessage::Rematch { mirror: true });
                             }
 
                             ui.add_space(20.0);
@@ -143,7 +158,7 @@ impl ActiveGame {
                                 )
                                 .clicked()
                             {
-                                msg = Some(PlayerMessage::Resign);
+                                msg = Some(PlayerMessage::Resign(None));
                             }
 
                             ui.add_space(20.0);
@@ -270,6 +285,32 @@ impl ActiveGame {
                             }
                             let mut hand_ui =
                                 ui.child_ui(hand_alloc, Layout::top_down(Align::LEFT));
+
+                            // Spectators have no hand, so they get to flip between each player's view instead
+                            if matches!(self.location, GameLocation::Spectating) {
+                                let next_perspective = (self.depot.gameplay.player_number + 1)
+                                    % self.players.len().max(1) as u64;
+                                let label = match self.players.get(next_perspective as usize) {
+                                    Some(player) => {
+                                        format!("VIEW AS {}", player.name.to_uppercase())
+                                    }
+                                    None => "SWITCH VIEW".to_string(),
+                                };
+                                let text = TextHelper::heavy(&label, 12.0, None, &mut hand_ui);
+                                if text
+                                    .centered_button(
+                                        self.depot.aesthetics.theme.button_secondary,
+                                        self.depot.aesthetics.theme.text,
+                                        &self.depot.aesthetics.map_texture,
+                                        &mut hand_ui,
+                                    )
+                                    .clicked()
+                                {
+                                    self.set_perspective(hand_ui.ctx(), next_perspective);
+                                }
+                                return;
+                            }
+
                             let active_hand = self.depot.gameplay.next_player_number.is_none()
                                 || self
                                     .depot
@@ -10,6 +10,7 @@ use eframe::{
 use crate::{
     lil_bits::TimerUI,
     utils::{
+        sounds::queue_low_time_sound,
         tex::{render_tex_quad, render_tex_quads, tiles},
         text::TextHelper,
     },
@@ -135,6 +136,13 @@ impl ActiveGame {
                                                 .is_some_and(|n| n == player.index as u64),
                                     )
                                     .render(Some(timer_width), false, ui);
+
+                                if TimerUI::low_time_warning(player, &self.depot).is_some() {
+                                    queue_low_time_sound(
+                                        &mut self.depot.audio,
+                                        self.depot.timing.current_time,
+                                    );
+                                }
                             }
 
                             ui.add_space(item_spacing);
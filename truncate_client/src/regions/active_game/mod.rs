@@ -3,7 +3,10 @@ use instant::Duration;
 use truncate_core::{
     board::{Board, Coordinate},
     generation::BoardSeed,
-    messages::{GamePlayerMessage, GameStateMessage, PlayerMessage, RoomCode},
+    messages::{
+        GameCatchUpMessage, GameEndReason, GamePlayerMessage, GameStateMessage, PlayerMessage,
+        RoomCode,
+    },
     npc::scoring::NPCPersonality,
     player::Hand,
     reporting::{BoardChange, BoardChangeAction, BoardChangeDetail, Change, TimeChange},
@@ -16,7 +19,7 @@ use eframe::{
 use hashbrown::HashMap;
 
 use crate::{
-    lil_bits::{BoardUI, DictionaryUI},
+    lil_bits::{BoardUI, DictionaryUI, MinimapUI},
     utils::{
         control_devices,
         depot::{
@@ -24,12 +27,15 @@ use crate::{
             TimingDepot, TruncateDepot, UIStateDepot,
         },
         mapper::{MappedBoard, MappedTiles},
+        settings::Settings,
+        sounds::queue_change_sounds,
         timing::get_qs_tick,
         Theme,
     },
 };
 
 mod actions_menu;
+mod blank_picker;
 mod control_strip;
 mod dictionary;
 mod header_strip;
@@ -51,6 +57,8 @@ pub enum GameLocation {
     Tutorial,
     Local,
     Online,
+    /// Watching an online game without a seat in it
+    Spectating,
 }
 
 #[derive(Clone)]
@@ -81,6 +89,7 @@ impl ActiveGame {
         next_player_number: Option<u64>,
         board: Board,
         hand: Hand,
+        hand_capacity: usize,
         map_texture: TextureHandle,
         theme: Theme,
         location: GameLocation,
@@ -93,7 +102,10 @@ impl ActiveGame {
             .collect::<Vec<_>>();
 
         let mut depot = TruncateDepot {
-            interactions: InteractionDepot::default(),
+            interactions: InteractionDepot {
+                view_only: matches!(location, GameLocation::Spectating),
+                ..InteractionDepot::default()
+            },
             regions: RegionDepot::default(),
             ui_state: UIStateDepot::default(),
             board_info: BoardDepot {
@@ -110,32 +122,29 @@ impl ActiveGame {
                 next_player_number,
                 error_msg: None,
                 winner: None,
+                drawn: false,
+                end_reason: GameEndReason::Board,
                 changes: Vec::new(),
                 last_battle_origin: None,
                 npc,
                 remaining_turns,
+                challenge_mode: false,
             },
             aesthetics: AestheticDepot {
                 theme: theme.clone(),
                 qs_tick: 0,
                 map_texture,
-                player_colors,
+                player_colors: player_colors.clone(),
                 destruction_tick: 0.05,
                 destruction_duration: 0.6,
+                destruction_square_ripple: true,
+                colorblind: false,
+                settings_tick: 0,
             },
             audio: AudioDepot::default(),
         };
 
-        #[cfg(target_arch = "wasm32")]
-        {
-            let local_storage = web_sys::window().unwrap().local_storage().unwrap().unwrap();
-            depot.audio.muted = local_storage
-                .get_item("truncate_muted")
-                .unwrap()
-                .unwrap_or_default()
-                .parse()
-                .unwrap_or_default();
-        }
+        depot.apply_settings(&Settings::load(), player_colors.iter().copied());
 
         Self {
             mapped_board: MappedBoard::new(
@@ -144,9 +153,9 @@ impl ActiveGame {
                 &board,
                 2,
                 player_number as usize,
-                theme.daytime,
+                depot.aesthetics.theme.daytime,
             ),
-            mapped_hand: MappedTiles::new(ctx, 7),
+            mapped_hand: MappedTiles::new(ctx, hand_capacity.max(1)),
             mapped_overlay: MappedTiles::new(ctx, 1),
             depot,
             players,
@@ -241,6 +250,7 @@ impl ActiveGame {
         };
 
         let dict_player_message = self.render_dictionary(ui);
+        let blank_player_message = self.render_blank_picker(&mut game_space_ui);
 
         let player_message = BoardUI::new(&self.board)
             .interactive(!self.depot.interactions.view_only)
@@ -252,15 +262,42 @@ impl ActiveGame {
                 &mut self.mapped_overlay,
                 &mut self.depot,
             )
+            .or(blank_player_message)
             .or(actions_player_message)
             .or(control_player_message)
             .or(timer_player_message)
             .or(dict_player_message)
             .or(sidebar_player_message);
 
+        MinimapUI::new(&self.mapped_board).render(&mut game_space_ui, &mut self.depot);
+
         kb_msg.or(player_message)
     }
 
+    /// Shows the board as the given player sees it, for spectators flipping between players
+    pub fn set_perspective(&mut self, ctx: &egui::Context, player_number: u64) {
+        self.depot.gameplay.player_number = player_number;
+        self.mapped_board = MappedBoard::new(
+            ctx,
+            &self.depot.aesthetics,
+            &self.board,
+            self.mapped_board.buffer(),
+            player_number as usize,
+            self.depot.aesthetics.theme.daytime,
+        );
+    }
+
+    /// Applies and saves new settings, taking effect immediately on the board
+    pub fn apply_settings(&mut self, settings: Settings) {
+        self.depot.apply_settings(
+            &settings,
+            self.players
+                .iter()
+                .map(|p| Color32::from_rgb(p.color.0, p.color.1, p.color.2)),
+        );
+        settings.save();
+    }
+
     pub fn apply_new_timing(&mut self, state_message: GameStateMessage) {
         let GameStateMessage {
             room_code: _,
@@ -273,6 +310,11 @@ impl ActiveGame {
             game_ends_at,
             paused,
             remaining_turns: _,
+            read_only: _,
+            turn: _,
+            battle_delay: _,
+            challenge_mode: _,
+            hand_capacity: _,
         } = state_message;
 
         self.players = players;
@@ -281,6 +323,43 @@ impl ActiveGame {
         self.depot.timing.paused = paused;
     }
 
+    /// Brings the board up to date after rejoining, without replaying the turns that were missed
+    pub fn apply_catch_up(&mut self, catch_up: GameCatchUpMessage) {
+        let GameCatchUpMessage {
+            room_code: _,
+            players,
+            player_number: _,
+            next_player_number,
+            squares,
+            hand,
+            game_ends_at,
+            remaining_turns,
+            paused,
+            turn: _,
+        } = catch_up;
+
+        for (coordinate, square) in squares {
+            _ = self.board.set_square(coordinate, square);
+        }
+        self.players = players;
+        self.hand = hand;
+        self.new_hand_tiles.clear();
+
+        self.depot.gameplay.next_player_number = next_player_number;
+        self.depot.timing.last_turn_change = self.depot.timing.current_time;
+        self.depot.timing.game_ends_at = game_ends_at;
+        self.depot.timing.paused = paused;
+        self.depot.gameplay.remaining_turns = remaining_turns;
+
+        // None of the missed turns are animated
+        self.depot.gameplay.changes = vec![];
+        self.board_changes.clear();
+        self.time_changes.clear();
+
+        self.depot.interactions.playing_tile = None;
+        self.depot.gameplay.error_msg = None;
+    }
+
     pub fn apply_new_state(&mut self, state_message: GameStateMessage) {
         let GameStateMessage {
             room_code: _,
@@ -293,8 +372,16 @@ impl ActiveGame {
             game_ends_at,
             paused,
             remaining_turns,
+            read_only: _,
+            turn: _,
+            battle_delay,
+            challenge_mode,
+            hand_capacity: _,
         } = state_message;
 
+        self.depot.timing.battle_delay = battle_delay;
+        self.depot.gameplay.challenge_mode = challenge_mode;
+
         // assert_eq!(self.room_code, room_code);
         // assert_eq!(self.player_number, player_number);
         self.players = players;
@@ -324,6 +411,11 @@ impl ActiveGame {
         self.depot.gameplay.remaining_turns = remaining_turns;
 
         self.depot.gameplay.changes = changes.clone();
+        queue_change_sounds(
+            &changes,
+            &mut self.depot.audio,
+            self.depot.timing.current_time,
+        );
 
         self.board_changes.clear();
         for board_change in changes.iter().filter_map(|c| match c {
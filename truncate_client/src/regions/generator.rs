@@ -2,10 +2,11 @@ use eframe::egui::{self, DragValue, Layout, RichText, Sense};
 use epaint::{emath::Align, vec2, Color32, TextureHandle, Vec2};
 use instant::Duration;
 use truncate_core::{
+    board::MAX_BOARD_DIMENSION,
     game::Game,
     generation::{
-        self, generate_board, ArtifactType, BoardElements, BoardGenerationResult, BoardNoiseParams,
-        BoardParams, BoardSeed, Symmetry, WaterLayer,
+        self, generate_board, ArtifactType, BoardElements, BoardGenerationError,
+        BoardGenerationResult, BoardNoiseParams, BoardParams, BoardSeed, Symmetry, WaterLayer,
     },
     messages::GamePlayerMessage,
     rules::{BoardGenesis, GameRules},
@@ -20,7 +21,7 @@ pub struct GeneratorState {
     seed: u32,
     infinite: bool,
     params: BoardParams,
-    generation_result: Option<Result<BoardGenerationResult, BoardGenerationResult>>,
+    generation_result: Option<Result<BoardGenerationResult, BoardGenerationError>>,
 }
 
 impl GeneratorState {
@@ -40,6 +41,7 @@ impl GeneratorState {
             Some(0),
             game.board.clone(),
             game.players[0].hand.clone(),
+            game.rules.hand_capacity,
             map_texture.clone(),
             theme.clone(),
             GameLocation::Local,
@@ -96,7 +98,7 @@ impl GeneratorState {
                     ui.label(RichText::new("Width").color(Color32::WHITE));
                     let r = ui.add(
                         DragValue::new(&mut self.params.land_dimensions[0])
-                            .clamp_range(4..=1000)
+                            .clamp_range(4..=MAX_BOARD_DIMENSION - 2)
                             .speed(0.05),
                     );
                     if r.changed() {
@@ -107,7 +109,7 @@ impl GeneratorState {
                     ui.label(RichText::new("Height").color(Color32::WHITE));
                     let r = ui.add(
                         DragValue::new(&mut self.params.land_dimensions[1])
-                            .clamp_range(4..=1000)
+                            .clamp_range(4..=MAX_BOARD_DIMENSION - 2)
                             .speed(0.05),
                     );
                     if r.changed() {
@@ -118,7 +120,7 @@ impl GeneratorState {
                     ui.label(RichText::new("Canvas Width").color(Color32::WHITE));
                     let r = ui.add(
                         DragValue::new(&mut self.params.canvas_dimensions[0])
-                            .clamp_range(4..=1000)
+                            .clamp_range(4..=MAX_BOARD_DIMENSION - 2)
                             .speed(0.05),
                     );
                     if r.changed() {
@@ -129,7 +131,7 @@ impl GeneratorState {
                     ui.label(RichText::new("Canvas Height").color(Color32::WHITE));
                     let r = ui.add(
                         DragValue::new(&mut self.params.canvas_dimensions[1])
-                            .clamp_range(4..=1000)
+                            .clamp_range(4..=MAX_BOARD_DIMENSION - 2)
                             .speed(0.05),
                     );
                     if r.changed() {
@@ -341,6 +343,9 @@ impl GeneratorState {
                 water_level: 0.5,
                 max_attempts,
                 params: self.params.clone(),
+                config: None,
+                enforce_symmetry: false,
+                max_dimension: MAX_BOARD_DIMENSION,
             }));
         }
 
@@ -355,7 +360,11 @@ impl GeneratorState {
         let generation_failed = generation_result.is_err();
         let BoardGenerationResult { board, iterations } = match generation_result {
             Ok(b) => b,
-            Err(b) => b,
+            Err(BoardGenerationError::Unsolvable(b)) => b,
+            Err(BoardGenerationError::TooLarge(e)) => {
+                ui.heading(RichText::new(e.to_string()).color(Color32::RED.lighten().lighten()));
+                return;
+            }
         };
         self.active_game.board = board.clone();
         self.active_game.board.cache_special_squares();
@@ -5,11 +5,16 @@ use epaint::{
 
 use truncate_core::{
     board::Board,
+    game::{color_distance, GAME_COLORS, MIN_COLOR_DISTANCE},
     generation::BoardSeed,
     messages::{LobbyPlayerMessage, PlayerMessage, RoomCode},
+    rules::{
+        ArtifactDefense, FogLevel, GameRules, OvertimeRule, Timing, TownDefense, TownsToDefeat,
+        Visibility, WinCondition, MAX_HAND_CAPACITY,
+    },
 };
 
-use eframe::egui::{self, Layout, Order, RichText, ScrollArea};
+use eframe::egui::{self, Layout, Order, RichText, ScrollArea, Sense};
 
 use crate::{
     lil_bits::EditorUI,
@@ -36,6 +41,8 @@ pub struct Lobby {
     pub room_code: RoomCode,
     pub players: Vec<LobbyPlayerMessage>,
     pub player_index: u64,
+    /// The room's rules, if they can be negotiated in this lobby
+    pub rules: Option<GameRules>,
     pub mapped_board: MappedBoard,
     pub editing_mode: BoardEditingMode,
     pub copied_code: bool,
@@ -50,20 +57,19 @@ impl Lobby {
         players: Vec<LobbyPlayerMessage>,
         player_index: u64,
         board: Board,
+        rules: Option<GameRules>,
         map_texture: TextureHandle,
     ) -> Self {
-        let player_colors: Vec<_> = players
-            .iter()
-            .map(|p| Color32::from_rgb(p.color.0, p.color.1, p.color.2))
-            .collect();
-
         let aesthetics = AestheticDepot {
             theme: Theme::day(),
             qs_tick: 0,
             map_texture,
-            player_colors,
+            player_colors: lobby_colors(&players),
             destruction_tick: 0.0,
             destruction_duration: 0.0,
+            destruction_square_ripple: false,
+            colorblind: false,
+            settings_tick: 0,
         };
 
         Self {
@@ -72,6 +78,7 @@ impl Lobby {
             mapped_board: MappedBoard::new(ctx, &aesthetics, &board, 1, 1, true),
             players,
             player_index,
+            rules,
             board,
             editing_mode: BoardEditingMode::None,
             copied_code: false,
@@ -92,6 +99,74 @@ impl Lobby {
         self.board = board;
     }
 
+    /// Takes in the lobby's players, recoloring the board if anyone's color changed
+    pub fn update_players(&mut self, players: Vec<LobbyPlayerMessage>, ui: &mut egui::Ui) {
+        let player_colors = lobby_colors(&players);
+        self.players = players;
+        if player_colors != self.aesthetics.player_colors {
+            self.aesthetics.player_colors = player_colors;
+            self.mapped_board.remap_texture(
+                &ui.ctx(),
+                &self.aesthetics,
+                &self.timing,
+                None,
+                None,
+                &self.board,
+            );
+        }
+    }
+
+    /// Whether the color can be told apart from every other player's
+    fn color_available(&self, color: (u8, u8, u8)) -> bool {
+        self.players.iter().all(|p| {
+            p.index == self.player_index as usize
+                || color_distance(p.color, color) >= MIN_COLOR_DISTANCE
+        })
+    }
+
+    fn render_color_choice(&mut self, ui: &mut egui::Ui) -> Option<PlayerMessage> {
+        let own_color = self.players.get(self.player_index as usize)?.color;
+        let mut picked = None;
+
+        ui.horizontal(|ui| {
+            for color in GAME_COLORS {
+                let available = self.color_available(color);
+                let (rect, response) = ui.allocate_exact_size(vec2(18.0, 18.0), Sense::click());
+                let fill = Color32::from_rgb(color.0, color.1, color.2);
+                ui.painter().rect_filled(
+                    rect,
+                    2.0,
+                    if available { fill } else { fill.diaphanize() },
+                );
+                if color == own_color {
+                    ui.painter().rect_stroke(
+                        rect.expand(2.0),
+                        2.0,
+                        Stroke::new(1.0, Color32::WHITE),
+                    );
+                }
+                if available && response.clicked() {
+                    picked = Some(color);
+                }
+            }
+
+            let mut custom = [own_color.0, own_color.1, own_color.2];
+            if egui::color_picker::color_edit_button_srgb(ui, &mut custom).changed() {
+                let color = (custom[0], custom[1], custom[2]);
+                if self.color_available(color) {
+                    picked = Some(color);
+                }
+            }
+        });
+
+        let color = picked.filter(|c| *c != own_color)?;
+        // Shown straight away, and corrected by the next lobby update if the server disagrees
+        if let Some(player) = self.players.get_mut(self.player_index as usize) {
+            player.color = color;
+        }
+        Some(PlayerMessage::EditColor(color))
+    }
+
     pub fn render_lobby(&mut self, ui: &mut egui::Ui, theme: &Theme) -> Option<PlayerMessage> {
         let mut msg = None;
 
@@ -210,6 +285,10 @@ impl Lobby {
                         );
                     }
 
+                    if let Some(color_msg) = self.render_color_choice(ui) {
+                        msg = Some(color_msg);
+                    }
+
                     ui.label(RichText::new("Other Players in Lobby:").color(Color32::WHITE));
                     for player in &self.players {
                         if player.index == self.player_index as usize {
@@ -223,6 +302,23 @@ impl Lobby {
                         ));
                     }
 
+                    if let Some(rules) = &mut self.rules {
+                        ui.add_space(12.0);
+                        ui.label(RichText::new("Rules:").color(Color32::WHITE));
+
+                        if self.player_index == 0 {
+                            if let Some(edit) =
+                                render_rule_buttons(rules, &self.aesthetics.map_texture, theme, ui)
+                            {
+                                msg = Some(edit);
+                            }
+                        } else {
+                            for line in rule_summary(rules) {
+                                ui.label(RichText::new(line).color(Color32::WHITE));
+                            }
+                        }
+                    }
+
                     ui.add_space(32.0);
 
                     let text = TextHelper::heavy("EDIT BOARD", 10.0, None, ui);
@@ -261,6 +357,7 @@ impl Lobby {
                 &mut self.mapped_board,
                 &mut self.editing_mode,
                 &self.aesthetics.player_colors,
+                self.rules.as_ref(),
             )
             .render(true, &mut lobby_ui, theme, &self.aesthetics.map_texture)
             {
@@ -279,3 +376,149 @@ impl Lobby {
         msg
     }
 }
+
+const TIMING_PRESETS: [Timing; 3] = [
+    Timing::None,
+    Timing::PerPlayer {
+        time_allowance: 10 * 60,
+        overtime_rule: OvertimeRule::Elimination,
+    },
+    Timing::Fischer {
+        base_time: 5 * 60,
+        increment: 5,
+    },
+];
+
+const FOG_PRESETS: [(Visibility, FogLevel); 4] = [
+    (Visibility::Standard, FogLevel::PieceVision),
+    (Visibility::LandFog, FogLevel::PieceVision),
+    (Visibility::LandFog, FogLevel::BattleArena),
+    (Visibility::TileFog, FogLevel::PieceVision),
+];
+
+/// Picks the preset after the current value, starting over if the value isn't a preset
+fn next_preset<T: Clone + PartialEq>(presets: &[T], current: &T) -> T {
+    let next = presets
+        .iter()
+        .position(|preset| preset == current)
+        .map(|i| (i + 1) % presets.len())
+        .unwrap_or_default();
+    presets[next].clone()
+}
+
+fn win_condition_label(win_condition: &WinCondition) -> String {
+    match win_condition {
+        WinCondition::Destination { .. } => "Win: Reach a town".into(),
+        WinCondition::Elimination => "Win: Eliminate opponents".into(),
+    }
+}
+
+fn timing_label(timing: &Timing) -> String {
+    match timing {
+        Timing::PerPlayer { time_allowance, .. } => {
+            format!("Time: {} min each", time_allowance / 60)
+        }
+        Timing::Fischer {
+            base_time,
+            increment,
+        } => format!("Time: {} min + {}s", base_time / 60, increment),
+        Timing::PerTurn { .. } => "Time: Per turn".into(),
+        Timing::Periodic { .. } => "Time: Periodic".into(),
+        Timing::None => "Time: Unlimited".into(),
+    }
+}
+
+fn fog_label(visibility: &Visibility, fog_level: &FogLevel) -> String {
+    match (visibility, fog_level) {
+        (Visibility::Standard, _) | (_, FogLevel::Full) => "Fog: None".into(),
+        (Visibility::TileFog, _) => "Fog: Hidden tiles".into(),
+        (_, FogLevel::BattleArena) => "Fog: Open arena".into(),
+        (_, FogLevel::BeyondDistance(distance)) => format!("Fog: Beyond {distance} squares"),
+        (_, FogLevel::PieceVision) => "Fog: Around your tiles".into(),
+    }
+}
+
+fn challenge_label(challenge_mode: bool) -> String {
+    if challenge_mode {
+        "Words: Judged when challenged".into()
+    } else {
+        "Words: Judged automatically".into()
+    }
+}
+
+fn rule_summary(rules: &GameRules) -> Vec<String> {
+    vec![
+        win_condition_label(&rules.win_condition),
+        timing_label(&rules.timing),
+        fog_label(&rules.visibility, &rules.fog_level),
+        challenge_label(rules.challenge_mode),
+        format!("Hand: {} tiles", rules.hand_capacity),
+    ]
+}
+
+/// Lets the host cycle each lobby-editable rule,
+/// returning the edited rules to send to the server.
+fn render_rule_buttons(
+    rules: &mut GameRules,
+    map_texture: &TextureHandle,
+    theme: &Theme,
+    ui: &mut egui::Ui,
+) -> Option<PlayerMessage> {
+    let mut changed = false;
+    let rule_button = |label: String, ui: &mut egui::Ui| {
+        TextHelper::heavy(&label, 10.0, None, ui)
+            .button(Color32::WHITE.diaphanize(), theme.text, map_texture, ui)
+            .clicked()
+    };
+
+    if rule_button(win_condition_label(&rules.win_condition), ui) {
+        rules.win_condition = match rules.win_condition {
+            WinCondition::Destination { .. } => WinCondition::Elimination,
+            WinCondition::Elimination => WinCondition::Destination {
+                town_defense: TownDefense::BeatenWithDefenseStrength(0),
+                artifact_defense: ArtifactDefense::Invincible,
+                towns_to_defeat: TownsToDefeat::Any,
+            },
+        };
+        changed = true;
+    }
+
+    if rule_button(timing_label(&rules.timing), ui) {
+        rules.timing = next_preset(&TIMING_PRESETS, &rules.timing);
+        changed = true;
+    }
+
+    if rule_button(fog_label(&rules.visibility, &rules.fog_level), ui) {
+        let current = (rules.visibility.clone(), rules.fog_level.clone());
+        (rules.visibility, rules.fog_level) = next_preset(&FOG_PRESETS, &current);
+        changed = true;
+    }
+
+    if rule_button(challenge_label(rules.challenge_mode), ui) {
+        rules.challenge_mode = !rules.challenge_mode;
+        changed = true;
+    }
+
+    ui.horizontal(|ui| {
+        if rules.hand_capacity > 1 && rule_button("-".into(), ui) {
+            rules.hand_capacity -= 1;
+            changed = true;
+        }
+        ui.label(
+            RichText::new(format!("Hand: {} tiles", rules.hand_capacity)).color(Color32::WHITE),
+        );
+        if rules.hand_capacity < MAX_HAND_CAPACITY && rule_button("+".into(), ui) {
+            rules.hand_capacity += 1;
+            changed = true;
+        }
+    });
+
+    changed.then(|| PlayerMessage::EditRules(rules.clone()))
+}
+
+fn lobby_colors(players: &[LobbyPlayerMessage]) -> Vec<Color32> {
+    players
+        .iter()
+        .map(|p| Color32::from_rgb(p.color.0, p.color.1, p.color.2))
+        .collect()
+}
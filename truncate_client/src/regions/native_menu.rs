@@ -12,7 +12,7 @@ use crate::{
         active_game::HeaderType, generator::GeneratorState, lobby::Lobby,
         single_player::SinglePlayerState, tutorial::TutorialState,
     },
-    utils,
+    utils::{self, macros::current_time},
 };
 
 use truncate_core::messages::PlayerMessage;
@@ -65,9 +65,38 @@ pub fn render_native_menu_if_required(
                     ],
                     0,
                     board,
+                    None,
                     outer.map_texture.clone(),
                 )));
             }
+            if ui.button("Practice").clicked() {
+                let seed = (current_time!().as_micros() % 243985691) as u32;
+                let rules_generation = GameRules::latest(Some(outer.launched_at_day)).0;
+                let practice_game = SinglePlayerState::practice(
+                    ui.ctx(),
+                    outer.map_texture.clone(),
+                    outer.theme.clone(),
+                    BoardSeed::new(seed),
+                    rules_generation,
+                    NPCPersonality::jet(),
+                    outer.event_dispatcher.clone(),
+                );
+                return Some(GameStatus::SinglePlayer(practice_game));
+            }
+            let past_day_id = ui.id().with("past_daily_puzzle");
+            let mut past_day =
+                ui.data_mut(|d| d.get_temp::<String>(past_day_id).unwrap_or_default());
+            ui.text_edit_singleline(&mut past_day);
+            let replay_clicked = ui.button("Replay Daily Puzzle").clicked();
+            ui.data_mut(|d| d.insert_temp(past_day_id, past_day.clone()));
+            if replay_clicked {
+                if let (Ok(day), Some(token)) =
+                    (past_day.trim().parse::<u32>(), &outer.logged_in_as)
+                {
+                    send_to_server(PlayerMessage::ReplayDailyPuzzle(token.clone(), day));
+                    return Some(GameStatus::PendingDaily);
+                }
+            }
             if ui.button("Behemoth").clicked() {
                 let behemoth_board =
                     Board::from_string(include_str!("../../tutorials/test_board.txt"));
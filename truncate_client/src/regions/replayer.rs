@@ -3,6 +3,7 @@ use epaint::{vec2, Color32, TextureHandle};
 use instant::Duration;
 use truncate_core::{
     game::Game,
+    messages::GameEndReason,
     moves::Move,
     reporting::{BoardChange, BoardChangeAction, BoardChangeDetail, Change},
 };
@@ -47,6 +48,10 @@ pub struct ReplayerState {
     theme: Theme,
     move_sequence: Vec<Move>,
     next_move: usize,
+    /// Stepping through the replay only stops after this player's moves,
+    /// while the other moves are still played to keep the board correct
+    focus_player: Option<usize>,
+    paused: bool,
     played_at_tick: Option<u64>,
     playback_speed: PlaybackSpeed,
     aesthetics: AestheticDepot,
@@ -78,6 +83,9 @@ impl ReplayerState {
             player_colors,
             destruction_tick: 0.05,
             destruction_duration: 0.6,
+            destruction_square_ripple: true,
+            colorblind: false,
+            settings_tick: 0,
         };
         let mapped_board = MappedBoard::new(ctx, &aesthetics, &game.board, 2, as_player, true);
 
@@ -87,10 +95,13 @@ impl ReplayerState {
             next_player_number: game.next_player.map(|p| p as u64),
             error_msg: None,
             winner: None,
+            drawn: false,
+            end_reason: GameEndReason::Board,
             changes: vec![],
             last_battle_origin: None,
             npc: None,
             remaining_turns: None,
+            challenge_mode: false,
         };
 
         game.start();
@@ -104,6 +115,8 @@ impl ReplayerState {
             theme,
             move_sequence,
             next_move: 0,
+            focus_player: None,
+            paused: false,
             played_at_tick: None,
             playback_speed: PlaybackSpeed::Regular,
             aesthetics,
@@ -113,17 +126,65 @@ impl ReplayerState {
     }
 
     pub fn play_next_turn(&mut self, current_time: Duration, qs_tick: u64) {
-        let Some(next_move) = self.move_sequence.get(self.next_move) else {
+        if self.next_move >= self.move_sequence.len() {
             return;
+        }
+
+        self.step_to(self.next_stop(), current_time, qs_tick);
+    }
+
+    /// Whether stepping through the replay stops after the first `n` moves.
+    /// The start and end of the game are always stopped at.
+    fn is_stop(&self, n: usize) -> bool {
+        match self.focus_player {
+            None => true,
+            Some(player) => {
+                n == 0
+                    || n == self.move_sequence.len()
+                    || self.move_sequence[n - 1].player() == player
+            }
+        }
+    }
+
+    fn next_stop(&self) -> usize {
+        let total_moves = self.move_sequence.len();
+        (self.next_move + 1..=total_moves)
+            .find(|n| self.is_stop(*n))
+            .unwrap_or(total_moves)
+    }
+
+    fn previous_stop(&self) -> usize {
+        (0..self.next_move)
+            .rev()
+            .find(|n| self.is_stop(*n))
+            .unwrap_or(0)
+    }
+
+    /// Shows the game as it was after the first `n` moves.
+    /// Moving forward continues from the current state,
+    /// while moving backward replays the game from the start.
+    pub fn step_to(&mut self, n: usize, current_time: Duration, qs_tick: u64) {
+        let n = n.min(self.move_sequence.len());
+        if n == self.next_move {
+            return;
+        }
+
+        let (from, already_played) = if n > self.next_move {
+            (&self.game, self.next_move)
+        } else {
+            (&self.base_game, 0)
         };
 
         let dict_lock = get_main_dict();
         let dict = dict_lock.as_ref().unwrap();
-        _ = self
-            .game
-            .play_turn(next_move.clone(), Some(dict), Some(dict), None);
+        self.game = from.replay_to(
+            &self.move_sequence[already_played..],
+            n - already_played,
+            Some(dict),
+            Some(dict),
+        );
 
-        self.next_move += 1;
+        self.next_move = n;
 
         self.timing.last_turn_change = current_time;
 
@@ -136,18 +197,6 @@ impl ReplayerState {
             .iter()
             .any(|change| matches!(change, Change::Battle(_)));
 
-        self.gameplay.last_battle_origin =
-            self.game
-                .recent_changes
-                .iter()
-                .find_map(|change| match change {
-                    Change::Board(BoardChange {
-                        detail: BoardChangeDetail { coordinate, .. },
-                        action: BoardChangeAction::Added,
-                    }) => Some(*coordinate),
-                    _ => None,
-                });
-
         if battle_occurred {
             self.gameplay.last_battle_origin =
                 self.game
@@ -172,6 +221,79 @@ impl ReplayerState {
         }
     }
 
+    fn render_scrubber(
+        &mut self,
+        ui: &mut egui::Ui,
+        theme: &Theme,
+        current_time: Duration,
+        qs_tick: u64,
+    ) {
+        let mut target_move = self.next_move;
+
+        ui.horizontal(|ui| {
+            let text = TextHelper::heavy("BACK", 12.0, None, ui);
+            if text
+                .button(theme.button_secondary, theme.text, &self.map_texture, ui)
+                .clicked()
+            {
+                self.paused = true;
+                target_move = self.previous_stop();
+            }
+
+            let text = if self.paused {
+                TextHelper::heavy("PLAY", 12.0, None, ui)
+            } else {
+                TextHelper::heavy("PAUSE", 12.0, None, ui)
+            };
+            if text
+                .button(theme.button_primary, theme.text, &self.map_texture, ui)
+                .clicked()
+            {
+                self.paused = !self.paused;
+            }
+
+            let text = TextHelper::heavy("FORWARD", 12.0, None, ui);
+            if text
+                .button(theme.button_secondary, theme.text, &self.map_texture, ui)
+                .clicked()
+            {
+                self.paused = true;
+                target_move = self.next_stop();
+            }
+        });
+
+        ui.add_space(10.0);
+
+        let focus_label = match self.focus_player {
+            Some(player) => format!("{}'S TURNS", self.game.players[player].name.to_uppercase()),
+            None => "ALL TURNS".to_string(),
+        };
+        let text = TextHelper::heavy(&focus_label, 12.0, None, ui);
+        if text
+            .button(theme.button_secondary, theme.text, &self.map_texture, ui)
+            .clicked()
+        {
+            self.focus_player = match self.focus_player {
+                None => Some(0),
+                Some(player) if player + 1 < self.game.players.len() => Some(player + 1),
+                Some(_) => None,
+            };
+        }
+
+        ui.add_space(10.0);
+
+        let total_moves = self.move_sequence.len();
+        let slider = egui::Slider::new(&mut target_move, 0..=total_moves)
+            .text(format!("of {total_moves} moves"));
+        if ui.add(slider).changed() {
+            self.paused = true;
+        }
+
+        if target_move != self.next_move {
+            self.step_to(target_move, current_time, qs_tick);
+        }
+    }
+
     pub fn render(
         &mut self,
         ui: &mut egui::Ui,
@@ -187,7 +309,7 @@ impl ReplayerState {
 
         self.timing.current_time = current_time.clone();
 
-        if elapsed >= self.playback_speed.ticks() {
+        if !self.paused && elapsed >= self.playback_speed.ticks() {
             self.play_next_turn(current_time, now);
         }
 
@@ -208,6 +330,7 @@ impl ReplayerState {
             .centered_button(theme.button_primary, theme.text, &self.map_texture, ui)
             .clicked()
         {
+            let focus_player = self.focus_player;
             *self = Self::new(
                 ui.ctx(),
                 self.map_texture.clone(),
@@ -216,8 +339,13 @@ impl ReplayerState {
                 self.move_sequence.clone(),
                 self.as_player,
             );
+            self.focus_player = focus_player;
         }
 
+        ui.add_space(20.0);
+
+        self.render_scrubber(ui, theme, current_time, now);
+
         self.mapped_board.remap_texture(
             ui.ctx(),
             &self.aesthetics,
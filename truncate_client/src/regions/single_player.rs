@@ -1,14 +1,18 @@
-use eframe::egui::{self, Layout, Sense};
-use epaint::{emath::Align, hex_color, vec2, TextureHandle};
+use eframe::egui::{self, Layout, Order, RichText, Sense};
+use epaint::{
+    emath::{Align, Align2},
+    hex_color, vec2, Color32, TextureHandle,
+};
 use instant::Duration;
 use truncate_core::{
     board::Board,
     game::{Game, GAME_COLOR_BLUE, GAME_COLOR_RED},
-    generation::BoardSeed,
-    messages::{DailyStats, GamePlayerMessage, GameStateMessage, PlayerMessage},
+    generation::{generate_board, BoardSeed},
+    messages::{DailyStats, GameEndReason, GamePlayerMessage, GameStateMessage, PlayerMessage},
     moves::Move,
-    npc::scoring::NPCPersonality,
-    reporting::WordMeaning,
+    npc::scoring::{BoardScore, NPCPersonality},
+    player::Hand,
+    reporting::WordDefinition,
     rules::GameRules,
 };
 
@@ -19,7 +23,12 @@ use crate::{
         ResultModalUI,
     },
     utils::{
-        game_evals::{client_best_move, forget, get_main_dict, remember},
+        daily::get_puzzle_day,
+        game_evals::{
+            client_analyze_position, client_best_move, client_book_move, forget, get_main_dict,
+            remember,
+        },
+        sounds::play_queued_sounds,
         text::TextHelper,
         Theme,
     },
@@ -42,6 +51,9 @@ pub struct SinglePlayerState {
     debugging_npc: bool,
     npc: NPCPersonality,
     waiting_on_backchannel: Option<String>,
+    /// The NPC's evaluation of the human player's position, with the turn it was made on
+    analysis: Option<(u32, BoardScore)>,
+    waiting_on_analysis: Option<(u32, String)>,
     pub header: HeaderType,
     pub daily_stats: Option<DailyStats>,
     pub best_game: Option<Game>,
@@ -49,6 +61,10 @@ pub struct SinglePlayerState {
     hide_splash: bool,
     pub move_sequence: Vec<Move>,
     event_dispatcher: EventDispatcher,
+    /// Practice games never talk to the server, so they keep working offline
+    offline: bool,
+    /// Daily puzzles replayed from a past day are tracked apart from that day's attempts
+    pub replay: bool,
 }
 
 impl SinglePlayerState {
@@ -70,7 +86,7 @@ impl SinglePlayerState {
         let mut game = Game::new(
             9,
             9,
-            seed.clone().map(|s| s.seed as u64),
+            seed.as_ref().map(BoardSeed::tile_seed),
             GameRules::generation(rules_generation),
         );
         if human_starts {
@@ -90,6 +106,7 @@ impl SinglePlayerState {
         board.cache_special_squares();
         game.board = board.clone();
 
+        game.track_word_history();
         game.start();
 
         let (filtered_board, _) = game.filter_game_to_player(if human_starts { 0 } else { 1 });
@@ -107,6 +124,7 @@ impl SinglePlayerState {
             Some(0),
             filtered_board.clone(),
             game.players[if human_starts { 0 } else { 1 }].hand.clone(),
+            game.rules.hand_capacity,
             map_texture.clone(),
             theme.clone(),
             GameLocation::Local,
@@ -129,6 +147,8 @@ impl SinglePlayerState {
             debugging_npc: false,
             npc,
             waiting_on_backchannel: None,
+            analysis: None,
+            waiting_on_analysis: None,
             header,
             daily_stats: None,
             best_game: None,
@@ -136,7 +156,82 @@ impl SinglePlayerState {
             hide_splash: false,
             move_sequence: vec![],
             event_dispatcher,
+            offline: false,
+            replay: false,
+        }
+    }
+
+    /// A game against the NPC that is generated and played entirely on this device
+    pub fn practice(
+        ctx: &egui::Context,
+        map_texture: TextureHandle,
+        theme: Theme,
+        seed: BoardSeed,
+        rules_generation: u32,
+        npc: NPCPersonality,
+        event_dispatcher: EventDispatcher,
+    ) -> Self {
+        let board = generate_board(seed.clone())
+            .expect("Common seeds can be reasonably expected to produce a board")
+            .board;
+        let header = HeaderType::Summary {
+            title: "Practice".to_string(),
+            attempt: None,
+        };
+
+        let mut state = Self::new(
+            "practice".to_string(),
+            ctx,
+            map_texture,
+            theme,
+            board,
+            Some(seed),
+            rules_generation,
+            true,
+            header,
+            npc,
+            event_dispatcher,
+        );
+        state.offline = true;
+        state
+    }
+
+    /// A game against the NPC on a hand-written board, for reproducing reported positions.
+    /// Replaces the human player's dealt tiles with `hand`, if given.
+    pub fn debug_board(
+        ctx: &egui::Context,
+        map_texture: TextureHandle,
+        theme: Theme,
+        board: Board,
+        hand: Option<Hand>,
+        rules_generation: u32,
+        npc: NPCPersonality,
+        event_dispatcher: EventDispatcher,
+    ) -> Self {
+        let header = HeaderType::Summary {
+            title: "Debug Board".to_string(),
+            attempt: None,
+        };
+
+        let mut state = Self::new(
+            "debug_board".to_string(),
+            ctx,
+            map_texture,
+            theme,
+            board,
+            None,
+            rules_generation,
+            true,
+            header,
+            npc,
+            event_dispatcher,
+        );
+        if let Some(hand) = hand {
+            state.game.players[0].hand = hand.clone();
+            state.active_game.hand = hand;
         }
+        state.offline = true;
+        state
     }
 
     fn sub_event(&mut self, event: String) {
@@ -181,7 +276,7 @@ impl SinglePlayerState {
         let mut game = Game::new(
             9,
             9,
-            Some(seed.seed as u64),
+            Some(seed.tile_seed()),
             GameRules::generation(self.rules_generation),
         );
         self.human_starts = human_starts;
@@ -205,6 +300,7 @@ impl SinglePlayerState {
         rand_board.cache_special_squares();
 
         game.board = rand_board;
+        game.track_word_history();
         game.start();
 
         let mut active_game = ActiveGame::new(
@@ -222,6 +318,7 @@ impl SinglePlayerState {
             game.players[if self.human_starts { 0 } else { 1 }]
                 .hand
                 .clone(),
+            game.rules.hand_capacity,
             self.map_texture.clone(),
             self.theme.clone(),
             GameLocation::Local,
@@ -229,6 +326,7 @@ impl SinglePlayerState {
             None,
         );
         active_game.depot.ui_state.game_header = self.header.clone();
+        active_game.depot.ui_state.analysis_open = self.active_game.depot.ui_state.analysis_open;
 
         self.sub_event("replay".to_string());
 
@@ -238,6 +336,8 @@ impl SinglePlayerState {
         self.next_response_at = None;
         self.winner = None;
         self.move_sequence = vec![];
+        self.analysis = None;
+        self.waiting_on_analysis = None;
         self.event_dispatcher = self.event_dispatcher.clone();
 
         if backchannel.is_open() {
@@ -249,7 +349,7 @@ impl SinglePlayerState {
 
     /// If the server sent through some new word definitions,
     /// dig deep and update all past battles to reference the definitions
-    pub fn hydrate_meanings(&mut self, definitions: Vec<(String, Option<Vec<WordMeaning>>)>) {
+    pub fn hydrate_meanings(&mut self, definitions: Vec<WordDefinition>) {
         self.active_game
             .turn_reports
             .iter_mut()
@@ -264,15 +364,49 @@ impl SinglePlayerState {
             .flat_map(|b| b.attackers.iter_mut().chain(b.defenders.iter_mut()))
             .for_each(|battle_word| {
                 if battle_word.meanings.is_none() {
-                    for (word, meanings) in &definitions {
-                        if battle_word.resolved_word.to_lowercase() == word.to_lowercase() {
-                            battle_word.meanings = meanings.clone();
+                    for def in &definitions {
+                        if battle_word.resolved_word.to_lowercase() == def.word.to_lowercase() {
+                            battle_word.meanings = def.meanings.clone();
                         }
                     }
                 }
             });
     }
 
+    /// Handles any messages an offline game would have sent to the server,
+    /// passing through the messages for online games untouched.
+    pub fn resolve_offline(&mut self, msgs: Vec<PlayerMessage>) -> Vec<PlayerMessage> {
+        if !self.offline {
+            return msgs;
+        }
+
+        for msg in msgs {
+            let PlayerMessage::RequestDefinitions(words) = msg else {
+                continue;
+            };
+
+            // Definitions live on the server, but we can still report what the local dictionary knows
+            let dict_lock = get_main_dict();
+            let dict = dict_lock.as_ref().unwrap();
+            let definitions: Vec<_> = words
+                .into_iter()
+                .map(|word| WordDefinition {
+                    data: dict.get(&word.to_lowercase()).cloned(),
+                    meanings: None,
+                    word,
+                })
+                .collect();
+            drop(dict_lock);
+
+            self.hydrate_meanings(definitions.clone());
+            if let Some(dict_ui) = &mut self.active_game.dictionary_ui {
+                dict_ui.load_definitions(definitions);
+            }
+        }
+
+        vec![]
+    }
+
     pub fn handle_move(
         &mut self,
         next_move: Move,
@@ -290,6 +424,7 @@ impl SinglePlayerState {
         match self.game.play_turn(next_move, Some(dict), Some(dict), None) {
             Ok(winner) => {
                 self.winner = winner;
+                self.active_game.depot.gameplay.drawn = self.game.drawn;
 
                 if track_events {
                     if let Some(winner) = winner {
@@ -298,6 +433,8 @@ impl SinglePlayerState {
                         } else {
                             self.sub_event("lost".to_string())
                         }
+                    } else if self.game.drawn {
+                        self.sub_event("drew".to_string())
                     }
                 }
 
@@ -370,6 +507,11 @@ impl SinglePlayerState {
                     game_ends_at: None,
                     paused: false,
                     remaining_turns: None,
+                    read_only: false,
+                    turn: self.game.turn_count,
+                    battle_delay: Some(self.game.rules.battle_delay),
+                    challenge_mode: self.game.rules.challenge_mode,
+                    hand_capacity: self.game.rules.hand_capacity,
                 };
                 self.active_game.apply_new_state(state_message);
 
@@ -382,6 +524,78 @@ impl SinglePlayerState {
         }
     }
 
+    /// Keeps the NPC's evaluation of the human player's position up to date,
+    /// only scoring what the human player can see.
+    fn update_analysis(&mut self, human_player: usize, backchannel: &Backchannel) {
+        if let Some((turn, pending_msg)) = &self.waiting_on_analysis {
+            let msg_response = backchannel.send_msg(crate::app_outer::BackchannelMsg::QueryFor {
+                id: pending_msg.clone(),
+            });
+            if let Some(msg_response) = msg_response {
+                let score: BoardScore = serde_json::from_str(&msg_response)
+                    .expect("Backchannel should be sending valid JSON");
+                self.analysis = Some((*turn, score));
+                self.waiting_on_analysis = None;
+            }
+            return;
+        }
+
+        if matches!(&self.analysis, Some((turn, _)) if *turn == self.game.turn_count) {
+            return;
+        }
+
+        let (filtered_board, _) = self.game.filter_game_to_player(human_player);
+        if backchannel.is_open() {
+            let pending_msg =
+                backchannel.send_msg(crate::app_outer::BackchannelMsg::AnalyzePosition {
+                    board: filtered_board,
+                    rules: self.game.rules.clone(),
+                    players: self.game.players.clone(),
+                    for_player: human_player,
+                    npc_params: self.npc.params,
+                });
+            self.waiting_on_analysis = pending_msg.map(|msg| (self.game.turn_count, msg));
+        } else {
+            let mut visible_game = self.game.clone();
+            visible_game.board = filtered_board;
+            let score = client_analyze_position(&visible_game, human_player, &self.npc.params);
+            self.analysis = Some((self.game.turn_count, score));
+        }
+    }
+
+    fn render_analysis(&self, ui: &mut egui::Ui) {
+        let Some((_, score)) = &self.analysis else {
+            return;
+        };
+
+        egui::Area::new(egui::Id::new("analysis_layer"))
+            .movable(false)
+            .order(Order::Foreground)
+            .anchor(Align2::LEFT_BOTTOM, vec2(10.0, -10.0))
+            .show(ui.ctx(), |ui| {
+                egui::Frame::none()
+                    .fill(hex_color!("#111111aa"))
+                    .inner_margin(8.0)
+                    .show(ui, |ui| {
+                        let verdict = if score.is_win() {
+                            "Winning position".to_string()
+                        } else if score.is_loss() {
+                            "Losing position".to_string()
+                        } else {
+                            format!("Position: {:+.2}", score.rank())
+                        };
+                        ui.label(RichText::new(verdict).color(Color32::WHITE).strong());
+
+                        for (component, value) in score.breakdown() {
+                            ui.label(
+                                RichText::new(format!("{component}: {value:+.2}"))
+                                    .color(Color32::WHITE),
+                            );
+                        }
+                    });
+            });
+    }
+
     pub fn render(
         &mut self,
         ui: &mut egui::Ui,
@@ -433,11 +647,17 @@ impl SinglePlayerState {
             .active_game
             .render(&mut ui, current_time, Some(&self.game))
             .map(|msg| (human_player, msg));
+        play_queued_sounds(&mut self.active_game.depot.audio, backchannel);
 
-        if matches!(next_msg, Some((_, PlayerMessage::Rematch))) {
+        if self.active_game.depot.ui_state.analysis_open {
+            self.update_analysis(human_player, backchannel);
+            self.render_analysis(&mut ui);
+        }
+
+        if matches!(next_msg, Some((_, PlayerMessage::Rematch { .. }))) {
             self.reset(current_time, ui.ctx(), backchannel);
             return msgs_to_server;
-        } else if matches!(next_msg, Some((_, PlayerMessage::Resign))) {
+        } else if matches!(next_msg, Some((_, PlayerMessage::Resign(_)))) {
             if self.hide_splash {
                 self.hide_splash = false;
             } else {
@@ -448,7 +668,7 @@ impl SinglePlayerState {
                             "Start again?".to_string(),
                         ))
                     }
-                    GameLocation::Online => {
+                    GameLocation::Online | GameLocation::Spectating => {
                         self.splash = Some(ResultModalUI::new_resigning(
                             &mut ui,
                             "Resign this game?".to_string(),
@@ -495,6 +715,30 @@ impl SinglePlayerState {
                         self.splash = None;
                         self.game.resign_player(human_player);
                         self.winner = Some(npc_player);
+                        self.active_game.depot.gameplay.end_reason = GameEndReason::Resigned {
+                            player: human_player as u64,
+                            reason: None,
+                        };
+
+                        let puzzle_day = self
+                            .active_game
+                            .depot
+                            .board_info
+                            .board_seed
+                            .as_ref()
+                            .and_then(|s| s.day);
+                        if let (Some(day), Some(token)) = (puzzle_day, logged_in_as) {
+                            msgs_to_server.push(PlayerMessage::PersistPuzzleMoves {
+                                player_token: token.clone(),
+                                day,
+                                human_player: human_player as u32,
+                                moves: self.move_sequence.clone(),
+                                won: false,
+                                drawn: false,
+                                replay: self.replay,
+                                resigned: true,
+                            });
+                        }
                     }
                     Some(ResultModalAction::SharedText) => {
                         self.sub_event("shared_text".to_string());
@@ -538,6 +782,7 @@ impl SinglePlayerState {
                 // Refresh our stats UI if we receive updated stats from the server
                 if let Some(mut stats) = self.daily_stats.take() {
                     stats.hydrate_missing_days();
+                    stats.calculate_streaks(get_puzzle_day(current_time));
 
                     let matches = match &self.splash {
                         Some(ResultModalUI {
@@ -588,6 +833,11 @@ impl SinglePlayerState {
             return msgs_to_server;
         }
 
+        // Nobody moves once the game is drawn
+        if self.game.drawn {
+            return msgs_to_server;
+        }
+
         if let Some(next_response_at) = self.next_response_at {
             if self.game.next_player.unwrap() == npc_player
                 && next_response_at > self.active_game.depot.timing.current_time
@@ -604,7 +854,21 @@ impl SinglePlayerState {
                 .unwrap()
                 .turn_starts_no_later_than
             {
-                if backchannel.is_open() {
+                let book_move = self
+                    .active_game
+                    .depot
+                    .board_info
+                    .board_seed
+                    .as_ref()
+                    .filter(|seed| seed.day.is_some())
+                    .and_then(|seed| client_book_move(seed, &self.game, &self.npc.params));
+
+                if let Some(book_move) = book_move {
+                    // Book moves are instant, but still wait for the turn to start
+                    if turn_starts_no_later_than <= current_time.as_secs() {
+                        next_msg = Some((npc_player, book_move));
+                    }
+                } else if backchannel.is_open() {
                     if let Some(pending_msg) = &self.waiting_on_backchannel {
                         // Do nothing if a message is pending but our turn hasn't yet started,
                         // we'll fetch the turn once we're allowed to play.
@@ -658,6 +922,11 @@ impl SinglePlayerState {
                 player,
                 positions: [from, to],
             }),
+            Some((player, PlayerMessage::Pass)) => Some(Move::Pass { player }),
+            Some((player, PlayerMessage::Exchange(tile))) => Some(Move::Exchange { player, tile }),
+            Some((player, PlayerMessage::Challenge(position))) => {
+                Some(Move::Challenge { player, position })
+            }
             _ => None,
         };
 
@@ -674,6 +943,9 @@ impl SinglePlayerState {
                                 human_player: human_player as u32,
                                 moves: self.move_sequence.clone(),
                                 won: self.winner == Some(human_player),
+                                drawn: self.game.drawn,
+                                replay: self.replay,
+                                resigned: false,
                             });
 
                             // Ensure we never pull up an old splash screen without this move
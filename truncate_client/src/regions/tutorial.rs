@@ -12,7 +12,7 @@ use truncate_core::{
     messages::{GamePlayerMessage, GameStateMessage, PlayerMessage},
     moves::Move,
     player::{Hand, Player},
-    reporting::WordMeaning,
+    reporting::WordDefinition,
     rules::GameRules,
 };
 
@@ -114,6 +114,17 @@ impl TutorialStage {
                     self.active_game.depot.interactions.highlight_squares =
                         Some(positions.to_vec());
                 }
+                Move::Challenge { position, .. } => {
+                    self.active_game.depot.interactions.highlight_squares = Some(vec![position]);
+                }
+                Move::Exchange { tile, .. } => {
+                    self.active_game.depot.interactions.highlight_tiles = Some(vec![tile]);
+                    self.active_game.depot.interactions.highlight_squares = None;
+                }
+                Move::Pass { .. } => {
+                    self.active_game.depot.interactions.highlight_tiles = None;
+                    self.active_game.depot.interactions.highlight_squares = None;
+                }
             }
         } else {
             self.active_game.depot.interactions.highlight_tiles = None;
@@ -179,6 +190,11 @@ impl TutorialStage {
                     game_ends_at: None,
                     paused: false,
                     remaining_turns: None,
+                    read_only: false,
+                    turn: self.game.turn_count,
+                    battle_delay: Some(self.game.rules.battle_delay),
+                    challenge_mode: self.game.rules.challenge_mode,
+                    hand_capacity: self.game.rules.hand_capacity,
                 };
                 self.active_game.apply_new_state(state_message);
                 self.active_game.depot.gameplay.winner = possible_winner;
@@ -218,7 +234,7 @@ impl TutorialState {
         }
     }
 
-    pub fn load_definitions(&mut self, definitions: Vec<(String, Option<Vec<WordMeaning>>)>) {
+    pub fn load_definitions(&mut self, definitions: Vec<WordDefinition>) {
         if let Some(stage) = &mut self.stage {
             if let Some(dict_ui) = &mut stage.active_game.dictionary_ui {
                 dict_ui.load_definitions(definitions);
@@ -272,6 +288,7 @@ impl TutorialState {
                         turn_starts_no_sooner_than: now,
                         paused_turn_delta: None,
                         swap_count: 0,
+                        swaps_used: 0,
                         penalties_incurred: 0,
                         color: GAME_COLOR_BLUE,
                         seen_tiles: HashSet::new(),
@@ -287,6 +304,7 @@ impl TutorialState {
                         turn_starts_no_sooner_than: None,
                         paused_turn_delta: None,
                         swap_count: 0,
+                        swaps_used: 0,
                         penalties_incurred: 0,
                         color: GAME_COLOR_RED,
                         seen_tiles: HashSet::new(),
@@ -305,6 +323,12 @@ impl TutorialState {
                 next_player: Some(0),
                 paused: false,
                 winner: None,
+                drawn: false,
+                consecutive_passes: 0,
+                turns_without_progress: 0,
+                fallen_towns: vec![],
+                history: None,
+                word_history: None,
             };
 
             let mut active_game = ActiveGame::new(
@@ -320,6 +344,7 @@ impl TutorialState {
                 Some(0),
                 game.board.clone(),
                 game.players[0].hand.clone(),
+                game.rules.hand_capacity,
                 map_texture,
                 theme.clone(),
                 GameLocation::Tutorial,
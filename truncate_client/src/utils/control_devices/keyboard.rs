@@ -1,8 +1,12 @@
+use std::collections::HashSet;
+
 use eframe::egui::{self, Key, Modifiers};
 use truncate_core::{
+    bag::BLANK_TILE,
     board::{Board, Coordinate, Square},
     messages::PlayerMessage,
     player::Hand,
+    rules::Truncation,
 };
 
 use crate::utils::depot::TruncateDepot;
@@ -24,6 +28,33 @@ const NUM_KEYS: [Key; 9] = [
     Key::Num9,
 ];
 
+/// Finds the closest playable square in the direction of `movement`,
+/// preferring squares that are more directly in line with the movement.
+fn next_playable(
+    playable: &HashSet<Coordinate>,
+    from: Coordinate,
+    movement: [isize; 2],
+) -> Option<Coordinate> {
+    playable
+        .iter()
+        .filter_map(|c| {
+            let dx = c.x as isize - from.x as isize;
+            let dy = c.y as isize - from.y as isize;
+            let along = dx * movement[0] + dy * movement[1];
+            let across = (dx * movement[1] - dy * movement[0]).abs();
+            (along > 0).then_some(((along + across * 2, across, c.y, c.x), *c))
+        })
+        .min_by_key(|(key, _)| *key)
+        .map(|(_, c)| c)
+}
+
+fn nearest_playable(playable: &HashSet<Coordinate>, from: Coordinate) -> Option<Coordinate> {
+    playable
+        .iter()
+        .min_by_key(|c| (c.x.abs_diff(from.x) + c.y.abs_diff(from.y), c.y, c.x))
+        .copied()
+}
+
 pub fn handle_input(
     ctx: &egui::Context,
     board: &Board,
@@ -33,6 +64,11 @@ pub fn handle_input(
     let mut needs_repaint = false;
     let mut msg = None;
 
+    // Under the default truncation rules, only tiles connected to an artifact survive,
+    // so this matches what the server will accept.
+    let player = depot.gameplay.player_number as usize;
+    let playable_positions = || board.playable_positions(player, &Truncation::Root);
+
     let ensure_board_selection = |depot: &mut TruncateDepot| {
         if let Some((coord, _)) = depot.interactions.selected_square_on_board {
             return coord;
@@ -68,6 +104,19 @@ pub fn handle_input(
             movement[1] *= -1;
         }
 
+        // While placing a tile, only stop on squares where it could be placed.
+        if depot.interactions.selected_tile_in_hand.is_some()
+            && !depot.interactions.keyboard_swap_mode
+        {
+            let playable = playable_positions();
+            if let Some(new_coord) = next_playable(&playable, current_selection, movement) {
+                let sq = board.get(new_coord).unwrap();
+                depot.interactions.selected_square_on_board = Some((new_coord, sq));
+                depot.interactions.previous_selected_square_on_board = Some((new_coord, sq));
+            }
+            return;
+        }
+
         let mut new_x = (current_selection.x as isize) + movement[0];
         let mut new_y = (current_selection.y as isize) + movement[1];
 
@@ -88,6 +137,27 @@ pub fn handle_input(
         }
     };
 
+    let toggle_swap_selection = |depot: &mut TruncateDepot| {
+        let current_selection = ensure_board_selection(depot);
+        if !matches!(board.get(current_selection), Ok(Square::Occupied { .. })) {
+            depot.interactions.selected_tile_on_board = None;
+            return None;
+        }
+
+        match depot.interactions.selected_tile_on_board.take() {
+            Some((already_selected_tile, _)) if already_selected_tile == current_selection => None,
+            Some((already_selected_tile, _)) => Some(PlayerMessage::Swap(
+                already_selected_tile,
+                current_selection,
+            )),
+            None => {
+                depot.interactions.selected_tile_on_board =
+                    Some((current_selection, board.get(current_selection).unwrap()));
+                None
+            }
+        }
+    };
+
     ctx.input_mut(|input| {
         if input.consume_key(Modifiers::NONE, Key::Period) {
             if !depot.ui_state.dictionary_open {
@@ -99,9 +169,17 @@ pub fn handle_input(
             }
         }
 
-        if input.consume_key(Modifiers::NONE, Key::Escape) && depot.ui_state.dictionary_open {
-            depot.ui_state.dictionary_open = false;
-            depot.ui_state.dictionary_focused = false;
+        if input.consume_key(Modifiers::NONE, Key::Escape) {
+            if depot.ui_state.dictionary_open {
+                depot.ui_state.dictionary_open = false;
+                depot.ui_state.dictionary_focused = false;
+            } else {
+                depot.interactions.selected_tile_in_hand = None;
+                depot.interactions.selected_tile_on_board = None;
+                depot.interactions.pending_blank = None;
+                depot.interactions.keyboard_swap_mode = false;
+                needs_repaint = true;
+            }
         }
 
         if depot.ui_state.dictionary_open {
@@ -127,12 +205,55 @@ pub fn handle_input(
 
         for key in 0..9 {
             if input.consume_key(Modifiers::NONE, NUM_KEYS[key]) {
-                let current_selection = ensure_board_selection(depot);
+                let Some(char) = hand.get(key) else {
+                    continue;
+                };
 
-                if let Some(char) = hand.get(key) {
-                    msg = Some(PlayerMessage::Place(current_selection, *char))
+                if depot.interactions.selected_tile_in_hand == Some((key, *char)) {
+                    depot.interactions.selected_tile_in_hand = None;
+                } else {
+                    depot.interactions.selected_tile_in_hand = Some((key, *char));
+                    depot.interactions.selected_tile_on_board = None;
+                    depot.interactions.keyboard_swap_mode = false;
+
+                    let current_selection = ensure_board_selection(depot);
+                    let playable = playable_positions();
+                    if !playable.contains(&current_selection) {
+                        if let Some(coord) = nearest_playable(&playable, current_selection) {
+                            let sq = board.get(coord).unwrap();
+                            depot.interactions.selected_square_on_board = Some((coord, sq));
+                            depot.interactions.previous_selected_square_on_board =
+                                Some((coord, sq));
+                        }
+                    }
+                }
+                needs_repaint = true;
+            }
+        }
+
+        if input.consume_key(Modifiers::NONE, Key::Tab) {
+            depot.interactions.keyboard_swap_mode = !depot.interactions.keyboard_swap_mode;
+            depot.interactions.selected_tile_in_hand = None;
+            depot.interactions.selected_tile_on_board = None;
+            ensure_board_selection(depot);
+            needs_repaint = true;
+        }
+
+        if input.consume_key(Modifiers::NONE, Key::Enter) {
+            if depot.interactions.keyboard_swap_mode {
+                msg = toggle_swap_selection(depot);
+            } else if let Some((_, char)) = depot.interactions.selected_tile_in_hand {
+                let current_selection = ensure_board_selection(depot);
+                if playable_positions().contains(&current_selection) {
+                    if char == BLANK_TILE {
+                        depot.interactions.pending_blank = Some(current_selection);
+                    } else {
+                        msg = Some(PlayerMessage::Place(current_selection, char));
+                    }
+                    depot.interactions.selected_tile_in_hand = None;
                 }
             }
+            needs_repaint = true;
         }
 
         for c in (b'A'..=b'Z').map(|c| [c]) {
@@ -141,36 +262,18 @@ pub fn handle_input(
                 Modifiers::NONE,
                 Key::from_name(letter).expect("letters should have keys"),
             ) {
-                let current_selection = ensure_board_selection(depot);
+                let letter = letter.chars().next().unwrap();
 
-                msg = Some(PlayerMessage::Place(
-                    current_selection,
-                    letter.chars().next().unwrap(),
-                ))
+                // While a blank is waiting on its letter, typing a letter assigns it
+                msg = match depot.interactions.pending_blank.take() {
+                    Some(coord) => Some(PlayerMessage::Place(coord, letter.to_ascii_lowercase())),
+                    None => Some(PlayerMessage::Place(ensure_board_selection(depot), letter)),
+                };
             }
         }
 
         if input.consume_key(Modifiers::NONE, Key::Space) {
-            let current_selection = ensure_board_selection(depot);
-            if matches!(board.get(current_selection), Ok(Square::Occupied { .. })) {
-                if let Some((already_selected_tile, _)) = depot.interactions.selected_tile_on_board
-                {
-                    if already_selected_tile == current_selection {
-                        depot.interactions.selected_tile_on_board = None;
-                    } else {
-                        msg = Some(PlayerMessage::Swap(
-                            already_selected_tile,
-                            current_selection,
-                        ));
-                        depot.interactions.selected_tile_on_board = None;
-                    }
-                } else {
-                    depot.interactions.selected_tile_on_board =
-                        Some((current_selection, board.get(current_selection).unwrap()));
-                }
-            } else {
-                depot.interactions.selected_tile_on_board = None;
-            }
+            msg = toggle_swap_selection(depot).or(msg.take());
         }
     });
 
@@ -1,16 +1,19 @@
+use std::ops::RangeInclusive;
+
 use epaint::{vec2, Color32, Rect, TextureHandle, Vec2};
+use hashbrown::HashMap;
 use instant::Duration;
 use truncate_core::{
     board::{Coordinate, Square},
     generation::BoardSeed,
-    messages::RoomCode,
+    messages::{GameEndReason, RoomCode},
     npc::scoring::NPCPersonality,
     reporting::Change,
 };
 
 use crate::regions::active_game::HeaderType;
 
-use super::Theme;
+use super::{settings::Settings, sounds::SoundEffect, theming::player_palette, Theme};
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct HoveredRegion {
@@ -35,6 +38,10 @@ pub struct InteractionDepot {
     pub playing_tile: Option<char>,
     pub hovered_tile_in_hand: Option<(usize, char)>,
     pub selected_tile_in_hand: Option<(usize, char)>,
+    /// Where a blank from the hand is being placed, while the player picks its letter
+    pub pending_blank: Option<Coordinate>,
+    /// Whether the keyboard's enter key selects tiles to swap, rather than placing tiles
+    pub keyboard_swap_mode: bool,
     pub highlight_tiles: Option<Vec<char>>,
     pub highlight_squares: Option<Vec<Coordinate>>,
 }
@@ -44,6 +51,10 @@ pub struct RegionDepot {
     pub hand_total_rect: Option<Rect>,
     pub hand_companion_rect: Option<Rect>,
     pub headers_total_rect: Option<Rect>,
+    /// Where the board's map (including its buffer) was last painted, in screen space
+    pub board_texture_rect: Option<Rect>,
+    /// The area the board is viewed through
+    pub board_viewport_rect: Option<Rect>,
 }
 
 #[derive(Clone, Default)]
@@ -56,6 +67,8 @@ pub struct UIStateDepot {
     pub is_touch: bool,
     pub game_header: HeaderType,
     pub actions_menu_open: bool,
+    /// Whether local games show the NPC's evaluation of the position
+    pub analysis_open: bool,
     pub dictionary_open: bool,
     pub dictionary_focused: bool,
     pub dictionary_opened_by_keyboard: bool,
@@ -68,6 +81,8 @@ pub struct BoardDepot {
     pub board_seed: Option<BoardSeed>,
     pub board_moved: bool,
     pub board_zoom: f32,
+    /// Zoom limits, relative to the zoom that fits the whole board on screen
+    pub zoom_range: RangeInclusive<f32>,
     pub board_pan: Vec2,
 }
 
@@ -77,17 +92,46 @@ impl Default for BoardDepot {
             board_seed: None,
             board_moved: false,
             board_zoom: 1.0,
+            zoom_range: 0.5..=4.0,
             board_pan: vec2(0.0, 0.0),
         }
     }
 }
 
+impl BoardDepot {
+    /// Multiplies the zoom by `delta` within `zoom_range`,
+    /// returning the multiplier that was actually applied.
+    pub fn zoom_by(&mut self, delta: f32) -> f32 {
+        let target =
+            (self.board_zoom * delta).clamp(*self.zoom_range.start(), *self.zoom_range.end());
+        let applied = target / self.board_zoom;
+        self.board_zoom = target;
+        applied
+    }
+}
+
 #[derive(Clone, Default)]
 pub struct TimingDepot {
     pub current_time: Duration,
     pub last_turn_change: Duration,
     pub game_ends_at: Option<u64>,
     pub paused: bool,
+    /// The rules' pause after a battle in seconds, if the game has told us
+    pub battle_delay: Option<u64>,
+}
+
+/// Share of the battle delay that destroyed tiles spend animating, leaving time for the ripple
+const DESTRUCTION_SHARE_OF_DELAY: f32 = 0.3;
+
+impl TimingDepot {
+    /// How long destroyed tiles animate for, paced to the rules' battle delay
+    /// so that the animation settles before the next turn starts
+    pub fn destruction_duration(&self, aesthetics: &AestheticDepot) -> f32 {
+        match self.battle_delay {
+            Some(delay) if delay > 0 => delay as f32 * DESTRUCTION_SHARE_OF_DELAY,
+            _ => aesthetics.destruction_duration,
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -97,10 +141,14 @@ pub struct GameplayDepot {
     pub next_player_number: Option<u64>,
     pub error_msg: Option<String>,
     pub winner: Option<usize>,
+    pub drawn: bool,
+    pub end_reason: GameEndReason,
     pub changes: Vec<Change>,
     pub last_battle_origin: Option<Coordinate>,
     pub npc: Option<NPCPersonality>,
     pub remaining_turns: Option<u64>,
+    /// Defending words are only judged when challenged, so the player is offered challenges
+    pub challenge_mode: bool,
 }
 
 #[derive(Clone)]
@@ -111,11 +159,43 @@ pub struct AestheticDepot {
     pub player_colors: Vec<Color32>,
     pub destruction_tick: f32,
     pub destruction_duration: f32,
+    /// Radiate battle explosions as a square rather than a diamond
+    pub destruction_square_ripple: bool,
+    pub colorblind: bool,
+    /// Bumped whenever a setting changes how the board looks, so that cached maps repaint
+    pub settings_tick: u32,
+}
+
+impl AestheticDepot {
+    /// Switches the theme and player colors to or from the colorblind-safe palette,
+    /// given the colors the players were originally assigned.
+    pub fn set_colorblind(&mut self, colorblind: bool, base_colors: impl Iterator<Item = Color32>) {
+        self.colorblind = colorblind;
+        self.theme = self.theme.clone().with_palette(colorblind);
+        self.player_colors = player_palette(base_colors, colorblind);
+        self.settings_tick = self.settings_tick.wrapping_add(1);
+    }
+
+    pub fn set_daytime(&mut self, daytime: bool) {
+        if self.theme.daytime == daytime {
+            return;
+        }
+
+        let theme = match (daytime, self.theme.use_old_art) {
+            (false, _) => Theme::night(),
+            (true, true) => Theme::old_day(),
+            (true, false) => Theme::day(),
+        };
+        self.theme = theme.with_palette(self.colorblind);
+        self.settings_tick = self.settings_tick.wrapping_add(1);
+    }
 }
 
 #[derive(Clone, Default)]
 pub struct AudioDepot {
     pub muted: bool,
+    pub pending_sounds: Vec<SoundEffect>,
+    pub last_played: HashMap<SoundEffect, Duration>,
 }
 
 #[derive(Clone)]
@@ -129,3 +209,25 @@ pub struct TruncateDepot {
     pub aesthetics: AestheticDepot,
     pub audio: AudioDepot,
 }
+
+impl TruncateDepot {
+    pub fn settings(&self) -> Settings {
+        Settings {
+            daytime: self.aesthetics.theme.daytime,
+            muted: self.audio.muted,
+            colorblind: self.aesthetics.colorblind,
+        }
+    }
+
+    /// Applies `settings` to this game, given the colors the players were originally assigned.
+    pub fn apply_settings(
+        &mut self,
+        settings: &Settings,
+        base_colors: impl Iterator<Item = Color32>,
+    ) {
+        self.audio.muted = settings.muted;
+        self.aesthetics.set_daytime(settings.daytime);
+        self.aesthetics
+            .set_colorblind(settings.colorblind, base_colors);
+    }
+}
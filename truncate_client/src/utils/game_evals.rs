@@ -2,12 +2,24 @@ use std::sync::{Mutex, MutexGuard};
 
 use truncate_core::{
     game::Game,
+    generation::BoardSeed,
     judge::{WordData, WordDict},
     messages::PlayerMessage,
-    npc::scoring::{NPCParams, NPCVocab},
+    npc::{
+        opening_book::OpeningBook,
+        scoring::{BoardScore, NPCParams, NPCVocab},
+        Caches,
+    },
 };
 
 pub static TRUNCATE_DICT: &str = include_str!("../../../dict_builder/final_wordlist.txt");
+const OPENING_BOOK: &[u8] = include_bytes!("../../../truncate_dueller/opening_book.yml");
+
+static LOADED_OPENING_BOOK: Mutex<Option<OpeningBook>> = Mutex::new(None);
+/// Kept between the NPC's moves, bounded so that long games don't grow the web worker
+static NPC_CACHES: Mutex<Option<Caches>> = Mutex::new(None);
+const NPC_CACHED_FLOODS: usize = 256;
+const NPC_CACHED_WORDS: usize = 20_000;
 
 static TOTAL_DICT: Mutex<Option<WordDict>> = Mutex::new(None);
 static SMALL_VOCAB_DICT_SAFE: Mutex<Option<WordDict>> = Mutex::new(None);
@@ -101,6 +113,24 @@ pub fn get_main_dict() -> MutexGuard<'static, Option<WordDict>> {
     TOTAL_DICT.lock().unwrap()
 }
 
+/// Finds the NPC's move in the opening book the dueller recorded for daily puzzles.
+/// The book was recorded at full strength, so weakened NPCs always search instead.
+pub fn client_book_move(
+    seed: &BoardSeed,
+    game: &Game,
+    npc_params: &NPCParams,
+) -> Option<PlayerMessage> {
+    if npc_params.difficulty < 1.0 {
+        return None;
+    }
+
+    let mut book = LOADED_OPENING_BOOK.lock().unwrap();
+    let book = book.get_or_insert_with(|| {
+        serde_yaml::from_slice(OPENING_BOOK).expect("Opening book should match the spec")
+    });
+    book.lookup(seed, game).cloned()
+}
+
 pub fn client_best_move(game: &Game, npc_params: &NPCParams) -> PlayerMessage {
     ensure_dicts();
 
@@ -118,14 +148,18 @@ pub fn client_best_move(game: &Game, npc_params: &NPCParams) -> PlayerMessage {
     let mut arb = truncate_core::npc::Arborist::pruning();
     arb.capped(npc_params.evaluation_cap);
 
-    let (best_move, _score) = truncate_core::game::Game::best_move(
+    let mut caches = NPC_CACHES.lock().unwrap();
+    let caches =
+        caches.get_or_insert_with(|| Caches::persistent(NPC_CACHED_FLOODS, NPC_CACHED_WORDS));
+
+    let best_move = truncate_core::game::Game::npc_move(
         game,
         npc_known_dict.as_ref(),
         player_known_dict.as_ref(),
-        npc_params.max_depth,
         Some(&mut arb),
-        false,
+        Some(caches),
         npc_params,
+        0,
     );
 
     let _end = instant::SystemTime::now()
@@ -136,6 +170,39 @@ pub fn client_best_move(game: &Game, npc_params: &NPCParams) -> PlayerMessage {
     best_move
 }
 
+/// Suggests the best `count` moves for the next player, best first,
+/// only considering words a player is likely to know.
+pub fn client_ranked_moves(game: &Game, count: usize) -> Vec<PlayerMessage> {
+    ensure_dicts();
+
+    let hint_dict = MEDIUM_VOCAB_DICT_SAFE.lock().unwrap();
+
+    game.ranked_moves(hint_dict.as_ref(), 2, count)
+        .into_iter()
+        .map(|(msg, _score)| msg)
+        .collect()
+}
+
+/// Scores the position for the given player the way the NPC would,
+/// without looking any moves ahead.
+pub fn client_analyze_position(
+    game: &Game,
+    for_player: usize,
+    npc_params: &NPCParams,
+) -> BoardScore {
+    ensure_dicts();
+
+    let total_dict = TOTAL_DICT.lock().unwrap();
+
+    game.static_eval(
+        total_dict.as_ref(),
+        for_player,
+        1,
+        &mut Caches::new(),
+        npc_params,
+    )
+}
+
 /// Adds the given word to the static dictionaries for the NPC
 pub fn remember(word: &String) {
     ensure_dicts();
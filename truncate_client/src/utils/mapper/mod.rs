@@ -29,6 +29,36 @@ mod image_manipulation;
 
 type WantsRepaint = bool;
 
+/// Tiles drawn smaller than this many points on screen are too small for their animation to be seen
+const MIN_ANIMATED_TILE_SIZE: f32 = 16.0;
+
+/// Maps that would need textures with more pixels than this are left unpainted,
+/// rather than allocating enough texture memory to crash the client
+const MAX_MAP_TEXTURE_PIXELS: usize = 4096 * 4096;
+
+/// Which tiles of the map are animated, by their row and column in the map's textures
+#[derive(Clone, Copy, PartialEq)]
+enum TileAnimation {
+    All,
+    Within {
+        rows: (usize, usize),
+        cols: (usize, usize),
+    },
+    Static,
+}
+
+impl TileAnimation {
+    fn animates(&self, row: usize, col: usize) -> bool {
+        match self {
+            TileAnimation::All => true,
+            TileAnimation::Within { rows, cols } => {
+                (rows.0..=rows.1).contains(&row) && (cols.0..=cols.1).contains(&col)
+            }
+            TileAnimation::Static => false,
+        }
+    }
+}
+
 #[derive(Clone)]
 struct ResolvedTextureLayers {
     terrain: TextureHandle,
@@ -103,6 +133,7 @@ struct MapState {
     prev_occupied_hover: Option<HoveredRegion>,
     prev_square_hover: Option<HoveredRegion>,
     prev_changes: Vec<Change>,
+    prev_settings_tick: u32,
     generic_tick: u32,
 }
 
@@ -124,6 +155,7 @@ pub struct MappedBoard {
     incoming_wind: u8,
     winds: VecDeque<u8>,
     distance_to_land: BoardDistances,
+    tile_animation: TileAnimation,
 }
 
 impl MappedBoard {
@@ -158,6 +190,7 @@ impl MappedBoard {
             incoming_wind: 0,
             winds: vec![0; board.width() + board.height()].into(),
             distance_to_land: board.flood_fill_water_from_land(),
+            tile_animation: TileAnimation::All,
         };
 
         mapper.remap_texture(ctx, aesthetics, &TimingDepot::default(), None, None, board);
@@ -169,12 +202,24 @@ impl MappedBoard {
         self.map_buffer
     }
 
+    /// Paints the board into `rect`, which may extend past the visible area when zoomed in.
+    /// Only the visible viewport of the offscreen textures is painted.
+    /// Those textures don't depend on the zoom or pan (they're sampled with nearest filtering),
+    /// so moving around the board never requires remapping.
     pub fn render_to_rect(&self, rect: Rect, ui_state: Option<&UIStateDepot>, ui: &mut egui::Ui) {
-        let uv = Rect::from_min_max(pos2(0.0, 0.0), pos2(1.0, 1.0));
+        let viewport = rect.intersect(ui.clip_rect());
+        if !viewport.is_positive() {
+            return;
+        }
+
+        let uv = Rect::from_min_max(
+            ((viewport.min - rect.min) / rect.size()).to_pos2(),
+            ((viewport.max - rect.min) / rect.size()).to_pos2(),
+        );
 
         let paint = |id: epaint::TextureId, color: Color32| {
             let mut mesh = Mesh::with_texture(id);
-            mesh.add_rect_with_uv(rect, uv, color);
+            mesh.add_rect_with_uv(viewport, uv, color);
             ui.painter().add(Shape::mesh(mesh));
         };
 
@@ -196,6 +241,53 @@ impl MappedBoard {
         }
     }
 
+    /// Limits animation to the tiles visible within `viewport`, for a board rendered into `rect`.
+    /// When zoomed out far enough that tiles are tiny, nothing is animated.
+    /// Tiles that aren't animated are painted static, and never ask for a repaint.
+    pub fn animate_within(&mut self, rect: Rect, viewport: Rect) {
+        let map_rows = self.layer_memory.len();
+        let map_cols = self.layer_memory.first().map_or(0, |row| row.len());
+        let visible = rect.intersect(viewport);
+
+        let animation = if map_rows == 0 || map_cols == 0 || !visible.is_positive() {
+            TileAnimation::Static
+        } else {
+            let tile_width = rect.width() / map_cols as f32;
+            let tile_height = rect.height() / map_rows as f32;
+            if tile_width.min(tile_height) < MIN_ANIMATED_TILE_SIZE {
+                TileAnimation::Static
+            } else {
+                let tile_at = |offset: f32, size: f32, count: usize| {
+                    ((offset / size).floor().max(0.0) as usize).min(count - 1)
+                };
+                TileAnimation::Within {
+                    rows: (
+                        tile_at(visible.min.y - rect.min.y, tile_height, map_rows),
+                        tile_at(visible.max.y - rect.min.y, tile_height, map_rows),
+                    ),
+                    cols: (
+                        tile_at(visible.min.x - rect.min.x, tile_width, map_cols),
+                        tile_at(visible.max.x - rect.min.x, tile_width, map_cols),
+                    ),
+                }
+            }
+        };
+
+        if animation != self.tile_animation {
+            self.tile_animation = animation;
+            // Tiles that have come into view need to pick their animation back up
+            self.generic_repaint_tick += 1;
+        }
+    }
+
+    /// Returns the terrain, structure, and piece textures for the whole map,
+    /// if they have been painted yet.
+    pub fn overview_textures(&self) -> Option<[&TextureHandle; 3]> {
+        self.resolved_textures
+            .as_ref()
+            .map(|tex| [&tex.terrain, &tex.structures, &tex.pieces])
+    }
+
     pub fn render_coord_to_rect(&self, mut coord: Coordinate, rect: Rect, ui: &mut egui::Ui) {
         let Some(memory) = &self.state_memory else {
             return;
@@ -271,8 +363,11 @@ impl MappedBoard {
         gameplay: Option<&GameplayDepot>,
         aesthetics: &AestheticDepot,
         timing: &TimingDepot,
+        animate: bool,
     ) -> WantsRepaint {
         let mut wants_repaint = false;
+        // Tiles that aren't animated are painted as they'll look once everything has settled
+        let tick = if animate { tick } else { 0 };
         let coord = SignedCoordinate::new(source_col, source_row);
         let dest_coord = Coordinate::new(dest_col, dest_row);
         let resolved_textures = self.resolved_textures.as_mut().unwrap();
@@ -313,11 +408,14 @@ impl MappedBoard {
         }
         let tile_layer_type = FGTexType::from((square, player_colors));
 
-        let wind_at_coord = self
-            .winds
-            .get(dest_col + dest_row)
-            .cloned()
-            .unwrap_or_default();
+        let wind_at_coord = if animate {
+            self.winds
+                .get(dest_col + dest_row)
+                .cloned()
+                .unwrap_or_default()
+        } else {
+            0
+        };
         let seed_at_coord = self.map_seed + (dest_row * dest_col + dest_col);
 
         let mut layers = Tex::terrain(
@@ -354,7 +452,12 @@ impl MappedBoard {
         let mut tile_was_swapped = false;
         let mut tile_was_victor = false;
 
-        let base_destructo_time = (timing.current_time - timing.last_turn_change).as_secs_f32();
+        let destruction_duration = timing.destruction_duration(aesthetics);
+        let base_destructo_time = if animate {
+            (timing.current_time - timing.last_turn_change).as_secs_f32()
+        } else {
+            f32::INFINITY
+        };
         let mut destructo_time = base_destructo_time;
 
         if let Some(gameplay) = gameplay {
@@ -364,7 +467,11 @@ impl MappedBoard {
             if let Some((battle_origin, coord)) =
                 gameplay.last_battle_origin.zip(coord.real_coord())
             {
-                let dist = coord.distance_to(&battle_origin) as f32;
+                let dist = if aesthetics.destruction_square_ripple {
+                    coord.distance_to_chebyshev(&battle_origin)
+                } else {
+                    coord.distance_to(&battle_origin)
+                } as f32;
                 destructo_time -= dist * aesthetics.destruction_tick;
                 if destructo_time < 0.0 {
                     destructo_time = 0.0;
@@ -389,7 +496,7 @@ impl MappedBoard {
             };
 
             let mut animated_variant = |player: usize| {
-                if destructo_time < aesthetics.destruction_duration {
+                if destructo_time < destruction_duration {
                     wants_repaint = true;
                     (MappedTileVariant::Healthy, Some(base_color(player)))
                 } else {
@@ -418,23 +525,21 @@ impl MappedBoard {
                             ..
                         } = change.detail.square
                         {
-                            let validity_color =
-                                if base_destructo_time < aesthetics.destruction_duration {
-                                    let traj = ((aesthetics.destruction_duration
-                                        - base_destructo_time)
-                                        .clamp(0.0, 1.0)
-                                        / aesthetics.destruction_duration)
-                                        .sqrt();
-                                    let color = alpha_blend(
-                                        base_color(player),
-                                        aesthetics.theme.word_invalid,
-                                        Some(traj),
-                                    );
-
-                                    Some(color)
-                                } else {
-                                    None
-                                };
+                            let validity_color = if base_destructo_time < destruction_duration {
+                                let traj = ((destruction_duration - base_destructo_time)
+                                    .clamp(0.0, 1.0)
+                                    / destruction_duration)
+                                    .sqrt();
+                                let color = alpha_blend(
+                                    base_color(player),
+                                    aesthetics.theme.word_invalid,
+                                    Some(traj),
+                                );
+
+                                Some(color)
+                            } else {
+                                None
+                            };
                             let (variant, color) = animated_variant(player);
 
                             let tile_layers = Tex::board_game_tile(
@@ -497,6 +602,8 @@ impl MappedBoard {
                             layers = layers.merge_below_self(tile_layers);
                         }
                     }
+                    // The obelisk or town itself is redrawn from the square below
+                    BoardChangeAction::Captured | BoardChangeAction::Restored => {}
                 }
             }
         }
@@ -597,11 +704,10 @@ impl MappedBoard {
                     player_colors.get(*player).cloned().map(|c| c.lighten())
                 };
 
-                if tile_was_victor && base_destructo_time < aesthetics.destruction_duration {
+                if tile_was_victor && base_destructo_time < destruction_duration {
                     wants_repaint = true;
-                    let traj = ((aesthetics.destruction_duration - base_destructo_time)
-                        .clamp(0.0, 1.0)
-                        / aesthetics.destruction_duration)
+                    let traj = ((destruction_duration - base_destructo_time).clamp(0.0, 1.0)
+                        / destruction_duration)
                         .sqrt();
                     color = color.map(|c| alpha_blend(c, aesthetics.theme.word_valid, Some(traj)));
                 }
@@ -646,12 +752,23 @@ impl MappedBoard {
                         aesthetics.theme.button_primary
                     }
                 };
+                // Colors alone can't be relied on to show validity,
+                // so colorblind mode also cracks invalid tiles and rings partial ones.
+                let (validity_variant, validity_highlight) = match validity {
+                    truncate_core::board::SquareValidity::Invalid if aesthetics.colorblind => {
+                        (MappedTileVariant::Dying, None)
+                    }
+                    truncate_core::board::SquareValidity::Partial if aesthetics.colorblind => {
+                        (MappedTileVariant::Healthy, Some(aesthetics.theme.text))
+                    }
+                    _ => (MappedTileVariant::Healthy, None),
+                };
                 let validity_layers = Tex::board_game_tile(
-                    MappedTileVariant::Healthy,
+                    validity_variant,
                     *tile,
                     orient(*player),
                     Some(validity_color),
-                    None,
+                    validity_highlight,
                     TileDecoration::None,
                     seed_at_coord,
                 )
@@ -663,11 +780,15 @@ impl MappedBoard {
                 if let Some((interactions, coord)) = interactions.zip(coord.real_coord()) {
                     if let Some((_, tile_char)) = interactions.selected_tile_in_hand {
                         // Don't show preview tiles if anything is being dragged (i.e. a tile from the hand)
+                        // The keyboard cursor previews the tile in the same way as hovering.
                         if !ctx.memory(|m| m.is_anything_being_dragged())
-                            && interactions
+                            && (interactions
                                 .hovered_unoccupied_square_on_board
                                 .as_ref()
                                 .is_some_and(|h| h.coord == Some(coord))
+                                || interactions
+                                    .selected_square_on_board
+                                    .is_some_and(|(c, _)| c == coord))
                         {
                             let self_color = gameplay
                                 .map(|gameplay| {
@@ -930,6 +1051,7 @@ impl MappedBoard {
             let square_hover_eq = memory.prev_square_hover == square_hover;
             let generic_tick_eq = memory.generic_tick == generic_repaint_tick;
             let winner_eq = memory.prev_winner == winner;
+            let settings_eq = memory.prev_settings_tick == aesthetics.settings_tick;
             if memory.prev_tick != aesthetics.qs_tick {
                 tick_eq = false;
             }
@@ -944,6 +1066,7 @@ impl MappedBoard {
                 && square_hover_eq
                 && generic_tick_eq
                 && winner_eq
+                && settings_eq
             {
                 return;
             }
@@ -979,6 +1102,14 @@ impl MappedBoard {
             if !winner_eq {
                 memory.prev_winner = winner;
             }
+            if !settings_eq {
+                memory.prev_settings_tick = aesthetics.settings_tick;
+                // Every tile's colors may have changed, so repaint the whole map
+                self.layer_memory
+                    .iter_mut()
+                    .flatten()
+                    .for_each(|layers| *layers = TexLayers::default());
+            }
         } else {
             self.state_memory = Some(MapState {
                 prev_board: board.clone(),
@@ -990,6 +1121,7 @@ impl MappedBoard {
                 prev_occupied_hover: occupied_hover,
                 prev_square_hover: square_hover,
                 prev_changes: vec![],
+                prev_settings_tick: aesthetics.settings_tick,
                 generic_tick: 0,
                 prev_winner: winner,
             });
@@ -1012,6 +1144,19 @@ impl MappedBoard {
 
         let final_width = measures.inner_tile_width_px * (board.width() + total_buffer) * 2;
         let final_height = measures.inner_tile_height_px * (board.height() + total_buffer) * 2;
+
+        let max_side = ctx.input(|i| i.max_texture_side);
+        if final_width > max_side
+            || final_height > max_side
+            || final_width * final_height > MAX_MAP_TEXTURE_PIXELS
+        {
+            if self.resolved_textures.take().is_some() {
+                eprintln!("Not painting a {final_width}x{final_height} map, as it is too large");
+            }
+            self.layer_memory.clear();
+            return;
+        }
+
         let sized_correct = self
             .resolved_textures
             .as_ref()
@@ -1049,6 +1194,7 @@ impl MappedBoard {
                     .and_then(|c| board.get(c).ok())
                     .unwrap_or_else(|| Square::Water { foggy: false });
 
+                let animate = self.tile_animation.animates(dest_row, dest_col);
                 let wants_repaint = self.paint_square_offscreen(
                     ctx,
                     board,
@@ -1066,6 +1212,7 @@ impl MappedBoard {
                     gameplay,
                     aesthetics,
                     timing,
+                    animate,
                 );
 
                 if wants_repaint {
@@ -1154,7 +1301,8 @@ impl MappedTiles {
         self.last_tick = tick;
 
         if self.capacity < self.slots.len() {
-            self.tile_texture = MappedTiles::reset_texture(self.slots.len(), egui_ctx);
+            self.capacity = self.slots.len();
+            self.tile_texture = MappedTiles::reset_texture(self.capacity, egui_ctx);
         }
 
         let measures = TEXTURE_MEASUREMENT
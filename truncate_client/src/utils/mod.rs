@@ -6,6 +6,8 @@ pub mod glyph_utils;
 pub mod includes;
 pub mod macros;
 pub mod mapper;
+pub mod settings;
+pub mod sounds;
 pub mod tex;
 pub mod text;
 pub mod theming;
@@ -0,0 +1,88 @@
+use serde::{Deserialize, Serialize};
+
+/// Player preferences that persist between sessions,
+/// in local storage on web and in a config file on native.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub daytime: bool,
+    pub muted: bool,
+    pub colorblind: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            daytime: true,
+            muted: false,
+            colorblind: false,
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+const SETTINGS_KEY: &str = "truncate_settings";
+
+impl Settings {
+    #[cfg(target_arch = "wasm32")]
+    pub fn load() -> Self {
+        let local_storage = web_sys::window().unwrap().local_storage().unwrap().unwrap();
+        if let Some(settings) = local_storage
+            .get_item(SETTINGS_KEY)
+            .unwrap()
+            .and_then(|s| serde_json::from_str(&s).ok())
+        {
+            return settings;
+        }
+
+        // Sound was stored on its own before settings were grouped together
+        Self {
+            muted: local_storage
+                .get_item("truncate_muted")
+                .unwrap()
+                .unwrap_or_default()
+                .parse()
+                .unwrap_or_default(),
+            ..Self::default()
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn save(&self) {
+        let local_storage = web_sys::window().unwrap().local_storage().unwrap().unwrap();
+        local_storage
+            .set_item(SETTINGS_KEY, &serde_json::to_string(self).unwrap())
+            .unwrap();
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn config_path() -> Option<std::path::PathBuf> {
+        let config_dir = std::env::var_os("XDG_CONFIG_HOME")
+            .map(std::path::PathBuf::from)
+            .or_else(|| {
+                std::env::var_os("HOME").map(|h| std::path::Path::new(&h).join(".config"))
+            })?;
+        Some(config_dir.join("truncate").join("settings.json"))
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load() -> Self {
+        Self::config_path()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default()
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save(&self) {
+        let Some(path) = Self::config_path() else {
+            return;
+        };
+        if let Some(dir) = path.parent() {
+            _ = std::fs::create_dir_all(dir);
+        }
+        if let Err(e) = std::fs::write(&path, serde_json::to_string_pretty(self).unwrap()) {
+            tracing::warn!("Failed to save settings to {}: {e}", path.display());
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use instant::Duration;
+use serde::{Deserialize, Serialize};
+use truncate_core::reporting::{BoardChangeAction, Change};
+
+use crate::app_outer::{Backchannel, BackchannelMsg};
+
+use super::depot::AudioDepot;
+
+/// The same sound won't be replayed within this window,
+/// so that a turn full of explosions plays one explosion.
+const SOUND_DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// The low time warning beeps about once per second on the clock
+const LOW_TIME_DEBOUNCE: Duration = Duration::from_millis(900);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum SoundEffect {
+    Added,
+    Defeated,
+    Truncated,
+    Exploded,
+    Victorious,
+    LowTime,
+}
+
+impl SoundEffect {
+    fn for_action(action: &BoardChangeAction) -> Option<Self> {
+        match action {
+            BoardChangeAction::Added => Some(Self::Added),
+            BoardChangeAction::Defeated => Some(Self::Defeated),
+            BoardChangeAction::Truncated => Some(Self::Truncated),
+            BoardChangeAction::Exploded => Some(Self::Exploded),
+            BoardChangeAction::Victorious => Some(Self::Victorious),
+            BoardChangeAction::Swapped
+            | BoardChangeAction::Captured
+            | BoardChangeAction::Restored => None,
+        }
+    }
+}
+
+/// Queues one sound for each kind of board change in `changes`,
+/// skipping any that were played too recently.
+pub fn queue_change_sounds(changes: &[Change], audio: &mut AudioDepot, now: Duration) {
+    if audio.muted {
+        return;
+    }
+
+    for change in changes {
+        let Change::Board(board_change) = change else {
+            continue;
+        };
+        let Some(sound) = SoundEffect::for_action(&board_change.action) else {
+            continue;
+        };
+        if audio.pending_sounds.contains(&sound) {
+            continue;
+        }
+        if audio
+            .last_played
+            .get(&sound)
+            .is_some_and(|played_at| now.saturating_sub(*played_at) < SOUND_DEBOUNCE)
+        {
+            continue;
+        }
+
+        audio.last_played.insert(sound, now);
+        audio.pending_sounds.push(sound);
+    }
+}
+
+/// Queues a beep for a clock that is running low,
+/// at most once per tick of the displayed countdown.
+pub fn queue_low_time_sound(audio: &mut AudioDepot, now: Duration) {
+    let sound = SoundEffect::LowTime;
+    if audio.muted || audio.pending_sounds.contains(&sound) {
+        return;
+    }
+    if audio
+        .last_played
+        .get(&sound)
+        .is_some_and(|played_at| now.saturating_sub(*played_at) < LOW_TIME_DEBOUNCE)
+    {
+        return;
+    }
+
+    audio.last_played.insert(sound, now);
+    audio.pending_sounds.push(sound);
+}
+
+/// Hands any queued sounds to the outer host to be played
+pub fn play_queued_sounds(audio: &mut AudioDepot, backchannel: &Backchannel) {
+    for sound in audio.pending_sounds.drain(..) {
+        if !audio.muted {
+            backchannel.send_msg(BackchannelMsg::PlaySound { sound });
+        }
+    }
+}
@@ -215,14 +215,21 @@ impl Tex {
         color: Option<Color32>,
         highlight: Option<Color32>,
     ) -> TexLayers {
+        // Blanks are placed as the lowercase form of their assigned letter,
+        // and are inked lighter to tell them apart from drawn letters
+        let ink = if truncate_core::bag::is_placed_blank(character) {
+            hex_color!("#7A7A7A")
+        } else {
+            hex_color!("#333333")
+        };
         let mut layers = TexLayers::default()
             .with_piece_texture(
                 tiles::quad::GAME_PIECE.tint(color.unwrap_or(Color32::WHITE)),
                 color,
             )
             .with_piece_character(
-                character,
-                hex_color!("#333333"),
+                character.to_ascii_uppercase(),
+                ink,
                 orientation != Direction::North,
                 -1,
             );
@@ -335,7 +342,7 @@ impl Tex {
                         color,
                     )
                     .with_piece_character(
-                        character,
+                        character.to_ascii_uppercase(),
                         hex_color!("#888888"),
                         orientation != Direction::North,
                         0,
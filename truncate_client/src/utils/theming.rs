@@ -144,6 +144,51 @@ impl Theme {
     }
 }
 
+/// Okabe-Ito colors, which stay distinguishable under the common forms of color blindness
+const COLORBLIND_PLAYER_COLORS: [Color32; 6] = [
+    Color32::from_rgb(0x00, 0x72, 0xB2),
+    Color32::from_rgb(0xE6, 0x9F, 0x00),
+    Color32::from_rgb(0xCC, 0x79, 0xA7),
+    Color32::from_rgb(0x00, 0x9E, 0x73),
+    Color32::from_rgb(0xF0, 0xE4, 0x42),
+    Color32::from_rgb(0x56, 0xB4, 0xE9),
+];
+
+impl Theme {
+    /// Swaps the word validity colors between the default red/green pair
+    /// and a blue/orange pair that reads under color blindness
+    pub fn with_palette(self, colorblind: bool) -> Self {
+        if colorblind {
+            Self {
+                word_valid: hex_color!("#0072B2"),
+                word_invalid: hex_color!("#D55E00"),
+                ..self
+            }
+        } else {
+            Self {
+                word_valid: hex_color!("#00A37D"),
+                word_invalid: hex_color!("#89043D"),
+                ..self
+            }
+        }
+    }
+}
+
+/// Returns the colors to draw each player with, replacing the
+/// server-assigned colors with a colorblind-safe set when requested
+pub fn player_palette(colors: impl Iterator<Item = Color32>, colorblind: bool) -> Vec<Color32> {
+    colors
+        .enumerate()
+        .map(|(i, color)| {
+            if colorblind {
+                COLORBLIND_PLAYER_COLORS[i % COLORBLIND_PLAYER_COLORS.len()]
+            } else {
+                color
+            }
+        })
+        .collect()
+}
+
 impl Theme {
     pub fn calc_rescale(
         &self,
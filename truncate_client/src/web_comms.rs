@@ -40,6 +40,8 @@ pub async fn connect(
 
     let most_recent_game_token: Arc<Mutex<Option<String>>> = Arc::new(Mutex::new(None));
     let most_recent_login: Arc<Mutex<Option<PlayerMessage>>> = Arc::new(Mutex::new(None));
+    // The last turn we saw in that game, so that reconnecting only fetches what we missed
+    let most_recent_turn: Arc<Mutex<Option<u32>>> = Arc::new(Mutex::new(None));
 
     let requested_login = AtomicBool::new(false);
 
@@ -61,6 +63,7 @@ pub async fn connect(
         // recreate the connection.
         if let PlayerMessage::RejoinGame(token) = &message {
             *most_recent_game_token.lock().unwrap() = Some(token.to_string());
+            *most_recent_turn.lock().unwrap() = None;
         }
 
         if let PlayerMessage::Login { .. } = &message {
@@ -109,7 +112,10 @@ pub async fn connect(
         }
 
         if let Some(token) = most_recent_game_token.lock().unwrap().clone() {
-            let reconnection_msg = PlayerMessage::RejoinGame(token);
+            let reconnection_msg = match *most_recent_turn.lock().unwrap() {
+                Some(turn) => PlayerMessage::RejoinGameFromTurn(token, turn),
+                None => PlayerMessage::RejoinGame(token),
+            };
             let encoded_reconnection_msg =
                 WsMessage::Text(serde_json::to_string(&reconnection_msg).unwrap());
             if outgoing.send(encoded_reconnection_msg).await.is_err() {
@@ -154,10 +160,24 @@ pub async fn connect(
                             }
                         }
                     }
-                    GameMessage::JoinedLobby(_, _, _, _, token) => {
+                    GameMessage::JoinedLobby(_, _, _, _, _, token) => {
                         // Store a token that we're interacting with, in case we need to
                         // recreate the connection.
                         *most_recent_game_token.lock().unwrap() = Some(token.to_string());
+                        *most_recent_turn.lock().unwrap() = None;
+                    }
+                    GameMessage::StartedGame(state)
+                    | GameMessage::GameUpdate(state)
+                    | GameMessage::GameTimingUpdate(state)
+                        if !state.read_only =>
+                    {
+                        *most_recent_turn.lock().unwrap() = Some(state.turn);
+                    }
+                    GameMessage::GameCatchUp(catch_up) => {
+                        *most_recent_turn.lock().unwrap() = Some(catch_up.turn);
+                    }
+                    GameMessage::GameEnd(..) | GameMessage::GameDraw(_) => {
+                        *most_recent_turn.lock().unwrap() = None;
                     }
                     _ => { /* no processing needed */ }
                 }
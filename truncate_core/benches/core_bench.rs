@@ -87,7 +87,7 @@ pub fn npc_benches(c: &mut Criterion) {
     });
 
     c.bench_function("quality_eval", |b| {
-        b.iter(|| game.eval_word_quality(&dict, 1, &mut Caches::new()))
+        b.iter(|| game.eval_word_quality(&dict, 1, &mut Caches::new(), &NPCParams::default()))
     });
 
     c.bench_function("defense_eval", |b| {
@@ -210,6 +210,8 @@ pub fn board_benches(c: &mut Criterion) {
 
     c.bench_function("get_shape", |b| b.iter(|| board.get_shape()));
 
+    c.bench_function("shape_hash", |b| b.iter(|| board.shape_hash()));
+
     c.bench_function("get_word_coordinates", |b| {
         b.iter(|| board.get_words(Coordinate { x: 2, y: 5 }))
     });
@@ -229,6 +231,7 @@ pub fn judge_benches(c: &mut Criterion) {
     let win_condition = rules::WinCondition::Destination {
         town_defense: rules::TownDefense::BeatenWithDefenseStrength(0),
         artifact_defense: rules::ArtifactDefense::BeatenWithDefenseStrength(0),
+        towns_to_defeat: rules::TownsToDefeat::Any,
     };
 
     c.bench_function("judge_with_double_alias", |b| {
@@ -1,4 +1,5 @@
 use oorandom::Rand32;
+use serde::{Deserialize, Serialize};
 use std::fmt;
 
 use crate::rules;
@@ -95,24 +96,68 @@ const TILE_GENERATIONS: [[usize; 26]; 2] = [
     ],
 ];
 
-#[derive(Debug, Clone)]
+/// A tile that can be played as any letter.
+/// Once placed, it sits on the board as the lowercase form of its assigned letter.
+pub const BLANK_TILE: char = '?';
+
+/// Whether a tile on the board is a blank, which is only ever assigned an ASCII letter.
+/// Real tiles are uppercase in every language, so a lowercase letter like `é` is a tile of its own.
+pub fn is_placed_blank(tile: char) -> bool {
+    tile.is_ascii_lowercase()
+}
+
+/// Tiles are uppercase, leaving lowercase ASCII letters to mean a placed blank
+fn uppercase_tile(tile: char) -> char {
+    let mut upper = tile.to_uppercase();
+    match (upper.next(), upper.next()) {
+        (Some(upper), None) => upper,
+        _ => tile,
+    }
+}
+
+/// A single change to the tiles in the bag, journaled so that a turn can be taken back
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum BagEntry {
+    /// The tile drawn, and the index it was removed from
+    Drawn {
+        index: usize,
+        tile: char,
+    },
+    Returned(char),
+    /// The number of tiles added when the bag was refilled
+    Filled(usize),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TileBag {
     bag: Vec<char>,
+    #[serde(with = "rng_state")]
     rng: Rand32,
     letter_distribution: Option<[usize; 26]>,
+    #[serde(default)]
+    blanks: usize,
+    /// The seed the RNG was created from, kept so that draws can be reproduced
+    #[serde(default)]
+    seed: u64,
+    /// Only kept while a turn is being recorded for undo
+    #[serde(skip)]
+    journal: Option<Vec<BagEntry>>,
 }
 
 impl TileBag {
     pub fn generation(gen: u32, seed: Option<u64>) -> Self {
         TileBag::custom(
-            TILE_GENERATIONS
-                .get(gen as usize)
-                .expect("Tilebag generation should exist")
-                .clone(),
+            TileBag::letter_distribution(gen).expect("Tilebag generation should exist"),
             seed,
         )
     }
 
+    /// How many of each letter, from A to Z, a generation's bag holds.
+    /// Generations are never edited once shipped, as daily seeds pin them.
+    pub fn letter_distribution(gen: u32) -> Option<[usize; 26]> {
+        TILE_GENERATIONS.get(gen as usize).copied()
+    }
+
     pub fn latest(seed: Option<u64>) -> (u32, Self) {
         assert!(!TILE_GENERATIONS.is_empty());
         let generation = (TILE_GENERATIONS.len() - 1) as u32;
@@ -120,61 +165,169 @@ impl TileBag {
     }
 
     pub fn custom(letter_distribution: [usize; 26], seed: Option<u64>) -> Self {
+        let seed = TileBag::resolve_seed(seed);
         let mut tile_bag = TileBag {
             bag: Vec::new(),
-            rng: Rand32::new(seed.unwrap_or_else(|| {
-                instant::SystemTime::now()
-                    .duration_since(instant::SystemTime::UNIX_EPOCH)
-                    .expect("Please don't play Truncate earlier than 1970")
-                    .as_secs()
-            })),
+            rng: Rand32::new(seed),
             letter_distribution: Some(letter_distribution),
+            blanks: 0,
+            seed,
+            journal: None,
         };
         tile_bag.fill();
         tile_bag
     }
 
+    /// Adds blank tiles to the bag, and to each future refill of the bag
+    pub fn with_blanks(mut self, blanks: usize) -> Self {
+        self.blanks = blanks;
+        self.bag.extend([BLANK_TILE].repeat(blanks));
+        self
+    }
+
     pub fn explicit(tiles: Vec<char>, seed: Option<u64>) -> Self {
+        let seed = TileBag::resolve_seed(seed);
         TileBag {
-            bag: tiles,
-            rng: Rand32::new(seed.unwrap_or_else(|| {
-                instant::SystemTime::now()
-                    .duration_since(instant::SystemTime::UNIX_EPOCH)
-                    .expect("Please don't play Truncate earlier than 1970")
-                    .as_secs()
-            })),
+            bag: tiles.into_iter().map(uppercase_tile).collect(),
+            rng: Rand32::new(seed),
             letter_distribution: None,
+            blanks: 0,
+            seed,
+            journal: None,
         }
     }
 
+    fn resolve_seed(seed: Option<u64>) -> u64 {
+        seed.unwrap_or_else(|| {
+            instant::SystemTime::now()
+                .duration_since(instant::SystemTime::UNIX_EPOCH)
+                .expect("Please don't play Truncate earlier than 1970")
+                .as_secs()
+        })
+    }
+
+    /// The seed this bag was created with, which reproduces
+    /// the same sequence of draws when passed back in as the seed
+    pub fn current_seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// The tiles left in the bag before it is next refilled
+    pub fn remaining(&self) -> &[char] {
+        &self.bag
+    }
+
     pub fn draw_tile(&mut self) -> char {
         if self.bag.is_empty() {
             self.fill();
         }
-        let index = self.rng.rand_range(0..self.bag.len() as u32);
-        self.bag.swap_remove(index as usize)
+        let index = self.rng.rand_range(0..self.bag.len() as u32) as usize;
+        let tile = self.bag.swap_remove(index);
+        self.note(BagEntry::Drawn { index, tile });
+        tile
     }
 
     // TODO: this doesn't stop us from returning tiles that weren't originally in the bag
     pub fn return_tile(&mut self, c: char) {
-        self.bag.push(c);
+        // Blanks lose their assigned letter when they leave the board
+        let tile = if is_placed_blank(c) { BLANK_TILE } else { c };
+        self.bag.push(tile);
+        self.note(BagEntry::Returned(tile));
     }
 
     fn fill(&mut self) {
         if let Some(letter_distribution) = self.letter_distribution {
+            let before = self.bag.len();
             self.bag.extend(
                 letter_distribution
                     .iter()
                     .enumerate()
                     .flat_map(|(letter, count)| [((letter as u8) + 65) as char].repeat(*count)),
             );
+            self.bag.extend([BLANK_TILE].repeat(self.blanks));
+            self.note(BagEntry::Filled(self.bag.len() - before));
         }
     }
+
+    fn note(&mut self, entry: BagEntry) {
+        if let Some(journal) = self.journal.as_mut() {
+            journal.push(entry);
+        }
+    }
+
+    /// Starts journaling every change to the bag, until `take_journal` is called
+    pub(crate) fn start_journal(&mut self) {
+        self.journal = Some(vec![]);
+    }
+
+    pub(crate) fn take_journal(&mut self) -> Vec<BagEntry> {
+        self.journal.take().unwrap_or_default()
+    }
+
+    /// Swaps the RNG's position with `state`, so that the bag draws from where `state` left off
+    pub(crate) fn exchange_rng_state(&mut self, state: &mut (u64, u64)) {
+        let current = self.rng.state();
+        self.rng = Rand32::from_state(*state);
+        *state = current;
+    }
+
+    pub(crate) fn rng_state(&self) -> (u64, u64) {
+        self.rng.state()
+    }
+
+    /// Reverses journaled changes, returning the tiles to the exact order they were in
+    pub(crate) fn revert(&mut self, entries: &[BagEntry]) {
+        for entry in entries.iter().rev() {
+            match *entry {
+                BagEntry::Drawn { index, tile } => {
+                    self.bag.push(tile);
+                    let last = self.bag.len() - 1;
+                    self.bag.swap(index, last);
+                }
+                BagEntry::Returned(_) => {
+                    self.bag.pop();
+                }
+                BagEntry::Filled(count) => {
+                    self.bag.truncate(self.bag.len() - count);
+                }
+            }
+        }
+    }
+
+    /// Plays journaled changes back in order, after they were reverted
+    pub(crate) fn reapply(&mut self, entries: &[BagEntry]) {
+        for entry in entries {
+            match *entry {
+                BagEntry::Drawn { index, .. } => {
+                    self.bag.swap_remove(index);
+                }
+                BagEntry::Returned(tile) => self.bag.push(tile),
+                BagEntry::Filled(_) => self.fill(),
+            }
+        }
+    }
+}
+
+/// Stores the RNG by its internal state, so that a restored bag
+/// continues drawing the same sequence of tiles
+mod rng_state {
+    use oorandom::Rand32;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(rng: &Rand32, serializer: S) -> Result<S::Ok, S::Error> {
+        rng.state().serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Rand32, D::Error> {
+        <(u64, u64)>::deserialize(deserializer).map(Rand32::from_state)
+    }
 }
 
 impl PartialEq for TileBag {
     fn eq(&self, rhs: &Self) -> bool {
-        self.bag == rhs.bag && self.letter_distribution == rhs.letter_distribution
+        self.bag == rhs.bag
+            && self.letter_distribution == rhs.letter_distribution
+            && self.blanks == rhs.blanks
     }
 }
 
@@ -188,6 +341,17 @@ impl fmt::Display for TileBag {
 pub mod tests {
     use super::*;
 
+    #[test]
+    fn non_ascii_tiles_return_as_themselves() {
+        let mut bag = TileBag::explicit(vec!['é', 'Ñ'], Some(1));
+        assert_eq!(bag.remaining(), &['É', 'Ñ']);
+
+        bag.return_tile('Ø');
+        bag.return_tile('ß');
+        bag.return_tile('e');
+        assert_eq!(bag.remaining(), &['É', 'Ñ', 'Ø', 'ß', BLANK_TILE]);
+    }
+
     #[test]
     fn refills() {
         let mut bag = a_b_bag();
@@ -196,6 +360,139 @@ pub mod tests {
         assert_eq!(drawn.filter(|&x| x == 'A').count(), 5);
     }
 
+    #[test]
+    fn rule_generations_pin_their_bags() {
+        use crate::rules::GameRules;
+
+        // The bag every game used before tile generations existed
+        let original = [
+            13, 3, 3, 6, 18, 3, 4, 3, 12, 2, 2, 5, 3, 8, 11, 3, 2, 9, 6, 9, 6, 3, 3, 2, 3, 2,
+        ];
+        assert_eq!(TileBag::letter_distribution(0), Some(original));
+        assert_eq!(
+            GameRules::generation(0).tile_bag(Some(1)),
+            TileBag::custom(original, Some(1))
+        );
+
+        let (latest_rules, _) = GameRules::latest(None);
+        let (latest_tiles, latest_bag) = TileBag::latest(Some(1));
+        assert_eq!(
+            GameRules::generation(latest_rules).tile_generation,
+            latest_tiles
+        );
+        assert_eq!(
+            GameRules::generation(latest_rules).tile_bag(Some(1)),
+            latest_bag
+        );
+    }
+
+    #[test]
+    fn blanks_are_opt_in() {
+        let plain = TileBag::generation(1, Some(12345));
+        let with_none = TileBag::generation(1, Some(12345)).with_blanks(0);
+        assert_eq!(plain, with_none);
+
+        let mut bag = a_b_bag().with_blanks(2);
+        let drawn: Vec<_> = (0..8).map(|_| bag.draw_tile()).collect();
+        assert_eq!(drawn.iter().filter(|t| **t == BLANK_TILE).count(), 4);
+
+        // Placed blanks come back as blanks, not as their assigned letter
+        bag.return_tile('e');
+        assert_eq!(bag.to_string(), "Letters in the bag:\n['?']");
+        bag.return_tile('é');
+        assert_eq!(bag.to_string(), "Letters in the bag:\n['?', '?']");
+    }
+
+    #[test]
+    fn journal_reverts_draws_exactly() {
+        let mut bag = TileBag::generation(1, Some(12345));
+        let before = bag.clone();
+        let mut rng_state = bag.rng_state();
+
+        bag.start_journal();
+        let drawn: Vec<_> = (0..7).map(|_| bag.draw_tile()).collect();
+        bag.return_tile(drawn[0]);
+        let entries = bag.take_journal();
+        let after = bag.clone();
+
+        bag.revert(&entries);
+        bag.exchange_rng_state(&mut rng_state);
+        assert_eq!(bag.remaining(), before.remaining());
+
+        // Reapplying lands on the same tiles and RNG position as the original draws
+        bag.reapply(&entries);
+        bag.exchange_rng_state(&mut rng_state);
+        assert_eq!(bag.remaining(), after.remaining());
+        let mut original = after;
+        assert_eq!(bag.draw_tile(), original.draw_tile());
+
+        // Refills are taken back along with the draws that triggered them
+        let mut bag = a_b_bag();
+        let before = bag.clone();
+        bag.start_journal();
+        for _ in 0..6 {
+            bag.draw_tile();
+        }
+        let entries = bag.take_journal();
+        assert_eq!(
+            entries
+                .iter()
+                .filter(|entry| **entry == BagEntry::Filled(2))
+                .count(),
+            2
+        );
+        bag.revert(&entries);
+        assert_eq!(bag.remaining(), before.remaining());
+    }
+
+    #[test]
+    fn reported_seed_reproduces_a_game() {
+        use crate::{game::Game, moves::Move, rules::GameRules};
+
+        let new_game = |seed| {
+            let mut game = Game::new(9, 9, seed, GameRules::generation(1));
+            game.add_player("A".into());
+            game.add_player("B".into());
+            game.start();
+            game
+        };
+
+        let mut reported = new_game(None);
+        let mut replayed = new_game(Some(reported.tile_seed()));
+        assert_eq!(reported.tile_seed(), replayed.tile_seed());
+        assert_eq!(reported.bag.remaining(), replayed.bag.remaining());
+
+        for _ in 0..200 {
+            if reported.winner.is_some() {
+                break;
+            }
+            let player = reported.next_player.unwrap();
+            let Some(position) = reported
+                .board
+                .playable_positions(player, &reported.rules.truncation)
+                .into_iter()
+                .min()
+            else {
+                break;
+            };
+            let next_move = Move::Place {
+                player,
+                tile: reported.players[player].hand.0[0],
+                position,
+            };
+
+            assert_eq!(
+                reported.play_turn(next_move.clone(), None, None, None),
+                replayed.play_turn(next_move, None, None, None)
+            );
+            for (reported_player, replayed_player) in reported.players.iter().zip(&replayed.players)
+            {
+                assert_eq!(reported_player.hand, replayed_player.hand);
+            }
+            assert_eq!(reported.bag.remaining(), replayed.bag.remaining());
+        }
+    }
+
     // Util functions
     pub fn a_b_bag() -> TileBag {
         let mut dist = [0; 26];
@@ -8,12 +8,19 @@ use std::slice::Iter;
 
 use super::reporting::{BoardChange, BoardChangeAction, BoardChangeDetail};
 use crate::bag::TileBag;
-use crate::error::GamePlayError;
+use crate::error::{BoardParseError, GamePlayError};
 use crate::judge::WordDict;
 use crate::reporting::Change;
-use crate::rules::{ArtifactDefense, GameRules, WinCondition};
+use crate::rules::{ArtifactDefense, GameRules, TownsToDefeat, WinCondition};
 use crate::{player, rules};
 
+/// How far players can see from their own artifacts and towns under fog of war
+const HOME_VISION_DISTANCE: usize = 6;
+
+/// The most squares a board can span in either direction, unless configured otherwise.
+/// Larger boards are refused, as they are too costly to generate and render.
+pub const MAX_BOARD_DIMENSION: usize = 64;
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Direction {
     NorthWest,
@@ -60,14 +67,77 @@ struct RedundantEdges {
     left: usize,
 }
 
-#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+/// A Zobrist-style hash of which squares hold tiles, covering the same information as `get_shape`.
+/// Every square has a fixed key, so placing or removing a tile XORs a single key in or out.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+struct ShapeHash(u64);
+
+/// splitmix64 of the square's index, so that neighbouring squares get unrelated keys
+fn shape_key(position: Coordinate, width: usize) -> u64 {
+    let mut z = (position.to_1d(width) as u64)
+        .wrapping_add(1)
+        .wrapping_mul(0x9E3779B97F4A7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+    z ^ (z >> 31)
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(from = "SerializedBoard")]
 pub struct Board {
     pub squares: Vec<Vec<Square>>,
     pub artifacts: Vec<Coordinate>,
     pub towns: Vec<Coordinate>,
     pub obelisks: Vec<Coordinate>,
     orientations: Vec<Direction>, // The side of the board that the player is sitting at, and the direction that their vertical words go in
-                                  // TODO: Move orientations off the Board and have them tagged against specific players
+    // TODO: Move orientations off the Board and have them tagged against specific players
+    /// Kept up to date by `set`, `clear` and `set_square`.
+    /// Writing to `squares` directly needs a `cache_special_squares` afterwards.
+    #[serde(skip)]
+    shape_hash: ShapeHash,
+    /// The previous value of every square written to, only kept while a turn is being recorded for undo
+    #[serde(skip)]
+    journal: Option<Vec<(Coordinate, Square)>>,
+}
+
+// Boards are equal when their squares and layout are, ignoring the shape hash derived from them
+// and any journal of the turn being recorded
+impl PartialEq for Board {
+    fn eq(&self, other: &Self) -> bool {
+        self.squares == other.squares
+            && self.artifacts == other.artifacts
+            && self.towns == other.towns
+            && self.obelisks == other.obelisks
+            && self.orientations == other.orientations
+    }
+}
+
+impl Eq for Board {}
+
+/// A `Board` as stored, without anything that is recomputed from its squares
+#[derive(Deserialize)]
+struct SerializedBoard {
+    squares: Vec<Vec<Square>>,
+    artifacts: Vec<Coordinate>,
+    towns: Vec<Coordinate>,
+    obelisks: Vec<Coordinate>,
+    orientations: Vec<Direction>,
+}
+
+impl From<SerializedBoard> for Board {
+    fn from(stored: SerializedBoard) -> Self {
+        let mut board = Board {
+            squares: stored.squares,
+            artifacts: stored.artifacts,
+            towns: stored.towns,
+            obelisks: stored.obelisks,
+            orientations: stored.orientations,
+            shape_hash: ShapeHash::default(),
+            journal: None,
+        };
+        board.shape_hash = ShapeHash(board.computed_shape_hash());
+        board
+    }
 }
 
 // TODO: provide a way to validate the board
@@ -96,6 +166,8 @@ impl Board {
             towns: vec![],
             obelisks: vec![],
             orientations: vec![Direction::North, Direction::South],
+            shape_hash: ShapeHash::default(),
+            journal: None,
         };
 
         let north_towns = [
@@ -151,6 +223,8 @@ impl Board {
             towns: vec![],
             obelisks: vec![],
             orientations: vec![Direction::North, Direction::South],
+            shape_hash: ShapeHash::default(),
+            journal: None,
         };
 
         let artifact_x = board_width / 2;
@@ -205,6 +279,18 @@ impl Board {
         &self.orientations
     }
 
+    /// Players beyond those with a stored orientation are seated around the remaining sides
+    pub fn orientation(&self, player: usize) -> Direction {
+        self.orientations.get(player).copied().unwrap_or_else(|| {
+            [
+                Direction::North,
+                Direction::South,
+                Direction::East,
+                Direction::West,
+            ][player % 4]
+        })
+    }
+
     pub fn land_width(&self) -> usize {
         unimplemented!("Need to calculate the playable dimensions")
     }
@@ -221,6 +307,23 @@ impl Board {
         self.squares.len()
     }
 
+    /// Rejects boards wider or taller than `max_dimension` squares.
+    /// Safe to call on boards from players, which may be empty or have ragged rows.
+    pub fn check_dimensions(&self, max_dimension: usize) -> Result<(), BoardParseError> {
+        let width = self.squares.iter().map(|row| row.len()).max().unwrap_or(0);
+        let height = self.squares.len();
+
+        if width > max_dimension || height > max_dimension {
+            return Err(BoardParseError::TooLarge {
+                width,
+                height,
+                max: max_dimension,
+            });
+        }
+
+        Ok(())
+    }
+
     pub fn towns(&self) -> Iter<Coordinate> {
         self.towns.iter()
     }
@@ -316,15 +419,13 @@ impl Board {
 
         self.artifacts.clear();
         self.towns.clear();
+        self.obelisks.clear();
+        self.shape_hash = ShapeHash::default();
 
         for coord in coords {
             match self.get(coord) {
-                Ok(
-                    Square::Water { .. }
-                    | Square::Land { .. }
-                    | Square::Occupied { .. }
-                    | Square::Fog { .. },
-                ) => {}
+                Ok(Square::Occupied { .. }) => self.toggle_shape(coord),
+                Ok(Square::Water { .. } | Square::Land { .. } | Square::Fog { .. }) => {}
                 Ok(Square::Obelisk { .. }) => self.obelisks.push(coord),
                 Ok(Square::Town { .. }) => self.towns.push(coord),
                 Ok(Square::Artifact { .. }) => self.artifacts.push(coord),
@@ -376,39 +477,78 @@ impl Board {
             return Err(GamePlayError::OutSideBoardDimensions { position });
         };
 
+        let previous = *square;
         *square = new_square;
+        if matches!(previous, Square::Occupied { .. })
+            != matches!(new_square, Square::Occupied { .. })
+        {
+            self.toggle_shape(position);
+        }
+        self.note(position, previous);
 
         Ok(())
     }
 
-    pub fn set(
-        &mut self,
-        position: Coordinate,
-        player: usize,
-        tile: char,
-        ref_dict: Option<&WordDict>,
-    ) -> Result<BoardChangeDetail, GamePlayError> {
+    /// Every square that differs from an earlier version of this board,
+    /// or `None` if the board has since changed size.
+    pub fn changed_squares(&self, previous: &Board) -> Option<Vec<(Coordinate, Square)>> {
+        if self.width() != previous.width() || self.height() != previous.height() {
+            return None;
+        }
+
+        Some(
+            self.squares
+                .iter()
+                .zip(previous.squares.iter())
+                .enumerate()
+                .flat_map(|(y, (row, previous_row))| {
+                    row.iter()
+                        .zip(previous_row.iter())
+                        .enumerate()
+                        .filter(|(_, (square, previous_square))| square != previous_square)
+                        .map(move |(x, (square, _))| (Coordinate { x, y }, *square))
+                })
+                .collect(),
+        )
+    }
+
+    /// Checks that `set` would succeed, without modifying the board
+    pub fn validate_set(&self, position: Coordinate, player: usize) -> Result<(), GamePlayError> {
         if self.artifacts.get(player).is_none() {
             return Err(GamePlayError::NonExistentPlayer { index: player });
         }
 
         match self
             .squares
-            .get_mut(position.y)
-            .and_then(|row| row.get_mut(position.x))
+            .get(position.y)
+            .and_then(|row| row.get(position.x))
         {
-            Some(square) if matches!(square, Square::Land { .. } | Square::Occupied { .. }) => {
-                *square = Square::Occupied {
-                    player,
-                    tile,
-                    validity: SquareValidity::Unknown,
-                    foggy: false,
-                };
-                Ok(())
-            }
+            Some(Square::Land { .. } | Square::Occupied { .. }) => Ok(()),
             Some(_) => Err(GamePlayError::InvalidPosition { position }),
             None => Err(GamePlayError::OutSideBoardDimensions { position }),
-        }?;
+        }
+    }
+
+    pub fn set(
+        &mut self,
+        position: Coordinate,
+        player: usize,
+        tile: char,
+        ref_dict: Option<&WordDict>,
+    ) -> Result<BoardChangeDetail, GamePlayError> {
+        self.validate_set(position, player)?;
+
+        let previous = self.squares[position.y][position.x];
+        if !matches!(previous, Square::Occupied { .. }) {
+            self.toggle_shape(position);
+        }
+        self.note(position, previous);
+        self.squares[position.y][position.x] = Square::Occupied {
+            player,
+            tile,
+            validity: SquareValidity::Unknown,
+            foggy: false,
+        };
 
         self.mark_validity(position, ref_dict);
 
@@ -425,6 +565,48 @@ impl Board {
         swap_rules: &rules::Swapping,
         ref_dict: Option<&WordDict>,
     ) -> Result<Vec<Change>, GamePlayError> {
+        let tiles = self.validate_swap(player, positions, swap_rules)?;
+
+        Ok(vec![
+            Change::Board(BoardChange {
+                detail: self.set(positions[0], player, tiles[1], ref_dict)?,
+                action: BoardChangeAction::Swapped,
+            }),
+            Change::Board(BoardChange {
+                detail: self.set(positions[1], player, tiles[0], ref_dict)?,
+                action: BoardChangeAction::Swapped,
+            }),
+        ])
+    }
+
+    /// Whether both positions sit on one unbroken row or column of the player's tiles
+    fn in_same_word(&self, player: usize, positions: [Coordinate; 2]) -> bool {
+        let [a, b] = positions;
+        let line: Vec<_> = if a.x == b.x {
+            (a.y.min(b.y)..=a.y.max(b.y))
+                .map(|y| Coordinate::new(a.x, y))
+                .collect()
+        } else if a.y == b.y {
+            (a.x.min(b.x)..=a.x.max(b.x))
+                .map(|x| Coordinate::new(x, a.y))
+                .collect()
+        } else {
+            return false;
+        };
+
+        line.into_iter().all(|coord| {
+            matches!(self.get(coord), Ok(Square::Occupied { player: owner, .. }) if owner == player)
+        })
+    }
+
+    /// Checks that `swap` would succeed without modifying the board,
+    /// returning the tiles that would be exchanged
+    pub fn validate_swap(
+        &self,
+        player: usize,
+        positions: [Coordinate; 2],
+        swap_rules: &rules::Swapping,
+    ) -> Result<[char; 2], GamePlayError> {
         if positions[0] == positions[1] {
             return Err(GamePlayError::SelfSwap);
         }
@@ -468,21 +650,21 @@ impl Board {
                 }
             }
             rules::Swapping::Universal(_) => { /* All swaps are allowed */ }
+            rules::Swapping::WithinWord(_) => {
+                if !self.in_same_word(player, positions) {
+                    return Err(GamePlayError::SwapOutsideWord);
+                }
+            }
             rules::Swapping::None => {
                 return Err(GamePlayError::NoSwapping);
             }
         }
 
-        Ok(vec![
-            Change::Board(BoardChange {
-                detail: self.set(positions[0], player, tiles[1], ref_dict)?,
-                action: BoardChangeAction::Swapped,
-            }),
-            Change::Board(BoardChange {
-                detail: self.set(positions[1], player, tiles[0], ref_dict)?,
-                action: BoardChangeAction::Swapped,
-            }),
-        ])
+        for position in positions {
+            self.validate_set(position, player)?;
+        }
+
+        Ok(tiles)
     }
 
     // TODO: safety on index access like get and set - ideally combine error checking for all 3
@@ -497,18 +679,20 @@ impl Board {
             .and_then(|y| y.get_mut(position.x as usize))
         {
             if matches!(square, Square::Occupied { .. }) {
-                let change = Some(BoardChangeDetail {
-                    square: *square,
-                    coordinate: position,
-                });
+                let previous = *square;
                 *square = Square::land();
+                self.toggle_shape(position);
+                self.note(position, previous);
 
                 self.neighbouring_squares(position)
                     .into_iter()
                     .filter(|(_, s)| matches!(s, Square::Occupied { .. }))
                     .for_each(|(c, _)| self.mark_validity(c, ref_dict));
 
-                return change;
+                return Some(BoardChangeDetail {
+                    square: previous,
+                    coordinate: position,
+                });
             }
         }
         None
@@ -535,14 +719,26 @@ impl Board {
                         foggy: false,
                     }
                 }
+                Square::Artifact { player, .. } => {
+                    *sq = Square::Artifact {
+                        player: player.clone(),
+                        defeated: false,
+                        foggy: false,
+                    }
+                }
                 _ => {}
             }
         }
+        // Every tile has been removed
+        self.shape_hash = ShapeHash::default();
     }
 
     pub fn defeat_player(&mut self, player_to_defeat: usize) {
         let towns = self.towns.clone();
         for town in towns {
+            if let Ok(previous) = self.get(town) {
+                self.note(town, previous);
+            }
             let Ok(sq) = self.get_mut(town) else {
                 continue;
             };
@@ -557,6 +753,105 @@ impl Board {
                 _ => {}
             }
         }
+
+        // Boards without towns rely on the artifact to mark the player as defeated
+        let artifacts = self.artifacts.clone();
+        for artifact in artifacts {
+            if let Ok(previous) = self.get(artifact) {
+                self.note(artifact, previous);
+            }
+            let Ok(sq) = self.get_mut(artifact) else {
+                continue;
+            };
+            match sq {
+                Square::Artifact { player, .. } if *player == player_to_defeat => {
+                    *sq = Square::Artifact {
+                        player: player_to_defeat,
+                        defeated: true,
+                        foggy: false,
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// A player is out of the game once any of their towns or artifacts have been defeated
+    pub fn player_is_defeated(&self, player_index: usize) -> bool {
+        self.towns
+            .iter()
+            .chain(self.artifacts.iter())
+            .any(|coord| match self.get(*coord) {
+                Ok(Square::Town {
+                    player, defeated, ..
+                })
+                | Ok(Square::Artifact {
+                    player, defeated, ..
+                }) => player == player_index && defeated,
+                _ => false,
+            })
+    }
+
+    /// Whether the player has lost under the given win condition.
+    /// Since nobody has tiles before their first turn,
+    /// elimination only applies to players who have played.
+    pub fn player_is_defeated_by(
+        &self,
+        player_index: usize,
+        win_condition: &WinCondition,
+        has_played: bool,
+    ) -> bool {
+        match win_condition {
+            WinCondition::Destination {
+                towns_to_defeat: TownsToDefeat::Any,
+                ..
+            } => self.player_is_defeated(player_index),
+            WinCondition::Destination {
+                towns_to_defeat: TownsToDefeat::All,
+                ..
+            } => {
+                let artifact_defeated = self.artifacts.iter().any(|coord| {
+                    matches!(
+                        self.get(*coord),
+                        Ok(Square::Artifact { player, defeated: true, .. }) if player == player_index
+                    )
+                });
+                let towns: Vec<_> = self
+                    .towns
+                    .iter()
+                    .filter_map(|coord| match self.get(*coord) {
+                        Ok(Square::Town {
+                            player, defeated, ..
+                        }) if player == player_index => Some(defeated),
+                        _ => None,
+                    })
+                    .collect();
+
+                artifact_defeated || (!towns.is_empty() && towns.iter().all(|defeated| *defeated))
+            }
+            WinCondition::Elimination => has_played
+                && !self.squares.iter().flatten().any(
+                    |sq| matches!(sq, Square::Occupied { player, .. } if *player == player_index),
+                ),
+        }
+    }
+
+    /// All players who have a town or artifact on this board
+    pub fn players(&self) -> Vec<usize> {
+        let mut players: Vec<_> = self
+            .towns
+            .iter()
+            .chain(self.artifacts.iter())
+            .filter_map(|coord| match self.get(*coord) {
+                Ok(Square::Town { player, .. }) | Ok(Square::Artifact { player, .. }) => {
+                    Some(player)
+                }
+                _ => None,
+            })
+            .collect();
+        players.sort();
+        players.dedup();
+        players
     }
 
     pub fn neighbouring_squares(&self, position: Coordinate) -> Vec<(Coordinate, Square)> {
@@ -572,12 +867,123 @@ impl Board {
             .collect()
     }
 
+    /// The opponents' tiles that `player` would explode by winning an attack from `origin`,
+    /// nearest first. Squares are caught in the blast whatever lies between them and `origin`.
+    pub fn explosion_area(
+        &self,
+        origin: Coordinate,
+        player: usize,
+        explosions: &rules::ExplosionRules,
+    ) -> Vec<Coordinate> {
+        let is_opponent_tile = |pos: Coordinate| matches!(self.get(pos), Ok(Square::Occupied { player: owner, .. }) if owner != player);
+
+        let mut visited = HashSet::from([origin]);
+        let mut blast = VecDeque::from([(origin, 0)]);
+        let mut exploded = vec![];
+
+        while let Some((pos, steps)) = blast.pop_front() {
+            if steps == explosions.radius {
+                continue;
+            }
+            for neighbour in pos.neighbors_4_iter() {
+                if self.get(neighbour).is_err() || !visited.insert(neighbour) {
+                    continue;
+                }
+                if is_opponent_tile(neighbour) {
+                    exploded.push(neighbour);
+                }
+                blast.push_back((neighbour, steps + 1));
+            }
+        }
+
+        if explosions.chain_reaction {
+            let mut chained = 0;
+            while let Some(pos) = exploded.get(chained).copied() {
+                chained += 1;
+                for neighbour in pos.neighbors_4_iter() {
+                    if is_opponent_tile(neighbour) && visited.insert(neighbour) {
+                        exploded.push(neighbour);
+                    }
+                }
+            }
+        }
+
+        exploded
+    }
+
     pub fn reciprocal_coordinate(&self, input: Coordinate) -> Coordinate {
         Coordinate {
             x: self.width() - 1 - input.x,
             y: self.height() - 1 - input.y,
         }
     }
+
+    /// Whether the board is point-symmetric about its center,
+    /// with each player's squares mirrored by their opponent's.
+    pub fn is_symmetric(&self) -> bool {
+        self.squares.iter().enumerate().all(|(y, row)| {
+            row.iter().enumerate().all(|(x, square)| {
+                let recip = self.reciprocal_coordinate(Coordinate { x, y });
+                let Ok(other) = self.get(recip) else {
+                    return false;
+                };
+
+                Self::mirrored_square(*square) == other
+            })
+        })
+    }
+
+    /// Turns the board half a turn about its center, handing each square to the opponent
+    /// of its owner so that both players keep their side of the board.
+    pub fn mirror(&mut self) {
+        self.squares.reverse();
+        for row in self.squares.iter_mut() {
+            row.reverse();
+            for square in row.iter_mut() {
+                *square = Self::mirrored_square(*square);
+            }
+        }
+
+        self.cache_special_squares();
+    }
+
+    /// The square as its owner's opponent would hold it, in a two player game
+    fn mirrored_square(square: Square) -> Square {
+        let opponent = |player: usize| if player == 0 { 1 } else { 0 };
+
+        match square {
+            Square::Town {
+                player,
+                defeated,
+                foggy,
+            } => Square::Town {
+                player: opponent(player),
+                defeated,
+                foggy,
+            },
+            Square::Artifact {
+                player,
+                defeated,
+                foggy,
+            } => Square::Artifact {
+                player: opponent(player),
+                defeated,
+                foggy,
+            },
+            Square::Occupied {
+                player,
+                tile,
+                validity,
+                foggy,
+            } => Square::Occupied {
+                player: opponent(player),
+                tile,
+                validity,
+                foggy,
+            },
+            sq => sq,
+        }
+    }
 }
 
 impl Board {
@@ -603,7 +1009,7 @@ impl Board {
 
         for (coords, word) in coords.into_iter().zip(words.into_iter()) {
             // TODO: Use the full judge here to handle, e.g., wildcards
-            let main_word_valid = ref_dict.contains_key(&word.to_ascii_lowercase());
+            let main_word_valid = ref_dict.contains_key(&word.to_lowercase());
             let ideal_validity = if main_word_valid {
                 SquareValidity::Valid
             } else {
@@ -621,7 +1027,7 @@ impl Board {
                     };
                     let valid_words: Vec<_> = words
                         .into_iter()
-                        .map(|w| ref_dict.contains_key(&w.to_ascii_lowercase()))
+                        .map(|w| ref_dict.contains_key(&w.to_lowercase()))
                         .collect();
                     if main_word_valid && valid_words.contains(&false) {
                         square_validity = SquareValidity::Partial;
@@ -631,6 +1037,11 @@ impl Board {
                     }
                 }
 
+                if let Ok(previous @ Square::Occupied { validity, .. }) = self.get(coord) {
+                    if validity != square_validity {
+                        self.note(coord, previous);
+                    }
+                }
                 match self.get_mut(coord) {
                     Ok(Square::Occupied { validity, .. }) => *validity = square_validity,
                     _ => {}
@@ -816,6 +1227,7 @@ impl Board {
         distances
     }
 
+    /// Distances the attacker's outermost tile can reach across the board
     pub fn flood_fill_attacks(&self, attacker: usize) -> BoardDistances {
         let pos_is_attacker = |pos: &Coordinate| match self.get(*pos) {
             Ok(Square::Occupied { player, .. }) if player == attacker => true,
@@ -899,6 +1311,7 @@ impl Board {
         distances
     }
 
+    /// Distances of each water square from the nearest land
     pub fn flood_fill_water_from_land(&self) -> BoardDistances {
         let mut distances = BoardDistances::new(self);
 
@@ -949,6 +1362,58 @@ impl Board {
         distances
     }
 
+    /// Distances from every square to the given target.
+    ///
+    /// Direct distances ignore tiles and only route around water,
+    /// whereas attackable distances only travel across open land.
+    /// Squares hidden by fog of war are never traversed, and foggy squares
+    /// only count for direct distances since their contents are stale,
+    /// so this should be called on the board as the player sees it.
+    pub fn distance_field_to(&self, target: Coordinate) -> BoardDistances {
+        let mut distances = BoardDistances::new(self);
+
+        if self.get(target).is_err() {
+            return distances;
+        }
+
+        let is_open_land = |sq: &Square| matches!(sq, Square::Land { foggy: false });
+
+        distances.set_direct(&target, 0);
+        distances.set_attackable(&target, 0);
+        let mut pts: VecDeque<_> = VecDeque::from([(target, 0, true)]);
+
+        while let Some((pt, dist, attackable)) = pts.pop_front() {
+            for (neighbor, square) in self.neighbouring_squares(pt) {
+                let passable = !matches!(square, Square::Water { .. } | Square::Fog {});
+                if !passable {
+                    continue;
+                }
+
+                let reaches_attackable = attackable
+                    && distances.attackable_distance(&neighbor).is_none()
+                    && !square.is_foggy();
+                if reaches_attackable {
+                    distances.set_attackable(&neighbor, dist + 1);
+                }
+
+                let reaches_direct = distances.direct_distance(&neighbor).is_none();
+                if reaches_direct {
+                    distances.set_direct(&neighbor, dist + 1);
+                }
+
+                if reaches_direct || reaches_attackable {
+                    pts.push_back((
+                        neighbor,
+                        dist + 1,
+                        reaches_attackable && is_open_land(&square),
+                    ));
+                }
+            }
+        }
+
+        distances
+    }
+
     /// Find the shortest land path between any two points on a board.
     /// Does NOT take into account tiles defended by either player,
     /// so isn't strictly correct once gameplay has begun.
@@ -1042,7 +1507,17 @@ impl Board {
     }
 
     pub fn proximity_to_enemy_town(&self, player_index: usize) -> Vec<usize> {
-        let distances = self.flood_fill_from_towns((player_index + 1) % 2);
+        let enemy_distances: Vec<_> = self
+            .players()
+            .into_iter()
+            .filter(|p| *p != player_index)
+            .filter(|p| {
+                self.towns.iter().any(
+                    |t| matches!(self.get(*t), Ok(Square::Town { player, .. }) if player == *p),
+                )
+            })
+            .map(|p| self.flood_fill_from_towns(p))
+            .collect();
 
         let rows = self.height();
         let cols = self.width();
@@ -1052,7 +1527,10 @@ impl Board {
             .flat_map(|(x, y)| {
                 let c = Coordinate { x, y };
                 if matches!(self.get(c), Ok(Square::Occupied{ player, .. }) if player == player_index) {
-                    distances.direct_distance(&c)
+                    enemy_distances
+                        .iter()
+                        .filter_map(|distances| distances.direct_distance(&c))
+                        .min()
                 } else {
                     None
                 }
@@ -1117,6 +1595,56 @@ impl Board {
         out
     }
 
+    /// A hash of `get_shape`, maintained as tiles are placed and removed
+    /// so that it costs nothing to read
+    pub fn shape_hash(&self) -> u64 {
+        self.shape_hash.0
+    }
+
+    fn toggle_shape(&mut self, position: Coordinate) {
+        self.shape_hash.0 ^= shape_key(position, self.width());
+    }
+
+    fn note(&mut self, position: Coordinate, previous: Square) {
+        if let Some(journal) = self.journal.as_mut() {
+            journal.push((position, previous));
+        }
+    }
+
+    /// Starts journaling the previous value of every square written to,
+    /// until `take_journal` is called
+    pub(crate) fn start_journal(&mut self) {
+        self.journal = Some(vec![]);
+    }
+
+    /// The value each written square held when journaling started
+    pub(crate) fn take_journal(&mut self) -> Vec<(Coordinate, Square)> {
+        let mut previous: Vec<(Coordinate, Square)> = vec![];
+        for (coordinate, square) in self.journal.take().unwrap_or_default() {
+            if previous.iter().all(|(c, _)| *c != coordinate) {
+                previous.push((coordinate, square));
+            }
+        }
+        // Squares that were written back to their original value didn't change
+        previous.retain(|(coordinate, square)| self.get(*coordinate).ok() != Some(*square));
+        previous
+    }
+
+    /// Recomputes `shape_hash` from scratch
+    fn computed_shape_hash(&self) -> u64 {
+        let width = self.squares.first().map_or(0, Vec::len);
+        self.squares
+            .iter()
+            .enumerate()
+            .flat_map(|(y, row)| {
+                row.iter()
+                    .enumerate()
+                    .filter(|(_, square)| matches!(square, Square::Occupied { .. }))
+                    .map(move |(x, _)| shape_key(Coordinate { x, y }, width))
+            })
+            .fold(0, |hash, key| hash ^ key)
+    }
+
     pub fn get_words(&self, position: Coordinate) -> Vec<Vec<Coordinate>> {
         let mut words: Vec<Vec<Coordinate>> = Vec::new();
         let owner = match self.get(position) {
@@ -1160,7 +1688,7 @@ impl Board {
         }
 
         // Reverse words based on the player's orientation
-        let orientation = self.orientations[owner];
+        let orientation = self.orientation(owner);
         if !orientation.read_top_to_bottom() {
             words[0].reverse();
         }
@@ -1190,6 +1718,7 @@ impl Board {
                 ..
             }
         );
+        let towns_are_combatants = matches!(rules.win_condition, WinCondition::Destination { .. });
         // Any neighbouring square belonging to another player is attacked. The words containing those squares are the defenders.
         let defenders = self
             .neighbouring_squares(position)
@@ -1208,7 +1737,7 @@ impl Board {
                     player: adjacent_player,
                     defeated,
                     ..
-                } => player != *adjacent_player && !defeated,
+                } => towns_are_combatants && player != *adjacent_player && !defeated,
                 _ => false,
             })
             .flat_map(|(position, _)| self.get_words(*position))
@@ -1216,6 +1745,16 @@ impl Board {
         (attackers, defenders)
     }
 
+    /// The player owning the first square of a word, whether that's a tile, town, or artifact
+    pub fn word_owner(&self, word: &[Coordinate]) -> Option<usize> {
+        match self.get(*word.first()?) {
+            Ok(Square::Occupied { player, .. })
+            | Ok(Square::Town { player, .. })
+            | Ok(Square::Artifact { player, .. }) => Some(player),
+            _ => None,
+        }
+    }
+
     pub fn word_strings(
         &self,
         coordinates: &Vec<Vec<Coordinate>>,
@@ -1253,6 +1792,83 @@ impl Board {
         }
     }
 
+    /// The words the tile at `position` is part of, in reading order, alongside their letters.
+    /// Words are cut short at squares hidden by fog of war so that they don't reveal any tiles
+    /// the viewing player can't see.
+    pub fn words_at(&self, position: Coordinate) -> Vec<(Vec<Coordinate>, String)> {
+        if !matches!(
+            self.get(position),
+            Ok(Square::Occupied { foggy: false, .. })
+        ) {
+            return vec![];
+        }
+
+        let hidden = |c: &Coordinate| self.get(*c).map_or(true, |sq| sq.is_foggy());
+        let mut words: Vec<Vec<Coordinate>> = self
+            .get_words(position)
+            .into_iter()
+            .filter_map(|word| {
+                let at = word.iter().position(|c| *c == position)?;
+                let start = word[..at].iter().rposition(hidden).map_or(0, |i| i + 1);
+                let end = word[at..]
+                    .iter()
+                    .position(hidden)
+                    .map_or(word.len(), |i| at + i);
+                Some(word[start..end].to_vec())
+            })
+            .collect();
+
+        // A lone tile is the same single letter word along both axes
+        words.dedup();
+        if words.iter().any(|w| w.len() > 1) {
+            words.retain(|w| w.len() > 1);
+        }
+
+        let Ok(strings) = self.word_strings(&words) else {
+            return vec![];
+        };
+        words.into_iter().zip(strings).collect()
+    }
+
+    /// Whether the player has any tiles on the board
+    pub fn has_tiles(&self, player: usize) -> bool {
+        self.squares
+            .iter()
+            .flatten()
+            .any(|sq| matches!(sq, Square::Occupied { player: p, .. } if *p == player))
+    }
+
+    /// Whether `position` is land closer to one of the player's artifacts than to any opponent's
+    pub fn is_own_land(&self, player: usize, position: Coordinate) -> bool {
+        if !matches!(self.get(position), Ok(Square::Land { .. })) {
+            return false;
+        }
+
+        let nearest = |own: bool| {
+            self.artifacts
+                .iter()
+                .filter(|artifact| {
+                    matches!(self.get(**artifact), Ok(Square::Artifact { player: p, .. }) if (p == player) == own)
+                })
+                .map(|artifact| artifact.distance_to(&position))
+                .min()
+        };
+
+        match (nearest(true), nearest(false)) {
+            (Some(own), Some(opponent)) => own < opponent,
+            (Some(_), None) => true,
+            (None, _) => false,
+        }
+    }
+
+    /// Every square the player can claim with `is_own_land`
+    pub fn own_land(&self, player: usize) -> HashSet<Coordinate> {
+        (0..self.height())
+            .flat_map(|y| (0..self.width()).map(move |x| Coordinate { x, y }))
+            .filter(|c| self.is_own_land(player, *c))
+            .collect()
+    }
+
     pub fn playable_positions(
         &self,
         for_player: usize,
@@ -1306,8 +1922,13 @@ impl Board {
         &self,
         player_index: usize,
         visibility: &rules::Visibility,
+        fog_level: &rules::FogLevel,
         seen_tiles: &HashSet<Coordinate>,
     ) -> Self {
+        if matches!(fog_level, rules::FogLevel::Full) {
+            return self.clone();
+        }
+
         let mut visible_coords: HashSet<Coordinate> = HashSet::new();
         let mut all_towns: HashSet<Coordinate> = HashSet::new();
 
@@ -1326,10 +1947,15 @@ impl Board {
                 Ok(Square::Artifact { player, .. }) | Ok(Square::Town { player, .. })
                     if player == player_index =>
                 {
+                    let vision_dist = match fog_level {
+                        rules::FogLevel::BeyondDistance(distance) => *distance,
+                        _ => HOME_VISION_DISTANCE,
+                    };
+
                     let mut sqs = HashSet::new();
                     sqs.insert(coord);
 
-                    for _ in 0..6 {
+                    for _ in 0..vision_dist {
                         let pts = sqs.iter().cloned().collect::<Vec<_>>();
                         for pt in pts {
                             sqs.extend(pt.neighbors_4_iter());
@@ -1369,10 +1995,10 @@ impl Board {
                         })
                         .max_by_key(|w| w.len());
 
-                    let vision_dist = if let Some(valid) = valid {
-                        valid.len().saturating_sub(4) + 3
-                    } else {
-                        2
+                    let vision_dist = match (fog_level, valid) {
+                        (rules::FogLevel::BeyondDistance(distance), _) => *distance,
+                        (_, Some(valid)) => valid.len().saturating_sub(4) + 3,
+                        (_, None) => 2,
                     };
 
                     let mut sqs = HashSet::new();
@@ -1400,6 +2026,23 @@ impl Board {
                 }
                 _ => {}
             }
+
+            // Every player's home is in plain sight, leaving only the arena between them fogged
+            if matches!(fog_level, rules::FogLevel::BattleArena)
+                && matches!(square, Ok(Square::Artifact { .. }))
+            {
+                let mut sqs = HashSet::new();
+                sqs.insert(coord);
+
+                for _ in 0..HOME_VISION_DISTANCE {
+                    let pts = sqs.iter().cloned().collect::<Vec<_>>();
+                    for pt in pts {
+                        sqs.extend(pt.neighbors_4_iter());
+                    }
+                }
+
+                visible_coords.extend(sqs);
+            }
         }
 
         let mut new_board = self.clone();
@@ -1430,9 +2073,14 @@ impl Board {
                     if !visible_coords.contains(&c) {
                         if seen_tiles.contains(&c) {
                             let make_land = match &mut new_board.squares[y][x] {
+                                Square::Obelisk { foggy, holder } => {
+                                    // Who holds it may have changed since it was last seen
+                                    *foggy = true;
+                                    *holder = None;
+                                    false
+                                }
                                 Square::Water { foggy }
                                 | Square::Land { foggy }
-                                | Square::Obelisk { foggy }
                                 | Square::Town { foggy, .. }
                                 | Square::Artifact { foggy, .. } => {
                                     *foggy = true;
@@ -1463,6 +2111,7 @@ impl Board {
         player_index: usize,
         player_coordinate: Coordinate,
         visibility: &rules::Visibility,
+        fog_level: &rules::FogLevel,
         seen_tiles: &HashSet<Coordinate>,
     ) -> Coordinate {
         let foggy_board = match visibility {
@@ -1471,7 +2120,7 @@ impl Board {
                 return player_coordinate;
             }
             rules::Visibility::LandFog | rules::Visibility::OnlyHouseFog => {
-                self.fog_of_war(player_index, visibility, seen_tiles)
+                self.fog_of_war(player_index, visibility, fog_level, seen_tiles)
             }
         };
 
@@ -1491,6 +2140,7 @@ impl Board {
         player_index: usize,
         game_coordinate: Coordinate,
         visibility: &rules::Visibility,
+        fog_level: &rules::FogLevel,
         seen_tiles: &HashSet<Coordinate>,
     ) -> Option<Coordinate> {
         let foggy_board = match visibility {
@@ -1499,7 +2149,7 @@ impl Board {
                 return Some(game_coordinate);
             }
             rules::Visibility::LandFog | rules::Visibility::OnlyHouseFog => {
-                self.fog_of_war(player_index, visibility, seen_tiles)
+                self.fog_of_war(player_index, visibility, fog_level, seen_tiles)
             }
         };
 
@@ -1526,6 +2176,7 @@ impl Board {
         &self,
         player_index: usize,
         visibility: &rules::Visibility,
+        fog_level: &rules::FogLevel,
         winner: &Option<usize>,
         seen_tiles: &HashSet<Coordinate>,
         trim_coords: bool,
@@ -1540,7 +2191,7 @@ impl Board {
             rules::Visibility::TileFog
             | rules::Visibility::LandFog
             | rules::Visibility::OnlyHouseFog => {
-                let mut foggy = self.fog_of_war(player_index, visibility, seen_tiles);
+                let mut foggy = self.fog_of_war(player_index, visibility, fog_level, seen_tiles);
 
                 if trim_coords {
                     // Remove extraneous water, so the client doesn't know the dimensions of the play area
@@ -1560,59 +2211,94 @@ impl Default for Board {
 }
 
 impl Board {
+    /// Builds a board from its string representation, panicking on malformed input.
+    /// Unlike `try_from_string`, boards without artifacts are allowed,
+    /// so that tests can describe partial boards.
     pub fn from_string<S: AsRef<str>>(s: S) -> Board {
+        match Board::parse(s.as_ref()) {
+            Ok(board) => board,
+            Err(e) => panic!("Couldn't build board from string: {e}"),
+        }
+    }
+
+    /// Builds a board from its string representation,
+    /// reporting the location of any malformed input.
+    pub fn try_from_string<S: AsRef<str>>(s: S) -> Result<Board, BoardParseError> {
+        let board = Board::parse(s.as_ref())?;
+
+        if board.artifacts.is_empty() {
+            return Err(BoardParseError::NoArtifacts);
+        }
+
+        Ok(board)
+    }
+
+    fn parse(s: &str) -> Result<Board, BoardParseError> {
         // Transform string into a board
         let mut squares: Vec<Vec<Square>> = vec![];
-        for line in s.as_ref().split('\n') {
+        let mut row_lines = vec![];
+        for (line_index, line) in s.split('\n').enumerate() {
             if line.chars().all(|c| c.is_whitespace()) {
                 continue;
             };
-            squares.push(
-                line.trim()
-                    .split(' ')
-                    .map(|tile| {
-                        let mut chars = tile.chars();
-                        match chars.next() {
-                            Some('~') => Square::water(),
-                            Some('_') => Square::land(),
-                            Some('|') => Square::artifact(
-                                chars
-                                    .next()
-                                    .expect("Square needs player")
-                                    .to_digit(10)
-                                    .unwrap() as usize,
-                            ),
-                            Some('#') => Square::town(
-                                chars
-                                    .next()
-                                    .expect("Square needs player")
-                                    .to_digit(10)
-                                    .unwrap() as usize,
-                            ),
-                            Some(tile) => Square::Occupied {
-                                player: chars
-                                    .next()
-                                    .expect("Square needs player")
-                                    .to_digit(10)
-                                    .unwrap() as usize,
-                                tile,
-                                validity: SquareValidity::Unknown,
-                                foggy: false,
-                            },
-                            _ => panic!("Couldn't build board from string"),
-                        }
-                    })
-                    .collect(),
-            );
+
+            let mut column = line.chars().take_while(|c| c.is_whitespace()).count() + 1;
+            let mut row = vec![];
+            for token in line.trim().split(' ') {
+                let bad_token = || BoardParseError::BadToken {
+                    token: token.to_string(),
+                    line: line_index + 1,
+                    column,
+                };
+
+                let mut chars = token.chars();
+                let first_char = chars.next();
+                let mut player = || {
+                    chars
+                        .next()
+                        .and_then(|c| c.to_digit(10))
+                        .map(|p| p as usize)
+                        .ok_or_else(bad_token)
+                };
+                let square = match first_char {
+                    Some('~') => Square::water(),
+                    Some('_') => Square::land(),
+                    Some('|') => Square::artifact(player()?),
+                    Some('#') => Square::town(player()?),
+                    Some('^') => Square::Obelisk {
+                        foggy: false,
+                        holder: player().ok(),
+                    },
+                    Some(tile) => Square::Occupied {
+                        player: player()?,
+                        tile,
+                        validity: SquareValidity::Unknown,
+                        foggy: false,
+                    },
+                    None => return Err(bad_token()),
+                };
+                row.push(square);
+
+                column += token.chars().count() + 1;
+            }
+            squares.push(row);
+            row_lines.push(line_index + 1);
         }
 
         // Make sure the board is an valid non-jagged grid
-        if squares
-            .iter()
-            .skip(1)
-            .any(|line| line.len() != squares[0].len())
-        {
-            panic!("Tried to make a jagged board");
+        if let Some(first_row) = squares.first() {
+            if let Some((row, line)) = squares
+                .iter()
+                .zip(row_lines)
+                .skip(1)
+                .find(|(row, _)| row.len() != first_row.len())
+            {
+                return Err(BoardParseError::RaggedRow {
+                    line,
+                    expected: first_row.len(),
+                    found: row.len(),
+                });
+            }
         }
 
         let mut board = Board {
@@ -1621,10 +2307,12 @@ impl Board {
             artifacts: vec![],
             obelisks: vec![],
             orientations: vec![Direction::North, Direction::South],
+            shape_hash: ShapeHash::default(),
+            journal: None,
         };
         board.cache_special_squares();
 
-        board
+        Ok(board)
     }
 }
 
@@ -1727,6 +2415,16 @@ impl Coordinate {
     pub fn distance_to(&self, other: &Coordinate) -> usize {
         self.x.abs_diff(other.x) + self.y.abs_diff(other.y)
     }
+
+    /// Number of 4-directional steps between the coordinates, as tiles are played
+    pub fn distance_to_orthogonal(&self, other: &Coordinate) -> usize {
+        self.distance_to(other)
+    }
+
+    /// Number of 8-directional steps between the coordinates, where diagonals count as one
+    pub fn distance_to_chebyshev(&self, other: &Coordinate) -> usize {
+        self.x.abs_diff(other.x).max(self.y.abs_diff(other.y))
+    }
 }
 
 impl fmt::Display for Coordinate {
@@ -1840,6 +2538,9 @@ pub enum Square {
     },
     Obelisk {
         foggy: bool,
+        /// The player currently holding this obelisk, when obelisks are capturable
+        #[serde(default)]
+        holder: Option<usize>,
     },
     Artifact {
         player: usize,
@@ -1865,7 +2566,10 @@ impl Square {
     }
 
     pub fn obelisk() -> Self {
-        Self::Obelisk { foggy: false }
+        Self::Obelisk {
+            foggy: false,
+            holder: None,
+        }
     }
 
     pub fn fog() -> Self {
@@ -1893,7 +2597,7 @@ impl Square {
             Square::Water { foggy }
             | Square::Land { foggy }
             | Square::Town { foggy, .. }
-            | Square::Obelisk { foggy }
+            | Square::Obelisk { foggy, .. }
             | Square::Artifact { foggy, .. }
             | Square::Occupied { foggy, .. } => *foggy,
             Square::Fog {} => true,
@@ -1907,6 +2611,9 @@ impl fmt::Display for Square {
             Square::Water { .. } => write!(f, "~~"),
             Square::Fog { .. } => write!(f, "░░"),
             Square::Land { .. } => write!(f, "__"),
+            Square::Obelisk {
+                holder: Some(p), ..
+            } => write!(f, "^{p}"),
             Square::Obelisk { .. } => write!(f, "^^"),
             Square::Town {
                 player: p,
@@ -1926,6 +2633,11 @@ impl fmt::Display for Square {
     }
 }
 
+/// Per-square distances produced by the board's flood fills.
+///
+/// Each square tracks two distances: `attackable`, along paths a player could
+/// actually play through, and `direct`, which disregards tiles in the way.
+/// Squares that can't be reached have no distance.
 #[derive(Clone)]
 pub struct BoardDistances {
     pub board_width: usize,
@@ -1971,14 +2683,49 @@ impl BoardDistances {
         self.direct.get_mut(pos)
     }
 
+    /// Distance to the square along playable paths, if it can be reached
     pub fn attackable_distance(&self, coord: &Coordinate) -> Option<usize> {
+        if coord.x >= self.board_width {
+            return None;
+        }
         let pos = coord.to_1d(self.board_width);
-        self.attackable[pos]
+        self.attackable.get(pos).copied().flatten()
     }
 
+    /// Distance to the square ignoring any tiles in the way, if it can be reached
     pub fn direct_distance(&self, coord: &Coordinate) -> Option<usize> {
+        if coord.x >= self.board_width {
+            return None;
+        }
         let pos = coord.to_1d(self.board_width);
-        self.direct[pos]
+        self.direct.get(pos).copied().flatten()
+    }
+
+    /// Combines two sets of distances, keeping the shortest distance to each square
+    pub fn closest(&self, other: &BoardDistances) -> Self {
+        assert_eq!(self.attackable.len(), other.attackable.len());
+
+        let closest = |a: &Option<usize>, b: &Option<usize>| match (a, b) {
+            (Some(a), Some(b)) => Some(*a.min(b)),
+            (Some(a), None) | (None, Some(a)) => Some(*a),
+            (None, None) => None,
+        };
+
+        BoardDistances {
+            board_width: self.board_width,
+            attackable: self
+                .attackable
+                .iter()
+                .zip(other.attackable.iter())
+                .map(|(a, b)| closest(a, b))
+                .collect(),
+            direct: self
+                .direct
+                .iter()
+                .zip(other.direct.iter())
+                .map(|(a, b)| closest(a, b))
+                .collect(),
+        }
     }
 
     pub fn difference(&self, other: &BoardDistances) -> Self {
@@ -2055,6 +2802,31 @@ pub mod tests {
         .builtin_dictionary
     }
 
+    #[test]
+    fn boards_compare_by_their_squares() {
+        let board = Board::from_string(
+            "__ S0 |0\n\
+             __ __ __\n\
+             |1 __ __",
+        );
+        let mut journaled = board.clone();
+        journaled.start_journal();
+        assert_eq!(board, journaled);
+
+        let mut placed = board.clone();
+        placed.set(Coordinate { x: 0, y: 0 }, 0, 'A', None).unwrap();
+        assert_ne!(board, placed);
+        assert_ne!(board.shape_hash(), placed.shape_hash());
+
+        // Shapes hash equally whenever the squares are equal
+        let mut placed_again = journaled;
+        placed_again
+            .set(Coordinate { x: 0, y: 0 }, 0, 'A', None)
+            .unwrap();
+        assert_eq!(placed, placed_again);
+        assert_eq!(placed.shape_hash(), placed_again.shape_hash());
+    }
+
     #[test]
     fn coord_flattening() {
         let coord = Coordinate { x: 4, y: 123 };
@@ -2062,6 +2834,69 @@ pub mod tests {
         assert_eq!(coord, Coordinate::from_1d(flat, 51));
     }
 
+    #[test]
+    fn reports_malformed_boards() {
+        assert_eq!(
+            Board::try_from_string(
+                "~~ |0 ~~\n\
+                 __ A __\n\
+                 ~~ |1 ~~"
+            ),
+            Err(BoardParseError::BadToken {
+                token: "A".into(),
+                line: 2,
+                column: 4
+            })
+        );
+
+        assert_eq!(
+            Board::try_from_string("~~ |0 ~~\n__ #x __"),
+            Err(BoardParseError::BadToken {
+                token: "#x".into(),
+                line: 2,
+                column: 4
+            })
+        );
+
+        assert_eq!(
+            Board::try_from_string("~~ |0 ~~\n__ __\n~~ |1 ~~"),
+            Err(BoardParseError::RaggedRow {
+                line: 2,
+                expected: 3,
+                found: 2
+            })
+        );
+
+        assert_eq!(
+            Board::try_from_string("~~ __ ~~\n~~ #0 ~~"),
+            Err(BoardParseError::NoArtifacts)
+        );
+
+        let board = Board::try_from_string("~~ |0 ~~\n__ A0 __\n~~ |1 ~~").unwrap();
+        assert_eq!(board, Board::from_string("~~ |0 ~~\n__ A0 __\n~~ |1 ~~"));
+    }
+
+    #[test]
+    fn rejects_oversized_boards() {
+        // Ten squares of land, plus the water either side
+        let board = Board::new(10, 5);
+        assert_eq!(board.check_dimensions(12), Ok(()));
+        assert_eq!(
+            board.check_dimensions(11),
+            Err(BoardParseError::TooLarge {
+                width: board.width(),
+                height: board.height(),
+                max: 11
+            })
+        );
+
+        let empty = Board {
+            squares: vec![],
+            ..Board::new(3, 3)
+        };
+        assert_eq!(empty.check_dimensions(MAX_BOARD_DIMENSION), Ok(()));
+    }
+
     fn default_swap_rules() -> SwapPenalty {
         SwapPenalty::Disallowed { allowed_swaps: 1 }
     }
@@ -2868,6 +3703,34 @@ pub mod tests {
         );
     }
 
+    #[test]
+    fn within_word_swapping() {
+        let b = Board::from_string(
+            "~~ ~~ |0 ~~ ~~\n\
+             __ __ C0 A0 T0\n\
+             __ __ R0 __ S0\n\
+             ~~ ~~ |1 ~~ ~~",
+        );
+        let rules = rules::Swapping::WithinWord(default_swap_rules());
+
+        let c = Coordinate { x: 2, y: 1 };
+        let t = Coordinate { x: 4, y: 1 };
+        let r = Coordinate { x: 2, y: 2 };
+        let s = Coordinate { x: 4, y: 2 };
+
+        assert_eq!(b.validate_swap(0, [c, t], &rules), Ok(['C', 'T']));
+        assert_eq!(b.validate_swap(0, [c, r], &rules), Ok(['C', 'R']));
+        assert_eq!(
+            b.validate_swap(0, [r, t], &rules),
+            Err(GamePlayError::SwapOutsideWord)
+        );
+        // Gaps split a row into separate words
+        assert_eq!(
+            b.validate_swap(0, [r, s], &rules),
+            Err(GamePlayError::SwapOutsideWord)
+        );
+    }
+
     #[test]
     fn noop_swapping() {
         let mut b = Board::from_string(
@@ -3031,6 +3894,88 @@ pub mod tests {
         }
     }
 
+    #[test]
+    fn words_at_stops_at_fog() {
+        let mut b = Board::from_string(
+            "__ __ C1 __ __\n\
+             __ __ R1 __ __\n\
+             S1 W1 O1 R1 D1\n\
+             __ __ S1 __ __\n\
+             __ __ S1 __ __",
+        );
+
+        let mut words: Vec<_> = b
+            .words_at(Coordinate { x: 2, y: 2 })
+            .into_iter()
+            .map(|(_, word)| word)
+            .collect();
+        words.sort();
+        assert_eq!(words, vec!["CROSS", "SWORD"]);
+
+        b.squares[0][2] = Square::fog();
+        b.squares[2][0] = Square::fog();
+        b.squares[2][1] = Square::fog();
+        let words = b.words_at(Coordinate { x: 2, y: 3 });
+        assert_eq!(words.len(), 1);
+        assert_eq!(words[0].1, "ROSS");
+        assert!(words[0].0.iter().all(|c| !b.get(*c).unwrap().is_foggy()));
+
+        // SWORD loses its hidden S and W
+        let words = b.words_at(Coordinate { x: 3, y: 2 });
+        assert_eq!(words.len(), 1);
+        assert_eq!(words[0].1, "ORD");
+
+        assert!(b.words_at(Coordinate { x: 2, y: 0 }).is_empty());
+        assert!(b.words_at(Coordinate { x: 0, y: 0 }).is_empty());
+    }
+
+    #[test]
+    fn diff_changed_squares() {
+        let before = Board::from_string(
+            "~~ |0 ~~\n\
+             __ A0 __\n\
+             __ __ __\n\
+             __ B1 __\n\
+             ~~ |1 ~~",
+        );
+        let mut after = before.clone();
+        after
+            .set_square(
+                Coordinate { x: 1, y: 2 },
+                Square::Occupied {
+                    player: 0,
+                    tile: 'X',
+                    validity: SquareValidity::Unknown,
+                    foggy: false,
+                },
+            )
+            .unwrap();
+        after
+            .set_square(Coordinate { x: 1, y: 3 }, Square::land())
+            .unwrap();
+
+        let changed = after.changed_squares(&before).unwrap();
+        assert_eq!(
+            changed,
+            vec![
+                (
+                    Coordinate { x: 1, y: 2 },
+                    after.get(Coordinate { x: 1, y: 2 }).unwrap()
+                ),
+                (Coordinate { x: 1, y: 3 }, Square::land()),
+            ]
+        );
+
+        let mut replayed = before.clone();
+        for (coordinate, square) in changed {
+            replayed.set_square(coordinate, square).unwrap();
+        }
+        assert_eq!(replayed.squares, after.squares);
+
+        assert_eq!(before.changed_squares(&before), Some(vec![]));
+        assert_eq!(after.changed_squares(&Board::new(3, 3)), None);
+    }
+
     #[test]
     fn apply_fog_of_war() {
         let board = Board::from_string(
@@ -3043,7 +3988,12 @@ pub mod tests {
              ~~ ~~ B1 ~~ ~~",
         );
 
-        let foggy = board.fog_of_war(1, &rules::Visibility::TileFog, &HashSet::new());
+        let foggy = board.fog_of_war(
+            1,
+            &rules::Visibility::TileFog,
+            &rules::FogLevel::PieceVision,
+            &HashSet::new(),
+        );
         assert_eq!(
             foggy.to_string(),
             "~~ ~~ __ ~~ ~~\n\
@@ -3068,7 +4018,12 @@ pub mod tests {
              ~~ ~~ B1 ~~ ~~",
         );
 
-        let foggy = board.fog_of_war(0, &rules::Visibility::TileFog, &HashSet::new());
+        let foggy = board.fog_of_war(
+            0,
+            &rules::Visibility::TileFog,
+            &rules::FogLevel::PieceVision,
+            &HashSet::new(),
+        );
         assert_eq!(
             foggy.to_string(),
             "~~ ~~ A0 ~~ ~~\n\
@@ -3095,7 +4050,12 @@ pub mod tests {
              ~~ ~~ B1 ~~ ~~ ~~ ~~ ~~ ~~ ~~",
         );
 
-        let mut foggy = board.fog_of_war(0, &rules::Visibility::LandFog, &HashSet::new());
+        let mut foggy = board.fog_of_war(
+            0,
+            &rules::Visibility::LandFog,
+            &rules::FogLevel::PieceVision,
+            &HashSet::new(),
+        );
         foggy.trim();
         assert_eq!(
             foggy.to_string(),
@@ -3109,6 +4069,165 @@ pub mod tests {
         );
     }
 
+    #[test]
+    fn fog_levels() {
+        let board = Board::from_string(
+            "~~ |0 ~~ ~~ ~~ ~~ ~~\n\
+             __ A0 __ __ __ __ __\n\
+             __ A0 __ __ __ __ __\n\
+             __ __ __ __ __ __ __\n\
+             __ __ __ __ __ __ __\n\
+             __ __ __ __ __ __ __\n\
+             __ __ __ __ __ __ __\n\
+             __ __ __ __ __ __ __\n\
+             __ __ __ __ __ __ __\n\
+             __ __ __ __ __ B1 __\n\
+             ~~ ~~ ~~ ~~ ~~ |1 ~~",
+        );
+        let fogged = |level: rules::FogLevel, x: usize, y: usize| {
+            let foggy = board.fog_of_war(0, &rules::Visibility::LandFog, &level, &HashSet::new());
+            matches!(foggy.get(Coordinate { x, y }), Ok(Square::Fog {}))
+        };
+
+        assert!(!fogged(rules::FogLevel::PieceVision, 1, 4));
+        assert!(fogged(rules::FogLevel::PieceVision, 5, 10));
+        assert!(fogged(rules::FogLevel::PieceVision, 0, 7));
+
+        let full = board.fog_of_war(
+            0,
+            &rules::Visibility::LandFog,
+            &rules::FogLevel::Full,
+            &HashSet::new(),
+        );
+        assert_eq!(full, board);
+
+        assert!(!fogged(rules::FogLevel::BeyondDistance(1), 2, 2));
+        assert!(fogged(rules::FogLevel::BeyondDistance(1), 1, 4));
+
+        // Both homes are visible, but the middle of the board stays fogged
+        assert!(!fogged(rules::FogLevel::BattleArena, 5, 10));
+        assert!(!fogged(rules::FogLevel::BattleArena, 1, 4));
+        assert!(fogged(rules::FogLevel::BattleArena, 0, 7));
+    }
+
+    #[test]
+    fn fog_never_leaks_hidden_squares() {
+        use crate::generation::{generate_board, BoardSeed};
+        use oorandom::Rand32;
+
+        for seed in 0..12 {
+            let mut board = generate_board(BoardSeed::new(seed))
+                .expect("Board can be resolved")
+                .board;
+            board.trim();
+
+            // Scatter both players' tiles across the land, and remember a few squares for each player
+            let mut rng = Rand32::new(seed as u64);
+            let mut seen = [HashSet::new(), HashSet::new()];
+            for y in 0..board.height() {
+                for x in 0..board.width() {
+                    let c = Coordinate { x, y };
+                    if matches!(board.get(c), Ok(Square::Land { .. })) && rng.rand_range(0..3) == 0
+                    {
+                        board.squares[y][x] = Square::Occupied {
+                            player: rng.rand_range(0..2) as usize,
+                            tile: (b'A' + rng.rand_range(0..26) as u8) as char,
+                            validity: SquareValidity::Unknown,
+                            foggy: false,
+                        };
+                    }
+                    for seen_tiles in seen.iter_mut() {
+                        if rng.rand_range(0..4) == 0 {
+                            seen_tiles.insert(c);
+                        }
+                    }
+                }
+            }
+
+            let visibilities = [
+                rules::Visibility::TileFog,
+                rules::Visibility::LandFog,
+                rules::Visibility::OnlyHouseFog,
+            ];
+            let fog_levels = [
+                rules::FogLevel::PieceVision,
+                rules::FogLevel::BeyondDistance(2),
+                rules::FogLevel::BattleArena,
+            ];
+
+            for player in 0..2 {
+                for visibility in &visibilities {
+                    for fog_level in &fog_levels {
+                        let seen_tiles = &seen[player];
+                        let view = board.filter_to_player(
+                            player, visibility, fog_level, &None, seen_tiles, true,
+                        );
+                        let context = format!(
+                            "seed {seed}, player {player}, {visibility:?}, {fog_level:?}:\n{view}"
+                        );
+
+                        // Check what actually goes over the wire
+                        let serialized = serde_json::to_value(&view).unwrap();
+                        let rows = serialized["squares"].as_array().unwrap();
+                        for (y, row) in rows.iter().enumerate() {
+                            for (x, square) in row.as_array().unwrap().iter().enumerate() {
+                                let (variant, fields) =
+                                    square.as_object().unwrap().iter().next().unwrap();
+                                let foggy = variant == "Fog" || fields["foggy"] == true;
+                                if !foggy {
+                                    continue;
+                                }
+
+                                assert!(fields.get("tile").is_none(), "Tile leaked for {context}");
+                                if fields.get("player").is_some() {
+                                    // Structures the player has already seen are remembered under fog
+                                    let game_coord = board.map_player_coord_to_game(
+                                        player,
+                                        Coordinate { x, y },
+                                        visibility,
+                                        fog_level,
+                                        seen_tiles,
+                                    );
+                                    assert!(
+                                        (variant == "Town" || variant == "Artifact")
+                                            && seen_tiles.contains(&game_coord),
+                                        "Owner leaked at {x},{y} for {context}"
+                                    );
+                                }
+                            }
+                        }
+
+                        // Players can always see their own tiles
+                        for y in 0..board.height() {
+                            for x in 0..board.width() {
+                                let c = Coordinate { x, y };
+                                let Ok(square @ Square::Occupied { player: owner, .. }) =
+                                    board.get(c)
+                                else {
+                                    continue;
+                                };
+                                if owner != player {
+                                    continue;
+                                }
+
+                                let view_coord = board
+                                    .map_game_coord_to_player(
+                                        player, c, visibility, fog_level, seen_tiles,
+                                    )
+                                    .expect("Own tiles should be within view");
+                                assert_eq!(
+                                    view.get(view_coord),
+                                    Ok(square),
+                                    "Own tile at {x},{y} was fogged for {context}"
+                                );
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
     #[test]
     fn remap_foggy_coordinates() {
         let board = Board::from_string(
@@ -3126,7 +4245,12 @@ pub mod tests {
              __ __ __ __ ~~ ~~ B1 ~~ ~~ ~~ ~~",
         );
         {
-            let mut foggy = board.fog_of_war(0, &rules::Visibility::LandFog, &HashSet::new());
+            let mut foggy = board.fog_of_war(
+                0,
+                &rules::Visibility::LandFog,
+                &rules::FogLevel::PieceVision,
+                &HashSet::new(),
+            );
             foggy.trim();
             assert_eq!(
                 foggy.to_string(),
@@ -3145,6 +4269,7 @@ pub mod tests {
                 0,
                 source_coord,
                 &rules::Visibility::LandFog,
+                &rules::FogLevel::PieceVision,
                 &HashSet::new(),
             );
             assert_eq!(game_coord, Coordinate { x: 5, y: 5 });
@@ -3153,13 +4278,19 @@ pub mod tests {
                     0,
                     game_coord,
                     &rules::Visibility::LandFog,
+                    &rules::FogLevel::PieceVision,
                     &HashSet::new()
                 ),
                 Some(source_coord)
             );
         }
         {
-            let mut foggy = board.fog_of_war(1, &rules::Visibility::LandFog, &HashSet::new());
+            let mut foggy = board.fog_of_war(
+                1,
+                &rules::Visibility::LandFog,
+                &rules::FogLevel::PieceVision,
+                &HashSet::new(),
+            );
             foggy.trim();
             assert_eq!(
                 foggy.to_string(),
@@ -3179,6 +4310,7 @@ pub mod tests {
                 1,
                 source_coord,
                 &rules::Visibility::LandFog,
+                &rules::FogLevel::PieceVision,
                 &HashSet::new(),
             );
             assert_eq!(game_coord, Coordinate { x: 8, y: 7 });
@@ -3187,10 +4319,111 @@ pub mod tests {
                     1,
                     game_coord,
                     &rules::Visibility::LandFog,
+                    &rules::FogLevel::PieceVision,
                     &HashSet::new()
                 ),
                 Some(source_coord)
             );
         }
     }
+
+    #[test]
+    fn symmetry_swaps_players() {
+        let symmetric = Board::from_string(
+            "~~ ~~ |0 ~~ ~~\n\
+             ~~ #0 __ __ ~~\n\
+             ~~ __ __ #1 ~~\n\
+             ~~ ~~ |1 ~~ ~~",
+        );
+        assert!(symmetric.is_symmetric());
+
+        let same_owner = Board::from_string(
+            "~~ ~~ |0 ~~ ~~\n\
+             ~~ #0 __ __ ~~\n\
+             ~~ __ __ #0 ~~\n\
+             ~~ ~~ |1 ~~ ~~",
+        );
+        assert!(!same_owner.is_symmetric());
+
+        let uneven_land = Board::from_string(
+            "~~ ~~ |0 ~~ ~~\n\
+             ~~ #0 __ __ __\n\
+             ~~ __ __ #1 ~~\n\
+             ~~ ~~ |1 ~~ ~~",
+        );
+        assert!(!uneven_land.is_symmetric());
+    }
+
+    #[test]
+    fn mirroring_keeps_each_player_on_their_side() {
+        let mut board = Board::from_string(
+            "~~ |0 #0 __\n\
+             A0 __ ~~ __\n\
+             __ #1 |1 ~~",
+        );
+        board.mirror();
+
+        assert_eq!(
+            board.to_string(),
+            "~~ |0 #0 __\n\
+             __ ~~ __ A1\n\
+             __ #1 |1 ~~"
+        );
+        assert_eq!(
+            board.artifacts,
+            vec![Coordinate::new(1, 0), Coordinate::new(2, 2)]
+        );
+        assert_eq!(board.get(Coordinate::new(2, 0)), Ok(Square::town(0)));
+
+        board.mirror();
+        assert_eq!(
+            board,
+            Board::from_string(
+                "~~ |0 #0 __\n\
+                 A0 __ ~~ __\n\
+                 __ #1 |1 ~~",
+            )
+        );
+    }
+
+    #[test]
+    fn distance_field_respects_fog() {
+        let b = Board::from_string(
+            "~~ ~~ |0 ~~ ~~\n\
+             ~~ __ __ __ ~~\n\
+             ~~ __ A1 __ ~~\n\
+             ~~ #0 __ __ ~~\n\
+             ~~ ~~ |1 ~~ ~~",
+        );
+
+        let field = b.distance_field_to(Coordinate::new(1, 3));
+        assert_eq!(field.direct_distance(&Coordinate::new(1, 3)), Some(0));
+        assert_eq!(field.direct_distance(&Coordinate::new(2, 1)), Some(3));
+        assert_eq!(field.direct_distance(&Coordinate::new(0, 0)), None);
+        // The tile at the center can be attacked, but not played through
+        assert_eq!(field.attackable_distance(&Coordinate::new(2, 2)), Some(2));
+        assert_eq!(field.attackable_distance(&Coordinate::new(3, 2)), Some(3));
+        assert_eq!(field.attackable_distance(&Coordinate::new(9, 9)), None);
+
+        let mut fogged = b.clone();
+        fogged.squares[2][2] = Square::fog();
+        fogged.squares[1][1] = Square::Land { foggy: true };
+
+        let field = fogged.distance_field_to(Coordinate::new(1, 3));
+        assert_eq!(field.direct_distance(&Coordinate::new(2, 2)), None);
+        assert_eq!(field.direct_distance(&Coordinate::new(1, 1)), Some(2));
+        assert_eq!(field.attackable_distance(&Coordinate::new(1, 1)), None);
+        assert_eq!(field.attackable_distance(&Coordinate::new(1, 2)), Some(1));
+    }
+
+    #[test]
+    fn coordinate_distances() {
+        let a = Coordinate::new(1, 1);
+        let b = Coordinate::new(4, 3);
+        assert_eq!(a.distance_to(&b), 5);
+        assert_eq!(a.distance_to_orthogonal(&b), 5);
+        assert_eq!(a.distance_to_chebyshev(&b), 3);
+        assert_eq!(b.distance_to_chebyshev(&a), 3);
+        assert_eq!(a.distance_to_chebyshev(&a), 0);
+    }
 }
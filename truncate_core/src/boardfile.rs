@@ -0,0 +1,297 @@
+//! The `.trboard` format, for sharing hand-authored boards as plain text.
+//!
+//! A file opens with a version line, followed by `key: value` metadata,
+//! then a `---` line and the board itself as written by `Board::to_string`:
+//!
+//! ```text
+//! truncate-board 1
+//! name: Twin Lakes
+//! players: 2
+//! rules: {"win_condition":...}
+//! ---
+//! ~~ |0 ~~
+//! __ #0 __
+//! __ #1 __
+//! ~~ |1 ~~
+//! ```
+//!
+//! `players` is required. `name` and `rules` are optional, with `rules` holding
+//! `GameRules` as single-line JSON. Unknown keys are ignored so that new metadata
+//! can be added without bumping the version.
+
+use std::fmt;
+
+use crate::{
+    board::{Board, Square},
+    error::BoardFileError,
+    rules::GameRules,
+};
+
+/// Bumped whenever a `.trboard` file would be misread by an older parser
+pub const BOARD_FILE_VERSION: u32 = 1;
+
+const VERSION_PREFIX: &str = "truncate-board";
+const BOARD_SEPARATOR: &str = "---";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BoardFile {
+    pub name: Option<String>,
+    pub player_count: usize,
+    pub rules: Option<GameRules>,
+    pub board: Board,
+}
+
+impl BoardFile {
+    pub fn new(board: Board, player_count: usize) -> Self {
+        Self {
+            name: None,
+            player_count,
+            rules: None,
+            board,
+        }
+    }
+
+    pub fn name(mut self, value: impl Into<String>) -> Self {
+        self.name = Some(value.into());
+        self
+    }
+
+    pub fn rules(mut self, value: GameRules) -> Self {
+        self.rules = Some(value);
+        self
+    }
+
+    pub fn parse(s: &str) -> Result<Self, BoardFileError> {
+        let mut lines = s.lines().enumerate().filter(|(_, l)| !l.trim().is_empty());
+
+        let version = lines
+            .next()
+            .and_then(|(_, l)| l.trim().strip_prefix(VERSION_PREFIX))
+            .and_then(|v| v.trim().parse::<u32>().ok())
+            .ok_or(BoardFileError::MissingVersion)?;
+        if version != BOARD_FILE_VERSION {
+            return Err(BoardFileError::UnsupportedVersion {
+                found: version,
+                expected: BOARD_FILE_VERSION,
+            });
+        }
+
+        let mut name = None;
+        let mut player_count = None;
+        let mut rules = None;
+        let mut board_start = None;
+
+        for (line_index, line) in lines {
+            let line = line.trim();
+            if line == BOARD_SEPARATOR {
+                board_start = Some(line_index + 1);
+                break;
+            }
+
+            let bad_metadata = |reason: String| BoardFileError::BadMetadata {
+                line: line_index + 1,
+                reason,
+            };
+            let Some((key, value)) = line.split_once(':') else {
+                return Err(bad_metadata(format!(
+                    "expected `key: value`, found '{line}'"
+                )));
+            };
+            let value = value.trim();
+
+            match key.trim() {
+                "name" => name = Some(value.to_string()),
+                "players" => {
+                    let count = value
+                        .parse::<usize>()
+                        .map_err(|_| bad_metadata(format!("'{value}' isn't a player count")))?;
+                    player_count = Some(count);
+                }
+                "rules" => {
+                    let parsed = serde_json::from_str(value)
+                        .map_err(|e| bad_metadata(format!("rules couldn't be read: {e}")))?;
+                    rules = Some(parsed);
+                }
+                _ => {}
+            }
+        }
+
+        let board_start = board_start.ok_or(BoardFileError::MissingBoard)?;
+        let player_count = player_count.ok_or(BoardFileError::MissingPlayerCount)?;
+        // Line numbers in board errors should point into the file, not the board section
+        let board_text = s
+            .lines()
+            .enumerate()
+            .map(|(i, l)| if i < board_start { "" } else { l })
+            .collect::<Vec<_>>()
+            .join("\n");
+        let board = Board::try_from_string(board_text)?;
+
+        let file = Self {
+            name,
+            player_count,
+            rules,
+            board,
+        };
+        file.validate()?;
+
+        Ok(file)
+    }
+
+    /// Checks that the board has a fair set of artifacts and towns for exactly the declared players
+    fn validate(&self) -> Result<(), BoardFileError> {
+        if self.player_count == 0 {
+            return Err(BoardFileError::NoPlayers);
+        }
+
+        let mut artifacts = vec![0; self.player_count];
+        let mut towns = vec![0; self.player_count];
+
+        for square in self.board.squares.iter().flatten() {
+            let owner = match square {
+                Square::Artifact { player, .. } => {
+                    if let Some(count) = artifacts.get_mut(*player) {
+                        *count += 1;
+                    }
+                    Some(*player)
+                }
+                Square::Town { player, .. } => {
+                    if let Some(count) = towns.get_mut(*player) {
+                        *count += 1;
+                    }
+                    Some(*player)
+                }
+                Square::Occupied { player, .. } => Some(*player),
+                Square::Obelisk { holder, .. } => *holder,
+                Square::Water { .. } | Square::Land { .. } | Square::Fog {} => None,
+            };
+
+            if let Some(player) = owner.filter(|p| *p >= self.player_count) {
+                return Err(BoardFileError::UndeclaredPlayer {
+                    player,
+                    player_count: self.player_count,
+                });
+            }
+        }
+
+        for (player, (&artifact_count, &town_count)) in artifacts.iter().zip(&towns).enumerate() {
+            if artifact_count == 0 {
+                return Err(BoardFileError::MissingArtifact { player });
+            }
+            if artifact_count != artifacts[0] {
+                return Err(BoardFileError::UnevenArtifacts {
+                    player,
+                    found: artifact_count,
+                    expected: artifacts[0],
+                });
+            }
+            if town_count != towns[0] {
+                return Err(BoardFileError::UnevenTowns {
+                    player,
+                    found: town_count,
+                    expected: towns[0],
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for BoardFile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{VERSION_PREFIX} {BOARD_FILE_VERSION}")?;
+        if let Some(name) = &self.name {
+            writeln!(f, "name: {name}")?;
+        }
+        writeln!(f, "players: {}", self.player_count)?;
+        if let Some(rules) = &self.rules {
+            let rules = serde_json::to_string(rules).expect("Rules should be serializable");
+            writeln!(f, "rules: {rules}")?;
+        }
+        writeln!(f, "{BOARD_SEPARATOR}")?;
+        writeln!(f, "{}", self.board)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn two_player_board() -> Board {
+        Board::from_string(
+            "~~ |0 ~~\n\
+             __ #0 __\n\
+             __ ^^ __\n\
+             __ #1 __\n\
+             ~~ |1 ~~",
+        )
+    }
+
+    #[test]
+    fn board_file_round_trip() {
+        let file = BoardFile::new(two_player_board(), 2)
+            .name("Twin Lakes")
+            .rules(GameRules::generation(1));
+
+        let text = file.to_string();
+        assert!(text.starts_with("truncate-board 1\nname: Twin Lakes\nplayers: 2\nrules: {"));
+        assert_eq!(BoardFile::parse(&text), Ok(file));
+
+        let bare = BoardFile::new(two_player_board(), 2);
+        assert_eq!(BoardFile::parse(&bare.to_string()), Ok(bare));
+    }
+
+    #[test]
+    fn board_file_rejects_mismatched_players() {
+        let header = "truncate-board 1\nplayers: 2\n---\n";
+
+        assert_eq!(
+            BoardFile::parse(&format!("{header}~~ |0 ~~\n__ #0 __\n__ #1 __")),
+            Err(BoardFileError::MissingArtifact { player: 1 })
+        );
+        assert_eq!(
+            BoardFile::parse(&format!("{header}|0 #0 #0\n__ __ __\n|1 #1 __")),
+            Err(BoardFileError::UnevenTowns {
+                player: 1,
+                found: 1,
+                expected: 2
+            })
+        );
+        assert_eq!(
+            BoardFile::parse(&format!("{header}|0 #0 __\n|1 #1 __\n|2 #2 __")),
+            Err(BoardFileError::UndeclaredPlayer {
+                player: 2,
+                player_count: 2
+            })
+        );
+        assert_eq!(
+            BoardFile::parse("truncate-board 1\nplayers: 2\n~~ |0 ~~"),
+            Err(BoardFileError::BadMetadata {
+                line: 3,
+                reason: "expected `key: value`, found '~~ |0 ~~'".into()
+            })
+        );
+        assert_eq!(
+            BoardFile::parse("truncate-board 2\nplayers: 2\n---\n|0 |1"),
+            Err(BoardFileError::UnsupportedVersion {
+                found: 2,
+                expected: 1
+            })
+        );
+    }
+
+    #[test]
+    fn board_file_reports_board_lines_within_the_file() {
+        assert_eq!(
+            BoardFile::parse("truncate-board 1\nplayers: 2\n---\n|0 |1\n__ ?!?"),
+            Err(BoardFileError::Board(
+                crate::error::BoardParseError::BadToken {
+                    token: "?!?".into(),
+                    line: 5,
+                    column: 4
+                }
+            ))
+        );
+    }
+}
@@ -1,4 +1,7 @@
-use crate::board::{Board, Square};
+use crate::{
+    board::{Board, Square},
+    game::Game,
+};
 
 pub const SQ_BLUE: &str = "🟦";
 pub const SQ_GREEN: &str = "🟩";
@@ -99,3 +102,96 @@ impl Board {
             .join("\n")
     }
 }
+
+/// Details of a daily puzzle attempt that aren't recorded on the `Game` itself
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DailyShareDetails {
+    pub day: u32,
+    /// Zero-indexed attempt number for the day
+    pub attempt: u32,
+    pub player: usize,
+}
+
+pub fn daily_share_header(day: u32) -> String {
+    if matches!(option_env!("TR_ENV"), Some("outpost")) {
+        format!("-- Truncate Outpost Day #{day} --")
+    } else {
+        format!("Truncate Town Day #{day}")
+    }
+}
+
+impl Game {
+    /// Summarises a finished daily puzzle as copyable text,
+    /// with the outcome above an emoji grid of the final board.
+    pub fn daily_share_text(&self, details: DailyShareDetails) -> String {
+        let DailyShareDetails {
+            day,
+            attempt,
+            player,
+        } = details;
+        let plur = |num: u32| if num == 1 { "" } else { "s" };
+        let moves = self.player_turn_count.get(player).copied().unwrap_or(0);
+
+        let outcome = if self.winner != Some(player) {
+            format!(
+                "Lost in {moves} move{} on attempt #{}",
+                plur(moves),
+                attempt + 1
+            )
+        } else if attempt == 0 {
+            format!("Won first try in {moves} move{}", plur(moves))
+        } else {
+            format!(
+                "Won on attempt #{} in {moves} move{}",
+                attempt + 1,
+                plur(moves)
+            )
+        };
+
+        format!(
+            "{}\n{outcome}\n{}",
+            daily_share_header(day),
+            self.board.emojify(player, self.winner)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::GameRules;
+
+    #[test]
+    fn daily_share_text_reports_outcome() {
+        let mut game = Game::new(9, 9, Some(3), GameRules::generation(1));
+        game.add_player("A".into());
+        game.add_player("B".into());
+        game.start();
+        game.player_turn_count = vec![1, 4];
+
+        let details = DailyShareDetails {
+            day: 12,
+            attempt: 2,
+            player: 1,
+        };
+        let text = game.daily_share_text(details);
+        let mut lines = text.lines();
+        assert_eq!(lines.next(), Some(daily_share_header(12).as_str()));
+        assert_eq!(lines.next(), Some("Lost in 4 moves on attempt #3"));
+        assert_eq!(
+            lines.collect::<Vec<_>>().join("\n"),
+            game.board.emojify(1, None)
+        );
+
+        game.winner = Some(1);
+        assert!(game
+            .daily_share_text(DailyShareDetails {
+                attempt: 0,
+                ..details
+            })
+            .contains("\nWon first try in 4 moves\n"));
+        assert!(game
+            .daily_share_text(details)
+            .contains("\nWon on attempt #3 in 4 moves\n"));
+    }
+}
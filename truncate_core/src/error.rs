@@ -1,7 +1,8 @@
 use super::board::Coordinate;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
-#[derive(Clone, Error, Debug, PartialEq)]
+#[derive(Clone, Error, Debug, PartialEq, Serialize, Deserialize)]
 pub enum GamePlayError {
     #[error("Invalid position ({:?}, {:?})", position.x, position.y)]
     InvalidPosition { position: Coordinate },
@@ -14,6 +15,13 @@ pub enum GamePlayError {
     #[error("Player {index:?} does not exist")]
     NonExistentPlayer { index: usize },
 
+    #[error("The game is already over")]
+    GameOver,
+    #[error("It is not player {player:?}'s turn")]
+    NotYourTurn { player: usize },
+    #[error("Player {player:?}'s turn has not yet started")]
+    TurnNotStarted { player: usize },
+
     #[error("Can't swap a square with itself")]
     SelfSwap,
     #[error("Can't swap two identical tiles")]
@@ -24,10 +32,14 @@ pub enum GamePlayError {
     UnownedSwap,
     #[error("You can't swap tiles between disconnected groups")]
     DisjointSwap,
+    #[error("You can only swap tiles within the same word")]
+    SwapOutsideWord,
     #[error("Swapping is disabled")]
     NoSwapping,
     #[error("You can't swap {count} in a row")]
     TooManySwaps { count: String },
+    #[error("You've used all of your swaps for this game")]
+    NoSwapsRemaining,
 
     #[error("You can't place a tile on top of another")]
     OccupiedPlace,
@@ -36,4 +48,117 @@ pub enum GamePlayError {
 
     #[error("Player {player:?} doesn't have a '{tile:?}' tile")]
     PlayerDoesNotHaveTile { player: usize, tile: char },
+    #[error("Blank tiles need to be assigned a letter when placed")]
+    UnassignedBlank,
+
+    #[error("Challenges are disabled")]
+    ChallengesDisabled,
+    #[error("You can only challenge an opponent's tile that you can see")]
+    InvalidChallenge,
+
+    #[error("Passing is disabled")]
+    PassingDisabled,
+    #[error("Exchanging tiles is disabled")]
+    ExchangesDisabled,
+    #[error("There are no tiles left in the bag to exchange for")]
+    EmptyBag,
+
+    #[error("That color is too similar to player {player:?}'s")]
+    ColorTooSimilar { player: usize },
+    #[error("Colors can't be changed once the game has started")]
+    ColorLocked,
+
+    #[error("Only tile placements can be previewed")]
+    NotAPlacement,
+
+    #[error("There are no turns to undo")]
+    NothingToUndo,
+    #[error("There are no turns to redo")]
+    NothingToRedo,
+}
+
+#[derive(Clone, Error, Debug, PartialEq)]
+pub enum SavegameError {
+    #[error("Savegame is version {found}, but only version {expected} can be loaded")]
+    UnsupportedVersion { found: u32, expected: u32 },
+    #[error("Savegame could not be read: {0}")]
+    Malformed(String),
+}
+
+#[derive(Clone, Error, Debug, PartialEq)]
+pub enum BoardParseError {
+    #[error("Couldn't read '{token}' at line {line}, column {column}")]
+    BadToken {
+        token: String,
+        line: usize,
+        column: usize,
+    },
+    #[error("Line {line} has {found} squares, but the board is {expected} squares wide")]
+    RaggedRow {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+    #[error("Board has no artifacts")]
+    NoArtifacts,
+    #[error("Board is {width}x{height} squares, but boards can be at most {max} squares across")]
+    TooLarge {
+        width: usize,
+        height: usize,
+        max: usize,
+    },
+}
+
+#[derive(Clone, Error, Debug, PartialEq)]
+pub enum BoardFileError {
+    #[error("Board files must start with a `truncate-board` version line")]
+    MissingVersion,
+    #[error("Board file is version {found}, but only version {expected} can be loaded")]
+    UnsupportedVersion { found: u32, expected: u32 },
+    #[error("Couldn't read line {line}: {reason}")]
+    BadMetadata { line: usize, reason: String },
+    #[error("Board file doesn't declare how many players it's for")]
+    MissingPlayerCount,
+    #[error("Board file has no `---` line before the board")]
+    MissingBoard,
+    #[error("Board file must be for at least one player")]
+    NoPlayers,
+    #[error(transparent)]
+    Board(#[from] BoardParseError),
+    #[error("Board has squares for player {player}, but is only for {player_count} players")]
+    UndeclaredPlayer { player: usize, player_count: usize },
+    #[error("Player {player} has no artifact")]
+    MissingArtifact { player: usize },
+    #[error("Player {player} has {found} artifacts, but player 0 has {expected}")]
+    UnevenArtifacts {
+        player: usize,
+        found: usize,
+        expected: usize,
+    },
+    #[error("Player {player} has {found} towns, but player 0 has {expected}")]
+    UnevenTowns {
+        player: usize,
+        found: usize,
+        expected: usize,
+    },
+}
+
+#[derive(Clone, Error, Debug, PartialEq)]
+pub enum ScenarioError {
+    #[error("Scenario could not be read: {0}")]
+    Malformed(String),
+    #[error("Scenario board could not be read: {0}")]
+    InvalidBoard(String),
+    #[error("Scenario action {action:?} should look like 'A -> 3,4' or '1,2 -> 3,4'")]
+    InvalidAction { action: String },
+    #[error("{hint}")]
+    UnexpectedMove { hint: String },
+    #[error("It is the computer's turn in this scenario")]
+    ComputerToMove,
+    #[error("It is the player's turn in this scenario")]
+    PlayerToMove,
+    #[error("The scenario has been completed")]
+    Complete,
+    #[error("Scripted move was rejected: {0}")]
+    Rejected(String),
 }
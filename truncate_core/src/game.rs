@@ -4,17 +4,21 @@ use std::ops::Sub;
 use time::Duration;
 use xxhash_rust::xxh3;
 
-use crate::bag::TileBag;
+use crate::bag::{is_placed_blank, TileBag, BLANK_TILE};
 use crate::board::{Coordinate, Square};
 use crate::error::GamePlayError;
+use crate::history::{GameHistory, PendingTurn, TurnHistory};
 use crate::judge::{Outcome, WordDict};
-use crate::reporting::{self, BoardChange, BoardChangeAction, BoardChangeDetail, TimeChange};
+use crate::reporting::{
+    self, BattlePreview, BattleReason, BattleReport, BattleWord, BoardChange, BoardChangeAction,
+    BoardChangeDetail, TimeChange,
+};
 use crate::rules::{self, GameRules, OvertimeRule};
 
 use super::board::Board;
 use super::judge::Judge;
 use super::moves::Move;
-use super::player::Player;
+use super::player::{Hand, Player};
 use super::reporting::Change;
 
 pub const GAME_COLOR_BLUE: (u8, u8, u8) = (80_u8, 167_u8, 232_u8);
@@ -31,6 +35,29 @@ pub const GAME_COLORS: [(u8, u8, u8); 5] = [
     GAME_COLOR_YELLOW,
 ];
 
+/// Player colors closer than this, by `color_distance`, are too hard to tell apart on the board
+pub const MIN_COLOR_DISTANCE: f32 = 120.0;
+
+/// Approximates how different two colors look, weighting RGB by the mean red level ("redmean")
+pub fn color_distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> f32 {
+    let red_mean = (a.0 as f32 + b.0 as f32) / 2.0;
+    let dr = a.0 as f32 - b.0 as f32;
+    let dg = a.1 as f32 - b.1 as f32;
+    let db = a.2 as f32 - b.2 as f32;
+
+    ((2.0 + red_mean / 256.0) * dr * dr
+        + 4.0 * dg * dg
+        + (2.0 + (255.0 - red_mean) / 256.0) * db * db)
+        .sqrt()
+}
+
+/// How a finished game ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameOutcome {
+    Winner(usize),
+    Draw,
+}
+
 #[derive(Debug, Clone)]
 pub struct Game {
     pub rules: GameRules,
@@ -47,6 +74,18 @@ pub struct Game {
     pub next_player: Option<usize>,
     pub paused: bool,
     pub winner: Option<usize>,
+    /// Set when the game ended without a winner, see `outcome`
+    pub drawn: bool,
+    /// How many turns in a row have been passed, across all players
+    pub consecutive_passes: usize,
+    /// How many turns in a row have made no progress, see `GameRules::stalemate_turns`
+    pub turns_without_progress: u32,
+    /// Each defeated town, alongside how many turns its owner had taken when it fell
+    pub fallen_towns: Vec<(Coordinate, u32)>,
+    /// Only tracked when requested via `track_history`, as the NPC clones games heavily
+    pub history: Option<TurnHistory>,
+    /// Only tracked when requested via `track_word_history`, for the same reason
+    pub word_history: Option<GameHistory>,
 }
 
 // TODO: Move this to a helper file somewhere
@@ -62,26 +101,9 @@ impl Game {
         let mut board = Board::new(width, height);
         board.grow();
 
-        let next_player = match &rules.timing {
-            rules::Timing::Periodic { .. } => None,
-            _ => Some(0),
-        };
-
         Self {
-            players: Vec::with_capacity(2),
-            board,
-            bag: TileBag::generation(rules.tile_generation, tile_seed),
-            judge: Judge::default(),
-            battle_count: 0,
-            turn_count: 0,
-            player_turn_count: Vec::with_capacity(2),
-            recent_changes: vec![],
-            started_at: None,
-            game_ends_at: None,
-            next_player,
-            paused: false,
-            winner: None,
-            rules,
+            bag: rules.tile_bag(tile_seed),
+            ..Self::from_board(board, Vec::with_capacity(2), rules)
         }
     }
 
@@ -94,50 +116,140 @@ impl Game {
         let mut board = Board::new_legacy(width, height);
         board.grow();
 
+        Self {
+            bag: rules.tile_bag(tile_seed),
+            ..Self::from_board(board, Vec::with_capacity(2), rules)
+        }
+    }
+
+    /// Sets up an unstarted game on a prebuilt board with the given players,
+    /// who are expected to be indexed by their position in `players`.
+    /// The first player moves first, unless the rules have everyone play at once,
+    /// and the bag is freshly seeded from the rules' tile generation.
+    pub fn from_board(mut board: Board, players: Vec<Player>, rules: GameRules) -> Self {
+        board.cache_special_squares();
+
         let next_player = match &rules.timing {
             rules::Timing::Periodic { .. } => None,
             _ => Some(0),
         };
 
         Self {
-            players: Vec::with_capacity(2),
+            player_turn_count: vec![0; players.len()],
+            players,
             board,
-            bag: TileBag::generation(rules.tile_generation, tile_seed),
+            bag: rules.tile_bag(None),
             judge: Judge::default(),
             battle_count: 0,
             turn_count: 0,
-            player_turn_count: Vec::with_capacity(2),
             recent_changes: vec![],
             started_at: None,
             game_ends_at: None,
             next_player,
             paused: false,
             winner: None,
+            drawn: false,
+            consecutive_passes: 0,
+            turns_without_progress: 0,
+            fallen_towns: vec![],
+            history: None,
+            word_history: None,
             rules,
         }
     }
 
-    pub fn add_player(&mut self, name: String) {
-        let time_allowance = match self.rules.timing {
+    fn time_allowance(&self) -> Option<Duration> {
+        match self.rules.timing {
             rules::Timing::PerPlayer {
                 time_allowance,
                 overtime_rule: _,
             } => Some(Duration::new(time_allowance as i64, 0)),
+            rules::Timing::Fischer { base_time, .. } => Some(Duration::new(base_time as i64, 0)),
             rules::Timing::None => None,
             rules::Timing::Periodic { .. } => None,
             _ => unimplemented!(),
-        };
+        }
+    }
+
+    pub fn add_player(&mut self, name: String) {
+        let time_allowance = self.time_allowance();
+        let color = self.default_color();
         self.players.push(Player::new(
             name,
             self.players.len(),
-            self.rules.hand_size,
+            self.rules.hand_capacity,
             &mut self.bag,
             time_allowance,
-            GAME_COLORS[self.players.len()],
+            color,
         ));
         self.player_turn_count.push(0);
     }
 
+    /// The next palette color, skipping any that existing players have picked something close to
+    fn default_color(&self) -> (u8, u8, u8) {
+        let offset = self.players.len();
+        (0..GAME_COLORS.len())
+            .map(|i| GAME_COLORS[(offset + i) % GAME_COLORS.len()])
+            .find(|color| self.color_clash(usize::MAX, *color).is_none())
+            .unwrap_or(GAME_COLORS[offset % GAME_COLORS.len()])
+    }
+
+    /// Finds another player whose color is too close to the given one
+    fn color_clash(&self, player: usize, color: (u8, u8, u8)) -> Option<usize> {
+        self.players
+            .iter()
+            .find(|p| p.index != player && color_distance(p.color, color) < MIN_COLOR_DISTANCE)
+            .map(|p| p.index)
+    }
+
+    /// Changes a player's color before the game starts,
+    /// as long as it can be told apart from every other player's.
+    pub fn recolor_player(
+        &mut self,
+        player: usize,
+        color: (u8, u8, u8),
+    ) -> Result<(), GamePlayError> {
+        if self.started_at.is_some() {
+            return Err(GamePlayError::ColorLocked);
+        }
+        if player >= self.players.len() {
+            return Err(GamePlayError::NonExistentPlayer { index: player });
+        }
+        if let Some(other) = self.color_clash(player, color) {
+            return Err(GamePlayError::ColorTooSimilar { player: other });
+        }
+
+        self.players[player].color = color;
+        Ok(())
+    }
+
+    /// Swaps in new rules before the game has started,
+    /// redealing every player's hand and clock to match them.
+    pub fn change_rules(&mut self, rules: GameRules) {
+        self.rules = rules;
+        let time_allowance = self.time_allowance();
+
+        for player in self.players.iter_mut() {
+            for tile in player.hand.0.drain(..) {
+                self.bag.return_tile(tile);
+            }
+            player.hand_capacity = self.rules.hand_capacity;
+            player.hand = Hand(
+                (0..player.hand_capacity)
+                    .map(|_| self.bag.draw_tile())
+                    .collect(),
+            );
+            player.allotted_time = time_allowance;
+            player.time_remaining = time_allowance;
+        }
+    }
+
+    /// The seed used to create this game's tile bag.
+    /// Passing it to `Game::new` reproduces the same draws for the same moves.
+    pub fn tile_seed(&self) -> u64 {
+        self.bag.current_seed()
+    }
+
     pub fn get_player(&self, player: usize) -> Option<&Player> {
         // TODO: Lookup player by `index` field rather than vec position
         self.players.get(player)
@@ -148,7 +260,9 @@ impl Game {
         self.started_at = Some(now);
 
         match self.rules.timing {
-            rules::Timing::PerPlayer { .. } | rules::Timing::None => {
+            rules::Timing::PerPlayer { .. }
+            | rules::Timing::Fischer { .. }
+            | rules::Timing::None => {
                 self.players[self.next_player.unwrap()].turn_starts_no_later_than = Some(now);
                 self.players[self.next_player.unwrap()].turn_starts_no_sooner_than = Some(now);
             }
@@ -169,6 +283,9 @@ impl Game {
         let mut most_overtime_player: Option<(Duration, usize)> = None;
 
         for (player_number, player) in self.players.iter().enumerate() {
+            if self.player_is_defeated(player_number) {
+                continue;
+            }
             let Some(mut time_remaining) = player.time_remaining else {
                 continue;
             };
@@ -221,6 +338,8 @@ impl Game {
     pub fn calculate_game_over(&mut self, current_player: Option<usize>) {
         let overtime_rule = match &self.rules.timing {
             rules::Timing::PerPlayer { overtime_rule, .. } => Some(overtime_rule),
+            // Running out of time on a Fischer clock loses the game
+            rules::Timing::Fischer { .. } => Some(&OvertimeRule::Elimination),
             _ => None,
         };
         if matches!(overtime_rule, Some(OvertimeRule::Elimination)) {
@@ -230,7 +349,7 @@ impl Game {
                         println!("{overtime_player} is over time! Defeating player.");
                     }
                     self.board.defeat_player(overtime_player);
-                    self.winner = Some((overtime_player + 1) % 2);
+                    self.settle_winner();
                 }
                 _ => {}
             }
@@ -280,44 +399,172 @@ impl Game {
                         }
                     }
 
-                    let winner = if remaining_players.len() == 1 {
-                        remaining_players.pop().unwrap()
-                    } else {
-                        0 // TODO: We need a draw mechanism
-                    };
+                    if remaining_players.len() == 1 {
+                        let winner = remaining_players.pop().unwrap();
 
-                    println!("{winner} wins on proximity!");
-                    (0..self.players.len())
-                        .filter(|p| *p != winner)
-                        .for_each(|p| self.board.defeat_player(p));
-                    self.winner = Some(winner);
+                        println!("{winner} wins on proximity!");
+                        (0..self.players.len())
+                            .filter(|p| *p != winner)
+                            .for_each(|p| self.board.defeat_player(p));
+                        self.winner = Some(winner);
+                    } else {
+                        println!("Players are tied on proximity, the game is a draw!");
+                        self.declare_draw();
+                    }
                 }
             }
         }
 
         // If any opponents were blocked out by this turn, they lose
-        for (player_index, _player) in self.players.iter().enumerate().filter(|(i, _)| {
-            if let Some(p) = current_player {
-                *i != p
-            } else {
-                true
-            }
-        }) {
-            if self
-                .board
-                .playable_positions(player_index, &self.rules.truncation)
-                .is_empty()
-            {
-                println!("{player_index} loses on being blocked!");
-                self.board.defeat_player(player_index);
-                self.winner = Some((player_index + 1) % 2);
-            }
+        let blocked_players: Vec<_> = (0..self.players.len())
+            .filter(|i| Some(*i) != current_player)
+            .filter(|i| !self.player_is_defeated(*i))
+            .filter(|i| {
+                self.board
+                    .playable_positions(*i, &self.rules.truncation)
+                    .is_empty()
+            })
+            .collect();
+        for player_index in blocked_players {
+            println!("{player_index} loses on being blocked!");
+            self.board.defeat_player(player_index);
+            self.settle_winner();
+        }
+
+        if !self.is_over() {
+            self.skip_defeated_next_player();
         }
     }
 
     pub fn resign_player(&mut self, resigning_player: usize) {
         self.board.defeat_player(resigning_player);
-        self.winner = Some((resigning_player + 1) % 2);
+        self.settle_winner();
+
+        if !self.is_over() {
+            self.skip_defeated_next_player();
+        }
+    }
+
+    /// Whether the player has been knocked out under this game's win condition
+    pub fn player_is_defeated(&self, player: usize) -> bool {
+        let has_played = self
+            .player_turn_count
+            .get(player)
+            .is_some_and(|turns| *turns > 0);
+        self.board
+            .player_is_defeated_by(player, &self.rules.win_condition, has_played)
+    }
+
+    /// Players who are still in the game
+    pub fn remaining_players(&self) -> Vec<usize> {
+        (0..self.players.len())
+            .filter(|p| !self.player_is_defeated(*p))
+            .collect()
+    }
+
+    /// Whether every player still in the game has passed since the board last changed,
+    /// which ends the game as a draw
+    pub fn all_players_passed(&self) -> bool {
+        self.consecutive_passes > 0 && self.consecutive_passes >= self.remaining_players().len()
+    }
+
+    /// Whether the game has gone `stalemate_turns` turns without progress,
+    /// which ends the game as a draw
+    pub fn is_stalemate(&self) -> bool {
+        self.rules
+            .stalemate_turns
+            .is_some_and(|turns| self.turns_without_progress >= turns)
+    }
+
+    /// Each player's closest approach to an enemy town, followed by how many towns are still standing.
+    /// A turn makes progress when it changes any of these.
+    fn progress_marker(&self) -> Vec<Option<usize>> {
+        let standing_towns = self
+            .board
+            .towns
+            .iter()
+            .filter(|t| {
+                matches!(
+                    self.board.get(**t),
+                    Ok(Square::Town {
+                        defeated: false,
+                        ..
+                    })
+                )
+            })
+            .count();
+
+        (0..self.players.len())
+            .map(|p| self.board.proximity_to_enemy_town(p).into_iter().min())
+            .chain(std::iter::once(Some(standing_towns)))
+            .collect()
+    }
+
+    /// How the game ended, if it has
+    pub fn outcome(&self) -> Option<GameOutcome> {
+        match (self.winner, self.drawn) {
+            (Some(winner), _) => Some(GameOutcome::Winner(winner)),
+            (None, true) => Some(GameOutcome::Draw),
+            (None, false) => None,
+        }
+    }
+
+    /// Whether the game has finished, either with a winner or as a draw
+    pub fn is_over(&self) -> bool {
+        self.outcome().is_some()
+    }
+
+    fn declare_draw(&mut self) {
+        if self.winner.is_none() {
+            self.drawn = true;
+        }
+    }
+
+    /// Declares a winner once every other player has been defeated,
+    /// or a draw if the last players were all defeated at once
+    fn settle_winner(&mut self) {
+        if let Some(winner) = Judge::winner(
+            &self.board,
+            &self.rules.win_condition,
+            &self.player_turn_count,
+        ) {
+            self.winner = Some(winner);
+        } else if self.remaining_players().is_empty() {
+            println!("Every player was defeated, the game is a draw!");
+            self.declare_draw();
+        }
+    }
+
+    /// Moves the turn along to the next player who hasn't been defeated
+    fn advance_next_player(&mut self) {
+        let player_count = self.players.len();
+        if let Some(mut next_player) = self.next_player {
+            for _ in 0..player_count {
+                next_player = (next_player + 1) % player_count;
+                if !self.player_is_defeated(next_player) {
+                    break;
+                }
+            }
+            self.next_player = Some(next_player);
+        }
+    }
+
+    /// If the player whose turn it is has been knocked out,
+    /// the turn passes to the next player still in the game
+    fn skip_defeated_next_player(&mut self) {
+        let Some(next_player) = self.next_player else {
+            return;
+        };
+        if !self.player_is_defeated(next_player) {
+            return;
+        }
+
+        self.advance_next_player();
+        if let Some(next_player) = self.next_player {
+            let now = now();
+            self.players[next_player].turn_starts_no_later_than = Some(now);
+            self.players[next_player].turn_starts_no_sooner_than = Some(now);
+        }
     }
 
     pub fn pause(&mut self) {
@@ -343,7 +590,7 @@ impl Game {
         self.paused = false;
 
         match self.rules.timing {
-            rules::Timing::PerPlayer { .. } => {
+            rules::Timing::PerPlayer { .. } | rules::Timing::Fischer { .. } => {
                 if let Some(next_player_index) = self.next_player {
                     let next_player = &mut self.players[next_player_index];
                     let paused_turn_delta = next_player.paused_turn_delta.unwrap_or_default();
@@ -380,36 +627,78 @@ impl Game {
         defender_dictionary: Option<&WordDict>,
         cached_word_judgements: Option<&mut HashMap<String, bool, xxh3::Xxh3Builder>>,
     ) -> Result<Option<usize>, String> {
-        if self.winner.is_some() {
+        let pending_turn = self.history.is_some().then(|| PendingTurn::capture(self));
+
+        let result = self.take_turn(
+            next_move,
+            attacker_dictionary,
+            defender_dictionary,
+            cached_word_judgements,
+        );
+
+        if let Some(pending_turn) = pending_turn {
+            // Journals are always taken, so that they stop growing after a rejected move
+            let snapshot = pending_turn.finish(self);
+            if let (Ok(_), Some(history)) = (&result, self.history.as_mut()) {
+                history.record(snapshot);
+            }
+        }
+
+        result
+    }
+
+    /// Returns this game as it would be after playing the first `n` of `moves`.
+    /// Playing is deterministic, so this can rebuild any point of a recorded game
+    /// from its starting state. Moves that are rejected are skipped, as they were originally.
+    pub fn replay_to(
+        &self,
+        moves: &[Move],
+        n: usize,
+        attacker_dictionary: Option<&WordDict>,
+        defender_dictionary: Option<&WordDict>,
+    ) -> Game {
+        let mut game = self.clone();
+        for next_move in moves.iter().take(n) {
+            if game.is_over() {
+                break;
+            }
+            _ = game.play_turn(
+                next_move.clone(),
+                attacker_dictionary,
+                defender_dictionary,
+                None,
+            );
+        }
+        game
+    }
+
+    fn take_turn(
+        &mut self,
+        next_move: Move,
+        attacker_dictionary: Option<&WordDict>,
+        defender_dictionary: Option<&WordDict>,
+        cached_word_judgements: Option<&mut HashMap<String, bool, xxh3::Xxh3Builder>>,
+    ) -> Result<Option<usize>, String> {
+        if self.is_over() {
             return Err("Game is already over".into());
         }
 
-        let player = match next_move {
-            Move::Place { player, .. } => player,
-            Move::Swap { player, .. } => player,
-        };
+        let player = next_move.player();
+        let is_pass = matches!(next_move, Move::Pass { .. });
+        let is_challenge = matches!(next_move, Move::Challenge { .. });
 
         self.calculate_game_over(Some(player));
-        if self.winner.is_some() {
+        if self.is_over() {
             return Ok(self.winner);
         }
 
-        match &self.rules.timing {
-            rules::Timing::Periodic { .. } => { /* All players can play */ }
-            _ => {
-                if player != self.next_player.unwrap() {
-                    return Err("Only the next player can play".into());
-                }
-            }
-        }
+        self.check_turn(player).map_err(|e| e.to_string())?;
 
-        if let Some(turn_start) = self.players[player].turn_starts_no_sooner_than {
-            if turn_start > now() {
-                return Err("Player's turn has not yet started".into());
-            }
-        } else {
-            return Err("Player's turn has not yet started".into());
-        }
+        let progress_before = self
+            .rules
+            .stalemate_turns
+            .is_some()
+            .then(|| self.progress_marker());
 
         self.recent_changes = match self.make_move(
             next_move,
@@ -424,6 +713,12 @@ impl Game {
             }
         };
 
+        if is_pass {
+            self.consecutive_passes += 1;
+        } else {
+            self.consecutive_passes = 0;
+        }
+
         // Track any new tiles that the player may have gained vision of from this turn
         {
             let seen = &mut self.players[player].seen_tiles;
@@ -431,6 +726,7 @@ impl Game {
             let newly_visible_board = self.board.filter_to_player(
                 player,
                 &self.rules.visibility,
+                &self.rules.fog_level,
                 &self.winner,
                 seen,
                 false,
@@ -438,32 +734,78 @@ impl Game {
 
             for (y, row) in newly_visible_board.squares.iter().enumerate() {
                 for (x, sq) in row.iter().enumerate() {
-                    if !matches!(sq, Square::Fog {}) {
-                        seen.insert(Coordinate::new(x, y));
+                    if !matches!(sq, Square::Fog {}) && seen.insert(Coordinate::new(x, y)) {
+                        if let Some(history) = self.history.as_mut() {
+                            history.revealed.push((player, Coordinate::new(x, y)));
+                        }
                     }
                 }
             }
         }
 
+        // A successful challenge doesn't use up the challenger's turn
+        if is_challenge
+            && self.recent_changes.iter().any(|change| {
+                matches!(
+                    change,
+                    Change::Battle(BattleReport {
+                        outcome: Outcome::AttackerWins(_),
+                        ..
+                    })
+                )
+            })
+        {
+            return Ok(None);
+        }
+
         self.turn_count += 1;
         self.player_turn_count[player] += 1;
 
+        let mut restored = vec![];
+        self.resolve_towns(player, &mut restored);
+        self.recent_changes.extend(restored);
+
+        if let Some(progress_before) = progress_before {
+            if self.progress_marker() == progress_before {
+                self.turns_without_progress += 1;
+            } else {
+                self.turns_without_progress = 0;
+            }
+        }
+
         // Check for winning via defeated towns or artifacts
-        if let Some(winner) = Judge::winner(&(self.board)) {
+        if let Some(winner) = Judge::winner(
+            &self.board,
+            &self.rules.win_condition,
+            &self.player_turn_count,
+        ) {
             self.winner = Some(winner);
             return Ok(Some(winner));
         }
 
         // Check for de-facto winning by blocking all moves
         self.calculate_game_over(Some(player));
-        if self.winner.is_some() {
+        if self.is_over() {
             return Ok(self.winner);
         }
 
-        if let Some(next_player) = self.next_player.as_mut() {
-            *next_player = (*next_player + 1) % self.players.len();
+        if self.all_players_passed() {
+            println!("Every player passed, the game is a draw!");
+            self.declare_draw();
+            return Ok(None);
+        }
+
+        if self.is_stalemate() {
+            println!(
+                "No progress was made for {} turns, the game is a draw!",
+                self.turns_without_progress
+            );
+            self.declare_draw();
+            return Ok(None);
         }
 
+        self.advance_next_player();
+
         let this_player = &mut self.players[player];
         if let Some(time_remaining) = &mut this_player.time_remaining {
             let turn_duration = now().saturating_sub(
@@ -475,6 +817,11 @@ impl Game {
 
             *time_remaining -= Duration::seconds(turn_duration as i64);
 
+            // We only reach this point once the move has been validated and played
+            if let rules::Timing::Fischer { increment, .. } = &self.rules.timing {
+                *time_remaining += Duration::seconds(*increment as i64);
+            }
+
             let overtime_rule = match &self.rules.timing {
                 rules::Timing::PerPlayer { overtime_rule, .. } => Some(overtime_rule),
                 _ => None,
@@ -543,6 +890,8 @@ impl Game {
         defender_dictionary: Option<&WordDict>,
         cached_word_judgements: Option<&mut HashMap<String, bool, xxh3::Xxh3Builder>>,
     ) -> Result<Vec<Change>, GamePlayError> {
+        self.validate_move(&game_move)?;
+
         let mut changes = vec![];
 
         match game_move {
@@ -551,42 +900,21 @@ impl Game {
                 tile,
                 position: player_reported_position,
             } => {
-                if self.get_player(player).is_none() {
-                    return Err(GamePlayError::NonExistentPlayer { index: player });
-                }
-
                 let position = self.board.map_player_coord_to_game(
                     player,
                     player_reported_position,
                     &self.rules.visibility,
+                    &self.rules.fog_level,
                     &self.players[player].seen_tiles,
                 );
 
-                if let Square::Occupied { .. } = self.board.get(position)? {
-                    return Err(GamePlayError::OccupiedPlace);
-                }
-
-                if !self.board.neighbouring_squares(position).iter().any(
-                    |&(_, square)| match square {
-                        Square::Occupied { player: p, .. } => p == player,
-                        Square::Artifact { player: p, .. } => p == player,
-                        _ => false,
-                    },
-                ) {
-                    return Err(GamePlayError::NonAdjacentPlace);
-                }
-
-                if !self.players[player].has_tile(tile) {
-                    return Err(GamePlayError::PlayerDoesNotHaveTile { player, tile });
-                }
-
                 changes.push(Change::Board(BoardChange {
                     detail: self
                         .board
                         .set(position, player, tile, attacker_dictionary)?,
                     action: BoardChangeAction::Added,
                 }));
-                changes.push(self.players[player].use_tile(tile, &mut self.bag)?);
+                changes.push(self.players[player].use_tile(Game::hand_tile(tile), &mut self.bag)?);
 
                 self.resolve_attack(
                     player,
@@ -599,6 +927,8 @@ impl Game {
 
                 self.players[player].swap_count = 0;
 
+                self.resolve_obelisks(&mut changes);
+
                 Ok(changes)
             }
             Move::Swap {
@@ -610,37 +940,20 @@ impl Game {
                         player_index,
                         player_reported_positions[0],
                         &self.rules.visibility,
+                        &self.rules.fog_level,
                         &self.players[player_index].seen_tiles,
                     ),
                     self.board.map_player_coord_to_game(
                         player_index,
                         player_reported_positions[1],
                         &self.rules.visibility,
+                        &self.rules.fog_level,
                         &self.players[player_index].seen_tiles,
                     ),
                 ];
 
                 let player = &mut self.players[player_index];
-                let swap_rules = match &self.rules.swapping {
-                    rules::Swapping::Contiguous(rules) => Some(rules),
-                    rules::Swapping::Universal(rules) => Some(rules),
-                    rules::Swapping::None => None,
-                };
-
-                match swap_rules {
-                    Some(rules::SwapPenalty::Disallowed { allowed_swaps }) => {
-                        let player_swaps = player.swap_count;
-                        if player_swaps >= *allowed_swaps {
-                            return Err(GamePlayError::TooManySwaps {
-                                count: match player_swaps + 1 {
-                                    2 => "twice".into(),
-                                    n => format!("{n} times"),
-                                },
-                            });
-                        }
-                    }
-                    _ => {}
-                }
+                let swap_rules = self.rules.swapping.penalty();
 
                 let mut swap_result = self.board.swap(
                     player_index,
@@ -650,6 +963,7 @@ impl Game {
                 )?;
 
                 player.swap_count += 1;
+                player.swaps_used += 1;
 
                 match swap_rules {
                     Some(rules::SwapPenalty::Time {
@@ -678,14 +992,677 @@ impl Game {
                             }
                         }
                     }
-                    Some(rules::SwapPenalty::Disallowed { .. }) => {
+                    Some(
+                        rules::SwapPenalty::Disallowed { .. } | rules::SwapPenalty::Limited { .. },
+                    ) => {
                         // Handled before move was made
                     }
                     None => {}
                 }
 
+                self.resolve_obelisks(&mut swap_result);
+
                 Ok(swap_result)
             }
+            Move::Challenge {
+                player,
+                position: player_reported_position,
+            } => {
+                let position = self.board.map_player_coord_to_game(
+                    player,
+                    player_reported_position,
+                    &self.rules.visibility,
+                    &self.rules.fog_level,
+                    &self.players[player].seen_tiles,
+                );
+
+                Ok(self.resolve_challenge(
+                    player,
+                    position,
+                    defender_dictionary,
+                    cached_word_judgements,
+                ))
+            }
+            Move::Pass { .. } => Ok(changes),
+            Move::Exchange { player, tile } => {
+                changes.push(self.players[player].exchange_tile(tile, &mut self.bag)?);
+                self.players[player].swap_count = 0;
+
+                Ok(changes)
+            }
+        }
+    }
+
+    /// Blanks are placed as the lowercase ASCII form of their assigned letter,
+    /// but are held in the hand as a `BLANK_TILE`
+    fn hand_tile(placed_tile: char) -> char {
+        if is_placed_blank(placed_tile) {
+            BLANK_TILE
+        } else {
+            placed_tile
+        }
+    }
+
+    /// Checks that `player` is allowed to move right now,
+    /// without looking at the move itself (see `validate_move`).
+    pub fn check_turn(&self, player: usize) -> Result<(), GamePlayError> {
+        if self.is_over() {
+            return Err(GamePlayError::GameOver);
+        }
+
+        if player >= self.players.len() {
+            return Err(GamePlayError::NonExistentPlayer { index: player });
+        }
+
+        match &self.rules.timing {
+            rules::Timing::Periodic { .. } => { /* All players can play */ }
+            _ => {
+                if Some(player) != self.next_player {
+                    return Err(GamePlayError::NotYourTurn { player });
+                }
+            }
+        }
+
+        match self.players[player].turn_starts_no_sooner_than {
+            Some(turn_start) if turn_start <= now() => Ok(()),
+            _ => Err(GamePlayError::TurnNotStarted { player }),
+        }
+    }
+
+    /// How many more swaps the player can make this game,
+    /// if the rules give each player a fixed number of them
+    pub fn remaining_swaps(&self, player: usize) -> Option<usize> {
+        let player = self.players.get(player)?;
+        match self.rules.swapping.penalty()? {
+            rules::SwapPenalty::Limited { swaps_per_game } => {
+                Some(swaps_per_game.saturating_sub(player.swaps_used))
+            }
+            rules::SwapPenalty::Time { .. } | rules::SwapPenalty::Disallowed { .. } => None,
+        }
+    }
+
+    /// Every tile the player could place and where, in no particular order.
+    /// A blank in hand is offered once per letter it could be assigned, as a lowercase tile.
+    pub fn legal_placements(&self, player: usize) -> Vec<(Coordinate, char)> {
+        let Some(player_state) = self.players.get(player) else {
+            return vec![];
+        };
+
+        let mut tiles: Vec<char> = vec![];
+        for &tile in player_state.hand.iter() {
+            if tile == BLANK_TILE {
+                tiles.extend('a'..='z');
+            } else {
+                tiles.push(tile);
+            }
+        }
+        tiles.sort();
+        tiles.dedup();
+
+        let mut positions = self
+            .board
+            .playable_positions(player, &self.rules.truncation);
+        if self.places_freely(player) {
+            positions.extend(self.board.own_land(player));
+        }
+
+        positions
+            .into_iter()
+            .flat_map(|position| tiles.iter().map(move |&tile| (position, tile)))
+            .collect()
+    }
+
+    /// Whether the player can place anywhere on their own land, rather than beside their pieces
+    fn places_freely(&self, player: usize) -> bool {
+        self.rules.first_placement == rules::FirstPlacement::OwnLand
+            && !self.board.has_tiles(player)
+    }
+
+    /// Every pair of the player's tiles that they are allowed to swap, in no particular order
+    pub fn legal_swaps(&self, player: usize) -> Vec<[Coordinate; 2]> {
+        if self.get_player(player).is_none() || self.remaining_swaps(player) == Some(0) {
+            return vec![];
+        }
+
+        let own_tiles: Vec<_> = self
+            .board
+            .squares
+            .iter()
+            .enumerate()
+            .flat_map(|(y, row)| {
+                row.iter()
+                    .enumerate()
+                    .filter_map(move |(x, square)| match square {
+                        Square::Occupied { player: owner, .. } if *owner == player => {
+                            Some(Coordinate::new(x, y))
+                        }
+                        _ => None,
+                    })
+            })
+            .collect();
+
+        own_tiles
+            .iter()
+            .enumerate()
+            .flat_map(|(i, from)| own_tiles[i + 1..].iter().map(move |to| [*from, *to]))
+            .filter(|positions| {
+                self.validate_move(&Move::Swap {
+                    player,
+                    positions: *positions,
+                })
+                .is_ok()
+            })
+            .collect()
+    }
+
+    /// Runs every check that `make_move` would, without applying the move
+    /// or modifying the board, hands, or bag.
+    pub fn validate_move(&self, game_move: &Move) -> Result<(), GamePlayError> {
+        match *game_move {
+            Move::Place {
+                player,
+                tile,
+                position: player_reported_position,
+            } => {
+                let Some(player_state) = self.get_player(player) else {
+                    return Err(GamePlayError::NonExistentPlayer { index: player });
+                };
+
+                let position = self.board.map_player_coord_to_game(
+                    player,
+                    player_reported_position,
+                    &self.rules.visibility,
+                    &self.rules.fog_level,
+                    &player_state.seen_tiles,
+                );
+
+                if let Square::Occupied { .. } = self.board.get(position)? {
+                    return Err(GamePlayError::OccupiedPlace);
+                }
+
+                let beside_own_piece = self.board.neighbouring_squares(position).iter().any(
+                    |&(_, square)| match square {
+                        Square::Occupied { player: p, .. } => p == player,
+                        Square::Artifact { player: p, .. } => p == player,
+                        _ => false,
+                    },
+                );
+                let free_placement =
+                    self.places_freely(player) && self.board.is_own_land(player, position);
+                if !beside_own_piece && !free_placement {
+                    return Err(GamePlayError::NonAdjacentPlace);
+                }
+
+                if tile == BLANK_TILE {
+                    return Err(GamePlayError::UnassignedBlank);
+                }
+
+                let hand_tile = Game::hand_tile(tile);
+                if !player_state.has_tile(hand_tile) {
+                    return Err(GamePlayError::PlayerDoesNotHaveTile {
+                        player,
+                        tile: hand_tile,
+                    });
+                }
+
+                self.board.validate_set(position, player)
+            }
+            Move::Swap {
+                player: player_index,
+                positions: player_reported_positions,
+            } => {
+                let Some(player) = self.get_player(player_index) else {
+                    return Err(GamePlayError::NonExistentPlayer {
+                        index: player_index,
+                    });
+                };
+
+                let positions = player_reported_positions.map(|position| {
+                    self.board.map_player_coord_to_game(
+                        player_index,
+                        position,
+                        &self.rules.visibility,
+                        &self.rules.fog_level,
+                        &player.seen_tiles,
+                    )
+                });
+
+                match self.rules.swapping.penalty() {
+                    Some(rules::SwapPenalty::Disallowed { allowed_swaps }) => {
+                        let player_swaps = player.swap_count;
+                        if player_swaps >= *allowed_swaps {
+                            return Err(GamePlayError::TooManySwaps {
+                                count: match player_swaps + 1 {
+                                    2 => "twice".into(),
+                                    n => format!("{n} times"),
+                                },
+                            });
+                        }
+                    }
+                    Some(rules::SwapPenalty::Limited { swaps_per_game }) => {
+                        if player.swaps_used >= *swaps_per_game {
+                            return Err(GamePlayError::NoSwapsRemaining);
+                        }
+                    }
+                    Some(rules::SwapPenalty::Time { .. }) | None => {}
+                }
+
+                self.board
+                    .validate_swap(player_index, positions, &self.rules.swapping)
+                    .map(|_| ())
+            }
+            Move::Challenge {
+                player: player_index,
+                position: player_reported_position,
+            } => {
+                let Some(player) = self.get_player(player_index) else {
+                    return Err(GamePlayError::NonExistentPlayer {
+                        index: player_index,
+                    });
+                };
+
+                if !self.rules.challenge_mode {
+                    return Err(GamePlayError::ChallengesDisabled);
+                }
+
+                let position = self.board.map_player_coord_to_game(
+                    player_index,
+                    player_reported_position,
+                    &self.rules.visibility,
+                    &self.rules.fog_level,
+                    &player.seen_tiles,
+                );
+
+                // Players can only challenge the tiles they can currently see.
+                // Fog reveals whole words once any of their tiles are visible,
+                // so every tile of a challengeable word is known to the challenger.
+                let visible_board = self.board.filter_to_player(
+                    player_index,
+                    &self.rules.visibility,
+                    &self.rules.fog_level,
+                    &self.winner,
+                    &player.seen_tiles,
+                    false,
+                );
+                match visible_board.get(position)? {
+                    Square::Occupied {
+                        player: owner,
+                        foggy: false,
+                        ..
+                    } if owner != player_index => Ok(()),
+                    _ => Err(GamePlayError::InvalidChallenge),
+                }
+            }
+            Move::Pass {
+                player: player_index,
+            } => {
+                if self.get_player(player_index).is_none() {
+                    return Err(GamePlayError::NonExistentPlayer {
+                        index: player_index,
+                    });
+                }
+
+                if !self.rules.allow_passing {
+                    return Err(GamePlayError::PassingDisabled);
+                }
+
+                Ok(())
+            }
+            Move::Exchange {
+                player: player_index,
+                tile,
+            } => {
+                let Some(player) = self.get_player(player_index) else {
+                    return Err(GamePlayError::NonExistentPlayer {
+                        index: player_index,
+                    });
+                };
+
+                if !self.rules.allow_exchanges {
+                    return Err(GamePlayError::ExchangesDisabled);
+                }
+
+                if !player.has_tile(tile) {
+                    return Err(GamePlayError::PlayerDoesNotHaveTile {
+                        player: player_index,
+                        tile,
+                    });
+                }
+
+                if self.bag.remaining().is_empty() {
+                    return Err(GamePlayError::EmptyBag);
+                }
+
+                Ok(())
+            }
+        }
+    }
+
+    /// Predicts the battle a placement would start, without changing the game.
+    /// Squares hidden from the placing player are left out, so the preview
+    /// only uses what that player could already see.
+    pub fn preview_placement(
+        &self,
+        game_move: &Move,
+        attacker_dictionary: Option<&WordDict>,
+        defender_dictionary: Option<&WordDict>,
+    ) -> Result<BattlePreview, GamePlayError> {
+        let Move::Place {
+            player,
+            tile,
+            position: player_reported_position,
+        } = *game_move
+        else {
+            return Err(GamePlayError::NotAPlacement);
+        };
+        self.validate_move(game_move)?;
+
+        let seen_tiles = &self.players[player].seen_tiles;
+        let position = self.board.map_player_coord_to_game(
+            player,
+            player_reported_position,
+            &self.rules.visibility,
+            &self.rules.fog_level,
+            seen_tiles,
+        );
+        let mut board = self.board.filter_to_player(
+            player,
+            &self.rules.visibility,
+            &self.rules.fog_level,
+            &self.winner,
+            seen_tiles,
+            false,
+        );
+        board.set(position, player, tile, attacker_dictionary)?;
+
+        let (attackers, defenders) = board.collect_combanants(player, position, &self.rules);
+        let attacking_words = board.word_strings(&attackers)?;
+        let defending_words = board.word_strings(&defenders)?;
+
+        let battle = if self.rules.challenge_mode {
+            self.judge.unchallenged_battle(
+                attacking_words.clone(),
+                defending_words,
+                &self.rules.battle_rules,
+                &self.rules.win_condition,
+                attacker_dictionary,
+                None,
+            )
+        } else {
+            self.judge.battle(
+                attacking_words.clone(),
+                defending_words,
+                &self.rules.battle_rules,
+                &self.rules.win_condition,
+                attacker_dictionary,
+                defender_dictionary,
+                None,
+            )
+        };
+
+        if let Some(battle) = battle {
+            return Ok(BattlePreview {
+                attackers: battle.attackers,
+                defenders: battle.defenders,
+                outcome: Some(battle.outcome),
+                reason: battle.reason,
+            });
+        }
+
+        // Without anything to fight, the placed words are still judged so they can be shown
+        let attackers = attacking_words
+            .into_iter()
+            .map(|word| {
+                let valid = self.judge.valid(
+                    &word,
+                    &self.rules.win_condition,
+                    attacker_dictionary,
+                    None,
+                    &mut None,
+                );
+                let is_valid = valid.is_some();
+                BattleWord {
+                    original_word: word.clone(),
+                    resolved_word: valid.unwrap_or(word),
+                    meanings: None,
+                    valid: Some(is_valid),
+                }
+            })
+            .collect();
+
+        Ok(BattlePreview {
+            attackers,
+            defenders: vec![],
+            outcome: None,
+            reason: None,
+        })
+    }
+
+    /// Judges every word through the challenged tile, removing any that are invalid.
+    /// The result is reported as a battle with no attackers, which the attacker
+    /// wins if any challenged word was invalid.
+    fn resolve_challenge(
+        &mut self,
+        challenger: usize,
+        position: Coordinate,
+        defender_dictionary: Option<&WordDict>,
+        mut cached_word_judgements: Option<&mut HashMap<String, bool, xxh3::Xxh3Builder>>,
+    ) -> Vec<Change> {
+        let challenged = self.board.get_words(position);
+        let challenged_words = self
+            .board
+            .word_strings(&challenged)
+            .expect("Words were just found and should be valid");
+
+        let defenders: Vec<_> = challenged_words
+            .into_iter()
+            .map(|word| {
+                let valid = self.judge.valid(
+                    &word,
+                    &self.rules.win_condition,
+                    defender_dictionary,
+                    None,
+                    &mut cached_word_judgements,
+                );
+                BattleWord {
+                    original_word: word.clone(),
+                    valid: Some(valid.is_some()),
+                    meanings: None,
+                    resolved_word: valid.unwrap_or(word),
+                }
+            })
+            .collect();
+
+        let losers: Vec<_> = defenders
+            .iter()
+            .enumerate()
+            .filter(|(_, word)| word.valid == Some(false))
+            .map(|(index, _)| index)
+            .collect();
+
+        let battle = BattleReport {
+            battle_number: Some(self.battle_count),
+            attackers: vec![],
+            defenders,
+            outcome: if losers.is_empty() {
+                Outcome::DefenderWins
+            } else {
+                Outcome::AttackerWins(losers.clone())
+            },
+            reason: Some(if losers.is_empty() {
+                BattleReason::ValidDefenders
+            } else {
+                BattleReason::InvalidDefenders
+            }),
+        };
+        self.battle_count += 1;
+
+        if let Some(word_history) = self.word_history.as_mut() {
+            let defender_owners: Vec<_> = challenged
+                .iter()
+                .map(|word| {
+                    self.board
+                        .word_owner(word)
+                        .expect("Challenged words should belong to a player")
+                })
+                .collect();
+            word_history.record_battle(self.turn_count, challenger, &battle, &defender_owners);
+        }
+
+        let mut changes = vec![Change::Battle(battle)];
+
+        let mut defeated: Vec<_> = losers
+            .into_iter()
+            .flat_map(|index| challenged[index].iter().copied())
+            .collect();
+        // Tiles shared by two invalid words should only be removed once
+        defeated.sort();
+        defeated.dedup();
+
+        changes.extend(defeated.into_iter().flat_map(|square| {
+            if let Ok(Square::Occupied { tile, .. }) = self.board.get(square) {
+                self.bag.return_tile(tile);
+            }
+            self.board.clear(square, defender_dictionary).map(|detail| {
+                Change::Board(BoardChange {
+                    detail,
+                    action: BoardChangeAction::Defeated,
+                })
+            })
+        }));
+
+        match self.rules.truncation {
+            rules::Truncation::Root => changes.extend(
+                self.board
+                    .truncate(&mut self.bag, defender_dictionary)
+                    .into_iter(),
+            ),
+            rules::Truncation::Larger => unimplemented!(),
+            rules::Truncation::None => {}
+        }
+
+        changes
+    }
+
+    /// Hands each capturable obelisk to the only player with tiles beside it.
+    /// A contested obelisk stays with its holder while they remain adjacent,
+    /// and an obelisk with no neighbouring tiles is released.
+    fn resolve_obelisks(&mut self, changes: &mut Vec<Change>) {
+        if self.rules.obelisks != rules::ObeliskRule::Capturable {
+            return;
+        }
+
+        for obelisk in self.board.obelisks.clone() {
+            let Ok(Square::Obelisk { foggy, holder }) = self.board.get(obelisk) else {
+                continue;
+            };
+
+            let mut neighbours: Vec<_> = self
+                .board
+                .neighbouring_squares(obelisk)
+                .into_iter()
+                .filter_map(|(_, square)| match square {
+                    Square::Occupied { player, .. } => Some(player),
+                    _ => None,
+                })
+                .collect();
+            neighbours.sort();
+            neighbours.dedup();
+
+            let new_holder = match neighbours.as_slice() {
+                [] => None,
+                [sole] => Some(*sole),
+                contested => holder.filter(|h| contested.contains(h)),
+            };
+
+            if new_holder == holder {
+                continue;
+            }
+
+            let square = Square::Obelisk {
+                foggy,
+                holder: new_holder,
+            };
+            _ = self.board.set_square(obelisk, square);
+            changes.push(Change::Board(BoardChange {
+                detail: BoardChangeDetail {
+                    square,
+                    coordinate: obelisk,
+                },
+                action: BoardChangeAction::Captured,
+            }));
+
+            if let Some(new_holder) = new_holder {
+                let bonus = self.bag.draw_tile();
+                changes.push(self.players[new_holder].add_special_tile(bonus));
+            }
+        }
+    }
+
+    /// Restores any of `player`'s defeated towns that have recovered under the `TownRecovery` rule,
+    /// either by this turn's placement landing beside them or by enough turns having passed.
+    fn resolve_towns(&mut self, player: usize, changes: &mut Vec<Change>) {
+        if self.rules.town_recovery == rules::TownRecovery::Permanent {
+            return;
+        }
+
+        let placed: Vec<_> = self
+            .recent_changes
+            .iter()
+            .filter_map(|change| match change {
+                Change::Board(BoardChange {
+                    detail: BoardChangeDetail { coordinate, .. },
+                    action: BoardChangeAction::Added,
+                }) => Some(*coordinate),
+                _ => None,
+            })
+            // The placed tile has to survive its battle to hold the town
+            .filter(|coordinate| {
+                matches!(
+                    self.board.get(*coordinate),
+                    Ok(Square::Occupied { player: p, .. }) if p == player
+                )
+            })
+            .collect();
+        let turns_taken = self.player_turn_count[player];
+
+        let recovered: Vec<_> = self
+            .fallen_towns
+            .iter()
+            .filter(|(town, fell_at)| {
+                let still_fallen = matches!(
+                    self.board.get(*town),
+                    Ok(Square::Town { player: p, defeated: true, .. }) if p == player
+                );
+                if !still_fallen {
+                    return false;
+                }
+                match self.rules.town_recovery {
+                    rules::TownRecovery::Permanent => false,
+                    rules::TownRecovery::Adjacent => placed
+                        .iter()
+                        .any(|tile| tile.neighbors_4_iter().any(|n| n == *town)),
+                    rules::TownRecovery::AfterTurns(turns) => turns_taken >= fell_at + turns,
+                }
+            })
+            .map(|(town, _)| *town)
+            .collect();
+
+        for town in recovered {
+            self.fallen_towns.retain(|(fallen, _)| *fallen != town);
+            let square = Square::Town {
+                player,
+                defeated: false,
+                foggy: false,
+            };
+            _ = self.board.set_square(town, square);
+            changes.push(Change::Board(BoardChange {
+                detail: BoardChangeDetail {
+                    square,
+                    coordinate: town,
+                },
+                action: BoardChangeAction::Restored,
+            }));
         }
     }
 
@@ -715,18 +1692,43 @@ impl Game {
             .word_strings(&defenders)
             .expect("Words were just found and should be valid");
 
-        if let Some(mut battle) = self.judge.battle(
-            attacking_words,
-            defending_words,
-            &self.rules.battle_rules,
-            &self.rules.win_condition,
-            attacker_dictionary,
-            defender_dictionary,
-            cached_word_judgements,
-        ) {
+        let battle = if self.rules.challenge_mode {
+            self.judge.unchallenged_battle(
+                attacking_words,
+                defending_words,
+                &self.rules.battle_rules,
+                &self.rules.win_condition,
+                attacker_dictionary,
+                cached_word_judgements,
+            )
+        } else {
+            self.judge.battle(
+                attacking_words,
+                defending_words,
+                &self.rules.battle_rules,
+                &self.rules.win_condition,
+                attacker_dictionary,
+                defender_dictionary,
+                cached_word_judgements,
+            )
+        };
+
+        if let Some(mut battle) = battle {
             battle.battle_number = Some(self.battle_count);
             self.battle_count += 1;
 
+            if let Some(word_history) = self.word_history.as_mut() {
+                let defender_owners: Vec<_> = defenders
+                    .iter()
+                    .map(|word| {
+                        self.board
+                            .word_owner(word)
+                            .expect("Defending words should belong to a player")
+                    })
+                    .collect();
+                word_history.record_battle(self.turn_count, player, &battle, &defender_owners);
+            }
+
             match battle.outcome.clone() {
                 Outcome::DefenderWins => {
                     changes.extend(defenders.iter().flatten().map(|coordinate| {
@@ -800,6 +1802,8 @@ impl Game {
                                         foggy: false,
                                     },
                                 );
+                                self.fallen_towns
+                                    .push((*square, self.player_turn_count[player]));
                             }
                             Ok(Square::Artifact { player, .. }) => {
                                 _ = self.board.set_square(
@@ -824,36 +1828,35 @@ impl Game {
                             })
                     }));
 
-                    // explode adjacent letters belonging to opponents
-                    changes.extend(self.board.neighbouring_squares(position).iter().flat_map(
-                        |neighbour| {
-                            if let (
-                                coordinate,
-                                Square::Occupied {
-                                    player: owner,
-                                    tile,
-                                    ..
-                                },
-                            ) = neighbour
-                            {
-                                if *owner != player {
-                                    self.bag.return_tile(*tile);
-                                    return self.board.clear(*coordinate, attacker_dictionary).map(
-                                        |detail| {
-                                            Change::Board(BoardChange {
-                                                detail,
-                                                action: BoardChangeAction::Exploded,
-                                            })
-                                        },
-                                    );
-                                }
-                            }
-                            None
-                        },
-                    ));
+                    // explode nearby letters belonging to opponents
+                    let exploded =
+                        self.board
+                            .explosion_area(position, player, &self.rules.explosions);
+                    changes.extend(exploded.into_iter().flat_map(|coordinate| {
+                        if let Ok(Square::Occupied { tile, .. }) = self.board.get(coordinate) {
+                            self.bag.return_tile(tile);
+                        }
+                        self.board
+                            .clear(coordinate, attacker_dictionary)
+                            .map(|detail| {
+                                Change::Board(BoardChange {
+                                    detail,
+                                    action: BoardChangeAction::Exploded,
+                                })
+                            })
+                    }));
                 }
             }
             changes.push(Change::Battle(battle));
+        } else if let Some(word_history) = self.word_history.as_mut() {
+            // A lone tile is found as the same one letter word along both axes
+            let mut formed_coords = attackers.clone();
+            formed_coords.dedup();
+            let formed_words = self
+                .board
+                .word_strings(&formed_coords)
+                .expect("Words were just found and should be valid");
+            word_history.record_unopposed(self.turn_count, player, formed_words);
         }
 
         match self.rules.truncation {
@@ -894,6 +1897,7 @@ impl Game {
         let visible_board = self.board.filter_to_player(
             player_index,
             &self.rules.visibility,
+            &self.rules.fog_level,
             &self.winner,
             seen,
             true,
@@ -905,6 +1909,7 @@ impl Game {
             &visible_board,
             player_index,
             &self.rules.visibility,
+            &self.rules.fog_level,
             &self.winner,
             seen,
         );
@@ -8,18 +8,19 @@ use oorandom::Rand32;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    board::{Board, BoardDistances, Coordinate, Square, SquareValidity},
+    board::{Board, BoardDistances, Coordinate, Square, SquareValidity, MAX_BOARD_DIMENSION},
+    error::BoardParseError,
     game::Game,
 };
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum ArtifactType {
     IslandV1,
     Coastal,
     Continental,
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum Symmetry {
     SmoothTwoFoldRotational,
     TwoFoldRotational,
@@ -33,20 +34,20 @@ pub struct BoardElements {
     pub obelisk: bool,
 }
 
-#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
 pub struct BoardNoiseParams {
     pub dispersion: [f64; 2],
     pub island_influence: f64,
     pub symmetric: Symmetry,
 }
 
-#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
 pub struct WaterLayer {
     pub params: BoardNoiseParams,
     pub density: f64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct BoardParams {
     pub land_layer: BoardNoiseParams,
     pub water_layer: Option<WaterLayer>,
@@ -121,6 +122,49 @@ impl BoardParams {
     }
 }
 
+/// Overrides on top of a board generation, used to create board variants
+/// that differ from the standard daily layout.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BoardConfig {
+    pub land_dimensions: [usize; 2],
+    pub towns_per_player: u32,
+    /// Roughly the fraction of inland squares that become lakes
+    pub water_ratio: f64,
+}
+
+impl BoardConfig {
+    pub fn blitz() -> Self {
+        Self {
+            land_dimensions: [7, 8],
+            towns_per_player: 1,
+            water_ratio: 0.0,
+        }
+    }
+
+    pub fn epic() -> Self {
+        Self {
+            land_dimensions: [14, 16],
+            towns_per_player: 3,
+            water_ratio: 0.2,
+        }
+    }
+
+    fn water_layer(&self, symmetric: Symmetry) -> Option<WaterLayer> {
+        if self.water_ratio <= 0.0 {
+            return None;
+        }
+
+        Some(WaterLayer {
+            params: BoardNoiseParams {
+                dispersion: [10.0, 10.0],
+                island_influence: 0.0,
+                symmetric,
+            },
+            density: self.water_ratio,
+        })
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum PreviousBoardResize {
     Enlarged,
@@ -133,11 +177,16 @@ pub struct BoardSeed {
     pub seed: u32,
     pub day: Option<u32>,
     pub params: BoardParams,
+    pub config: Option<BoardConfig>,
+    /// Reroll until the board is point-symmetric between the two players
+    pub enforce_symmetry: bool,
     pub current_iteration: usize,
     pub width_resize_state: Option<PreviousBoardResize>,
     pub height_resize_state: Option<PreviousBoardResize>,
     pub water_level: f64,
     pub max_attempts: usize,
+    /// Seeds for boards wider or taller than this many squares are refused
+    pub max_dimension: usize,
 }
 
 impl BoardSeed {
@@ -148,11 +197,14 @@ impl BoardSeed {
             seed,
             day: None,
             params,
+            config: None,
+            enforce_symmetry: false,
             current_iteration: 0,
             width_resize_state: None,
             height_resize_state: None,
             water_level: 0.5,
             max_attempts: 10000, // Default to trying for a very long time (try not to panic for a user)
+            max_dimension: MAX_BOARD_DIMENSION,
         }
     }
 
@@ -163,11 +215,14 @@ impl BoardSeed {
             seed,
             day: None,
             params,
+            config: None,
+            enforce_symmetry: false,
             current_iteration: 0,
             width_resize_state: None,
             height_resize_state: None,
             water_level: 0.5,
             max_attempts: 10000, // Default to trying for a very long time (try not to panic for a user)
+            max_dimension: MAX_BOARD_DIMENSION,
         }
     }
 
@@ -181,6 +236,51 @@ impl BoardSeed {
         self
     }
 
+    pub fn config(mut self, config: BoardConfig) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    pub fn enforce_symmetry(mut self) -> Self {
+        self.enforce_symmetry = true;
+        self
+    }
+
+    pub fn max_dimension(mut self, max_dimension: usize) -> Self {
+        self.max_dimension = max_dimension;
+        self
+    }
+
+    /// Rejects seeds whose boards would be wider or taller than `max_dimension` squares.
+    /// Measured on the canvas the board is generated within, including its ring of water.
+    pub fn check_dimensions(&self) -> Result<(), BoardParseError> {
+        let (land, canvas) = match &self.config {
+            Some(config) => (
+                config.land_dimensions,
+                config.land_dimensions.map(|d| d.saturating_mul(2)),
+            ),
+            None => (self.params.land_dimensions, self.params.canvas_dimensions),
+        };
+        let width = land[0].max(canvas[0]).saturating_add(2);
+        let height = land[1].max(canvas[1]).saturating_add(2);
+
+        if width > self.max_dimension || height > self.max_dimension {
+            return Err(BoardParseError::TooLarge {
+                width,
+                height,
+                max: self.max_dimension,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Seeds the tile bag for games on this board, so that every game from the
+    /// same seed (such as a daily puzzle) deals the same tiles in the same order
+    pub fn tile_seed(&self) -> u64 {
+        self.seed as u64
+    }
+
     fn internal_reroll(&mut self) {
         let mut rng = Rand32::new(self.seed as u64);
         let r = rng.rand_u32();
@@ -209,40 +309,77 @@ pub struct BoardGenerationResult {
     pub iterations: usize,
 }
 
+#[derive(Debug)]
+pub enum BoardGenerationError {
+    /// The seed asks for a board larger than its `max_dimension`, so nothing was generated
+    TooLarge(BoardParseError),
+    /// No attempt produced a usable board, leaving the last one that was tried
+    Unsolvable(BoardGenerationResult),
+}
+
 pub fn generate_board(
     mut board_seed: BoardSeed,
-) -> Result<BoardGenerationResult, BoardGenerationResult> {
+) -> Result<BoardGenerationResult, BoardGenerationError> {
+    // Refuse outright, rather than allocating a canvas that could exhaust memory
+    board_seed
+        .check_dimensions()
+        .map_err(BoardGenerationError::TooLarge)?;
+
     let BoardSeed {
         generation: _,
         seed,
         day: _,
+        config,
+        enforce_symmetry,
         current_iteration,
         width_resize_state,
         height_resize_state: _,
         water_level,
         max_attempts,
+        max_dimension: _,
         params:
             BoardParams {
-                land_layer,
-                water_layer,
-                land_dimensions: ideal_land_dimensions,
-                canvas_dimensions,
+                mut land_layer,
+                mut water_layer,
+                land_dimensions: mut ideal_land_dimensions,
+                mut canvas_dimensions,
                 maximum_town_density,
                 maximum_town_distance,
                 minimum_choke,
-                artifact_type,
+                mut artifact_type,
                 ideal_artifact_extremity,
                 elements,
             },
     } = board_seed;
 
+    if enforce_symmetry {
+        if matches!(land_layer.symmetric, Symmetry::Asymmetric) {
+            land_layer.symmetric = Symmetry::SmoothTwoFoldRotational;
+        }
+        if let Some(water_layer) = water_layer.as_mut() {
+            water_layer.params.symmetric = land_layer.symmetric;
+        }
+        // Island artifacts are placed independently for each player
+        if matches!(artifact_type, ArtifactType::IslandV1) {
+            artifact_type = ArtifactType::Coastal;
+        }
+    }
+
+    let mut town_count = None;
+    if let Some(config) = config {
+        ideal_land_dimensions = config.land_dimensions;
+        canvas_dimensions = [config.land_dimensions[0] * 2, config.land_dimensions[1] * 2];
+        water_layer = config.water_layer(land_layer.symmetric);
+        town_count = Some(config.towns_per_player);
+    }
+
     let retry_with = |mut board_seed: BoardSeed, failed_board: Board| {
         board_seed.internal_reroll();
         if current_iteration > max_attempts {
-            return Err(BoardGenerationResult {
+            return Err(BoardGenerationError::Unsolvable(BoardGenerationResult {
                 board: failed_board,
                 iterations: max_attempts,
-            });
+            }));
         } else {
             return generate_board(board_seed);
         }
@@ -454,6 +591,7 @@ pub fn generate_board(
                 &shortest_attack_path,
                 maximum_town_density,
                 maximum_town_distance,
+                town_count,
                 land_layer.symmetric,
             )
             .is_err()
@@ -471,6 +609,18 @@ pub fn generate_board(
         }
     }
 
+    if enforce_symmetry && !board.is_symmetric() {
+        if current_iteration > max_attempts {
+            return Err(BoardGenerationError::Unsolvable(BoardGenerationResult {
+                board,
+                iterations: max_attempts,
+            }));
+        }
+        board_seed.external_reroll();
+        board_seed.current_iteration += 1;
+        return generate_board(board_seed);
+    }
+
     Ok(BoardGenerationResult {
         board,
         iterations: current_iteration,
@@ -505,6 +655,7 @@ trait BoardGenerator {
         main_road: &Vec<Coordinate>,
         maximum_town_density: f64,
         maximum_town_distance: f64,
+        town_count: Option<u32>,
         symmetric: Symmetry,
     ) -> Result<(), ()>;
 
@@ -1042,6 +1193,7 @@ impl BoardGenerator for Board {
         main_road: &Vec<Coordinate>,
         maximum_town_density: f64,
         maximum_town_distance: f64,
+        town_count: Option<u32>,
         symmetric: Symmetry,
     ) -> Result<(), ()> {
         let artifacts = &self.artifacts;
@@ -1095,7 +1247,10 @@ impl BoardGenerator for Board {
             return Err(());
         }
         let maximum_town_goal = ((town_pairs.len() as f64 * maximum_town_density) as u32).max(1);
-        let town_goal = town_seed.rand_range(0..maximum_town_goal) + 1;
+        let town_goal = match town_count {
+            Some(count) => count,
+            None => town_seed.rand_range(0..maximum_town_goal) + 1,
+        };
 
         for _ in 0..town_goal {
             let Some((town_zero, town_one)) = town_pairs.next() else {
@@ -1116,13 +1271,21 @@ impl BoardGenerator for Board {
         }
 
         self.cache_special_squares();
-        let check_player_has_town = |p: usize| {
-            self.towns.iter().any(
-                |coord| matches!(self.get(*coord), Ok(Square::Town { player, .. }) if player == p),
-            )
+        let player_town_count = |p: usize| {
+            self.towns
+                .iter()
+                .filter(|coord| {
+                    matches!(self.get(**coord), Ok(Square::Town { player, .. }) if player == p)
+                })
+                .count() as u32
         };
 
-        if check_player_has_town(0) && check_player_has_town(1) {
+        let has_enough_towns = |p: usize| match town_count {
+            Some(count) => player_town_count(p) == count,
+            None => player_town_count(p) > 0,
+        };
+
+        if has_enough_towns(0) && has_enough_towns(1) {
             Ok(())
         } else {
             Err(())
@@ -1175,6 +1338,7 @@ pub fn get_game_verification(game: &Game) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{moves::Move, rules::GameRules};
 
     #[test]
     fn reroll_test() {
@@ -1191,4 +1355,138 @@ mod tests {
             "Board 1 from {bare_seed_1}:\n{board_one}\n\nrerolled to {bare_seed_2}:\n{board_two}"
         ));
     }
+
+    #[test]
+    fn board_seed_deals_identical_tiles() {
+        let seed = BoardSeed::new(12345).day(0);
+        let setup = || {
+            let board = generate_board(seed.clone())
+                .expect("Board can be resolved")
+                .board;
+            let mut game = Game::new(9, 9, Some(seed.tile_seed()), GameRules::generation(1));
+            game.add_player("P1".into());
+            game.add_player("P2".into());
+            game.board = board;
+            game.rules.battle_delay = 0;
+            game.start();
+            game
+        };
+
+        let mut first = setup();
+        let mut second = setup();
+
+        for _ in 0..20 {
+            for (a, b) in first.players.iter().zip(&second.players) {
+                assert_eq!(a.hand, b.hand);
+            }
+            assert_eq!(first.bag, second.bag);
+
+            if first.winner.is_some() {
+                break;
+            }
+            let player = first
+                .next_player
+                .expect("Turn-based rules have a next player");
+            let Some((position, tile)) = first.legal_placements(player).first().copied() else {
+                break;
+            };
+            let next_move = Move::Place {
+                player,
+                tile,
+                position,
+            };
+            first
+                .play_turn(next_move.clone(), None, None, None)
+                .expect("Legal placements can be played");
+            second
+                .play_turn(next_move, None, None, None)
+                .expect("Legal placements can be played");
+        }
+
+        assert!(first.player_turn_count.iter().sum::<u32>() > 1);
+    }
+
+    #[test]
+    fn board_variants() {
+        let standard = generate_board(BoardSeed::new(12345))
+            .expect("Board can be resolved")
+            .board;
+
+        for config in [BoardConfig::blitz(), BoardConfig::epic()] {
+            let seed = BoardSeed::new(12345).config(config);
+            let board = generate_board(seed.clone())
+                .expect("Board can be resolved")
+                .board;
+            let regenerated = generate_board(seed).expect("Board can be resolved").board;
+            assert_eq!(board.to_string(), regenerated.to_string());
+
+            assert!(board
+                .shortest_path_between(&board.artifacts[0], &board.artifacts[1])
+                .is_some());
+
+            for p in 0..2 {
+                let towns = board
+                    .towns
+                    .iter()
+                    .filter(|coord| {
+                        matches!(board.get(**coord), Ok(Square::Town { player, .. }) if player == p)
+                    })
+                    .count();
+                assert_eq!(towns as u32, config.towns_per_player);
+            }
+        }
+
+        let blitz = generate_board(BoardSeed::new(12345).config(BoardConfig::blitz()))
+            .unwrap()
+            .board;
+        let epic = generate_board(BoardSeed::new(12345).config(BoardConfig::epic()))
+            .unwrap()
+            .board;
+        assert!(blitz.width() < standard.width() && standard.width() < epic.width());
+    }
+
+    #[test]
+    fn oversized_seeds_are_refused() {
+        let epic = BoardSeed::new(12345).config(BoardConfig::epic());
+        assert_eq!(epic.check_dimensions(), Ok(()));
+
+        let capped = epic.max_dimension(20);
+        assert_eq!(
+            capped.check_dimensions(),
+            Err(BoardParseError::TooLarge {
+                width: 30,
+                height: 34,
+                max: 20
+            })
+        );
+        assert!(matches!(
+            generate_board(capped),
+            Err(BoardGenerationError::TooLarge(BoardParseError::TooLarge {
+                max: 20,
+                ..
+            }))
+        ));
+
+        // Refused before any canvas is allocated, however large the request
+        let mut huge = BoardSeed::new(12345);
+        huge.params.land_dimensions = [usize::MAX / 4, usize::MAX / 4];
+        huge.params.canvas_dimensions = [usize::MAX / 2, usize::MAX / 2];
+        assert!(matches!(
+            generate_board(huge),
+            Err(BoardGenerationError::TooLarge(_))
+        ));
+    }
+
+    #[test]
+    fn symmetric_boards() {
+        for seed in [12345, 54321, 777] {
+            let board = generate_board(BoardSeed::new(seed).enforce_symmetry())
+                .expect("Board can be resolved")
+                .board;
+            assert!(
+                board.is_symmetric(),
+                "Board from {seed} was not symmetric:\n{board}"
+            );
+        }
+    }
 }
@@ -0,0 +1,671 @@
+use serde::{Deserialize, Serialize};
+use time::Duration;
+
+use crate::{
+    bag::BagEntry,
+    board::{Coordinate, Square},
+    error::GamePlayError,
+    game::Game,
+    judge::Outcome,
+    player::{Hand, Player},
+    reporting::{BattleReport, Change},
+};
+
+/// The parts of a player that can be modified by playing a turn,
+/// other than the tiles they gain vision of
+#[derive(Debug, Clone, PartialEq)]
+struct PlayerSnapshot {
+    hand: Hand,
+    time_remaining: Option<Duration>,
+    turn_starts_no_later_than: Option<u64>,
+    turn_starts_no_sooner_than: Option<u64>,
+    swap_count: usize,
+    swaps_used: usize,
+    penalties_incurred: usize,
+}
+
+impl PlayerSnapshot {
+    fn capture(player: &Player) -> Self {
+        Self {
+            hand: player.hand.clone(),
+            time_remaining: player.time_remaining,
+            turn_starts_no_later_than: player.turn_starts_no_later_than,
+            turn_starts_no_sooner_than: player.turn_starts_no_sooner_than,
+            swap_count: player.swap_count,
+            swaps_used: player.swaps_used,
+            penalties_incurred: player.penalties_incurred,
+        }
+    }
+
+    /// Swaps the stored state with the player's live state,
+    /// leaving this snapshot holding whatever was overwritten.
+    fn exchange(&mut self, player: &mut Player) {
+        std::mem::swap(&mut self.hand, &mut player.hand);
+        std::mem::swap(&mut self.time_remaining, &mut player.time_remaining);
+        std::mem::swap(
+            &mut self.turn_starts_no_later_than,
+            &mut player.turn_starts_no_later_than,
+        );
+        std::mem::swap(
+            &mut self.turn_starts_no_sooner_than,
+            &mut player.turn_starts_no_sooner_than,
+        );
+        std::mem::swap(&mut self.swap_count, &mut player.swap_count);
+        std::mem::swap(&mut self.swaps_used, &mut player.swaps_used);
+        std::mem::swap(&mut self.penalties_incurred, &mut player.penalties_incurred);
+    }
+}
+
+/// The minimal state needed to move a game across one turn boundary.
+/// Applying a snapshot returns the game to the stored state, and leaves
+/// the snapshot holding the state it replaced, so the same value
+/// is used for both undoing and redoing a turn.
+#[derive(Debug, Clone)]
+pub struct TurnSnapshot {
+    /// Only the squares that differ across the turn
+    squares: Vec<(Coordinate, Square)>,
+    players: Vec<PlayerSnapshot>,
+    /// Every tile drawn from or returned to the bag during the turn
+    bag_entries: Vec<BagEntry>,
+    /// The bag's RNG position, so that redone turns draw identical tiles
+    bag_rng: (u64, u64),
+    /// Tiles that players gained vision of during the turn
+    revealed: Vec<(usize, Coordinate)>,
+    /// How many words the game's word history held before the turn
+    word_count: usize,
+    /// The words recorded by the turn, held here while it is taken back
+    words: Vec<WordRecord>,
+    /// Whether the turn is currently taken back, which decides
+    /// which way the bag, revealed tiles, and words are moved
+    undone: bool,
+    next_player: Option<usize>,
+    battle_count: u32,
+    turn_count: u32,
+    player_turn_count: Vec<u32>,
+    recent_changes: Vec<Change>,
+    winner: Option<usize>,
+    drawn: bool,
+    consecutive_passes: usize,
+    turns_without_progress: u32,
+    fallen_towns: Vec<(Coordinate, u32)>,
+}
+
+impl TurnSnapshot {
+    fn exchange(&mut self, game: &mut Game) {
+        for (coordinate, square) in self.squares.iter_mut() {
+            let current = game
+                .board
+                .get(*coordinate)
+                .expect("Snapshot squares should exist on the board");
+            // Set through the board so that its shape hash follows along
+            _ = game.board.set_square(*coordinate, *square);
+            *square = current;
+        }
+
+        for (snapshot, player) in self.players.iter_mut().zip(game.players.iter_mut()) {
+            snapshot.exchange(player);
+        }
+
+        if self.undone {
+            game.bag.reapply(&self.bag_entries);
+            for (player, coordinate) in &self.revealed {
+                game.players[*player].seen_tiles.insert(*coordinate);
+            }
+            if let Some(word_history) = game.word_history.as_mut() {
+                word_history.words.append(&mut self.words);
+            }
+        } else {
+            game.bag.revert(&self.bag_entries);
+            for (player, coordinate) in &self.revealed {
+                game.players[*player].seen_tiles.remove(coordinate);
+            }
+            if let Some(word_history) = game.word_history.as_mut() {
+                let word_count = self.word_count.min(word_history.words.len());
+                self.words = word_history.words.split_off(word_count);
+            }
+        }
+        game.bag.exchange_rng_state(&mut self.bag_rng);
+        self.undone = !self.undone;
+
+        std::mem::swap(&mut self.next_player, &mut game.next_player);
+        std::mem::swap(&mut self.battle_count, &mut game.battle_count);
+        std::mem::swap(&mut self.turn_count, &mut game.turn_count);
+        std::mem::swap(&mut self.player_turn_count, &mut game.player_turn_count);
+        std::mem::swap(&mut self.recent_changes, &mut game.recent_changes);
+        std::mem::swap(&mut self.winner, &mut game.winner);
+        std::mem::swap(&mut self.drawn, &mut game.drawn);
+        std::mem::swap(&mut self.consecutive_passes, &mut game.consecutive_passes);
+        std::mem::swap(
+            &mut self.turns_without_progress,
+            &mut game.turns_without_progress,
+        );
+        std::mem::swap(&mut self.fallen_towns, &mut game.fallen_towns);
+    }
+}
+
+/// State captured before a turn is played, which is completed into a
+/// `TurnSnapshot` from the board and bag journals once the turn has resolved.
+pub(crate) struct PendingTurn {
+    snapshot: TurnSnapshot,
+}
+
+impl PendingTurn {
+    pub(crate) fn capture(game: &mut Game) -> Self {
+        game.board.start_journal();
+        game.bag.start_journal();
+        if let Some(history) = game.history.as_mut() {
+            history.revealed.clear();
+        }
+
+        Self {
+            snapshot: TurnSnapshot {
+                squares: vec![],
+                players: game.players.iter().map(PlayerSnapshot::capture).collect(),
+                bag_entries: vec![],
+                bag_rng: game.bag.rng_state(),
+                revealed: vec![],
+                word_count: game.word_history.as_ref().map_or(0, |h| h.words.len()),
+                words: vec![],
+                undone: false,
+                next_player: game.next_player,
+                battle_count: game.battle_count,
+                turn_count: game.turn_count,
+                player_turn_count: game.player_turn_count.clone(),
+                recent_changes: game.recent_changes.clone(),
+                winner: game.winner,
+                drawn: game.drawn,
+                consecutive_passes: game.consecutive_passes,
+                turns_without_progress: game.turns_without_progress,
+                fallen_towns: game.fallen_towns.clone(),
+            },
+        }
+    }
+
+    pub(crate) fn finish(self, game: &mut Game) -> TurnSnapshot {
+        let Self { mut snapshot } = self;
+
+        snapshot.squares = game.board.take_journal();
+        snapshot.bag_entries = game.bag.take_journal();
+        if let Some(history) = game.history.as_mut() {
+            snapshot.revealed = std::mem::take(&mut history.revealed);
+        }
+
+        snapshot
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TurnHistory {
+    undo: Vec<TurnSnapshot>,
+    redo: Vec<TurnSnapshot>,
+    /// Tiles revealed to players by the turn being played
+    pub(crate) revealed: Vec<(usize, Coordinate)>,
+}
+
+impl TurnHistory {
+    pub(crate) fn record(&mut self, snapshot: TurnSnapshot) {
+        self.undo.push(snapshot);
+        // Playing a fresh turn invalidates anything that was undone
+        self.redo.clear();
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WordRole {
+    Attack,
+    Defense,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WordOutcome {
+    Won,
+    Lost,
+    /// The word was formed without touching any opponent
+    Unopposed,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WordRecord {
+    pub word: String,
+    pub turn: u32,
+    pub player: usize,
+    pub role: WordRole,
+    pub outcome: WordOutcome,
+}
+
+/// Every word formed or battled over the course of a game, for post-game statistics
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GameHistory {
+    words: Vec<WordRecord>,
+}
+
+impl GameHistory {
+    pub fn words(&self) -> &[WordRecord] {
+        &self.words
+    }
+
+    pub fn player_words(&self, player: usize) -> impl Iterator<Item = &WordRecord> {
+        self.words.iter().filter(move |w| w.player == player)
+    }
+
+    /// The longest word the player won a battle with, preferring the earliest on ties
+    pub fn longest_winning_word(&self, player: usize) -> Option<&WordRecord> {
+        self.player_words(player)
+            .filter(|w| w.outcome == WordOutcome::Won)
+            .fold(None, |longest: Option<&WordRecord>, w| match longest {
+                Some(l) if l.word.chars().count() >= w.word.chars().count() => Some(l),
+                _ => Some(w),
+            })
+    }
+
+    pub(crate) fn record_unopposed(&mut self, turn: u32, player: usize, words: Vec<String>) {
+        self.words.extend(words.into_iter().map(|word| WordRecord {
+            word,
+            turn,
+            player,
+            role: WordRole::Attack,
+            outcome: WordOutcome::Unopposed,
+        }));
+    }
+
+    /// Records both sides of a battle, where `defender_owners` lines up with the battle's defenders
+    pub(crate) fn record_battle(
+        &mut self,
+        turn: u32,
+        attacker: usize,
+        battle: &BattleReport,
+        defender_owners: &[usize],
+    ) {
+        let (attacker_outcome, losers) = match &battle.outcome {
+            Outcome::AttackerWins(losers) => (WordOutcome::Won, losers.as_slice()),
+            Outcome::DefenderWins => (WordOutcome::Lost, [].as_slice()),
+        };
+
+        self.words
+            .extend(battle.attackers.iter().map(|attacker_word| WordRecord {
+                word: attacker_word.resolved_word.clone(),
+                turn,
+                player: attacker,
+                role: WordRole::Attack,
+                outcome: attacker_outcome,
+            }));
+
+        self.words.extend(
+            battle
+                .defenders
+                .iter()
+                .zip(defender_owners)
+                .enumerate()
+                .map(|(index, (defender_word, owner))| WordRecord {
+                    word: defender_word.resolved_word.clone(),
+                    turn,
+                    player: *owner,
+                    role: WordRole::Defense,
+                    outcome: if losers.contains(&index) {
+                        WordOutcome::Lost
+                    } else {
+                        WordOutcome::Won
+                    },
+                }),
+        );
+    }
+}
+
+impl Game {
+    /// Start recording every word played, battled, and challenged.
+    pub fn track_word_history(&mut self) {
+        self.word_history.get_or_insert_with(GameHistory::default);
+    }
+
+    /// Start recording turns so that they can be undone and redone.
+    pub fn track_history(&mut self) {
+        self.history.get_or_insert_with(TurnHistory::default);
+    }
+
+    /// Reverts the board, hands, bag, and turn order to their state
+    /// before the most recent call to `play_turn`.
+    pub fn undo_last_turn(&mut self) -> Result<(), GamePlayError> {
+        let Some(mut snapshot) = self.history.as_mut().and_then(|h| h.undo.pop()) else {
+            return Err(GamePlayError::NothingToUndo);
+        };
+
+        snapshot.exchange(self);
+        self.history
+            .as_mut()
+            .expect("History was just read")
+            .redo
+            .push(snapshot);
+
+        Ok(())
+    }
+
+    /// Re-applies the most recently undone turn.
+    pub fn redo(&mut self) -> Result<(), GamePlayError> {
+        let Some(mut snapshot) = self.history.as_mut().and_then(|h| h.redo.pop()) else {
+            return Err(GamePlayError::NothingToRedo);
+        };
+
+        snapshot.exchange(self);
+        self.history
+            .as_mut()
+            .expect("History was just read")
+            .undo
+            .push(snapshot);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        bag::tests as TileUtils,
+        board::{Board, SquareValidity},
+        judge::{Judge, WordData, WordDict},
+        moves::Move,
+        rules::GameRules,
+    };
+
+    fn battle_game() -> Game {
+        let b = Board::from_string(
+            "__ S0 X0 |0 __\n\
+             __ T0 __ __ __\n\
+             __ R0 __ __ __\n\
+             __ __ I1 __ __\n\
+             __ __ T1 |1 __",
+        );
+        let mut bag = TileUtils::trivial_bag();
+        let players = vec![
+            Player::new("A".into(), 0, 7, &mut bag, None, (0, 0, 0)),
+            Player::new("B".into(), 1, 7, &mut bag, None, (0, 0, 0)),
+        ];
+
+        let mut game = Game {
+            board: b,
+            bag,
+            players,
+            player_turn_count: vec![0, 0],
+            judge: Judge::new(vec!["ARTS".into(), "IT".into()]),
+            ..Game::new_legacy(1, 1, None, GameRules::generation(0))
+        };
+        game.track_history();
+        game.start();
+        game
+    }
+
+    #[test]
+    fn empty_history_errors() {
+        let mut game = battle_game();
+        assert_eq!(game.undo_last_turn(), Err(GamePlayError::NothingToUndo));
+        assert_eq!(game.redo(), Err(GamePlayError::NothingToRedo));
+
+        let mut untracked = Game::new(3, 3, None, GameRules::generation(0));
+        assert_eq!(
+            untracked.undo_last_turn(),
+            Err(GamePlayError::NothingToUndo)
+        );
+    }
+
+    #[test]
+    fn undo_and_redo_battle() {
+        let mut game = battle_game();
+        let before_board = game.board.clone();
+        let before_bag = game.bag.clone();
+        let before_players = game.players.clone();
+
+        game.play_turn(
+            Move::Place {
+                player: 0,
+                tile: 'A',
+                position: Coordinate { x: 1, y: 3 },
+            },
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(
+            game.board.to_string(),
+            "__ S0 X0 |0 __\n\
+             __ T0 __ __ __\n\
+             __ R0 __ __ __\n\
+             __ A0 __ __ __\n\
+             __ __ __ |1 __",
+        );
+        let after_board = game.board.clone();
+        let after_bag = game.bag.clone();
+        let after_players = game.players.clone();
+
+        game.undo_last_turn().unwrap();
+        assert_eq!(game.board, before_board);
+        assert_eq!(game.bag, before_bag);
+        assert_eq!(game.players, before_players);
+        assert_eq!(game.next_player, Some(0));
+        assert_eq!(game.player_turn_count, vec![0, 0]);
+        assert_eq!(game.winner, None);
+
+        game.redo().unwrap();
+        assert_eq!(game.board, after_board);
+        assert_eq!(game.bag, after_bag);
+        assert_eq!(game.players, after_players);
+        assert_eq!(game.next_player, Some(1));
+        assert_eq!(game.player_turn_count, vec![1, 0]);
+
+        assert_eq!(game.redo(), Err(GamePlayError::NothingToRedo));
+    }
+
+    #[test]
+    fn undo_restores_neighbouring_validity() {
+        let mut game = battle_game();
+        let dict: WordDict = ["arts", "it"]
+            .into_iter()
+            .map(|word| {
+                (
+                    word.to_string(),
+                    WordData {
+                        extensions: 0,
+                        rel_freq: 0.0,
+                        objectionable: false,
+                    },
+                )
+            })
+            .collect();
+        let before_board = game.board.clone();
+
+        // Placing beside STR re-judges it, which isn't reported as a change of its own
+        game.play_turn(
+            Move::Place {
+                player: 0,
+                tile: 'A',
+                position: Coordinate { x: 1, y: 3 },
+            },
+            Some(&dict),
+            Some(&dict),
+            None,
+        )
+        .unwrap();
+        assert!(matches!(
+            game.board.get(Coordinate { x: 1, y: 0 }),
+            Ok(Square::Occupied { validity, .. }) if validity != SquareValidity::Unknown
+        ));
+
+        game.undo_last_turn().unwrap();
+        assert_eq!(game.board, before_board);
+    }
+
+    #[test]
+    fn redo_draws_identical_tiles() {
+        let mut game = Game::new(9, 9, Some(1234), GameRules::generation(1));
+        game.add_player("A".into());
+        game.add_player("B".into());
+        game.track_history();
+        game.start();
+
+        let position = *game
+            .board
+            .playable_positions(0, &game.rules.truncation)
+            .iter()
+            .min()
+            .unwrap();
+        let tile = game.players[0].hand.0[0];
+        game.play_turn(
+            Move::Place {
+                player: 0,
+                tile,
+                position,
+            },
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        let drawn_hand = game.players[0].hand.clone();
+
+        game.undo_last_turn().unwrap();
+        game.redo().unwrap();
+        assert_eq!(game.players[0].hand, drawn_hand);
+
+        // Replaying the turn from scratch after undoing should also draw the same tiles
+        game.undo_last_turn().unwrap();
+        game.play_turn(
+            Move::Place {
+                player: 0,
+                tile,
+                position,
+            },
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        assert_eq!(game.players[0].hand, drawn_hand);
+        assert!(!game.history.as_ref().unwrap().can_redo());
+    }
+
+    #[test]
+    fn undo_takes_back_recorded_words() {
+        let mut game = battle_game();
+        game.rules.battle_delay = 0;
+        game.track_word_history();
+
+        game.play_turn(
+            Move::Place {
+                player: 0,
+                tile: 'A',
+                position: Coordinate { x: 1, y: 3 },
+            },
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        let battle_words = game.word_history.as_ref().unwrap().words().to_vec();
+        assert_eq!(battle_words.len(), 2);
+
+        game.undo_last_turn().unwrap();
+        assert!(game.word_history.as_ref().unwrap().words().is_empty());
+        assert_eq!(
+            game.word_history.as_ref().unwrap().longest_winning_word(0),
+            None
+        );
+
+        game.redo().unwrap();
+        assert_eq!(game.word_history.as_ref().unwrap().words(), battle_words);
+
+        // Playing something else in place of an undone turn only keeps the new words
+        game.undo_last_turn().unwrap();
+        game.play_turn(
+            Move::Place {
+                player: 0,
+                tile: 'A',
+                position: Coordinate { x: 4, y: 0 },
+            },
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        let words = game.word_history.as_ref().unwrap().words();
+        assert_eq!(words.len(), 1);
+        assert_eq!(words[0].outcome, WordOutcome::Unopposed);
+    }
+
+    #[test]
+    fn word_history_records_battles() {
+        let mut game = battle_game();
+        game.rules.battle_delay = 0;
+        game.track_word_history();
+
+        game.play_turn(
+            Move::Place {
+                player: 0,
+                tile: 'A',
+                position: Coordinate { x: 1, y: 3 },
+            },
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        game.play_turn(
+            Move::Place {
+                player: 1,
+                tile: 'A',
+                position: Coordinate { x: 4, y: 4 },
+            },
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+
+        let word_history = game.word_history.as_ref().unwrap();
+        let record = |word: &str, turn, player, role, outcome| WordRecord {
+            word: word.into(),
+            turn,
+            player,
+            role,
+            outcome,
+        };
+        assert_eq!(
+            word_history.words(),
+            &[
+                record("ARTS", 0, 0, WordRole::Attack, WordOutcome::Won),
+                record("IT", 0, 1, WordRole::Defense, WordOutcome::Lost),
+                record("A", 1, 1, WordRole::Attack, WordOutcome::Unopposed),
+            ]
+        );
+        assert_eq!(
+            word_history
+                .longest_winning_word(0)
+                .map(|w| w.word.as_str()),
+            Some("ARTS")
+        );
+        assert_eq!(word_history.longest_winning_word(1), None);
+
+        let mut untracked = battle_game();
+        untracked
+            .play_turn(
+                Move::Place {
+                    player: 0,
+                    tile: 'A',
+                    position: Coordinate { x: 1, y: 3 },
+                },
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+        assert!(untracked.word_history.is_none());
+    }
+}
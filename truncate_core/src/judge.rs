@@ -2,7 +2,7 @@ use serde::{Deserialize, Serialize};
 use xxhash_rust::xxh3;
 
 use crate::{
-    reporting::{BattleReport, BattleWord},
+    reporting::{BattleReason, BattleReport, BattleWord},
     rules,
 };
 
@@ -12,7 +12,7 @@ use std::{
     fmt::{self, Display},
 };
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct WordData {
     pub extensions: u32,
     pub rel_freq: f32,
@@ -40,6 +40,9 @@ impl fmt::Display for Outcome {
 #[derive(Debug, Clone)]
 pub struct Judge {
     pub builtin_dictionary: WordDict,
+    /// Extra words that are valid alongside whichever dictionary is judging,
+    /// such as the custom words of a private room
+    pub overlay_dictionary: WordDict,
     aliases: HashMap<char, Vec<char>>,
 }
 
@@ -47,6 +50,7 @@ impl Default for Judge {
     fn default() -> Self {
         Self {
             builtin_dictionary: HashMap::new(),
+            overlay_dictionary: HashMap::new(),
             aliases: HashMap::new(),
         }
     }
@@ -67,10 +71,29 @@ impl Judge {
         }
         Self {
             builtin_dictionary: dictionary,
+            overlay_dictionary: HashMap::new(),
             aliases: HashMap::new(),
         }
     }
 
+    /// Adds words to the overlay dictionary, returning how many weren't already there.
+    /// Overlay words aren't scored, so their extensions and frequency are zero.
+    pub fn extend_overlay<S: AsRef<str>>(&mut self, words: impl IntoIterator<Item = S>) -> usize {
+        let before = self.overlay_dictionary.len();
+        for word in words {
+            let word = word.as_ref().trim().to_lowercase();
+            if word.is_empty() {
+                continue;
+            }
+            self.overlay_dictionary.entry(word).or_insert(WordData {
+                extensions: 0,
+                rel_freq: 0.0,
+                objectionable: false,
+            });
+        }
+        self.overlay_dictionary.len() - before
+    }
+
     pub fn set_alias(&mut self, alias_target: Vec<char>) -> char {
         for p in ['1', '2', '3', '4', '5', '6', '7', '8', '9'] {
             if self.aliases.contains_key(&p) {
@@ -90,30 +113,67 @@ impl Judge {
     // TODO: accept a config that chooses between different win conditions, like occupying enough quadrants
     // TODO: error (or possibly return a tie) if there are multiple winners - this assume turn based play
     // TODO: put this somewhere better, it conceptually works as a judge associated function, but it only uses values from the board
-    pub fn winner(board: &Board) -> Option<usize> {
-        for town_coord in board.towns() {
-            if let Ok(Square::Town {
-                player,
-                defeated: true,
+    /// The last player standing under the given win condition, if only one remains.
+    /// `turns_taken` is indexed by player, for conditions that depend on having played.
+    pub fn winner(
+        board: &Board,
+        win_condition: &rules::WinCondition,
+        turns_taken: &[u32],
+    ) -> Option<usize> {
+        let player_count = board.players().last().map_or(0, |p| p + 1).max(2);
+
+        if !matches!(
+            win_condition,
+            rules::WinCondition::Destination {
+                towns_to_defeat: rules::TownsToDefeat::Any,
                 ..
-            }) = board.get(*town_coord)
-            {
-                return Some((player + 1) % 2);
             }
+        ) {
+            let has_played = |p: usize| turns_taken.get(p).is_some_and(|turns| *turns > 0);
+            let defeated: Vec<_> = (0..player_count)
+                .filter(|p| board.player_is_defeated_by(*p, win_condition, has_played(*p)))
+                .collect();
+            let first_defeated = *defeated.first()?;
+            let remaining: Vec<_> = (0..player_count)
+                .filter(|p| !defeated.contains(p))
+                .collect();
+
+            return match remaining.as_slice() {
+                [last_player] => Some(*last_player),
+                [] => Some((first_defeated + 1) % player_count),
+                _ => None,
+            };
         }
 
-        for artifact_coord in board.artifacts() {
-            if let Ok(Square::Artifact {
+        let mut defeated = vec![];
+        for coord in board.towns().chain(board.artifacts()) {
+            if let Ok(Square::Town {
+                player,
+                defeated: true,
+                ..
+            })
+            | Ok(Square::Artifact {
                 player,
                 defeated: true,
                 ..
-            }) = board.get(*artifact_coord)
+            }) = board.get(*coord)
             {
-                return Some((player + 1) % 2);
+                if !defeated.contains(&player) {
+                    defeated.push(player);
+                }
             }
         }
 
-        None
+        let first_defeated = *defeated.first()?;
+        let remaining: Vec<_> = (0..player_count)
+            .filter(|p| !defeated.contains(p))
+            .collect();
+
+        match remaining.as_slice() {
+            [last_player] => Some(*last_player),
+            [] => Some((first_defeated + 1) % player_count),
+            _ => None,
+        }
     }
 
     // If there are no attackers or no defenders there is no battle
@@ -122,6 +182,51 @@ impl Judge {
     //
     // There is a defender's advantage, so an attacking word has to be at least 2 letters longer than a defending word to be stronger than it.
     pub fn battle<S: AsRef<str> + Clone + Display>(
+        &self,
+        attackers: Vec<S>,
+        defenders: Vec<S>,
+        battle_rules: &rules::BattleRules,
+        win_rules: &rules::WinCondition,
+        attacker_dictionary: Option<&WordDict>,
+        defender_dictionary: Option<&WordDict>,
+        cached_word_judgements: Option<&mut HashMap<String, bool, xxh3::Xxh3Builder>>,
+    ) -> Option<BattleReport> {
+        self.judge_battle(
+            attackers,
+            defenders,
+            battle_rules,
+            win_rules,
+            attacker_dictionary,
+            defender_dictionary,
+            cached_word_judgements,
+            false,
+        )
+    }
+
+    /// Battles as in `challenge_mode`, where defending words are assumed valid
+    /// and only lose on length. Their validity is left as `None` in the report.
+    pub fn unchallenged_battle<S: AsRef<str> + Clone + Display>(
+        &self,
+        attackers: Vec<S>,
+        defenders: Vec<S>,
+        battle_rules: &rules::BattleRules,
+        win_rules: &rules::WinCondition,
+        attacker_dictionary: Option<&WordDict>,
+        cached_word_judgements: Option<&mut HashMap<String, bool, xxh3::Xxh3Builder>>,
+    ) -> Option<BattleReport> {
+        self.judge_battle(
+            attackers,
+            defenders,
+            battle_rules,
+            win_rules,
+            attacker_dictionary,
+            None,
+            cached_word_judgements,
+            true,
+        )
+    }
+
+    fn judge_battle<S: AsRef<str> + Clone + Display>(
         &self,
         attackers: Vec<S>,
         defenders: Vec<S>,
@@ -130,6 +235,7 @@ impl Judge {
         attacker_dictionary: Option<&WordDict>,
         defender_dictionary: Option<&WordDict>,
         mut cached_word_judgements: Option<&mut HashMap<String, bool, xxh3::Xxh3Builder>>,
+        trust_defenders: bool,
     ) -> Option<BattleReport> {
         // If there are no attackers or no defenders there is no battle
         if attackers.is_empty() || defenders.is_empty() {
@@ -166,6 +272,7 @@ impl Judge {
                 })
                 .collect(),
             outcome: Outcome::DefenderWins,
+            reason: None,
         };
 
         // The defender wins if any attacking word is invalid
@@ -175,10 +282,19 @@ impl Judge {
             .all(|word| word.valid == Some(false))
         {
             battle_report.outcome = Outcome::DefenderWins;
+            battle_report.reason = Some(BattleReason::InvalidAttackers);
             return Some(battle_report);
         }
 
         for defense in &mut battle_report.defenders {
+            // Towns and artifacts are judged by the rules rather than the dictionary,
+            // so are never left to a challenge
+            let is_symbolic =
+                defense.original_word.contains('#') || defense.original_word.contains('|');
+            if trust_defenders && !is_symbolic {
+                continue;
+            }
+
             let valid = self.valid(
                 &*defense.resolved_word,
                 win_rules,
@@ -218,9 +334,19 @@ impl Judge {
         let attacker_wins_outright = attackers.iter().any(|word| word.as_ref().contains('¤'));
         if attacker_wins_outright {
             battle_report.outcome = Outcome::AttackerWins(vec![]);
+            battle_report.reason = Some(BattleReason::Explosion);
             return Some(battle_report);
         }
 
+        // Unchallenged defenders only lose on length
+        let is_unproven = |word: &BattleWord| {
+            if trust_defenders {
+                word.valid == Some(false)
+            } else {
+                word.valid != Some(true)
+            }
+        };
+
         let actually_words: Vec<_> = battle_report
             .defenders
             .iter()
@@ -242,7 +368,7 @@ impl Judge {
         let weak_word_defenders: Vec<_> = actually_words
             .iter()
             .filter(|(_, word)| {
-                word.valid != Some(true)
+                is_unproven(word)
                     || word.resolved_word.len() as isize + battle_rules.length_delta as isize
                         <= longest_attacker.len() as isize
             })
@@ -253,18 +379,32 @@ impl Judge {
         let weak_symbolic_defenders: Vec<_> = symbolic_words
             .iter()
             .filter(|(_, word)| {
-                word.valid != Some(true)
+                is_unproven(word)
                     || word.resolved_word.len() as isize + battle_rules.length_delta as isize
                         <= longest_attacker.len() as isize
             })
             .map(|(index, _)| *index)
             .collect();
 
+        // Any defender losing to invalidity takes precedence when explaining the outcome
+        let reason_for = |losers: &[usize]| {
+            if losers
+                .iter()
+                .any(|i| is_unproven(&battle_report.defenders[*i]))
+            {
+                BattleReason::InvalidDefenders
+            } else {
+                BattleReason::Length
+            }
+        };
+
         // Normal battles without towns or artifacts, easy cases.
         if symbolic_words.is_empty() {
             if weak_word_defenders.is_empty() {
                 battle_report.outcome = Outcome::DefenderWins;
+                battle_report.reason = Some(BattleReason::Length);
             } else {
+                battle_report.reason = Some(reason_for(&weak_word_defenders));
                 battle_report.outcome = Outcome::AttackerWins(weak_word_defenders);
             }
 
@@ -293,6 +433,10 @@ impl Judge {
             // Catch the unreachable case of no words with beatable words
             (_, false, true) => unreachable!(),
         };
+        battle_report.reason = Some(match &battle_report.outcome {
+            Outcome::AttackerWins(losers) => reason_for(losers),
+            Outcome::DefenderWins => BattleReason::Length,
+        });
 
         Some(battle_report)
     }
@@ -398,9 +542,12 @@ impl Judge {
                 return valid;
             }
 
+            // Blank tiles sit in words as the lowercase form of their assigned letter
+            let lowercase = word.as_ref().to_lowercase();
             if external_dictionary
                 .unwrap_or(&judge.builtin_dictionary)
-                .contains_key(&word.as_ref().to_lowercase())
+                .contains_key(&lowercase)
+                || judge.overlay_dictionary.contains_key(&lowercase)
             {
                 Some(word.as_ref().to_string().to_uppercase())
             } else {
@@ -443,6 +590,7 @@ mod tests {
         rules::WinCondition::Destination {
             town_defense: rules::TownDefense::BeatenWithDefenseStrength(0),
             artifact_defense: rules::ArtifactDefense::BeatenWithDefenseStrength(0),
+            towns_to_defeat: rules::TownsToDefeat::Any,
         }
     }
 
@@ -671,6 +819,39 @@ mod tests {
         );
     }
 
+    #[test]
+    fn blanks_act_as_their_letter() {
+        let j = short_dict();
+        assert_eq!(
+            j.battle(
+                vec!["JoLlY"],
+                vec!["FAT"],
+                &test_battle_rules(),
+                &test_win_rules(),
+                None,
+                None,
+                None
+            )
+            .unwrap()
+            .outcome,
+            Outcome::AttackerWins(vec![0])
+        );
+        assert_eq!(
+            j.battle(
+                vec!["JOLLY"],
+                vec!["fOlK"],
+                &test_battle_rules(),
+                &test_win_rules(),
+                None,
+                None,
+                None
+            )
+            .unwrap()
+            .outcome,
+            Outcome::DefenderWins
+        );
+    }
+
     #[test]
     fn defender_weaker() {
         let j = short_dict();
@@ -755,6 +936,25 @@ mod tests {
         );
     }
 
+    #[test]
+    fn overlay_dictionary() {
+        let mut j = short_dict();
+        let external = b_dict().builtin_dictionary;
+        let valid = |j: &Judge, word| {
+            j.valid(word, &test_win_rules(), Some(&external), None, &mut None)
+                .is_some()
+        };
+
+        assert!(!valid(&j, "QAT"));
+        assert_eq!(j.extend_overlay(["qat", " QAT ", "zax", ""]), 2);
+        assert!(valid(&j, "QAT"));
+        assert_eq!(j.extend_overlay(["qat"]), 0);
+
+        // The external dictionary is still consulted alongside the overlay
+        assert!(valid(&j, "BIG"));
+        assert!(!valid(&j, "JOLLY"));
+    }
+
     #[test]
     fn wildcards() {
         let j = short_dict();
@@ -977,7 +1177,8 @@ mod tests {
                     meanings: None,
                     valid: Some(false)
                 }],
-                outcome: Outcome::AttackerWins(vec![0])
+                outcome: Outcome::AttackerWins(vec![0]),
+                reason: Some(BattleReason::InvalidDefenders)
             })
         );
         assert_eq!(
@@ -1004,7 +1205,8 @@ mod tests {
                     meanings: None,
                     valid: None
                 }],
-                outcome: Outcome::DefenderWins
+                outcome: Outcome::DefenderWins,
+                reason: Some(BattleReason::InvalidAttackers)
             })
         );
 
@@ -1032,7 +1234,8 @@ mod tests {
                     meanings: None,
                     valid: Some(false)
                 }],
-                outcome: Outcome::AttackerWins(vec![0])
+                outcome: Outcome::AttackerWins(vec![0]),
+                reason: Some(BattleReason::InvalidDefenders)
             })
         );
         assert_eq!(
@@ -1059,11 +1262,88 @@ mod tests {
                     meanings: None,
                     valid: Some(true)
                 }],
-                outcome: Outcome::DefenderWins
+                outcome: Outcome::DefenderWins,
+                reason: Some(BattleReason::Length)
             })
         );
     }
 
+    #[test]
+    fn battle_descriptions() {
+        let j = short_dict();
+        let described = |attackers: Vec<&str>, defenders: Vec<&str>| {
+            j.battle(
+                attackers,
+                defenders,
+                &test_battle_rules(),
+                &test_win_rules(),
+                None,
+                None,
+                None,
+            )
+            .unwrap()
+            .describe()
+        };
+
+        assert_eq!(
+            described(vec!["JOLLY"], vec!["BAG"]),
+            "JOLLY (valid, length 5) defeated BAG (valid, length 3) on length."
+        );
+        assert_eq!(
+            described(vec!["ARTS"], vec!["BAG"]),
+            "BAG (valid, length 3) held off ARTS (valid, length 4) on length."
+        );
+        assert_eq!(
+            described(vec!["BAG"], vec!["XYZ"]),
+            "BAG (valid, length 3) defeated XYZ (invalid, length 3) by invalidity."
+        );
+        assert_eq!(
+            described(vec!["R*G"], vec!["BAG"]),
+            "BAG (unchecked, length 3) held off R*G (invalid, length 3) as the attack was invalid."
+        );
+    }
+
+    #[test]
+    fn configurable_win_conditions() {
+        let mut b = Board::from_string(
+            "~~ ~~ |0 ~~ ~~\n\
+             ~~ #0 __ #0 ~~\n\
+             ~~ __ A0 __ ~~\n\
+             ~~ #1 __ #1 ~~\n\
+             ~~ ~~ |1 ~~ ~~",
+        );
+        let destination = |towns_to_defeat| rules::WinCondition::Destination {
+            town_defense: rules::TownDefense::BeatenByContact,
+            artifact_defense: rules::ArtifactDefense::Invincible,
+            towns_to_defeat,
+        };
+        let any_town = destination(rules::TownsToDefeat::Any);
+        let all_towns = destination(rules::TownsToDefeat::All);
+        let elimination = rules::WinCondition::Elimination;
+
+        assert_eq!(Judge::winner(&b, &any_town, &[1, 1]), None);
+        assert_eq!(Judge::winner(&b, &all_towns, &[1, 1]), None);
+        // Player 1 has played but has no tiles left on the board
+        assert_eq!(Judge::winner(&b, &elimination, &[1, 1]), Some(0));
+        // Nobody is eliminated before they have had a turn
+        assert_eq!(Judge::winner(&b, &elimination, &[1, 0]), None);
+
+        b.squares[3][1] = Square::Town {
+            player: 1,
+            defeated: true,
+            foggy: false,
+        };
+        assert_eq!(Judge::winner(&b, &any_town, &[1, 1]), Some(0));
+        assert_eq!(Judge::winner(&b, &all_towns, &[1, 1]), None);
+
+        b.squares[3][3] = Square::Town {
+            player: 1,
+            defeated: true,
+            foggy: false,
+        };
+        assert_eq!(Judge::winner(&b, &all_towns, &[1, 1]), Some(0));
+    }
+
     // #[test]
     // fn main_dict() {
     //     let j = Judge::default();
@@ -1,9 +1,11 @@
 pub mod bag;
 pub mod board;
+pub mod boardfile;
 pub mod emojification;
 pub mod error;
 pub mod game;
 pub mod generation;
+pub mod history;
 pub mod judge;
 pub mod messages;
 pub mod moves;
@@ -11,3 +13,5 @@ pub mod npc;
 pub mod player;
 pub mod reporting;
 pub mod rules;
+pub mod savegame;
+pub mod scenario;
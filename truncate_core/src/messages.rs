@@ -5,13 +5,16 @@ use std::{
 use time::Duration;
 
 use serde::{Deserialize, Serialize};
+use strum_macros::IntoStaticStr;
 
 use crate::{
-    board::{Board, Coordinate},
+    board::{Board, Coordinate, Square},
+    error::GamePlayError,
     game::Game,
     moves::Move,
     player::{Hand, Player},
-    reporting::{Change, WordMeaning},
+    reporting::{Change, WordDefinition},
+    rules::GameRules,
 };
 
 pub type RoomCode = String;
@@ -30,7 +33,7 @@ pub struct NoncedPlayerMessage {
     pub message: PlayerMessage,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, IntoStaticStr)]
 pub enum PlayerMessage {
     Ping,
     NewGame {
@@ -38,17 +41,33 @@ pub enum PlayerMessage {
         effective_day: u32,
     },
     JoinGame(RoomCode, String, Option<TruncateToken>),
+    Spectate(RoomCode),
     RejoinGame(TruncateToken),
+    /// Rejoins a game, only needing what changed since the last turn the client saw
+    RejoinGameFromTurn(TruncateToken, u32),
     EditBoard(Board),
+    /// Only the lobby's host may change the rules
+    EditRules(GameRules),
     EditName(String),
+    /// Rejected if the color is too close to another player's
+    EditColor((u8, u8, u8)),
     StartGame,
-    Resign,
+    /// Resigns from the game, with an optional reason kept for stats
+    Resign(Option<String>),
     Place(Coordinate, char),
     Swap(Coordinate, Coordinate),
-    Rematch,
+    Challenge(Coordinate),
+    Pass,
+    /// Trades a tile in the hand for a new one from the bag
+    Exchange(char),
+    /// Starts a new game with the same players, optionally on the mirror image of the board
+    Rematch {
+        mirror: bool,
+    },
     Pause,
     Unpause,
     RequestDefinitions(Vec<String>),
+    Chat(String),
     CreateAnonymousPlayer {
         screen_width: u32,
         screen_height: u32,
@@ -64,19 +83,37 @@ pub enum PlayerMessage {
         referrer: String,
     },
     LoadDailyPuzzle(TruncateToken, u32),
+    /// Starts a fresh run at a past day's puzzle, tracked apart from that day's original attempts
+    ReplayDailyPuzzle(TruncateToken, u32),
     PersistPuzzleMoves {
         player_token: TruncateToken,
         day: u32,
         human_player: u32,
+        #[serde(with = "crate::moves::packing::packed")]
         moves: Vec<Move>,
         won: bool,
+        #[serde(default)]
+        drawn: bool,
+        #[serde(default)]
+        replay: bool,
+        #[serde(default)]
+        resigned: bool,
     },
     RequestStats(TruncateToken),
+    RequestDailyLeaderboard(u32),
+    /// Requests a page of the player's finished multiplayer games, most recent first
+    RequestGameHistory(TruncateToken, u32),
     LoadReplay(String),
     MarkChangelogRead(String),
     GenericEvent {
         name: String,
     },
+    /// Adds words to a room's dictionary, or to every room's if no room is given
+    AddWords {
+        admin_secret: String,
+        room: Option<RoomCode>,
+        words: Vec<String>,
+    },
 }
 
 impl fmt::Display for PlayerMessage {
@@ -96,19 +133,34 @@ impl fmt::Display for PlayerMessage {
                     "Join game {room} as player {name}, but also maybe with token {token:#?}"
                 )
             }
+            PlayerMessage::Spectate(room) => write!(f, "Spectate game {room}"),
             PlayerMessage::RejoinGame(token) => {
                 write!(f, "Player wants to rejoin a game using the token {}", token)
             }
+            PlayerMessage::RejoinGameFromTurn(token, turn) => {
+                write!(
+                    f,
+                    "Player wants to rejoin a game from turn {turn} using the token {token}"
+                )
+            }
             PlayerMessage::EditBoard(board) => write!(f, "Set board to {board}"),
+            PlayerMessage::EditRules(rules) => write!(f, "Set rules to {rules:?}"),
             PlayerMessage::EditName(name) => write!(f, "Set name to {name}"),
+            PlayerMessage::EditColor(color) => write!(f, "Set color to {color:?}"),
             PlayerMessage::StartGame => write!(f, "Start the game"),
-            PlayerMessage::Resign => write!(f, "Resign"),
+            PlayerMessage::Resign(None) => write!(f, "Resign"),
+            PlayerMessage::Resign(Some(reason)) => write!(f, "Resign because {reason}"),
             PlayerMessage::Place(coord, tile) => write!(f, "Place {} at {}", tile, coord),
             PlayerMessage::Swap(a, b) => write!(f, "Swap the tiles at {} and {}", a, b),
-            PlayerMessage::Rematch => write!(f, "Rematch!"),
+            PlayerMessage::Challenge(coord) => write!(f, "Challenge the word at {}", coord),
+            PlayerMessage::Pass => write!(f, "Pass"),
+            PlayerMessage::Exchange(tile) => write!(f, "Exchange {}", tile),
+            PlayerMessage::Rematch { mirror: false } => write!(f, "Rematch!"),
+            PlayerMessage::Rematch { mirror: true } => write!(f, "Rematch on the mirrored board!"),
             PlayerMessage::Pause => write!(f, "Pause!"),
             PlayerMessage::Unpause => write!(f, "Unpause!"),
             PlayerMessage::RequestDefinitions(words) => write!(f, "Get definition of {words:?}"),
+            PlayerMessage::Chat(text) => write!(f, "Say {text}"),
             PlayerMessage::CreateAnonymousPlayer { .. } => {
                 write!(f, "Create a new anonymous player in the database")
             }
@@ -118,19 +170,35 @@ impl fmt::Display for PlayerMessage {
             PlayerMessage::LoadDailyPuzzle(_token, day) => {
                 write!(f, "Load any partial puzzle for day {day:?}")
             }
+            PlayerMessage::ReplayDailyPuzzle(_token, day) => {
+                write!(f, "Replay the puzzle for day {day:?}")
+            }
             PlayerMessage::PersistPuzzleMoves {
                 player_token: _,
                 human_player: _,
                 day,
                 moves,
                 won: _,
+                drawn: _,
+                replay: _,
+                resigned: _,
             } => {
                 write!(f, "Persist {} move(s) for day {day:?}", moves.len())
             }
             PlayerMessage::RequestStats(_token) => write!(f, "Requesting daily puzzle stats!"),
+            PlayerMessage::RequestDailyLeaderboard(day) => {
+                write!(f, "Request the leaderboard for day {day:?}")
+            }
+            PlayerMessage::RequestGameHistory(_token, page) => {
+                write!(f, "Request page {page} of the player's game history")
+            }
             PlayerMessage::LoadReplay(id) => write!(f, "Requesting the replay for {id}!"),
             PlayerMessage::MarkChangelogRead(id) => write!(f, "Marked changelog {id} as read"),
             PlayerMessage::GenericEvent { name } => write!(f, "Tracking a {name} event"),
+            PlayerMessage::AddWords { room, words, .. } => match room {
+                Some(room) => write!(f, "Add {} word(s) to room {room}", words.len()),
+                None => write!(f, "Add {} word(s) to every room", words.len()),
+            },
         }
     }
 }
@@ -151,10 +219,13 @@ pub struct GamePlayerMessage {
     pub time_remaining: Option<Duration>,
     pub turn_starts_no_later_than: Option<u64>,
     pub paused_turn_delta: Option<i64>,
+    /// Swaps this player has left, if the rules limit them
+    #[serde(default)]
+    pub remaining_swaps: Option<usize>,
 }
 
 impl GamePlayerMessage {
-    pub fn new(p: &Player, _game: &Game) -> Self {
+    pub fn new(p: &Player, game: &Game) -> Self {
         Self {
             name: p.name.clone(),
             index: p.index,
@@ -163,6 +234,7 @@ impl GamePlayerMessage {
             time_remaining: p.time_remaining,
             turn_starts_no_later_than: p.turn_starts_no_later_than,
             paused_turn_delta: p.paused_turn_delta,
+            remaining_swaps: game.remaining_swaps(p.index),
         }
     }
 }
@@ -179,6 +251,21 @@ pub struct GameStateMessage {
     pub game_ends_at: Option<u64>,
     pub remaining_turns: Option<u64>,
     pub paused: bool,
+    /// Set for spectators, who see the whole board but can't play
+    #[serde(default)]
+    pub read_only: bool,
+    /// The number of turns played, which clients report back when rejoining
+    #[serde(default)]
+    pub turn: u32,
+    /// Seconds the next turn waits after a battle, which clients pace battle animations to
+    #[serde(default)]
+    pub battle_delay: Option<u64>,
+    /// Whether defending words are only judged when challenged, so clients can offer challenges
+    #[serde(default)]
+    pub challenge_mode: bool,
+    /// The number of tiles hands are refilled to, which clients size their hand display to
+    #[serde(default)]
+    pub hand_capacity: usize,
 }
 
 impl fmt::Display for GameStateMessage {
@@ -198,11 +285,45 @@ impl fmt::Display for GameStateMessage {
     }
 }
 
+/// Brings a rejoining player's board up to date without resending all of it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameCatchUpMessage {
+    pub room_code: RoomCode,
+    pub players: Vec<GamePlayerMessage>,
+    pub player_number: PlayerNumber,
+    pub next_player_number: Option<PlayerNumber>,
+    /// Squares of the player's view that changed since the turn they last saw
+    pub squares: Vec<(Coordinate, Square)>,
+    pub hand: Hand,
+    pub game_ends_at: Option<u64>,
+    pub remaining_turns: Option<u64>,
+    pub paused: bool,
+    pub turn: u32,
+}
+
+impl fmt::Display for GameCatchUpMessage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "• Game {}, next up {:?}\n• Caught up to turn {} with {} changed square(s)\n• Hand: {}",
+            self.room_code,
+            self.next_player_number,
+            self.turn,
+            self.squares.len(),
+            self.hand,
+        )
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DailyStateMessage {
     pub puzzle_day: u32,
     pub attempt: u32,
+    #[serde(with = "crate::moves::packing::packed")]
     pub current_moves: Vec<Move>,
+    /// Whether these attempts replay a past day rather than count towards it
+    #[serde(default)]
+    pub replay: bool,
 }
 
 impl fmt::Display for DailyStateMessage {
@@ -232,12 +353,93 @@ pub struct DailyResult {
     pub attempts: Vec<DailyAttempt>,
 }
 
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DailyLeaderboardEntry {
+    /// Players with the same move count share a rank
+    pub rank: u32,
+    pub name: String,
+    pub moves: u32,
+}
+
+/// How a finished game was decided, kept separate from who won it
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GameEndReason {
+    /// Decided by play on the board
+    #[default]
+    Board,
+    Resigned {
+        player: PlayerNumber,
+        reason: Option<String>,
+    },
+    /// The player stayed disconnected for too long
+    Forfeited { player: PlayerNumber },
+    /// The player's clock ran out
+    TimedOut { player: PlayerNumber },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GameResult {
+    Won,
+    Lost,
+    Drawn,
+}
+
+/// A finished multiplayer game, as seen by one of its players
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GameHistoryEntry {
+    pub room_code: RoomCode,
+    pub opponents: Vec<String>,
+    pub result: GameResult,
+    /// Unix timestamp, in seconds
+    pub finished_at: u64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GameHistoryPage {
+    pub page: u32,
+    pub entries: Vec<GameHistoryEntry>,
+    pub has_more: bool,
+}
+
 #[derive(Default, Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct DailyStats {
     pub days: BTreeMap<u32, DailyResult>,
+    /// Consecutive days won, ending today or yesterday
+    #[serde(default)]
+    pub current_streak: u32,
+    #[serde(default)]
+    pub longest_streak: u32,
 }
 
 impl DailyStats {
+    /// Recalculates the streaks of consecutive days won, as seen from `today`.
+    /// A streak ending yesterday is still current, since today's puzzle may not be played yet.
+    pub fn calculate_streaks(&mut self, today: u32) {
+        let mut longest = 0;
+        let mut run = 0;
+        let mut last_won: Option<u32> = None;
+
+        for (&day, result) in &self.days {
+            if !result.attempts.iter().any(|a| a.won) {
+                continue;
+            }
+            run = if last_won.is_some_and(|last| last + 1 == day) {
+                run + 1
+            } else {
+                1
+            };
+            longest = longest.max(run);
+            last_won = Some(day);
+        }
+
+        self.longest_streak = longest;
+        self.current_streak = if last_won.is_some_and(|last| last + 1 >= today) {
+            run
+        } else {
+            0
+        };
+    }
+
     pub fn hydrate_missing_days(&mut self) {
         let Some((start_day, _)) = self.days.first_key_value() else {
             return;
@@ -256,6 +458,11 @@ impl DailyStats {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum GameMessage {
     Ping,
+    /// The server's clock, in milliseconds since the Unix epoch,
+    /// so that clients can show timers that agree with the server
+    ClockSync {
+        server_time: u64,
+    },
     Ack(Nonce),
     PleaseLogin,
     JoinedLobby(
@@ -263,32 +470,63 @@ pub enum GameMessage {
         RoomCode,
         Vec<LobbyPlayerMessage>,
         Board,
+        GameRules,
         TruncateToken,
     ),
-    LobbyUpdate(PlayerNumber, RoomCode, Vec<LobbyPlayerMessage>, Board),
+    LobbyUpdate(
+        PlayerNumber,
+        RoomCode,
+        Vec<LobbyPlayerMessage>,
+        Board,
+        GameRules,
+    ),
     StartedGame(GameStateMessage),
     GameTimingUpdate(GameStateMessage),
     GameUpdate(GameStateMessage),
-    GameEnd(GameStateMessage, PlayerNumber),
+    /// Sent instead of `StartedGame` to a player rejoining a game they were recently in
+    GameCatchUp(GameCatchUpMessage),
+    GameEnd(GameStateMessage, PlayerNumber, GameEndReason),
+    /// Sent instead of `GameEnd` when the game finished without a winner
+    GameDraw(GameStateMessage),
+    /// Sent to spectators when the players they were watching start a rematch in a new room
+    SpectatingRematch(RoomCode),
     GameError(RoomCode, PlayerNumber, String),
+    MoveRejected {
+        reason: GamePlayError,
+    },
     GenericError(String),
-    SupplyDefinitions(Vec<(String, Option<Vec<WordMeaning>>)>),
+    SupplyDefinitions(Vec<WordDefinition>),
+    ChatMessage {
+        from: PlayerNumber,
+        text: String,
+        at: u64,
+    },
     LoggedInAs {
         token: TruncateToken,
         unread_changelogs: Vec<String>,
     },
     ResumeDailyPuzzle(DailyStateMessage, Option<DailyStateMessage>), // (latest, best)
     DailyStats(DailyStats),
+    DailyLeaderboard {
+        day: u32,
+        entries: Vec<DailyLeaderboardEntry>,
+    },
     LoadDailyReplay(DailyStateMessage),
+    GameHistory(GameHistoryPage),
+    /// Sent to everyone connected when the UTC day rolls over onto a new daily puzzle
+    NewDailyAvailable {
+        day: u32,
+    },
 }
 
 impl fmt::Display for GameMessage {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             GameMessage::Ping => write!(f, "Game ping"),
+            GameMessage::ClockSync { server_time } => write!(f, "Server time is {server_time}ms"),
             GameMessage::Ack(_) => write!(f, "ACK"),
             GameMessage::PleaseLogin => write!(f, "Server is requesting player to login"),
-            GameMessage::JoinedLobby(player, room, players, board, _token) => write!(
+            GameMessage::JoinedLobby(player, room, players, board, _rules, _token) => write!(
                 f,
                 "Joined lobby {} as player {} with players {}. Board is:\n{}",
                 player,
@@ -300,7 +538,7 @@ impl fmt::Display for GameMessage {
                     .join(", "),
                 board
             ),
-            GameMessage::LobbyUpdate(player, room, players, board) => write!(
+            GameMessage::LobbyUpdate(player, room, players, board, _rules) => write!(
                 f,
                 "Update to lobby {} as player {}. Players are {}. Board is:\n{}",
                 player,
@@ -315,14 +553,25 @@ impl fmt::Display for GameMessage {
             GameMessage::StartedGame(game) => write!(f, "Started game:\n{}", game),
             GameMessage::GameTimingUpdate(game) => write!(f, "Update to timing:\n{}", game),
             GameMessage::GameUpdate(game) => write!(f, "Update to game:\n{}", game),
-            GameMessage::GameEnd(game, winner) => {
-                write!(f, "Conclusion of game, winner was {}:\n{}", winner, game)
+            GameMessage::GameCatchUp(game) => write!(f, "Catching up on game:\n{}", game),
+            GameMessage::GameEnd(game, winner, reason) => {
+                write!(
+                    f,
+                    "Conclusion of game, winner was {} by {:?}:\n{}",
+                    winner, reason, game
+                )
             }
+            GameMessage::GameDraw(game) => write!(f, "Conclusion of game, as a draw:\n{}", game),
+            GameMessage::SpectatingRematch(room) => write!(f, "Spectating the rematch in {}", room),
             GameMessage::GameError(_, _, msg) => write!(f, "Error in game: {}", msg),
+            GameMessage::MoveRejected { reason } => write!(f, "Move rejected: {}", reason),
             GameMessage::GenericError(msg) => write!(f, "Generic error: {}", msg),
             GameMessage::SupplyDefinitions(_) => {
                 write!(f, "Supplying definitions for words")
             }
+            GameMessage::ChatMessage { from, text, .. } => {
+                write!(f, "Player {from} said {text}")
+            }
             GameMessage::LoggedInAs { .. } => {
                 write!(f, "Logged in as a player")
             }
@@ -330,7 +579,67 @@ impl fmt::Display for GameMessage {
                 write!(f, "Starting puzzle:\n{}", puzzle)
             }
             GameMessage::DailyStats(stats) => write!(f, "Stats for {} days", stats.days.len()),
+            GameMessage::DailyLeaderboard { day, entries } => {
+                write!(f, "Leaderboard of {} players for day {day}", entries.len())
+            }
             GameMessage::LoadDailyReplay(puzzle) => write!(f, "Loading puzzle replay:\n{}", puzzle),
+            GameMessage::GameHistory(history) => write!(
+                f,
+                "Page {} of game history, with {} game(s)",
+                history.page,
+                history.entries.len()
+            ),
+            GameMessage::NewDailyAvailable { day } => {
+                write!(f, "Daily puzzle {day} is now available")
+            }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats_for(days: &[(u32, bool)]) -> DailyStats {
+        DailyStats {
+            days: days
+                .iter()
+                .map(|&(day, won)| {
+                    let attempts = vec![DailyAttempt {
+                        id: day.to_string(),
+                        moves: 5,
+                        won,
+                    }];
+                    (day, DailyResult { attempts })
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn daily_streaks() {
+        let mut stats = stats_for(&[
+            (1, true),
+            (2, true),
+            (3, true),
+            (4, false),
+            (6, true),
+            (7, true),
+        ]);
+
+        stats.calculate_streaks(7);
+        assert_eq!((stats.current_streak, stats.longest_streak), (2, 3));
+
+        // Today's puzzle hasn't been solved yet, but the streak holds until tomorrow
+        stats.calculate_streaks(8);
+        assert_eq!((stats.current_streak, stats.longest_streak), (2, 3));
+
+        stats.calculate_streaks(9);
+        assert_eq!((stats.current_streak, stats.longest_streak), (0, 3));
+
+        let mut empty = DailyStats::default();
+        empty.calculate_streaks(8);
+        assert_eq!((empty.current_streak, empty.longest_streak), (0, 0));
+    }
+}
@@ -6,6 +6,7 @@ use super::board::Coordinate;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Move {
+    /// Blank tiles are placed by passing the lowercase form of their assigned letter as the `tile`
     Place {
         player: usize,
         tile: char,
@@ -15,6 +16,26 @@ pub enum Move {
         player: usize,
         positions: [Coordinate; 2],
     },
+    /// Judges the opponent's words through `position` when playing with `challenge_mode`
+    Challenge { player: usize, position: Coordinate },
+    /// Ends the turn without changing the board, when playing with `allow_passing`
+    Pass { player: usize },
+    /// Returns `tile` from the player's hand to the bag in exchange for a random one,
+    /// when playing with `allow_exchanges`
+    Exchange { player: usize, tile: char },
+}
+
+impl Move {
+    /// The index of the player making this move
+    pub fn player(&self) -> usize {
+        match self {
+            Move::Place { player, .. }
+            | Move::Swap { player, .. }
+            | Move::Challenge { player, .. }
+            | Move::Pass { player }
+            | Move::Exchange { player, .. } => *player,
+        }
+    }
 }
 
 impl PartialEq for Move {
@@ -46,6 +67,29 @@ impl PartialEq for Move {
                     && (l_positions == r_positions
                         || (l_positions[0] == r_positions[1] && l_positions[1] == r_positions[0]))
             }
+            (
+                Self::Challenge {
+                    player: l_player,
+                    position: l_position,
+                },
+                Self::Challenge {
+                    player: r_player,
+                    position: r_position,
+                },
+            ) => l_player == r_player && l_position == r_position,
+            (Self::Pass { player: l_player }, Self::Pass { player: r_player }) => {
+                l_player == r_player
+            }
+            (
+                Self::Exchange {
+                    player: l_player,
+                    tile: l_tile,
+                },
+                Self::Exchange {
+                    player: r_player,
+                    tile: r_tile,
+                },
+            ) => l_player == r_player && l_tile == r_tile,
             _ => false,
         }
     }
@@ -53,15 +97,15 @@ impl PartialEq for Move {
 
 #[cfg(test)]
 mod tests {
-    use crate::bag::TileBag;
+    use crate::bag::{TileBag, BLANK_TILE};
     use crate::board::{Board, Coordinate, Square, SquareValidity};
     use crate::error::GamePlayError;
-    use crate::game::Game;
-    use crate::judge::Judge;
-    use crate::player::Player;
+    use crate::game::{Game, GameOutcome};
+    use crate::judge::{Judge, Outcome};
+    use crate::player::{Hand, Player};
     use crate::reporting::*;
     use crate::reporting::{BoardChange, BoardChangeAction};
-    use crate::rules::GameRules;
+    use crate::rules::{ExplosionRules, GameRules, Truncation};
 
     use super::super::bag::tests as TileUtils;
     use super::*;
@@ -130,6 +174,91 @@ mod tests {
         );
     }
 
+    fn first_placement_game(first_placement: crate::rules::FirstPlacement) -> Game {
+        let b = Board::from_string(
+            "~~ ~~ |0 ~~ ~~\n\
+             __ __ __ __ __\n\
+             __ __ __ __ __\n\
+             __ __ __ __ __\n\
+             __ __ __ __ __\n\
+             ~~ ~~ |1 ~~ ~~",
+        );
+        let mut bag = TileUtils::trivial_bag();
+        let players = vec![
+            Player::new("A".into(), 0, 7, &mut bag, None, (0, 0, 0)),
+            Player::new("B".into(), 1, 7, &mut bag, None, (0, 0, 0)),
+        ];
+        let rules = GameRules {
+            first_placement,
+            truncation: crate::rules::Truncation::None,
+            ..GameRules::generation(0)
+        };
+
+        Game {
+            board: b,
+            bag,
+            players,
+            player_turn_count: vec![0, 0],
+            judge: short_dict(),
+            ..Game::new_legacy(1, 1, None, rules)
+        }
+    }
+
+    fn place(player: usize, x: usize, y: usize) -> Move {
+        Move::Place {
+            player,
+            tile: 'A',
+            position: Coordinate { x, y },
+        }
+    }
+
+    #[test]
+    fn first_placement_beside_dock() {
+        let game = first_placement_game(crate::rules::FirstPlacement::BesideDock);
+
+        assert_eq!(game.validate_move(&place(0, 2, 1)), Ok(()));
+        assert_eq!(
+            game.validate_move(&place(0, 0, 2)),
+            Err(GamePlayError::NonAdjacentPlace)
+        );
+        assert!(game
+            .legal_placements(0)
+            .iter()
+            .all(|(position, _)| *position == Coordinate { x: 2, y: 1 }));
+    }
+
+    #[test]
+    fn first_placement_on_own_land() {
+        let mut game = first_placement_game(crate::rules::FirstPlacement::OwnLand);
+
+        // Anywhere nearer your own dock is open, but the far side of the board isn't
+        assert_eq!(game.validate_move(&place(0, 0, 2)), Ok(()));
+        assert_eq!(game.validate_move(&place(0, 4, 1)), Ok(()));
+        assert_eq!(
+            game.validate_move(&place(0, 0, 3)),
+            Err(GamePlayError::NonAdjacentPlace)
+        );
+        assert!(game
+            .legal_placements(0)
+            .iter()
+            .any(|(position, _)| *position == Coordinate { x: 0, y: 2 }));
+        assert!(game
+            .legal_placements(0)
+            .iter()
+            .all(|(position, _)| position.y < 3));
+
+        // Once a tile is down, placements go back to growing from your pieces
+        game.make_move(place(0, 0, 2), None, None, None).unwrap();
+        assert_eq!(
+            game.validate_move(&place(0, 4, 1)),
+            Err(GamePlayError::NonAdjacentPlace)
+        );
+        assert_eq!(game.validate_move(&place(0, 0, 1)), Ok(()));
+
+        // Opponents without tiles still place freely on their own land
+        assert_eq!(game.validate_move(&place(1, 4, 4)), Ok(()));
+    }
+
     #[test]
     fn can_place_and_swap() {
         let mut bag = TileUtils::a_b_bag();
@@ -305,6 +434,273 @@ mod tests {
         );
     }
 
+    #[test]
+    fn limited_swaps_per_game() {
+        use crate::rules::{SwapPenalty, Swapping};
+
+        let b = Board::from_string(
+            "~~ |0 ~~ ~~\n\
+             __ A0 C0 __\n\
+             __ __ __ __\n\
+             __ __ __ __\n\
+             __ B1 D1 __\n\
+             ~~ |1 ~~ ~~",
+        );
+        let mut bag = TileUtils::trivial_bag();
+        let players = vec![
+            Player::new("A".into(), 0, 7, &mut bag, None, (0, 0, 0)),
+            Player::new("B".into(), 1, 7, &mut bag, None, (0, 0, 0)),
+        ];
+        let rules = GameRules {
+            swapping: Swapping::Contiguous(SwapPenalty::Limited { swaps_per_game: 2 }),
+            ..GameRules::generation(0)
+        };
+
+        let mut game = Game {
+            board: b,
+            bag,
+            players,
+            player_turn_count: vec![0, 0],
+            judge: short_dict(),
+            ..Game::new_legacy(1, 1, None, rules)
+        };
+        game.start();
+
+        let swap = |player: usize| {
+            let y = if player == 0 { 1 } else { 4 };
+            Move::Swap {
+                player,
+                positions: [Coordinate { x: 1, y }, Coordinate { x: 2, y }],
+            }
+        };
+
+        assert_eq!(game.remaining_swaps(0), Some(2));
+        game.play_turn(swap(0), None, None, None).unwrap();
+        game.play_turn(swap(1), None, None, None).unwrap();
+        assert_eq!(game.remaining_swaps(0), Some(1));
+
+        // Placing a tile doesn't give any swaps back
+        game.play_turn(
+            Move::Place {
+                player: 0,
+                tile: 'A',
+                position: Coordinate { x: 0, y: 1 },
+            },
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        game.play_turn(swap(1), None, None, None).unwrap();
+        assert_eq!(game.remaining_swaps(0), Some(1));
+
+        game.play_turn(swap(0), None, None, None).unwrap();
+        assert_eq!(game.remaining_swaps(0), Some(0));
+        assert_eq!(game.remaining_swaps(1), Some(0));
+
+        game.play_turn(
+            Move::Place {
+                player: 1,
+                tile: 'A',
+                position: Coordinate { x: 0, y: 4 },
+            },
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            game.play_turn(swap(0), None, None, None),
+            Err(GamePlayError::NoSwapsRemaining.to_string())
+        );
+    }
+
+    #[test]
+    fn validation_leaves_game_untouched() {
+        let mut bag = TileUtils::a_b_bag();
+        let players = vec![Player::new("A".into(), 0, 7, &mut bag, None, (0, 0, 0))];
+
+        let mut game = Game {
+            bag,
+            players,
+            player_turn_count: vec![0, 0],
+            judge: short_dict(),
+            ..Game::new_legacy(3, 3, None, GameRules::generation(0))
+        };
+
+        let place = |tile, x, y| Move::Place {
+            player: 0,
+            tile,
+            position: Coordinate { x, y },
+        };
+
+        let board = game.board.clone();
+        let bag = game.bag.clone();
+        let hand = game.players[0].hand.clone();
+
+        assert_eq!(game.validate_move(&place('A', 3, 2)), Ok(()));
+        assert_eq!(
+            game.validate_move(&place('A', 4, 3)),
+            Err(GamePlayError::NonAdjacentPlace)
+        );
+        assert_eq!(
+            game.validate_move(&place('Z', 3, 2)),
+            Err(GamePlayError::PlayerDoesNotHaveTile {
+                player: 0,
+                tile: 'Z'
+            })
+        );
+        assert_eq!(
+            game.validate_move(&Move::Place {
+                player: 3,
+                tile: 'A',
+                position: Coordinate { x: 3, y: 2 }
+            }),
+            Err(GamePlayError::NonExistentPlayer { index: 3 })
+        );
+
+        assert_eq!(game.board, board);
+        assert_eq!(game.bag, bag);
+        assert_eq!(game.players[0].hand, hand);
+
+        // Validation agrees with actually making the moves
+        game.make_move(place('A', 3, 2), None, None, None).unwrap();
+        assert_eq!(
+            game.validate_move(&place('B', 3, 2)),
+            game.make_move(place('B', 3, 2), None, None, None)
+                .map(|_| ())
+        );
+        game.make_move(place('B', 3, 3), None, None, None).unwrap();
+
+        let swap = Move::Swap {
+            player: 0,
+            positions: [Coordinate { x: 3, y: 2 }, Coordinate { x: 3, y: 2 }],
+        };
+        assert_eq!(game.validate_move(&swap), Err(GamePlayError::SelfSwap));
+
+        let swap = Move::Swap {
+            player: 0,
+            positions: [Coordinate { x: 3, y: 2 }, Coordinate { x: 3, y: 3 }],
+        };
+        let board = game.board.clone();
+        assert_eq!(game.validate_move(&swap), Ok(()));
+        assert_eq!(game.board, board);
+        assert!(game.make_move(swap, None, None, None).is_ok());
+    }
+
+    #[test]
+    fn only_the_next_player_can_move() {
+        let mut game = Game::new(9, 9, Some(3), GameRules::generation(1));
+        game.add_player("A".into());
+        game.add_player("B".into());
+        game.start();
+
+        let current = game.next_player.unwrap();
+        let other = 1 - current;
+        assert_eq!(game.check_turn(current), Ok(()));
+        assert_eq!(
+            game.check_turn(other),
+            Err(GamePlayError::NotYourTurn { player: other })
+        );
+        assert_eq!(
+            game.check_turn(2),
+            Err(GamePlayError::NonExistentPlayer { index: 2 })
+        );
+
+        let position = *game
+            .board
+            .playable_positions(other, &game.rules.truncation)
+            .iter()
+            .min()
+            .unwrap();
+        let board = game.board.clone();
+        assert!(game
+            .play_turn(
+                Move::Place {
+                    player: other,
+                    tile: game.players[other].hand.0[0],
+                    position,
+                },
+                None,
+                None,
+                None
+            )
+            .is_err());
+        assert_eq!(game.board, board);
+
+        game.winner = Some(current);
+        assert_eq!(game.check_turn(current), Err(GamePlayError::GameOver));
+    }
+
+    #[test]
+    fn place_blank_tiles() {
+        let mut bag = TileUtils::trivial_bag();
+        let players = vec![Player::new("A".into(), 0, 7, &mut bag, None, (0, 0, 0))];
+
+        let mut game = Game {
+            bag,
+            players,
+            player_turn_count: vec![0, 0],
+            judge: short_dict(),
+            ..Game::new_legacy(3, 3, None, GameRules::generation(0))
+        };
+        game.players[0].hand = Hand(vec![BLANK_TILE]);
+
+        assert_eq!(
+            game.make_move(
+                Move::Place {
+                    player: 0,
+                    tile: BLANK_TILE,
+                    position: Coordinate { x: 3, y: 2 },
+                },
+                None,
+                None,
+                None
+            ),
+            Err(GamePlayError::UnassignedBlank)
+        );
+
+        let changes = game
+            .make_move(
+                Move::Place {
+                    player: 0,
+                    tile: 'e',
+                    position: Coordinate { x: 3, y: 2 },
+                },
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+        assert!(changes.iter().any(|c| matches!(
+            c,
+            Change::Hand(HandChange { removed, .. }) if removed == &vec![BLANK_TILE]
+        )));
+        assert!(matches!(
+            game.board.get(Coordinate { x: 3, y: 2 }),
+            Ok(Square::Occupied { tile: 'e', .. })
+        ));
+        assert_eq!(game.players[0].hand, Hand(vec!['A']));
+
+        // Only blanks can be assigned a letter
+        assert_eq!(
+            game.make_move(
+                Move::Place {
+                    player: 0,
+                    tile: 'a',
+                    position: Coordinate { x: 3, y: 3 },
+                },
+                None,
+                None,
+                None
+            ),
+            Err(GamePlayError::PlayerDoesNotHaveTile {
+                player: 0,
+                tile: BLANK_TILE
+            })
+        );
+    }
+
     #[test]
     fn invalid_player_or_tile() {
         let mut bag = TileBag::latest(None).1;
@@ -478,8 +874,382 @@ mod tests {
         );
     }
 
+    /// A single battle: `player` places `tile` at `position` on the `before` board
+    /// under rules `generation`, which should leave the `after` board and `winner`.
+    /// New regression cases only need an entry in `battle_resolution_cases`,
+    /// filling in whatever differs from `BATTLE`.
+    struct BattleCase {
+        name: &'static str,
+        generation: u32,
+        before: &'static str,
+        player: usize,
+        tile: char,
+        position: Coordinate,
+        attacker_dictionary: Option<fn() -> Judge>,
+        defender_dictionary: Option<fn() -> Judge>,
+        after: &'static str,
+        winner: Option<usize>,
+    }
+
+    const BATTLE: BattleCase = BattleCase {
+        name: "",
+        generation: 0,
+        before: "",
+        player: 0,
+        tile: 'A',
+        position: Coordinate { x: 0, y: 0 },
+        attacker_dictionary: None,
+        defender_dictionary: None,
+        after: "",
+        winner: None,
+    };
+
+    impl BattleCase {
+        fn resolve(&self) -> Game {
+            let mut bag = TileUtils::trivial_bag();
+            let players = vec![
+                Player::new("A".into(), 0, 7, &mut bag, None, (0, 0, 0)),
+                Player::new("B".into(), 1, 7, &mut bag, None, (0, 0, 0)),
+            ];
+
+            let mut game = Game {
+                board: Board::from_string(self.before),
+                bag,
+                players,
+                player_turn_count: vec![0, 0],
+                judge: short_dict(),
+                ..Game::new_legacy(3, 1, None, GameRules::generation(self.generation))
+            };
+            game.next_player = Some(self.player);
+            game.start();
+
+            let attacker_dictionary = self.attacker_dictionary.map(|dict| dict());
+            let defender_dictionary = self.defender_dictionary.map(|dict| dict());
+            game.play_turn(
+                Move::Place {
+                    player: self.player,
+                    tile: self.tile,
+                    position: self.position,
+                },
+                attacker_dictionary.as_ref().map(|j| &j.builtin_dictionary),
+                defender_dictionary.as_ref().map(|j| &j.builtin_dictionary),
+                None,
+            )
+            .unwrap_or_else(|e| panic!("{} (generation {}): {e}", self.name, self.generation));
+
+            game
+        }
+    }
+
+    #[test]
+    fn battle_resolution_cases() {
+        let c = |x: usize, y: usize| Coordinate::new(x, y);
+
+        let cases = [
+            BattleCase {
+                name: "successful attack",
+                before: "__ S0 X0 |0 __\n\
+                         __ T0 __ __ __\n\
+                         __ R0 __ __ __\n\
+                         __ __ I1 __ __\n\
+                         __ __ T1 |1 __",
+                position: c(1, 3),
+                after: "__ S0 X0 |0 __\n\
+                        __ T0 __ __ __\n\
+                        __ R0 __ __ __\n\
+                        __ A0 __ __ __\n\
+                        __ __ __ |1 __",
+                ..BATTLE
+            },
+            BattleCase {
+                name: "failed attack",
+                before: "__ X0 X0 |0 __\n\
+                         __ T0 __ __ __\n\
+                         __ R0 __ __ __\n\
+                         __ __ I1 __ __\n\
+                         __ __ T1 |1 __",
+                position: c(1, 3),
+                after: "__ __ X0 |0 __\n\
+                        __ __ __ __ __\n\
+                        __ __ __ __ __\n\
+                        __ __ I1 __ __\n\
+                        __ __ T1 |1 __",
+                ..BATTLE
+            },
+            BattleCase {
+                name: "explosion",
+                before: "__ __ S0 |0 __\n\
+                         __ __ T0 __ __\n\
+                         __ __ R0 __ __\n\
+                         __ B1 __ X1 __\n\
+                         __ I1 __ X1 __\n\
+                         __ G1 X1 X1 __\n\
+                         ~~ ~~ |1 ~~ ~~",
+                position: c(2, 3),
+                after: "__ __ S0 |0 __\n\
+                        __ __ T0 __ __\n\
+                        __ __ R0 __ __\n\
+                        __ __ A0 __ __\n\
+                        __ I1 __ __ __\n\
+                        __ G1 X1 __ __\n\
+                        ~~ ~~ |1 ~~ ~~",
+                ..BATTLE
+            },
+            BattleCase {
+                name: "no defenders",
+                before: "~~ |0 __ ~~ ~~\n\
+                         __ __ __ __ __\n\
+                         __ __ __ __ __\n\
+                         __ __ __ __ __\n\
+                         ~~ ~~ T1 |1 ~~",
+                position: c(2, 0),
+                after: "~~ |0 A0 ~~ ~~\n\
+                        __ __ __ __ __\n\
+                        __ __ __ __ __\n\
+                        __ __ __ __ __\n\
+                        ~~ ~~ T1 |1 ~~",
+                ..BATTLE
+            },
+            BattleCase {
+                name: "attacker's dictionary lacks their word",
+                before: "__ S0 X0 |0 __\n\
+                         __ T0 __ __ __\n\
+                         __ R0 __ __ __\n\
+                         __ __ A1 __ __\n\
+                         __ __ R1 __ __\n\
+                         __ __ T1 __ __\n\
+                         __ __ S1 |1 __",
+                position: c(1, 3),
+                attacker_dictionary: Some(b_dict),
+                after: "__ __ X0 |0 __\n\
+                        __ __ __ __ __\n\
+                        __ __ __ __ __\n\
+                        __ __ A1 __ __\n\
+                        __ __ R1 __ __\n\
+                        __ __ T1 __ __\n\
+                        __ __ S1 |1 __",
+                ..BATTLE
+            },
+            BattleCase {
+                name: "defender's dictionary lacks their word",
+                before: "__ S0 X0 |0 __\n\
+                         __ T0 __ __ __\n\
+                         __ R0 __ __ __\n\
+                         __ __ A1 __ __\n\
+                         __ __ R1 __ __\n\
+                         __ __ T1 __ __\n\
+                         __ __ S1 |1 __",
+                position: c(1, 3),
+                defender_dictionary: Some(b_dict),
+                after: "__ S0 X0 |0 __\n\
+                        __ T0 __ __ __\n\
+                        __ R0 __ __ __\n\
+                        __ A0 __ __ __\n\
+                        __ __ __ __ __\n\
+                        __ __ __ __ __\n\
+                        __ __ __ |1 __",
+                ..BATTLE
+            },
+            BattleCase {
+                name: "win",
+                before: "__ __ S0 |0 __\n\
+                         __ __ T0 __ __\n\
+                         __ A0 R0 __ __\n\
+                         D0 B0 __ X1 __\n\
+                         N0 __ __ X1 __\n\
+                         __ __ X1 X1 __\n\
+                         #1 #1 |1 #1 #1",
+                position: c(0, 5),
+                after: "__ __ S0 |0 __\n\
+                        __ __ T0 __ __\n\
+                        __ A0 R0 __ __\n\
+                        D0 B0 __ X1 __\n\
+                        N0 __ __ X1 __\n\
+                        A0 __ X1 X1 __\n\
+                        ⊭1 #1 |1 #1 #1",
+                winner: Some(0),
+                ..BATTLE
+            },
+            BattleCase {
+                name: "failed win",
+                before: "__ __ S0 |0 __\n\
+                         __ __ T0 __ __\n\
+                         __ A0 R0 __ __\n\
+                         G0 B0 __ X1 __\n\
+                         N0 __ __ X1 __\n\
+                         __ __ X1 X1 __\n\
+                         #1 #1 |1 #1 #1",
+                position: c(0, 5),
+                after: "__ __ S0 |0 __\n\
+                        __ __ T0 __ __\n\
+                        __ A0 R0 __ __\n\
+                        __ B0 __ X1 __\n\
+                        __ __ __ X1 __\n\
+                        __ __ X1 X1 __\n\
+                        #1 #1 |1 #1 #1",
+                ..BATTLE
+            },
+            BattleCase {
+                name: "failed win due to battle",
+                before: "__ __ S0 |0 __\n\
+                         __ __ T0 __ __\n\
+                         __ A0 R0 __ __\n\
+                         D0 B0 __ X1 __\n\
+                         N0 __ __ X1 __\n\
+                         __ B1 I1 G1 __\n\
+                         #1 #1 |1 #1 #1",
+                position: c(0, 5),
+                after: "__ __ S0 |0 __\n\
+                        __ __ T0 __ __\n\
+                        __ A0 R0 __ __\n\
+                        __ B0 __ X1 __\n\
+                        __ __ __ X1 __\n\
+                        __ B1 I1 G1 __\n\
+                        #1 #1 |1 #1 #1",
+                ..BATTLE
+            },
+            BattleCase {
+                name: "win after battle",
+                before: "__ __ S0 |0 __\n\
+                         __ __ T0 __ __\n\
+                         __ A0 R0 __ __\n\
+                         D0 B0 __ X1 __\n\
+                         N0 __ __ X1 __\n\
+                         __ X1 I1 G1 __\n\
+                         #1 #1 |1 #1 #1",
+                position: c(0, 5),
+                after: "__ __ S0 |0 __\n\
+                        __ __ T0 __ __\n\
+                        __ A0 R0 __ __\n\
+                        D0 B0 __ __ __\n\
+                        N0 __ __ __ __\n\
+                        A0 __ __ __ __\n\
+                        ⊭1 #1 |1 #1 #1",
+                winner: Some(0),
+                ..BATTLE
+            },
+            BattleCase {
+                name: "win via explosion",
+                before: "__ __ __ __ S0 |0 __\n\
+                         __ __ __ __ T0 __ __\n\
+                         __ __ __ A0 R0 __ __\n\
+                         __ __ D0 B0 __ X1 __\n\
+                         __ __ N0 __ __ X1 __\n\
+                         X1 X1 __ B1 I1 G1 __\n\
+                         |1 #1 #1 #1 |1 #1 #1",
+                position: c(2, 5),
+                after: "__ __ __ __ S0 |0 __\n\
+                        __ __ __ __ T0 __ __\n\
+                        __ __ __ A0 R0 __ __\n\
+                        __ __ D0 B0 __ X1 __\n\
+                        __ __ N0 __ __ X1 __\n\
+                        __ __ A0 __ I1 G1 __\n\
+                        |1 #1 ⊭1 #1 |1 #1 #1",
+                winner: Some(0),
+                ..BATTLE
+            },
+            BattleCase {
+                name: "win via blocking",
+                before: "__ __ __ __ S0 |0 __\n\
+                         #1 __ __ __ T0 __ __\n\
+                         ~~ __ __ A0 R0 __ __\n\
+                         |1 __ D0 B0 __ __ __\n\
+                         ~~ __ __ __ __ __ __\n\
+                         #1 __ __ __ __ __ __",
+                position: c(1, 3),
+                after: "__ __ __ __ S0 |0 __\n\
+                        ⊭1 __ __ __ T0 __ __\n\
+                        ~~ __ __ A0 R0 __ __\n\
+                        |1 A0 D0 B0 __ __ __\n\
+                        ~~ __ __ __ __ __ __\n\
+                        ⊭1 __ __ __ __ __ __",
+                winner: Some(0),
+                ..BATTLE
+            },
+            // Generation 0 needs attackers to be two letters longer than defenders,
+            // while generation 2 only needs them to be one letter longer
+            BattleCase {
+                name: "ARTS loses to BIG",
+                generation: 0,
+                before: "__ S0 X0 |0 __\n\
+                         __ T0 __ __ __\n\
+                         __ R0 __ __ __\n\
+                         __ __ __ __ __\n\
+                         __ B1 I1 G1 __\n\
+                         __ __ __ |1 __",
+                position: c(1, 3),
+                after: "__ __ X0 |0 __\n\
+                        __ __ __ __ __\n\
+                        __ __ __ __ __\n\
+                        __ __ __ __ __\n\
+                        __ B1 I1 G1 __\n\
+                        __ __ __ |1 __",
+                ..BATTLE
+            },
+            BattleCase {
+                name: "ARTS beats BIG",
+                generation: 2,
+                before: "__ S0 X0 |0 __\n\
+                         __ T0 __ __ __\n\
+                         __ R0 __ __ __\n\
+                         __ __ __ __ __\n\
+                         __ B1 I1 G1 __\n\
+                         __ __ __ |1 __",
+                position: c(1, 3),
+                after: "__ S0 X0 |0 __\n\
+                        __ T0 __ __ __\n\
+                        __ R0 __ __ __\n\
+                        __ A0 __ __ __\n\
+                        __ __ __ __ __\n\
+                        __ __ __ |1 __",
+                ..BATTLE
+            },
+            // Artifacts can't be attacked in generation 0, but fall to any valid word in generation 2
+            BattleCase {
+                name: "AND can't attack an artifact",
+                generation: 0,
+                before: "__ |0 __\n\
+                         __ D0 __\n\
+                         __ N0 __\n\
+                         __ __ __\n\
+                         __ |1 __",
+                position: c(1, 3),
+                after: "__ |0 __\n\
+                        __ D0 __\n\
+                        __ N0 __\n\
+                        __ A0 __\n\
+                        __ |1 __",
+                ..BATTLE
+            },
+            BattleCase {
+                name: "AND defeats an artifact",
+                generation: 2,
+                before: "__ |0 __\n\
+                         __ D0 __\n\
+                         __ N0 __\n\
+                         __ __ __\n\
+                         __ |1 __",
+                position: c(1, 3),
+                after: "__ |0 __\n\
+                        __ D0 __\n\
+                        __ N0 __\n\
+                        __ A0 __\n\
+                        __ |1 __",
+                winner: Some(0),
+                ..BATTLE
+            },
+        ];
+
+        for case in cases {
+            let game = case.resolve();
+            let context = format!("{} (generation {})", case.name, case.generation);
+            assert_eq!(game.board.to_string(), case.after, "{context}");
+            assert_eq!(game.winner, case.winner, "{context}");
+        }
+    }
+
     #[test]
-    fn resolve_successful_attack() {
+    fn shape_hash_follows_moves_and_battles() {
         let b = Board::from_string(
             "__ S0 X0 |0 __\n\
              __ T0 __ __ __\n\
@@ -502,72 +1272,31 @@ mod tests {
             ..Game::new_legacy(1, 1, None, GameRules::generation(0))
         };
 
-        game.make_move(
-            Move::Place {
-                player: 0,
-                tile: 'A',
-                position: Coordinate { x: 1, y: 3 },
-            },
-            None,
-            None,
-            None,
-        )
-        .unwrap();
-
-        assert_eq!(
-            game.board.to_string(),
-            "__ S0 X0 |0 __\n\
-             __ T0 __ __ __\n\
-             __ R0 __ __ __\n\
-             __ A0 __ __ __\n\
-             __ __ __ |1 __",
-        )
-    }
+        let recomputed = |board: &Board| Board::from_string(board.to_string()).shape_hash();
+        assert_eq!(game.board.shape_hash(), recomputed(&game.board));
 
-    #[test]
-    fn resolve_failed_attack() {
-        let b = Board::from_string(
-            "__ X0 X0 |0 __\n\
-             __ T0 __ __ __\n\
-             __ R0 __ __ __\n\
-             __ __ I1 __ __\n\
-             __ __ T1 |1 __",
-        );
-        let mut bag = TileUtils::trivial_bag();
-        let players = vec![
-            Player::new("A".into(), 0, 7, &mut bag, None, (0, 0, 0)),
-            Player::new("B".into(), 1, 7, &mut bag, None, (0, 0, 0)),
+        let placements = [
+            // Defeats both of player 1's tiles
+            (0, Coordinate { x: 1, y: 3 }),
+            (1, Coordinate { x: 3, y: 3 }),
+            (0, Coordinate { x: 0, y: 0 }),
+            // Attacks player 1's new tile
+            (0, Coordinate { x: 2, y: 3 }),
         ];
-
-        let mut game = Game {
-            board: b,
-            bag,
-            players,
-            player_turn_count: vec![0, 0],
-            judge: short_dict(),
-            ..Game::new_legacy(3, 1, None, GameRules::generation(0))
-        };
-
-        game.make_move(
-            Move::Place {
-                player: 0,
-                tile: 'A',
-                position: Coordinate { x: 1, y: 3 },
-            },
-            None,
-            None,
-            None,
-        )
-        .unwrap();
-
-        assert_eq!(
-            game.board.to_string(),
-            "__ __ X0 |0 __\n\
-             __ __ __ __ __\n\
-             __ __ __ __ __\n\
-             __ __ I1 __ __\n\
-             __ __ T1 |1 __",
-        )
+        for (player, position) in placements {
+            game.make_move(
+                Move::Place {
+                    player,
+                    tile: 'A',
+                    position,
+                },
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+            assert_eq!(game.board.shape_hash(), recomputed(&game.board));
+        }
     }
 
     #[test]
@@ -633,7 +1362,7 @@ mod tests {
     }
 
     #[test]
-    fn resolve_explosion() {
+    fn resolve_explosion_radius_two() {
         let b = Board::from_string(
             "__ __ S0 |0 __\n\
              __ __ T0 __ __\n\
@@ -648,6 +1377,15 @@ mod tests {
             Player::new("A".into(), 0, 7, &mut bag, None, (0, 0, 0)),
             Player::new("B".into(), 1, 7, &mut bag, None, (0, 0, 0)),
         ];
+        // Truncation is off so that only the battle and explosion clear tiles
+        let rules = GameRules {
+            explosions: ExplosionRules {
+                radius: 2,
+                chain_reaction: false,
+            },
+            truncation: Truncation::None,
+            ..GameRules::generation(0)
+        };
 
         let mut game = Game {
             board: b,
@@ -655,7 +1393,7 @@ mod tests {
             players,
             player_turn_count: vec![0, 0],
             judge: short_dict(),
-            ..Game::new_legacy(3, 1, None, GameRules::generation(0))
+            ..Game::new_legacy(3, 1, None, rules)
         };
 
         game.make_move(
@@ -670,34 +1408,43 @@ mod tests {
         )
         .unwrap();
 
-        assert_eq!(
-            game.board.to_string(),
-            "__ __ S0 |0 __\n\
-             __ __ T0 __ __\n\
-             __ __ R0 __ __\n\
-             __ __ A0 __ __\n\
-             __ I1 __ __ __\n\
-             __ G1 X1 __ __\n\
-             ~~ ~~ |1 ~~ ~~",
-        );
+        // The blast reaches I and the lower X two steps away, but not G three steps away
+        insta::assert_snapshot!(game.board.to_string(), @r###"
+        __ __ S0 |0 __
+        __ __ T0 __ __
+        __ __ R0 __ __
+        __ __ A0 __ __
+        __ __ __ __ __
+        __ G1 __ __ __
+        ~~ ~~ |1 ~~ ~~
+        "###);
     }
 
     #[test]
-    fn resolve_win() {
+    fn resolve_explosion_chain_reaction() {
         let b = Board::from_string(
             "__ __ S0 |0 __\n\
              __ __ T0 __ __\n\
-             __ A0 R0 __ __\n\
-             D0 B0 __ X1 __\n\
-             N0 __ __ X1 __\n\
-             __ __ X1 X1 __\n\
-             #1 #1 |1 #1 #1",
+             __ __ R0 __ __\n\
+             __ B1 __ X1 __\n\
+             __ I1 __ X1 __\n\
+             __ G1 X1 X1 __\n\
+             ~~ ~~ |1 ~~ ~~",
         );
         let mut bag = TileUtils::trivial_bag();
         let players = vec![
             Player::new("A".into(), 0, 7, &mut bag, None, (0, 0, 0)),
             Player::new("B".into(), 1, 7, &mut bag, None, (0, 0, 0)),
         ];
+        // Truncation is off so that only the battle and explosion clear tiles
+        let rules = GameRules {
+            explosions: ExplosionRules {
+                radius: 1,
+                chain_reaction: true,
+            },
+            truncation: Truncation::None,
+            ..GameRules::generation(0)
+        };
 
         let mut game = Game {
             board: b,
@@ -705,51 +1452,42 @@ mod tests {
             players,
             player_turn_count: vec![0, 0],
             judge: short_dict(),
-            ..Game::new_legacy(3, 1, None, GameRules::generation(0))
+            ..Game::new_legacy(3, 1, None, rules)
         };
-        game.start();
 
-        _ = game.play_turn(
+        game.make_move(
             Move::Place {
                 player: 0,
                 tile: 'A',
-                position: Coordinate { x: 0, y: 5 },
+                position: Coordinate { x: 2, y: 3 },
             },
             None,
             None,
             None,
-        );
+        )
+        .unwrap();
 
-        assert_eq!(
-            game.board.to_string(),
-            "__ __ S0 |0 __\n\
-             __ __ T0 __ __\n\
-             __ A0 R0 __ __\n\
-             D0 B0 __ X1 __\n\
-             N0 __ __ X1 __\n\
-             A0 __ X1 X1 __\n\
-             ⊭1 #1 |1 #1 #1",
-        );
-        assert_eq!(
-            game.board.get(Coordinate { x: 0, y: 6 }).unwrap(),
-            Square::Town {
-                player: 1,
-                defeated: true,
-                foggy: false
-            }
-        );
-        assert_eq!(game.winner, Some(0));
+        // Exploding B sets off the rest of its cluster
+        insta::assert_snapshot!(game.board.to_string(), @r###"
+        __ __ S0 |0 __
+        __ __ T0 __ __
+        __ __ R0 __ __
+        __ __ A0 __ __
+        __ __ __ __ __
+        __ __ __ __ __
+        ~~ ~~ |1 ~~ ~~
+        "###);
     }
 
     #[test]
-    fn resolve_failed_win() {
+    fn defeated_towns_recover_over_time() {
         let b = Board::from_string(
             "__ __ S0 |0 __\n\
              __ __ T0 __ __\n\
              __ A0 R0 __ __\n\
-             G0 B0 __ X1 __\n\
+             D0 B0 __ X1 __\n\
              N0 __ __ X1 __\n\
-             __ __ X1 X1 __\n\
+             __ X1 I1 G1 __\n\
              #1 #1 |1 #1 #1",
         );
         let mut bag = TileUtils::trivial_bag();
@@ -757,6 +1495,16 @@ mod tests {
             Player::new("A".into(), 0, 7, &mut bag, None, (0, 0, 0)),
             Player::new("B".into(), 1, 7, &mut bag, None, (0, 0, 0)),
         ];
+        let rules = GameRules {
+            win_condition: crate::rules::WinCondition::Destination {
+                town_defense: crate::rules::TownDefense::BeatenWithDefenseStrength(0),
+                artifact_defense: crate::rules::ArtifactDefense::Invincible,
+                towns_to_defeat: crate::rules::TownsToDefeat::All,
+            },
+            town_recovery: crate::rules::TownRecovery::AfterTurns(1),
+            battle_delay: 0,
+            ..GameRules::generation(0)
+        };
 
         let mut game = Game {
             board: b,
@@ -764,10 +1512,11 @@ mod tests {
             players,
             player_turn_count: vec![0, 0],
             judge: short_dict(),
-            ..Game::new_legacy(3, 1, None, GameRules::generation(0))
+            ..Game::new_legacy(3, 1, None, rules)
         };
         game.start();
 
+        let town = Coordinate { x: 0, y: 6 };
         _ = game.play_turn(
             Move::Place {
                 player: 0,
@@ -778,324 +1527,344 @@ mod tests {
             None,
             None,
         );
-
-        assert_eq!(
-            game.board.to_string(),
-            "__ __ S0 |0 __\n\
-             __ __ T0 __ __\n\
-             __ A0 R0 __ __\n\
-             __ B0 __ X1 __\n\
-             __ __ __ X1 __\n\
-             __ __ X1 X1 __\n\
-             #1 #1 |1 #1 #1",
-        );
         assert_eq!(
-            game.board.get(Coordinate { x: 0, y: 6 }).unwrap(),
-            Square::Town {
+            game.board.get(town),
+            Ok(Square::Town {
                 player: 1,
-                defeated: false,
+                defeated: true,
                 foggy: false
-            }
+            })
         );
         assert_eq!(game.winner, None);
-    }
-
-    #[test]
-    fn resolve_failed_win_due_to_battle() {
-        let b = Board::from_string(
-            "__ __ S0 |0 __\n\
-             __ __ T0 __ __\n\
-             __ A0 R0 __ __\n\
-             D0 B0 __ X1 __\n\
-             N0 __ __ X1 __\n\
-             __ B1 I1 G1 __\n\
-             #1 #1 |1 #1 #1",
-        );
-        let mut bag = TileUtils::trivial_bag();
-        let players = vec![
-            Player::new("A".into(), 0, 7, &mut bag, None, (0, 0, 0)),
-            Player::new("B".into(), 1, 7, &mut bag, None, (0, 0, 0)),
-        ];
-
-        let mut game = Game {
-            board: b,
-            bag,
-            players,
-            player_turn_count: vec![0, 0],
-            judge: short_dict(),
-            ..Game::new_legacy(3, 1, None, GameRules::generation(0))
-        };
-        game.start();
 
-        _ = game.play_turn(
+        // The town is back once its owner has taken another turn
+        game.play_turn(
             Move::Place {
-                player: 0,
+                player: 1,
                 tile: 'A',
-                position: Coordinate { x: 0, y: 5 },
+                position: Coordinate { x: 2, y: 5 },
             },
             None,
             None,
             None,
-        );
-
-        assert_eq!(
-            game.board.to_string(),
-            "__ __ S0 |0 __\n\
-             __ __ T0 __ __\n\
-             __ A0 R0 __ __\n\
-             __ B0 __ X1 __\n\
-             __ __ __ X1 __\n\
-             __ B1 I1 G1 __\n\
-             #1 #1 |1 #1 #1",
-        );
+        )
+        .unwrap();
         assert_eq!(
-            game.board.get(Coordinate { x: 0, y: 6 }).unwrap(),
-            Square::Town {
+            game.board.get(town),
+            Ok(Square::Town {
                 player: 1,
                 defeated: false,
                 foggy: false
-            }
+            })
         );
-        assert_eq!(game.winner, None);
+        assert!(game.recent_changes.iter().any(|change| matches!(
+            change,
+            Change::Board(BoardChange {
+                action: BoardChangeAction::Restored,
+                detail: BoardChangeDetail { coordinate, .. },
+            }) if *coordinate == town
+        )));
+        assert!(game.fallen_towns.is_empty());
     }
 
     #[test]
-    fn resolve_win_after_battle() {
+    fn resolve_three_player_elimination() {
         let b = Board::from_string(
-            "__ __ S0 |0 __\n\
-             __ __ T0 __ __\n\
-             __ A0 R0 __ __\n\
-             D0 B0 __ X1 __\n\
-             N0 __ __ X1 __\n\
-             __ X1 I1 G1 __\n\
-             #1 #1 |1 #1 #1",
+            "__ __ |0 __ __\n\
+             __ __ __ __ __\n\
+             |2 __ __ __ |1\n\
+             __ __ __ __ __\n\
+             __ __ __ __ __",
         );
         let mut bag = TileUtils::trivial_bag();
         let players = vec![
             Player::new("A".into(), 0, 7, &mut bag, None, (0, 0, 0)),
             Player::new("B".into(), 1, 7, &mut bag, None, (0, 0, 0)),
+            Player::new("C".into(), 2, 7, &mut bag, None, (0, 0, 0)),
         ];
 
         let mut game = Game {
             board: b,
             bag,
             players,
-            player_turn_count: vec![0, 0],
+            player_turn_count: vec![0, 0, 0],
             judge: short_dict(),
             ..Game::new_legacy(3, 1, None, GameRules::generation(0))
         };
         game.start();
 
-        _ = game.play_turn(
-            Move::Place {
-                player: 0,
-                tile: 'A',
-                position: Coordinate { x: 0, y: 5 },
-            },
-            None,
-            None,
-            None,
-        );
+        // Losing one of three players doesn't end the game
+        game.resign_player(1);
+        assert_eq!(game.winner, None);
+        assert_eq!(game.remaining_players(), vec![0, 2]);
+        assert_eq!(game.next_player, Some(0));
 
         assert_eq!(
-            game.board.to_string(),
-            "__ __ S0 |0 __\n\
-             __ __ T0 __ __\n\
-             __ A0 R0 __ __\n\
-             D0 B0 __ __ __\n\
-             N0 __ __ __ __\n\
-             A0 __ __ __ __\n\
-             ⊭1 #1 |1 #1 #1",
-        );
-        assert_eq!(
-            game.board.get(Coordinate { x: 0, y: 6 }).unwrap(),
-            Square::Town {
-                player: 1,
-                defeated: true,
-                foggy: false
-            }
+            game.play_turn(
+                Move::Place {
+                    player: 0,
+                    tile: 'A',
+                    position: Coordinate { x: 2, y: 1 },
+                },
+                None,
+                None,
+                None,
+            ),
+            Ok(None)
         );
+        // The defeated player is skipped in the turn order
+        assert_eq!(game.next_player, Some(2));
+
+        game.resign_player(2);
         assert_eq!(game.winner, Some(0));
     }
 
     #[test]
-    fn resolve_win_via_explosion() {
+    fn challenge_mode_battles() {
         let b = Board::from_string(
-            "__ __ __ __ S0 |0 __\n\
-             __ __ __ __ T0 __ __\n\
-             __ __ __ A0 R0 __ __\n\
-             __ __ D0 B0 __ X1 __\n\
-             __ __ N0 __ __ X1 __\n\
-             X1 X1 __ B1 I1 G1 __\n\
-             |1 #1 #1 #1 |1 #1 #1",
+            "__ S0 X0 |0 __\n\
+             __ T0 __ __ __\n\
+             __ R0 __ __ __\n\
+             __ __ Z1 __ B1\n\
+             __ __ Z1 __ I1\n\
+             __ __ Z1 __ G1\n\
+             __ __ |1 __ |1",
         );
         let mut bag = TileUtils::trivial_bag();
         let players = vec![
             Player::new("A".into(), 0, 7, &mut bag, None, (0, 0, 0)),
             Player::new("B".into(), 1, 7, &mut bag, None, (0, 0, 0)),
         ];
+        let rules = GameRules {
+            challenge_mode: true,
+            battle_delay: 0,
+            ..GameRules::generation(0)
+        };
 
         let mut game = Game {
             board: b,
             bag,
             players,
             player_turn_count: vec![0, 0],
-            judge: short_dict(),
-            ..Game::new_legacy(3, 1, None, GameRules::generation(0))
+            judge: Judge::new(vec!["ARTS".into(), "BIG".into(), "GIB".into()]),
+            ..Game::new_legacy(1, 1, None, rules)
         };
         game.start();
 
-        _ = game.play_turn(
+        // Defending words are trusted, so the long ZZZ holds off the attack
+        game.play_turn(
             Move::Place {
                 player: 0,
                 tile: 'A',
-                position: Coordinate { x: 2, y: 5 },
+                position: Coordinate { x: 1, y: 3 },
             },
             None,
             None,
             None,
-        );
-
+        )
+        .unwrap();
         assert_eq!(
             game.board.to_string(),
-            "__ __ __ __ S0 |0 __\n\
-             __ __ __ __ T0 __ __\n\
-             __ __ __ A0 R0 __ __\n\
-             __ __ D0 B0 __ X1 __\n\
-             __ __ N0 __ __ X1 __\n\
-             __ __ A0 __ I1 G1 __\n\
-             |1 #1 ⊭1 #1 |1 #1 #1",
+            "__ __ X0 |0 __\n\
+             __ __ __ __ __\n\
+             __ __ __ __ __\n\
+             __ __ Z1 __ B1\n\
+             __ __ Z1 __ I1\n\
+             __ __ Z1 __ G1\n\
+             __ __ |1 __ |1",
         );
-        assert_eq!(
-            game.board.get(Coordinate { x: 2, y: 6 }).unwrap(),
-            Square::Town {
+
+        game.play_turn(
+            Move::Place {
                 player: 1,
-                defeated: true,
-                foggy: false
-            }
-        );
-        assert_eq!(game.winner, Some(0));
-    }
+                tile: 'A',
+                position: Coordinate { x: 3, y: 6 },
+            },
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        assert_eq!(game.next_player, Some(0));
 
-    #[test]
-    fn resolve_win_via_blocking() {
-        let b = Board::from_string(
-            "__ __ __ __ S0 |0 __\n\
-             #1 __ __ __ T0 __ __\n\
-             ~~ __ __ A0 R0 __ __\n\
-             |1 __ D0 B0 __ __ __\n\
-             ~~ __ __ __ __ __ __\n\
-             #1 __ __ __ __ __ __",
-        );
-        let mut bag = TileUtils::trivial_bag();
-        let players = vec![
-            Player::new("A".into(), 0, 7, &mut bag, None, (0, 0, 0)),
-            Player::new("B".into(), 1, 7, &mut bag, None, (0, 0, 0)),
-        ];
-
-        let mut game = Game {
-            board: b,
-            bag,
-            players,
-            player_turn_count: vec![0, 0],
-            judge: short_dict(),
-            ..Game::new_legacy(3, 1, None, GameRules::generation(0))
-        };
-        game.start();
+        // Players can only challenge opponent tiles
+        assert_eq!(
+            game.play_turn(
+                Move::Challenge {
+                    player: 0,
+                    position: Coordinate { x: 2, y: 0 },
+                },
+                None,
+                None,
+                None,
+            ),
+            Err(GamePlayError::InvalidChallenge.to_string())
+        );
+        assert_eq!(
+            game.play_turn(
+                Move::Challenge {
+                    player: 0,
+                    position: Coordinate { x: 0, y: 0 },
+                },
+                None,
+                None,
+                None,
+            ),
+            Err(GamePlayError::InvalidChallenge.to_string())
+        );
 
-        _ = game.play_turn(
-            Move::Place {
+        // A successful challenge removes the word and keeps the turn
+        game.play_turn(
+            Move::Challenge {
                 player: 0,
-                tile: 'A',
-                position: Coordinate { x: 1, y: 3 },
+                position: Coordinate { x: 2, y: 4 },
             },
             None,
             None,
             None,
+        )
+        .unwrap();
+        assert_eq!(
+            game.board.to_string(),
+            "__ __ X0 |0 __\n\
+             __ __ __ __ __\n\
+             __ __ __ __ __\n\
+             __ __ __ __ B1\n\
+             __ __ __ __ I1\n\
+             __ __ __ __ G1\n\
+             __ __ |1 A1 |1",
         );
+        assert_eq!(game.next_player, Some(0));
+        assert_eq!(game.turn_count, 2);
 
+        // A failed challenge leaves the board alone and uses up the turn
+        game.play_turn(
+            Move::Challenge {
+                player: 0,
+                position: Coordinate { x: 4, y: 4 },
+            },
+            None,
+            None,
+            None,
+        )
+        .unwrap();
         assert_eq!(
             game.board.to_string(),
-            "__ __ __ __ S0 |0 __\n\
-             ⊭1 __ __ __ T0 __ __\n\
-             ~~ __ __ A0 R0 __ __\n\
-             |1 A0 D0 B0 __ __ __\n\
-             ~~ __ __ __ __ __ __\n\
-             ⊭1 __ __ __ __ __ __",
+            "__ __ X0 |0 __\n\
+             __ __ __ __ __\n\
+             __ __ __ __ __\n\
+             __ __ __ __ B1\n\
+             __ __ __ __ I1\n\
+             __ __ __ __ G1\n\
+             __ __ |1 A1 |1",
         );
+        assert_eq!(game.next_player, Some(1));
+        assert_eq!(game.turn_count, 3);
+
+        game.rules.challenge_mode = false;
         assert_eq!(
-            game.board.get(Coordinate { x: 0, y: 1 }).unwrap(),
-            Square::Town {
-                player: 1,
-                defeated: true,
-                foggy: false
-            }
+            game.play_turn(
+                Move::Challenge {
+                    player: 1,
+                    position: Coordinate { x: 2, y: 0 },
+                },
+                None,
+                None,
+                None,
+            ),
+            Err(GamePlayError::ChallengesDisabled.to_string())
         );
-        assert_eq!(game.winner, Some(0));
     }
 
     #[test]
-    fn resolve_noop() {
+    fn challenge_truncates_cut_off_tiles() {
         let b = Board::from_string(
-            "~~ |0 __ ~~ ~~\n\
-             __ __ __ __ __\n\
+            "__ __ X0 |0 __\n\
              __ __ __ __ __\n\
              __ __ __ __ __\n\
-             ~~ ~~ T1 |1 ~~",
+             __ __ Z1 B1 __\n\
+             __ __ Z1 __ __\n\
+             __ __ Z1 __ __\n\
+             __ __ |1 __ __",
         );
         let mut bag = TileUtils::trivial_bag();
         let players = vec![
             Player::new("A".into(), 0, 7, &mut bag, None, (0, 0, 0)),
             Player::new("B".into(), 1, 7, &mut bag, None, (0, 0, 0)),
         ];
+        let rules = GameRules {
+            challenge_mode: true,
+            battle_delay: 0,
+            ..GameRules::generation(0)
+        };
 
         let mut game = Game {
             board: b,
             bag,
             players,
             player_turn_count: vec![0, 0],
-            judge: short_dict(),
-            ..Game::new_legacy(3, 1, None, GameRules::generation(0))
+            judge: Judge::new(vec!["ARTS".into(), "BIG".into(), "GIB".into()]),
+            ..Game::new_legacy(1, 1, None, rules)
         };
+        game.start();
 
-        game.make_move(
-            Move::Place {
+        // Removing ZZZ leaves the B with no path back to its dock
+        game.play_turn(
+            Move::Challenge {
                 player: 0,
-                tile: 'A',
-                position: Coordinate { x: 2, y: 0 },
+                position: Coordinate { x: 2, y: 4 },
             },
             None,
             None,
             None,
         )
         .unwrap();
-
         assert_eq!(
             game.board.to_string(),
-            "~~ |0 A0 ~~ ~~\n\
+            "__ __ X0 |0 __\n\
              __ __ __ __ __\n\
              __ __ __ __ __\n\
              __ __ __ __ __\n\
-             ~~ ~~ T1 |1 ~~",
-        )
+             __ __ __ __ __\n\
+             __ __ __ __ __\n\
+             __ __ |1 __ __",
+        );
+        assert!(game.recent_changes.iter().any(|change| matches!(
+            change,
+            Change::Board(BoardChange {
+                action: BoardChangeAction::Truncated,
+                detail: BoardChangeDetail {
+                    coordinate: Coordinate { x: 3, y: 3 },
+                    ..
+                },
+            })
+        )));
     }
 
     #[test]
-    fn resolve_with_smaller_attack_dict() {
+    fn fischer_increment_only_on_valid_moves() {
+        use time::Duration;
+
         let b = Board::from_string(
-            "__ S0 X0 |0 __\n\
-             __ T0 __ __ __\n\
-             __ R0 __ __ __\n\
-             __ __ A1 __ __\n\
-             __ __ R1 __ __\n\
-             __ __ T1 __ __\n\
-             __ __ S1 |1 __",
+            "__ __ |0 __ __\n\
+             __ __ __ __ __\n\
+             __ __ __ __ __\n\
+             __ __ |1 __ __",
         );
         let mut bag = TileUtils::trivial_bag();
+        let base_time = Duration::seconds(60);
         let players = vec![
-            Player::new("A".into(), 0, 7, &mut bag, None, (0, 0, 0)),
-            Player::new("B".into(), 1, 7, &mut bag, None, (0, 0, 0)),
+            Player::new("A".into(), 0, 7, &mut bag, Some(base_time), (0, 0, 0)),
+            Player::new("B".into(), 1, 7, &mut bag, Some(base_time), (0, 0, 0)),
         ];
+        let rules = GameRules {
+            timing: crate::rules::Timing::Fischer {
+                base_time: 60,
+                increment: 5,
+            },
+            battle_delay: 0,
+            ..GameRules::generation(0)
+        };
 
         let mut game = Game {
             board: b,
@@ -1103,80 +1872,476 @@ mod tests {
             players,
             player_turn_count: vec![0, 0],
             judge: short_dict(),
-            ..Game::new_legacy(1, 1, None, GameRules::generation(0))
+            ..Game::new_legacy(1, 1, None, rules)
         };
+        game.start();
 
-        game.make_move(
+        // A rejected move doesn't credit the increment
+        assert!(game
+            .play_turn(
+                Move::Place {
+                    player: 0,
+                    tile: 'A',
+                    position: Coordinate { x: 0, y: 3 },
+                },
+                None,
+                None,
+                None,
+            )
+            .is_err());
+        assert_eq!(game.players[0].time_remaining, Some(base_time));
+
+        game.play_turn(
             Move::Place {
                 player: 0,
                 tile: 'A',
-                position: Coordinate { x: 1, y: 3 },
+                position: Coordinate { x: 2, y: 1 },
             },
-            Some(&b_dict().builtin_dictionary),
+            None,
             None,
             None,
         )
         .unwrap();
 
-        assert_eq!(
-            game.board.to_string(),
-            "__ __ X0 |0 __\n\
+        // Allow for the clock ticking over a second during the test
+        let time_remaining = game.players[0].time_remaining.unwrap();
+        assert!(time_remaining <= base_time + Duration::seconds(5));
+        assert!(time_remaining >= base_time + Duration::seconds(4));
+        assert_eq!(game.players[1].time_remaining, Some(base_time));
+    }
+
+    #[test]
+    fn passing_is_opt_in_and_mutual_passes_draw() {
+        let b = Board::from_string(
+            "__ __ |0 __ __\n\
              __ __ __ __ __\n\
              __ __ __ __ __\n\
-             __ __ A1 __ __\n\
-             __ __ R1 __ __\n\
-             __ __ T1 __ __\n\
-             __ __ S1 |1 __",
+             __ __ |1 __ __",
+        );
+        let mut bag = TileUtils::trivial_bag();
+        let players = vec![
+            Player::new("A".into(), 0, 7, &mut bag, None, (0, 0, 0)),
+            Player::new("B".into(), 1, 7, &mut bag, None, (0, 0, 0)),
+        ];
+        let mut game = Game {
+            board: b.clone(),
+            bag,
+            players,
+            player_turn_count: vec![0, 0],
+            judge: short_dict(),
+            ..Game::new_legacy(1, 1, None, GameRules::generation(0))
+        };
+        game.rules.battle_delay = 0;
+        game.start();
+
+        assert_eq!(
+            game.make_move(Move::Pass { player: 0 }, None, None, None),
+            Err(GamePlayError::PassingDisabled)
+        );
+
+        game.rules.allow_passing = true;
+
+        game.play_turn(Move::Pass { player: 0 }, None, None, None)
+            .unwrap();
+        assert_eq!(game.board, b);
+        assert_eq!(game.next_player, Some(1));
+        assert!(!game.all_players_passed());
+
+        // Any other move resets the run of passes
+        game.play_turn(
+            Move::Place {
+                player: 1,
+                tile: 'A',
+                position: Coordinate { x: 2, y: 2 },
+            },
+            None,
+            None,
+            None,
         )
+        .unwrap();
+        game.play_turn(Move::Pass { player: 0 }, None, None, None)
+            .unwrap();
+        assert!(!game.all_players_passed());
+
+        game.play_turn(Move::Pass { player: 1 }, None, None, None)
+            .unwrap();
+        assert!(game.all_players_passed());
+        assert_eq!(game.winner, None);
+        assert_eq!(game.outcome(), Some(GameOutcome::Draw));
+
+        // Nothing can be played once the game is drawn
+        assert!(game
+            .play_turn(Move::Pass { player: 0 }, None, None, None)
+            .is_err());
     }
 
     #[test]
-    fn resolve_with_smaller_defense_dict() {
+    fn turns_without_progress_stalemate() {
         let b = Board::from_string(
-            "__ S0 X0 |0 __\n\
-             __ T0 __ __ __\n\
-             __ R0 __ __ __\n\
-             __ __ A1 __ __\n\
-             __ __ R1 __ __\n\
-             __ __ T1 __ __\n\
-             __ __ S1 |1 __",
+            "__ __ |0 __ __\n\
+             __ __ __ __ __\n\
+             __ __ __ __ __\n\
+             __ __ __ __ __\n\
+             __ __ __ __ __\n\
+             __ __ __ __ __\n\
+             __ __ |1 #1 __",
         );
         let mut bag = TileUtils::trivial_bag();
         let players = vec![
             Player::new("A".into(), 0, 7, &mut bag, None, (0, 0, 0)),
             Player::new("B".into(), 1, 7, &mut bag, None, (0, 0, 0)),
         ];
-
+        let rules = GameRules {
+            allow_passing: true,
+            stalemate_turns: Some(3),
+            battle_delay: 0,
+            ..GameRules::generation(0)
+        };
         let mut game = Game {
             board: b,
             bag,
             players,
             player_turn_count: vec![0, 0],
             judge: short_dict(),
-            ..Game::new_legacy(1, 1, None, GameRules::generation(0))
+            ..Game::new_legacy(1, 1, None, rules)
         };
+        game.start();
 
-        game.make_move(
+        let place = |game: &mut Game, x: usize, y: usize| {
+            game.play_turn(
+                Move::Place {
+                    player: 0,
+                    tile: 'A',
+                    position: Coordinate { x, y },
+                },
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+        };
+
+        // Getting closer to the enemy town is progress
+        place(&mut game, 2, 1);
+        assert_eq!(game.turns_without_progress, 0);
+
+        game.play_turn(Move::Pass { player: 1 }, None, None, None)
+            .unwrap();
+        assert_eq!(game.turns_without_progress, 1);
+
+        // Playing further away from the town is not
+        place(&mut game, 1, 1);
+        assert_eq!(game.turns_without_progress, 2);
+        assert!(!game.is_stalemate());
+        assert!(!game.is_over());
+
+        game.play_turn(Move::Pass { player: 1 }, None, None, None)
+            .unwrap();
+        assert!(game.is_stalemate());
+        assert_eq!(game.winner, None);
+        assert_eq!(game.outcome(), Some(GameOutcome::Draw));
+    }
+
+    #[test]
+    fn capturable_obelisks() {
+        let b = Board::from_string(
+            "__ A0 |0 __ __\n\
+             __ __ __ __ __\n\
+             __ ^^ __ __ __\n\
+             __ __ __ __ __\n\
+             __ A1 |1 __ __",
+        );
+        let mut bag = TileUtils::trivial_bag();
+        let players = vec![
+            Player::new("A".into(), 0, 7, &mut bag, None, (0, 0, 0)),
+            Player::new("B".into(), 1, 7, &mut bag, None, (0, 0, 0)),
+        ];
+        let rules = GameRules {
+            obelisks: crate::rules::ObeliskRule::Capturable,
+            battle_delay: 0,
+            ..GameRules::generation(0)
+        };
+
+        let mut game = Game {
+            board: b,
+            bag,
+            players,
+            player_turn_count: vec![0, 0],
+            judge: Judge::new(vec!["AA".into()]),
+            ..Game::new_legacy(1, 1, None, rules)
+        };
+        game.start();
+
+        // Being the only player beside the obelisk captures it, drawing a bonus tile
+        game.play_turn(
             Move::Place {
                 player: 0,
                 tile: 'A',
+                position: Coordinate { x: 1, y: 1 },
+            },
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            game.board.get(Coordinate { x: 1, y: 2 }),
+            Ok(Square::Obelisk {
+                foggy: false,
+                holder: Some(0)
+            })
+        );
+        assert!(game.recent_changes.iter().any(|change| matches!(
+            change,
+            Change::Board(BoardChange {
+                action: BoardChangeAction::Captured,
+                ..
+            })
+        )));
+        assert_eq!(game.players[0].hand.len(), 8);
+
+        // A contested obelisk stays with whoever already holds it
+        game.play_turn(
+            Move::Place {
+                player: 1,
+                tile: 'A',
                 position: Coordinate { x: 1, y: 3 },
             },
             None,
-            Some(&b_dict().builtin_dictionary),
+            None,
             None,
         )
         .unwrap();
+        assert_eq!(
+            game.board.get(Coordinate { x: 1, y: 2 }),
+            Ok(Square::Obelisk {
+                foggy: false,
+                holder: Some(0)
+            })
+        );
+        assert_eq!(game.players[1].hand.len(), 7);
+    }
+
+    #[test]
+    fn legal_placements_and_swaps() {
+        let b = Board::from_string(
+            "~~ |0 ~~\n\
+             __ A0 __\n\
+             __ B0 __\n\
+             __ __ __\n\
+             ~~ |1 ~~",
+        );
+        let mut bag = TileUtils::trivial_bag();
+        let players = vec![
+            Player::new("A".into(), 0, 7, &mut bag, None, (0, 0, 0)),
+            Player::new("B".into(), 1, 7, &mut bag, None, (0, 0, 0)),
+        ];
+
+        let mut game = Game {
+            board: b,
+            bag,
+            players,
+            player_turn_count: vec![0, 0],
+            judge: short_dict(),
+            ..Game::new_legacy(1, 1, None, GameRules::generation(0))
+        };
+        game.start();
+        game.players[0].hand = Hand(vec!['A', 'A', BLANK_TILE]);
+
+        // Five open squares, each taking the A or any letter assigned to the blank
+        let placements = game.legal_placements(0);
+        assert_eq!(placements.len(), 5 * 27);
+        assert!(placements.contains(&(Coordinate { x: 1, y: 3 }, 'A')));
+        assert!(placements.contains(&(Coordinate { x: 0, y: 2 }, 'z')));
+        assert!(placements.iter().all(|&(position, tile)| game
+            .validate_move(&Move::Place {
+                player: 0,
+                tile,
+                position
+            })
+            .is_ok()));
 
         assert_eq!(
-            game.board.to_string(),
+            game.legal_swaps(0),
+            vec![[Coordinate { x: 1, y: 1 }, Coordinate { x: 1, y: 2 }]]
+        );
+        assert!(game.legal_swaps(1).is_empty());
+        assert!(game.legal_placements(5).is_empty());
+        assert!(game.legal_swaps(5).is_empty());
+    }
+
+    #[test]
+    fn preview_placement_leaves_game_untouched() {
+        let b = Board::from_string(
             "__ S0 X0 |0 __\n\
              __ T0 __ __ __\n\
              __ R0 __ __ __\n\
-             __ A0 __ __ __\n\
+             __ __ I1 __ __\n\
+             __ __ T1 |1 __",
+        );
+        let mut bag = TileUtils::trivial_bag();
+        let players = vec![
+            Player::new("A".into(), 0, 7, &mut bag, None, (0, 0, 0)),
+            Player::new("B".into(), 1, 7, &mut bag, None, (0, 0, 0)),
+        ];
+
+        let game = Game {
+            board: b,
+            bag,
+            players,
+            player_turn_count: vec![0, 0],
+            judge: short_dict(),
+            ..Game::new_legacy(1, 1, None, GameRules::generation(0))
+        };
+        let board_before = game.board.to_string();
+        let hand_before = game.players[0].hand.clone();
+
+        let preview = game
+            .preview_placement(
+                &Move::Place {
+                    player: 0,
+                    tile: 'A',
+                    position: Coordinate { x: 1, y: 3 },
+                },
+                None,
+                None,
+            )
+            .unwrap();
+        assert!(matches!(preview.outcome, Some(Outcome::AttackerWins(_))));
+        assert!(!preview.defenders.is_empty());
+        assert!(preview
+            .defenders
+            .iter()
+            .all(|word| word.valid == Some(false)));
+        assert_eq!(game.board.to_string(), board_before);
+        assert_eq!(game.players[0].hand, hand_before);
+
+        // Placing away from the opponent only judges the new words
+        let preview = game
+            .preview_placement(
+                &Move::Place {
+                    player: 0,
+                    tile: 'A',
+                    position: Coordinate { x: 0, y: 0 },
+                },
+                None,
+                None,
+            )
+            .unwrap();
+        assert_eq!(preview.outcome, None);
+        assert!(preview.defenders.is_empty());
+        assert!(!preview.attackers.is_empty());
+
+        assert_eq!(
+            game.preview_placement(
+                &Move::Swap {
+                    player: 0,
+                    positions: [Coordinate { x: 1, y: 0 }, Coordinate { x: 1, y: 1 }],
+                },
+                None,
+                None,
+            ),
+            Err(GamePlayError::NotAPlacement)
+        );
+    }
+
+    #[test]
+    fn exchanges_trade_a_hand_tile_for_one_from_the_bag() {
+        let b = Board::from_string(
+            "__ __ |0 __ __\n\
              __ __ __ __ __\n\
              __ __ __ __ __\n\
-             __ __ __ |1 __",
-        )
+             __ __ |1 __ __",
+        );
+        let mut bag = TileBag::explicit(vec!['A', 'A', 'A', 'Q'], Some(1));
+        let players = vec![
+            Player::new("A".into(), 0, 1, &mut bag, None, (0, 0, 0)),
+            Player::new("B".into(), 1, 1, &mut bag, None, (0, 0, 0)),
+        ];
+        let mut game = Game {
+            board: b.clone(),
+            bag,
+            players,
+            player_turn_count: vec![0, 0],
+            judge: short_dict(),
+            ..Game::new_legacy(1, 1, None, GameRules::generation(0))
+        };
+        game.rules.battle_delay = 0;
+        game.start();
+
+        let tile = game.players[0].hand.0[0];
+        assert_eq!(
+            game.make_move(Move::Exchange { player: 0, tile }, None, None, None),
+            Err(GamePlayError::ExchangesDisabled)
+        );
+
+        game.rules.allow_exchanges = true;
+
+        assert_eq!(
+            game.make_move(
+                Move::Exchange {
+                    player: 0,
+                    tile: 'Z'
+                },
+                None,
+                None,
+                None
+            ),
+            Err(GamePlayError::PlayerDoesNotHaveTile {
+                player: 0,
+                tile: 'Z'
+            })
+        );
+
+        let mut remaining = game.bag.remaining().to_vec();
+        game.play_turn(Move::Exchange { player: 0, tile }, None, None, None)
+            .unwrap();
+
+        // The exchange costs the turn without touching the board,
+        // and the bag ends up holding the returned tile in place of the drawn one
+        assert_eq!(game.board, b);
+        assert_eq!(game.next_player, Some(1));
+        assert_eq!(game.players[0].hand.len(), 1);
+        let drawn = game.players[0].hand.0[0];
+        let drawn_at = remaining.iter().position(|t| *t == drawn).unwrap();
+        remaining.swap_remove(drawn_at);
+        remaining.push(tile);
+        remaining.sort();
+        let mut after = game.bag.remaining().to_vec();
+        after.sort();
+        assert_eq!(after, remaining);
+
+        // With nothing left in the bag, there is nothing to exchange for
+        game.bag = TileBag::explicit(vec![], Some(1));
+        let tile = game.players[1].hand.0[0];
+        assert_eq!(
+            game.make_move(Move::Exchange { player: 1, tile }, None, None, None),
+            Err(GamePlayError::EmptyBag)
+        );
+    }
+
+    #[test]
+    fn from_board_sets_up_an_unstarted_game() {
+        let board = Board::from_string(
+            "__ __ |0 __ __\n\
+             __ __ __ __ __\n\
+             __ __ #1 __ __\n\
+             __ __ |1 __ __",
+        );
+        let mut bag = TileUtils::trivial_bag();
+        let players = vec![
+            Player::new("A".into(), 0, 7, &mut bag, None, (0, 0, 0)),
+            Player::new("B".into(), 1, 7, &mut bag, None, (0, 0, 0)),
+        ];
+
+        let game = Game::from_board(board.clone(), players.clone(), GameRules::generation(0));
+        assert_eq!(game.board, board);
+        assert_eq!(game.players, players);
+        assert_eq!(game.player_turn_count, vec![0, 0]);
+        assert_eq!(game.turn_count, 0);
+        assert_eq!(game.next_player, Some(0));
+        assert_eq!(game.started_at, None);
+        assert_eq!(game.board.towns().count(), 1);
+        assert_eq!(game.board.artifacts().count(), 2);
     }
 }
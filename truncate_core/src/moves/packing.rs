@@ -1,7 +1,225 @@
+//! Compact encodings for lists of moves.
+//!
+//! Moves are packed into bytes with [`pack_moves`], laid out as:
+//!
+//! - A single version byte, currently [`PACKING_VERSION`].
+//! - Each move in turn, starting with a tag byte. The high nibble of the tag holds the kind of move
+//!   (`0` place, `1` swap, `2` challenge, `3` pass, `4` exchange) and the low nibble holds the player.
+//!   Players from 15 upwards set the low nibble to `0xF` and follow the tag with the player as a varint.
+//! - The rest of the move as varints: a place is `x y tile`, a swap is `x y x y`,
+//!   a challenge is `x y`, a pass has nothing more, and an exchange is `tile`.
+//!
+//! Varints are unsigned LEB128, so coordinates on a normal board and ASCII tiles take a single byte each.
+//! Packing a list of moves always gives a prefix of packing a longer list that begins with those moves.
+
+use thiserror::Error;
+
 use crate::board::Coordinate;
 
 use super::Move;
 
+/// The version of the binary format written by [`pack_moves`]
+pub const PACKING_VERSION: u8 = 1;
+
+const PLACE_TAG: u8 = 0;
+const SWAP_TAG: u8 = 1;
+const CHALLENGE_TAG: u8 = 2;
+const PASS_TAG: u8 = 3;
+const EXCHANGE_TAG: u8 = 4;
+const WIDE_PLAYER: u8 = 0xF;
+
+#[derive(Clone, Error, Debug, PartialEq)]
+pub enum PackingError {
+    #[error("Packed moves are missing their version byte")]
+    MissingVersion,
+    #[error("Packed moves are version {0}, but only version {PACKING_VERSION} can be read")]
+    UnsupportedVersion(u8),
+    #[error("Unknown move kind {0} in packed moves")]
+    UnknownMove(u8),
+    #[error("Packed moves ended partway through a move")]
+    Truncated,
+    #[error("Packed number does not fit in this platform's integers")]
+    Overflow,
+    #[error("Packed tile {0:#x} is not a valid character")]
+    InvalidTile(u32),
+}
+
+fn push_varint(packed: &mut Vec<u8>, mut value: usize) {
+    while value >= 0x80 {
+        packed.push((value as u8 & 0x7F) | 0x80);
+        value >>= 7;
+    }
+    packed.push(value as u8);
+}
+
+fn read_varint(bytes: &mut impl Iterator<Item = u8>) -> Result<usize, PackingError> {
+    let mut value: usize = 0;
+    let mut shift = 0;
+
+    loop {
+        let byte = bytes.next().ok_or(PackingError::Truncated)?;
+        let low = (byte & 0x7F) as usize;
+
+        if shift >= usize::BITS || (low << shift) >> shift != low {
+            return Err(PackingError::Overflow);
+        }
+        value |= low << shift;
+
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+        shift += 7;
+    }
+}
+
+fn push_coordinate(packed: &mut Vec<u8>, coord: Coordinate) {
+    push_varint(packed, coord.x);
+    push_varint(packed, coord.y);
+}
+
+fn read_coordinate(bytes: &mut impl Iterator<Item = u8>) -> Result<Coordinate, PackingError> {
+    Ok(Coordinate {
+        x: read_varint(bytes)?,
+        y: read_varint(bytes)?,
+    })
+}
+
+fn read_tile(bytes: &mut impl Iterator<Item = u8>) -> Result<char, PackingError> {
+    let tile = u32::try_from(read_varint(bytes)?).map_err(|_| PackingError::Overflow)?;
+    char::from_u32(tile).ok_or(PackingError::InvalidTile(tile))
+}
+
+fn push_tag(packed: &mut Vec<u8>, kind: u8, player: usize) {
+    if player < WIDE_PLAYER as usize {
+        packed.push((kind << 4) | player as u8);
+    } else {
+        packed.push((kind << 4) | WIDE_PLAYER);
+        push_varint(packed, player);
+    }
+}
+
+/// Packs moves into the versioned binary format described in the [module docs](self)
+pub fn pack_moves(moves: &[Move]) -> Vec<u8> {
+    let mut packed = Vec::with_capacity(1 + moves.len() * 4);
+    packed.push(PACKING_VERSION);
+
+    for m in moves {
+        match m {
+            Move::Place {
+                player,
+                tile,
+                position,
+            } => {
+                push_tag(&mut packed, PLACE_TAG, *player);
+                push_coordinate(&mut packed, *position);
+                push_varint(&mut packed, *tile as usize);
+            }
+            Move::Swap {
+                player,
+                positions: [from, to],
+            } => {
+                push_tag(&mut packed, SWAP_TAG, *player);
+                push_coordinate(&mut packed, *from);
+                push_coordinate(&mut packed, *to);
+            }
+            Move::Challenge { player, position } => {
+                push_tag(&mut packed, CHALLENGE_TAG, *player);
+                push_coordinate(&mut packed, *position);
+            }
+            Move::Pass { player } => {
+                push_tag(&mut packed, PASS_TAG, *player);
+            }
+            Move::Exchange { player, tile } => {
+                push_tag(&mut packed, EXCHANGE_TAG, *player);
+                push_varint(&mut packed, *tile as usize);
+            }
+        }
+    }
+
+    packed
+}
+
+/// Reads moves written by [`pack_moves`]
+pub fn unpack_moves(packed: &[u8]) -> Result<Vec<Move>, PackingError> {
+    let (&version, rest) = packed.split_first().ok_or(PackingError::MissingVersion)?;
+    if version != PACKING_VERSION {
+        return Err(PackingError::UnsupportedVersion(version));
+    }
+
+    let mut moves = Vec::with_capacity(rest.len() / 4);
+    let mut bytes = rest.iter().copied();
+
+    while let Some(tag) = bytes.next() {
+        let player = match tag & 0xF {
+            WIDE_PLAYER => read_varint(&mut bytes)?,
+            player => player as usize,
+        };
+
+        let next_move = match tag >> 4 {
+            PLACE_TAG => {
+                let position = read_coordinate(&mut bytes)?;
+                Move::Place {
+                    player,
+                    tile: read_tile(&mut bytes)?,
+                    position,
+                }
+            }
+            SWAP_TAG => Move::Swap {
+                player,
+                positions: [read_coordinate(&mut bytes)?, read_coordinate(&mut bytes)?],
+            },
+            CHALLENGE_TAG => Move::Challenge {
+                player,
+                position: read_coordinate(&mut bytes)?,
+            },
+            PASS_TAG => Move::Pass { player },
+            EXCHANGE_TAG => Move::Exchange {
+                player,
+                tile: read_tile(&mut bytes)?,
+            },
+            kind => return Err(PackingError::UnknownMove(kind)),
+        };
+
+        moves.push(next_move);
+    }
+
+    Ok(moves)
+}
+
+/// Serializes a list of moves through [`pack_moves`], for use with `#[serde(with = "...")]`
+/// on messages that send whole games of moves. The bytes are sent as a hex string,
+/// the same way daily attempts are stored, as a list of numbers would be larger than the moves.
+pub mod packed {
+    use std::fmt::Write;
+
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    use super::{pack_moves, unpack_moves, Move};
+
+    pub fn serialize<S: Serializer>(moves: &[Move], serializer: S) -> Result<S::Ok, S::Error> {
+        let packed = pack_moves(moves);
+        let mut hex = String::with_capacity(packed.len() * 2);
+        for byte in packed {
+            _ = write!(hex, "{byte:02x}");
+        }
+        serializer.serialize_str(&hex)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Move>, D::Error> {
+        let hex = String::deserialize(deserializer)?;
+        let packed = (0..hex.len())
+            .step_by(2)
+            .map(|i| {
+                hex.get(i..i + 2)
+                    .filter(|byte| byte.bytes().all(|c| c.is_ascii_hexdigit()))
+                    .and_then(|byte| u8::from_str_radix(byte, 16).ok())
+            })
+            .collect::<Option<Vec<u8>>>()
+            .ok_or_else(|| D::Error::custom("Packed moves aren't valid hex"))?;
+        unpack_moves(&packed).map_err(D::Error::custom)
+    }
+}
+
 fn pack_coord(coord: Coordinate) -> String {
     let x = coord.x.to_string();
     let y = coord.y.to_string();
@@ -19,7 +237,9 @@ fn unpack_coord(packed_coord: &String) -> Result<Coordinate, ()> {
     })
 }
 
-pub fn pack_moves(moves: &Vec<Move>, player_count: usize) -> String {
+/// Packs moves into the readable text format that daily attempts were stored in
+/// before the binary format, e.g. `[0]1203A11B<11/1009>` or `?1203?.!Q`
+pub fn pack_moves_text(moves: &Vec<Move>, player_count: usize) -> String {
     let mut packed = String::with_capacity(moves.len() * 3);
 
     let mut next_player: usize = 0;
@@ -34,6 +254,9 @@ pub fn pack_moves(moves: &Vec<Move>, player_count: usize) -> String {
         next_player = match first_move {
             Move::Place { player, .. } => *player,
             Move::Swap { player, .. } => *player,
+            Move::Challenge { player, .. } => *player,
+            Move::Pass { player } => *player,
+            Move::Exchange { player, .. } => *player,
         };
         packed.push_str(&format!("[{next_player}]"));
     };
@@ -70,6 +293,39 @@ pub fn pack_moves(moves: &Vec<Move>, player_count: usize) -> String {
                 packed.push_str(&pack_coord(*to));
                 packed.push('>');
 
+                incr_player(&mut next_player);
+            }
+            Move::Challenge { player, position } => {
+                if *player != next_player {
+                    next_player = *player;
+                    packed.push_str(&format!("[{player}]"));
+                }
+
+                packed.push('?');
+                packed.push_str(&pack_coord(*position));
+                packed.push('?');
+
+                incr_player(&mut next_player);
+            }
+            Move::Pass { player } => {
+                if *player != next_player {
+                    next_player = *player;
+                    packed.push_str(&format!("[{player}]"));
+                }
+
+                packed.push('.');
+
+                incr_player(&mut next_player);
+            }
+            Move::Exchange { player, tile } => {
+                if *player != next_player {
+                    next_player = *player;
+                    packed.push_str(&format!("[{player}]"));
+                }
+
+                packed.push('!');
+                packed.push(*tile);
+
                 incr_player(&mut next_player);
             }
         }
@@ -78,7 +334,7 @@ pub fn pack_moves(moves: &Vec<Move>, player_count: usize) -> String {
     packed
 }
 
-pub fn unpack_moves(packed_moves: &String, player_count: usize) -> Result<Vec<Move>, ()> {
+pub fn unpack_moves_text(packed_moves: &String, player_count: usize) -> Result<Vec<Move>, ()> {
     let mut moves = Vec::with_capacity(packed_moves.len() / 3);
 
     enum State {
@@ -87,6 +343,8 @@ pub fn unpack_moves(packed_moves: &String, player_count: usize) -> Result<Vec<Mo
         Place(String),
         SwapFrom(String),
         SwapTo(Coordinate, String),
+        Challenge(String),
+        Exchange,
     }
 
     let mut i = packed_moves.chars();
@@ -106,6 +364,15 @@ pub fn unpack_moves(packed_moves: &String, player_count: usize) -> Result<Vec<Mo
                     state = State::Place(c.to_string());
                 } else if c == '<' {
                     state = State::SwapFrom(String::new());
+                } else if c == '?' {
+                    state = State::Challenge(String::new());
+                } else if c == '.' {
+                    // . passes the turn
+                    moves.push(Move::Pass {
+                        player: incr_player(&mut player),
+                    });
+                } else if c == '!' {
+                    state = State::Exchange;
                 } else if c == '[' {
                     state = State::SetPlayer(String::new());
                 } else {
@@ -165,6 +432,29 @@ pub fn unpack_moves(packed_moves: &String, player_count: usize) -> Result<Vec<Mo
                     return Err(());
                 }
             }
+            // ?1204? challenges the word at [12, 4]
+            State::Challenge(s) => {
+                if c.is_numeric() {
+                    s.push(c);
+                } else if c == '?' {
+                    let position = unpack_coord(s)?;
+                    moves.push(Move::Challenge {
+                        player: incr_player(&mut player),
+                        position,
+                    });
+                    state = State::None;
+                } else {
+                    return Err(());
+                }
+            }
+            // !Q exchanges the Q in hand for a tile from the bag
+            State::Exchange => {
+                moves.push(Move::Exchange {
+                    player: incr_player(&mut player),
+                    tile: c,
+                });
+                state = State::None;
+            }
         }
     }
 
@@ -173,8 +463,46 @@ pub fn unpack_moves(packed_moves: &String, player_count: usize) -> Result<Vec<Mo
 
 #[cfg(test)]
 mod tests {
+    use serde::{Deserialize, Serialize};
+
     use super::*;
 
+    #[derive(Debug, Serialize, Deserialize)]
+    struct PackedGame {
+        #[serde(with = "packed")]
+        moves: Vec<Move>,
+    }
+
+    #[test]
+    fn packed_moves_serialize_as_hex() {
+        let moves: Vec<_> = (0..40)
+            .map(|i| Move::Place {
+                player: i % 2,
+                tile: 'E',
+                position: Coordinate {
+                    x: i,
+                    y: 20 - i / 2,
+                },
+            })
+            .collect();
+        let bytes = pack_moves(&moves);
+
+        let json = serde_json::to_string(&PackedGame {
+            moves: moves.clone(),
+        })
+        .unwrap();
+        assert_eq!(json.len(), r#"{"moves":""}"#.len() + bytes.len() * 2);
+        assert!(json.len() < serde_json::to_string(&bytes).unwrap().len());
+        assert!(json.len() < serde_json::to_string(&moves).unwrap().len());
+
+        let game: PackedGame = serde_json::from_str(&json).unwrap();
+        assert_eq!(game.moves, moves);
+
+        for bad in [r#"{"moves":"0"}"#, r#"{"moves":"zz"}"#, r#"{"moves":"+1"}"#] {
+            assert!(serde_json::from_str::<PackedGame>(bad).is_err(), "{bad}");
+        }
+    }
+
     #[test]
     fn test_packing_moves() {
         let moves = vec![
@@ -204,11 +532,92 @@ mod tests {
             },
         ];
 
-        let packed = pack_moves(&moves, 2);
+        let packed = pack_moves_text(&moves, 2);
 
         assert_eq!(packed, "[0]1203A11B001301J<11/1009>33R".to_string());
 
-        let unpacked = unpack_moves(&packed, 2);
+        let unpacked = unpack_moves_text(&packed, 2);
+
+        assert_eq!(unpacked, Ok(moves));
+    }
+
+    #[test]
+    fn test_packing_challenges() {
+        let moves = vec![
+            Move::Challenge {
+                player: 0,
+                position: Coordinate { x: 12, y: 3 },
+            },
+            Move::Place {
+                player: 0,
+                tile: 'B',
+                position: Coordinate { x: 1, y: 1 },
+            },
+            Move::Place {
+                player: 1,
+                tile: 'R',
+                position: Coordinate { x: 3, y: 3 },
+            },
+        ];
+
+        let packed = pack_moves_text(&moves, 2);
+
+        assert_eq!(packed, "[0]?1203?[0]11B33R".to_string());
+
+        let unpacked = unpack_moves_text(&packed, 2);
+
+        assert_eq!(unpacked, Ok(moves));
+    }
+
+    #[test]
+    fn test_packing_passes() {
+        let moves = vec![
+            Move::Place {
+                player: 0,
+                tile: 'B',
+                position: Coordinate { x: 1, y: 1 },
+            },
+            Move::Pass { player: 1 },
+            Move::Pass { player: 0 },
+            Move::Place {
+                player: 1,
+                tile: 'R',
+                position: Coordinate { x: 3, y: 3 },
+            },
+        ];
+
+        let packed = pack_moves_text(&moves, 2);
+
+        assert_eq!(packed, "[0]11B..33R".to_string());
+
+        let unpacked = unpack_moves_text(&packed, 2);
+
+        assert_eq!(unpacked, Ok(moves));
+    }
+
+    #[test]
+    fn test_packing_exchanges() {
+        let moves = vec![
+            Move::Exchange {
+                player: 0,
+                tile: 'Q',
+            },
+            Move::Exchange {
+                player: 1,
+                tile: '?',
+            },
+            Move::Place {
+                player: 0,
+                tile: 'B',
+                position: Coordinate { x: 1, y: 1 },
+            },
+        ];
+
+        let packed = pack_moves_text(&moves, 2);
+
+        assert_eq!(packed, "[0]!Q!?11B".to_string());
+
+        let unpacked = unpack_moves_text(&packed, 2);
 
         assert_eq!(unpacked, Ok(moves));
     }
@@ -242,11 +651,11 @@ mod tests {
             },
         ];
 
-        let packed = pack_moves(&moves, 3);
+        let packed = pack_moves_text(&moves, 3);
 
         assert_eq!(packed, "[0]1203A11B001301J<11/1009>33R".to_string());
 
-        let unpacked = unpack_moves(&packed, 3);
+        let unpacked = unpack_moves_text(&packed, 3);
 
         assert_eq!(unpacked, Ok(moves));
     }
@@ -290,15 +699,145 @@ mod tests {
             },
         ];
 
-        let packed = pack_moves(&moves, 2);
+        let packed = pack_moves_text(&moves, 2);
 
         assert_eq!(
             packed,
             "[0]1203A[0]11B[0]001301J<11/1009>22E33R[9]00X".to_string()
         );
 
-        let unpacked = unpack_moves(&packed, 2);
+        let unpacked = unpack_moves_text(&packed, 2);
 
         assert_eq!(unpacked, Ok(moves));
     }
+
+    #[test]
+    fn test_binary_packing() {
+        let moves = vec![
+            Move::Place {
+                player: 0,
+                tile: 'A',
+                position: Coordinate { x: 12, y: 3 },
+            },
+            Move::Swap {
+                player: 1,
+                positions: [Coordinate { x: 1, y: 1 }, Coordinate { x: 200, y: 9 }],
+            },
+            Move::Challenge {
+                player: 0,
+                position: Coordinate { x: 4, y: 5 },
+            },
+            Move::Pass { player: 20 },
+            Move::Exchange {
+                player: 1,
+                tile: 'Q',
+            },
+        ];
+
+        let packed = pack_moves(&moves);
+
+        #[rustfmt::skip]
+        let expected = vec![
+            PACKING_VERSION,
+            0x00, 12, 3, b'A',
+            0x11, 1, 1, 0xC8, 0x01, 9,
+            0x20, 4, 5,
+            0x3F, 20,
+            0x41, b'Q',
+        ];
+        assert_eq!(packed, expected);
+        assert_eq!(unpack_moves(&packed), Ok(moves));
+    }
+
+    #[test]
+    fn test_binary_packing_errors() {
+        assert_eq!(unpack_moves(&[]), Err(PackingError::MissingVersion));
+        assert_eq!(unpack_moves(&[PACKING_VERSION]), Ok(vec![]));
+        assert_eq!(
+            unpack_moves(&[PACKING_VERSION + 1, 0x30]),
+            Err(PackingError::UnsupportedVersion(PACKING_VERSION + 1))
+        );
+        assert_eq!(
+            unpack_moves(&[PACKING_VERSION, 0x90]),
+            Err(PackingError::UnknownMove(9))
+        );
+        assert_eq!(
+            unpack_moves(&[PACKING_VERSION, 0x00, 12, 0x83]),
+            Err(PackingError::Truncated)
+        );
+        assert_eq!(
+            unpack_moves(&[PACKING_VERSION, 0x00, 1, 1, 0x80, 0xB0, 0x03]),
+            Err(PackingError::InvalidTile(0xD800))
+        );
+    }
+
+    fn random_moves(rng: &mut oorandom::Rand32) -> Vec<Move> {
+        let random_usize = |rng: &mut oorandom::Rand32| match rng.rand_range(0..4) {
+            0 => rng.rand_range(0..16) as usize,
+            1 => rng.rand_range(0..300) as usize,
+            2 => rng.rand_u32() as usize,
+            _ => (rng.rand_u32() as usize) << (rng.rand_range(0..usize::BITS - 31)),
+        };
+        let random_coord = |rng: &mut oorandom::Rand32| Coordinate {
+            x: random_usize(rng),
+            y: random_usize(rng),
+        };
+        let tiles = ['A', 'z', 'Ö', '字', '🦀'];
+
+        (0..rng.rand_range(0..40))
+            .map(|_| {
+                let player = if rng.rand_float() < 0.9 {
+                    rng.rand_range(0..2) as usize
+                } else {
+                    random_usize(rng)
+                };
+                match rng.rand_range(0..5) {
+                    0 => Move::Place {
+                        player,
+                        tile: tiles[rng.rand_range(0..tiles.len() as u32) as usize],
+                        position: random_coord(rng),
+                    },
+                    1 => Move::Swap {
+                        player,
+                        positions: [random_coord(rng), random_coord(rng)],
+                    },
+                    2 => Move::Challenge {
+                        player,
+                        position: random_coord(rng),
+                    },
+                    3 => Move::Pass { player },
+                    _ => Move::Exchange {
+                        player,
+                        tile: tiles[rng.rand_range(0..tiles.len() as u32) as usize],
+                    },
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_binary_packing_round_trip_fuzz() {
+        let mut rng = oorandom::Rand32::new(87);
+
+        for _ in 0..2000 {
+            let moves = random_moves(&mut rng);
+            let packed = pack_moves(&moves);
+
+            assert_eq!(unpack_moves(&packed), Ok(moves.clone()));
+
+            // Packing the opening moves of a game gives the opening bytes of the whole game
+            let split = rng.rand_range(0..moves.len() as u32 + 1) as usize;
+            assert!(packed.starts_with(&pack_moves(&moves[..split])));
+
+            // Cut off or corrupted bytes are rejected rather than panicking
+            let cut = rng.rand_range(0..packed.len() as u32) as usize;
+            _ = unpack_moves(&packed[..cut]);
+            if packed.len() > 1 {
+                let mut corrupted = packed.clone();
+                let flipped = rng.rand_range(1..packed.len() as u32) as usize;
+                corrupted[flipped] ^= rng.rand_range(1..256) as u8;
+                _ = unpack_moves(&corrupted);
+            }
+        }
+    }
 }
@@ -0,0 +1,122 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    hash::Hash,
+};
+
+use xxhash_rust::xxh3;
+
+/// A map holding at most `capacity` entries,
+/// evicting whichever entry was least recently read or written.
+/// Without a capacity it is a plain map, and recency isn't tracked at all.
+pub struct Lru<K, V> {
+    entries: HashMap<K, (V, u64), xxh3::Xxh3Builder>,
+    recency: BTreeMap<u64, K>,
+    clock: u64,
+    capacity: Option<usize>,
+}
+
+impl<K: Hash + Eq + Clone, V> Lru<K, V> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: Some(capacity.max(1)),
+            ..Self::unbounded()
+        }
+    }
+
+    pub fn unbounded() -> Self {
+        Self {
+            entries: HashMap::with_hasher(xxh3::Xxh3Builder::new()),
+            recency: BTreeMap::new(),
+            clock: 0,
+            capacity: None,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.entries.contains_key(key)
+    }
+
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        let (value, last_used) = self.entries.get_mut(key)?;
+        if self.capacity.is_none() {
+            return Some(value);
+        }
+
+        self.clock += 1;
+        let previous = std::mem::replace(last_used, self.clock);
+        if let Some(tracked) = self.recency.remove(&previous) {
+            self.recency.insert(self.clock, tracked);
+        }
+
+        Some(value)
+    }
+
+    pub fn insert(&mut self, key: K, value: V) {
+        let Some(capacity) = self.capacity else {
+            self.entries.insert(key, (value, 0));
+            return;
+        };
+
+        self.clock += 1;
+        if let Some((_, previous)) = self.entries.insert(key.clone(), (value, self.clock)) {
+            self.recency.remove(&previous);
+        }
+        self.recency.insert(self.clock, key);
+
+        while self.entries.len() > capacity {
+            let (_, oldest) = self
+                .recency
+                .pop_first()
+                .expect("Over capacity maps have entries");
+            self.entries.remove(&oldest);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.recency.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let mut lru = Lru::new(2);
+        lru.insert('a', 1);
+        lru.insert('b', 2);
+        assert_eq!(lru.get(&'a'), Some(&1));
+
+        lru.insert('c', 3);
+        assert_eq!(lru.len(), 2);
+        assert!(!lru.contains_key(&'b'));
+        assert_eq!(lru.get(&'a'), Some(&1));
+        assert_eq!(lru.get(&'c'), Some(&3));
+
+        lru.insert('a', 4);
+        lru.insert('d', 5);
+        assert!(!lru.contains_key(&'c'));
+        assert_eq!(lru.get(&'a'), Some(&4));
+    }
+
+    #[test]
+    fn unbounded_never_evicts() {
+        let mut lru = Lru::unbounded();
+        for i in 0..100 {
+            lru.insert(i, i);
+        }
+        assert_eq!(lru.len(), 100);
+        assert_eq!(lru.get(&0), Some(&0));
+        assert!(lru.recency.is_empty());
+    }
+}
@@ -1,28 +1,56 @@
 use std::{
     collections::{HashMap, HashSet},
     ops::Div,
+    time::Duration,
 };
 
+use instant::Instant;
+use serde::{Deserialize, Serialize};
+
 use crate::{
+    bag::is_placed_blank,
     board::{BoardDistances, Coordinate, Square},
     game::Game,
     judge::WordDict,
     messages::PlayerMessage,
     moves::Move,
     player::Hand,
+    rules::{ExplosionRules, ObeliskRule, TownRecovery, TownsToDefeat, WinCondition},
 };
 
+mod lru;
+pub mod opening_book;
 pub mod scoring;
 
 use scoring::BoardScore;
 use xxhash_rust::xxh3;
 
+use self::lru::Lru;
+
 use self::scoring::NPCParams;
 
+/// The deepest search attempted when searching within a time budget
+pub const TIMED_SEARCH_DEPTH: usize = 24;
+
+/// How far down the ranking of moves an NPC below full difficulty can fall
+const DEGRADED_CANDIDATES: usize = 5;
+
+/// The letters the NPC tries its blanks as, since trying every letter
+/// would multiply the moves searched for each blank by 26
+const BLANK_LETTERS: &str = "aeilnorstu";
+
+/// How many boards are assessed between checks of the clock
+const DEADLINE_CHECK_INTERVAL: usize = 32;
+
+/// How many of the strongest moves are explained when logging the NPC's choice
+const LOGGED_CANDIDATES: usize = 3;
+
 pub struct Arborist {
     assessed: usize,
     prune: bool,
     cap: usize,
+    deadline: Option<Instant>,
+    timed_out: bool,
 }
 impl Arborist {
     pub fn pruning() -> Self {
@@ -30,6 +58,8 @@ impl Arborist {
             assessed: 0,
             prune: true,
             cap: std::usize::MAX,
+            deadline: None,
+            timed_out: false,
         }
     }
 
@@ -41,13 +71,33 @@ impl Arborist {
         self.cap = cap;
     }
 
-    /// Useful for testing, dead in production code
-    #[allow(dead_code)]
+    /// Stops assessing boards once `budget` has elapsed from now
+    pub fn time_limited(&mut self, budget: Duration) {
+        self.deadline = Some(Instant::now() + budget);
+        self.timed_out = false;
+    }
+
+    /// Assesses every board without pruning, for NPCs that search without it
     fn exhaustive() -> Self {
         Self {
             assessed: 0,
             prune: false,
             cap: std::usize::MAX,
+            deadline: None,
+            timed_out: false,
+        }
+    }
+
+    /// An arborist for one thread of a parallel search, sharing this arborist's
+    /// deadline but assessing at most `share` boards of its own.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn worker(&self, share: usize) -> Self {
+        Self {
+            assessed: 0,
+            prune: self.prune,
+            cap: share,
+            deadline: self.deadline,
+            timed_out: false,
         }
     }
 
@@ -56,22 +106,114 @@ impl Arborist {
     }
 
     fn tick(&mut self) {
-        self.assessed += 1
+        self.assessed += 1;
+
+        if let Some(deadline) = self.deadline {
+            if self.assessed % DEADLINE_CHECK_INTERVAL == 0 && Instant::now() >= deadline {
+                self.timed_out = true;
+            }
+        }
+    }
+
+    fn over_budget(&self) -> bool {
+        self.assessed > self.cap || self.timed_out
+    }
+}
+
+/// A turn that the search can explore for the next player
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Candidate {
+    Place(Coordinate, char),
+    /// Only explored when the rules allow passing
+    Pass,
+    /// Only explored when the rules allow exchanges and the bag has tiles to give
+    Exchange(char),
+}
+
+impl Candidate {
+    fn to_move(self, player: usize) -> Move {
+        match self {
+            Candidate::Place(position, tile) => Move::Place {
+                player,
+                tile,
+                position,
+            },
+            Candidate::Pass => Move::Pass { player },
+            Candidate::Exchange(tile) => Move::Exchange { player, tile },
+        }
+    }
+
+    fn to_message(self) -> PlayerMessage {
+        match self {
+            Candidate::Place(position, tile) => PlayerMessage::Place(position, tile),
+            Candidate::Pass => PlayerMessage::Pass,
+            Candidate::Exchange(tile) => PlayerMessage::Exchange(tile),
+        }
     }
 }
 
+/// Memoized work from a search.
+/// A single search uses a fresh `Caches`, but callers can keep one from `Caches::persistent`
+/// and pass it to successive `Game::npc_move` or `Game::best_move_cached` calls
+/// to reuse flood fills and word judgements between moves.
 pub struct Caches {
-    cached_floods: HashMap<Vec<u64>, (BoardDistances, BoardDistances), xxh3::Xxh3Builder>,
-    cached_scores: HashMap<(Coordinate, char, usize), usize, xxh3::Xxh3Builder>,
+    cached_floods: Lru<u64, (BoardDistances, BoardDistances)>,
+    cached_scores: HashMap<(Candidate, usize), usize, xxh3::Xxh3Builder>,
     cached_words: HashMap<String, bool, xxh3::Xxh3Builder>,
+    word_capacity: usize,
+    /// The board shape and player that `cached_scores` were assessed from
+    searched_from: Option<(u64, usize)>,
 }
 
 impl Caches {
     pub fn new() -> Self {
         Self {
-            cached_floods: HashMap::with_hasher(xxh3::Xxh3Builder::new()),
+            cached_floods: Lru::unbounded(),
             cached_scores: HashMap::with_hasher(xxh3::Xxh3Builder::new()),
             cached_words: HashMap::with_hasher(xxh3::Xxh3Builder::new()),
+            word_capacity: std::usize::MAX,
+            searched_from: None,
+        }
+    }
+
+    /// A cache to be kept between moves, holding at most
+    /// `flood_capacity` flood fills and `word_capacity` word judgements.
+    pub fn persistent(flood_capacity: usize, word_capacity: usize) -> Self {
+        Self {
+            cached_floods: Lru::new(flood_capacity),
+            word_capacity,
+            ..Self::new()
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.cached_floods.len() + self.cached_scores.len() + self.cached_words.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Drops anything that can't be reused for a search of `game` by `for_player`.
+    fn prepare_for(&mut self, game: &Game, for_player: usize) {
+        let shape = game.board.shape_hash();
+        match &self.searched_from {
+            Some((_, prev_player)) if *prev_player != for_player => {
+                // Flood fills are keyed on shape alone, so are only valid for one player
+                self.cached_floods.clear();
+                self.cached_scores.clear();
+            }
+            Some((prev_shape, _)) if *prev_shape != shape => {
+                // Move ordering scores belong to the position they were searched from
+                self.cached_scores.clear();
+            }
+            _ => {}
+        }
+        self.searched_from = Some((shape, for_player));
+
+        // Word judgements are cheap to redo, so they're dropped wholesale rather than tracked
+        if self.cached_words.len() > self.word_capacity {
+            self.cached_words.clear();
         }
     }
 }
@@ -85,6 +227,30 @@ impl Game {
         counter: Option<&mut Arborist>,
         log: bool,
         npc_params: &NPCParams,
+    ) -> (PlayerMessage, BoardScore) {
+        Game::best_move_cached(
+            game,
+            self_dictionary,
+            opponent_dictionary,
+            depth,
+            counter,
+            log,
+            npc_params,
+            &mut Caches::new(),
+        )
+    }
+
+    /// Searches like `best_move`, reusing and adding to `caches`.
+    /// Anything cached from a different position or player is discarded first.
+    pub fn best_move_cached(
+        game: &Game,
+        self_dictionary: Option<&WordDict>,
+        opponent_dictionary: Option<&WordDict>,
+        depth: usize,
+        counter: Option<&mut Arborist>,
+        log: bool,
+        npc_params: &NPCParams,
+        caches: &mut Caches,
     ) -> (PlayerMessage, BoardScore) {
         let evaluation_player = game
             .next_player
@@ -95,9 +261,9 @@ impl Game {
         } else {
             Arborist::exhaustive()
         };
-        let mut caches = Caches::new();
+        caches.prepare_for(game, evaluation_player);
 
-        let mut run_mini = |partial_depth: usize, arborist: &mut Arborist| {
+        let run_mini = |partial_depth: usize, arborist: &mut Arborist| {
             Game::minimax(
                 game.clone(),
                 self_dictionary,
@@ -109,33 +275,195 @@ impl Game {
                 BoardScore::inf(),
                 evaluation_player,
                 arborist,
-                &mut caches,
+                caches,
                 npc_params,
             )
         };
 
+        let arborist = counter.unwrap_or_else(|| &mut internal_arborist);
+        Game::deepen(
+            game,
+            self_dictionary,
+            evaluation_player,
+            depth,
+            arborist,
+            log,
+            npc_params,
+            run_mini,
+        )
+    }
+
+    /// Evaluates each root move on its own thread, otherwise searching like `best_move`.
+    /// Each root move keeps its own `Caches` across the deepening layers, and gets an equal
+    /// share of the arborist's remaining cap, so that the result doesn't depend on how the
+    /// threads are scheduled. A layer is discarded if any root move runs over its share.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn best_move_parallel(
+        game: &Game,
+        self_dictionary: Option<&WordDict>,
+        opponent_dictionary: Option<&WordDict>,
+        depth: usize,
+        counter: Option<&mut Arborist>,
+        log: bool,
+        npc_params: &NPCParams,
+    ) -> (PlayerMessage, BoardScore) {
+        use rayon::prelude::*;
+
+        let evaluation_player = game
+            .next_player
+            .expect("Minimax only works in non-periodic playmodes");
+
+        let mut internal_arborist = if npc_params.pruning {
+            Arborist::pruning()
+        } else {
+            Arborist::exhaustive()
+        };
+
+        let mut root = game.clone();
+        // Root instrumentation doesn't depend on the search depth, as no aliasing happens here
+        root.instrument_unknown_game_state(evaluation_player, depth, depth);
+        let root_moves = root.possible_moves();
+        let mut root_caches: Vec<_> = root_moves.iter().map(|_| Caches::new()).collect();
+
+        let run_parallel = |partial_depth: usize, arborist: &mut Arborist| {
+            if root.is_over() || root_moves.is_empty() {
+                return Game::minimax(
+                    root.clone(),
+                    self_dictionary,
+                    opponent_dictionary,
+                    partial_depth,
+                    partial_depth,
+                    0,
+                    BoardScore::neg_inf(),
+                    BoardScore::inf(),
+                    evaluation_player,
+                    arborist,
+                    &mut Caches::new(),
+                    npc_params,
+                );
+            }
+
+            let share = arborist.cap.saturating_sub(arborist.assessed) / root_moves.len();
+            let parent: &Arborist = arborist;
+
+            let scores: Vec<_> = root_moves
+                .par_iter()
+                .zip(root_caches.par_iter_mut())
+                .map(|(&candidate, caches)| {
+                    let mut worker = parent.worker(share);
+                    worker.tick();
+                    if worker.over_budget() {
+                        return (None, worker);
+                    }
+
+                    let mut next_turn = root.clone();
+                    next_turn
+                        .play_turn(
+                            candidate.to_move(evaluation_player),
+                            self_dictionary,
+                            opponent_dictionary,
+                            Some(&mut caches.cached_words),
+                        )
+                        .expect("Should be exploring valid turns");
+                    let score = Game::minimax(
+                        next_turn,
+                        self_dictionary,
+                        opponent_dictionary,
+                        partial_depth,
+                        partial_depth - 1,
+                        1,
+                        BoardScore::neg_inf(),
+                        BoardScore::inf(),
+                        evaluation_player,
+                        &mut worker,
+                        caches,
+                        npc_params,
+                    )
+                    .0;
+
+                    (Some(score), worker)
+                })
+                .collect();
+
+            arborist.assessed += scores
+                .iter()
+                .map(|(_, worker)| worker.assessed)
+                .sum::<usize>();
+            arborist.timed_out |= scores.iter().any(|(_, worker)| worker.timed_out);
+            if scores.iter().any(|(_, worker)| worker.over_budget()) {
+                arborist.assessed = arborist.assessed.max(arborist.cap.saturating_add(1));
+            }
+
+            // Reduce in move order, so that ties resolve the same way as the serial search
+            let mut max_score = BoardScore::neg_inf();
+            let mut relevant_move = None;
+            for (candidate, (score, _)) in root_moves.iter().zip(scores) {
+                let Some(score) = score else {
+                    continue;
+                };
+                if score > max_score {
+                    max_score = score;
+                    relevant_move = Some(*candidate);
+                }
+            }
+
+            (max_score, relevant_move)
+        };
+
+        let arborist = counter.unwrap_or_else(|| &mut internal_arborist);
+        Game::deepen(
+            game,
+            self_dictionary,
+            evaluation_player,
+            depth,
+            arborist,
+            log,
+            npc_params,
+            run_parallel,
+        )
+    }
+
+    /// Runs `run_layer` at increasing depths, keeping the deepest layer
+    /// that was explored without exceeding the arborist's budget.
+    fn deepen(
+        game: &Game,
+        self_dictionary: Option<&WordDict>,
+        evaluation_player: usize,
+        depth: usize,
+        arborist: &mut Arborist,
+        log: bool,
+        npc_params: &NPCParams,
+        mut run_layer: impl FnMut(usize, &mut Arborist) -> (BoardScore, Option<Candidate>),
+    ) -> (PlayerMessage, BoardScore) {
         let mut latest = None;
         let mut looked = 0;
 
-        let arborist = counter.unwrap_or_else(|| &mut internal_arborist);
         for d in 1..depth {
-            let maybelatest = Some(run_mini(d, arborist));
-            if arborist.assessed > arborist.cap {
+            let maybelatest = Some(run_layer(d, arborist));
+            // Discard partially explored layers, falling back to the last complete layer
+            if arborist.over_budget() {
                 break;
             }
             latest = maybelatest;
             looked = d;
         }
 
-        if arborist.assessed < arborist.cap {
-            let maybelatest = Some(run_mini(depth, arborist));
-            if arborist.assessed < arborist.cap {
+        if arborist.assessed < arborist.cap && !arborist.timed_out {
+            let maybelatest = Some(run_layer(depth, arborist));
+            if arborist.assessed < arborist.cap && !arborist.timed_out {
                 latest = maybelatest;
                 looked = depth;
             }
         }
 
-        let Some((best_score, Some((position, tile)))) = latest else {
+        // Always complete the shallowest search, so that there is a move to play
+        if latest.is_none() {
+            let mut fallback_arborist = Arborist::pruning();
+            latest = Some(run_layer(1, &mut fallback_arborist));
+            looked = 1;
+        }
+
+        let Some((best_score, Some(candidate))) = latest else {
             panic!("Expected a valid position to be playable");
         };
 
@@ -150,9 +478,246 @@ impl Game {
             if let Some(board) = &best_score.board {
                 println!("Bot is aiming for the board {board}");
             }
+
+            game.log_top_candidates(self_dictionary, looked, npc_params);
         }
 
-        (PlayerMessage::Place(position, tile), best_score)
+        (candidate.to_message(), best_score)
+    }
+
+    /// Deepens the search until `budget` has elapsed,
+    /// returning the best move from the deepest fully explored layer.
+    pub fn best_move_within(
+        game: &Game,
+        self_dictionary: Option<&WordDict>,
+        opponent_dictionary: Option<&WordDict>,
+        budget: Duration,
+        log: bool,
+        npc_params: &NPCParams,
+    ) -> (PlayerMessage, BoardScore) {
+        let mut arborist = if npc_params.pruning {
+            Arborist::pruning()
+        } else {
+            Arborist::exhaustive()
+        };
+        arborist.time_limited(budget);
+
+        Game::best_move(
+            game,
+            self_dictionary,
+            opponent_dictionary,
+            TIMED_SEARCH_DEPTH,
+            Some(&mut arborist),
+            log,
+            npc_params,
+        )
+    }
+
+    /// Picks the NPC's move, which is the best move unless `npc_params.difficulty`
+    /// is below 1, in which case weaker moves are sometimes chosen instead.
+    /// The choice depends only on `seed` and the game state, so replaying a game
+    /// against the same NPC reproduces its moves. Moves that lose the game are never
+    /// chosen over the best move. Searches reuse `caches` if given one kept between moves.
+    pub fn npc_move(
+        game: &Game,
+        self_dictionary: Option<&WordDict>,
+        opponent_dictionary: Option<&WordDict>,
+        counter: Option<&mut Arborist>,
+        caches: Option<&mut Caches>,
+        npc_params: &NPCParams,
+        seed: u64,
+    ) -> PlayerMessage {
+        if npc_params.difficulty < 1.0 {
+            // Ranked as deeply as the best move is searched for, so the ranking leads with it
+            let ranked = game.ranked_moves_with(
+                self_dictionary,
+                opponent_dictionary,
+                npc_params.max_depth,
+                DEGRADED_CANDIDATES,
+                npc_params,
+            );
+
+            if let Some((best_move, _)) = ranked.first().cloned() {
+                let state = format!(
+                    "{}{}",
+                    game.board,
+                    game.players[game.next_player.unwrap()].hand
+                );
+                let mut rng =
+                    oorandom::Rand32::new(xxh3::xxh3_64_with_seed(state.as_bytes(), seed));
+
+                // Each step further down the ranking is taken with a probability of `1 - difficulty`
+                let mut chosen = None;
+                for (candidate, _) in ranked.into_iter().filter(|(_, score)| !score.is_loss()) {
+                    chosen = Some(candidate);
+                    if rng.rand_float() < npc_params.difficulty {
+                        break;
+                    }
+                }
+
+                return chosen.unwrap_or(best_move);
+            }
+        }
+
+        // Move ordering scores could change which of equally good moves is found,
+        // so only the flood fills and word judgements are carried over between moves
+        let mut fresh_caches = Caches::new();
+        let caches = match caches {
+            Some(caches) => {
+                caches.cached_scores.clear();
+                caches
+            }
+            None => &mut fresh_caches,
+        };
+        Game::best_move_cached(
+            game,
+            self_dictionary,
+            opponent_dictionary,
+            npc_params.max_depth,
+            counter,
+            false,
+            npc_params,
+            caches,
+        )
+        .0
+    }
+
+    /// Scores every place, swap, pass and exchange available to the next player by searching
+    /// `depth` turns ahead from each, returning the best `n` from best to worst.
+    pub fn ranked_moves(
+        &self,
+        dict: Option<&WordDict>,
+        depth: usize,
+        n: usize,
+    ) -> Vec<(PlayerMessage, BoardScore)> {
+        self.ranked_moves_with(dict, dict, depth, n, &NPCParams::default())
+    }
+
+    fn ranked_moves_with(
+        &self,
+        self_dictionary: Option<&WordDict>,
+        opponent_dictionary: Option<&WordDict>,
+        depth: usize,
+        n: usize,
+        npc_params: &NPCParams,
+    ) -> Vec<(PlayerMessage, BoardScore)> {
+        let evaluation_player = self
+            .next_player
+            .expect("Minimax only works in non-periodic playmodes");
+        let depth = depth.max(1);
+
+        let mut root = self.clone();
+        root.instrument_unknown_game_state(evaluation_player, depth, depth);
+
+        let places = root.possible_moves().into_iter().map(Candidate::to_message);
+        let swaps = root
+            .possible_swaps()
+            .into_iter()
+            .map(|[from, to]| PlayerMessage::Swap(from, to));
+
+        let mut caches = Caches::new();
+        let mut ranked: Vec<_> = places
+            .chain(swaps)
+            .filter_map(|msg| {
+                let next_move = match msg {
+                    PlayerMessage::Place(position, tile) => Move::Place {
+                        player: evaluation_player,
+                        tile,
+                        position,
+                    },
+                    PlayerMessage::Swap(from, to) => Move::Swap {
+                        player: evaluation_player,
+                        positions: [from, to],
+                    },
+                    PlayerMessage::Pass => Move::Pass {
+                        player: evaluation_player,
+                    },
+                    PlayerMessage::Exchange(tile) => Move::Exchange {
+                        player: evaluation_player,
+                        tile,
+                    },
+                    _ => unreachable!("Only places, swaps, passes and exchanges are ranked"),
+                };
+
+                let mut next_turn = root.clone();
+                next_turn
+                    .play_turn(
+                        next_move,
+                        self_dictionary,
+                        opponent_dictionary,
+                        Some(&mut caches.cached_words),
+                    )
+                    .ok()?;
+
+                let (score, _) = Game::minimax(
+                    next_turn,
+                    self_dictionary,
+                    opponent_dictionary,
+                    depth,
+                    depth - 1,
+                    1,
+                    BoardScore::neg_inf(),
+                    BoardScore::inf(),
+                    evaluation_player,
+                    &mut Arborist::pruning(),
+                    &mut caches,
+                    npc_params,
+                );
+
+                Some((msg, score))
+            })
+            .collect();
+
+        // Stable, so equally scored moves keep places ahead of swaps
+        ranked.sort_by(|(_, a), (_, b)| b.partial_cmp(a).unwrap_or(std::cmp::Ordering::Equal));
+        ranked.truncate(n);
+        ranked
+    }
+
+    /// Prints the strongest few moves with their score components and the words they'd form,
+    /// to explain why the NPC preferred one move over another
+    fn log_top_candidates(&self, dict: Option<&WordDict>, depth: usize, npc_params: &NPCParams) {
+        let evaluation_player = self
+            .next_player
+            .expect("Minimax only works in non-periodic playmodes");
+
+        println!("Bot's top {LOGGED_CANDIDATES} candidates at a depth of {depth}:");
+        let ranked = self.ranked_moves_with(dict, dict, depth, LOGGED_CANDIDATES, npc_params);
+        for (i, (candidate, score)) in ranked.iter().enumerate() {
+            println!("  {}. {candidate}", i + 1);
+            if score.is_win() || score.is_loss() {
+                println!(
+                    "     Leads to a {}",
+                    if score.is_win() { "win" } else { "loss" }
+                );
+            }
+            for (component, value) in score.breakdown() {
+                println!("     {component}: {value:.3}");
+            }
+
+            let PlayerMessage::Place(position, tile) = candidate else {
+                continue;
+            };
+            let mut board = self.board.clone();
+            if board
+                .set(*position, evaluation_player, *tile, None)
+                .is_err()
+            {
+                continue;
+            }
+            let words = board.get_words(*position);
+            for word in board.word_strings(&words).unwrap_or_default() {
+                let valid = self
+                    .judge
+                    .valid(&word, &self.rules.win_condition, dict, None, &mut None)
+                    .is_some();
+                println!(
+                    "     Forms {word} ({} letters, {})",
+                    word.chars().count(),
+                    if valid { "valid" } else { "invalid" }
+                );
+            }
+        }
     }
 
     fn minimax(
@@ -168,11 +733,11 @@ impl Game {
         arborist: &mut Arborist,
         caches: &mut Caches,
         npc_params: &NPCParams,
-    ) -> (BoardScore, Option<(Coordinate, char)>) {
+    ) -> (BoardScore, Option<Candidate>) {
         game.instrument_unknown_game_state(for_player, total_depth, depth);
         let pruning = arborist.prune();
 
-        if depth == 0 || game.winner.is_some() {
+        if depth == 0 || game.is_over() {
             return (
                 game.static_eval(self_dictionary, for_player, depth, caches, npc_params),
                 None,
@@ -180,18 +745,18 @@ impl Game {
         }
 
         let mut possible_moves = game.possible_moves();
-        possible_moves.sort_by_cached_key(|(position, tile)| {
+        possible_moves.sort_by_cached_key(|candidate| {
             std::usize::MAX
                 - caches
                     .cached_scores
-                    .get(&(*position, *tile, layer))
+                    .get(&(*candidate, layer))
                     .unwrap_or(&std::usize::MAX)
         });
 
         let mut turn_score =
-            |game: &Game, tile: char, position: Coordinate, alpha: BoardScore, beta: BoardScore| {
+            |game: &Game, candidate: Candidate, alpha: BoardScore, beta: BoardScore| {
                 arborist.tick();
-                if arborist.assessed > arborist.cap {
+                if arborist.over_budget() {
                     return None;
                 }
                 let mut next_turn = game.clone();
@@ -208,11 +773,7 @@ impl Game {
 
                 next_turn
                     .play_turn(
-                        Move::Place {
-                            player: next_player,
-                            tile,
-                            position,
-                        },
+                        candidate.to_move(next_player),
                         attacker_dict,
                         defender_dict,
                         Some(&mut caches.cached_words),
@@ -237,12 +798,11 @@ impl Game {
                 if is_players_turn {
                     caches
                         .cached_scores
-                        .insert((position, tile, layer), score.usize_rank());
+                        .insert((candidate, layer), score.usize_rank());
                 } else {
-                    caches.cached_scores.insert(
-                        (position, tile, layer),
-                        std::usize::MAX - score.usize_rank(),
-                    );
+                    caches
+                        .cached_scores
+                        .insert((candidate, layer), std::usize::MAX - score.usize_rank());
                 }
 
                 Some(score)
@@ -252,15 +812,14 @@ impl Game {
             let mut max_score = BoardScore::neg_inf();
             let mut relevant_move = None;
 
-            for (position, tile) in possible_moves {
-                let Some(score) = turn_score(&game, tile, position, alpha.clone(), beta.clone())
-                else {
+            for candidate in possible_moves.iter().copied() {
+                let Some(score) = turn_score(&game, candidate, alpha.clone(), beta.clone()) else {
                     break;
                 };
 
                 if score > max_score {
                     max_score = score.clone();
-                    relevant_move = Some((position, tile));
+                    relevant_move = Some(candidate);
                 }
                 if max_score > alpha {
                     alpha = score;
@@ -278,15 +837,14 @@ impl Game {
             let mut min_score = BoardScore::inf();
             let mut relevant_move = None;
 
-            for (position, tile) in possible_moves {
-                let Some(score) = turn_score(&game, tile, position, alpha.clone(), beta.clone())
-                else {
+            for candidate in possible_moves.iter().copied() {
+                let Some(score) = turn_score(&game, candidate, alpha.clone(), beta.clone()) else {
                     break;
                 };
 
                 if score < min_score {
                     min_score = score.clone();
-                    relevant_move = Some((position, tile));
+                    relevant_move = Some(candidate);
                 }
                 if min_score < beta {
                     beta = score;
@@ -303,39 +861,49 @@ impl Game {
         }
     }
 
-    fn possible_moves(&self) -> Vec<(Coordinate, char)> {
-        let mut playable_tiles: Vec<_> = self
-            .players
-            .get(self.next_player.unwrap())
-            .unwrap()
-            .hand
-            .iter()
-            .cloned()
-            .collect::<HashSet<_>>()
-            .into_iter()
-            .collect();
-
-        playable_tiles.sort();
-
-        let playable_squares = self
-            .board
-            .playable_positions(self.next_player.unwrap(), &self.rules.truncation);
-
-        let mut coords: Vec<_> = playable_squares
+    /// Every place available to the next player, followed by a pass
+    /// and an exchange of each distinct tile in hand if the rules allow them
+    fn possible_moves(&self) -> Vec<Candidate> {
+        let player = self.next_player.unwrap();
+        let mut coords: Vec<_> = self
+            .legal_placements(player)
             .into_iter()
-            .flat_map(|sq| playable_tiles.iter().cloned().map(move |t| (sq, t)))
+            .filter(|(_, tile)| !is_placed_blank(*tile) || BLANK_LETTERS.contains(*tile))
             .collect();
 
         // TODO: Build move heuristic to deterministically sort these moves by quality
         coords.sort_by(|a, b| {
-            if self.next_player.unwrap() == 0 {
+            let by_square = if player == 0 {
                 a.0.cmp(&b.0)
             } else {
                 b.0.cmp(&a.0)
-            }
+            };
+            by_square.then(a.1.cmp(&b.1))
         });
 
-        coords
+        let mut candidates: Vec<_> = coords
+            .into_iter()
+            .map(|(position, tile)| Candidate::Place(position, tile))
+            .collect();
+
+        // Passing is explored last, so that equally scored places are preferred
+        if self.rules.allow_passing {
+            candidates.push(Candidate::Pass);
+        }
+
+        if self.rules.allow_exchanges && !self.bag.remaining().is_empty() {
+            let mut hand: Vec<_> = self.players[player].hand.iter().copied().collect();
+            hand.sort();
+            hand.dedup();
+            candidates.extend(hand.into_iter().map(Candidate::Exchange));
+        }
+
+        candidates
+    }
+
+    /// All pairs of the next player's tiles that they are allowed to swap
+    fn possible_swaps(&self) -> Vec<[Coordinate; 2]> {
+        self.legal_swaps(self.next_player.unwrap())
     }
 
     fn instrument_unknown_game_state(
@@ -344,13 +912,18 @@ impl Game {
         total_depth: usize,
         current_depth: usize,
     ) {
-        let unknown_player_index = (evaluation_player + 1) % self.players.len();
+        // Simulated turns shouldn't be recorded, nor copied into every branch
+        self.history = None;
+        self.word_history = None;
 
         let player = &mut self.players[evaluation_player];
 
         // Remove timing concerns from the simulated turns
         self.rules.battle_delay = 0;
 
+        // Searches are too shallow to reach a stalemate, so skip tracking progress
+        self.rules.stalemate_turns = None;
+
         // Prevent the evaluation player from being given new tiles in future turns
         player.hand_capacity = 0;
 
@@ -362,17 +935,26 @@ impl Game {
             player.hand = Hand(vec![alias; current_depth]);
         }
 
-        // Prevent the NPC from making decisions based on the opponent's tiles,
+        // Prevent the NPC from making decisions based on the opponents' tiles,
         // assume all valid plays.
-        self.players[unknown_player_index].hand = Hand(vec!['*']);
+        for (index, unknown_player) in self.players.iter_mut().enumerate() {
+            if index != evaluation_player {
+                unknown_player.hand = Hand(vec!['*']);
+            }
+        }
     }
 }
 
-#[derive(Clone, Debug, Default, PartialEq)]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
 pub struct WordQualityScores {
     word_length: f32,
     word_validity: f32,
     word_extensibility: f32,
+    /// The share of words that are valid but objectionable
+    word_objectionability: f32,
+    /// How rarely the words are used, from 0 when common to 1 when rarest.
+    /// Left at zero whenever any of the words are invalid, so rarity never favours them.
+    word_rarity: f32,
 }
 
 impl Div<f32> for WordQualityScores {
@@ -383,6 +965,8 @@ impl Div<f32> for WordQualityScores {
             word_length: self.word_length / rhs,
             word_validity: self.word_validity / rhs,
             word_extensibility: self.word_extensibility / rhs,
+            word_objectionability: self.word_objectionability / rhs,
+            word_rarity: self.word_rarity / rhs,
         }
     }
 }
@@ -404,25 +988,42 @@ impl Game {
         npc_params: &NPCParams,
     ) -> BoardScore {
         let word_quality = if let Some(external_dictionary) = external_dictionary {
-            self.eval_word_quality(external_dictionary, for_player, caches)
+            self.eval_word_quality(external_dictionary, for_player, caches, npc_params)
         } else {
             WordQualityScores::default()
         };
-        let for_opponent = (for_player + 1) % self.players.len();
+        let mut opponents: Vec<_> = (0..self.players.len())
+            .filter(|p| *p != for_player)
+            .collect();
+        if opponents.is_empty() {
+            opponents.push(for_player);
+        }
 
-        let shape = self.board.get_shape();
-        let (self_attack_distances, opponent_attack_distances) =
-            if let Some(res) = caches.cached_floods.get(&shape) {
-                res
-            } else {
-                let self_attack_distances = self.board.flood_fill_attacks(for_player);
-                let opponent_attack_distances = self.board.flood_fill_attacks(for_opponent);
-                caches.cached_floods.insert(
-                    shape.clone(),
-                    (self_attack_distances, opponent_attack_distances),
-                );
-                caches.cached_floods.get(&shape).unwrap()
-            };
+        let shape = self.board.shape_hash();
+        if !caches.cached_floods.contains_key(&shape) {
+            let self_attack_distances = self.board.flood_fill_attacks(for_player);
+            // With multiple opponents, a square is only as safe as its distance from the closest one
+            let opponent_attack_distances = opponents
+                .iter()
+                .map(|opponent| self.board.flood_fill_attacks(*opponent))
+                .reduce(|a, b| a.closest(&b))
+                .expect("There is always at least one opponent");
+            caches
+                .cached_floods
+                .insert(shape, (self_attack_distances, opponent_attack_distances));
+        }
+        let (self_attack_distances, opponent_attack_distances) = caches
+            .cached_floods
+            .get(&shape)
+            .expect("Floods were just cached");
+
+        // Attacks are scored against whichever opponent is most exposed
+        let weakest_opponent = |eval: &dyn Fn(usize) -> f32| {
+            opponents
+                .iter()
+                .map(|opponent| eval(*opponent))
+                .fold(f32::MAX, f32::min)
+        };
 
         BoardScore::default()
             .npc_params(*npc_params)
@@ -434,11 +1035,13 @@ impl Game {
                 for_player,
             ))
             .raced_attack(
-                1.0 - self.eval_min_raced_distance_to_towns(
-                    &self_attack_distances,
-                    &opponent_attack_distances,
-                    for_opponent,
-                ),
+                1.0 - weakest_opponent(&|opponent| {
+                    self.eval_min_raced_distance_to_towns(
+                        &self_attack_distances,
+                        &opponent_attack_distances,
+                        opponent,
+                    )
+                }),
             )
             .self_defense(self.eval_min_distance_to_towns(
                 &opponent_attack_distances,
@@ -446,11 +1049,13 @@ impl Game {
                 DefenceEvalType::Attackable,
             ))
             .self_attack(
-                1.0 - self.eval_min_distance_to_towns(
-                    &self_attack_distances,
-                    for_opponent,
-                    DefenceEvalType::Attackable,
-                ),
+                1.0 - weakest_opponent(&|opponent| {
+                    self.eval_min_distance_to_towns(
+                        &self_attack_distances,
+                        opponent,
+                        DefenceEvalType::Attackable,
+                    )
+                }),
             )
             .direct_defence(self.eval_min_distance_to_towns(
                 &opponent_attack_distances,
@@ -458,14 +1063,52 @@ impl Game {
                 DefenceEvalType::Direct,
             ))
             .direct_attack(
-                1.0 - self.eval_min_distance_to_towns(
-                    &self_attack_distances,
-                    for_opponent,
-                    DefenceEvalType::Direct,
-                ),
+                1.0 - weakest_opponent(&|opponent| {
+                    self.eval_min_distance_to_towns(
+                        &self_attack_distances,
+                        opponent,
+                        DefenceEvalType::Direct,
+                    )
+                }),
             )
+            .obelisk_control(self.eval_obelisk_control(
+                self_attack_distances,
+                opponent_attack_distances,
+                for_player,
+            ))
+            .explosion_potential(self.eval_explosion_potential(for_player))
             .self_win(self.winner == Some(for_player))
-            .opponent_win(self.winner == Some(for_opponent))
+            .opponent_win(self.winner.is_some_and(|winner| winner != for_player))
+    }
+
+    /// The towns an attacker still needs to reach to win, and whether reaching any one of them is enough.
+    /// Returns no towns when towns don't decide the game.
+    fn contested_towns(&self, defender: usize) -> (Vec<Coordinate>, bool) {
+        let towns_to_defeat = match &self.rules.win_condition {
+            WinCondition::Destination {
+                towns_to_defeat, ..
+            } => *towns_to_defeat,
+            WinCondition::Elimination => return (vec![], true),
+        };
+
+        // A town that can be recovered still has to be held down until the rest have fallen
+        let fallen_for_good = self.rules.town_recovery == TownRecovery::Permanent;
+        let towns = self
+            .board
+            .towns
+            .iter()
+            .filter(|town_pt| {
+                matches!(
+                    self.board.get(**town_pt),
+                    Ok(Square::Town { player: p, defeated, .. })
+                        if defender == p
+                            && !(defeated && fallen_for_good && towns_to_defeat == TownsToDefeat::All)
+                )
+            })
+            .cloned()
+            .collect();
+
+        (towns, towns_to_defeat == TownsToDefeat::Any)
     }
 
     pub fn eval_min_distance_to_towns(
@@ -474,24 +1117,21 @@ impl Game {
         defender: usize,
         defence_type: DefenceEvalType,
     ) -> f32 {
-        let towns = self.board.towns.clone();
         let max_score = self.board.width() + self.board.height();
+        let (defense_towns, any_town_wins) = self.contested_towns(defender);
 
-        let defense_towns = towns
-            .into_iter()
-            .filter(
-                |town_pt| matches!(self.board.get(*town_pt), Ok(Square::Town{player: p, ..}) if defender == p),
-            ).collect::<Vec<_>>();
-
-        let score = defense_towns
-            .iter()
-            .map(|town_pt| match defence_type {
-                DefenceEvalType::Attackable => {
-                    distances.attackable_distance(town_pt).unwrap_or(max_score)
-                }
-                DefenceEvalType::Direct => distances.direct_distance(town_pt).unwrap_or(max_score),
-            })
-            .min();
+        let town_distances = defense_towns.iter().map(|town_pt| match defence_type {
+            DefenceEvalType::Attackable => {
+                distances.attackable_distance(town_pt).unwrap_or(max_score)
+            }
+            DefenceEvalType::Direct => distances.direct_distance(town_pt).unwrap_or(max_score),
+        });
+        // When every town must fall, the defender is only as threatened as their safest town
+        let score = if any_town_wins {
+            town_distances.min()
+        } else {
+            town_distances.max()
+        };
 
         (score.unwrap_or(max_score) as f32) / (max_score as f32)
     }
@@ -502,34 +1142,129 @@ impl Game {
         defenders_tiles: &BoardDistances,
         defender: usize,
     ) -> f32 {
-        let towns = self.board.towns.clone();
         let max_score = self.board.width() + self.board.height();
+        let (defense_towns, any_town_wins) = self.contested_towns(defender);
 
-        let defense_towns = towns
-            .into_iter()
-            .filter(
-                |town_pt| matches!(self.board.get(*town_pt), Ok(Square::Town{player: p, ..}) if defender == p),
-            ).collect::<Vec<_>>();
+        let town_races = defense_towns.iter().map(|town_pt| {
+            let mut can_defend_in = defenders_tiles
+                .attackable_distance(town_pt)
+                .unwrap_or(max_score);
+            let mut can_attack_in = attackers_tiles
+                .attackable_distance(town_pt)
+                .unwrap_or(max_score);
+
+            // Fudge the numbers so that even races look bad for the defender
+            can_defend_in += 2;
+            can_attack_in = can_attack_in.saturating_sub(2);
+
+            can_defend_in.saturating_sub(can_attack_in)
+        });
+        // When every town must fall, the defender is only as threatened as their safest town
+        let score = if any_town_wins {
+            town_races.max()
+        } else {
+            town_races.min()
+        };
+
+        ((max_score as f32) - score.unwrap_or(max_score) as f32) / (max_score as f32)
+    }
+
+    /// From 0 to 1, the share of capturable obelisks the player holds or is closer to claiming.
+    /// Always 0 when obelisks can't be captured.
+    pub fn eval_obelisk_control(
+        &self,
+        self_tiles: &BoardDistances,
+        opponent_tiles: &BoardDistances,
+        for_player: usize,
+    ) -> f32 {
+        if self.rules.obelisks != ObeliskRule::Capturable || self.board.obelisks.is_empty() {
+            return 0.0;
+        }
+
+        let max_score = self.board.width() + self.board.height();
+        let reach = |distances: &BoardDistances, obelisk: Coordinate| {
+            obelisk
+                .neighbors_4_iter()
+                .filter_map(|pt| distances.direct_distance(&pt))
+                .min()
+                .unwrap_or(max_score)
+        };
+
+        let control: f32 = self
+            .board
+            .obelisks
+            .iter()
+            .map(|obelisk| match self.board.get(*obelisk) {
+                Ok(Square::Obelisk {
+                    holder: Some(holder),
+                    ..
+                }) => {
+                    if holder == for_player {
+                        1.0
+                    } else {
+                        0.0
+                    }
+                }
+                _ => {
+                    let lead =
+                        reach(opponent_tiles, *obelisk) as f32 - reach(self_tiles, *obelisk) as f32;
+                    (0.5 + lead / (2 * max_score) as f32).clamp(0.0, 1.0)
+                }
+            })
+            .sum();
+
+        control / self.board.obelisks.len() as f32
+    }
+
+    /// From 0 to 1, the share of opponents' tiles that the player's best placed attack
+    /// next turn would explode, should it win.
+    /// Always 0 under the original explosion rules, which only reach the attacked square's neighbours.
+    pub fn eval_explosion_potential(&self, for_player: usize) -> f32 {
+        if self.rules.explosions == ExplosionRules::default() {
+            return 0.0;
+        }
 
-        let score = defense_towns
+        let opponent_tiles = self
+            .board
+            .squares
             .iter()
-            .map(|town_pt| {
-                let mut can_defend_in = defenders_tiles
-                    .attackable_distance(town_pt)
-                    .unwrap_or(max_score);
-                let mut can_attack_in = attackers_tiles
-                    .attackable_distance(town_pt)
-                    .unwrap_or(max_score);
-
-                // Fudge the numbers so that even races look bad for the defender
-                can_defend_in += 2;
-                can_attack_in = can_attack_in.saturating_sub(2);
-
-                can_defend_in.saturating_sub(can_attack_in)
+            .flatten()
+            .filter(|sq| matches!(sq, Square::Occupied { player, .. } if *player != for_player))
+            .count();
+        if opponent_tiles == 0 {
+            return 0.0;
+        }
+
+        let is_tile = |pos: Coordinate, owned: bool| match self.board.get(pos) {
+            Ok(Square::Occupied { player, .. }) => (player == for_player) == owned,
+            _ => false,
+        };
+
+        // Attacks can be made from any empty square between the player's tiles and an opponent's
+        let best_explosion = self
+            .board
+            .squares
+            .iter()
+            .enumerate()
+            .flat_map(|(y, row)| {
+                row.iter()
+                    .enumerate()
+                    .map(move |(x, sq)| (Coordinate { x, y }, sq))
+            })
+            .filter(|(pos, sq)| {
+                matches!(sq, Square::Land { .. })
+                    && pos.neighbors_4_iter().any(|n| is_tile(n, true))
+                    && pos.neighbors_4_iter().any(|n| is_tile(n, false))
             })
-            .max();
+            .map(|(pos, _)| {
+                self.board
+                    .explosion_area(pos, for_player, &self.rules.explosions)
+                    .len()
+            })
+            .max()
+            .unwrap_or_default();
 
-        ((max_score as f32) - score.unwrap_or(max_score) as f32) / (max_score as f32)
+        best_explosion as f32 / opponent_tiles as f32
     }
 
     pub fn eval_word_quality(
@@ -537,9 +1272,12 @@ impl Game {
         external_dictionary: &WordDict,
         player: usize,
         caches: &mut Caches,
+        npc_params: &NPCParams,
     ) -> WordQualityScores {
         let mut assessed_tiles: HashSet<Coordinate> = HashSet::new();
         let mut num_words = 0;
+        let mut num_valid_words = 0;
+        let mut rarity = 0.0;
 
         let mut word_scores = WordQualityScores::default();
 
@@ -578,6 +1316,14 @@ impl Game {
                                     (word_data.extensions as f32).sqrt().min(100.0) / 100.0;
 
                                 word_scores.word_validity += 1.0;
+                                num_valid_words += 1;
+                                rarity += (1.0 - word_data.rel_freq).clamp(0.0, 1.0);
+
+                                // Only measured for personalities that are put off by these words
+                                if npc_params.objectionable_words != 0.0 && word_data.objectionable
+                                {
+                                    word_scores.word_objectionability += 1.0;
+                                }
                             }
                         }
                     }
@@ -585,6 +1331,11 @@ impl Game {
             }
         }
 
+        // Only weighed by some personalities, and never for boards holding invalid words
+        if npc_params.word_rarity != 0.0 && num_valid_words == num_words {
+            word_scores.word_rarity = rarity;
+        }
+
         if num_words > 0 {
             word_scores / num_words as f32
         } else {
@@ -781,6 +1532,8 @@ mod tests {
                     word_length: 0.4,
                     word_validity: 1.0,
                     word_extensibility: 0.4700235,
+                    word_objectionability: 0.0,
+                    word_rarity: 0.0,
                 },
                 raced_defense: 0.0,
                 raced_attack: 1.0,
@@ -788,6 +1541,8 @@ mod tests {
                 self_attack: 0.0,
                 direct_defence: 1.0,
                 direct_attack: 0.0,
+                obelisk_control: 0.0,
+                explosion_potential: 0.0,
                 self_win: false,
                 opponent_win: false,
             } / B: BoardScore {
@@ -798,6 +1553,8 @@ mod tests {
                     word_length: 0.4,
                     word_validity: 1.0,
                     word_extensibility: 0.5438283,
+                    word_objectionability: 0.0,
+                    word_rarity: 0.0,
                 },
                 raced_defense: 0.0,
                 raced_attack: 1.0,
@@ -805,6 +1562,8 @@ mod tests {
                 self_attack: 0.0,
                 direct_defence: 1.0,
                 direct_attack: 0.0,
+                obelisk_control: 0.0,
+                explosion_potential: 0.0,
                 self_win: false,
                 opponent_win: false,
             }
@@ -961,6 +1720,385 @@ mod tests {
         }
     }
 
+    #[test]
+    fn timed_search_returns_a_playable_move() {
+        let dict = dict();
+        let game = test_game(
+            r###"
+            ~~ ~~ |0 ~~
+            ~~ S0 O0 ~~
+            ~~ T0 A0 Y0
+            ~~ A0 ~~ ~~
+            ~~ R0 __ ~~
+            ~~ __ A1 |1
+            ~~ ~~ |1 ~~
+            ~~ ~~ ~~ ~~
+            "###,
+            "XZF",
+        );
+
+        // Even with no time to think, the shallowest layer is completed
+        for budget in [Duration::ZERO, Duration::from_millis(200)] {
+            let (best_move, _) = Game::best_move_within(
+                &game,
+                Some(&dict),
+                Some(&dict),
+                budget,
+                false,
+                &NPCParams::default(),
+            );
+
+            let PlayerMessage::Place(position, tile) = best_move else {
+                panic!("Expected a placement");
+            };
+            assert_eq!(
+                game.validate_move(&Move::Place {
+                    player: 1,
+                    tile,
+                    position
+                }),
+                Ok(())
+            );
+        }
+    }
+
+    #[test]
+    fn npc_assigns_letters_to_blanks() {
+        let dict = dict();
+        let game = test_game(
+            r###"
+            ~~ ~~ |0 ~~
+            ~~ S0 O0 ~~
+            ~~ T0 A0 Y0
+            ~~ A0 ~~ ~~
+            ~~ R0 __ ~~
+            ~~ __ A1 |1
+            ~~ ~~ |1 ~~
+            ~~ ~~ ~~ ~~
+            "###,
+            "??",
+        );
+
+        let (best_move, _) = Game::best_move(
+            &game,
+            Some(&dict),
+            Some(&dict),
+            2,
+            None,
+            false,
+            &NPCParams::default(),
+        );
+
+        let PlayerMessage::Place(position, tile) = best_move else {
+            panic!("Expected a placement");
+        };
+        assert!(BLANK_LETTERS.contains(tile));
+        assert_eq!(
+            game.validate_move(&Move::Place {
+                player: 1,
+                tile,
+                position
+            }),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn parallel_search_matches_serial_search() {
+        let dict = dict();
+        let game = test_game(
+            r###"
+            ~~ ~~ |0 ~~
+            ~~ S0 O0 ~~
+            ~~ T0 A0 Y0
+            ~~ A0 ~~ ~~
+            ~~ R0 __ ~~
+            ~~ __ A1 |1
+            ~~ ~~ |1 ~~
+            ~~ ~~ ~~ ~~
+            "###,
+            "XYZA",
+        );
+
+        let search = |parallel: bool| {
+            let mut arb = Arborist::pruning();
+            let (best_move, score) = if parallel {
+                Game::best_move_parallel(
+                    &game,
+                    Some(&dict),
+                    Some(&dict),
+                    3,
+                    Some(&mut arb),
+                    false,
+                    &NPCParams::default(),
+                )
+            } else {
+                Game::best_move(
+                    &game,
+                    Some(&dict),
+                    Some(&dict),
+                    3,
+                    Some(&mut arb),
+                    false,
+                    &NPCParams::default(),
+                )
+            };
+            (best_move, score.usize_rank(), arb.assessed())
+        };
+
+        // Equally scored moves may be explored in a different order,
+        // so only the score of the chosen move is guaranteed to match
+        let (_, serial_rank, _) = search(false);
+        let (parallel_move, parallel_rank, parallel_assessed) = search(true);
+        assert_eq!(parallel_rank, serial_rank);
+        let PlayerMessage::Place(position, tile) = parallel_move else {
+            panic!("Expected a placement");
+        };
+        assert_eq!(
+            game.validate_move(&Move::Place {
+                player: 1,
+                tile,
+                position
+            }),
+            Ok(())
+        );
+
+        // The cap applies to the total across all threads, split evenly between the root moves
+        // so that a capped search finds the same move however its threads are scheduled
+        let capped_search = || {
+            let mut capped = Arborist::pruning();
+            capped.capped(50);
+            let (best_move, _) = Game::best_move_parallel(
+                &game,
+                Some(&dict),
+                Some(&dict),
+                3,
+                Some(&mut capped),
+                false,
+                &NPCParams::default(),
+            );
+            (best_move, capped.assessed())
+        };
+        let (capped_move, capped_assessed) = capped_search();
+        assert!(capped_assessed < parallel_assessed);
+        for _ in 0..5 {
+            assert_eq!(capped_search(), (capped_move.clone(), capped_assessed));
+        }
+    }
+
+    #[test]
+    fn persistent_caches_are_bounded_and_reusable() {
+        let dict = dict();
+        let game = test_game(
+            r###"
+            ~~ ~~ |0 ~~
+            ~~ S0 O0 ~~
+            ~~ T0 A0 Y0
+            ~~ A0 ~~ ~~
+            ~~ R0 __ ~~
+            ~~ __ A1 |1
+            ~~ ~~ |1 ~~
+            ~~ ~~ ~~ ~~
+            "###,
+            "XYZA",
+        );
+
+        let search = |game: &Game, caches: &mut Caches| {
+            Game::best_move_cached(
+                game,
+                Some(&dict),
+                Some(&dict),
+                3,
+                None,
+                false,
+                &NPCParams::default(),
+                caches,
+            )
+        };
+
+        let mut caches = Caches::persistent(8, 1000);
+        let (fresh_move, fresh_score) = Game::best_move(
+            &game,
+            Some(&dict),
+            Some(&dict),
+            3,
+            None,
+            false,
+            &NPCParams::default(),
+        );
+        let (first_move, first_score) = search(&game, &mut caches);
+        assert_eq!(first_move, fresh_move);
+        assert_eq!(first_score.usize_rank(), fresh_score.usize_rank());
+        assert!(caches.cached_floods.len() <= 8);
+        assert!(!caches.cached_scores.is_empty());
+
+        // Reused scores only reorder the search, so the result is equally good
+        let (_, repeat_score) = search(&game, &mut caches);
+        assert_eq!(repeat_score.usize_rank(), first_score.usize_rank());
+
+        // Move ordering scores from another position are dropped
+        let mut next_game = game.clone();
+        let PlayerMessage::Place(position, tile) = first_move else {
+            panic!("Expected a placement");
+        };
+        next_game
+            .play_turn(
+                Move::Place {
+                    player: 1,
+                    tile,
+                    position,
+                },
+                Some(&dict),
+                Some(&dict),
+                None,
+            )
+            .unwrap();
+        next_game.next_player = Some(1);
+        caches.prepare_for(&next_game, 1);
+        assert!(caches.cached_scores.is_empty());
+        assert!(caches.cached_floods.len() <= 8);
+    }
+
+    #[test]
+    fn ranked_moves_include_swaps() {
+        let dict = dict();
+        let game = test_game(
+            r###"
+            ~~ ~~ |0 ~~
+            ~~ S0 O0 ~~
+            ~~ T0 A0 Y0
+            ~~ A0 ~~ ~~
+            ~~ R0 __ ~~
+            ~~ __ T1 ~~
+            ~~ __ A1 |1
+            ~~ ~~ |1 ~~
+            "###,
+            "XZF",
+        );
+
+        let ranked = game.ranked_moves(Some(&dict), 2, usize::MAX);
+        assert!(ranked.windows(2).all(|pair| pair[0].1 >= pair[1].1));
+        assert!(ranked
+            .iter()
+            .any(|(msg, _)| matches!(msg, PlayerMessage::Swap(..))));
+        assert!(ranked
+            .iter()
+            .any(|(msg, _)| matches!(msg, PlayerMessage::Place(..))));
+
+        let top = game.ranked_moves(Some(&dict), 2, 3);
+        assert_eq!(top.len(), 3);
+        assert_eq!(top[0].0, ranked[0].0);
+    }
+
+    #[test]
+    fn degraded_npc_plays_reproducible_legal_moves() {
+        let dict = dict();
+        let game = test_game(
+            r###"
+            ~~ ~~ |0 ~~
+            ~~ S0 O0 ~~
+            ~~ T0 A0 Y0
+            ~~ A0 ~~ ~~
+            ~~ R0 __ ~~
+            ~~ __ T1 ~~
+            ~~ __ A1 |1
+            ~~ ~~ |1 ~~
+            "###,
+            "XZF",
+        );
+
+        let play = |difficulty: f32, seed: u64| {
+            let npc_params = NPCParams {
+                difficulty,
+                ..NPCParams::default()
+            };
+            Game::npc_move(
+                &game,
+                Some(&dict),
+                Some(&dict),
+                None,
+                None,
+                &npc_params,
+                seed,
+            )
+        };
+
+        let (best_move, _) = Game::best_move(
+            &game,
+            Some(&dict),
+            Some(&dict),
+            1,
+            None,
+            false,
+            &NPCParams::default(),
+        );
+        assert_eq!(play(1.0, 0), best_move);
+
+        for seed in 0..5 {
+            let easy_move = play(0.0, seed);
+            assert_eq!(play(0.0, seed), easy_move);
+
+            let next_move = match easy_move {
+                PlayerMessage::Place(position, tile) => Move::Place {
+                    player: 1,
+                    tile,
+                    position,
+                },
+                PlayerMessage::Swap(from, to) => Move::Swap {
+                    player: 1,
+                    positions: [from, to],
+                },
+                _ => panic!("Expected a place or swap"),
+            };
+            assert_eq!(game.validate_move(&next_move), Ok(()));
+        }
+    }
+
+    #[test]
+    fn word_rarity_is_only_scored_when_weighted_and_every_word_is_valid() {
+        let dict = dict();
+        let rarity_params = NPCParams {
+            word_rarity: 1.0,
+            ..NPCParams::default()
+        };
+
+        let valid_game = test_game(
+            r###"
+            ~~ ~~ |0 ~~ ~~
+            __ S0 O0 __ __
+            __ __ __ __ __
+            __ __ T1 __ __
+            __ __ A1 __ __
+            __ __ R1 __ __
+            ~~ ~~ |1 ~~ ~~
+            "###,
+            "A",
+        );
+        let unweighted =
+            valid_game.eval_word_quality(&dict, 1, &mut Caches::new(), &NPCParams::default());
+        let weighted = valid_game.eval_word_quality(&dict, 1, &mut Caches::new(), &rarity_params);
+        assert_eq!(unweighted.word_rarity, 0.0);
+        assert!(weighted.word_rarity > 0.0);
+        assert_eq!(weighted.word_validity, unweighted.word_validity);
+
+        let partly_invalid_game = test_game(
+            r###"
+            ~~ ~~ |0 ~~ ~~
+            __ S0 O0 __ __
+            __ __ __ __ __
+            __ T1 __ Q1 __
+            __ A1 __ X1 __
+            __ R1 __ Z1 __
+            ~~ ~~ |1 ~~ ~~
+            "###,
+            "A",
+        );
+        let weighted =
+            partly_invalid_game.eval_word_quality(&dict, 1, &mut Caches::new(), &rarity_params);
+        assert_eq!(weighted.word_validity, 0.5);
+        assert_eq!(weighted.word_rarity, 0.0);
+    }
+
     #[test]
     fn generic_npc_tests() {
         let dict = dict();
@@ -0,0 +1,186 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use xxhash_rust::xxh3;
+
+use crate::{game::Game, generation::BoardSeed, messages::PlayerMessage};
+
+/// How many turns from the start of a game are recorded in opening books
+pub const OPENING_BOOK_TURNS: u32 = 6;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OpeningBookEntry {
+    pub seed: u32,
+    pub board_generation: u32,
+    pub turn: u32,
+    /// Identifies the board and hand the move was found for,
+    /// since the same turn can be reached from different positions.
+    pub position: u64,
+    pub best_move: PlayerMessage,
+}
+
+impl OpeningBookEntry {
+    fn key(&self) -> (u32, u32, u32, u64) {
+        (self.seed, self.board_generation, self.turn, self.position)
+    }
+}
+
+/// Precomputed moves for the start of games on generated boards
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct OpeningBook {
+    entries: Vec<OpeningBookEntry>,
+}
+
+impl OpeningBook {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn entries(&self) -> &[OpeningBookEntry] {
+        &self.entries
+    }
+
+    pub fn position_key(game: &Game) -> u64 {
+        let next_player = game
+            .next_player
+            .expect("Opening books only work in non-periodic playmodes");
+        let position = format!("{}\n{}", game.board, game.players[next_player].hand);
+        xxh3::xxh3_64(position.as_bytes())
+    }
+
+    /// Records the move for the game's next player, if the game is still within its opening.
+    /// Returns whether the move was recorded.
+    pub fn record(&mut self, seed: &BoardSeed, game: &Game, best_move: PlayerMessage) -> bool {
+        if game.turn_count >= OPENING_BOOK_TURNS {
+            return false;
+        }
+
+        let entry = OpeningBookEntry {
+            seed: seed.seed,
+            board_generation: seed.generation,
+            turn: game.turn_count,
+            position: Self::position_key(game),
+            best_move,
+        };
+
+        match self.entries.iter_mut().find(|e| e.key() == entry.key()) {
+            Some(existing) => *existing = entry,
+            None => self.entries.push(entry),
+        }
+
+        true
+    }
+
+    /// Adds the entries from `other`, replacing any of ours recorded for the same position
+    pub fn extend(&mut self, other: OpeningBook) {
+        let mut index: HashMap<_, _> = self
+            .entries
+            .iter()
+            .enumerate()
+            .map(|(i, e)| (e.key(), i))
+            .collect();
+
+        for entry in other.entries {
+            match index.get(&entry.key()) {
+                Some(&i) => self.entries[i] = entry,
+                None => {
+                    index.insert(entry.key(), self.entries.len());
+                    self.entries.push(entry);
+                }
+            }
+        }
+    }
+
+    pub fn lookup(&self, seed: &BoardSeed, game: &Game) -> Option<&PlayerMessage> {
+        if game.turn_count >= OPENING_BOOK_TURNS {
+            return None;
+        }
+
+        let key = (
+            seed.seed,
+            seed.generation,
+            game.turn_count,
+            Self::position_key(game),
+        );
+        self.entries
+            .iter()
+            .find(|e| e.key() == key)
+            .map(|e| &e.best_move)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::GameRules;
+
+    fn opening_game() -> Game {
+        let mut game = Game::new(9, 9, Some(3), GameRules::generation(1));
+        game.add_player("A".into());
+        game.add_player("B".into());
+        game.start();
+        game
+    }
+
+    #[test]
+    fn book_only_matches_its_position() {
+        let seed = BoardSeed::new(12);
+        let game = opening_game();
+        let book_move = PlayerMessage::Place(
+            *game
+                .board
+                .playable_positions(0, &game.rules.truncation)
+                .iter()
+                .min()
+                .unwrap(),
+            game.players[0].hand.0[0],
+        );
+
+        let mut book = OpeningBook::new();
+        assert!(book.record(&seed, &game, book_move.clone()));
+        assert_eq!(book.lookup(&seed, &game), Some(&book_move));
+        assert_eq!(book.lookup(&BoardSeed::new(13), &game), None);
+
+        let mut other_hand = game.clone();
+        other_hand.players[0].hand.0.push('Q');
+        assert_eq!(book.lookup(&seed, &other_hand), None);
+
+        let mut late_game = game.clone();
+        late_game.turn_count = OPENING_BOOK_TURNS;
+        assert!(!book.record(&seed, &late_game, book_move.clone()));
+        assert_eq!(book.lookup(&seed, &late_game), None);
+
+        let restored: OpeningBook =
+            serde_json::from_str(&serde_json::to_string(&book).unwrap()).unwrap();
+        assert_eq!(restored, book);
+    }
+
+    #[test]
+    fn extending_replaces_matching_positions() {
+        let seed = BoardSeed::new(12);
+        let game = opening_game();
+        let mut next_turn = game.clone();
+        next_turn.turn_count += 1;
+
+        let mut book = OpeningBook::new();
+        book.record(&seed, &game, PlayerMessage::Pass);
+        book.record(&seed, &next_turn, PlayerMessage::Pass);
+
+        let mut other = OpeningBook::new();
+        other.record(&seed, &game, PlayerMessage::Exchange('A'));
+        other.record(&BoardSeed::new(13), &game, PlayerMessage::Pass);
+
+        book.extend(other);
+        assert_eq!(book.entries().len(), 3);
+        assert_eq!(
+            book.lookup(&seed, &game),
+            Some(&PlayerMessage::Exchange('A'))
+        );
+        assert_eq!(book.lookup(&seed, &next_turn), Some(&PlayerMessage::Pass));
+        assert_eq!(
+            book.lookup(&BoardSeed::new(13), &game),
+            Some(&PlayerMessage::Pass)
+        );
+    }
+}
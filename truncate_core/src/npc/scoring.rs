@@ -28,6 +28,37 @@ pub struct NPCParams {
     pub word_validity: f32,
     pub word_length: f32,
     pub word_extensibility: f32,
+    /// Penalty for spelling objectionable words, applied against the word quality.
+    /// Zero unless a personality opts in, with a penalty that outweighs `word_validity`
+    /// so that they're only played to win or defend.
+    #[serde(default)]
+    pub objectionable_words: f32,
+    /// From 0 to 1, how reliably the NPC plays its best move rather than a weaker one
+    #[serde(default = "full_difficulty")]
+    pub difficulty: f32,
+    /// Weight for holding, or being first to reach, capturable obelisks
+    #[serde(default = "obelisk_weight")]
+    pub obelisk_control: f32,
+    /// Weight for threatening attacks whose explosions would take out many opponent tiles.
+    /// Only scored under explosion rules that reach past the attacked square's neighbours.
+    #[serde(default = "explosion_weight")]
+    pub explosion_potential: f32,
+    /// Weight for spelling rarer words, giving a personality a taste for interesting vocabulary.
+    /// Zero for the standard personalities, which don't score rarity at all.
+    #[serde(default)]
+    pub word_rarity: f32,
+}
+
+fn full_difficulty() -> f32 {
+    1.0
+}
+
+fn obelisk_weight() -> f32 {
+    NPCParams::default().obelisk_control
+}
+
+fn explosion_weight() -> f32 {
+    NPCParams::default().explosion_potential
 }
 
 #[derive(Clone)]
@@ -56,6 +87,11 @@ impl Default for NPCParams {
             word_validity: 3.0,
             word_length: 1.0,
             word_extensibility: 1.0,
+            objectionable_words: 0.0,
+            difficulty: 1.0,
+            obelisk_control: 2.0,
+            explosion_potential: 1.0,
+            word_rarity: 0.0,
         }
     }
 }
@@ -95,17 +131,32 @@ impl NPCPersonality {
         }
     }
 
+    pub fn amber() -> Self {
+        Self {
+            name: "amber".to_string(),
+            params: NPCParams {
+                evaluation_cap: 5000,
+                max_depth: 1,
+                vocab: NPCVocab::Small,
+                difficulty: 0.6,
+                objectionable_words: 4.0,
+                ..NPCParams::default()
+            },
+        }
+    }
+
     pub fn from_id(id: impl AsRef<str>) -> Option<Self> {
         match id.as_ref() {
             "opal" => Some(Self::opal()),
             "jet" => Some(Self::jet()),
             "mellite" => Some(Self::mellite()),
+            "amber" => Some(Self::amber()),
             _ => None,
         }
     }
 }
 
-#[derive(Clone, Default, PartialEq)]
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct BoardScore {
     infinity: bool,
     neg_infinity: bool,
@@ -117,6 +168,8 @@ pub struct BoardScore {
     self_attack: f32,
     direct_defence: f32,
     direct_attack: f32,
+    obelisk_control: f32,
+    explosion_potential: f32,
     self_win: bool,
     opponent_win: bool,
     npc_params: NPCParams,
@@ -136,6 +189,8 @@ impl Debug for BoardScore {
             .field("self_attack", &self.self_attack)
             .field("direct_defence", &self.direct_defence)
             .field("direct_attack", &self.direct_attack)
+            .field("obelisk_control", &self.obelisk_control)
+            .field("explosion_potential", &self.explosion_potential)
             .field("self_win", &self.self_win)
             .field("opponent_win", &self.opponent_win)
             .finish()
@@ -193,6 +248,16 @@ impl BoardScore {
         self
     }
 
+    pub fn obelisk_control(mut self, value: f32) -> Self {
+        self.obelisk_control = value;
+        self
+    }
+
+    pub fn explosion_potential(mut self, value: f32) -> Self {
+        self.explosion_potential = value;
+        self
+    }
+
     pub fn self_win(mut self, value: bool) -> Self {
         self.self_win = value;
         self
@@ -228,9 +293,59 @@ impl BoardScore {
             + self.self_attack * self.npc_params.self_attack
             + self.direct_defence * self.npc_params.direct_defence
             + self.direct_attack * self.npc_params.direct_attack
+            + self.obelisk_control * self.npc_params.obelisk_control
+            + self.explosion_potential * self.npc_params.explosion_potential
             + self.word_quality.word_validity * self.npc_params.word_validity
             + self.word_quality.word_length * self.npc_params.word_length
             + self.word_quality.word_extensibility * self.npc_params.word_extensibility
+            + self.word_quality.word_rarity * self.npc_params.word_rarity
+            - self.word_quality.word_objectionability * self.npc_params.objectionable_words
+    }
+
+    /// Whether this score ends with the opponent winning
+    pub fn is_loss(&self) -> bool {
+        self.neg_infinity || (self.opponent_win && !self.self_win)
+    }
+
+    /// Whether this score ends with the evaluated player winning
+    pub fn is_win(&self) -> bool {
+        self.infinity || (self.self_win && !self.opponent_win)
+    }
+
+    /// Each component's weighted contribution to `rank`, for explaining an evaluation
+    pub fn breakdown(&self) -> Vec<(&'static str, f32)> {
+        let params = &self.npc_params;
+        let words = &self.word_quality;
+        vec![
+            ("word_validity", words.word_validity * params.word_validity),
+            ("word_length", words.word_length * params.word_length),
+            (
+                "word_extensibility",
+                words.word_extensibility * params.word_extensibility,
+            ),
+            ("word_rarity", words.word_rarity * params.word_rarity),
+            (
+                "word_objectionability",
+                -words.word_objectionability * params.objectionable_words,
+            ),
+            ("raced_defense", self.raced_defense * params.raced_defense),
+            ("raced_attack", self.raced_attack * params.raced_attack),
+            ("self_defense", self.self_defense * params.self_defense),
+            ("self_attack", self.self_attack * params.self_attack),
+            (
+                "direct_defence",
+                self.direct_defence * params.direct_defence,
+            ),
+            ("direct_attack", self.direct_attack * params.direct_attack),
+            (
+                "obelisk_control",
+                self.obelisk_control * params.obelisk_control,
+            ),
+            (
+                "explosion_potential",
+                self.explosion_potential * params.explosion_potential,
+            ),
+        ]
     }
 
     pub fn usize_rank(&self) -> usize {
@@ -295,14 +410,41 @@ mod tests {
             word_length: 0.0,
             word_validity: 0.6,
             word_extensibility: 0.0,
+            word_objectionability: 0.0,
+            word_rarity: 0.0,
         });
         let b = BoardScore::default().word_quality(WordQualityScores {
             word_length: 0.0,
             word_validity: 0.5,
             word_extensibility: 0.0,
+            word_objectionability: 0.0,
+            word_rarity: 0.0,
         });
 
         assert!(a > b);
+
+        let clean = WordQualityScores {
+            word_length: 0.0,
+            word_validity: 0.0,
+            word_extensibility: 0.0,
+            word_objectionability: 0.0,
+            word_rarity: 0.0,
+        };
+        let objectionable = WordQualityScores {
+            word_length: 0.0,
+            word_validity: 1.0,
+            word_extensibility: 0.0,
+            word_objectionability: 1.0,
+            word_rarity: 0.0,
+        };
+
+        // Only personalities that opt in are put off objectionable words
+        assert!(
+            BoardScore::default().word_quality(clean.clone())
+                < BoardScore::default().word_quality(objectionable.clone())
+        );
+        let wary = BoardScore::default().npc_params(NPCPersonality::amber().params);
+        assert!(wary.clone().word_quality(clean) > wary.word_quality(objectionable));
     }
 
     #[test]
@@ -331,4 +473,27 @@ mod tests {
         assert!(late_loss > early_loss);
         assert!(late_better_loss > late_loss);
     }
+
+    #[test]
+    fn breakdown_explains_rank() {
+        let score = BoardScore::default()
+            .npc_params(NPCParams::default())
+            .word_quality(WordQualityScores {
+                word_length: 0.4,
+                word_validity: 0.8,
+                word_extensibility: 0.2,
+                word_objectionability: 0.1,
+                word_rarity: 0.3,
+            })
+            .raced_defense(0.5)
+            .self_attack(0.25)
+            .direct_attack(1.0);
+
+        let total: f32 = score.breakdown().iter().map(|(_, value)| value).sum();
+        assert!((total - score.rank()).abs() < 0.0001);
+
+        let transported: BoardScore =
+            serde_json::from_str(&serde_json::to_string(&score).unwrap()).unwrap();
+        assert_eq!(transported, score);
+    }
 }
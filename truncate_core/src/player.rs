@@ -76,6 +76,9 @@ pub struct Player {
     pub turn_starts_no_sooner_than: Option<u64>,
     pub paused_turn_delta: Option<i64>,
     pub swap_count: usize,
+    /// Every swap made this game, for rules that limit swaps per game
+    #[serde(default)]
+    pub swaps_used: usize,
     pub penalties_incurred: usize,
     pub color: (u8, u8, u8),
     /// Tracked when in a fog of war game,
@@ -103,6 +106,7 @@ impl Player {
             turn_starts_no_sooner_than: None,
             paused_turn_delta: None,
             swap_count: 0,
+            swaps_used: 0,
             penalties_incurred: 0,
             color,
             seen_tiles: HashSet::new(),
@@ -140,6 +144,31 @@ impl Player {
         }
     }
 
+    /// Swaps `tile` in the hand for a new tile from the bag.
+    /// The replacement is drawn before `tile` goes back, so it can't be drawn straight back out.
+    pub fn exchange_tile(
+        &mut self,
+        tile: char,
+        bag: &mut TileBag,
+    ) -> Result<Change, GamePlayError> {
+        let Some(index) = self.hand.iter().position(|t| t == &tile) else {
+            return Err(GamePlayError::PlayerDoesNotHaveTile {
+                player: self.index,
+                tile,
+            });
+        };
+
+        let replacement = bag.draw_tile();
+        self.hand.replace(index, replacement);
+        bag.return_tile(tile);
+
+        Ok(Change::Hand(HandChange {
+            player: self.index,
+            removed: vec![tile],
+            added: vec![replacement],
+        }))
+    }
+
     pub fn add_special_tile(&mut self, tile: char) -> Change {
         self.hand.add(tile);
         Change::Hand(HandChange {
@@ -179,6 +208,66 @@ mod tests {
         // }
     }
 
+    #[test]
+    fn hand_capacity_from_rules() {
+        for capacity in [5, 9] {
+            let mut rules = crate::rules::GameRules::generation(1);
+            rules.hand_capacity = capacity;
+            let mut game = crate::game::Game::new(9, 9, Some(1), rules);
+            game.add_player("A".into());
+            game.add_player("B".into());
+
+            for player in &game.players {
+                assert_eq!(player.hand.len(), capacity);
+                assert_eq!(player.hand_capacity, capacity);
+            }
+        }
+
+        // Players with no capacity are never given new tiles
+        let mut bag = TileBag::latest(Some(1)).1;
+        let mut player = Player::new("A".into(), 0, 0, &mut bag, None, (0, 0, 0));
+        player.hand = Hand(vec!['A', 'B']);
+        player.use_tile('A', &mut bag).unwrap();
+        assert_eq!(player.hand, Hand(vec!['B']));
+    }
+
+    #[test]
+    fn lobby_rules_redeal_hands() {
+        use crate::rules::{GameRules, Timing, MAX_HAND_CAPACITY};
+
+        let mut game = crate::game::Game::new(9, 9, Some(1), GameRules::generation(1));
+        game.add_player("A".into());
+        game.add_player("B".into());
+
+        let mut requested = GameRules::generation(1);
+        requested.hand_capacity = 9;
+        requested.timing = Timing::Fischer {
+            base_time: 300,
+            increment: 5,
+        };
+        requested.blank_tiles = 3;
+        requested.challenge_mode = true;
+        requested.language = Some("fr".into());
+        let rules = game.rules.with_lobby_settings(&requested).unwrap();
+        // Only the lobby settings are taken from the request
+        assert_eq!(rules.blank_tiles, 0);
+        assert!(rules.challenge_mode);
+        assert_eq!(rules.language.as_deref(), Some("fr"));
+
+        game.change_rules(rules);
+        for player in &game.players {
+            assert_eq!(player.hand.len(), 9);
+            assert_eq!(player.hand_capacity, 9);
+            assert_eq!(player.time_remaining, Some(Duration::new(300, 0)));
+        }
+
+        requested.hand_capacity = MAX_HAND_CAPACITY + 1;
+        assert!(game.rules.with_lobby_settings(&requested).is_none());
+        requested.hand_capacity = 7;
+        requested.timing = Timing::PerTurn { time_allowance: 30 };
+        assert!(game.rules.with_lobby_settings(&requested).is_none());
+    }
+
     // TODO(liam): Redo / re-enable tests
     // #[test]
     // fn get_works() -> Result<(), GamePlayError> {
@@ -3,7 +3,7 @@ use std::{collections::HashSet, fmt};
 
 use crate::{
     board::{Board, Coordinate, Square},
-    judge::Outcome,
+    judge::{Outcome, WordData},
     rules,
 };
 
@@ -15,6 +15,8 @@ pub enum BoardChangeAction {
     Defeated,
     Truncated,
     Exploded,
+    Captured,
+    Restored,
 }
 
 impl fmt::Display for BoardChangeAction {
@@ -26,6 +28,8 @@ impl fmt::Display for BoardChangeAction {
             BoardChangeAction::Defeated => write!(f, "Defeated"),
             BoardChangeAction::Truncated => write!(f, "Truncated"),
             BoardChangeAction::Exploded => write!(f, "Exploded"),
+            BoardChangeAction::Captured => write!(f, "Captured"),
+            BoardChangeAction::Restored => write!(f, "Restored"),
         }
     }
 }
@@ -77,6 +81,25 @@ pub struct WordMeaning {
     pub defs: Vec<String>,
 }
 
+/// Words with a relative frequency below this are flagged as advanced vocabulary
+pub const ADVANCED_WORD_FREQ: f32 = 0.90;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct WordDefinition {
+    pub word: String,
+    pub meanings: Option<Vec<WordMeaning>>,
+    /// Frequency data from the dictionary, if the word is in it
+    pub data: Option<WordData>,
+}
+
+impl WordDefinition {
+    pub fn is_advanced(&self) -> bool {
+        self.data
+            .as_ref()
+            .is_some_and(|data| data.rel_freq < ADVANCED_WORD_FREQ)
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct BattleWord {
     pub original_word: String,
@@ -100,12 +123,111 @@ impl fmt::Display for BattleWord {
     }
 }
 
+impl BattleWord {
+    fn describe(&self) -> String {
+        if self.original_word.contains('#') {
+            return "a town".to_string();
+        }
+        if self.original_word.contains('|') {
+            return "an artifact".to_string();
+        }
+
+        format!(
+            "{} ({}, length {})",
+            self.resolved_word,
+            match self.valid {
+                Some(true) => "valid",
+                Some(false) => "invalid",
+                None => "unchecked",
+            },
+            self.resolved_word.chars().count()
+        )
+    }
+}
+
+/// Which rule decided the outcome of a battle
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BattleReason {
+    /// None of the attacking words were valid
+    InvalidAttackers,
+    /// Defending words lost by being invalid
+    InvalidDefenders,
+    /// Every word that mattered was valid, so the battle came down to length
+    Length,
+    /// An attacking word contained an explosive tile
+    Explosion,
+    /// A challenge found every challenged word to be valid
+    ValidDefenders,
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct BattleReport {
     pub battle_number: Option<u32>,
     pub attackers: Vec<BattleWord>,
     pub defenders: Vec<BattleWord>,
     pub outcome: Outcome,
+    /// Missing for reports that weren't produced by a battle, such as dictionary lookups
+    #[serde(default)]
+    pub reason: Option<BattleReason>,
+}
+
+/// The expected result of placing a tile, from `Game::preview_placement`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BattlePreview {
+    /// The words the placed tile would form
+    pub attackers: Vec<BattleWord>,
+    /// The visible opposing words they would battle
+    pub defenders: Vec<BattleWord>,
+    /// Missing if the placement wouldn't start a battle
+    pub outcome: Option<Outcome>,
+    pub reason: Option<BattleReason>,
+}
+
+impl BattleReport {
+    /// Summarizes the battle in a sentence, e.g.
+    /// "QUARTZ (valid, length 6) defeated CAT (valid, length 3) on length."
+    pub fn describe(&self) -> String {
+        let list = |words: &mut dyn Iterator<Item = &BattleWord>| {
+            let words: Vec<_> = words.map(|w| w.describe()).collect();
+            match words.as_slice() {
+                [] => "nothing".to_string(),
+                [word] => word.clone(),
+                [rest @ .., last] => format!("{} and {last}", rest.join(", ")),
+            }
+        };
+
+        let attackers = if self.attackers.is_empty() {
+            "The challenge".to_string()
+        } else {
+            list(&mut self.attackers.iter())
+        };
+        let reason = match self.reason {
+            Some(BattleReason::InvalidAttackers) => " as the attack was invalid",
+            Some(BattleReason::InvalidDefenders) => " by invalidity",
+            Some(BattleReason::Length) => " on length",
+            Some(BattleReason::Explosion) => " with an explosion",
+            Some(BattleReason::ValidDefenders) => " as every word was valid",
+            None => "",
+        };
+
+        match &self.outcome {
+            Outcome::AttackerWins(losers) if losers.is_empty() => {
+                format!("{attackers} won{reason}.")
+            }
+            Outcome::AttackerWins(losers) => format!(
+                "{attackers} defeated {}{reason}.",
+                list(&mut losers.iter().filter_map(|i| self.defenders.get(*i)))
+            ),
+            Outcome::DefenderWins if self.attackers.is_empty() => format!(
+                "{} survived the challenge{reason}.",
+                list(&mut self.defenders.iter())
+            ),
+            Outcome::DefenderWins => format!(
+                "{} held off {attackers}{reason}.",
+                list(&mut self.defenders.iter())
+            ),
+        }
+    }
 }
 
 impl fmt::Display for BattleReport {
@@ -169,6 +291,7 @@ pub(crate) fn filter_to_player(
     visible_board: &Board,
     player_index: usize,
     visibility: &rules::Visibility,
+    fog_level: &rules::FogLevel,
     winner: &Option<usize>,
     seen_tiles: &HashSet<Coordinate>,
 ) -> Vec<Change> {
@@ -194,6 +317,7 @@ pub(crate) fn filter_to_player(
                     player_index,
                     *coordinate,
                     visibility,
+                    fog_level,
                     seen_tiles,
                 ) else {
                     return None;
@@ -224,6 +348,7 @@ pub(crate) fn filter_to_player(
                     | rules::Visibility::LandFog
                     | rules::Visibility::OnlyHouseFog => match visible_board.get(relative_coord) {
                         Ok(Square::Occupied { .. }) => Some(relative_change),
+                        Ok(Square::Obelisk { foggy: false, .. }) => Some(relative_change),
                         _ => None,
                     },
                 }
@@ -3,43 +3,81 @@
 use serde::{Deserialize, Serialize};
 
 use crate::{
+    bag::TileBag,
     board::Board,
     generation::{
         ArtifactType, BoardElements, BoardNoiseParams, BoardParams, BoardSeed, Symmetry, WaterLayer,
     },
 };
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum TownDefense {
     BeatenByContact,
     BeatenByValidity,
     BeatenWithDefenseStrength(usize),
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum ArtifactDefense {
     Invincible,
     BeatenWithDefenseStrength(usize),
 }
 
+/// How many of a player's towns must fall before they are defeated
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum TownsToDefeat {
+    #[default]
+    Any,
+    All,
+}
+
+/// Where a player may place while they have no tiles on the board
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FirstPlacement {
+    /// Beside their own artifact, their dock, like any placement that isn't touching their tiles
+    #[default]
+    BesideDock,
+    /// On any land closer to one of their own artifacts than to an opponent's.
+    /// Tiles that don't connect to a dock are lost under `Truncation::Root`,
+    /// so this is meant to be played with `Truncation::None`.
+    OwnLand,
+}
+
+/// Whether a player can win back their defeated towns.
+/// Since losing any town ends the game under `TownsToDefeat::Any`,
+/// recovery only comes into play when every town must fall.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TownRecovery {
+    /// Defeated towns stay defeated for the rest of the game
+    #[default]
+    Permanent,
+    /// A defeated town is restored when its owner places a tile beside it
+    Adjacent,
+    /// A defeated town is restored once its owner has taken this many more turns
+    AfterTurns(u32),
+}
+
 /// Conditions which, when hit, end the game and mark a winner
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum WinCondition {
     Destination {
         town_defense: TownDefense,
         artifact_defense: ArtifactDefense,
+        #[serde(default)]
+        towns_to_defeat: TownsToDefeat,
     },
-    Elimination, // TODO: Implement
+    /// Players are defeated once they have played and have no tiles left on the board
+    Elimination,
 }
 
 /// Metrics to used to assign a winner when no condition was hit
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum WinMetric {
     TownProximity,
     ObeliskProximity,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Visibility {
     Standard,
     TileFog,
@@ -47,14 +85,39 @@ pub enum Visibility {
     OnlyHouseFog,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// How much of the board stays visible in the fogged visibility modes
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FogLevel {
+    /// Pieces see further the longer their words are
+    #[default]
+    PieceVision,
+    /// Nothing is hidden, even in fogged modes
+    Full,
+    /// Everything further than this many tiles from your pieces is fogged
+    BeyondDistance(usize),
+    /// Every player's home is visible, and only the arena between them is fogged
+    BattleArena,
+}
+
+/// What, if anything, holding an obelisk does for a player
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ObeliskRule {
+    /// Obelisks are scenery, only used to settle `WinMetric::ObeliskProximity`
+    #[default]
+    Inert,
+    /// The only player with tiles beside an obelisk holds it,
+    /// and draws an extra tile each time they capture it
+    Capturable,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Truncation {
     Root,
     Larger, // TODO: Implement
     None,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum OvertimeRule {
     FreeWildcard { period: usize },
     Bomb { period: usize },
@@ -62,12 +125,18 @@ pub enum OvertimeRule {
     Elimination,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Timing {
     PerPlayer {
         time_allowance: usize,
         overtime_rule: OvertimeRule,
     },
+    /// A Fischer clock: each player starts with `base_time` seconds,
+    /// and is credited `increment` seconds after every move they successfully make
+    Fischer {
+        base_time: usize,
+        increment: usize,
+    },
     PerTurn {
         // TODO: Implement
         time_allowance: usize,
@@ -79,25 +148,57 @@ pub enum Timing {
     None,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum TileBagBehaviour {
     Standard,
     Infinite, // TODO: Implement
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct BattleRules {
     pub length_delta: isize,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// How a winning attack blasts apart opponents' tiles around the tile that was played
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExplosionRules {
+    /// Opponents' tiles within this many steps of the played tile are exploded
+    pub radius: usize,
+    /// Opponents' tiles touching an exploded tile are exploded in turn,
+    /// clearing out every cluster the blast reaches
+    pub chain_reaction: bool,
+}
+
+impl Default for ExplosionRules {
+    fn default() -> Self {
+        Self {
+            radius: 1,
+            chain_reaction: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Swapping {
     Contiguous(SwapPenalty),
     Universal(SwapPenalty),
+    /// Tiles can only be swapped with others in the same row or column of a word
+    WithinWord(SwapPenalty),
     None,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl Swapping {
+    pub fn penalty(&self) -> Option<&SwapPenalty> {
+        match self {
+            Swapping::Contiguous(penalty)
+            | Swapping::Universal(penalty)
+            | Swapping::WithinWord(penalty) => Some(penalty),
+            Swapping::None => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum SwapPenalty {
     Time {
         swap_threshold: usize,
@@ -106,9 +207,13 @@ pub enum SwapPenalty {
     Disallowed {
         allowed_swaps: usize,
     },
+    /// Each player has a fixed number of swaps to spend across the whole game
+    Limited {
+        swaps_per_game: usize,
+    },
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum BoardGenesis {
     Passthrough,
     SpecificBoard(Board),
@@ -116,24 +221,68 @@ pub enum BoardGenesis {
     Random(BoardParams),
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct GameRules {
     pub generation: Option<u32>,
     pub win_condition: WinCondition,
     pub win_metric: WinMetric,
     pub visibility: Visibility,
+    /// Existing games and daily seeds keep the piece vision they were played with
+    #[serde(default)]
+    pub fog_level: FogLevel,
+    /// Boards predating capturable obelisks leave them inert
+    #[serde(default)]
+    pub obelisks: ObeliskRule,
+    /// Boards predating town recovery lose their towns for good
+    #[serde(default)]
+    pub town_recovery: TownRecovery,
+    /// Boards predating free first placements start beside the dock
+    #[serde(default)]
+    pub first_placement: FirstPlacement,
     pub truncation: Truncation,
     pub timing: Timing,
-    pub hand_size: usize,
+    /// The number of tiles each player's hand is refilled to.
+    /// A capacity of zero means hands are never refilled.
+    #[serde(alias = "hand_size")]
+    pub hand_capacity: usize,
     pub tile_generation: u32,
     pub tile_bag_behaviour: TileBagBehaviour,
+    /// Blank tiles added to the bag, which are assigned a letter as they are placed
+    #[serde(default)]
+    pub blank_tiles: usize,
+    /// Defending words are assumed valid in battles,
+    /// and are only judged when an opponent challenges them
+    #[serde(default)]
+    pub challenge_mode: bool,
+    /// Players may pass their turn without placing or swapping
+    #[serde(default)]
+    pub allow_passing: bool,
+    /// Players may spend their turn trading a tile from their hand for a new one from the bag.
+    /// Boards predating exchanges never allow them.
+    #[serde(default)]
+    pub allow_exchanges: bool,
     pub battle_rules: BattleRules,
+    /// Boards predating explosion rules only blast the squares beside the played tile
+    #[serde(default)]
+    pub explosions: ExplosionRules,
     pub swapping: Swapping,
     pub battle_delay: u64,
     pub max_turns: Option<u64>,
+    /// Ends the game as a draw once this many turns pass without any player getting
+    /// closer to an enemy town or any town changing hands.
+    /// Boards predating stalemates play on until they run out of time or turns.
+    #[serde(default)]
+    pub stalemate_turns: Option<u32>,
+    /// The language tag of the dictionary words are judged against,
+    /// or `None` for the default English dictionary
+    #[serde(default)]
+    pub language: Option<String>,
     pub board_genesis: BoardGenesis,
 }
 
+/// The largest hand a lobby host can choose
+pub const MAX_HAND_CAPACITY: usize = 12;
+
 #[derive(Debug, Clone, Copy)]
 struct EffectiveRuleDay(u32);
 
@@ -145,18 +294,33 @@ const RULE_GENERATIONS: [(Option<EffectiveRuleDay>, GameRules); 3] = [
             win_condition: WinCondition::Destination {
                 town_defense: TownDefense::BeatenWithDefenseStrength(0),
                 artifact_defense: ArtifactDefense::Invincible,
+                towns_to_defeat: TownsToDefeat::Any,
             },
             win_metric: WinMetric::TownProximity,
             visibility: Visibility::Standard,
+            fog_level: FogLevel::PieceVision,
+            obelisks: ObeliskRule::Inert,
+            town_recovery: TownRecovery::Permanent,
+            first_placement: FirstPlacement::BesideDock,
             truncation: Truncation::Root,
             timing: Timing::None,
-            hand_size: 7,
+            hand_capacity: 7,
             tile_generation: 0,
             tile_bag_behaviour: TileBagBehaviour::Standard,
+            blank_tiles: 0,
+            challenge_mode: false,
+            allow_passing: false,
+            allow_exchanges: false,
             battle_rules: BattleRules { length_delta: 2 },
+            explosions: ExplosionRules {
+                radius: 1,
+                chain_reaction: false,
+            },
             swapping: Swapping::Contiguous(SwapPenalty::Disallowed { allowed_swaps: 1 }),
             battle_delay: 2,
             max_turns: None,
+            stalemate_turns: None,
+            language: None,
             board_genesis: BoardGenesis::Passthrough,
         },
     ),
@@ -167,18 +331,33 @@ const RULE_GENERATIONS: [(Option<EffectiveRuleDay>, GameRules); 3] = [
             win_condition: WinCondition::Destination {
                 town_defense: TownDefense::BeatenWithDefenseStrength(0),
                 artifact_defense: ArtifactDefense::Invincible,
+                towns_to_defeat: TownsToDefeat::Any,
             },
             win_metric: WinMetric::TownProximity,
             visibility: Visibility::Standard,
+            fog_level: FogLevel::PieceVision,
+            obelisks: ObeliskRule::Inert,
+            town_recovery: TownRecovery::Permanent,
+            first_placement: FirstPlacement::BesideDock,
             truncation: Truncation::Root,
             timing: Timing::None,
-            hand_size: 7,
+            hand_capacity: 7,
             tile_generation: 1,
             tile_bag_behaviour: TileBagBehaviour::Standard,
+            blank_tiles: 0,
+            challenge_mode: false,
+            allow_passing: false,
+            allow_exchanges: false,
             battle_rules: BattleRules { length_delta: 2 },
+            explosions: ExplosionRules {
+                radius: 1,
+                chain_reaction: false,
+            },
             swapping: Swapping::Contiguous(SwapPenalty::Disallowed { allowed_swaps: 1 }),
             battle_delay: 2,
             max_turns: None,
+            stalemate_turns: None,
+            language: None,
             board_genesis: BoardGenesis::Passthrough,
         },
     ),
@@ -189,18 +368,33 @@ const RULE_GENERATIONS: [(Option<EffectiveRuleDay>, GameRules); 3] = [
             win_condition: WinCondition::Destination {
                 town_defense: TownDefense::BeatenWithDefenseStrength(0),
                 artifact_defense: ArtifactDefense::BeatenWithDefenseStrength(0),
+                towns_to_defeat: TownsToDefeat::Any,
             },
             win_metric: WinMetric::TownProximity,
             visibility: Visibility::Standard,
+            fog_level: FogLevel::PieceVision,
+            obelisks: ObeliskRule::Inert,
+            town_recovery: TownRecovery::Permanent,
+            first_placement: FirstPlacement::BesideDock,
             truncation: Truncation::Root,
             timing: Timing::None,
-            hand_size: 7,
+            hand_capacity: 7,
             tile_generation: 1,
             tile_bag_behaviour: TileBagBehaviour::Standard,
+            blank_tiles: 0,
+            challenge_mode: false,
+            allow_passing: false,
+            allow_exchanges: false,
             battle_rules: BattleRules { length_delta: 1 },
+            explosions: ExplosionRules {
+                radius: 1,
+                chain_reaction: false,
+            },
             swapping: Swapping::Contiguous(SwapPenalty::Disallowed { allowed_swaps: 1 }),
             battle_delay: 2,
             max_turns: None,
+            stalemate_turns: None,
+            language: None,
             board_genesis: BoardGenesis::Passthrough,
         },
     ),
@@ -216,6 +410,11 @@ impl GameRules {
         rules
     }
 
+    /// A full bag of this generation's tiles, including any blanks
+    pub fn tile_bag(&self, seed: Option<u64>) -> TileBag {
+        TileBag::generation(self.tile_generation, seed).with_blanks(self.blank_tiles)
+    }
+
     pub fn latest(effective_date: Option<u32>) -> (u32, Self) {
         RULE_GENERATIONS
             .iter()
@@ -234,27 +433,69 @@ impl GameRules {
             .expect("Should always be an effective rule set")
     }
 
+    /// Takes the settings a lobby host may change from `requested`, keeping the rest of these rules.
+    /// Returns `None` if any of the requested settings can't be played.
+    pub fn with_lobby_settings(&self, requested: &GameRules) -> Option<Self> {
+        let playable_timing = match &requested.timing {
+            Timing::PerTurn { .. } => false,
+            Timing::PerPlayer { overtime_rule, .. } => {
+                !matches!(overtime_rule, OvertimeRule::RemoveTiles { .. })
+            }
+            Timing::Fischer { .. } | Timing::Periodic { .. } | Timing::None => true,
+        };
+        if !playable_timing || !(1..=MAX_HAND_CAPACITY).contains(&requested.hand_capacity) {
+            return None;
+        }
+
+        Some(Self {
+            win_condition: requested.win_condition.clone(),
+            win_metric: requested.win_metric.clone(),
+            timing: requested.timing.clone(),
+            visibility: requested.visibility.clone(),
+            fog_level: requested.fog_level.clone(),
+            hand_capacity: requested.hand_capacity,
+            challenge_mode: requested.challenge_mode,
+            language: requested.language.clone(),
+            ..self.clone()
+        })
+    }
+
     pub fn tuesday() -> Self {
         Self {
             generation: None, // hydrated on fetch
             win_condition: WinCondition::Destination {
                 town_defense: TownDefense::BeatenWithDefenseStrength(0),
                 artifact_defense: ArtifactDefense::Invincible,
+                towns_to_defeat: TownsToDefeat::Any,
             },
             win_metric: WinMetric::ObeliskProximity,
             visibility: Visibility::LandFog,
+            fog_level: FogLevel::PieceVision,
+            obelisks: ObeliskRule::Inert,
+            town_recovery: TownRecovery::Permanent,
+            first_placement: FirstPlacement::BesideDock,
             truncation: Truncation::None,
             timing: Timing::PerPlayer {
                 time_allowance: 75 * 60,
                 overtime_rule: OvertimeRule::Elimination,
             },
-            hand_size: 7,
+            hand_capacity: 7,
             tile_generation: 1,
             tile_bag_behaviour: TileBagBehaviour::Standard,
+            blank_tiles: 0,
+            challenge_mode: false,
+            allow_passing: false,
+            allow_exchanges: false,
             battle_rules: BattleRules { length_delta: 1 },
+            explosions: ExplosionRules {
+                radius: 1,
+                chain_reaction: false,
+            },
             swapping: Swapping::Contiguous(SwapPenalty::Disallowed { allowed_swaps: 1 }),
             battle_delay: 2,
             max_turns: Some(1050),
+            stalemate_turns: None,
+            language: None,
             board_genesis: BoardGenesis::Random(BoardParams {
                 land_layer: BoardNoiseParams {
                     dispersion: [3.0, 3.0],
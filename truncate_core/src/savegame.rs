@@ -0,0 +1,301 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bag::TileBag,
+    board::{Board, Coordinate},
+    error::SavegameError,
+    game::Game,
+    judge::Judge,
+    player::Player,
+    reporting::Change,
+    rules::GameRules,
+};
+
+/// Bumped whenever `Savegame` changes in a way that old saves can't be read as,
+/// so that they are rejected rather than misread. Fields added with `#[serde(default)]`,
+/// here or in the rules and players saved within, keep old saves readable without a bump.
+pub const SAVEGAME_VERSION: u32 = 1;
+
+#[derive(Deserialize)]
+struct SavegameVersion {
+    version: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Savegame {
+    version: u32,
+    rules: GameRules,
+    players: Vec<Player>,
+    board: Board,
+    bag: TileBag,
+    battle_count: u32,
+    turn_count: u32,
+    player_turn_count: Vec<u32>,
+    recent_changes: Vec<Change>,
+    started_at: Option<u64>,
+    game_ends_at: Option<u64>,
+    next_player: Option<usize>,
+    paused: bool,
+    winner: Option<usize>,
+    #[serde(default)]
+    drawn: bool,
+    #[serde(default)]
+    consecutive_passes: usize,
+    #[serde(default)]
+    turns_without_progress: u32,
+    #[serde(default)]
+    fallen_towns: Vec<(Coordinate, u32)>,
+    /// Words added to the judge's overlay, such as a private room's custom words
+    #[serde(default)]
+    overlay_words: Vec<String>,
+}
+
+impl Game {
+    /// Serializes everything needed to resume this game, including the bag's RNG position.
+    /// The judge's dictionary and any undo or word history are not included,
+    /// though words added to the judge's overlay are.
+    pub fn to_savegame(&self) -> String {
+        let mut overlay_words: Vec<_> = self.judge.overlay_dictionary.keys().cloned().collect();
+        overlay_words.sort();
+
+        let savegame = Savegame {
+            version: SAVEGAME_VERSION,
+            rules: self.rules.clone(),
+            players: self.players.clone(),
+            board: self.board.clone(),
+            bag: self.bag.clone(),
+            battle_count: self.battle_count,
+            turn_count: self.turn_count,
+            player_turn_count: self.player_turn_count.clone(),
+            recent_changes: self.recent_changes.clone(),
+            started_at: self.started_at,
+            game_ends_at: self.game_ends_at,
+            next_player: self.next_player,
+            paused: self.paused,
+            winner: self.winner,
+            drawn: self.drawn,
+            consecutive_passes: self.consecutive_passes,
+            turns_without_progress: self.turns_without_progress,
+            fallen_towns: self.fallen_towns.clone(),
+            overlay_words,
+        };
+
+        serde_json::to_string(&savegame).expect("Game should be serializable")
+    }
+
+    pub fn from_savegame(savegame: &str) -> Result<Self, SavegameError> {
+        let SavegameVersion { version } =
+            serde_json::from_str(savegame).map_err(|e| SavegameError::Malformed(e.to_string()))?;
+        if version != SAVEGAME_VERSION {
+            return Err(SavegameError::UnsupportedVersion {
+                found: version,
+                expected: SAVEGAME_VERSION,
+            });
+        }
+
+        let Savegame {
+            version: _,
+            rules,
+            players,
+            board,
+            bag,
+            battle_count,
+            turn_count,
+            player_turn_count,
+            recent_changes,
+            started_at,
+            game_ends_at,
+            next_player,
+            paused,
+            winner,
+            drawn,
+            consecutive_passes,
+            turns_without_progress,
+            fallen_towns,
+            overlay_words,
+        } = serde_json::from_str(savegame).map_err(|e| SavegameError::Malformed(e.to_string()))?;
+
+        let mut judge = Judge::default();
+        judge.extend_overlay(overlay_words);
+
+        Ok(Game {
+            rules,
+            players,
+            board,
+            bag,
+            judge,
+            battle_count,
+            turn_count,
+            player_turn_count,
+            recent_changes,
+            started_at,
+            game_ends_at,
+            next_player,
+            paused,
+            winner,
+            drawn,
+            consecutive_passes,
+            turns_without_progress,
+            fallen_towns,
+            history: None,
+            word_history: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use oorandom::Rand32;
+
+    use super::*;
+    use crate::moves::Move;
+
+    /// Picks a placement for the next player, driven only by the game state and `rng`
+    fn random_move(game: &Game, rng: &mut Rand32) -> Move {
+        let player = game.next_player.unwrap();
+        let mut positions: Vec<_> = game
+            .board
+            .playable_positions(player, &game.rules.truncation)
+            .into_iter()
+            .collect();
+        positions.sort_by_key(|c| (c.y, c.x));
+
+        let hand = &game.players[player].hand;
+        Move::Place {
+            player,
+            tile: hand.0[rng.rand_range(0..hand.len() as u32) as usize],
+            position: positions[rng.rand_range(0..positions.len() as u32) as usize],
+        }
+    }
+
+    #[test]
+    fn savegame_round_trip() {
+        let mut game = Game::new(9, 9, Some(42), GameRules::generation(1));
+        game.add_player("A".into());
+        game.add_player("B".into());
+        game.start();
+
+        let mut rng = Rand32::new(7);
+        for _ in 0..20 {
+            let next_move = random_move(&game, &mut rng);
+            _ = game.play_turn(next_move, None, None, None);
+            if game.winner.is_some() {
+                break;
+            }
+        }
+
+        let mut restored = Game::from_savegame(&game.to_savegame()).unwrap();
+        assert_eq!(restored.board, game.board);
+        assert_eq!(restored.players, game.players);
+        assert_eq!(restored.bag, game.bag);
+        assert_eq!(restored.next_player, game.next_player);
+        assert_eq!(restored.player_turn_count, game.player_turn_count);
+        assert_eq!(restored.winner, game.winner);
+
+        for _ in 0..10 {
+            if game.winner.is_some() {
+                break;
+            }
+            let next_move = random_move(&game, &mut rng);
+            assert_eq!(
+                game.play_turn(next_move.clone(), None, None, None),
+                restored.play_turn(next_move, None, None, None)
+            );
+            assert_eq!(restored.board, game.board);
+            assert_eq!(restored.players, game.players);
+            assert_eq!(restored.bag, game.bag);
+            assert_eq!(restored.winner, game.winner);
+        }
+    }
+
+    #[test]
+    fn overlay_words_are_saved() {
+        let mut game = Game::new(9, 9, Some(42), GameRules::generation(1));
+        game.judge.extend_overlay(["qat", "zax"]);
+
+        let restored = Game::from_savegame(&game.to_savegame()).unwrap();
+        let mut words: Vec<_> = restored.judge.overlay_dictionary.keys().cloned().collect();
+        words.sort();
+        assert_eq!(words, vec!["qat".to_string(), "zax".to_string()]);
+    }
+
+    #[test]
+    fn replay_to_matches_sequential_play() {
+        let mut game = Game::new(9, 9, Some(42), GameRules::generation(1));
+        game.add_player("A".into());
+        game.add_player("B".into());
+        game.start();
+        let start = game.clone();
+
+        let mut rng = Rand32::new(11);
+        let mut moves = vec![];
+        let mut states = vec![];
+        for _ in 0..12 {
+            if game.winner.is_some() {
+                break;
+            }
+            let next_move = random_move(&game, &mut rng);
+            _ = game.play_turn(next_move.clone(), None, None, None);
+            moves.push(next_move);
+            states.push(game.clone());
+        }
+
+        assert_eq!(start.replay_to(&moves, 0, None, None).board, start.board);
+        for (n, state) in states.iter().enumerate() {
+            let replayed = start.replay_to(&moves, n + 1, None, None);
+            assert_eq!(replayed.board, state.board);
+            assert_eq!(replayed.players, state.players);
+            assert_eq!(replayed.recent_changes, state.recent_changes);
+        }
+    }
+
+    #[test]
+    fn savegame_loads_without_defaulted_fields() {
+        let mut game = Game::new(9, 9, Some(42), GameRules::generation(1));
+        game.add_player("A".into());
+        game.add_player("B".into());
+        game.start();
+
+        let mut savegame: serde_json::Value = serde_json::from_str(&game.to_savegame()).unwrap();
+        let fields = savegame.as_object_mut().unwrap();
+        for field in [
+            "drawn",
+            "consecutive_passes",
+            "turns_without_progress",
+            "fallen_towns",
+            "overlay_words",
+        ] {
+            fields.remove(field);
+        }
+        for player in fields["players"].as_array_mut().unwrap() {
+            player.as_object_mut().unwrap().remove("swaps_used");
+        }
+
+        let restored = Game::from_savegame(&savegame.to_string()).unwrap();
+        assert_eq!(restored.board, game.board);
+        assert_eq!(restored.players, game.players);
+        assert_eq!(restored.next_player, game.next_player);
+    }
+
+    #[test]
+    fn savegame_rejects_other_versions() {
+        let game = Game::new(9, 9, Some(42), GameRules::generation(1));
+        let savegame = game.to_savegame().replacen(
+            &format!("\"version\":{SAVEGAME_VERSION}"),
+            "\"version\":0",
+            1,
+        );
+
+        assert_eq!(
+            Game::from_savegame(&savegame).err(),
+            Some(SavegameError::UnsupportedVersion {
+                found: 0,
+                expected: SAVEGAME_VERSION
+            })
+        );
+        assert!(matches!(
+            Game::from_savegame("{}"),
+            Err(SavegameError::Malformed(_))
+        ));
+    }
+}
@@ -0,0 +1,323 @@
+use std::collections::HashSet;
+
+use serde::Deserialize;
+
+use crate::{
+    bag::TileBag,
+    board::{Board, Coordinate},
+    error::ScenarioError,
+    game::{Game, GAME_COLOR_BLUE, GAME_COLOR_RED},
+    judge::Judge,
+    moves::Move,
+    player::{Hand, Player},
+    rules::GameRules,
+};
+
+/// The player's index in every scenario, with the computer always playing second
+pub const SCENARIO_PLAYER: usize = 0;
+pub const SCENARIO_COMPUTER: usize = 1;
+
+/// A guided lesson, as authored in a scenario file
+#[derive(Debug, Clone, Deserialize)]
+pub struct ScenarioScript {
+    pub name: String,
+    pub board: String,
+    pub player_hand: String,
+    pub computer_hand: String,
+    /// Words the lesson's judge accepts, so that scenarios don't depend on the full dictionary
+    #[serde(default)]
+    pub words: Vec<String>,
+    /// Defaults to the latest rules
+    #[serde(default)]
+    pub rules_generation: Option<u32>,
+    pub steps: Vec<ScriptStep>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScriptActor {
+    #[default]
+    You,
+    Computer,
+}
+
+/// A single scripted move, and what to tell the player about it
+#[derive(Debug, Clone, Deserialize)]
+pub struct ScriptStep {
+    #[serde(default)]
+    pub actor: ScriptActor,
+    pub prompt: String,
+    /// The only move allowed at this step, written as `A -> 3,4` to place an A at 3,4,
+    /// or as `3,4 -> 5,6` to swap the tiles at 3,4 and 5,6
+    pub action: String,
+    /// Shown when the player tries any other move, falling back to the prompt
+    #[serde(default)]
+    pub hint: Option<String>,
+    /// The tile drawn after this step's move, keeping hands predictable
+    pub gets: char,
+}
+
+impl ScriptStep {
+    pub fn hint(&self) -> &str {
+        self.hint.as_deref().unwrap_or(&self.prompt)
+    }
+
+    fn player(&self) -> usize {
+        match self.actor {
+            ScriptActor::You => SCENARIO_PLAYER,
+            ScriptActor::Computer => SCENARIO_COMPUTER,
+        }
+    }
+}
+
+fn parse_coordinate(pos: &str) -> Option<Coordinate> {
+    let (x, y) = pos.trim().split_once(',')?;
+
+    Some(Coordinate {
+        x: x.trim().parse().ok()?,
+        y: y.trim().parse().ok()?,
+    })
+}
+
+/// Reads a step's `action` as a move for `player`
+pub fn parse_action(player: usize, action: &str) -> Result<Move, ScenarioError> {
+    let invalid = || ScenarioError::InvalidAction {
+        action: action.to_string(),
+    };
+
+    let (from, to) = action.split_once("->").ok_or_else(invalid)?;
+    let to = parse_coordinate(to).ok_or_else(invalid)?;
+
+    if let Some(from) = parse_coordinate(from) {
+        return Ok(Move::Swap {
+            player,
+            positions: [from, to],
+        });
+    }
+
+    let mut tile = from.trim().chars();
+    match (tile.next(), tile.next()) {
+        (Some(tile), None) => Ok(Move::Place {
+            player,
+            tile,
+            position: to,
+        }),
+        _ => Err(invalid()),
+    }
+}
+
+/// A game that only accepts the moves its script allows, in order
+#[derive(Debug, Clone)]
+pub struct Scenario {
+    pub script: ScenarioScript,
+    pub game: Game,
+    step: usize,
+}
+
+impl Scenario {
+    pub fn from_yaml(yaml: &str) -> Result<Self, ScenarioError> {
+        let script =
+            serde_yaml::from_str(yaml).map_err(|e| ScenarioError::Malformed(e.to_string()))?;
+        Self::new(script)
+    }
+
+    /// Sets up the scripted board and hands,
+    /// checking up front that every step's action can be read
+    pub fn new(script: ScenarioScript) -> Result<Self, ScenarioError> {
+        for step in &script.steps {
+            parse_action(step.player(), &step.action)?;
+        }
+        let board = Board::try_from_string(&script.board)
+            .map_err(|e| ScenarioError::InvalidBoard(e.to_string()))?;
+
+        let rules = match script.rules_generation {
+            Some(generation) => GameRules::generation(generation),
+            None => GameRules::latest(None).1,
+        };
+
+        let scripted_player = |name: &str, index: usize, hand: &str, color| Player {
+            name: name.into(),
+            index,
+            hand: Hand(hand.chars().collect()),
+            hand_capacity: hand.chars().count(),
+            allotted_time: None,
+            time_remaining: None,
+            turn_starts_no_later_than: None,
+            turn_starts_no_sooner_than: None,
+            paused_turn_delta: None,
+            swap_count: 0,
+            swaps_used: 0,
+            penalties_incurred: 0,
+            color,
+            seen_tiles: HashSet::new(),
+        };
+
+        let players = vec![
+            scripted_player("You", SCENARIO_PLAYER, &script.player_hand, GAME_COLOR_BLUE),
+            scripted_player(
+                "Computer",
+                SCENARIO_COMPUTER,
+                &script.computer_hand,
+                GAME_COLOR_RED,
+            ),
+        ];
+        let mut game = Game::from_board(board, players, rules);
+        game.judge = Judge::new(script.words.clone());
+        game.rules.battle_delay = 0;
+        game.start();
+
+        Ok(Self {
+            script,
+            game,
+            step: 0,
+        })
+    }
+
+    /// The step waiting to be played, or `None` once the scenario is complete
+    pub fn current_step(&self) -> Option<&ScriptStep> {
+        self.script.steps.get(self.step)
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.current_step().is_none()
+    }
+
+    /// Plays the player's move if it is the one the script expects,
+    /// otherwise leaving the game untouched and returning the step's hint
+    pub fn play_turn(&mut self, next_move: Move) -> Result<Option<usize>, ScenarioError> {
+        let step = self.current_step().ok_or(ScenarioError::Complete)?;
+        if step.actor != ScriptActor::You {
+            return Err(ScenarioError::ComputerToMove);
+        }
+
+        let expected = parse_action(SCENARIO_PLAYER, &step.action)?;
+        if next_move != expected {
+            return Err(ScenarioError::UnexpectedMove {
+                hint: step.hint().to_string(),
+            });
+        }
+
+        self.play_step(expected)
+    }
+
+    /// Plays the computer's scripted move, when the current step belongs to the computer
+    pub fn play_computer_turn(&mut self) -> Result<Option<usize>, ScenarioError> {
+        let step = self.current_step().ok_or(ScenarioError::Complete)?;
+        if step.actor != ScriptActor::Computer {
+            return Err(ScenarioError::PlayerToMove);
+        }
+
+        let scripted_move = parse_action(SCENARIO_COMPUTER, &step.action)?;
+        self.play_step(scripted_move)
+    }
+
+    fn play_step(&mut self, next_move: Move) -> Result<Option<usize>, ScenarioError> {
+        let gets = self.current_step().ok_or(ScenarioError::Complete)?.gets;
+        self.game.bag = TileBag::explicit(vec![gets], None);
+
+        let winner = self
+            .game
+            .play_turn(next_move, None, None, None)
+            .map_err(ScenarioError::Rejected)?;
+
+        self.step += 1;
+        Ok(winner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{board::Square, reporting::Change};
+
+    use super::*;
+
+    const LONGER_WORD: &str = include_str!("../scenarios/attack_with_a_longer_word.yml");
+
+    #[test]
+    fn longer_word_scenario() {
+        let mut scenario = Scenario::from_yaml(LONGER_WORD).unwrap();
+        let board = scenario.game.board.clone();
+
+        // Any move other than the scripted one is turned away with a hint
+        let wrong_move = Move::Place {
+            player: SCENARIO_PLAYER,
+            tile: 'E',
+            position: Coordinate { x: 8, y: 3 },
+        };
+        assert_eq!(
+            scenario.play_turn(wrong_move),
+            Err(ScenarioError::UnexpectedMove {
+                hint: scenario.current_step().unwrap().hint().to_string()
+            })
+        );
+        assert_eq!(scenario.game.board, board);
+
+        assert_eq!(
+            scenario.play_computer_turn(),
+            Err(ScenarioError::PlayerToMove)
+        );
+
+        let scripted_move = parse_action(SCENARIO_PLAYER, "L -> 8,3").unwrap();
+        assert_eq!(scenario.play_turn(scripted_move.clone()), Ok(None));
+        assert!(scenario
+            .game
+            .recent_changes
+            .iter()
+            .any(|change| matches!(change, Change::Battle(_))));
+        // The shorter defending word was removed from the board
+        assert!(!matches!(
+            scenario.game.board.get(Coordinate { x: 8, y: 4 }),
+            Ok(Square::Occupied { .. })
+        ));
+
+        scenario.play_computer_turn().unwrap();
+        assert!(scenario.is_complete());
+        assert_eq!(
+            scenario.play_turn(scripted_move),
+            Err(ScenarioError::Complete)
+        );
+    }
+
+    #[test]
+    fn malformed_boards_are_reported() {
+        let mut script: ScenarioScript = serde_yaml::from_str(LONGER_WORD).unwrap();
+        script.board = "__ ?? __\n__ |0 __".into();
+        assert!(matches!(
+            Scenario::new(script),
+            Err(ScenarioError::InvalidBoard(_))
+        ));
+    }
+
+    #[test]
+    fn hand_capacity_counts_tiles() {
+        let mut script: ScenarioScript = serde_yaml::from_str(LONGER_WORD).unwrap();
+        script.player_hand = "ÉÅL".into();
+        let scenario = Scenario::new(script).unwrap();
+        assert_eq!(scenario.game.players[SCENARIO_PLAYER].hand_capacity, 3);
+    }
+
+    #[test]
+    fn scenario_actions() {
+        assert_eq!(
+            parse_action(0, "A -> 3,4"),
+            Ok(Move::Place {
+                player: 0,
+                tile: 'A',
+                position: Coordinate { x: 3, y: 4 },
+            })
+        );
+        assert_eq!(
+            parse_action(1, "3,4 -> 5,6"),
+            Ok(Move::Swap {
+                player: 1,
+                positions: [Coordinate { x: 3, y: 4 }, Coordinate { x: 5, y: 6 }],
+            })
+        );
+        assert_eq!(
+            parse_action(0, "AB -> 3,4"),
+            Err(ScenarioError::InvalidAction {
+                action: "AB -> 3,4".into()
+            })
+        );
+    }
+}
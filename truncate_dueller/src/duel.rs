@@ -0,0 +1,234 @@
+use std::time::Duration;
+
+use truncate_core::{
+    board::Board,
+    game::Game,
+    messages::PlayerMessage,
+    moves::Move,
+    npc::scoring::{BoardScore, NPCParams},
+    reporting::Change,
+    rules::GameRules,
+};
+
+use crate::dicts::{ensure_dicts, get_dicts, Dicts};
+
+/// Matches still running after this many turns are abandoned without a result
+pub const MAX_MATCH_TURNS: u32 = 200;
+
+/// Matches are drawn once this many turns pass without either NPC making progress
+pub const MATCH_STALEMATE_TURNS: u32 = 40;
+
+/// A move picked by the NPC whose turn it was
+#[derive(Debug, Clone)]
+pub struct MatchMove {
+    pub turn: u32,
+    pub player: usize,
+    /// The move as the NPC picked it, in the form a player would send it
+    pub message: PlayerMessage,
+    pub game_move: Move,
+    /// How the NPC scored the board its search expected this move to lead to
+    pub score: BoardScore,
+    /// The number of boards assessed to find the move
+    pub nodes: usize,
+    pub duration: Duration,
+}
+
+#[derive(Debug, Clone)]
+pub struct MatchResult {
+    pub winner: Option<usize>,
+    pub drawn: bool,
+    /// Whether the draw came from neither NPC making progress
+    pub stalemate: bool,
+    pub moves: Vec<MatchMove>,
+    /// The game as it stood when the match ended
+    pub game: Game,
+}
+
+impl MatchResult {
+    /// Whether the match was won or drawn, rather than abandoned at `MAX_MATCH_TURNS`
+    pub fn finished(&self) -> bool {
+        self.winner.is_some() || self.drawn
+    }
+}
+
+/// Returns the best move along with its score and the number of boards assessed to find it
+pub fn best_move(
+    game: &Game,
+    npc_params: &NPCParams,
+    dicts: &Dicts,
+) -> (PlayerMessage, BoardScore, usize) {
+    ensure_dicts();
+
+    let mut arb = truncate_core::npc::Arborist::pruning();
+    arb.capped(npc_params.evaluation_cap);
+    let search_depth = npc_params.max_depth;
+
+    let (best_move, score) = truncate_core::game::Game::best_move_parallel(
+        game,
+        Some(&dicts.restricted),
+        Some(&dicts.restricted),
+        search_depth,
+        Some(&mut arb),
+        false,
+        npc_params,
+    );
+
+    (best_move, score, arb.assessed())
+}
+
+/// Sets up a two player game on `board`, dealing tiles from `tile_seed`
+pub fn match_game(board: Board, rules: GameRules, tile_seed: u64) -> Game {
+    let mut game = Game::from_board(board, vec![], rules);
+    game.bag = game.rules.tile_bag(Some(tile_seed));
+    game.add_player("P1".into());
+    game.add_player("P2".into());
+
+    game.rules.battle_delay = 0;
+    game.rules.stalemate_turns = Some(MATCH_STALEMATE_TURNS);
+    game.start();
+
+    game
+}
+
+/// Plays `npc_a` as the first player against `npc_b` on `board`.
+/// Searches are capped by board count rather than time, and the parallel search splits its
+/// cap evenly between root moves, so the same inputs always play out the same match.
+pub fn run_match(
+    board: Board,
+    rules: GameRules,
+    tile_seed: u64,
+    npc_a: &NPCParams,
+    npc_b: &NPCParams,
+) -> MatchResult {
+    play_match(
+        match_game(board, rules, tile_seed),
+        [npc_a, npc_b],
+        false,
+        |_, _| {},
+    )
+}
+
+/// Plays out `game` with each player's moves picked by their NPC in `npcs`.
+/// `on_move` sees each move alongside the game as it was before the move was played.
+pub fn play_match(
+    mut game: Game,
+    npcs: [&NPCParams; 2],
+    log: bool,
+    mut on_move: impl FnMut(&Game, &MatchMove),
+) -> MatchResult {
+    let mut dicts = get_dicts();
+    let mut moves = vec![];
+
+    while game.turn_count < MAX_MATCH_TURNS {
+        let next_player = game.next_player.expect("Matches alternate turns");
+        let search_started = instant::SystemTime::now();
+        let (best_move_for_next_player, score, nodes) = best_move(&game, npcs[next_player], &dicts);
+
+        let next_move = match best_move_for_next_player.clone() {
+            PlayerMessage::Place(position, tile) => Move::Place {
+                player: next_player,
+                tile,
+                position,
+            },
+            PlayerMessage::Swap(from, to) => Move::Swap {
+                player: next_player,
+                positions: [from, to],
+            },
+            PlayerMessage::Pass => Move::Pass {
+                player: next_player,
+            },
+            PlayerMessage::Exchange(tile) => Move::Exchange {
+                player: next_player,
+                tile,
+            },
+            _ => unreachable!(),
+        };
+
+        let match_move = MatchMove {
+            turn: game.turn_count,
+            player: next_player,
+            message: best_move_for_next_player,
+            game_move: next_move.clone(),
+            score,
+            nodes,
+            duration: search_started.elapsed().unwrap_or_default(),
+        };
+        on_move(&game, &match_move);
+        moves.push(match_move);
+
+        let pre_board = game.board.to_string();
+        let pre_tiles = game.players[next_player].hand.clone();
+
+        match game.play_turn(
+            next_move.clone(),
+            Some(&dicts.total),
+            Some(&dicts.total),
+            None,
+        ) {
+            Ok(Some(winner)) => {
+                if log {
+                    println!("\nWINNING BOARD:\n{}", game.board);
+                }
+                return MatchResult {
+                    winner: Some(winner),
+                    drawn: false,
+                    stalemate: false,
+                    moves,
+                    game,
+                };
+            }
+            Ok(None) if game.drawn => {
+                return MatchResult {
+                    winner: None,
+                    drawn: true,
+                    stalemate: game.is_stalemate(),
+                    moves,
+                    game,
+                };
+            }
+            Ok(None) => {
+                if log {
+                    let post_board = game.board.to_string();
+
+                    let zipped_board = pre_board
+                        .lines()
+                        .zip(post_board.lines())
+                        .map(|(a, b)| format!("{a}   >   {b}"))
+                        .collect::<Vec<_>>()
+                        .join("\n");
+
+                    println!("\nPlayer {next_player} had tiles {pre_tiles:?}\nPicked {next_move:?}:\n{zipped_board}");
+                }
+
+                // NPC learns words as a result of battles that reveal validity
+                for battle in game
+                    .recent_changes
+                    .iter()
+                    .filter_map(|change| match change {
+                        Change::Battle(battle) => Some(battle),
+                        _ => None,
+                    })
+                {
+                    for word in battle.attackers.iter().chain(battle.defenders.iter()) {
+                        if word.valid == Some(true) {
+                            let dict_word = word.original_word.to_lowercase();
+
+                            dicts.remember(&dict_word);
+                        }
+                    }
+                }
+            }
+            Err(e) => {
+                panic!("NPC move was rejected:\n{e}\n{}", game.board);
+            }
+        }
+    }
+
+    MatchResult {
+        winner: None,
+        drawn: false,
+        stalemate: false,
+        moves,
+        game,
+    }
+}
@@ -0,0 +1,2 @@
+pub mod dicts;
+pub mod duel;
@@ -1,155 +1,77 @@
-use dicts::{get_dicts, Dicts};
-use rayon::iter::{IntoParallelIterator, ParallelIterator};
-use storage::{load_file, write_file, SeedNote};
+use rayon::iter::{IntoParallelIterator, IntoParallelRefIterator, ParallelIterator};
+use storage::{load_book, load_file, write_book, write_file, SeedNote};
 use truncate_core::{
     game::Game,
     generation::{generate_board, get_game_verification, BoardSeed},
-    messages::PlayerMessage,
-    moves::Move,
-    npc::scoring::{NPCParams, NPCPersonality},
+    npc::{opening_book::OpeningBook, scoring::NPCPersonality},
     rules::GameRules,
 };
+use truncate_dueller::{
+    dicts::ensure_dicts,
+    duel::{match_game, play_match, run_match},
+};
 
-use crate::dicts::ensure_dicts;
+use crate::stats::{MoveSample, MoveTimings};
 
-mod dicts;
+mod stats;
 mod storage;
 
-fn best_move(game: &Game, npc_params: &NPCParams, dicts: &Dicts) -> PlayerMessage {
-    ensure_dicts();
-
-    let mut arb = truncate_core::npc::Arborist::pruning();
-    arb.capped(npc_params.evaluation_cap);
-    let search_depth = npc_params.max_depth;
-
-    let (best_move, _score) = truncate_core::game::Game::best_move(
-        game,
-        Some(&dicts.restricted),
-        Some(&dicts.restricted),
-        search_depth,
-        Some(&mut arb),
-        false,
-        npc_params,
-    );
-
-    best_move
-}
-
 fn evaluate_single_seed(
     seed: BoardSeed,
     log: bool,
     latest_rules_generation: u32,
-) -> Option<SeedNote> {
-    let maximum_turns = 200;
-
-    let mut game = get_game_for_seed(seed.clone(), latest_rules_generation);
+) -> Option<(SeedNote, OpeningBook)> {
+    let game = get_game_for_seed(seed.clone(), latest_rules_generation);
 
     let verification = get_game_verification(&game);
     let npc_params = NPCPersonality::jet().params;
-    let mut dicts = get_dicts();
-
-    while game.turn_count < maximum_turns {
-        let best_move_for_next_player = best_move(&game, &npc_params, &dicts);
-        let next_player = game.next_player.unwrap();
-
-        let next_move = match best_move_for_next_player {
-            PlayerMessage::Place(position, tile) => Move::Place {
-                player: next_player,
-                tile,
-                position,
-            },
-            PlayerMessage::Swap(from, to) => Move::Swap {
-                player: next_player,
-                positions: [from, to],
-            },
-            _ => unreachable!(),
-        };
-
-        let pre_board = game.board.to_string();
-        let pre_tiles = game.players[next_player].hand.clone();
-
-        match game.play_turn(
-            next_move.clone(),
-            Some(&dicts.total),
-            Some(&dicts.total),
-            None,
-        ) {
-            Ok(Some(winner)) => {
-                if log {
-                    println!("\nWINNING BOARD:\n{}", game.board);
-                }
-                return Some(SeedNote {
-                    rerolls: 0,
-                    best_player: winner,
-                    board_generation: seed.generation,
-                    rules_generation: latest_rules_generation,
-                    verification,
-                });
-            }
-            Ok(None) => {
-                if log {
-                    let post_board = game.board.to_string();
-
-                    let zipped_board = pre_board
-                        .lines()
-                        .zip(post_board.lines())
-                        .map(|(a, b)| format!("{a}   >   {b}"))
-                        .collect::<Vec<_>>()
-                        .join("\n");
-
-                    println!("\nPlayer {next_player} had tiles {pre_tiles:?}\nPicked {next_move:?}:\n{zipped_board}");
-                }
-
-                // NPC learns words as a result of battles that reveal validity
-                for battle in game
-                    .recent_changes
-                    .iter()
-                    .filter_map(|change| match change {
-                        truncate_core::reporting::Change::Battle(battle) => Some(battle),
-                        _ => None,
-                    })
-                {
-                    for word in battle.attackers.iter().chain(battle.defenders.iter()) {
-                        if word.valid == Some(true) {
-                            let dict_word = word.original_word.to_lowercase();
-
-                            dicts.remember(&dict_word);
-                        }
-                    }
-                }
-            }
-            Err(e) => {
-                panic!("Errored on seed {seed:?}:\n{e}");
-            }
-        }
-    }
-
-    None
+    let mut book = OpeningBook::new();
+    let mut timings = MoveTimings::new(npc_params.evaluation_cap);
+
+    let result = play_match(game, [&npc_params, &npc_params], log, |game, match_move| {
+        timings.record(MoveSample {
+            turn: match_move.turn,
+            player: match_move.player,
+            duration: match_move.duration,
+            nodes: match_move.nodes,
+            board: game.board.to_string(),
+        });
+
+        book.record(&seed, game, match_move.message.clone());
+    });
+    println!("{}", timings.summary(seed.seed));
+
+    // A drawn seed can't rank the players, so it is rerolled like an unfinished one
+    let winner = result.winner?;
+    Some((
+        SeedNote {
+            rerolls: 0,
+            best_player: winner,
+            board_generation: seed.generation,
+            rules_generation: latest_rules_generation,
+            verification,
+        },
+        book,
+    ))
 }
 
 fn get_game_for_seed(seed: BoardSeed, rules_generation: u32) -> Game {
-    let mut board = generate_board(seed.clone())
+    let board = generate_board(seed.clone())
         .expect("Generation should be possible from this seed")
         .board;
-    board.cache_special_squares();
 
-    let mut game = Game::new(
-        9,
-        9,
-        Some(seed.seed as u64),
+    match_game(
+        board,
         GameRules::generation(rules_generation),
-    );
-    game.add_player("P1".into());
-    game.add_player("P2".into());
-
-    game.board = board.clone();
-    game.rules.battle_delay = 0;
-    game.start();
-
-    game
+        seed.tile_seed(),
+    )
 }
 
-fn evaluate_seed(mut seed: BoardSeed, log: bool, latest_rules_generation: u32) -> (u32, SeedNote) {
+fn evaluate_seed(
+    mut seed: BoardSeed,
+    log: bool,
+    latest_rules_generation: u32,
+) -> (u32, SeedNote, OpeningBook) {
     let core_seed = seed.seed;
 
     seed.external_reroll();
@@ -169,50 +91,127 @@ fn evaluate_seed(mut seed: BoardSeed, log: bool, latest_rules_generation: u32) -
 
     println!("Evaluated notes for {core_seed} with {rerolls} reroll(s)");
 
-    let mut seed_notes = seed_result.unwrap();
+    let (mut seed_notes, book) = seed_result.unwrap();
     seed_notes.rerolls = rerolls;
     println!("........ {core_seed}, {seed_notes:#?}");
-    (core_seed, seed_notes)
+    (core_seed, seed_notes, book)
 }
 
-fn verify_note(seed: &u32, note: &SeedNote) -> bool {
+/// Replays the generation of a stored seed, checking it still produces the recorded game
+fn verify_note(seed: &u32, note: &SeedNote) -> Result<(), String> {
     let mut board_seed = BoardSeed::new_with_generation(note.board_generation, *seed);
     for _ in 0..(note.rerolls) {
         board_seed.external_reroll();
     }
 
     let game = get_game_for_seed(board_seed, note.rules_generation);
-
-    println!("{}", game.board);
-
     let verification = get_game_verification(&game);
 
     if verification != note.verification {
-        eprintln!("! ! ! ! ! ! ! ! ! ! ! Failed verification for {seed}");
-    } else {
-        println!("Seed {seed} was verified ({} rerolls)", note.rerolls);
+        return Err(format!(
+            "Failed verification for seed {seed} ({} rerolls)\n  expected: {}\n  actual:   {verification}\n{}",
+            note.rerolls, note.verification, game.board
+        ));
     }
 
-    verification == note.verification
+    println!("Seed {seed} was verified ({} rerolls)", note.rerolls);
+    Ok(())
+}
+
+/// Plays two personalities against each other across `count` daily boards, swapping who goes
+/// first each board, and reports how often each of them won.
+fn compare_personalities(a: &NPCPersonality, b: &NPCPersonality, count: u32) {
+    let rules_generation = GameRules::latest(None).0;
+
+    let results: Vec<_> = (0..count)
+        .into_par_iter()
+        .map(|day| {
+            let seed = BoardSeed::new(day);
+            let board = generate_board(seed.clone())
+                .expect("Generation should be possible from this seed")
+                .board;
+            let a_first = day % 2 == 0;
+            let (first, second) = if a_first { (a, b) } else { (b, a) };
+
+            let result = run_match(
+                board,
+                GameRules::generation(rules_generation),
+                seed.tile_seed(),
+                &first.params,
+                &second.params,
+            );
+            let a_won = result.winner.map(|winner| (winner == 0) == a_first);
+            println!(
+                "Day {day}: {} after {} moves",
+                match a_won {
+                    Some(true) => format!("{} won", a.name),
+                    Some(false) => format!("{} won", b.name),
+                    None if result.stalemate => "stalemated".to_string(),
+                    None if result.drawn => "drawn".to_string(),
+                    None => "unfinished".to_string(),
+                },
+                result.moves.len()
+            );
+            a_won
+        })
+        .collect();
+
+    let a_wins = results.iter().filter(|r| **r == Some(true)).count();
+    let b_wins = results.iter().filter(|r| **r == Some(false)).count();
+    let percent = |wins: usize| 100.0 * wins as f32 / count.max(1) as f32;
+    println!(
+        "{}: {a_wins} wins ({:.1}%)\n{}: {b_wins} wins ({:.1}%)\nNo result: {}",
+        a.name,
+        percent(a_wins),
+        b.name,
+        percent(b_wins),
+        results.len() - a_wins - b_wins
+    );
 }
 
 fn main() {
     let quantity = 30;
 
+    let args = std::env::args().collect::<Vec<_>>();
+
+    if let Some(i) = args.iter().position(|a| a == "--match") {
+        let personality = |arg: Option<&String>| {
+            let id = arg.expect("Usage: --match <personality> <personality> [count]");
+            NPCPersonality::from_id(id).unwrap_or_else(|| panic!("No personality named {id}"))
+        };
+        let a = personality(args.get(i + 1));
+        let b = personality(args.get(i + 2));
+        let count = args
+            .get(i + 3)
+            .map(|c| c.parse().expect("Count should be a number"))
+            .unwrap_or(quantity);
+
+        ensure_dicts();
+        compare_personalities(&a, &b, count);
+        return;
+    }
+
     let mut current_notes = load_file();
     ensure_dicts();
+    let verify_only = args.iter().any(|a| a == "--verify-only");
 
-    let verifies: Vec<_> = current_notes
+    let verified = current_notes
         .notes
-        .iter()
-        .map(|(seed, note)| verify_note(seed, note))
-        .collect();
+        .par_iter()
+        .try_for_each(|(seed, note)| verify_note(seed, note));
 
-    if verifies.contains(&false) {
-        panic!("One or more seeds failed to verify");
+    if let Err(failure) = verified {
+        if verify_only {
+            eprintln!("{failure}");
+            std::process::exit(1);
+        }
+        panic!("{failure}");
     }
 
-    let args = std::env::args().collect::<Vec<_>>();
+    if verify_only {
+        println!("All {} seeds verified", current_notes.notes.len());
+        return;
+    }
 
     if let Some(seed) = args.get(1) {
         let day = seed.parse().expect("Seed should be a number");
@@ -240,9 +239,12 @@ fn main() {
         })
         .collect();
 
-    for (seed, notes) in results {
+    let mut current_book = load_book();
+    for (seed, notes, book) in results {
         current_notes.notes.insert(seed, notes);
+        current_book.extend(book);
     }
 
     write_file(current_notes);
+    write_book(current_book);
 }
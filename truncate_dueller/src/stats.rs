@@ -0,0 +1,93 @@
+use std::time::Duration;
+
+/// How long the NPC spent picking a single move
+pub struct MoveSample {
+    pub turn: u32,
+    pub player: usize,
+    pub duration: Duration,
+    pub nodes: usize,
+    pub board: String,
+}
+
+/// Search times and node counts for every move of a duel
+pub struct MoveTimings {
+    node_cap: usize,
+    samples: Vec<MoveSample>,
+}
+
+impl MoveTimings {
+    pub fn new(node_cap: usize) -> Self {
+        Self {
+            node_cap,
+            samples: vec![],
+        }
+    }
+
+    pub fn record(&mut self, sample: MoveSample) {
+        self.samples.push(sample);
+    }
+
+    /// Moves whose search was cut short by the node cap
+    fn capped(&self) -> impl Iterator<Item = &MoveSample> {
+        self.samples.iter().filter(|s| s.nodes >= self.node_cap)
+    }
+
+    pub fn summary(&self, seed: u32) -> String {
+        let mut millis: Vec<_> = self
+            .samples
+            .iter()
+            .map(|s| s.duration.as_millis())
+            .collect();
+        let mut nodes: Vec<_> = self.samples.iter().map(|s| s.nodes).collect();
+
+        let mut summary = format!(
+            "Move timings for seed {seed} ({} moves):\n  \
+             time  p50 {}ms, p95 {}ms, max {}ms\n  \
+             nodes p50 {}, p95 {}, max {}",
+            self.samples.len(),
+            percentile(&mut millis, 0.5),
+            percentile(&mut millis, 0.95),
+            percentile(&mut millis, 1.0),
+            percentile(&mut nodes, 0.5),
+            percentile(&mut nodes, 0.95),
+            percentile(&mut nodes, 1.0),
+        );
+
+        let capped = self.capped().count();
+        if capped == 0 {
+            summary.push_str(&format!(
+                "\n  No moves reached the {} node cap",
+                self.node_cap
+            ));
+            return summary;
+        }
+
+        summary.push_str(&format!(
+            "\n  {capped} move(s) reached the {} node cap:",
+            self.node_cap
+        ));
+        for sample in self.capped() {
+            summary.push_str(&format!(
+                "\n  > turn {} (player {}): {} nodes in {}ms\n{}",
+                sample.turn,
+                sample.player,
+                sample.nodes,
+                sample.duration.as_millis(),
+                sample.board
+            ));
+        }
+
+        summary
+    }
+}
+
+/// Nearest-rank percentile, where `p` is between 0 and 1
+fn percentile<T: Ord + Copy + Default>(values: &mut [T], p: f64) -> T {
+    if values.is_empty() {
+        return T::default();
+    }
+    values.sort_unstable();
+
+    let rank = (p * values.len() as f64).ceil() as usize;
+    values[rank.clamp(1, values.len()) - 1]
+}
@@ -1,11 +1,16 @@
 use std::{collections::BTreeMap, path::PathBuf};
 
 use serde::{Deserialize, Serialize};
+use truncate_core::npc::opening_book::OpeningBook;
 
 fn note_file() -> PathBuf {
     PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("seed_notes.yml")
 }
 
+fn book_file() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("opening_book.yml")
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SeedNote {
     pub rerolls: usize,
@@ -35,3 +40,17 @@ pub fn write_file(notes: NotesFile) {
     let output_content = serde_yaml::to_string(&notes).unwrap();
     std::fs::write(note_file(), output_content).expect("Writing notes should succeed");
 }
+
+pub fn load_book() -> OpeningBook {
+    std::fs::read_to_string(book_file())
+        .map(|file| {
+            serde_yaml::from_str(&file)
+                .expect("If the file exists, it should match the opening book format")
+        })
+        .unwrap_or_default()
+}
+
+pub fn write_book(book: OpeningBook) {
+    let output_content = serde_yaml::to_string(&book).unwrap();
+    std::fs::write(book_file(), output_content).expect("Writing the opening book should succeed");
+}
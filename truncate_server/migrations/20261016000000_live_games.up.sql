@@ -0,0 +1,8 @@
+-- Live Games Table
+CREATE TABLE live_games (
+    room_code VARCHAR(255) PRIMARY KEY,
+    effective_day INT NOT NULL,
+    savegame TEXT NOT NULL,
+    finished BOOLEAN NOT NULL DEFAULT false,
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
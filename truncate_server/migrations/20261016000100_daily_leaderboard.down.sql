@@ -0,0 +1,5 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS daily_puzzle_results_by_day;
+
+ALTER TABLE daily_puzzle_attempts
+    DROP COLUMN IF EXISTS completed_at;
@@ -0,0 +1,5 @@
+-- Track when an attempt was won, for breaking leaderboard ties
+ALTER TABLE daily_puzzle_attempts
+    ADD COLUMN completed_at TIMESTAMP WITH TIME ZONE;
+
+CREATE INDEX daily_puzzle_results_by_day ON daily_puzzle_results (daily_puzzle);
@@ -0,0 +1,3 @@
+-- Add down migration script here
+ALTER TABLE daily_puzzle_attempts
+    DROP COLUMN IF EXISTS drawn;
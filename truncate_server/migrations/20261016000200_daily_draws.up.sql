@@ -0,0 +1,3 @@
+-- Record attempts that ended in a draw, so they aren't counted as losses
+ALTER TABLE daily_puzzle_attempts
+    ADD COLUMN drawn BOOLEAN NOT NULL DEFAULT false;
@@ -0,0 +1,11 @@
+-- Finished multiplayer games, with one row for each logged in player
+CREATE TABLE game_history (
+    history_id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    player_id UUID NOT NULL REFERENCES players(player_id),
+    room_code VARCHAR(255) NOT NULL,
+    opponents TEXT[] NOT NULL,
+    result VARCHAR(16) NOT NULL,
+    finished_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX game_history_by_player ON game_history (player_id, finished_at DESC);
@@ -0,0 +1,15 @@
+-- Add down migration script here
+DELETE FROM daily_puzzle_attempts
+    WHERE result_id IN (SELECT result_id FROM daily_puzzle_results WHERE replay);
+
+DELETE FROM daily_puzzle_results WHERE replay;
+
+ALTER TABLE daily_puzzle_results
+    DROP CONSTRAINT daily_puzzle_results_player_id_daily_puzzle_replay_key;
+
+ALTER TABLE daily_puzzle_results
+    ADD CONSTRAINT daily_puzzle_results_player_id_daily_puzzle_key
+    UNIQUE (player_id, daily_puzzle);
+
+ALTER TABLE daily_puzzle_results
+    DROP COLUMN IF EXISTS replay;
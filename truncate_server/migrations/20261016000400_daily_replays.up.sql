@@ -0,0 +1,10 @@
+-- Replays of past days get their own results, so they never count towards the original day
+ALTER TABLE daily_puzzle_results
+    ADD COLUMN replay BOOLEAN NOT NULL DEFAULT false;
+
+ALTER TABLE daily_puzzle_results
+    DROP CONSTRAINT daily_puzzle_results_player_id_daily_puzzle_key;
+
+ALTER TABLE daily_puzzle_results
+    ADD CONSTRAINT daily_puzzle_results_player_id_daily_puzzle_replay_key
+    UNIQUE (player_id, daily_puzzle, replay);
@@ -0,0 +1,9 @@
+-- Nonces that have already been used, so that replays are rejected by every server and across restarts
+CREATE TABLE burned_nonces (
+    token TEXT NOT NULL,
+    nonce_id BIGINT NOT NULL,
+    generated_at BIGINT NOT NULL,
+    PRIMARY KEY (token, nonce_id, generated_at)
+);
+
+CREATE INDEX burned_nonces_by_age ON burned_nonces (generated_at);
@@ -0,0 +1,7 @@
+-- Add down migration script here
+ALTER TABLE daily_puzzle_attempts
+    DROP COLUMN IF EXISTS resigned;
+
+ALTER TABLE game_history
+    DROP COLUMN IF EXISTS resign_reason,
+    DROP COLUMN IF EXISTS ended_by;
@@ -0,0 +1,7 @@
+-- Separate games decided on the board from resignations and forfeits
+ALTER TABLE game_history
+    ADD COLUMN ended_by VARCHAR(16) NOT NULL DEFAULT 'board',
+    ADD COLUMN resign_reason TEXT;
+
+ALTER TABLE daily_puzzle_attempts
+    ADD COLUMN resigned BOOLEAN NOT NULL DEFAULT false;
@@ -0,0 +1,266 @@
+use std::{sync::Arc, time::Duration};
+
+use serde::Serialize;
+use tokio::net::{TcpListener, TcpStream};
+use truncate_core::game::now;
+use uuid::Uuid;
+
+use crate::{
+    errors::TruncateServerError,
+    game_state::GameManager,
+    http::{read_request, respond},
+    storage::{daily, recordings},
+    ServerState,
+};
+
+/// How long to wait on each game's lock before reporting it as busy
+const LOCK_TIMEOUT: Duration = Duration::from_millis(100);
+/// How many deadlock reports are kept around for the admin endpoint
+pub const MAX_DEADLOCK_REPORTS: usize = 20;
+
+#[derive(Serialize)]
+struct PlayerSummary {
+    name: String,
+    connected: bool,
+}
+
+#[derive(Serialize)]
+struct GameSummary {
+    room_code: String,
+    /// Set if the game couldn't be locked in time, in which case nothing else is known
+    busy: bool,
+    players: Vec<PlayerSummary>,
+    spectators: usize,
+    turn_count: u32,
+    started: bool,
+    finished: bool,
+    age_secs: u64,
+}
+
+impl GameSummary {
+    fn busy(room_code: String) -> Self {
+        Self {
+            room_code,
+            busy: true,
+            players: vec![],
+            spectators: 0,
+            turn_count: 0,
+            started: false,
+            finished: false,
+            age_secs: 0,
+        }
+    }
+
+    fn from_manager(room_code: String, game_manager: &GameManager) -> Self {
+        Self {
+            room_code,
+            busy: false,
+            players: game_manager
+                .core_game
+                .players
+                .iter()
+                .zip(&game_manager.players)
+                .map(|(player, connection)| PlayerSummary {
+                    name: player.name.clone(),
+                    connected: connection.socket.is_some(),
+                })
+                .collect(),
+            spectators: game_manager.spectators.len(),
+            turn_count: game_manager.core_game.turn_count,
+            started: game_manager.core_game.started_at.is_some(),
+            finished: game_manager.core_game.is_over(),
+            age_secs: now().saturating_sub(game_manager.created_at),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct AdminReport {
+    games: Vec<GameSummary>,
+    deadlocks: Vec<String>,
+}
+
+async fn summarize(server_state: ServerState, room_code: Option<String>) -> Vec<GameSummary> {
+    // Each game can take up to LOCK_TIMEOUT to lock, so this runs off the runtime
+    tokio::task::spawn_blocking(move || summarize_blocking(&server_state, room_code.as_deref()))
+        .await
+        .unwrap_or_else(|e| {
+            tracing::error!(error = ?e, "Errored summarizing games: {e}");
+            vec![]
+        })
+}
+
+fn summarize_blocking(server_state: &ServerState, room_code: Option<&str>) -> Vec<GameSummary> {
+    // Clone the handles out so that the games map isn't held while locking each game
+    let mut games: Vec<_> = server_state
+        .games
+        .lock()
+        .iter()
+        .filter(|(code, _)| room_code.map_or(true, |c| c.eq_ignore_ascii_case(code)))
+        .map(|(code, game)| (code.clone(), game.clone()))
+        .collect();
+    games.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    games
+        .into_iter()
+        .map(|(code, game)| match game.try_lock_for(LOCK_TIMEOUT) {
+            Some(game_manager) => GameSummary::from_manager(code, &game_manager),
+            None => GameSummary::busy(code),
+        })
+        .collect()
+}
+
+/// Compares a given secret with the expected one in a time that depends only on their lengths,
+/// so that response times don't reveal how much of a guess was right
+pub fn secrets_match(given: &str, expected: &str) -> bool {
+    let (given, expected) = (given.as_bytes(), expected.as_bytes());
+    let difference = given
+        .iter()
+        .zip(expected)
+        .fold(given.len() ^ expected.len(), |difference, (a, b)| {
+            difference | usize::from(a ^ b)
+        });
+    std::hint::black_box(difference) == 0
+}
+
+async fn handle_request(server_state: ServerState, stream: TcpStream, secret: String) {
+    let Some((request, stream)) = read_request(stream).await else {
+        return;
+    };
+
+    let authorized = request
+        .header("authorization")
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .is_some_and(|given| secrets_match(given, &secret));
+    if !authorized {
+        return respond(stream, "401 Unauthorized", "{}".into()).await;
+    }
+
+    let Some(path) = request.get_path() else {
+        return respond(stream, "405 Method Not Allowed", "{}".into()).await;
+    };
+    if let Some(id) = path
+        .strip_prefix("/attempts/")
+        .and_then(|p| p.strip_suffix("/verify"))
+    {
+        return verify_attempt(server_state, stream, id).await;
+    }
+    if path == "/recordings/verify" {
+        return verify_recordings(server_state, stream).await;
+    }
+
+    match path.strip_prefix("/games") {
+        Some("") => {
+            let report = AdminReport {
+                games: summarize(server_state.clone(), None).await,
+                deadlocks: server_state.deadlock_reports.lock().clone(),
+            };
+            let body = serde_json::to_string(&report).expect("Reports should be serializable");
+            respond(stream, "200 OK", body).await
+        }
+        Some(code) if code.starts_with('/') => {
+            match summarize(server_state, Some(code[1..].to_string()))
+                .await
+                .pop()
+            {
+                Some(summary) => {
+                    let body =
+                        serde_json::to_string(&summary).expect("Reports should be serializable");
+                    respond(stream, "200 OK", body).await
+                }
+                None => respond(stream, "404 Not Found", "{}".into()).await,
+            }
+        }
+        _ => respond(stream, "404 Not Found", "{}".into()).await,
+    }
+}
+
+/// Replays a stored daily attempt, for settling disputes over its result
+async fn verify_attempt(server_state: ServerState, stream: TcpStream, id: &str) {
+    let Ok(id) = Uuid::parse_str(id) else {
+        return respond(stream, "400 Bad Request", "{}".into()).await;
+    };
+
+    match daily::verify_attempt(&server_state, id).await {
+        Ok(Some(verification)) => {
+            let body =
+                serde_json::to_string(&verification).expect("Reports should be serializable");
+            respond(stream, "200 OK", body).await
+        }
+        Ok(None) => respond(stream, "404 Not Found", "{}".into()).await,
+        Err(TruncateServerError::DatabaseOffline) => {
+            respond(stream, "503 Service Unavailable", "{}".into()).await
+        }
+        Err(e) => {
+            tracing::error!(%id, error = ?e, "Errored verifying attempt: {e}");
+            respond(stream, "500 Internal Server Error", "{}".into()).await
+        }
+    }
+}
+
+/// Replays every recorded game, for batch audits of finished games
+async fn verify_recordings(server_state: ServerState, stream: TcpStream) {
+    let Some(path) = &server_state.game_recordings else {
+        return respond(stream, "404 Not Found", "{}".into()).await;
+    };
+
+    let contents = match tokio::fs::read_to_string(path).await {
+        Ok(contents) => contents,
+        Err(e) => {
+            tracing::error!(path = %path.display(), error = ?e, "Errored reading recordings: {e}");
+            return respond(stream, "500 Internal Server Error", "{}".into()).await;
+        }
+    };
+
+    // Replaying every game is slow, so it runs off the runtime against a snapshot of the dictionary
+    let (words, added_words) = {
+        let word_db = server_state.word_db.lock();
+        (
+            Arc::clone(&word_db.valid_words),
+            word_db.added_words.clone(),
+        )
+    };
+    let verifications = match tokio::task::spawn_blocking(move || {
+        recordings::verify_recordings(&contents, &words, &added_words)
+    })
+    .await
+    {
+        Ok(verifications) => verifications,
+        Err(e) => {
+            tracing::error!(error = ?e, "Errored replaying recordings: {e}");
+            return respond(stream, "500 Internal Server Error", "{}".into()).await;
+        }
+    };
+    let body = serde_json::to_string(&verifications).expect("Reports should be serializable");
+    respond(stream, "200 OK", body).await
+}
+
+/// Serves read-only summaries of the running games, for operators
+pub async fn serve_admin(server_state: ServerState, addr: String, secret: String) {
+    let listener = match TcpListener::bind(&addr).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            tracing::error!(%addr, "Failed to bind the admin endpoint: {e}");
+            return;
+        }
+    };
+    tracing::info!(%addr, "Admin endpoint listening");
+
+    while let Ok((stream, _)) = listener.accept().await {
+        tokio::spawn(handle_request(server_state.clone(), stream, secret.clone()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn secrets_match_only_exactly() {
+        assert!(secrets_match("hunter2", "hunter2"));
+        assert!(!secrets_match("hunter3", "hunter2"));
+        assert!(!secrets_match("hunter", "hunter2"));
+        assert!(!secrets_match("hunter22", "hunter2"));
+        assert!(!secrets_match("", "hunter2"));
+    }
+}
@@ -1,34 +1,105 @@
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{HashMap, HashSet},
+    env, fs,
+    path::Path,
+    sync::Arc,
+};
 
 use rand::seq::SliceRandom;
-use rusqlite::Connection;
+use rusqlite::{Connection, OpenFlags};
+use thiserror::Error;
 use truncate_core::{
-    judge::{WordData, WordDict},
+    judge::{Judge, WordData, WordDict},
     reporting::WordMeaning,
 };
 
+/// Longer chat messages are cut off at this many characters
+pub const MAX_CHAT_LENGTH: usize = 280;
+
 pub static TRUNCATE_DICT: &str = include_str!("../../dict_builder/final_wordlist.txt");
+/// A few hundred common words, enough to play games locally with `DEV_DICTIONARY=true`.
+/// Only debug builds carry it, so release servers always play with the full dictionary.
+#[cfg(debug_assertions)]
+pub static DEV_DICT: &str = include_str!("dev_wordlist.txt");
+
+#[derive(Error, Debug)]
+pub enum DefsError {
+    #[error(
+        "the {dictionary} dictionary has no words. Run the dict builder to regenerate \
+         dict_builder/final_wordlist.txt, or set DEV_DICTIONARY=true to use a small built-in list"
+    )]
+    EmptyWordlist { dictionary: String },
+    #[error(
+        "line {line} of the {dictionary} dictionary isn't `<word> <extensions> <frequency>`: {content:?}. \
+         Re-run the dict builder, or set DEV_DICTIONARY=true to use a small built-in list"
+    )]
+    MalformedWordlist {
+        dictionary: String,
+        line: usize,
+        content: String,
+    },
+    #[error(
+        "{path} isn't a usable word definition database ({source}). Download a fresh defs.db, \
+         or remove the file to run without definitions"
+    )]
+    MalformedDefinitions {
+        path: String,
+        source: rusqlite::Error,
+    },
+}
 
 pub struct WordDB {
     pub conn: Option<Connection>,
-    pub valid_words: WordDict,
+    /// The English dictionary compiled into the server,
+    /// shared so that long replays can run without holding the database lock
+    pub valid_words: Arc<WordDict>,
+    /// Dictionaries for any other languages, keyed by their language tag
+    pub other_languages: HashMap<String, WordDict>,
+    /// Words added by admins for every room, kept apart from the dictionaries
+    /// so that adding a few never copies a whole dictionary
+    pub added_words: WordDict,
     pub room_codes: Vec<String>,
     pub allocated_room_codes: HashSet<String>,
 }
 
 impl WordDB {
+    /// Sets up the dictionaries, taking room codes from the short, inoffensive English words
+    pub fn new(
+        conn: Option<Connection>,
+        valid_words: WordDict,
+        other_languages: HashMap<String, WordDict>,
+    ) -> Self {
+        let room_codes: Vec<_> = valid_words
+            .iter()
+            .filter(|(word, data)| word.len() < 6 && !data.objectionable)
+            .map(|(word, _)| word)
+            .cloned()
+            .collect();
+
+        tracing::info!(room_codes = room_codes.len(), "Room codes available");
+
+        Self {
+            conn,
+            room_codes,
+            valid_words: Arc::new(valid_words),
+            other_languages,
+            added_words: HashMap::new(),
+            allocated_room_codes: HashSet::new(),
+        }
+    }
+
     pub fn get_word(&self, word: &str) -> Option<Vec<WordMeaning>> {
         let Some(conn) = &self.conn else { return None };
 
         let mut stmt = conn
             .prepare("SELECT definitions FROM words WHERE word = ?")
-            .unwrap();
+            .ok()?;
 
         let def_str: Option<String> = stmt
             .query(&[word])
-            .unwrap()
+            .ok()?
             .next()
-            .unwrap()
+            .ok()?
             .map(|row| row.get_unwrap("definitions"));
 
         def_str
@@ -36,6 +107,15 @@ impl WordDB {
             .flatten()
     }
 
+    /// The dictionary games in the given language are judged against,
+    /// where `None` is the default English dictionary
+    pub fn dictionary(&self, language: Option<&str>) -> Option<&WordDict> {
+        match language {
+            None => Some(&self.valid_words),
+            Some(language) => self.other_languages.get(language),
+        }
+    }
+
     fn rand_code(&self) -> String {
         self.room_codes
             .choose(&mut rand::thread_rng())
@@ -43,6 +123,74 @@ impl WordDB {
             .expect("No words in dataset")
     }
 
+    /// Replaces the letters of any objectionable words, in any language, with asterisks.
+    /// Words are runs of letters, so any other character separates them and is kept as is.
+    pub fn censor(&self, text: &str) -> String {
+        let mut censored = String::with_capacity(text.len());
+        let mut word = String::new();
+        for c in text.chars() {
+            if c.is_alphabetic() {
+                word.push(c);
+                continue;
+            }
+            self.push_censored(&mut censored, &word);
+            word.clear();
+            censored.push(c);
+        }
+        self.push_censored(&mut censored, &word);
+        censored
+    }
+
+    fn push_censored(&self, censored: &mut String, word: &str) {
+        let lowercase = word.to_lowercase();
+        let objectionable = std::iter::once(&*self.valid_words)
+            .chain(self.other_languages.values())
+            .any(|dict| dict.get(&lowercase).is_some_and(|data| data.objectionable));
+        if objectionable {
+            censored.extend(word.chars().map(|_| '*'));
+        } else {
+            censored.push_str(word);
+        }
+    }
+
+    /// Trims, shortens and censors a player's message to others,
+    /// or returns `None` if nothing is left to send
+    pub fn clean_chat(&self, text: &str) -> Option<String> {
+        let text: String = text.trim().chars().take(MAX_CHAT_LENGTH).collect();
+        let text = text.trim_end();
+        (!text.is_empty()).then(|| self.censor(text))
+    }
+
+    /// Adds words that are valid in every game, returning how many were new.
+    /// Added words aren't scored, so their extensions and frequency are zero.
+    pub fn merge_words(&mut self, words: &[String]) -> usize {
+        let before = self.added_words.len();
+        for word in words {
+            let word = word.trim().to_lowercase();
+            if word.is_empty() || self.valid_words.contains_key(&word) {
+                continue;
+            }
+            self.added_words.entry(word).or_insert(WordData {
+                extensions: 0,
+                rel_freq: 0.0,
+                objectionable: false,
+            });
+        }
+        self.added_words.len() - before
+    }
+
+    /// Makes the words added for every game valid for a game's judge
+    pub fn apply_added_words(&self, judge: &mut Judge) {
+        if !self.added_words.is_empty() {
+            judge.extend_overlay(self.added_words.keys());
+        }
+    }
+
+    /// Marks a code as taken, such as by a game restored from the database
+    pub fn reserve_code(&mut self, code: &str) {
+        self.allocated_room_codes.insert(code.to_lowercase());
+    }
+
     // TODO: Reclaim codes after use
     pub fn get_free_code(&mut self) -> String {
         let mut word = self.rand_code();
@@ -54,53 +202,321 @@ impl WordDB {
     }
 }
 
-pub fn read_defs() -> WordDB {
-    println!("Loading word definitions...");
+pub fn read_defs() -> Result<WordDB, DefsError> {
+    tracing::info!("Loading word definitions...");
 
     let defs_file = option_env!("TR_DEFS_FILE").unwrap_or_else(|| "/truncate/defs.db");
 
-    let mut valid_words = HashMap::new();
-    let lines = TRUNCATE_DICT.lines();
+    let valid_words = read_builtin_wordlist()?;
+    let other_languages = read_other_languages()?;
+
+    let word_db_connection = open_defs(defs_file)?;
+    if word_db_connection.is_some() {
+        tracing::info!(defs_file, "Connected to the word definition database");
+    } else {
+        tracing::warn!(defs_file, "No word definitions available. Set a TR_DEFS_FILE environment variable to point to a word db.");
+    }
+
+    Ok(WordDB::new(
+        word_db_connection,
+        valid_words,
+        other_languages,
+    ))
+}
+
+/// Parses the English dictionary, or the small development one when asked for in a debug build
+fn read_builtin_wordlist() -> Result<WordDict, DefsError> {
+    if env::var("DEV_DICTIONARY").is_ok_and(|s| s == "true") {
+        #[cfg(debug_assertions)]
+        {
+            tracing::warn!("Using the built-in development dictionary — most words won't be valid");
+            return parse_wordlist("development", DEV_DICT);
+        }
+        #[cfg(not(debug_assertions))]
+        tracing::warn!(
+            "DEV_DICTIONARY is only available in debug builds, using the full dictionary"
+        );
+    }
 
-    for line in lines {
+    parse_wordlist("English", TRUNCATE_DICT)
+}
+
+/// Opens the definitions database if it exists, checking that it has the `words` table we query.
+/// SQLite would otherwise happily create an empty database at a mistyped path.
+fn open_defs(defs_file: &str) -> Result<Option<Connection>, DefsError> {
+    if !Path::new(defs_file).exists() {
+        return Ok(None);
+    }
+
+    let malformed = |source| DefsError::MalformedDefinitions {
+        path: defs_file.to_string(),
+        source,
+    };
+    let conn = Connection::open_with_flags(defs_file, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(malformed)?;
+    conn.prepare("SELECT definitions FROM words LIMIT 1")
+        .map_err(malformed)?;
+
+    Ok(Some(conn))
+}
+
+/// Parses the `<word> <extensions> <frequency>` lines output by the dict builder
+fn parse_wordlist(dictionary: &str, contents: &str) -> Result<WordDict, DefsError> {
+    let mut words = HashMap::new();
+
+    for (index, line) in contents.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let malformed = || DefsError::MalformedWordlist {
+            dictionary: dictionary.to_string(),
+            line: index + 1,
+            content: line.to_string(),
+        };
         let mut chunks = line.split(' ');
 
-        let mut word = chunks.next().unwrap().to_string();
-        let objectionable = word.chars().next() == Some('*');
+        let mut word = chunks.next().ok_or_else(malformed)?.to_string();
+        let objectionable = word.starts_with('*');
         if objectionable {
             word.remove(0);
         }
+        let extensions = chunks
+            .next()
+            .and_then(|c| c.parse().ok())
+            .ok_or_else(malformed)?;
+        let rel_freq = chunks
+            .next()
+            .and_then(|c| c.parse().ok())
+            .ok_or_else(malformed)?;
 
-        valid_words.insert(
+        words.insert(
             word,
             WordData {
-                extensions: chunks.next().unwrap().parse().unwrap(),
-                rel_freq: chunks.next().unwrap().parse().unwrap(),
+                extensions,
+                rel_freq,
                 objectionable,
             },
         );
     }
 
-    let word_db_connection = Connection::open(defs_file).ok();
-    if word_db_connection.is_some() {
-        println!("Connected to the word definition database at {defs_file}");
-    } else {
-        println!("No word definitions available at {defs_file}. Set a TR_DEFS_FILE environment variable to point to a word db.");
+    if words.is_empty() {
+        return Err(DefsError::EmptyWordlist {
+            dictionary: dictionary.to_string(),
+        });
     }
 
-    let room_codes: Vec<_> = valid_words
-        .iter()
-        .filter(|(word, data)| word.len() < 6 && !data.objectionable)
-        .map(|(word, _)| word)
-        .cloned()
-        .collect();
+    Ok(words)
+}
 
-    println!("There are {} room codes available", room_codes.len());
+/// Loads any `final_wordlist_<language>.txt` files from the `TR_DICTS_DIR` directory
+fn read_other_languages() -> Result<HashMap<String, WordDict>, DefsError> {
+    let mut dictionaries = HashMap::new();
+    let Ok(dir) = env::var("TR_DICTS_DIR") else {
+        return Ok(dictionaries);
+    };
+    let Ok(files) = fs::read_dir(&dir) else {
+        tracing::warn!(%dir, "Couldn't read the dictionaries directory");
+        return Ok(dictionaries);
+    };
 
-    WordDB {
-        conn: word_db_connection,
-        room_codes,
-        valid_words,
-        allocated_room_codes: HashSet::new(),
+    for file in files.flatten() {
+        let name = file.file_name().to_string_lossy().to_string();
+        let Some(language) = name
+            .strip_prefix("final_wordlist_")
+            .and_then(|n| n.strip_suffix(".txt"))
+        else {
+            continue;
+        };
+
+        match fs::read_to_string(file.path()) {
+            Ok(contents) => {
+                let words = parse_wordlist(language, &contents)?;
+                tracing::info!(language, words = words.len(), "Loaded dictionary");
+                dictionaries.insert(language.to_string(), words);
+            }
+            Err(e) => tracing::warn!(language, "Couldn't read the dictionary: {e}"),
+        }
+    }
+
+    Ok(dictionaries)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// A word database without definitions, holding only the given words
+    pub(crate) fn word_db(english: &[&str], other_languages: &[(&str, &[&str])]) -> WordDB {
+        let dictionary = |words: &[&str]| -> WordDict {
+            words
+                .iter()
+                .map(|word| {
+                    (
+                        word.to_string(),
+                        WordData {
+                            extensions: 0,
+                            rel_freq: 0.0,
+                            objectionable: false,
+                        },
+                    )
+                })
+                .collect()
+        };
+
+        WordDB::new(
+            None,
+            dictionary(english),
+            other_languages
+                .iter()
+                .map(|(language, words)| (language.to_string(), dictionary(words)))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn dictionaries_by_language() {
+        let words = word_db(&["art"], &[("fr", &["arte"])]);
+
+        assert!(words.dictionary(None).unwrap().contains_key("art"));
+        assert!(words.dictionary(Some("fr")).unwrap().contains_key("arte"));
+        assert!(!words.dictionary(Some("fr")).unwrap().contains_key("art"));
+        assert!(words.dictionary(Some("de")).is_none());
+    }
+
+    /// A word database where DARN is objectionable in English and ZUT in French
+    fn censoring_db() -> WordDB {
+        let mut words = word_db(&["art", "darn"], &[("fr", &["zut"])]);
+        Arc::make_mut(&mut words.valid_words)
+            .get_mut("darn")
+            .unwrap()
+            .objectionable = true;
+        words
+            .other_languages
+            .get_mut("fr")
+            .unwrap()
+            .get_mut("zut")
+            .unwrap()
+            .objectionable = true;
+        words
+    }
+
+    #[test]
+    fn censors_objectionable_words() {
+        let words = censoring_db();
+
+        assert_eq!(words.censor("darn art"), "**** art");
+        assert_eq!(words.censor("Darn, ZUT!"), "****, ***!");
+        assert_eq!(words.censor("darn\nheck\tdarn"), "****\nheck\t****");
+        assert_eq!(words.censor("darn,darn"), "****,****");
+        assert_eq!(words.censor("darned art"), "darned art");
+        assert_eq!(words.censor("  "), "  ");
+    }
+
+    #[test]
+    fn cleans_chat() {
+        let words = censoring_db();
+
+        assert_eq!(words.clean_chat("  darn it \n").as_deref(), Some("**** it"));
+        assert_eq!(words.clean_chat(" \t\n"), None);
+
+        let long = "a".repeat(MAX_CHAT_LENGTH + 20);
+        let cleaned = words.clean_chat(&format!("  {long}")).unwrap();
+        assert_eq!(cleaned.chars().count(), MAX_CHAT_LENGTH);
+
+        // Cutting a message off can leave trailing space to trim
+        let spaced = format!("{} b", "a".repeat(MAX_CHAT_LENGTH - 1));
+        assert_eq!(
+            words.clean_chat(&spaced).unwrap(),
+            "a".repeat(MAX_CHAT_LENGTH - 1)
+        );
+    }
+
+    #[test]
+    fn added_words_leave_the_dictionary_shared() {
+        let mut words = word_db(&["art"], &[]);
+        let snapshot = Arc::clone(&words.valid_words);
+
+        let added = words.merge_words(&["Qat".into(), "art".into(), " ".into(), "qat".into()]);
+        assert_eq!(added, 1);
+        assert!(Arc::ptr_eq(&snapshot, &words.valid_words));
+        assert!(!words.valid_words.contains_key("qat"));
+
+        let mut judge = Judge::default();
+        words.apply_added_words(&mut judge);
+        assert!(judge.overlay_dictionary.contains_key("qat"));
+        assert!(!judge.overlay_dictionary.contains_key("art"));
+    }
+
+    #[test]
+    fn parses_wordlists() {
+        let words = parse_wordlist("test", "art 12 0.5\n\n*darn 0 0.25\n").unwrap();
+
+        assert_eq!(words.len(), 2);
+        assert_eq!(words["art"].extensions, 12);
+        assert_eq!(words["art"].rel_freq, 0.5);
+        assert!(!words["art"].objectionable);
+        assert!(words["darn"].objectionable);
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    fn parses_the_development_wordlist() {
+        assert!(parse_wordlist("development", DEV_DICT).is_ok());
+    }
+
+    #[test]
+    fn rejects_empty_wordlists() {
+        assert!(matches!(
+            parse_wordlist("test", "\n  \n"),
+            Err(DefsError::EmptyWordlist { dictionary }) if dictionary == "test"
+        ));
+    }
+
+    #[test]
+    fn rejects_malformed_wordlists() {
+        for (contents, bad_line) in [
+            ("art 12 0.5\nbig 3\n", 2),
+            ("art twelve 0.5\n", 1),
+            ("art 12 0.5\n\nbig 3 often\n", 3),
+        ] {
+            match parse_wordlist("test", contents) {
+                Err(DefsError::MalformedWordlist { line, .. }) => {
+                    assert_eq!(line, bad_line, "{contents:?}")
+                }
+                other => panic!("{contents:?} parsed as {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn opens_definitions_only_when_usable() {
+        let dir = env::temp_dir();
+
+        // Without a file, the server runs without definitions
+        let missing = dir.join(format!("missing-{}.db", uuid::Uuid::new_v4()));
+        assert!(open_defs(missing.to_str().unwrap()).unwrap().is_none());
+
+        // A file that isn't a definitions database is reported rather than ignored
+        let garbage = dir.join(format!("garbage-{}.db", uuid::Uuid::new_v4()));
+        fs::write(&garbage, "not a database").unwrap();
+        let result = open_defs(garbage.to_str().unwrap());
+        fs::remove_file(&garbage).unwrap();
+        assert!(matches!(
+            result,
+            Err(DefsError::MalformedDefinitions { .. })
+        ));
+
+        // As is a database without the words table
+        let empty = dir.join(format!("empty-{}.db", uuid::Uuid::new_v4()));
+        Connection::open(&empty)
+            .unwrap()
+            .execute("CREATE TABLE other (id INTEGER)", [])
+            .unwrap();
+        let result = open_defs(empty.to_str().unwrap());
+        fs::remove_file(&empty).unwrap();
+        assert!(matches!(
+            result,
+            Err(DefsError::MalformedDefinitions { .. })
+        ));
     }
 }
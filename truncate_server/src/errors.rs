@@ -1,3 +1,5 @@
+use std::net::SocketAddr;
+
 use thiserror::Error;
 use uuid::Uuid;
 
@@ -15,4 +17,32 @@ pub enum TruncateServerError {
     PuzzleComplete,
     #[error("something about this request was malformed")]
     BadRequest,
+    #[error(transparent)]
+    BackgroundTask(#[from] tokio::task::JoinError),
+}
+
+/// Problems handling a player's message. These are reported back to the player
+/// as a `GenericError`, rather than ending their connection.
+#[derive(Error, Debug)]
+pub enum ServerError {
+    #[error("You aren't in a game")]
+    NotInGame,
+    #[error("You aren't a player in this game")]
+    NotAPlayer,
+    #[error("Nothing is stored for player {0} in this game")]
+    UnknownPlayer(usize),
+    #[error("The game has already started")]
+    GameStarted,
+    #[error("Those rules aren't available for custom games")]
+    UnavailableRules,
+    #[error("No dictionary is available for the language {0:?}")]
+    UnknownLanguage(String),
+    #[error("This message has already been handled")]
+    ReplayedNonce,
+    #[error("Something went wrong on our end, please try again")]
+    StorageFailed,
+    #[error("no connection is open to {0}")]
+    NotConnected(SocketAddr),
+    #[error("the connection to {0} has closed")]
+    ConnectionClosed(SocketAddr),
 }
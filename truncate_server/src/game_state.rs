@@ -1,22 +1,61 @@
 use instant::Duration;
 use parking_lot::{Mutex, MutexGuard};
 use serde::{Deserialize, Serialize};
-use std::{net::SocketAddr, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    net::SocketAddr,
+    sync::Arc,
+};
 use truncate_core::{
-    board::{Board, Coordinate},
-    game::Game,
-    generation::{ArtifactType, BoardParams},
-    messages::{GameMessage, GamePlayerMessage, GameStateMessage, LobbyPlayerMessage},
+    board::{Board, Coordinate, MAX_BOARD_DIMENSION},
+    error::GamePlayError,
+    game::{now, Game, GameOutcome},
+    generation::{get_game_verification, ArtifactType, BoardParams},
+    messages::{
+        GameCatchUpMessage, GameEndReason, GameMessage, GamePlayerMessage, GameResult,
+        GameStateMessage, LobbyPlayerMessage,
+    },
     moves::Move,
+    player::Hand,
     reporting::Change,
     rules::GameRules,
 };
+use uuid::Uuid;
+
+use crate::{
+    definitions::WordDB,
+    errors::ServerError,
+    storage::{daily::encode_moves, game_history::FinishedGameRecord, recordings::GameRecording},
+};
+
+/// How many turns back a rejoining player can be caught up from,
+/// before they are sent the full game state instead
+const CATCH_UP_TURNS: usize = 16;
+
+/// Live games are drawn once this many turns pass without any progress,
+/// rather than being left open forever
+const STALEMATE_TURNS: u32 = 60;
+
+/// What each player could see at the end of a turn
+struct TurnViews {
+    turn: u32,
+    boards: Vec<Board>,
+}
 
-use crate::definitions::WordDB;
+/// How the server responds to a player who keeps sending illegal moves
+#[derive(Debug, Clone, Copy)]
+pub struct RejectedMoveLimit {
+    /// Consecutive rejected moves before the server steps in
+    pub max_consecutive: usize,
+    /// Whether the player forfeits once the limit is reached, rather than only being told
+    pub forfeit: bool,
+}
 
 #[derive(Debug, Clone)]
 pub struct Player {
     pub socket: Option<SocketAddr>,
+    /// The logged in account in this seat, used to record the game in their history
+    pub account: Option<Uuid>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -28,21 +67,117 @@ pub struct PlayerClaims {
 pub struct GameManager {
     pub game_id: String,
     pub players: Vec<Player>,
+    /// Sockets watching the game, which aren't counted towards the player cap
+    pub spectators: Vec<Player>,
     pub core_game: Game,
     pub effective_day: u32,
+    pub created_at: u64,
+    /// Disconnected players who will forfeit unless they rejoin,
+    /// mapped to an id for that specific disconnection
+    pub pending_forfeits: HashMap<usize, u64>,
+    pub disconnections: u64,
+    pub paused_for_disconnect: bool,
+    /// How the game was decided, once it has a winner
+    pub end_reason: GameEndReason,
+    /// Set once the finished game has been handed off to be stored in each player's history
+    history_recorded: bool,
+    /// The game as it started, which recordings are replayed from.
+    /// Unknown for games restored after a restart, which are never recorded.
+    starting_savegame: Option<String>,
+    /// Every move the game has accepted, in order
+    played_moves: Vec<Move>,
+    /// Set once the finished game has been handed off to be recorded
+    recording_taken: bool,
+    /// Recent per-player views, which rejoining players are caught up from
+    turn_views: VecDeque<TurnViews>,
+    pub rejected_move_limit: Option<RejectedMoveLimit>,
+    /// Illegal moves each player has sent since their last legal one
+    rejected_moves: HashMap<usize, usize>,
 }
 
 impl GameManager {
     pub fn new(game_id: String, effective_day: u32) -> Self {
-        let game = Game::new(9, 9, None, GameRules::latest(Some(effective_day)).1);
+        let game = Game::new(9, 9, None, default_rules(effective_day));
         // let game = Game::new(9, 9, None, GameRules::tuesday());
 
         Self {
             game_id,
             players: vec![],
+            spectators: vec![],
             core_game: game,
             effective_day,
+            created_at: now(),
+            pending_forfeits: HashMap::new(),
+            disconnections: 0,
+            paused_for_disconnect: false,
+            end_reason: GameEndReason::Board,
+            history_recorded: false,
+            starting_savegame: None,
+            played_moves: vec![],
+            recording_taken: false,
+            turn_views: VecDeque::new(),
+            rejected_move_limit: None,
+            rejected_moves: HashMap::new(),
+        }
+    }
+
+    /// Restores a game that was persisted before a restart.
+    /// Players have no socket until they reconnect with `RejoinGame`, so every player of a
+    /// running game is pending a forfeit, for which the caller needs to start the timers.
+    pub fn rehydrate(game_id: String, effective_day: u32, core_game: Game) -> Self {
+        let created_at = core_game.started_at.unwrap_or_else(now);
+
+        let mut game_manager = Self {
+            game_id,
+            players: core_game
+                .players
+                .iter()
+                .map(|_| Player {
+                    socket: None,
+                    account: None,
+                })
+                .collect(),
+            spectators: vec![],
+            core_game,
+            effective_day,
+            created_at,
+            pending_forfeits: HashMap::new(),
+            disconnections: 0,
+            paused_for_disconnect: false,
+            end_reason: GameEndReason::Board,
+            history_recorded: false,
+            starting_savegame: None,
+            played_moves: vec![],
+            recording_taken: false,
+            turn_views: VecDeque::new(),
+            rejected_move_limit: None,
+            rejected_moves: HashMap::new(),
+        };
+
+        if game_manager.core_game.started_at.is_some() && !game_manager.core_game.is_over() {
+            for player_index in 0..game_manager.players.len() {
+                game_manager.disconnections += 1;
+                game_manager
+                    .pending_forfeits
+                    .insert(player_index, game_manager.disconnections);
+            }
+        }
+
+        game_manager
+    }
+
+    /// Milliseconds until the next player runs out of time, as of now,
+    /// for scheduling a check on the game's timeout.
+    pub fn next_timeout_ms(&self) -> Option<i128> {
+        if self.core_game.is_over() {
+            return None;
+        }
+        let next_player = &self.core_game.players[self.core_game.next_player?];
+        let mut time_remaining = next_player.time_remaining?;
+        if let Some(turn_starts) = next_player.turn_starts_no_later_than {
+            time_remaining -= time::Duration::seconds(now().saturating_sub(turn_starts) as i64);
         }
+        Some(time_remaining.whole_milliseconds())
     }
 
     pub fn get_player_index(&self, player_addr: SocketAddr) -> Option<usize> {
@@ -58,9 +193,9 @@ impl GameManager {
         }
     }
 
-    pub fn add_player(&mut self, player: Player, name: String) -> Result<usize, ()> {
+    pub fn add_player(&mut self, player: Player, name: String) -> Result<usize, ServerError> {
         if self.core_game.started_at.is_some() {
-            return Err(()); // TODO: Error types
+            return Err(ServerError::GameStarted);
         }
         // TODO: Check player #
         self.core_game.add_player(name);
@@ -68,29 +203,214 @@ impl GameManager {
         Ok(self.players.len() - 1)
     }
 
-    pub fn reconnect_player(&mut self, socket: SocketAddr, index: usize) -> Result<(), ()> {
+    pub fn add_spectator(&mut self, spectator: Player) {
+        if !self.spectators.iter().any(|s| s.socket == spectator.socket) {
+            self.spectators.push(spectator);
+        }
+    }
+
+    pub fn is_spectator(&self, socket: SocketAddr) -> bool {
+        self.spectators.iter().any(|s| s.socket == Some(socket))
+    }
+
+    pub fn reconnect_player(
+        &mut self,
+        socket: SocketAddr,
+        index: usize,
+        account: Option<Uuid>,
+    ) -> Result<(), ServerError> {
         match self.players.get_mut(index) {
             Some(existing_player) => {
                 existing_player.socket = Some(socket);
+                if account.is_some() {
+                    existing_player.account = account;
+                }
+                self.pending_forfeits.remove(&index);
                 Ok(())
             }
             None => {
-                eprintln!("Couldn't reconnect player. Nothing stored for player {index}");
-                Err(())
+                tracing::warn!(room_code = %self.game_id, player_index = index, "Couldn't reconnect player, as nothing is stored for them");
+                Err(ServerError::UnknownPlayer(index))
             }
         }
     }
 
-    pub fn rename_player(&mut self, socket: SocketAddr, name: String) -> Result<(), ()> {
+    /// Detaches a closed socket from the game. If it belonged to a player in a running game,
+    /// returns their index and an id for this disconnection, to be passed to `forfeit` later.
+    pub fn disconnect(&mut self, socket: SocketAddr) -> Option<(usize, u64)> {
+        self.spectators.retain(|s| s.socket != Some(socket));
+
+        let player_index = self.get_player_index(socket)?;
+        self.players[player_index].socket = None;
+
+        if self.core_game.started_at.is_none() || self.core_game.is_over() {
+            return None;
+        }
+
+        self.disconnections += 1;
+        self.pending_forfeits
+            .insert(player_index, self.disconnections);
+        Some((player_index, self.disconnections))
+    }
+
+    /// Periodic games run everyone's clock at once, so they pause while anyone is disconnected
+    pub fn pause_for_disconnect(
+        &mut self,
+        words: Arc<Mutex<WordDB>>,
+    ) -> Vec<(&Player, GameMessage)> {
+        let periodic = matches!(
+            self.core_game.rules.timing,
+            truncate_core::rules::Timing::Periodic { .. }
+        );
+        if !periodic || self.core_game.paused {
+            return vec![];
+        }
+
+        self.paused_for_disconnect = true;
+        self.pause(words)
+    }
+
+    /// Unpauses a game paused by `pause_for_disconnect`, once every player is back
+    pub fn resume_after_disconnect(
+        &mut self,
+        words: Arc<Mutex<WordDB>>,
+    ) -> Vec<(&Player, GameMessage)> {
+        if !self.paused_for_disconnect || !self.pending_forfeits.is_empty() {
+            return vec![];
+        }
+
+        self.paused_for_disconnect = false;
+        self.unpause(words)
+    }
+
+    /// Resigns a player who didn't return after the given disconnection
+    pub fn forfeit(
+        &mut self,
+        player_index: usize,
+        disconnection: u64,
+    ) -> Vec<(&Player, GameMessage)> {
+        if self.pending_forfeits.get(&player_index) != Some(&disconnection) {
+            return vec![];
+        }
+        self.pending_forfeits.remove(&player_index);
+
+        if self.core_game.is_over() {
+            return vec![];
+        }
+
+        if self.paused_for_disconnect && self.pending_forfeits.is_empty() {
+            self.paused_for_disconnect = false;
+            self.core_game.unpause();
+        }
+
+        self.resign_player(
+            player_index,
+            GameEndReason::Forfeited {
+                player: player_index as u64,
+            },
+        )
+    }
+
+    /// Whether the game can be reaped: nobody in it is connected, nobody is due back from a
+    /// disconnection, and it was last active (or created, if never active) no later than `cutoff`
+    pub fn is_abandoned(
+        &self,
+        peers: &HashSet<SocketAddr>,
+        last_active: Option<u64>,
+        cutoff: u64,
+    ) -> bool {
+        let connected = self
+            .players
+            .iter()
+            .chain(&self.spectators)
+            .any(|p| p.socket.is_some_and(|socket| peers.contains(&socket)));
+        let reconnecting = !self.pending_forfeits.is_empty();
+        let last_active = last_active.unwrap_or(self.created_at);
+
+        !connected && !reconnecting && last_active <= cutoff
+    }
+
+    /// Each logged in player's result, once the game has finished.
+    /// Only returned the first time, so that a game is never stored in someone's history twice.
+    pub fn take_finished_records(&mut self) -> Vec<FinishedGameRecord> {
+        let Some(outcome) = self.core_game.outcome() else {
+            return vec![];
+        };
+        if self.history_recorded {
+            return vec![];
+        }
+        self.history_recorded = true;
+
+        self.players
+            .iter()
+            .enumerate()
+            .filter_map(|(player_index, player)| {
+                let result = match outcome {
+                    GameOutcome::Winner(winner) if winner == player_index => GameResult::Won,
+                    GameOutcome::Winner(_) => GameResult::Lost,
+                    GameOutcome::Draw => GameResult::Drawn,
+                };
+
+                Some(FinishedGameRecord {
+                    player_id: player.account?,
+                    opponents: self
+                        .core_game
+                        .players
+                        .iter()
+                        .filter(|opponent| opponent.index != player_index)
+                        .map(|opponent| opponent.name.clone())
+                        .collect(),
+                    result,
+                    end_reason: self.end_reason.clone(),
+                })
+            })
+            .collect()
+    }
+
+    /// The finished game's moves and result, for appending to the recordings file.
+    /// Only returned the first time, so that a game is never recorded twice.
+    pub fn take_recording(&mut self) -> Option<GameRecording> {
+        if !self.core_game.is_over() || self.recording_taken {
+            return None;
+        }
+        self.recording_taken = true;
+
+        Some(GameRecording {
+            room_code: self.game_id.clone(),
+            finished_at: now(),
+            start: self.starting_savegame.clone()?,
+            moves: encode_moves(&self.played_moves),
+            winner: self.core_game.winner,
+            drawn: self.core_game.drawn,
+            end_reason: self.end_reason.clone(),
+            final_verification: get_game_verification(&self.core_game),
+        })
+    }
+
+    pub fn rename_player(&mut self, socket: SocketAddr, name: String) -> Result<(), ServerError> {
         if let Some(player_index) = self.get_player_index(socket) {
             self.core_game.players[player_index].name = name;
             Ok(())
         } else {
-            eprintln!("Couldn't rename player. Nothing stored for player {socket}");
-            Err(())
+            tracing::warn!(room_code = %self.game_id, player_addr = %socket, "Couldn't rename player, as nothing is stored for them");
+            Err(ServerError::NotAPlayer)
         }
     }
 
+    pub fn recolor_player(
+        &mut self,
+        socket: SocketAddr,
+        color: (u8, u8, u8),
+    ) -> Result<(), String> {
+        let Some(player_index) = self.get_player_index(socket) else {
+            tracing::warn!(room_code = %self.game_id, player_addr = %socket, "Couldn't recolor player, as nothing is stored for them");
+            return Err("You aren't a player in this game".into());
+        };
+        self.core_game
+            .recolor_player(player_index, color)
+            .map_err(|e| e.to_string())
+    }
+
     pub fn player_list(&self) -> Vec<LobbyPlayerMessage> {
         self.core_game
             .players
@@ -107,41 +427,29 @@ impl GameManager {
         self.core_game.board = board;
     }
 
+    /// Applies the lobby-editable parts of the requested rules,
+    /// rejecting combinations that custom games don't support
+    /// and languages that the server has no dictionary for.
+    pub fn edit_rules(&mut self, rules: GameRules, words: &WordDB) -> Result<(), ServerError> {
+        let Some(rules) = self.core_game.rules.with_lobby_settings(&rules) else {
+            return Err(ServerError::UnavailableRules);
+        };
+        if let Some(language) = &rules.language {
+            if words.dictionary(Some(language)).is_none() {
+                return Err(ServerError::UnknownLanguage(language.clone()));
+            }
+        }
+        self.core_game.change_rules(rules);
+        Ok(())
+    }
+
     pub fn game_msg(
         &self,
         player_index: usize,
         word_map: Option<&MutexGuard<'_, WordDB>>,
     ) -> GameStateMessage {
         let (board, mut changes) = self.core_game.filter_game_to_player(player_index);
-
-        if let Some(definitions) = word_map {
-            for battle in changes.iter_mut().filter_map(|change| match change {
-                Change::Battle(battle) => Some(battle),
-                _ => None,
-            }) {
-                for word in &mut battle
-                    .attackers
-                    .iter_mut()
-                    .filter(|w| w.valid == Some(true))
-                {
-                    if let Some(meanings) = definitions.get_word(&word.resolved_word.to_lowercase())
-                    {
-                        word.meanings = Some(meanings.clone());
-                    }
-                }
-
-                for word in &mut battle
-                    .defenders
-                    .iter_mut()
-                    .filter(|w| w.valid == Some(true))
-                {
-                    if let Some(meanings) = definitions.get_word(&word.resolved_word.to_lowercase())
-                    {
-                        word.meanings = Some(meanings.clone());
-                    }
-                }
-            }
-        }
+        add_definitions(&mut changes, word_map);
 
         let hand = self
             .core_game
@@ -172,7 +480,129 @@ impl GameManager {
             game_ends_at: self.core_game.game_ends_at,
             paused: self.core_game.paused,
             remaining_turns,
+            read_only: false,
+            turn: self.core_game.turn_count,
+            battle_delay: Some(self.core_game.rules.battle_delay),
+            challenge_mode: self.core_game.rules.challenge_mode,
+            hand_capacity: self.core_game.rules.hand_capacity,
+        }
+    }
+
+    /// Stores what every player can see now, so that they can later be caught up from this turn
+    fn record_turn_views(&mut self) {
+        let boards = (0..self.players.len())
+            .map(|player_index| self.core_game.filter_game_to_player(player_index).0)
+            .collect();
+
+        self.turn_views.push_back(TurnViews {
+            turn: self.core_game.turn_count,
+            boards,
+        });
+        while self.turn_views.len() > CATCH_UP_TURNS {
+            self.turn_views.pop_front();
+        }
+    }
+
+    /// The squares that changed in a player's view since the given turn,
+    /// or `None` if that turn is too old and the full state must be sent.
+    /// Whole squares are diffed rather than replaying each turn's changes,
+    /// so battles and fog lifting in the meantime can't desync the board.
+    pub fn catch_up_msg(&self, player_index: usize, turn: u32) -> Option<GameCatchUpMessage> {
+        if self.core_game.is_over() {
+            return None;
+        }
+
+        let previous_view = self
+            .turn_views
+            .iter()
+            .find(|views| views.turn == turn)?
+            .boards
+            .get(player_index)?;
+        let (board, _) = self.core_game.filter_game_to_player(player_index);
+        let squares = board.changed_squares(previous_view)?;
+
+        let hand = self.core_game.get_player(player_index)?.hand.clone();
+        let remaining_turns = self
+            .core_game
+            .rules
+            .max_turns
+            .map(|max| max.saturating_sub(self.core_game.turn_count as u64));
+
+        Some(GameCatchUpMessage {
+            room_code: self.game_id.clone(),
+            players: self
+                .core_game
+                .players
+                .iter()
+                .map(|p| GamePlayerMessage::new(p, &self.core_game))
+                .collect(),
+            player_number: player_index as u64,
+            next_player_number: self.core_game.next().map(|n| n as u64),
+            squares,
+            hand,
+            game_ends_at: self.core_game.game_ends_at,
+            remaining_turns,
+            paused: self.core_game.paused,
+            turn: self.core_game.turn_count,
+        })
+    }
+
+    /// The full game state with no fog, but without any player's hand
+    pub fn spectator_msg(&self, word_map: Option<&MutexGuard<'_, WordDB>>) -> GameStateMessage {
+        let mut changes: Vec<_> = self
+            .core_game
+            .recent_changes
+            .iter()
+            .filter(|change| !matches!(change, Change::Hand(_)))
+            .cloned()
+            .collect();
+        add_definitions(&mut changes, word_map);
+
+        let remaining_turns = self
+            .core_game
+            .rules
+            .max_turns
+            .map(|max| max.saturating_sub(self.core_game.turn_count as u64));
+
+        GameStateMessage {
+            room_code: self.game_id.clone(),
+            players: self
+                .core_game
+                .players
+                .iter()
+                .map(|p| GamePlayerMessage::new(p, &self.core_game))
+                .collect(),
+            player_number: 0,
+            next_player_number: self.core_game.next().map(|n| n as u64),
+            board: self.core_game.board.clone(),
+            hand: Hand(vec![]),
+            changes,
+            game_ends_at: self.core_game.game_ends_at,
+            paused: self.core_game.paused,
+            remaining_turns,
+            read_only: true,
+            turn: self.core_game.turn_count,
+            battle_delay: Some(self.core_game.rules.battle_delay),
+            challenge_mode: self.core_game.rules.challenge_mode,
+            hand_capacity: self.core_game.rules.hand_capacity,
+        }
+    }
+
+    /// Wraps the spectator view for every spectator, to be sent alongside the player messages
+    pub fn spectator_messages(
+        &self,
+        word_map: Option<&MutexGuard<'_, WordDB>>,
+        wrap: impl Fn(GameStateMessage) -> GameMessage,
+    ) -> Vec<(&Player, GameMessage)> {
+        if self.spectators.is_empty() {
+            return vec![];
         }
+
+        let message = wrap(self.spectator_msg(word_map));
+        self.spectators
+            .iter()
+            .map(|spectator| (spectator, message.clone()))
+            .collect()
     }
 
     pub fn start(&mut self) -> Vec<(Player, GameMessage)> {
@@ -198,6 +628,9 @@ impl GameManager {
                         height_resize_state: None,
                         water_level: 0.5,
                         max_attempts: 10000,
+                        config: None,
+                        enforce_symmetry: false,
+                        max_dimension: MAX_BOARD_DIMENSION,
                     },
                 );
                 self.core_game.board = rand_board.expect("Board can be resolved").board;
@@ -208,6 +641,8 @@ impl GameManager {
         self.core_game.board.trim();
 
         self.core_game.start();
+        self.starting_savegame = Some(self.core_game.to_savegame());
+        self.record_turn_views();
         let mut messages = Vec::with_capacity(self.players.len());
 
         // TODO: Maintain an index of Player to the Game player index
@@ -218,32 +653,222 @@ impl GameManager {
                 GameMessage::StartedGame(self.game_msg(player_index, None)),
             ));
         }
+        for spectator in &self.spectators {
+            messages.push((
+                spectator.clone(),
+                GameMessage::StartedGame(self.spectator_msg(None)),
+            ));
+        }
 
         messages
     }
 
-    pub fn resign(&mut self, player: SocketAddr) -> Vec<(&Player, GameMessage)> {
+    /// Resigns the player at the socket. Their reason is shown to everyone and kept
+    /// in the game history, so it is cleaned up the same way as chat.
+    pub fn resign(
+        &mut self,
+        player: SocketAddr,
+        reason: Option<String>,
+        words: &WordDB,
+    ) -> Vec<(&Player, GameMessage)> {
         if let Some(player_index) = self.get_player_index(player) {
-            self.core_game.resign_player(player_index);
-            let mut messages = Vec::with_capacity(self.players.len());
+            self.resign_player(
+                player_index,
+                GameEndReason::Resigned {
+                    player: player_index as u64,
+                    reason: reason.and_then(|reason| words.clean_chat(&reason)),
+                },
+            )
+        } else {
+            tracing::warn!(room_code = %self.game_id, player_addr = %player, "Ignoring a move from someone who isn't playing in this game");
+            vec![]
+        }
+    }
 
-            if let Some(winner) = self.core_game.winner {
-                for (player_index, player) in self.players.iter().enumerate() {
-                    let mut end_game_msg = self.game_msg(player_index, None);
-                    end_game_msg.changes = vec![];
-                    messages.push((
-                        player,
-                        GameMessage::GameEnd(self.game_msg(player_index, None), winner as u64),
-                    ));
+    fn resign_player(
+        &mut self,
+        player_index: usize,
+        end_reason: GameEndReason,
+    ) -> Vec<(&Player, GameMessage)> {
+        self.remove_from_play(player_index);
+        // With more than two players, only the resignation that ends the game decides it
+        if self.core_game.winner.is_some() {
+            self.end_reason = end_reason;
+        }
+        self.resignation_messages()
+    }
+
+    fn remove_from_play(&mut self, player_index: usize) {
+        self.core_game.resign_player(player_index);
+        // Resigning clears tiles without playing a turn, so earlier views can't be diffed against
+        self.turn_views.clear();
+    }
+
+    fn resignation_messages(&self) -> Vec<(&Player, GameMessage)> {
+        let mut messages = Vec::with_capacity(self.players.len());
+
+        if self.core_game.drawn {
+            return self.draw_messages(None);
+        }
+
+        if let Some(winner) = self.core_game.winner {
+            for (player_index, player) in self.players.iter().enumerate() {
+                let mut end_game_msg = self.game_msg(player_index, None);
+                end_game_msg.changes = vec![];
+                messages.push((
+                    player,
+                    GameMessage::GameEnd(
+                        self.game_msg(player_index, None),
+                        winner as u64,
+                        self.end_reason.clone(),
+                    ),
+                ));
+            }
+            messages.extend(self.spectator_messages(None, |msg| {
+                GameMessage::GameEnd(msg, winner as u64, self.end_reason.clone())
+            }));
+        } else {
+            // With more than two players, the game continues without the resigning player
+            for (player_index, player) in self.players.iter().enumerate() {
+                messages.push((
+                    player,
+                    GameMessage::GameUpdate(self.game_msg(player_index, None)),
+                ));
+            }
+            messages.extend(self.spectator_messages(None, GameMessage::GameUpdate));
+        }
+
+        messages
+    }
+
+    /// Ends the game as a draw for every player and spectator
+    fn draw_messages(
+        &self,
+        word_map: Option<&MutexGuard<'_, WordDB>>,
+    ) -> Vec<(&Player, GameMessage)> {
+        let mut messages = Vec::with_capacity(self.players.len());
+        for (player_index, player) in self.players.iter().enumerate() {
+            messages.push((
+                player,
+                GameMessage::GameDraw(self.game_msg(player_index, word_map)),
+            ));
+        }
+        messages.extend(self.spectator_messages(word_map, GameMessage::GameDraw));
+
+        messages
+    }
+
+    /// Tells a player their move was illegal, and steps in once they've sent too many in a row
+    /// so that a broken client can't hold the game hostage.
+    fn reject_move(
+        &mut self,
+        player_index: usize,
+        reason: GamePlayError,
+    ) -> Vec<(&Player, GameMessage)> {
+        let rejections = self.rejected_moves.entry(player_index).or_default();
+        *rejections += 1;
+        let rejections = *rejections;
+
+        let Some(limit) = self
+            .rejected_move_limit
+            .filter(|limit| rejections >= limit.max_consecutive)
+        else {
+            return vec![(
+                &self.players[player_index],
+                GameMessage::MoveRejected { reason },
+            )];
+        };
+        self.rejected_moves.remove(&player_index);
+
+        let forfeiting = limit.forfeit && !self.core_game.is_over();
+        let error = GameMessage::GameError(
+            self.game_id.clone(),
+            player_index as u64,
+            format!(
+                "Sent {rejections} illegal moves in a row{}",
+                if forfeiting {
+                    ", so you have forfeited the game"
+                } else {
+                    ""
                 }
+            ),
+        );
+
+        if forfeiting {
+            self.remove_from_play(player_index);
+            if self.core_game.winner.is_some() {
+                self.end_reason = GameEndReason::Forfeited {
+                    player: player_index as u64,
+                };
             }
+        }
+        let mut messages = vec![(&self.players[player_index], error)];
+        if forfeiting {
+            messages.extend(self.resignation_messages());
+        }
+        messages
+    }
 
-            messages
-        } else {
-            todo!("Handle missing player");
+    /// Plays a move in the core game, keeping it for the game's recording if it was made.
+    /// Words are judged against the dictionary for the language the room chose.
+    fn play_turn(&mut self, next_move: Move, words_db: &WordDB) -> Result<Option<usize>, String> {
+        let language = self.core_game.rules.language.as_deref();
+        let Some(dictionary) = words_db.dictionary(language) else {
+            return Err(
+                ServerError::UnknownLanguage(language.unwrap_or_default().to_string()).to_string(),
+            );
+        };
+
+        words_db.apply_added_words(&mut self.core_game.judge);
+
+        let turn_count = self.core_game.turn_count;
+        let overtime_player = self.core_game.any_player_is_overtime();
+        let mover = next_move.player();
+        let result =
+            self.core_game
+                .play_turn(next_move.clone(), Some(dictionary), Some(dictionary), None);
+
+        // A move sent after its player ran out of time ends the game without being played
+        let timed_out = self.core_game.is_over() && self.core_game.turn_count == turn_count;
+        if result.is_ok() && !timed_out {
+            self.played_moves.push(next_move);
+        }
+        if self.core_game.is_over() {
+            self.end_reason = if timed_out {
+                GameEndReason::TimedOut {
+                    player: overtime_player.unwrap_or(mover) as u64,
+                }
+            } else {
+                GameEndReason::Board
+            };
+        }
+
+        result
+    }
+
+    /// Ends the game if a player's clock has run out while nobody was moving,
+    /// recording that as the reason the game ended.
+    pub fn check_timeout(&mut self) {
+        if self.core_game.is_over() {
+            return;
+        }
+
+        let overtime_player = self.core_game.any_player_is_overtime();
+        self.core_game.calculate_game_over(None);
+        if let (Some(player), true) = (overtime_player, self.core_game.is_over()) {
+            self.end_reason = GameEndReason::TimedOut {
+                player: player as u64,
+            };
         }
     }
 
+    /// Re-validates a move against the server's copy of the game before applying it,
+    /// so that out-of-turn or illegal moves are rejected without touching game state.
+    fn authorize_move(&self, player_index: usize, next_move: &Move) -> Result<(), GamePlayError> {
+        self.core_game.check_turn(player_index)?;
+        self.core_game.validate_move(next_move)
+    }
+
     pub fn play(
         &mut self,
         player: SocketAddr,
@@ -251,59 +876,13 @@ impl GameManager {
         tile: char,
         words: Arc<Mutex<WordDB>>,
     ) -> Vec<(&Player, GameMessage)> {
-        let mut messages = Vec::with_capacity(self.players.len());
-
-        if let Some(player_index) = self.get_player_index(player) {
-            let words_db = words.lock();
-            match self.core_game.play_turn(
-                Move::Place {
-                    player: player_index,
-                    tile,
-                    position,
-                },
-                Some(&words_db.valid_words),
-                Some(&words_db.valid_words),
-                None,
-            ) {
-                Ok(Some(winner)) => {
-                    for (player_index, player) in self.players.iter().enumerate() {
-                        messages.push((
-                            player,
-                            GameMessage::GameEnd(
-                                self.game_msg(player_index, Some(&words_db)),
-                                winner as u64,
-                            ),
-                        ));
-                    }
-                    return messages;
-                }
-                Ok(None) => {
-                    for (player_index, player) in self.players.iter().enumerate() {
-                        messages.push((
-                            player,
-                            GameMessage::GameUpdate(self.game_msg(player_index, Some(&words_db))),
-                        ));
-                    }
-                    return messages;
-                }
-                Err(msg) => {
-                    return vec![(
-                        &self.players[player_index],
-                        GameMessage::GameError(
-                            self.game_id.clone(),
-                            player_index as u64,
-                            msg.into(),
-                        ),
-                    )]
-                }
-            }
-        } else {
-            todo!("Handle missing player");
-        }
+        self.apply_move(player, words, |player| Move::Place {
+            player,
+            tile,
+            position,
+        })
     }
 
-    // TODO: Combine method with play and pass in a `Move` type
-    // (need to solve the player lookup first)
     pub fn swap(
         &mut self,
         player: SocketAddr,
@@ -311,53 +890,102 @@ impl GameManager {
         to: Coordinate,
         words: Arc<Mutex<WordDB>>,
     ) -> Vec<(&Player, GameMessage)> {
-        let mut messages = Vec::with_capacity(self.players.len());
+        self.apply_move(player, words, |player| Move::Swap {
+            player,
+            positions: [from, to],
+        })
+    }
 
-        if let Some(player_index) = self.get_player_index(player) {
-            let words_db = words.lock();
-            match self.core_game.play_turn(
-                Move::Swap {
-                    player: player_index,
-                    positions: [from, to],
-                },
-                Some(&words_db.valid_words),
-                Some(&words_db.valid_words),
-                None,
-            ) {
-                Ok(Some(_)) => {
-                    unreachable!("Cannot win by swapping")
-                }
-                Ok(None) => {
-                    for (player_index, player) in self.players.iter().enumerate() {
-                        messages.push((
-                            player,
-                            GameMessage::GameUpdate(self.game_msg(player_index, None)),
-                        ));
-                    }
-
-                    messages
-                }
-                Err(msg) => {
-                    return vec![(
-                        &self.players[player_index],
-                        GameMessage::GameError(
-                            self.game_id.clone(),
-                            player_index as u64,
-                            msg.into(),
+    pub fn pass(
+        &mut self,
+        player: SocketAddr,
+        words: Arc<Mutex<WordDB>>,
+    ) -> Vec<(&Player, GameMessage)> {
+        self.apply_move(player, words, |player| Move::Pass { player })
+    }
+
+    /// Authorizes and plays the move built by `next_move` for whichever player is at `player`,
+    /// returning the messages that tell everyone how the game has changed.
+    /// Any move can end the game, as a player whose clock has run out loses on their next move.
+    fn apply_move(
+        &mut self,
+        player: SocketAddr,
+        words: Arc<Mutex<WordDB>>,
+        next_move: impl FnOnce(usize) -> Move,
+    ) -> Vec<(&Player, GameMessage)> {
+        let Some(player_index) = self.get_player_index(player) else {
+            tracing::warn!(room_code = %self.game_id, player_addr = %player, "Ignoring a move from someone who isn't playing in this game");
+            return vec![];
+        };
+
+        let words_db = words.lock();
+        let next_move = next_move(player_index);
+        if let Err(reason) = self.authorize_move(player_index, &next_move) {
+            return self.reject_move(player_index, reason);
+        }
+        self.rejected_moves.remove(&player_index);
+
+        let mut messages = Vec::with_capacity(self.players.len());
+        match self.play_turn(next_move, &words_db) {
+            Ok(Some(winner)) => {
+                for (player_index, player) in self.players.iter().enumerate() {
+                    messages.push((
+                        player,
+                        GameMessage::GameEnd(
+                            self.game_msg(player_index, Some(&words_db)),
+                            winner as u64,
+                            self.end_reason.clone(),
                         ),
-                    )]
+                    ));
                 }
+                messages.extend(self.spectator_messages(Some(&words_db), |msg| {
+                    GameMessage::GameEnd(msg, winner as u64, self.end_reason.clone())
+                }));
+                messages
             }
-        } else {
-            todo!("Handle missing player");
+            Ok(None) if self.core_game.drawn => self.draw_messages(Some(&words_db)),
+            Ok(None) => {
+                self.record_turn_views();
+                for (player_index, player) in self.players.iter().enumerate() {
+                    messages.push((
+                        player,
+                        GameMessage::GameUpdate(self.game_msg(player_index, Some(&words_db))),
+                    ));
+                }
+                messages.extend(self.spectator_messages(Some(&words_db), GameMessage::GameUpdate));
+                messages
+            }
+            Err(msg) => vec![(
+                &self.players[player_index],
+                GameMessage::GameError(self.game_id.clone(), player_index as u64, msg.into()),
+            )],
         }
     }
 
+    pub fn exchange(
+        &mut self,
+        player: SocketAddr,
+        tile: char,
+        words: Arc<Mutex<WordDB>>,
+    ) -> Vec<(&Player, GameMessage)> {
+        self.apply_move(player, words, |player| Move::Exchange { player, tile })
+    }
+
+    pub fn challenge(
+        &mut self,
+        player: SocketAddr,
+        position: Coordinate,
+        words: Arc<Mutex<WordDB>>,
+    ) -> Vec<(&Player, GameMessage)> {
+        self.apply_move(player, words, |player| Move::Challenge { player, position })
+    }
+
     pub fn pause(&mut self, words: Arc<Mutex<WordDB>>) -> Vec<(&Player, GameMessage)> {
         self.core_game.pause();
 
         let words_db = words.lock();
-        self.players
+        let mut messages: Vec<_> = self
+            .players
             .iter()
             .enumerate()
             .map(|(player_index, player)| {
@@ -366,14 +994,18 @@ impl GameManager {
                     GameMessage::GameTimingUpdate(self.game_msg(player_index, Some(&words_db))),
                 )
             })
-            .collect()
+            .collect();
+        messages.extend(self.spectator_messages(Some(&words_db), GameMessage::GameTimingUpdate));
+
+        messages
     }
 
     pub fn unpause(&mut self, words: Arc<Mutex<WordDB>>) -> Vec<(&Player, GameMessage)> {
         self.core_game.unpause();
 
         let words_db = words.lock();
-        self.players
+        let mut messages: Vec<_> = self
+            .players
             .iter()
             .enumerate()
             .map(|(player_index, player)| {
@@ -382,6 +1014,396 @@ impl GameManager {
                     GameMessage::GameTimingUpdate(self.game_msg(player_index, Some(&words_db))),
                 )
             })
+            .collect();
+        messages.extend(self.spectator_messages(Some(&words_db), GameMessage::GameTimingUpdate));
+
+        messages
+    }
+}
+
+/// The rules new rooms start with, which draw live games that stop making progress
+/// unless the rules already set their own limit
+fn default_rules(effective_day: u32) -> GameRules {
+    let mut rules = GameRules::latest(Some(effective_day)).1;
+    rules.stalemate_turns.get_or_insert(STALEMATE_TURNS);
+    rules
+}
+
+fn add_definitions(changes: &mut [Change], word_map: Option<&MutexGuard<'_, WordDB>>) {
+    let Some(definitions) = word_map else {
+        return;
+    };
+
+    for battle in changes.iter_mut().filter_map(|change| match change {
+        Change::Battle(battle) => Some(battle),
+        _ => None,
+    }) {
+        for word in battle
+            .attackers
+            .iter_mut()
+            .chain(battle.defenders.iter_mut())
+            .filter(|w| w.valid == Some(true))
+        {
+            if let Some(meanings) = definitions.get_word(&word.resolved_word.to_lowercase()) {
+                word.meanings = Some(meanings.clone());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use truncate_core::{bag::TileBag, board::Square, player::Player as CorePlayer, rules::Timing};
+
+    use super::*;
+    use crate::definitions::{tests::word_db, MAX_CHAT_LENGTH};
+
+    /// A started game where player 0 can place an A at (1, 3) to attack IT with ARTS
+    fn battle_manager() -> GameManager {
+        let board = Board::from_string(
+            "__ S0 X0 |0 __\n\
+             __ T0 __ __ __\n\
+             __ R0 __ __ __\n\
+             __ __ I1 __ __\n\
+             __ __ T1 |1 __",
+        );
+        let mut bag = TileBag::explicit(vec!['A'; 20], Some(1));
+        let players = vec![
+            CorePlayer::new("A".into(), 0, 7, &mut bag, None, (0, 0, 0)),
+            CorePlayer::new("B".into(), 1, 7, &mut bag, None, (0, 0, 0)),
+        ];
+        let rules = GameRules {
+            battle_delay: 0,
+            ..GameRules::generation(0)
+        };
+
+        let mut manager = GameManager::new("test".into(), 0);
+        manager.core_game = Game::from_board(board, players, rules);
+        manager.core_game.bag = bag;
+        manager.core_game.start();
+        manager.players = (0..2)
+            .map(|port| Player {
+                socket: Some(SocketAddr::from(([127, 0, 0, 1], 9000 + port))),
+                account: None,
+            })
+            .collect();
+        manager
+    }
+
+    fn place_a() -> Move {
+        Move::Place {
+            player: 0,
+            tile: 'A',
+            position: Coordinate { x: 1, y: 3 },
+        }
+    }
+
+    #[test]
+    fn moves_are_judged_in_the_room_language() {
+        let words = word_db(&["arts", "it"], &[("fr", &["it"])]);
+        let defender = Coordinate { x: 2, y: 3 };
+
+        // ARTS is an English word, so it beats IT
+        let mut english = battle_manager();
+        english.play_turn(place_a(), &words).unwrap();
+        assert!(matches!(
+            english.core_game.board.get(defender),
+            Ok(Square::Land { .. })
+        ));
+
+        // ARTS isn't in the French dictionary, so the attack fails
+        let mut french = battle_manager();
+        french.core_game.rules.language = Some("fr".into());
+        french.play_turn(place_a(), &words).unwrap();
+        assert!(matches!(
+            french.core_game.board.get(defender),
+            Ok(Square::Occupied { .. })
+        ));
+
+        // Games in a language the server no longer has can't be played on
+        let mut unknown = battle_manager();
+        unknown.core_game.rules.language = Some("de".into());
+        assert!(unknown.play_turn(place_a(), &words).is_err());
+        assert_eq!(unknown.core_game.turn_count, 0);
+    }
+
+    /// A started game on a Fischer clock that player 0 has already run out of
+    fn overtime_manager() -> GameManager {
+        let mut manager = battle_manager();
+        manager.core_game.rules.timing = Timing::Fischer {
+            base_time: 60,
+            increment: 0,
+        };
+        manager.core_game.players[0].time_remaining = Some(time::Duration::seconds(-1));
+        manager
+    }
+
+    #[test]
+    fn moves_after_the_clock_runs_out_end_the_game_on_time() {
+        let words = Arc::new(Mutex::new(word_db(&["arts", "it"], &[])));
+        let mut manager = overtime_manager();
+        let socket = manager.players[0].socket.unwrap();
+
+        let messages = manager.play(socket, Coordinate { x: 1, y: 3 }, 'A', words);
+        assert!(!messages.is_empty());
+        for (_, message) in &messages {
+            assert!(
+                matches!(
+                    message,
+                    GameMessage::GameEnd(_, 1, GameEndReason::TimedOut { player: 0 })
+                ),
+                "{message:?}"
+            );
+        }
+        assert!(manager.played_moves.is_empty());
+    }
+
+    #[test]
+    fn clocks_running_out_between_moves_end_the_game_on_time() {
+        let mut manager = overtime_manager();
+        manager.check_timeout();
+        assert_eq!(manager.core_game.winner, Some(1));
+        assert_eq!(manager.end_reason, GameEndReason::TimedOut { player: 0 });
+
+        // Clocks with time left don't end anything
+        let mut manager = battle_manager();
+        manager.check_timeout();
+        assert!(!manager.core_game.is_over());
+        assert_eq!(manager.end_reason, GameEndReason::Board);
+    }
+
+    #[test]
+    fn only_idle_games_without_anyone_around_are_abandoned() {
+        let mut manager = battle_manager();
+        let long_ago = manager.created_at.saturating_sub(60);
+        let sockets: HashSet<_> = manager.players.iter().filter_map(|p| p.socket).collect();
+
+        assert!(manager.is_abandoned(&HashSet::new(), None, manager.created_at));
+        assert!(!manager.is_abandoned(&HashSet::new(), None, long_ago));
+        assert!(!manager.is_abandoned(&HashSet::new(), Some(manager.created_at), long_ago));
+        assert!(manager.is_abandoned(&HashSet::new(), Some(long_ago), long_ago));
+
+        // Connected players and spectators keep the game around
+        assert!(!manager.is_abandoned(&sockets, None, manager.created_at));
+        let spectator = SocketAddr::from(([127, 0, 0, 1], 9100));
+        manager.add_spectator(Player {
+            socket: Some(spectator),
+            account: None,
+        });
+        assert!(!manager.is_abandoned(&HashSet::from([spectator]), None, manager.created_at));
+
+        // As does a player who still has time to come back
+        let player = manager.players[0].socket.unwrap();
+        manager.disconnect(player);
+        assert!(!manager.is_abandoned(&HashSet::new(), None, manager.created_at));
+    }
+
+    /// Sends player 0 a move with a tile they don't hold, returning the messages it produced
+    fn send_illegal_move(
+        manager: &mut GameManager,
+        words: &Arc<Mutex<WordDB>>,
+    ) -> Vec<GameMessage> {
+        let socket = manager.players[0].socket.unwrap();
+        manager
+            .play(socket, Coordinate { x: 1, y: 3 }, 'Z', Arc::clone(words))
+            .into_iter()
+            .map(|(_, message)| message)
             .collect()
     }
+
+    #[test]
+    fn illegal_moves_are_rejected_without_a_limit() {
+        let words = Arc::new(Mutex::new(word_db(&["arts", "it"], &[])));
+        let mut manager = battle_manager();
+
+        for _ in 0..10 {
+            let messages = send_illegal_move(&mut manager, &words);
+            assert!(matches!(
+                messages.as_slice(),
+                [GameMessage::MoveRejected { .. }]
+            ));
+        }
+        assert!(!manager.core_game.is_over());
+        assert_eq!(manager.core_game.turn_count, 0);
+    }
+
+    #[test]
+    fn legal_moves_reset_the_rejection_count() {
+        let words = Arc::new(Mutex::new(word_db(&["arts", "it"], &[])));
+        let mut manager = battle_manager();
+        manager.rejected_move_limit = Some(RejectedMoveLimit {
+            max_consecutive: 3,
+            forfeit: true,
+        });
+
+        for _ in 0..2 {
+            send_illegal_move(&mut manager, &words);
+        }
+        let socket = manager.players[0].socket.unwrap();
+        manager.play(socket, Coordinate { x: 1, y: 3 }, 'A', Arc::clone(&words));
+        assert!(manager.rejected_moves.is_empty());
+    }
+
+    #[test]
+    fn repeated_illegal_moves_warn_at_the_limit() {
+        let words = Arc::new(Mutex::new(word_db(&["arts", "it"], &[])));
+        let mut manager = battle_manager();
+        manager.rejected_move_limit = Some(RejectedMoveLimit {
+            max_consecutive: 3,
+            forfeit: false,
+        });
+
+        for _ in 0..2 {
+            let messages = send_illegal_move(&mut manager, &words);
+            assert!(matches!(
+                messages.as_slice(),
+                [GameMessage::MoveRejected { .. }]
+            ));
+        }
+        let messages = send_illegal_move(&mut manager, &words);
+        assert!(matches!(
+            messages.as_slice(),
+            [GameMessage::GameError(_, 0, _)]
+        ));
+        assert!(!manager.core_game.is_over());
+
+        // The count starts over after the warning
+        let messages = send_illegal_move(&mut manager, &words);
+        assert!(matches!(
+            messages.as_slice(),
+            [GameMessage::MoveRejected { .. }]
+        ));
+    }
+
+    #[test]
+    fn repeated_illegal_moves_forfeit_at_the_limit() {
+        let words = Arc::new(Mutex::new(word_db(&["arts", "it"], &[])));
+        let mut manager = battle_manager();
+        manager.rejected_move_limit = Some(RejectedMoveLimit {
+            max_consecutive: 2,
+            forfeit: true,
+        });
+
+        send_illegal_move(&mut manager, &words);
+        let messages = send_illegal_move(&mut manager, &words);
+        assert!(matches!(messages[0], GameMessage::GameError(_, 0, _)));
+        for message in &messages[1..] {
+            assert!(
+                matches!(
+                    message,
+                    GameMessage::GameEnd(_, 1, GameEndReason::Forfeited { player: 0 })
+                ),
+                "{message:?}"
+            );
+        }
+        assert_eq!(manager.core_game.winner, Some(1));
+    }
+
+    #[test]
+    fn unexpected_requests_are_reported_as_errors() {
+        let words = word_db(&["arts"], &[]);
+        let stranger = SocketAddr::from(([127, 0, 0, 1], 9200));
+        let mut manager = battle_manager();
+
+        assert!(matches!(
+            manager.add_player(
+                Player {
+                    socket: Some(stranger),
+                    account: None,
+                },
+                "C".into()
+            ),
+            Err(ServerError::GameStarted)
+        ));
+        assert!(matches!(
+            manager.reconnect_player(stranger, 5, None),
+            Err(ServerError::UnknownPlayer(5))
+        ));
+        assert!(matches!(
+            manager.rename_player(stranger, "C".into()),
+            Err(ServerError::NotAPlayer)
+        ));
+
+        let mut rules = manager.core_game.rules.clone();
+        rules.hand_capacity = 0;
+        assert!(matches!(
+            manager.edit_rules(rules, &words),
+            Err(ServerError::UnavailableRules)
+        ));
+    }
+
+    #[test]
+    fn moves_from_strangers_are_ignored() {
+        let words = Arc::new(Mutex::new(word_db(&["arts", "it"], &[])));
+        let stranger = SocketAddr::from(([127, 0, 0, 1], 9200));
+        let mut manager = battle_manager();
+        let position = Coordinate { x: 1, y: 3 };
+
+        assert!(manager
+            .play(stranger, position, 'A', Arc::clone(&words))
+            .is_empty());
+        assert!(manager
+            .swap(stranger, position, position, Arc::clone(&words))
+            .is_empty());
+        assert!(manager.pass(stranger, words).is_empty());
+        assert_eq!(manager.core_game.turn_count, 0);
+    }
+
+    #[test]
+    fn resignation_reasons_are_cleaned_like_chat() {
+        let reason = |given: &str| {
+            let words = word_db(&[], &[]);
+            let mut manager = battle_manager();
+            let socket = manager.players[0].socket.unwrap();
+            manager.resign(socket, Some(given.into()), &words);
+            match &manager.end_reason {
+                GameEndReason::Resigned { player: 0, reason } => reason.clone(),
+                other => panic!("Resigning ended the game with {other:?}"),
+            }
+        };
+
+        assert_eq!(reason("  gg \n").as_deref(), Some("gg"));
+        assert_eq!(reason(" \t "), None);
+        assert_eq!(
+            reason(&"a".repeat(MAX_CHAT_LENGTH * 2)).unwrap().len(),
+            MAX_CHAT_LENGTH
+        );
+    }
+
+    #[test]
+    fn new_rooms_default_to_a_stalemate_limit() {
+        let words = word_db(&[], &[]);
+        let mut manager = GameManager::new("test".into(), 0);
+        assert_eq!(
+            manager.core_game.rules.stalemate_turns,
+            Some(STALEMATE_TURNS)
+        );
+
+        // The limit isn't a lobby setting, so edited rules keep the room's own
+        let mut rules = manager.core_game.rules.clone();
+        rules.stalemate_turns = Some(5);
+        manager.edit_rules(rules, &words).unwrap();
+        assert_eq!(
+            manager.core_game.rules.stalemate_turns,
+            Some(STALEMATE_TURNS)
+        );
+    }
+
+    #[test]
+    fn rooms_only_choose_loaded_languages() {
+        let words = word_db(&["arts"], &[("fr", &["art"])]);
+        let mut manager = GameManager::new("test".into(), 0);
+
+        let mut rules = manager.core_game.rules.clone();
+        rules.language = Some("de".into());
+        assert!(matches!(
+            manager.edit_rules(rules.clone(), &words),
+            Err(ServerError::UnknownLanguage(_))
+        ));
+        assert_eq!(manager.core_game.rules.language, None);
+
+        rules.language = Some("fr".into());
+        manager.edit_rules(rules, &words).unwrap();
+        assert_eq!(manager.core_game.rules.language.as_deref(), Some("fr"));
+    }
 }
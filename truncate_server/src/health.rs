@@ -0,0 +1,104 @@
+use std::time::Duration;
+
+use serde::Serialize;
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::{
+    http::{read_request, respond},
+    ServerState,
+};
+
+/// Readiness fails rather than waiting on a database that has stopped answering
+const DB_CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Serialize)]
+struct Readiness {
+    /// `None` when the server is running without a database
+    database: Option<bool>,
+    words_loaded: bool,
+}
+
+impl Readiness {
+    fn ready(&self) -> bool {
+        self.database != Some(false) && self.words_loaded
+    }
+}
+
+async fn check_readiness(server_state: &ServerState) -> Readiness {
+    // Migrations run before the pool is stored, so a stored pool has a migrated database
+    let database = match &server_state.truncate_db {
+        Some(pool) => {
+            let query = sqlx::query("SELECT 1;").execute(pool);
+            Some(matches!(
+                tokio::time::timeout(DB_CHECK_TIMEOUT, query).await,
+                Ok(Ok(_))
+            ))
+        }
+        None => None,
+    };
+
+    Readiness {
+        database,
+        words_loaded: !server_state.word_db.lock().valid_words.is_empty(),
+    }
+}
+
+async fn handle_request(server_state: ServerState, stream: TcpStream) {
+    // Headers are ignored, as these endpoints don't need auth
+    let Some((request, stream)) = read_request(stream).await else {
+        return;
+    };
+    let Some(path) = request.get_path() else {
+        return respond(stream, "405 Method Not Allowed", "{}".into()).await;
+    };
+
+    match path {
+        "/healthz" => respond(stream, "200 OK", "{}".into()).await,
+        "/readyz" => {
+            let readiness = check_readiness(&server_state).await;
+            let status = if readiness.ready() {
+                "200 OK"
+            } else {
+                "503 Service Unavailable"
+            };
+            let body = serde_json::to_string(&readiness).expect("Readiness should be serializable");
+            respond(stream, status, body).await
+        }
+        _ => respond(stream, "404 Not Found", "{}".into()).await,
+    }
+}
+
+/// Serves unauthenticated liveness and readiness probes, for orchestrators
+pub async fn serve_health(server_state: ServerState, addr: String) {
+    let listener = match TcpListener::bind(&addr).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            tracing::error!(%addr, "Failed to bind the health endpoint: {e}");
+            return;
+        }
+    };
+    tracing::info!(%addr, "Health endpoint listening");
+
+    while let Ok((stream, _)) = listener.accept().await {
+        tokio::spawn(handle_request(server_state.clone(), stream));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn readiness_needs_words_and_any_configured_database() {
+        let readiness = |database, words_loaded| Readiness {
+            database,
+            words_loaded,
+        };
+
+        assert!(readiness(None, true).ready());
+        assert!(readiness(Some(true), true).ready());
+        assert!(!readiness(Some(false), true).ready());
+        assert!(!readiness(None, false).ready());
+        assert!(!readiness(Some(true), false).ready());
+    }
+}
@@ -0,0 +1,137 @@
+use std::time::Duration;
+
+use tokio::{
+    io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+    net::TcpStream,
+};
+
+/// Guards against clients sending endless headers
+const MAX_HEADER_LINES: usize = 64;
+/// Guards against clients sending endless lines
+const MAX_LINE_LENGTH: u64 = 8 * 1024;
+/// Guards against clients that connect and then go quiet
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The parts of a request that the operator endpoints care about
+pub struct Request {
+    method: String,
+    path: String,
+    headers: Vec<(String, String)>,
+}
+
+impl Request {
+    /// The requested path without any trailing slash, if this is a well-formed GET request
+    pub fn get_path(&self) -> Option<&str> {
+        (self.method == "GET" && !self.path.is_empty()).then(|| self.path.trim_end_matches('/'))
+    }
+
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Reads one line, giving up on lines that are too long or too slow to arrive.
+/// An empty line means the client has closed the connection.
+async fn read_line(reader: &mut BufReader<TcpStream>) -> Option<String> {
+    let mut line = String::new();
+    let mut limited = (&mut *reader).take(MAX_LINE_LENGTH);
+    let read = limited.read_line(&mut line);
+    match tokio::time::timeout(READ_TIMEOUT, read).await {
+        Ok(Ok(0)) => Some(line),
+        Ok(Ok(_)) if line.ends_with('\n') => Some(line),
+        _ => None,
+    }
+}
+
+/// Reads the request line and headers, handing back the stream to respond on.
+/// Any body is left unread, as none of the endpoints take one.
+pub async fn read_request(stream: TcpStream) -> Option<(Request, TcpStream)> {
+    let mut reader = BufReader::new(stream);
+
+    let request_line = read_line(&mut reader).await?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let path = parts.next().unwrap_or_default().to_string();
+
+    let mut headers = vec![];
+    for _ in 0..MAX_HEADER_LINES {
+        let header = read_line(&mut reader).await?;
+        let header = header.trim();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            headers.push((name.trim().to_string(), value.trim().to_string()));
+        }
+    }
+
+    let request = Request {
+        method,
+        path,
+        headers,
+    };
+    Some((request, reader.into_inner()))
+}
+
+pub async fn respond(mut stream: TcpStream, status: &str, body: String) {
+    let response = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    _ = stream.write_all(response.as_bytes()).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::net::TcpListener;
+
+    use super::*;
+
+    /// Sends the raw bytes over a local connection, returning what the server reads from them
+    async fn parse(raw: &[u8]) -> Option<Request> {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let mut client = TcpStream::connect(listener.local_addr().unwrap())
+            .await
+            .unwrap();
+        let (server, _) = listener.accept().await.unwrap();
+
+        client.write_all(raw).await.unwrap();
+        client.shutdown().await.unwrap();
+        read_request(server).await.map(|(request, _)| request)
+    }
+
+    #[tokio::test]
+    async fn reads_get_requests() {
+        let request =
+            parse(b"GET /games/ HTTP/1.1\r\nAuthorization: Bearer abc\r\nHost: x\r\n\r\n")
+                .await
+                .unwrap();
+
+        assert_eq!(request.get_path(), Some("/games"));
+        assert_eq!(request.header("authorization"), Some("Bearer abc"));
+        assert_eq!(request.header("host"), Some("x"));
+        assert_eq!(request.header("cookie"), None);
+    }
+
+    #[tokio::test]
+    async fn only_get_requests_have_paths() {
+        let request = parse(b"POST /games HTTP/1.1\r\n\r\n").await.unwrap();
+        assert_eq!(request.get_path(), None);
+
+        let request = parse(b"").await.unwrap();
+        assert_eq!(request.get_path(), None);
+    }
+
+    #[tokio::test]
+    async fn gives_up_on_overlong_or_unfinished_lines() {
+        let mut long_line = b"GET /".to_vec();
+        long_line.extend(std::iter::repeat(b'a').take(MAX_LINE_LENGTH as usize));
+        long_line.extend(b" HTTP/1.1\r\n\r\n");
+        assert!(parse(&long_line).await.is_none());
+
+        assert!(parse(b"GET /healthz HTTP/1.1\r\nHost: x").await.is_none());
+    }
+}
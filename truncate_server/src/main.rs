@@ -1,13 +1,17 @@
+mod admin;
 mod definitions;
 mod errors;
 mod game_state;
+mod health;
+mod http;
+mod rate_limit;
 mod storage;
 
 use parking_lot::Mutex;
 use sqlx::postgres::PgPoolOptions;
 use sqlx::PgPool;
-use std::collections::HashMap;
-use std::{env, io::Error as IoError, net::SocketAddr, sync::Arc};
+use std::collections::{HashMap, HashSet};
+use std::{env, io::Error as IoError, net::SocketAddr, path::PathBuf, sync::Arc};
 use uuid::Uuid;
 
 use definitions::WordDB;
@@ -17,62 +21,61 @@ use tokio::net::{TcpListener, TcpStream};
 use tokio::sync::mpsc;
 use tokio::sync::mpsc::UnboundedSender;
 use tokio_stream::wrappers::UnboundedReceiverStream;
+use tracing::Instrument;
+use tracing_subscriber::EnvFilter;
 use tungstenite::protocol::Message;
 
 use crate::definitions::read_defs;
-use crate::game_state::{Player, PlayerClaims};
+use crate::errors::{ServerError, TruncateServerError};
+use crate::game_state::{Player, PlayerClaims, RejectedMoveLimit};
+use crate::rate_limit::RateLimiter;
 use crate::storage::accounts::{mark_changelog_read, LoginResponse};
 use crate::storage::daily;
 use crate::storage::events::create_event;
+use crate::storage::nonces::{self, NonceTracker};
+use crate::storage::{game_history, live_games, recordings};
 use game_state::GameManager;
 use storage::accounts::{self, mark_most_changelogs_read, AuthedTruncateToken};
+use truncate_core::board::MAX_BOARD_DIMENSION;
+use truncate_core::game::{Game, GameOutcome};
 use truncate_core::messages::{
-    DailyStateMessage, GameMessage, GameStateMessage, LobbyPlayerMessage, Nonce,
-    NoncedPlayerMessage, PlayerMessage,
+    DailyLeaderboardEntry, DailyStateMessage, GameCatchUpMessage, GameMessage, GameStateMessage,
+    LobbyPlayerMessage, Nonce, NoncedPlayerMessage, PlayerMessage,
 };
+use truncate_core::reporting::WordDefinition;
 
-// TODO: Also find a way to include this in the database to prevent replay if reconnecting to a different backend
-#[derive(Default)]
-pub struct NonceTracker {
-    map: HashMap<AuthedTruncateToken, HashSet<Nonce>>,
-}
-
-impl NonceTracker {
-    fn burn_nonce(&mut self, user: AuthedTruncateToken, nonce: Nonce) -> Result<(), ()> {
-        let set = self.map.entry(user).or_default();
-
-        let current_time = truncate_core::game::now();
-
-        // Reject all nonces older than an hour.
-        if nonce.generated_at < current_time.saturating_sub(60 * 60) {
-            return Err(());
-        }
-
-        if set.insert(nonce) {
-            Ok(())
-        } else {
-            Err(())
-        }
-    }
-
-    fn cleanup(&mut self, minutes: u64) {
-        let current_time = truncate_core::game::now();
-
-        self.map.values_mut().for_each(|set| {
-            set.retain(|n| n.generated_at > current_time.saturating_sub(60 * minutes));
-        })
-    }
-}
+/// How long a loaded daily leaderboard is served before querying it again
+const LEADERBOARD_CACHE_SECS: u64 = 60;
 
 #[derive(Clone)]
 pub struct ServerState {
     games: Arc<Mutex<HashMap<String, Arc<Mutex<GameManager>>>>>,
     assignments: Arc<Mutex<HashMap<SocketAddr, String>>>,
     peers: Arc<Mutex<HashMap<SocketAddr, UnboundedSender<GameMessage>>>>,
+    /// Games that have changed since they were last written to the database
+    dirty_games: Arc<Mutex<HashSet<String>>>,
+    /// When each game last changed, for reaping abandoned games
+    game_activity: Arc<Mutex<HashMap<String, u64>>>,
     word_db: Arc<Mutex<WordDB>>,
     nonces: Arc<Mutex<NonceTracker>>,
     truncate_db: Option<PgPool>,
     jwt_key: HS256Key,
+    /// How long a disconnected player has to rejoin before forfeiting
+    disconnect_grace_secs: u64,
+    /// How long a game can sit without activity or connected players before it is reaped
+    game_ttl_secs: u64,
+    /// Applied to every game, so that clients spamming illegal moves are cut off
+    rejected_move_limit: Option<RejectedMoveLimit>,
+    /// The most recent findings of the deadlock detector
+    deadlock_reports: Arc<Mutex<Vec<String>>>,
+    /// Required by admin-only messages and the admin endpoint, which are disabled without it
+    admin_secret: Option<String>,
+    /// Recently served daily leaderboards, and when they were loaded
+    daily_leaderboards: Arc<Mutex<HashMap<u32, (u64, Vec<DailyLeaderboardEntry>)>>>,
+    /// Where every finished game is appended for offline analysis, if recording is turned on
+    game_recordings: Option<PathBuf>,
+    /// The most squares a board from a custom lobby can span in either direction
+    max_board_dimension: usize,
 }
 
 impl ServerState {
@@ -84,7 +87,12 @@ impl ServerState {
         self.word_db.lock().get_free_code()
     }
 
-    fn add_new_game(&self, game_id: &String, game_state: GameManager) -> Arc<Mutex<GameManager>> {
+    fn add_new_game(
+        &self,
+        game_id: &String,
+        mut game_state: GameManager,
+    ) -> Arc<Mutex<GameManager>> {
+        game_state.rejected_move_limit = self.rejected_move_limit;
         let game = Arc::new(Mutex::new(game_state));
         let game_id = game_id.to_lowercase();
 
@@ -93,10 +101,23 @@ impl ServerState {
         game
     }
 
+    /// Records activity on a game, queueing it to be written to the database
+    fn mark_game_dirty(&self, game_id: &String) {
+        let game_id = game_id.to_lowercase();
+        self.game_activity
+            .lock()
+            .insert(game_id.clone(), truncate_core::game::now());
+
+        if self.truncate_db.is_none() && self.game_recordings.is_none() {
+            return;
+        }
+        self.dirty_games.lock().insert(game_id);
+    }
+
     fn attach_player_to_game(&self, addr: &SocketAddr, game_id: &String) {
         let mut assignments = self.assignments.lock();
         let game_id = game_id.to_lowercase();
-        println!("Assigning {addr} to {game_id}");
+        tracing::info!(player_addr = %addr, room_code = %game_id, "Assigning player to game");
         assignments.insert(*addr, game_id);
     }
 
@@ -120,18 +141,22 @@ impl ServerState {
         self.peers.lock().get(addr).cloned()
     }
 
-    fn send_to_player(&self, addr: &SocketAddr, msg: GameMessage) -> Result<(), ()> {
-        // TODO: Use a better error crate and stop using `Result<(), ()>`
-
+    fn send_to_player(&self, addr: &SocketAddr, msg: GameMessage) -> Result<(), ServerError> {
         let Some(peer_tx) = self.get_player_tx(addr) else {
-            return Err(());
+            return Err(ServerError::NotConnected(*addr));
         };
 
-        let Ok(_) = peer_tx.send(msg) else {
-            return Err(());
-        };
+        peer_tx
+            .send(msg)
+            .map_err(|_| ServerError::ConnectionClosed(*addr))
+    }
 
-        Ok(())
+    /// Sends a message to a player who may have disconnected since we last looked,
+    /// for which there is nothing to do but note that it went nowhere
+    fn send_or_log(&self, addr: &SocketAddr, msg: GameMessage) {
+        if let Err(e) = self.send_to_player(addr, msg) {
+            tracing::debug!(player_addr = %addr, "Dropping message: {e}");
+        }
     }
 }
 
@@ -141,46 +166,97 @@ async fn handle_player_msg(
     server_state: ServerState,
     connection_info_mutex: Arc<Mutex<ConnectionInfo>>,
 ) -> Result<(), tungstenite::Error> {
-    let (nonce, mut parsed_msg) = {
-        if let Ok(nonced_msg) = serde_json::from_str::<NoncedPlayerMessage>(msg.to_text().unwrap())
-        {
+    let Ok(msg_text) = msg.to_text() else {
+        tracing::debug!(player_addr = %player_addr, "Ignoring a message that isn't text");
+        return Ok(());
+    };
+
+    let (nonce, parsed_msg) = {
+        if let Ok(nonced_msg) = serde_json::from_str::<NoncedPlayerMessage>(msg_text) {
             (Some(nonced_msg.nonce), nonced_msg.message)
-        } else if let Ok(bare_msg) = serde_json::from_str::<PlayerMessage>(msg.to_text().unwrap()) {
+        } else if let Ok(bare_msg) = serde_json::from_str::<PlayerMessage>(msg_text) {
             (None, bare_msg)
         } else {
+            tracing::debug!(player_addr = %player_addr, "Ignoring unreadable message");
             return Ok(());
         }
     };
 
+    let message_type: &'static str = (&parsed_msg).into();
+    let span = tracing::info_span!("game", room_code = tracing::field::Empty, message_type);
+    if let Some(room_code) = server_state.assignments.lock().get(&player_addr) {
+        span.record("room_code", room_code.as_str());
+    }
+
+    respond_to_player_msg(
+        nonce,
+        parsed_msg,
+        player_addr,
+        server_state,
+        connection_info_mutex,
+    )
+    .instrument(span)
+    .await
+}
+
+/// Acts on a parsed message, within the span of whichever game the player is in
+async fn respond_to_player_msg(
+    nonce: Option<Nonce>,
+    mut parsed_msg: PlayerMessage,
+    player_addr: SocketAddr,
+    server_state: ServerState,
+    connection_info_mutex: Arc<Mutex<ConnectionInfo>>,
+) -> Result<(), tungstenite::Error> {
+    // Every message is logged at debug level, so this is quiet unless asked for
+    tracing::debug!(player_addr = %player_addr, "Handling message");
+
+    let expensive = matches!(
+        parsed_msg,
+        PlayerMessage::RequestStats(_)
+            | PlayerMessage::RequestDailyLeaderboard(_)
+            | PlayerMessage::RequestGameHistory(_, _)
+            | PlayerMessage::LoadReplay(_)
+            | PlayerMessage::CreateAnonymousPlayer { .. }
+    );
+    if !connection_info_mutex.lock().rate_limiter.allow(expensive) {
+        // Nonced messages aren't acknowledged, so the client will resend them later
+        server_state.send_or_log(
+            &player_addr,
+            GameMessage::GenericError("Too many requests, please slow down".into()),
+        );
+        return Ok(());
+    }
+
     if let Some(nonce) = nonce {
         let Some(connection_player) = connection_info_mutex.lock().player.clone() else {
             // Prevent processing any nonces unless the player is logged in.
             // The player will have to re-send this message after logging in.
             // PleaseLogin tells the client to login prior to re-sending their messages,
             // otherwise they'll thrash waiting for an ack on this message.
-            server_state
-                .send_to_player(&player_addr, GameMessage::PleaseLogin)
-                .unwrap();
+            server_state.send_or_log(&player_addr, GameMessage::PleaseLogin);
 
             return Ok(());
         };
 
         // Pre-acknowledge this message as "handled".
         // If the server panics, we don't want the client to keep thrashing on this message.
-        server_state
-            .send_to_player(&player_addr, GameMessage::Ack(nonce.clone()))
-            .unwrap();
-
-        let mut nm = server_state.nonces.lock();
+        server_state.send_or_log(&player_addr, GameMessage::Ack(nonce.clone()));
 
-        if nm.burn_nonce(connection_player, nonce).is_err() {
+        if nonces::burn_nonce(&server_state, connection_player, nonce)
+            .await
+            .is_err()
+        {
             // Allow some information-retrieval messages to be replayed,
             // since duplicate replies can be handled by the client,
             // and if the response from the server was lost in a disconnect
             // they may be stuck waiting for the info (e.g. waiting for DailyStats to show splash screen)
             let replayable = matches!(
                 parsed_msg,
-                RequestDefinitions(_) | RequestStats(_) | LoadReplay(_)
+                RequestDefinitions(_)
+                    | RequestStats(_)
+                    | RequestDailyLeaderboard(_)
+                    | RequestGameHistory(_, _)
+                    | LoadReplay(_)
             );
 
             if !replayable {
@@ -208,15 +284,45 @@ async fn handle_player_msg(
         }
     }
 
+    let known_turn = match &parsed_msg {
+        RejoinGameFromTurn(_, turn) => Some(*turn),
+        _ => None,
+    };
+    let replaying_daily = matches!(parsed_msg, ReplayDailyPuzzle(_, _));
+
     let player_err = |msg: String| {
-        server_state
-            .send_to_player(&player_addr, GameMessage::GenericError(msg))
-            .unwrap();
+        server_state.send_or_log(&player_addr, GameMessage::GenericError(msg));
         Ok(())
     };
 
+    let is_gameplay = matches!(
+        parsed_msg,
+        EditBoard(_)
+            | EditRules(_)
+            | EditName(_)
+            | EditColor(_)
+            | StartGame
+            | Resign(_)
+            | Place(_, _)
+            | Swap(_, _)
+            | Challenge(_)
+            | Pass
+            | Exchange(_)
+            | Rematch { .. }
+            | Pause
+            | Unpause
+    );
+    if is_gameplay {
+        let spectating = server_state
+            .get_game_by_player(&player_addr)
+            .is_some_and(|game| game.lock().is_spectator(player_addr));
+        if spectating {
+            return player_err("Spectators can't play in this game".into());
+        }
+    }
+
     match parsed_msg {
-        Ping => { /* TODO: Track pings and notify the game when players disconnect */ }
+        Ping => { /* TODO: Track pings. Closed connections are handled in `handle_disconnect` */ }
         NewGame {
             mut player_name,
             effective_day,
@@ -225,6 +331,7 @@ async fn handle_player_msg(
             let mut game = GameManager::new(new_game_id.clone(), effective_day);
 
             let connection_player = connection_info_mutex.lock().player.clone();
+            let account = connection_player.as_ref().map(|p| p.player());
             _ = create_event(&server_state, &"new_game".into(), connection_player).await;
 
             if &player_name == "___AUTO___" {
@@ -234,6 +341,7 @@ async fn handle_player_msg(
             game.add_player(
                 Player {
                     socket: Some(player_addr.clone()),
+                    account,
                 },
                 player_name.clone(),
             )
@@ -241,8 +349,10 @@ async fn handle_player_msg(
 
             let color = game.core_game.players[0].color;
             let board = game.core_game.board.clone();
+            let rules = game.core_game.rules.clone();
 
             server_state.add_new_game(&new_game_id, game);
+            server_state.mark_game_dirty(&new_game_id);
             server_state.attach_player_to_game(&player_addr, &new_game_id);
 
             let claims = Claims::with_custom_claims(
@@ -257,37 +367,39 @@ async fn handle_player_msg(
                 .authenticate(claims)
                 .expect("Claims should be serializable");
 
-            server_state
-                .send_to_player(
-                    &player_addr,
-                    GameMessage::JoinedLobby(
-                        0,
-                        new_game_id,
-                        vec![LobbyPlayerMessage {
-                            name: player_name,
-                            color,
-                            index: 0,
-                        }],
-                        board,
-                        token,
-                    ),
-                )
-                .unwrap();
+            server_state.send_or_log(
+                &player_addr,
+                GameMessage::JoinedLobby(
+                    0,
+                    new_game_id,
+                    vec![LobbyPlayerMessage {
+                        name: player_name,
+                        color,
+                        index: 0,
+                    }],
+                    board,
+                    rules,
+                    token,
+                ),
+            );
         }
         JoinGame(room_code, mut player_name, _) => {
             let code = room_code.to_ascii_lowercase();
             if let Some(existing_game) = server_state.get_game_by_code(&code) {
                 let connection_player = connection_info_mutex.lock().player.clone();
+                let account = connection_player.as_ref().map(|p| p.player());
                 _ = create_event(&server_state, &"join_game".into(), connection_player).await;
 
                 let mut game_manager = existing_game.lock();
 
-                // TODO: This is the easiest place to check for lobby capacity right now,
-                // but we'll need to reevaluate if we ever support >2 players, or spectators.
-                if game_manager.players.len() >= 2 {
+                // TODO: This is the easiest place to check for lobby capacity right now.
+                // Spectators are tracked separately, so they don't count towards it.
+                let capacity = game_manager.core_game.board.artifacts.len().max(2);
+                if game_manager.players.len() >= capacity {
                     return player_err(format!(
-                        "Room {} already has two players, cannot join",
-                        code.to_ascii_uppercase()
+                        "Room {} already has {} players, cannot join",
+                        code.to_ascii_uppercase(),
+                        capacity
                     ));
                 }
 
@@ -300,9 +412,12 @@ async fn handle_player_msg(
                 if let Ok(player_index) = game_manager.add_player(
                     Player {
                         socket: Some(player_addr.clone()),
+                        account,
                     },
                     player_name.clone(),
                 ) {
+                    server_state.mark_game_dirty(&code);
+
                     let claims = Claims::with_custom_claims(
                         PlayerClaims {
                             player_index,
@@ -315,35 +430,33 @@ async fn handle_player_msg(
                         .authenticate(claims)
                         .expect("Claims should be serializable");
 
-                    server_state
-                        .send_to_player(
-                            &player_addr,
-                            GameMessage::JoinedLobby(
-                                player_index as u64,
-                                code.clone(),
-                                game_manager.player_list(),
-                                game_manager.core_game.board.clone(),
-                                token,
-                            ),
-                        )
-                        .unwrap();
+                    server_state.send_or_log(
+                        &player_addr,
+                        GameMessage::JoinedLobby(
+                            player_index as u64,
+                            code.clone(),
+                            game_manager.player_list(),
+                            game_manager.core_game.board.clone(),
+                            game_manager.core_game.rules.clone(),
+                            token,
+                        ),
+                    );
 
                     for player in &game_manager.players {
                         let Some(socket) = player.socket else {
                             continue;
                         };
 
-                        server_state
-                            .send_to_player(
-                                &socket,
-                                GameMessage::LobbyUpdate(
-                                    player_index as u64,
-                                    code.clone(),
-                                    game_manager.player_list(),
-                                    game_manager.core_game.board.clone(),
-                                ),
-                            )
-                            .unwrap();
+                        server_state.send_or_log(
+                            &socket,
+                            GameMessage::LobbyUpdate(
+                                player_index as u64,
+                                code.clone(),
+                                game_manager.player_list(),
+                                game_manager.core_game.board.clone(),
+                                game_manager.core_game.rules.clone(),
+                            ),
+                        );
                     }
                 } else {
                     // TODO: Render a better error here
@@ -356,7 +469,41 @@ async fn handle_player_msg(
                 return player_err(format!("Room {} does not exist", code.to_ascii_uppercase()));
             }
         }
-        RejoinGame(token) => {
+        Spectate(room_code) => {
+            let code = room_code.to_ascii_lowercase();
+            let Some(existing_game) = server_state.get_game_by_code(&code) else {
+                return player_err(format!("Room {} does not exist", code.to_ascii_uppercase()));
+            };
+
+            let mut game_manager = existing_game.lock();
+            if game_manager.get_player_index(player_addr).is_some() {
+                return player_err("Players can't spectate their own game".into());
+            }
+            if game_manager.core_game.started_at.is_none() {
+                return player_err(format!(
+                    "Room {} hasn't started yet",
+                    code.to_ascii_uppercase()
+                ));
+            }
+
+            server_state.attach_player_to_game(&player_addr, &code);
+            game_manager.add_spectator(Player {
+                socket: Some(player_addr.clone()),
+                account: None,
+            });
+
+            let words_db = server_state.words();
+            let state = game_manager.spectator_msg(Some(&words_db.lock()));
+            let message = match game_manager.core_game.outcome() {
+                Some(GameOutcome::Winner(winner)) => {
+                    GameMessage::GameEnd(state, winner as u64, game_manager.end_reason.clone())
+                }
+                Some(GameOutcome::Draw) => GameMessage::GameDraw(state),
+                None => GameMessage::StartedGame(state),
+            };
+            server_state.send_or_log(&player_addr, message);
+        }
+        RejoinGame(token) | RejoinGameFromTurn(token, _) => {
             let Ok(claims) = server_state
                 .jwt_key
                 .verify_token::<PlayerClaims>(&token, None)
@@ -373,33 +520,52 @@ async fn handle_player_msg(
             let code = room_code.to_ascii_lowercase();
             if let Some(existing_game) = server_state.get_game_by_code(&code) {
                 let mut game_manager = existing_game.lock();
-                println!("Trying to reconnect player {player_index} to room {code}");
-                match game_manager.reconnect_player(player_addr.clone(), player_index) {
+                tracing::info!(player_index, room_code = %code, "Reconnecting player");
+                let account = connection_info_mutex
+                    .lock()
+                    .player
+                    .as_ref()
+                    .map(|p| p.player());
+                match game_manager.reconnect_player(player_addr.clone(), player_index, account) {
                     Ok(_) => {
                         server_state.attach_player_to_game(&player_addr, &code);
 
+                        // The rejoining player receives the resumed state below
+                        for (player, message) in
+                            game_manager.resume_after_disconnect(server_state.words())
+                        {
+                            let Some(socket) = player.socket else {
+                                continue;
+                            };
+                            if socket != player_addr {
+                                server_state.send_or_log(&socket, message);
+                            }
+                        }
+                        server_state.mark_game_dirty(&code);
+
                         if game_manager.core_game.started_at.is_some() {
-                            server_state
-                                .send_to_player(
-                                    &player_addr,
-                                    GameMessage::StartedGame(
-                                        game_manager.game_msg(player_index, Some(&words_db.lock())),
-                                    ),
-                                )
-                                .unwrap();
+                            // Players who were recently in the game only need what they missed
+                            let catch_up = known_turn
+                                .and_then(|turn| game_manager.catch_up_msg(player_index, turn));
+                            let message = match catch_up {
+                                Some(catch_up) => GameMessage::GameCatchUp(catch_up),
+                                None => GameMessage::StartedGame(
+                                    game_manager.game_msg(player_index, Some(&words_db.lock())),
+                                ),
+                            };
+                            server_state.send_or_log(&player_addr, message);
                         } else {
-                            server_state
-                                .send_to_player(
-                                    &player_addr,
-                                    GameMessage::JoinedLobby(
-                                        player_index as u64,
-                                        code.clone(),
-                                        game_manager.player_list(),
-                                        game_manager.core_game.board.clone(),
-                                        token,
-                                    ),
-                                )
-                                .unwrap();
+                            server_state.send_or_log(
+                                &player_addr,
+                                GameMessage::JoinedLobby(
+                                    player_index as u64,
+                                    code.clone(),
+                                    game_manager.player_list(),
+                                    game_manager.core_game.board.clone(),
+                                    game_manager.core_game.rules.clone(),
+                                    token,
+                                ),
+                            );
                         }
                     }
                     Err(_) => {
@@ -414,9 +580,14 @@ async fn handle_player_msg(
             }
         }
         EditBoard(board) => {
+            if let Err(e) = board.check_dimensions(server_state.max_board_dimension) {
+                return player_err(e.to_string());
+            }
+
             if let Some(existing_game) = server_state.get_game_by_player(&player_addr) {
                 let mut game_manager = existing_game.lock();
                 game_manager.edit_board(board.clone());
+                server_state.mark_game_dirty(&game_manager.game_id);
                 let player_list: Vec<_> = game_manager
                     .core_game
                     .players
@@ -429,33 +600,73 @@ async fn handle_player_msg(
                     .collect();
 
                 let Some(player_index) = game_manager.get_player_index(player_addr) else {
-                    todo!("Handle player editing the board without having a turn index");
+                    return player_err(ServerError::NotAPlayer.to_string());
                 };
 
                 for player in &game_manager.players {
                     let Some(socket) = player.socket else {
                         continue;
                     };
-                    server_state
-                        .send_to_player(
-                            &socket,
-                            GameMessage::LobbyUpdate(
-                                player_index as u64,
-                                game_manager.game_id.clone(),
-                                player_list.clone(),
-                                board.clone(),
-                            ),
-                        )
-                        .unwrap();
+                    server_state.send_or_log(
+                        &socket,
+                        GameMessage::LobbyUpdate(
+                            player_index as u64,
+                            game_manager.game_id.clone(),
+                            player_list.clone(),
+                            board.clone(),
+                            game_manager.core_game.rules.clone(),
+                        ),
+                    );
+                }
+            } else {
+                return player_err(ServerError::NotInGame.to_string());
+            }
+        }
+        EditRules(rules) => {
+            if let Some(existing_game) = server_state.get_game_by_player(&player_addr) {
+                let mut game_manager = existing_game.lock();
+                if game_manager.core_game.started_at.is_some() {
+                    return player_err("Rules can't change once the game has started".into());
+                }
+
+                let Some(player_index) = game_manager.get_player_index(player_addr) else {
+                    return player_err(ServerError::NotAPlayer.to_string());
+                };
+                if player_index != 0 {
+                    return player_err("Only the host can change the rules".into());
+                }
+
+                let edited = game_manager.edit_rules(rules, &server_state.word_db.lock());
+                if let Err(e) = edited {
+                    return player_err(e.to_string());
+                }
+                server_state.mark_game_dirty(&game_manager.game_id);
+
+                for player in &game_manager.players {
+                    let Some(socket) = player.socket else {
+                        continue;
+                    };
+                    server_state.send_or_log(
+                        &socket,
+                        GameMessage::LobbyUpdate(
+                            player_index as u64,
+                            game_manager.game_id.clone(),
+                            game_manager.player_list(),
+                            game_manager.core_game.board.clone(),
+                            game_manager.core_game.rules.clone(),
+                        ),
+                    );
                 }
             } else {
-                todo!("Handle player not being enrolled in a game");
+                return player_err(ServerError::NotInGame.to_string());
             }
         }
         EditName(name) => {
             if let Some(existing_game) = server_state.get_game_by_player(&player_addr) {
                 let mut game_manager = existing_game.lock();
                 if game_manager.rename_player(player_addr, name).is_ok() {
+                    server_state.mark_game_dirty(&game_manager.game_id);
+
                     let player_list: Vec<_> = game_manager
                         .core_game
                         .players
@@ -475,21 +686,50 @@ async fn handle_player_msg(
                         let Some(socket) = player.socket else {
                             continue;
                         };
-                        server_state
-                            .send_to_player(
-                                &socket,
-                                GameMessage::LobbyUpdate(
-                                    player_index as u64,
-                                    game_manager.game_id.clone(),
-                                    player_list.clone(),
-                                    game_manager.core_game.board.clone(),
-                                ),
-                            )
-                            .unwrap();
+                        server_state.send_or_log(
+                            &socket,
+                            GameMessage::LobbyUpdate(
+                                player_index as u64,
+                                game_manager.game_id.clone(),
+                                player_list.clone(),
+                                game_manager.core_game.board.clone(),
+                                game_manager.core_game.rules.clone(),
+                            ),
+                        );
                     }
                 }
             } else {
-                todo!("Handle player not being enrolled in a game");
+                return player_err(ServerError::NotInGame.to_string());
+            }
+        }
+        EditColor(color) => {
+            let Some(existing_game) = server_state.get_game_by_player(&player_addr) else {
+                return player_err("Can't pick a color outside of a lobby".into());
+            };
+            let mut game_manager = existing_game.lock();
+            if let Err(e) = game_manager.recolor_player(player_addr, color) {
+                return player_err(e);
+            }
+            server_state.mark_game_dirty(&game_manager.game_id);
+
+            let Some(player_index) = game_manager.get_player_index(player_addr) else {
+                unreachable!("Player just recolored themselves");
+            };
+            let player_list = game_manager.player_list();
+            for player in &game_manager.players {
+                let Some(socket) = player.socket else {
+                    continue;
+                };
+                server_state.send_or_log(
+                    &socket,
+                    GameMessage::LobbyUpdate(
+                        player_index as u64,
+                        game_manager.game_id.clone(),
+                        player_list.clone(),
+                        game_manager.core_game.board.clone(),
+                        game_manager.core_game.rules.clone(),
+                    ),
+                );
             }
         }
         StartGame => {
@@ -498,44 +738,46 @@ async fn handle_player_msg(
                 _ = create_event(&server_state, &"start_game".into(), connection_player).await;
 
                 let mut game_manager = existing_game.lock();
-                for (player, message) in game_manager.start() {
+                let messages = game_manager.start();
+
+                match &game_manager.core_game.rules.timing {
+                    truncate_core::rules::Timing::Periodic {
+                        total_time_allowance,
+                        ..
+                    } => {
+                        tokio::spawn(check_game_over(
+                            game_manager.game_id.clone(),
+                            (*total_time_allowance + 1) as i128 * 1000,
+                            server_state.clone(),
+                        ));
+                    }
+                    _ => {}
+                };
+
+                for (player, message) in messages {
                     let Some(socket) = player.socket else {
                         continue;
                     };
-
-                    let room_code = game_manager.game_id.clone();
-
-                    match &game_manager.core_game.rules.timing {
-                        truncate_core::rules::Timing::Periodic {
-                            total_time_allowance,
-                            ..
-                        } => {
-                            tokio::spawn(check_game_over(
-                                room_code,
-                                (*total_time_allowance + 1) as i128 * 1000,
-                                server_state.clone(),
-                            ));
-                        }
-                        _ => {}
-                    };
-
-                    server_state.send_to_player(&socket, message).unwrap();
+                    server_state.send_or_log(&socket, message);
                 }
+                server_state.mark_game_dirty(&game_manager.game_id);
             } else {
-                todo!("Handle player not being enrolled in a game");
+                return player_err(ServerError::NotInGame.to_string());
             }
         }
-        Resign => {
+        Resign(reason) => {
             if let Some(existing_game) = server_state.get_game_by_player(&player_addr) {
                 let mut game_manager = existing_game.lock();
-                for (player, message) in game_manager.resign(player_addr) {
+                let word_db = server_state.word_db.lock();
+                for (player, message) in game_manager.resign(player_addr, reason, &word_db) {
                     let Some(socket) = player.socket else {
                         continue;
                     };
-                    server_state.send_to_player(&socket, message).unwrap();
+                    server_state.send_or_log(&socket, message);
                 }
+                server_state.mark_game_dirty(&game_manager.game_id);
             } else {
-                todo!("Handle player not being enrolled in a game");
+                return player_err(ServerError::NotInGame.to_string());
             }
         }
         Place(position, tile) => {
@@ -547,11 +789,11 @@ async fn handle_player_msg(
                     let Some(socket) = player.socket else {
                         continue;
                     };
-                    server_state.send_to_player(&socket, message).unwrap();
+                    server_state.send_or_log(&socket, message);
                 }
-                // TODO: Error handling flow
+                server_state.mark_game_dirty(&game_manager.game_id);
             } else {
-                todo!("Handle player not being enrolled in a game");
+                return player_err(ServerError::NotInGame.to_string());
             }
         }
         Swap(from, to) => {
@@ -563,20 +805,66 @@ async fn handle_player_msg(
                     let Some(socket) = player.socket else {
                         continue;
                     };
-                    server_state.send_to_player(&socket, message).unwrap();
+                    server_state.send_or_log(&socket, message);
                 }
-                // TODO: Error handling flow
+                server_state.mark_game_dirty(&game_manager.game_id);
             } else {
-                todo!("Handle player not being enrolled in a game");
+                return player_err(ServerError::NotInGame.to_string());
             }
         }
-        Rematch => {
+        Challenge(position) => {
+            if let Some(existing_game) = server_state.get_game_by_player(&player_addr) {
+                let mut game_manager = existing_game.lock();
+                for (player, message) in
+                    game_manager.challenge(player_addr, position, server_state.words())
+                {
+                    let Some(socket) = player.socket else {
+                        continue;
+                    };
+                    server_state.send_or_log(&socket, message);
+                }
+                server_state.mark_game_dirty(&game_manager.game_id);
+            } else {
+                return player_err(ServerError::NotInGame.to_string());
+            }
+        }
+        Pass => {
+            if let Some(existing_game) = server_state.get_game_by_player(&player_addr) {
+                let mut game_manager = existing_game.lock();
+                for (player, message) in game_manager.pass(player_addr, server_state.words()) {
+                    let Some(socket) = player.socket else {
+                        continue;
+                    };
+                    server_state.send_or_log(&socket, message);
+                }
+                server_state.mark_game_dirty(&game_manager.game_id);
+            } else {
+                return player_err(ServerError::NotInGame.to_string());
+            }
+        }
+        Exchange(tile) => {
+            if let Some(existing_game) = server_state.get_game_by_player(&player_addr) {
+                let mut game_manager = existing_game.lock();
+                for (player, message) in
+                    game_manager.exchange(player_addr, tile, server_state.words())
+                {
+                    let Some(socket) = player.socket else {
+                        continue;
+                    };
+                    server_state.send_or_log(&socket, message);
+                }
+                server_state.mark_game_dirty(&game_manager.game_id);
+            } else {
+                return player_err(ServerError::NotInGame.to_string());
+            }
+        }
+        Rematch { mirror } => {
             if let Some(existing_game) = server_state.get_game_by_player(&player_addr) {
                 let connection_player = connection_info_mutex.lock().player.clone();
                 _ = create_event(&server_state, &"rematch".into(), connection_player).await;
 
                 let mut existing_game_manager = existing_game.lock();
-                if existing_game_manager.core_game.winner.is_none() {
+                if !existing_game_manager.core_game.is_over() {
                     return player_err("Cannot rematch unfinished game".into());
                 } else {
                     let new_game_id = server_state.game_code();
@@ -585,7 +873,12 @@ async fn handle_player_msg(
 
                     let mut next_board = existing_game_manager.core_game.board.clone();
                     next_board.reset();
+                    if mirror {
+                        next_board.mirror();
+                    }
                     new_game.core_game.board = next_board;
+                    // Rooms keep whatever rules their host negotiated in the lobby
+                    new_game.core_game.rules = existing_game_manager.core_game.rules.clone();
 
                     let mut next_sockets = existing_game_manager.players.clone();
                     next_sockets.rotate_left(1);
@@ -605,9 +898,13 @@ async fn handle_player_msg(
                             .expect("Failed to add player to game");
                     }
 
+                    // Spectators follow the players into the rematch
+                    new_game.spectators = std::mem::take(&mut existing_game_manager.spectators);
+
                     drop(existing_game_manager); // Done with the old game, don't accidentally use it.
 
                     let new_game = server_state.add_new_game(&new_game_id, new_game);
+                    server_state.mark_game_dirty(&new_game_id);
                     let new_game_manager = new_game.lock();
 
                     for (i, player) in new_game_manager.players.iter().enumerate() {
@@ -629,18 +926,29 @@ async fn handle_player_msg(
                             .authenticate(claims)
                             .expect("Claims should be serializable");
 
-                        server_state
-                            .send_to_player(
-                                &socket,
-                                GameMessage::JoinedLobby(
-                                    i as u64,
-                                    new_game_id.clone(),
-                                    new_game_manager.player_list(),
-                                    new_game_manager.core_game.board.clone(),
-                                    token,
-                                ),
-                            )
-                            .unwrap();
+                        server_state.send_or_log(
+                            &socket,
+                            GameMessage::JoinedLobby(
+                                i as u64,
+                                new_game_id.clone(),
+                                new_game_manager.player_list(),
+                                new_game_manager.core_game.board.clone(),
+                                new_game_manager.core_game.rules.clone(),
+                                token,
+                            ),
+                        );
+                    }
+
+                    for spectator in &new_game_manager.spectators {
+                        let Some(socket) = spectator.socket else {
+                            continue;
+                        };
+
+                        server_state.attach_player_to_game(&socket, &new_game_id);
+                        server_state.send_or_log(
+                            &socket,
+                            GameMessage::SpectatingRematch(new_game_id.clone()),
+                        );
                     }
                 }
             }
@@ -652,11 +960,12 @@ async fn handle_player_msg(
                     let Some(socket) = player.socket else {
                         continue;
                     };
-                    server_state.send_to_player(&socket, message).unwrap();
+                    server_state.send_or_log(&socket, message);
                 }
+                server_state.mark_game_dirty(&game_manager.game_id);
                 // TODO: Error handling flow
             } else {
-                todo!("Handle player not being enrolled in a game");
+                return player_err(ServerError::NotInGame.to_string());
             }
         }
         Unpause => {
@@ -666,25 +975,63 @@ async fn handle_player_msg(
                     let Some(socket) = player.socket else {
                         continue;
                     };
-                    server_state.send_to_player(&socket, message).unwrap();
+                    server_state.send_or_log(&socket, message);
                 }
+                server_state.mark_game_dirty(&game_manager.game_id);
                 // TODO: Error handling flow
             } else {
-                todo!("Handle player not being enrolled in a game");
+                return player_err(ServerError::NotInGame.to_string());
             }
         }
         RequestDefinitions(words) => {
+            // Frequencies come from the dictionary of the game being played, if any
+            let language = server_state
+                .get_game_by_player(&player_addr)
+                .and_then(|game| game.lock().core_game.rules.language.clone());
             let word_db = server_state.word_db.lock();
+            let dictionary = word_db.dictionary(language.as_deref());
             let definitions: Vec<_> = words
                 .iter()
-                .map(|word| (word.clone(), word_db.get_word(&word.to_lowercase()).clone()))
+                .map(|word| {
+                    let lowercase = word.to_lowercase();
+                    WordDefinition {
+                        word: word.clone(),
+                        meanings: word_db.get_word(&lowercase),
+                        data: dictionary.and_then(|words| words.get(&lowercase)).cloned(),
+                    }
+                })
                 .collect();
             // Don't hold the lock while sending messages
             drop(word_db);
 
-            server_state
-                .send_to_player(&player_addr, GameMessage::SupplyDefinitions(definitions))
-                .unwrap();
+            server_state.send_or_log(&player_addr, GameMessage::SupplyDefinitions(definitions));
+        }
+        Chat(text) => {
+            // Chat is only relayed between players enrolled in a game
+            let Some(existing_game) = server_state.get_game_by_player(&player_addr) else {
+                return Ok(());
+            };
+            let game_manager = existing_game.lock();
+            let Some(player_index) = game_manager.get_player_index(player_addr) else {
+                return Ok(());
+            };
+
+            let Some(text) = server_state.word_db.lock().clean_chat(&text) else {
+                return Ok(());
+            };
+
+            let message = GameMessage::ChatMessage {
+                from: player_index as u64,
+                text,
+                at: truncate_core::game::now(),
+            };
+
+            for player in game_manager.players.iter().chain(&game_manager.spectators) {
+                let Some(socket) = player.socket else {
+                    continue;
+                };
+                server_state.send_or_log(&socket, message.clone());
+            }
         }
         CreateAnonymousPlayer {
             screen_width,
@@ -714,18 +1061,17 @@ async fn handle_player_msg(
                 let mut connection_info = connection_info_mutex.lock();
                 connection_info.player = Some(authed_token.clone());
 
-                server_state
-                    .send_to_player(
-                        &player_addr,
-                        GameMessage::LoggedInAs {
-                            token: authed_token.token(),
-                            unread_changelogs: vec![],
-                        },
-                    )
-                    .unwrap();
+                server_state.send_or_log(
+                    &player_addr,
+                    GameMessage::LoggedInAs {
+                        token: authed_token.token(),
+                        unread_changelogs: vec![],
+                    },
+                );
             }
-            Err(_) => {
-                todo!("Error handling for database actions");
+            Err(e) => {
+                tracing::error!(error = ?e, "Couldn't create an anonymous player: {e}");
+                return player_err(ServerError::StorageFailed.to_string());
             }
         },
         Login {
@@ -751,51 +1097,49 @@ async fn handle_player_msg(
                 let mut connection_info = connection_info_mutex.lock();
                 connection_info.player = Some(authed);
 
-                server_state
-                    .send_to_player(
-                        &player_addr,
-                        GameMessage::LoggedInAs {
-                            token: player_token,
-                            unread_changelogs: unread_changelogs
-                                .into_iter()
-                                .map(|c| c.changelog_id)
-                                .collect(),
-                        },
-                    )
-                    .unwrap();
+                server_state.send_or_log(
+                    &player_addr,
+                    GameMessage::LoggedInAs {
+                        token: player_token,
+                        unread_changelogs: unread_changelogs
+                            .into_iter()
+                            .map(|c| c.changelog_id)
+                            .collect(),
+                    },
+                );
             }
             Err(_e) => {
-                eprintln!(
-                    "Player tried to login with a bad token and failed ! ! ! ! ! ! ! ! ! ! !"
-                );
+                tracing::warn!(player_addr = %player_addr, "Player tried to login with a bad token");
                 return player_err("Invalid Token".into());
             }
         },
-        LoadDailyPuzzle(token, day) => {
+        LoadDailyPuzzle(token, day) | ReplayDailyPuzzle(token, day) => {
             let Ok(authed) = accounts::auth_player_token(&server_state, token) else {
                 return player_err("Invalid Token".into());
             };
 
+            if day > daily::latest_puzzle_day() {
+                return player_err(format!("The puzzle for day {day} isn't out yet"));
+            }
+
             if let Ok(Some((puzzle, best))) =
-                daily::load_attempt(&server_state, authed, day as i32).await
+                daily::load_attempt(&server_state, authed, day as i32, replaying_daily).await
             {
                 server_state
-                    .send_to_player(&player_addr, GameMessage::ResumeDailyPuzzle(puzzle, best))
-                    .unwrap();
+                    .send_or_log(&player_addr, GameMessage::ResumeDailyPuzzle(puzzle, best));
             } else {
-                server_state
-                    .send_to_player(
-                        &player_addr,
-                        GameMessage::ResumeDailyPuzzle(
-                            DailyStateMessage {
-                                puzzle_day: day,
-                                attempt: 0,
-                                current_moves: vec![],
-                            },
-                            None,
-                        ),
-                    )
-                    .unwrap();
+                server_state.send_or_log(
+                    &player_addr,
+                    GameMessage::ResumeDailyPuzzle(
+                        DailyStateMessage {
+                            puzzle_day: day,
+                            attempt: 0,
+                            current_moves: vec![],
+                            replay: replaying_daily,
+                        },
+                        None,
+                    ),
+                );
             }
         }
         LoadReplay(id) => {
@@ -807,9 +1151,7 @@ async fn handle_player_msg(
             };
 
             if let Ok(Some(puzzle)) = daily::load_exact_attempt(&server_state, uuid).await {
-                server_state
-                    .send_to_player(&player_addr, GameMessage::LoadDailyReplay(puzzle))
-                    .unwrap();
+                server_state.send_or_log(&player_addr, GameMessage::LoadDailyReplay(puzzle));
             } else {
                 return player_err("Replay does not exist".into());
             }
@@ -820,6 +1162,9 @@ async fn handle_player_msg(
             human_player,
             moves,
             won,
+            drawn,
+            replay,
+            resigned,
         } => {
             let Ok(authed) = accounts::auth_player_token(&server_state, player_token) else {
                 return player_err("Invalid Token".into());
@@ -832,10 +1177,13 @@ async fn handle_player_msg(
                 human_player as i32,
                 moves,
                 won,
+                drawn,
+                replay,
+                resigned,
             )
             .await
             {
-                eprintln!("Errored persisting daily game moves: {e}\n{e:?}");
+                tracing::error!(error = ?e, "Errored persisting daily game moves: {e}");
             }
         }
         RequestStats(token) => {
@@ -845,18 +1193,90 @@ async fn handle_player_msg(
 
             match daily::load_stats(&server_state, authed).await {
                 Ok(stats) => {
-                    server_state
-                        .send_to_player(&player_addr, GameMessage::DailyStats(stats))
-                        .unwrap();
+                    server_state.send_or_log(&player_addr, GameMessage::DailyStats(stats));
+                }
+                Err(e) => {
+                    tracing::error!(error = ?e, "Errored loading stats for player: {e}");
+                }
+            }
+        }
+        RequestGameHistory(token, page) => {
+            let Ok(authed) = accounts::auth_player_token(&server_state, token) else {
+                return player_err("Invalid Token".into());
+            };
+
+            match game_history::load_history(&server_state, authed, page).await {
+                Ok(history) => {
+                    server_state.send_or_log(&player_addr, GameMessage::GameHistory(history));
                 }
                 Err(e) => {
-                    eprintln!("Errored loading stats for player: {e}\n{e:?}");
+                    tracing::error!(error = ?e, "Errored loading game history for player: {e}");
+                }
+            }
+        }
+        RequestDailyLeaderboard(day) => {
+            let cached = server_state
+                .daily_leaderboards
+                .lock()
+                .get(&day)
+                .filter(|(loaded_at, _)| {
+                    truncate_core::game::now() < loaded_at + LEADERBOARD_CACHE_SECS
+                })
+                .map(|(_, entries)| entries.clone());
+
+            let entries = match cached {
+                Some(entries) => entries,
+                None => match daily::load_leaderboard(&server_state, day as i32).await {
+                    Ok(entries) => {
+                        server_state
+                            .daily_leaderboards
+                            .lock()
+                            .insert(day, (truncate_core::game::now(), entries.clone()));
+                        entries
+                    }
+                    Err(e) => {
+                        tracing::error!(day, error = ?e, "Errored loading leaderboard: {e}");
+                        return Ok(());
+                    }
+                },
+            };
+
+            server_state.send_or_log(&player_addr, GameMessage::DailyLeaderboard { day, entries });
+        }
+        AddWords {
+            admin_secret,
+            room,
+            words,
+        } => {
+            let authorized = server_state
+                .admin_secret
+                .as_deref()
+                .is_some_and(|secret| admin::secrets_match(&admin_secret, secret));
+            if !authorized {
+                return player_err("Adding words requires the admin secret".into());
+            }
+
+            match room {
+                Some(code) => {
+                    let Some(existing_game) = server_state.get_game_by_code(&code) else {
+                        return player_err(format!(
+                            "Room {} does not exist",
+                            code.to_ascii_uppercase()
+                        ));
+                    };
+                    let added = existing_game.lock().core_game.judge.extend_overlay(&words);
+                    tracing::info!(room_code = %code, added, "Added words to room");
+                }
+                None => {
+                    let added = server_state.word_db.lock().merge_words(&words);
+                    tracing::info!(added, "Added words to every room");
                 }
             }
         }
         MarkChangelogRead(id) => {
             let Some(connection_player) = connection_info_mutex.lock().player.clone() else {
-                eprintln!(
+                tracing::warn!(
+                    player_addr = %player_addr,
                     "No connection player found, but player wanted to mark changelog as read"
                 );
                 return Ok(());
@@ -876,6 +1296,8 @@ async fn handle_player_msg(
 #[derive(Default)]
 struct ConnectionInfo {
     player: Option<AuthedTruncateToken>,
+    /// Dropped along with the rest of the connection once the socket closes
+    rate_limiter: RateLimiter,
 }
 
 async fn handle_connection(server_state: ServerState, raw_stream: TcpStream, addr: SocketAddr) {
@@ -904,15 +1326,24 @@ async fn handle_connection(server_state: ServerState, raw_stream: TcpStream, add
                         room_code,
                         players,
                         next_player_number,
+                        read_only: false,
                         ..
                     })
                     | GameMessage::GameTimingUpdate(GameStateMessage {
                         room_code,
                         players,
                         next_player_number,
+                        read_only: false,
                         ..
                     })
                     | GameMessage::StartedGame(GameStateMessage {
+                        room_code,
+                        players,
+                        next_player_number,
+                        read_only: false,
+                        ..
+                    })
+                    | GameMessage::GameCatchUp(GameCatchUpMessage {
                         room_code,
                         players,
                         next_player_number,
@@ -920,8 +1351,13 @@ async fn handle_connection(server_state: ServerState, raw_stream: TcpStream, add
                     }) => {
                         if let Some(next_player) = next_player_number {
                             let next_player = &players[*next_player as usize];
+                            // Any Fischer increment from the last move is already included here
                             if let Some(time_remaining) = next_player.time_remaining {
-                                println!("Some player has {time_remaining} time left");
+                                tracing::debug!(
+                                    room_code = %room_code,
+                                    %time_remaining,
+                                    "Scheduling a check for the game's timeout"
+                                );
                                 tokio::spawn(check_game_over(
                                     room_code.clone(),
                                     time_remaining.whole_milliseconds(),
@@ -943,8 +1379,59 @@ async fn handle_connection(server_state: ServerState, raw_stream: TcpStream, add
 
     let mut peer_map = server_state.peers.lock();
     peer_map.remove(&addr);
+    drop(peer_map);
+
+    handle_disconnect(&server_state, addr);
+}
+
+fn handle_disconnect(server_state: &ServerState, addr: SocketAddr) {
+    let Some(existing_game) = server_state.get_game_by_player(&addr) else {
+        return;
+    };
+    let mut game_manager = existing_game.lock();
+    let Some((player_index, disconnection)) = game_manager.disconnect(addr) else {
+        return;
+    };
+
+    for (player, message) in game_manager.pause_for_disconnect(server_state.words()) {
+        let Some(socket) = player.socket else {
+            continue;
+        };
+        server_state.send_or_log(&socket, message);
+    }
+    server_state.mark_game_dirty(&game_manager.game_id);
+
+    tokio::spawn(forfeit_after_grace(
+        game_manager.game_id.clone(),
+        player_index,
+        disconnection,
+        server_state.clone(),
+    ));
+}
+
+#[tracing::instrument(skip_all, fields(room_code = %game_id))]
+async fn forfeit_after_grace(
+    game_id: String,
+    player_index: usize,
+    disconnection: u64,
+    server_state: ServerState,
+) {
+    tokio::time::sleep(Duration::from_secs(server_state.disconnect_grace_secs).into()).await;
+
+    let Some(existing_game) = server_state.get_game_by_code(&game_id) else {
+        return;
+    };
+    let mut game_manager = existing_game.lock();
+    for (player, message) in game_manager.forfeit(player_index, disconnection) {
+        let Some(socket) = player.socket else {
+            continue;
+        };
+        server_state.send_or_log(&socket, message);
+    }
+    server_state.mark_game_dirty(&game_id);
 }
 
+#[tracing::instrument(skip_all, fields(room_code = %game_id))]
 async fn check_game_over(game_id: String, check_in_ms: i128, server_state: ServerState) {
     if check_in_ms.is_negative() {
         return;
@@ -956,11 +1443,19 @@ async fn check_game_over(game_id: String, check_in_ms: i128, server_state: Serve
         return;
     };
     let mut game_manager = existing_game.lock();
-    game_manager.core_game.calculate_game_over(None);
+    game_manager.check_timeout();
 
     let words_db = server_state.words();
 
-    if let Some(winner) = game_manager.core_game.winner {
+    if let Some(outcome) = game_manager.core_game.outcome() {
+        let end_reason = game_manager.end_reason.clone();
+        let end_game = |msg| match outcome {
+            GameOutcome::Winner(winner) => {
+                GameMessage::GameEnd(msg, winner as u64, end_reason.clone())
+            }
+            GameOutcome::Draw => GameMessage::GameDraw(msg),
+        };
+
         for (player_index, player) in game_manager.players.iter().enumerate() {
             let Some(socket) = player.socket else {
                 continue;
@@ -968,9 +1463,73 @@ async fn check_game_over(game_id: String, check_in_ms: i128, server_state: Serve
             let mut end_game_msg = game_manager.game_msg(player_index, Some(&words_db.lock()));
             // Don't send any of the latest battles or hand changes
             end_game_msg.changes = vec![];
-            server_state
-                .send_to_player(&socket, GameMessage::GameEnd(end_game_msg, winner as u64))
-                .unwrap();
+            server_state.send_or_log(&socket, end_game(end_game_msg));
+        }
+
+        let mut spectator_msg = game_manager.spectator_msg(Some(&words_db.lock()));
+        spectator_msg.changes = vec![];
+        for spectator in &game_manager.spectators {
+            let Some(socket) = spectator.socket else {
+                continue;
+            };
+            server_state.send_or_log(&socket, end_game(spectator_msg.clone()));
+        }
+
+        server_state.mark_game_dirty(&game_id);
+    }
+}
+
+async fn persist_games(server_state: ServerState) {
+    loop {
+        // Write changed games in batches, so that move handlers never wait on the database
+        tokio::time::sleep(Duration::from_secs(2).into()).await;
+
+        let dirty_games: Vec<_> = server_state.dirty_games.lock().drain().collect();
+        for game_id in dirty_games {
+            let Some(existing_game) = server_state.get_game_by_code(&game_id) else {
+                continue;
+            };
+            let (effective_day, savegame, finished, finished_records, recording) = {
+                let mut game_manager = existing_game.lock();
+                (
+                    game_manager.effective_day,
+                    game_manager.core_game.to_savegame(),
+                    game_manager.core_game.is_over(),
+                    game_manager.take_finished_records(),
+                    server_state
+                        .game_recordings
+                        .is_some()
+                        .then(|| game_manager.take_recording())
+                        .flatten(),
+                )
+            };
+
+            if let (Some(path), Some(recording)) = (&server_state.game_recordings, recording) {
+                if let Err(e) = recordings::append_recording(path, &recording).await {
+                    tracing::error!(room_code = %game_id, error = ?e, "Errored recording finished game: {e}");
+                }
+            }
+
+            if server_state.truncate_db.is_none() {
+                continue;
+            }
+
+            if let Err(e) =
+                live_games::persist_game(&server_state, &game_id, effective_day, savegame, finished)
+                    .await
+            {
+                tracing::error!(room_code = %game_id, error = ?e, "Errored persisting live game: {e}");
+            }
+
+            // However the game ended (including timeouts from `check_game_over`),
+            // it reaches here once finished and is added to each player's history
+            if !finished_records.is_empty() {
+                if let Err(e) =
+                    game_history::record_game(&server_state, &game_id, finished_records).await
+                {
+                    tracing::error!(room_code = %game_id, error = ?e, "Errored recording game history: {e}");
+                }
+            }
         }
     }
 }
@@ -980,8 +1539,57 @@ async fn clean_nonces(server_state: ServerState) {
         // Clean all old nonces every five minutes
         tokio::time::sleep(Duration::from_mins(5).into()).await;
 
-        let mut nonce_manager = server_state.nonces.lock();
-        nonce_manager.cleanup(90);
+        server_state.nonces.lock().cleanup(90);
+
+        if server_state.truncate_db.is_some() {
+            if let Err(e) = nonces::expire_nonces(&server_state, 90).await {
+                tracing::error!(error = ?e, "Errored expiring nonces: {e}");
+            }
+        }
+    }
+}
+
+async fn reap_games(server_state: ServerState) {
+    loop {
+        // Look for abandoned games every five minutes
+        tokio::time::sleep(Duration::from_mins(5).into()).await;
+
+        let cutoff = truncate_core::game::now().saturating_sub(server_state.game_ttl_secs);
+        let peers: HashSet<_> = server_state.peers.lock().keys().copied().collect();
+        let activity = server_state.game_activity.lock().clone();
+
+        let mut reaped = vec![];
+        server_state.games.lock().retain(|game_id, game| {
+            // A game that's locked right now is clearly still in use
+            let Some(game_manager) = game.try_lock() else {
+                return true;
+            };
+
+            let keep = !game_manager.is_abandoned(&peers, activity.get(game_id).copied(), cutoff);
+            if !keep {
+                reaped.push(game_id.clone());
+            }
+            keep
+        });
+
+        server_state
+            .assignments
+            .lock()
+            .retain(|_, game_id| !reaped.contains(game_id));
+        server_state
+            .game_activity
+            .lock()
+            .retain(|game_id, _| !reaped.contains(game_id));
+
+        for game_id in &reaped {
+            if let Err(e) = live_games::remove_game(&server_state, game_id).await {
+                if !matches!(e, TruncateServerError::DatabaseOffline) {
+                    tracing::error!(room_code = %game_id, error = ?e, "Errored removing reaped game: {e}");
+                }
+            }
+        }
+
+        tracing::info!(reaped = reaped.len(), "Reaped abandoned games");
     }
 }
 
@@ -990,14 +1598,19 @@ async fn ping_peers(server_state: ServerState) {
         // Ping all clients every five seconds
         tokio::time::sleep(Duration::from_secs(5).into()).await;
         let mut bad_peers = vec![];
+        let server_time = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("Server clock should be after 1970")
+            .as_millis() as u64;
         let mut peer_map = server_state.peers.lock();
         let all_peers = peer_map.iter();
         for (peer_key, peer_tx) in all_peers {
-            match peer_tx.send(GameMessage::Ping) {
-                Ok(()) => {}
-                Err(_) => {
-                    bad_peers.push(peer_key.clone());
-                }
+            // Clients measure their clock drift against the time sent alongside each ping
+            let sent = peer_tx
+                .send(GameMessage::Ping)
+                .and_then(|()| peer_tx.send(GameMessage::ClockSync { server_time }));
+            if sent.is_err() {
+                bad_peers.push(peer_key.clone());
             }
         }
         for bad_peer in bad_peers {
@@ -1006,10 +1619,28 @@ async fn ping_peers(server_state: ServerState) {
     }
 }
 
+async fn announce_new_dailies(server_state: ServerState) {
+    let mut current_day = daily::utc_puzzle_day();
+    loop {
+        // Check for the day rolling over every thirty seconds
+        tokio::time::sleep(Duration::from_secs(30).into()).await;
+        let day = daily::utc_puzzle_day();
+        if day == current_day {
+            continue;
+        }
+        current_day = day;
+
+        let peer_map = server_state.peers.lock();
+        tracing::info!(day, peers = peer_map.len(), "Announcing a new daily puzzle");
+        for peer_tx in peer_map.values() {
+            // Closed peers are cleaned up by the next round of pings
+            _ = peer_tx.send(GameMessage::NewDailyAvailable { day });
+        }
+    }
+}
+
 #[tokio::main]
 async fn main() -> Result<(), IoError> {
-    println!("Starting up...");
-
     let addr = env::args()
         .nth(1)
         .unwrap_or_else(|| "0.0.0.0:8080".to_string());
@@ -1017,28 +1648,80 @@ async fn main() -> Result<(), IoError> {
     // Load from env file if one exists (local dev).
     _ = dotenvy::dotenv();
 
+    // Verbosity is set with RUST_LOG, e.g. `RUST_LOG=truncate_server=debug` to log every message
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")),
+        )
+        .init();
+    tracing::info!("Starting up...");
+
     let jwt_key = if let Some(s) = env::var("SIGNING_SECRET").ok() {
-        println!("Loading the signing secret for JWTs");
+        tracing::info!("Loading the signing secret for JWTs");
         HS256Key::from_bytes(&hex::decode(s).expect("Signing secret should be valid hex"))
     } else {
         let k = HS256Key::generate();
-        println!("Running without a dedicated secret — generating a new one:");
-        println!("{}", hex::encode(k.to_bytes()));
+        tracing::warn!(
+            secret = %hex::encode(k.to_bytes()),
+            "Running without a dedicated secret — generating a new one"
+        );
         k
     };
 
+    let disconnect_grace_secs = env::var("DISCONNECT_GRACE_SECONDS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(60);
+    let game_ttl_secs = env::var("GAME_TTL_SECONDS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(2 * 60 * 60);
+    // A limit of zero lets players send illegal moves indefinitely
+    let rejected_move_limit = env::var("REJECTED_MOVE_LIMIT")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(20);
+    let rejected_move_limit = (rejected_move_limit > 0).then(|| RejectedMoveLimit {
+        max_consecutive: rejected_move_limit,
+        forfeit: env::var("REJECTED_MOVE_FORFEIT").is_ok_and(|s| s == "true"),
+    });
+
+    // Boards past this size are refused, as they are too large for clients to render
+    let max_board_dimension = env::var("MAX_BOARD_DIMENSION")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(MAX_BOARD_DIMENSION);
+
+    let word_db = match read_defs() {
+        Ok(word_db) => word_db,
+        Err(e) => {
+            tracing::error!("Couldn't load the dictionary: {e}");
+            std::process::exit(1);
+        }
+    };
+
     let mut server_state = ServerState {
         games: Arc::new(Mutex::new(HashMap::new())),
         assignments: Arc::new(Mutex::new(HashMap::new())),
         peers: Arc::new(Mutex::new(HashMap::new())),
-        word_db: Arc::new(Mutex::new(read_defs())),
+        dirty_games: Arc::new(Mutex::new(HashSet::new())),
+        game_activity: Arc::new(Mutex::new(HashMap::new())),
+        word_db: Arc::new(Mutex::new(word_db)),
         nonces: Arc::new(Mutex::new(NonceTracker::default())),
         truncate_db: None,
         jwt_key,
+        disconnect_grace_secs,
+        game_ttl_secs,
+        rejected_move_limit,
+        deadlock_reports: Arc::new(Mutex::new(vec![])),
+        admin_secret: env::var("ADMIN_SECRET").ok(),
+        daily_leaderboards: Arc::new(Mutex::new(HashMap::new())),
+        game_recordings: env::var("GAME_RECORDINGS_PATH").ok().map(PathBuf::from),
+        max_board_dimension,
     };
 
     if let Ok(db_url) = env::var("DATABASE_URL") {
-        println!("Initializing database shtuff");
+        tracing::info!("Initializing database shtuff");
 
         let pool = PgPoolOptions::new()
             .max_connections(5)
@@ -1046,7 +1729,7 @@ async fn main() -> Result<(), IoError> {
             .await
             .expect("Database should be alive");
 
-        println!("Running database migrations");
+        tracing::info!("Running database migrations");
         sqlx::migrate!("./migrations")
             .set_ignore_missing(true)
             .run(&pool)
@@ -1055,18 +1738,86 @@ async fn main() -> Result<(), IoError> {
 
         server_state.truncate_db = Some(pool);
 
-        println!("Database is ready.");
+        match live_games::load_unfinished_games(&server_state).await {
+            Ok(live_games) => {
+                tracing::info!(games = live_games.len(), "Restoring live games");
+                for live_game in live_games {
+                    match Game::from_savegame(&live_game.savegame) {
+                        Ok(core_game) => {
+                            server_state
+                                .word_db
+                                .lock()
+                                .reserve_code(&live_game.room_code);
+                            server_state.mark_game_dirty(&live_game.room_code);
+                            let restored = server_state.add_new_game(
+                                &live_game.room_code,
+                                GameManager::rehydrate(
+                                    live_game.room_code.clone(),
+                                    live_game.effective_day,
+                                    core_game,
+                                ),
+                            );
+
+                            // Nobody is connected yet, so the game resumes as if everyone had just dropped
+                            let game_manager = restored.lock();
+                            for (&player_index, &disconnection) in &game_manager.pending_forfeits {
+                                tokio::spawn(forfeit_after_grace(
+                                    game_manager.game_id.clone(),
+                                    player_index,
+                                    disconnection,
+                                    server_state.clone(),
+                                ));
+                            }
+                            if let Some(check_in_ms) = game_manager.next_timeout_ms() {
+                                // Clocks that ran out while we were down are checked straight away
+                                tokio::spawn(check_game_over(
+                                    game_manager.game_id.clone(),
+                                    check_in_ms.max(0),
+                                    server_state.clone(),
+                                ));
+                            }
+                        }
+                        Err(e) => {
+                            tracing::error!(room_code = %live_game.room_code, "Couldn't restore game: {e}");
+                        }
+                    }
+                }
+            }
+            Err(e) => {
+                tracing::error!(error = ?e, "Errored loading live games: {e}");
+            }
+        }
+
+        tracing::info!("Database is ready.");
     } else {
-        println!("Running the Truncate server without a database connection.");
+        tracing::warn!("Running the Truncate server without a database connection.");
+    }
+
+    if let Some(path) = &server_state.game_recordings {
+        tracing::info!(path = %path.display(), "Recording finished games");
+    }
+    if server_state.truncate_db.is_some() || server_state.game_recordings.is_some() {
+        tokio::spawn(persist_games(server_state.clone()));
     }
 
     let try_socket = TcpListener::bind(&addr).await;
     let listener = try_socket.expect("Failed to bind");
-    println!("Listening on: {}", addr);
+    tracing::info!(%addr, "Listening");
 
     tokio::spawn(ping_peers(server_state.clone()));
+    tokio::spawn(announce_new_dailies(server_state.clone()));
     tokio::spawn(clean_nonces(server_state.clone()));
+    tokio::spawn(reap_games(server_state.clone()));
+
+    let health_addr = env::var("HEALTH_ADDR").unwrap_or_else(|_| "0.0.0.0:8082".to_string());
+    tokio::spawn(health::serve_health(server_state.clone(), health_addr));
+
+    if let Some(secret) = server_state.admin_secret.clone() {
+        let admin_addr = env::var("ADMIN_ADDR").unwrap_or_else(|_| "0.0.0.0:8081".to_string());
+        tokio::spawn(admin::serve_admin(server_state.clone(), admin_addr, secret));
+    }
 
+    let deadlock_reports = Arc::clone(&server_state.deadlock_reports);
     std::thread::spawn(move || loop {
         std::thread::sleep(std::time::Duration::from_secs(10));
         let deadlocks = parking_lot::deadlock::check_deadlock();
@@ -1074,18 +1825,31 @@ async fn main() -> Result<(), IoError> {
             continue;
         }
 
-        println!("{} deadlocks detected", deadlocks.len());
+        tracing::error!(deadlocks = deadlocks.len(), "Deadlocks detected");
+        let mut reports = deadlock_reports.lock();
         for (i, threads) in deadlocks.iter().enumerate() {
-            println!("Deadlock #{}", i);
             for t in threads {
-                println!("Thread Id {:#?}", t.thread_id());
-                println!("{:#?}", t.backtrace());
+                tracing::error!(
+                    deadlock = i,
+                    thread_id = ?t.thread_id(),
+                    backtrace = ?t.backtrace(),
+                    "Deadlocked thread"
+                );
             }
+
+            let thread_ids: Vec<_> = threads.iter().map(|t| t.thread_id()).collect();
+            reports.push(format!(
+                "Deadlock at {} between threads {thread_ids:?}",
+                truncate_core::game::now()
+            ));
         }
+        let excess = reports.len().saturating_sub(admin::MAX_DEADLOCK_REPORTS);
+        reports.drain(..excess);
     });
 
     while let Ok((stream, addr)) = listener.accept().await {
-        tokio::spawn(handle_connection(server_state.clone(), stream, addr));
+        let span = tracing::info_span!("connection", player_addr = %addr);
+        tokio::spawn(handle_connection(server_state.clone(), stream, addr).instrument(span));
     }
 
     Ok(())
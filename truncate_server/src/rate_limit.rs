@@ -0,0 +1,126 @@
+use std::time::Instant;
+
+/// Burst size and refill rate for all messages from a connection
+const GENERAL_BURST: f64 = 30.0;
+const GENERAL_PER_SECOND: f64 = 10.0;
+
+/// Burst size and refill rate for messages that hit the database
+const EXPENSIVE_BURST: f64 = 5.0;
+const EXPENSIVE_PER_SECOND: f64 = 0.5;
+
+/// Allows bursts of up to `capacity` messages, refilling at `per_second` messages each second
+pub struct TokenBucket {
+    capacity: f64,
+    per_second: f64,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    pub fn new(capacity: f64, per_second: f64) -> Self {
+        Self {
+            capacity,
+            per_second,
+            tokens: capacity,
+            last_refill: Instant::now(),
+        }
+    }
+
+    /// Tops up the bucket and reports whether a message would fit, without spending anything
+    pub fn has_token(&mut self) -> bool {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.per_second).min(self.capacity);
+        self.last_refill = now;
+
+        self.tokens >= 1.0
+    }
+
+    pub fn take(&mut self) {
+        self.tokens -= 1.0;
+    }
+}
+
+pub struct RateLimiter {
+    general: TokenBucket,
+    expensive: TokenBucket,
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self {
+            general: TokenBucket::new(GENERAL_BURST, GENERAL_PER_SECOND),
+            expensive: TokenBucket::new(EXPENSIVE_BURST, EXPENSIVE_PER_SECOND),
+        }
+    }
+}
+
+impl RateLimiter {
+    /// Expensive messages have to fit within both buckets
+    pub fn allow(&mut self, expensive: bool) -> bool {
+        if !self.general.has_token() || (expensive && !self.expensive.has_token()) {
+            return false;
+        }
+
+        self.general.take();
+        if expensive {
+            self.expensive.take();
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn buckets_allow_a_burst_then_refill() {
+        let mut bucket = TokenBucket::new(3.0, 2.0);
+        for _ in 0..3 {
+            assert!(bucket.has_token());
+            bucket.take();
+        }
+        assert!(!bucket.has_token());
+
+        bucket.last_refill -= Duration::from_secs(1);
+        for _ in 0..2 {
+            assert!(bucket.has_token());
+            bucket.take();
+        }
+        assert!(!bucket.has_token());
+
+        // Refills never go past the burst size
+        bucket.last_refill -= Duration::from_secs(60);
+        assert!(bucket.has_token());
+        assert_eq!(bucket.tokens, 3.0);
+    }
+
+    #[test]
+    fn expensive_messages_need_both_buckets() {
+        let mut limiter = RateLimiter::default();
+        for _ in 0..EXPENSIVE_BURST as usize {
+            assert!(limiter.allow(true));
+        }
+        assert!(!limiter.allow(true));
+
+        // Refused messages don't spend from the general bucket
+        let general_tokens = limiter.general.tokens;
+        assert!(!limiter.allow(true));
+        assert!(limiter.general.tokens >= general_tokens);
+        assert!(limiter.allow(false));
+    }
+
+    #[test]
+    fn general_messages_are_limited() {
+        let mut limiter = RateLimiter::default();
+        for _ in 0..GENERAL_BURST as usize {
+            assert!(limiter.allow(false));
+        }
+        assert!(!limiter.allow(false));
+        assert!(!limiter.allow(true));
+        assert_eq!(limiter.expensive.tokens, EXPENSIVE_BURST);
+    }
+}
@@ -21,6 +21,15 @@ impl AuthedTruncateToken {
     pub fn token(&self) -> TruncateToken {
         self.token.clone()
     }
+
+    /// A token for a new player, without going through the JWT key
+    #[cfg(test)]
+    pub(crate) fn for_tests(token: &str) -> Self {
+        Self {
+            token: token.to_string(),
+            player_id: Uuid::new_v4(),
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize)]
@@ -1,8 +1,16 @@
-use std::collections::BTreeMap;
+use std::{collections::BTreeMap, sync::Arc};
 
+use serde::{Deserialize, Serialize};
 use truncate_core::{
-    messages::{DailyAttempt, DailyResult, DailyStateMessage, DailyStats},
-    moves::{self, packing::pack_moves, Move},
+    game::Game,
+    generation::{generate_board, get_game_verification, BoardSeed},
+    judge::WordDict,
+    messages::{DailyAttempt, DailyLeaderboardEntry, DailyResult, DailyStateMessage, DailyStats},
+    moves::{
+        packing::{pack_moves, unpack_moves, unpack_moves_text},
+        Move,
+    },
+    rules::GameRules,
 };
 use uuid::Uuid;
 
@@ -10,6 +18,40 @@ use crate::{errors::TruncateServerError, ServerState};
 
 use super::accounts::AuthedTruncateToken;
 
+/// The most players returned for any one day's leaderboard
+pub const MAX_LEADERBOARD_ENTRIES: i64 = 25;
+
+/// Unix day of the first daily puzzle, matching the client's `DAILY_PUZZLE_DAY_ZERO`
+const DAILY_PUZZLE_DAY_ZERO: u64 = 19751;
+
+const SEED_NOTES: &[u8] = include_bytes!("../../../truncate_dueller/seed_notes.yml");
+
+/// The parts of the dueller's seed notes needed to rebuild a puzzle
+#[derive(Deserialize)]
+struct SeedNote {
+    rerolls: usize,
+    board_generation: u32,
+    rules_generation: u32,
+    verification: String,
+}
+
+#[derive(Deserialize)]
+struct NotesFile {
+    notes: BTreeMap<u32, SeedNote>,
+}
+
+/// The newest puzzle day that may be played, allowing for timezones a day ahead of UTC
+pub fn latest_puzzle_day() -> u32 {
+    let utc_day = truncate_core::game::now() / (60 * 60 * 24);
+    (utc_day + 1).saturating_sub(DAILY_PUZZLE_DAY_ZERO) as u32
+}
+
+/// The puzzle day as it stands in UTC, which rolls over at UTC midnight
+pub fn utc_puzzle_day() -> u32 {
+    let utc_day = truncate_core::game::now() / (60 * 60 * 24);
+    utc_day.saturating_sub(DAILY_PUZZLE_DAY_ZERO) as u32
+}
+
 pub struct AttemptRecord {
     attempt_id: Uuid,
     attempt_number: i32,
@@ -21,12 +63,15 @@ pub struct DailyPuzzleRecord {
 }
 
 /// Returns any partial or completed attempt for a given player on the requested day.
+/// Replays of a past day are tracked separately from the attempts made on that day.
 pub async fn load_attempt(
     server_state: &ServerState,
     player: AuthedTruncateToken,
     daily_puzzle: i32,
+    replay: bool,
 ) -> Result<Option<(DailyStateMessage, Option<DailyStateMessage>)>, TruncateServerError> {
-    let Some(daily_puzzle_record) = get_day_record(server_state, player, daily_puzzle).await?
+    let Some(daily_puzzle_record) =
+        get_day_record(server_state, player, daily_puzzle, replay).await?
     else {
         return Ok(None);
     };
@@ -40,7 +85,7 @@ pub async fn load_attempt(
     let best_record = get_best_attempt_for_day(server_state, daily_puzzle_record.result_id)
         .await?
         .map(|a| {
-            let Ok(best) = moves::packing::unpack_moves(&a.sequence_of_moves, 2) else {
+            let Ok(best) = decode_moves(&a.sequence_of_moves) else {
                 // If move parsing fails, move on as if there was no attempt.
                 return None;
             };
@@ -48,12 +93,12 @@ pub async fn load_attempt(
                 puzzle_day: daily_puzzle.try_into().unwrap_or_default(),
                 attempt: a.attempt_number.try_into().unwrap_or_default(),
                 current_moves: best,
+                replay,
             })
         })
         .flatten();
 
-    let Ok(current_moves) = moves::packing::unpack_moves(&attempt_record.sequence_of_moves, 2)
-    else {
+    let Ok(current_moves) = decode_moves(&attempt_record.sequence_of_moves) else {
         // If move parsing fails, move on as if there was no attempt.
         return Ok(None);
     };
@@ -63,6 +108,7 @@ pub async fn load_attempt(
             puzzle_day: daily_puzzle.try_into().unwrap_or_default(),
             attempt: attempt_record.attempt_number.try_into().unwrap_or_default(),
             current_moves,
+            replay,
         },
         best_record,
     )))
@@ -73,23 +119,25 @@ pub async fn get_or_create_latest_attempt(
     player: AuthedTruncateToken,
     daily_puzzle: i32,
     human_player: i32,
+    replay: bool,
 ) -> Result<(DailyStateMessage, AttemptRecord), TruncateServerError> {
     let Some(pool) = &server_state.truncate_db else {
         return Err(TruncateServerError::DatabaseOffline);
     };
     let player_id = player.player();
 
-    let daily_puzzle_record = get_day_record(server_state, player, daily_puzzle).await?;
+    let daily_puzzle_record = get_day_record(server_state, player, daily_puzzle, replay).await?;
 
     let result_id = if let Some(dpr) = daily_puzzle_record {
         dpr.result_id
     } else {
         let new_puzzle_record = sqlx::query_as!(
             DailyPuzzleRecord,
-            "INSERT INTO daily_puzzle_results (player_id, daily_puzzle, human_player) VALUES ($1, $2, $3) RETURNING result_id",
+            "INSERT INTO daily_puzzle_results (player_id, daily_puzzle, human_player, replay) VALUES ($1, $2, $3, $4) RETURNING result_id",
             player_id,
             daily_puzzle,
-            human_player
+            human_player,
+            replay
         )
         .fetch_one(pool)
         .await?;
@@ -102,7 +150,7 @@ pub async fn get_or_create_latest_attempt(
         None => create_new_attempt(server_state, result_id).await?,
     };
 
-    let current_moves = match moves::packing::unpack_moves(&latest_attempt.sequence_of_moves, 2) {
+    let current_moves = match decode_moves(&latest_attempt.sequence_of_moves) {
         Ok(moves) => moves,
         Err(_) => {
             // Something went wrong with this attempt — move on to a new one.
@@ -116,6 +164,7 @@ pub async fn get_or_create_latest_attempt(
             puzzle_day: daily_puzzle.try_into().unwrap_or_default(),
             attempt: latest_attempt.attempt_number.try_into().unwrap_or_default(),
             current_moves,
+            replay,
         },
         latest_attempt,
     ))
@@ -125,6 +174,7 @@ async fn get_day_record(
     server_state: &ServerState,
     player: AuthedTruncateToken,
     daily_puzzle: i32,
+    replay: bool,
 ) -> Result<Option<DailyPuzzleRecord>, TruncateServerError> {
     let Some(pool) = &server_state.truncate_db else {
         return Err(TruncateServerError::DatabaseOffline);
@@ -133,9 +183,10 @@ async fn get_day_record(
 
     let daily_puzzle_record = sqlx::query_as!(
         DailyPuzzleRecord,
-        "SELECT result_id FROM daily_puzzle_results WHERE player_id = $1 AND daily_puzzle = $2",
+        "SELECT result_id FROM daily_puzzle_results WHERE player_id = $1 AND daily_puzzle = $2 AND replay = $3",
         player_id,
-        daily_puzzle
+        daily_puzzle,
+        replay
     )
     .fetch_optional(pool)
     .await?;
@@ -216,21 +267,30 @@ pub async fn persist_moves(
     human_player: i32,
     moves: Vec<Move>,
     won: bool,
+    drawn: bool,
+    replay: bool,
+    resigned: bool,
 ) -> Result<(), TruncateServerError> {
     let Some(pool) = &server_state.truncate_db else {
         return Err(TruncateServerError::DatabaseOffline);
     };
 
-    let (_, mut attempt) =
-        get_or_create_latest_attempt(server_state, player.clone(), daily_puzzle, human_player)
-            .await?;
+    let (_, mut attempt) = get_or_create_latest_attempt(
+        server_state,
+        player.clone(),
+        daily_puzzle,
+        human_player,
+        replay,
+    )
+    .await?;
 
-    let packed_moves = pack_moves(&moves, 2);
+    let continues_attempt = decode_moves(&attempt.sequence_of_moves)
+        .is_ok_and(|stored_moves| moves.starts_with(&stored_moves));
 
-    if !packed_moves.starts_with(&attempt.sequence_of_moves) {
+    if !continues_attempt {
         // sacré bleu! somebody is trying to change history!
         // no sir, we will create a new attempt for these moves.
-        let day_record = get_day_record(server_state, player, daily_puzzle)
+        let day_record = get_day_record(server_state, player, daily_puzzle, replay)
             .await?
             .expect("Getting the latest attempt should have created the relevant day");
         attempt = create_new_attempt(server_state, day_record.result_id).await?;
@@ -242,21 +302,30 @@ pub async fn persist_moves(
             let player = match m {
                 Move::Place { player, .. } => player,
                 Move::Swap { player, .. } => player,
+                Move::Challenge { player, .. } => player,
+                Move::Pass { player } => player,
+                Move::Exchange { player, .. } => player,
             };
             *player as i32 == human_player
         })
         .count();
 
     // TODO: If `won` is supposedly true, we should simulate the puzzle
-    // to ensure that the move sequence indeed wins
+    // to ensure that the move sequence indeed wins (see `verify_attempt`)
 
     sqlx::query!(
         "UPDATE daily_puzzle_attempts 
-         SET sequence_of_moves = $1, move_count = $2, won = $3
-         WHERE attempt_id = $4",
-        packed_moves,
+         SET sequence_of_moves = $1, move_count = $2, won = $3, drawn = $4, resigned = $5,
+             completed_at = CASE
+                WHEN $3 THEN COALESCE(completed_at, CURRENT_TIMESTAMP)
+                ELSE NULL
+             END
+         WHERE attempt_id = $6",
+        encode_moves(&moves),
         human_moves as i32,
         won,
+        drawn,
+        resigned,
         attempt.attempt_id
     )
     .execute(pool)
@@ -265,6 +334,22 @@ pub async fn persist_moves(
     Ok(())
 }
 
+/// Stores moves as hex of their binary packing in the `sequence_of_moves` text column
+pub fn encode_moves(moves: &[Move]) -> String {
+    hex::encode(pack_moves(moves))
+}
+
+/// Reads moves stored by `encode_moves`, along with attempts stored in the
+/// older text packing, which is always empty or starts with a `[player]` marker
+pub fn decode_moves(sequence_of_moves: &str) -> Result<Vec<Move>, ()> {
+    if sequence_of_moves.is_empty() || sequence_of_moves.starts_with('[') {
+        return unpack_moves_text(&sequence_of_moves.to_string(), 2);
+    }
+
+    let packed = hex::decode(sequence_of_moves).map_err(|_| ())?;
+    unpack_moves(&packed).map_err(|_| ())
+}
+
 pub async fn load_stats(
     server_state: &ServerState,
     player: AuthedTruncateToken,
@@ -293,7 +378,7 @@ pub async fn load_stats(
         JOIN 
             daily_puzzle_attempts dpa ON dpr.result_id = dpa.result_id
         WHERE 
-            dpr.player_id = $1
+            dpr.player_id = $1 AND NOT dpr.replay
         GROUP BY 
             dpr.daily_puzzle;",
         player_id
@@ -322,9 +407,66 @@ pub async fn load_stats(
         )
     });
 
-    Ok(DailyStats {
+    let mut stats = DailyStats {
         days: BTreeMap::from_iter(day_iter),
-    })
+        ..Default::default()
+    };
+    // Puzzle days roll over in the player's local time, which we don't know here,
+    // so clients recalculate streaks against their own day when displaying them.
+    let utc_day = truncate_core::game::now() / (60 * 60 * 24);
+    stats.calculate_streaks(utc_day.saturating_sub(DAILY_PUZZLE_DAY_ZERO) as u32);
+
+    Ok(stats)
+}
+
+/// Returns the players who won the given day in the fewest moves,
+/// taking each player's best attempt and breaking ties by who finished first.
+pub async fn load_leaderboard(
+    server_state: &ServerState,
+    daily_puzzle: i32,
+) -> Result<Vec<DailyLeaderboardEntry>, TruncateServerError> {
+    let Some(pool) = &server_state.truncate_db else {
+        return Err(TruncateServerError::DatabaseOffline);
+    };
+
+    let rows = sqlx::query!(
+        r#"WITH best_attempts AS (
+            SELECT DISTINCT ON (dpr.player_id)
+                dpr.player_id,
+                dpa.move_count,
+                COALESCE(dpa.completed_at, dpa.attempt_started) AS completed_at
+            FROM
+                daily_puzzle_results dpr
+            JOIN
+                daily_puzzle_attempts dpa ON dpr.result_id = dpa.result_id
+            WHERE
+                dpr.daily_puzzle = $1 AND dpa.won AND NOT dpr.replay
+            ORDER BY
+                dpr.player_id, dpa.move_count, completed_at
+        )
+        SELECT
+            RANK() OVER (ORDER BY move_count) AS "rank!",
+            'Player ' || UPPER(LEFT(MD5(player_id::text), 6)) AS "display_name!",
+            move_count AS "move_count!"
+        FROM
+            best_attempts
+        ORDER BY
+            move_count, completed_at
+        LIMIT $2;"#,
+        daily_puzzle,
+        MAX_LEADERBOARD_ENTRIES
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| DailyLeaderboardEntry {
+            rank: row.rank.try_into().unwrap_or_default(),
+            name: row.display_name,
+            moves: row.move_count.try_into().unwrap_or_default(),
+        })
+        .collect())
 }
 
 /// Returns an attempt given its ID
@@ -340,6 +482,7 @@ pub async fn load_exact_attempt(
         attempt_number: i32,
         sequence_of_moves: String,
         daily_puzzle: i32,
+        replay: bool,
     }
 
     let record = sqlx::query_as!(
@@ -347,7 +490,8 @@ pub async fn load_exact_attempt(
         "SELECT 
             dpa.sequence_of_moves,
             dpa.attempt_number,
-            dpr.daily_puzzle
+            dpr.daily_puzzle,
+            dpr.replay
         FROM
             daily_puzzle_attempts dpa
         JOIN 
@@ -363,8 +507,7 @@ pub async fn load_exact_attempt(
         return Ok(None);
     };
 
-    let Ok(current_moves) = moves::packing::unpack_moves(&attempt_record.sequence_of_moves, 2)
-    else {
+    let Ok(current_moves) = decode_moves(&attempt_record.sequence_of_moves) else {
         // If move parsing fails, move on as if there was no attempt.
         return Ok(None);
     };
@@ -373,5 +516,265 @@ pub async fn load_exact_attempt(
         puzzle_day: attempt_record.daily_puzzle.try_into().unwrap_or_default(),
         attempt: attempt_record.attempt_number.try_into().unwrap_or_default(),
         current_moves,
+        replay: attempt_record.replay,
     }))
 }
+
+/// The outcome of replaying a stored attempt against a freshly generated puzzle
+#[derive(Debug, Serialize)]
+pub struct AttemptVerification {
+    pub daily_puzzle: u32,
+    pub moves: usize,
+    /// Whether the rebuilt puzzle matches the one the seed notes were written for,
+    /// if the notes cover this day
+    pub start_matches: Option<bool>,
+    /// The first move the rebuilt game rejected, if any
+    pub diverged_at: Option<usize>,
+    pub stored_won: bool,
+    pub replayed_won: bool,
+    pub stored_drawn: bool,
+    pub replayed_drawn: bool,
+    /// `get_game_verification` of the final position, for comparing against a client's copy
+    pub final_verification: String,
+    pub matches: bool,
+}
+
+/// Rebuilds the game for a daily puzzle exactly as the client sets it up
+fn daily_puzzle_game(day: u32) -> (Game, Option<String>) {
+    let loaded_notes: NotesFile =
+        serde_yaml::from_slice(SEED_NOTES).expect("Seed notes should match the spec");
+
+    let (board_seed, rules_generation, verification) = match loaded_notes.notes.get(&day) {
+        Some(note) => {
+            let mut board_seed =
+                BoardSeed::new_with_generation(note.board_generation, day).day(day);
+            for _ in 0..note.rerolls {
+                board_seed.external_reroll();
+            }
+            (
+                board_seed,
+                note.rules_generation,
+                Some(note.verification.clone()),
+            )
+        }
+        None => (
+            BoardSeed::new(day).day(day),
+            GameRules::latest(Some(day)).0,
+            None,
+        ),
+    };
+
+    let mut board = generate_board(board_seed.clone())
+        .expect("Common seeds should always generate a board")
+        .board;
+    board.cache_special_squares();
+
+    let mut game = Game::new(
+        9,
+        9,
+        Some(board_seed.tile_seed()),
+        GameRules::generation(rules_generation),
+    );
+    game.add_player("P1".into());
+    game.add_player("P2".into());
+    game.board = board;
+    // Replays are played back instantly, and battle delays don't change the outcome
+    game.rules.battle_delay = 0;
+    game.start();
+
+    (game, verification)
+}
+
+/// Replays a stored attempt move by move, confirming that its recorded result holds up.
+/// Returns `None` if no such attempt exists.
+pub async fn verify_attempt(
+    server_state: &ServerState,
+    id: Uuid,
+) -> Result<Option<AttemptVerification>, TruncateServerError> {
+    let Some(pool) = &server_state.truncate_db else {
+        return Err(TruncateServerError::DatabaseOffline);
+    };
+
+    let record = sqlx::query!(
+        "SELECT 
+            dpa.sequence_of_moves,
+            dpa.won,
+            dpa.drawn,
+            dpr.daily_puzzle,
+            dpr.human_player
+        FROM
+            daily_puzzle_attempts dpa
+        JOIN 
+            daily_puzzle_results dpr ON dpr.result_id = dpa.result_id
+        WHERE
+            attempt_id = $1",
+        id
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    let Some(record) = record else {
+        return Ok(None);
+    };
+    let stored_moves =
+        decode_moves(&record.sequence_of_moves).map_err(|_| TruncateServerError::BadRequest)?;
+    let daily_puzzle: u32 = record.daily_puzzle.try_into().unwrap_or_default();
+    let human_player = record.human_player as usize;
+    let (stored_won, stored_drawn) = (record.won, record.drawn);
+
+    // Replaying is slow, so it runs off the runtime against a snapshot of the dictionary
+    let (words, added_words) = {
+        let word_db = server_state.word_db.lock();
+        (
+            Arc::clone(&word_db.valid_words),
+            word_db.added_words.clone(),
+        )
+    };
+    let verification = tokio::task::spawn_blocking(move || {
+        replay_attempt(
+            id,
+            daily_puzzle,
+            human_player,
+            &stored_moves,
+            (stored_won, stored_drawn),
+            &words,
+            &added_words,
+        )
+    })
+    .await?;
+
+    Ok(Some(verification))
+}
+
+/// Plays a daily puzzle attempt's moves against the rebuilt puzzle, comparing the
+/// result it reaches with the stored `(won, drawn)` result
+fn replay_attempt(
+    id: Uuid,
+    daily_puzzle: u32,
+    human_player: usize,
+    stored_moves: &[Move],
+    (stored_won, stored_drawn): (bool, bool),
+    words: &WordDict,
+    added_words: &WordDict,
+) -> AttemptVerification {
+    let (mut game, expected_start) = daily_puzzle_game(daily_puzzle);
+    game.judge.extend_overlay(added_words.keys());
+    let start_matches = expected_start.map(|v| v == get_game_verification(&game));
+
+    let mut diverged_at = None;
+    for (turn, next_move) in stored_moves.iter().enumerate() {
+        if game.is_over() {
+            diverged_at = Some(turn);
+            tracing::warn!(%id, turn, "Attempt has moves after its game ended");
+            break;
+        }
+        if let Err(e) = game.play_turn(next_move.clone(), Some(words), Some(words), None) {
+            diverged_at = Some(turn);
+            tracing::warn!(%id, turn, ?next_move, "Replayed attempt diverged: {e}");
+            break;
+        }
+    }
+
+    let replayed_won = game.winner == Some(human_player);
+    let replayed_drawn = game.drawn;
+    let matches = diverged_at.is_none()
+        && start_matches != Some(false)
+        && replayed_won == stored_won
+        && replayed_drawn == stored_drawn;
+
+    if !matches {
+        tracing::warn!(
+            %id,
+            daily_puzzle,
+            ?start_matches,
+            stored_won,
+            replayed_won,
+            stored_drawn,
+            replayed_drawn,
+            "Replayed attempt doesn't match its stored result"
+        );
+    }
+
+    AttemptVerification {
+        daily_puzzle,
+        moves: stored_moves.len(),
+        start_matches,
+        diverged_at,
+        stored_won,
+        replayed_won,
+        stored_drawn,
+        replayed_drawn,
+        final_verification: get_game_verification(&game),
+        matches,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use truncate_core::board::Coordinate;
+
+    use super::*;
+
+    #[test]
+    fn replay_reports_diverging_turn() {
+        // No hand holds a '#', so the first move can never be played
+        let stored_moves = decode_moves(&encode_moves(&[Move::Place {
+            player: 0,
+            tile: '#',
+            position: Coordinate { x: 0, y: 0 },
+        }]))
+        .unwrap();
+
+        let verification = replay_attempt(
+            Uuid::nil(),
+            0,
+            0,
+            &stored_moves,
+            (false, false),
+            &WordDict::new(),
+            &WordDict::new(),
+        );
+        assert_eq!(verification.moves, 1);
+        assert_eq!(verification.diverged_at, Some(0));
+        assert!(!verification.matches);
+    }
+
+    #[test]
+    fn replay_reports_mismatched_result() {
+        let stored_moves: Vec<Move> = vec![];
+        let (game, _) = daily_puzzle_game(0);
+
+        // Without any moves the puzzle is still in progress, so a stored win doesn't hold up
+        let verification = replay_attempt(
+            Uuid::nil(),
+            0,
+            0,
+            &stored_moves,
+            (true, false),
+            &WordDict::new(),
+            &WordDict::new(),
+        );
+        assert_eq!(verification.diverged_at, None);
+        assert!(verification.start_matches.is_some());
+        assert!(!verification.replayed_won);
+        assert!(!verification.matches);
+        assert_eq!(
+            verification.final_verification,
+            get_game_verification(&game)
+        );
+
+        let verification = replay_attempt(
+            Uuid::nil(),
+            0,
+            0,
+            &stored_moves,
+            (false, false),
+            &WordDict::new(),
+            &WordDict::new(),
+        );
+        assert_eq!(
+            verification.matches,
+            verification.start_matches == Some(true)
+        );
+    }
+}
@@ -11,7 +11,7 @@ pub async fn create_event(
         return Ok(());
     };
 
-    println!("Tracking event: {event_type}");
+    tracing::debug!(%event_type, "Tracking event");
 
     let Some(pool) = &server_state.truncate_db else {
         return Err(TruncateServerError::DatabaseOffline);
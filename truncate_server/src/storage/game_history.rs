@@ -0,0 +1,129 @@
+use truncate_core::messages::{GameEndReason, GameHistoryEntry, GameHistoryPage, GameResult};
+use uuid::Uuid;
+
+use crate::{errors::TruncateServerError, ServerState};
+
+use super::accounts::AuthedTruncateToken;
+
+/// The most games returned in one page of a player's history
+pub const GAME_HISTORY_PAGE_SIZE: i64 = 20;
+
+/// One logged in player's result in a finished game
+pub struct FinishedGameRecord {
+    pub player_id: Uuid,
+    pub opponents: Vec<String>,
+    pub result: GameResult,
+    pub end_reason: GameEndReason,
+}
+
+fn result_name(result: GameResult) -> &'static str {
+    match result {
+        GameResult::Won => "won",
+        GameResult::Lost => "lost",
+        GameResult::Drawn => "drawn",
+    }
+}
+
+/// How the game ended, and the resigning player's reason if they gave one
+fn end_reason_columns(end_reason: &GameEndReason) -> (&'static str, Option<&str>) {
+    match end_reason {
+        GameEndReason::Board => ("board", None),
+        GameEndReason::Resigned { reason, .. } => ("resigned", reason.as_deref()),
+        GameEndReason::Forfeited { .. } => ("forfeited", None),
+        GameEndReason::TimedOut { .. } => ("timed_out", None),
+    }
+}
+
+fn parse_result(name: &str) -> Option<GameResult> {
+    match name {
+        "won" => Some(GameResult::Won),
+        "lost" => Some(GameResult::Lost),
+        "drawn" => Some(GameResult::Drawn),
+        _ => None,
+    }
+}
+
+pub async fn record_game(
+    server_state: &ServerState,
+    room_code: &String,
+    records: Vec<FinishedGameRecord>,
+) -> Result<(), TruncateServerError> {
+    let Some(pool) = &server_state.truncate_db else {
+        return Err(TruncateServerError::DatabaseOffline);
+    };
+
+    for record in records {
+        let (ended_by, resign_reason) = end_reason_columns(&record.end_reason);
+        sqlx::query!(
+            "INSERT INTO game_history (
+                player_id,
+                room_code,
+                opponents,
+                result,
+                ended_by,
+                resign_reason
+            ) VALUES ($1, $2, $3, $4, $5, $6);",
+            record.player_id,
+            room_code,
+            &record.opponents,
+            result_name(record.result),
+            ended_by,
+            resign_reason
+        )
+        .execute(pool)
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Returns a page of the player's finished games, most recent first
+pub async fn load_history(
+    server_state: &ServerState,
+    player: AuthedTruncateToken,
+    page: u32,
+) -> Result<GameHistoryPage, TruncateServerError> {
+    let Some(pool) = &server_state.truncate_db else {
+        return Err(TruncateServerError::DatabaseOffline);
+    };
+
+    // Fetch one extra game to find out whether there is another page
+    let mut rows = sqlx::query!(
+        r#"SELECT
+            room_code,
+            opponents,
+            result,
+            EXTRACT(EPOCH FROM finished_at)::BIGINT AS "finished_at!"
+        FROM
+            game_history
+        WHERE
+            player_id = $1
+        ORDER BY
+            finished_at DESC
+        LIMIT $2 OFFSET $3;"#,
+        player.player(),
+        GAME_HISTORY_PAGE_SIZE + 1,
+        page as i64 * GAME_HISTORY_PAGE_SIZE
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let has_more = rows.len() as i64 > GAME_HISTORY_PAGE_SIZE;
+    rows.truncate(GAME_HISTORY_PAGE_SIZE as usize);
+
+    Ok(GameHistoryPage {
+        page,
+        entries: rows
+            .into_iter()
+            .filter_map(|row| {
+                Some(GameHistoryEntry {
+                    result: parse_result(&row.result)?,
+                    room_code: row.room_code,
+                    opponents: row.opponents,
+                    finished_at: row.finished_at.try_into().unwrap_or_default(),
+                })
+            })
+            .collect(),
+        has_more,
+    })
+}
@@ -0,0 +1,81 @@
+use crate::{errors::TruncateServerError, ServerState};
+
+pub struct LiveGameRecord {
+    pub room_code: String,
+    pub effective_day: u32,
+    pub savegame: String,
+}
+
+pub async fn persist_game(
+    server_state: &ServerState,
+    room_code: &String,
+    effective_day: u32,
+    savegame: String,
+    finished: bool,
+) -> Result<(), TruncateServerError> {
+    let Some(pool) = &server_state.truncate_db else {
+        return Err(TruncateServerError::DatabaseOffline);
+    };
+
+    sqlx::query!(
+        "INSERT INTO live_games (
+            room_code,
+            effective_day,
+            savegame,
+            finished
+        ) VALUES ($1, $2, $3, $4)
+        ON CONFLICT (room_code) DO UPDATE SET
+            effective_day = EXCLUDED.effective_day,
+            savegame = EXCLUDED.savegame,
+            finished = EXCLUDED.finished,
+            updated_at = CURRENT_TIMESTAMP;",
+        room_code,
+        effective_day as i32,
+        savegame,
+        finished
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn remove_game(
+    server_state: &ServerState,
+    room_code: &String,
+) -> Result<(), TruncateServerError> {
+    let Some(pool) = &server_state.truncate_db else {
+        return Err(TruncateServerError::DatabaseOffline);
+    };
+
+    sqlx::query!("DELETE FROM live_games WHERE room_code = $1;", room_code)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn load_unfinished_games(
+    server_state: &ServerState,
+) -> Result<Vec<LiveGameRecord>, TruncateServerError> {
+    let Some(pool) = &server_state.truncate_db else {
+        return Err(TruncateServerError::DatabaseOffline);
+    };
+
+    let rows = sqlx::query!(
+        "SELECT room_code, effective_day, savegame
+        FROM live_games
+        WHERE finished = false;"
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| LiveGameRecord {
+            room_code: row.room_code,
+            effective_day: row.effective_day as u32,
+            savegame: row.savegame,
+        })
+        .collect())
+}
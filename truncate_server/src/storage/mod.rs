@@ -1,3 +1,7 @@
 pub mod accounts;
 pub mod daily;
 pub mod events;
+pub mod game_history;
+pub mod live_games;
+pub mod nonces;
+pub mod recordings;
@@ -0,0 +1,175 @@
+use std::collections::{HashMap, HashSet};
+
+use truncate_core::messages::Nonce;
+
+use crate::{
+    errors::{ServerError, TruncateServerError},
+    ServerState,
+};
+
+use super::accounts::AuthedTruncateToken;
+
+/// Nonces older than this are rejected outright, so only newer ones need remembering
+const NONCE_LIFETIME_SECS: u64 = 60 * 60;
+
+/// Nonces burned by this server, checked before asking the database
+#[derive(Default)]
+pub struct NonceTracker {
+    map: HashMap<AuthedTruncateToken, HashSet<Nonce>>,
+}
+
+impl NonceTracker {
+    fn burn_nonce(&mut self, user: AuthedTruncateToken, nonce: Nonce) -> Result<(), ServerError> {
+        let set = self.map.entry(user).or_default();
+
+        let current_time = truncate_core::game::now();
+
+        // Reject all nonces older than an hour.
+        if nonce.generated_at < current_time.saturating_sub(NONCE_LIFETIME_SECS) {
+            return Err(ServerError::ReplayedNonce);
+        }
+
+        if set.insert(nonce) {
+            Ok(())
+        } else {
+            Err(ServerError::ReplayedNonce)
+        }
+    }
+
+    pub fn cleanup(&mut self, minutes: u64) {
+        let current_time = truncate_core::game::now();
+
+        self.map.values_mut().for_each(|set| {
+            set.retain(|n| n.generated_at > current_time.saturating_sub(60 * minutes));
+        })
+    }
+}
+
+/// Burns a nonce, failing if it has been used before by this or any other server.
+/// Without a database, only the nonces seen by this server are checked.
+pub async fn burn_nonce(
+    server_state: &ServerState,
+    user: AuthedTruncateToken,
+    nonce: Nonce,
+) -> Result<(), ServerError> {
+    let token = user.token();
+    server_state.nonces.lock().burn_nonce(user, nonce.clone())?;
+
+    if server_state.truncate_db.is_none() {
+        return Ok(());
+    }
+
+    match persist_nonce(server_state, &token, &nonce).await {
+        Ok(true) => Ok(()),
+        Ok(false) => Err(ServerError::ReplayedNonce),
+        Err(e) => {
+            // Losing replay protection across servers is better than dropping the message
+            tracing::error!(error = ?e, "Errored persisting nonce: {e}");
+            Ok(())
+        }
+    }
+}
+
+/// Records the nonce, returning false if it was already recorded
+async fn persist_nonce(
+    server_state: &ServerState,
+    token: &str,
+    nonce: &Nonce,
+) -> Result<bool, TruncateServerError> {
+    let Some(pool) = &server_state.truncate_db else {
+        return Err(TruncateServerError::DatabaseOffline);
+    };
+
+    let inserted = sqlx::query!(
+        "INSERT INTO burned_nonces (
+            token,
+            nonce_id,
+            generated_at
+        ) VALUES ($1, $2, $3)
+        ON CONFLICT DO NOTHING;",
+        token,
+        nonce.id as i64,
+        nonce.generated_at as i64
+    )
+    .execute(pool)
+    .await?
+    .rows_affected();
+
+    Ok(inserted == 1)
+}
+
+/// Forgets persisted nonces generated more than `minutes` ago, which would be rejected by age anyway
+pub async fn expire_nonces(
+    server_state: &ServerState,
+    minutes: u64,
+) -> Result<(), TruncateServerError> {
+    let Some(pool) = &server_state.truncate_db else {
+        return Err(TruncateServerError::DatabaseOffline);
+    };
+
+    let cutoff = truncate_core::game::now().saturating_sub(60 * minutes);
+    sqlx::query!(
+        "DELETE FROM burned_nonces WHERE generated_at < $1;",
+        cutoff as i64
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use truncate_core::game::now;
+
+    use super::*;
+
+    fn nonce(id: u64, generated_at: u64) -> Nonce {
+        Nonce { generated_at, id }
+    }
+
+    #[test]
+    fn nonces_burn_once_per_player() {
+        let mut tracker = NonceTracker::default();
+        let (alice, bob) = (
+            AuthedTruncateToken::for_tests("alice"),
+            AuthedTruncateToken::for_tests("bob"),
+        );
+
+        assert!(tracker.burn_nonce(alice.clone(), nonce(1, now())).is_ok());
+        assert!(matches!(
+            tracker.burn_nonce(alice.clone(), nonce(1, now())),
+            Err(ServerError::ReplayedNonce)
+        ));
+        assert!(tracker.burn_nonce(alice, nonce(2, now())).is_ok());
+        assert!(tracker.burn_nonce(bob, nonce(1, now())).is_ok());
+    }
+
+    #[test]
+    fn expired_nonces_are_rejected() {
+        let mut tracker = NonceTracker::default();
+        let user = AuthedTruncateToken::for_tests("alice");
+        let expired = now() - NONCE_LIFETIME_SECS - 60;
+
+        assert!(matches!(
+            tracker.burn_nonce(user, nonce(1, expired)),
+            Err(ServerError::ReplayedNonce)
+        ));
+    }
+
+    #[test]
+    fn cleanup_forgets_only_old_nonces() {
+        let mut tracker = NonceTracker::default();
+        let user = AuthedTruncateToken::for_tests("alice");
+        let recent = now();
+        let older = now() - 30 * 60;
+
+        tracker.burn_nonce(user.clone(), nonce(1, recent)).unwrap();
+        tracker.burn_nonce(user.clone(), nonce(2, older)).unwrap();
+        tracker.cleanup(10);
+
+        // Only the recent nonce is still remembered locally
+        assert!(tracker.burn_nonce(user.clone(), nonce(1, recent)).is_err());
+        assert!(tracker.burn_nonce(user, nonce(2, older)).is_ok());
+    }
+}
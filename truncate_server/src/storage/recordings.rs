@@ -0,0 +1,262 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use tokio::{fs::OpenOptions, io::AsyncWriteExt};
+use truncate_core::{
+    game::Game, generation::get_game_verification, judge::WordDict, messages::GameEndReason,
+    rules::Timing,
+};
+
+use super::daily::decode_moves;
+
+/// A finished game, with everything needed to replay it move by move.
+/// Stored as one line of JSON in the recordings file.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GameRecording {
+    pub room_code: String,
+    pub finished_at: u64,
+    /// The savegame of the game as it started, which includes the seed and state of its bag
+    pub start: String,
+    /// Every move the game accepted, stored the same way as daily puzzle attempts
+    pub moves: String,
+    pub winner: Option<usize>,
+    pub drawn: bool,
+    pub end_reason: GameEndReason,
+    /// `get_game_verification` of the final position
+    pub final_verification: String,
+}
+
+/// The outcome of replaying a recorded game from its starting savegame
+#[derive(Debug, Serialize)]
+pub struct RecordingVerification {
+    pub room_code: String,
+    pub moves: usize,
+    /// The first move the rebuilt game rejected, if any
+    pub diverged_at: Option<usize>,
+    pub recorded_winner: Option<usize>,
+    pub replayed_winner: Option<usize>,
+    pub recorded_drawn: bool,
+    pub replayed_drawn: bool,
+    pub final_verification: String,
+    pub matches: bool,
+}
+
+/// Appends a finished game to the recordings file, creating the file if needed
+pub async fn append_recording(path: &Path, recording: &GameRecording) -> std::io::Result<()> {
+    let mut line = serde_json::to_string(recording).expect("Recordings should be serializable");
+    line.push('\n');
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .await?;
+    file.write_all(line.as_bytes()).await
+}
+
+/// Replays a recorded game, confirming that its moves lead to the recorded final position.
+/// Games that ended off the board (resignations, forfeits, running out of time) don't finish
+/// when replayed, so their winner is only compared if the replay reaches a result itself.
+pub fn verify_recording(
+    recording: &GameRecording,
+    words: &WordDict,
+    added_words: &WordDict,
+) -> RecordingVerification {
+    let mut verification = RecordingVerification {
+        room_code: recording.room_code.clone(),
+        moves: 0,
+        diverged_at: Some(0),
+        recorded_winner: recording.winner,
+        replayed_winner: None,
+        recorded_drawn: recording.drawn,
+        replayed_drawn: false,
+        final_verification: String::new(),
+        matches: false,
+    };
+
+    let Ok(mut game) = Game::from_savegame(&recording.start) else {
+        tracing::warn!(room_code = %recording.room_code, "Recording has an unreadable start");
+        return verification;
+    };
+    let Ok(moves) = decode_moves(&recording.moves) else {
+        tracing::warn!(room_code = %recording.room_code, "Recording has unreadable moves");
+        return verification;
+    };
+    verification.moves = moves.len();
+    verification.diverged_at = None;
+
+    // Replays are played back instantly, so neither clocks nor battle delays apply
+    game.rules.timing = Timing::None;
+    game.rules.battle_delay = 0;
+    game.judge.extend_overlay(added_words.keys());
+
+    for (turn, next_move) in moves.iter().enumerate() {
+        if game.is_over() {
+            verification.diverged_at = Some(turn);
+            tracing::warn!(room_code = %recording.room_code, turn, "Recording has moves after its game ended");
+            break;
+        }
+        if let Err(e) = game.play_turn(next_move.clone(), Some(words), Some(words), None) {
+            verification.diverged_at = Some(turn);
+            tracing::warn!(room_code = %recording.room_code, turn, ?next_move, "Replayed recording diverged: {e}");
+            break;
+        }
+    }
+
+    verification.replayed_winner = game.winner;
+    verification.replayed_drawn = game.drawn;
+    verification.final_verification = get_game_verification(&game);
+    verification.matches = verification.diverged_at.is_none()
+        && verification.final_verification == recording.final_verification
+        && (!game.is_over() || (game.winner == recording.winner && game.drawn == recording.drawn));
+
+    verification
+}
+
+/// Replays every game in the contents of a recordings file, for batch audits
+pub fn verify_recordings(
+    recordings: &str,
+    words: &WordDict,
+    added_words: &WordDict,
+) -> Vec<RecordingVerification> {
+    recordings
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| match serde_json::from_str::<GameRecording>(line) {
+            Ok(recording) => Some(verify_recording(&recording, words, added_words)),
+            Err(e) => {
+                tracing::warn!("Skipping an unreadable recording: {e}");
+                None
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use truncate_core::{
+        bag::TileBag,
+        board::{Board, Coordinate},
+        moves::Move,
+        player::Player,
+        rules::GameRules,
+    };
+
+    use super::*;
+    use crate::{definitions::tests::word_db, storage::daily::encode_moves};
+
+    /// A game where player 0 attacks IT with ARTS, recorded as it finished
+    fn recording(words: &WordDict) -> GameRecording {
+        let board = Board::from_string(
+            "__ S0 X0 |0 __\n\
+             __ T0 __ __ __\n\
+             __ R0 __ __ __\n\
+             __ __ I1 __ __\n\
+             __ __ T1 |1 __",
+        );
+        let mut bag = TileBag::explicit(vec!['A'; 20], Some(1));
+        let players = vec![
+            Player::new("A".into(), 0, 7, &mut bag, None, (0, 0, 0)),
+            Player::new("B".into(), 1, 7, &mut bag, None, (0, 0, 0)),
+        ];
+        let rules = GameRules {
+            battle_delay: 0,
+            ..GameRules::generation(0)
+        };
+        let mut game = Game::from_board(board, players, rules);
+        game.bag = bag;
+        game.start();
+        let start = game.to_savegame();
+
+        let moves = [Move::Place {
+            player: 0,
+            tile: 'A',
+            position: Coordinate { x: 1, y: 3 },
+        }];
+        for next_move in &moves {
+            game.play_turn(next_move.clone(), Some(words), Some(words), None)
+                .unwrap();
+        }
+
+        GameRecording {
+            room_code: "test".into(),
+            finished_at: 0,
+            start,
+            moves: encode_moves(&moves),
+            winner: game.winner,
+            drawn: game.drawn,
+            end_reason: GameEndReason::Board,
+            final_verification: get_game_verification(&game),
+        }
+    }
+
+    #[test]
+    fn replays_recorded_games() {
+        let words = word_db(&["arts", "it"], &[]);
+        let recording = recording(&words.valid_words);
+
+        let verification = verify_recording(&recording, &words.valid_words, &WordDict::new());
+        assert_eq!(verification.moves, 1);
+        assert_eq!(verification.diverged_at, None);
+        assert_eq!(
+            verification.final_verification,
+            recording.final_verification
+        );
+        assert!(verification.matches);
+
+        // Words added for every room count when replaying
+        let mut missing_arts = word_db(&["it"], &[]);
+        missing_arts.merge_words(&["arts".into()]);
+        let verification = verify_recording(
+            &recording,
+            &missing_arts.valid_words,
+            &missing_arts.added_words,
+        );
+        assert!(verification.matches);
+        let verification =
+            verify_recording(&recording, &missing_arts.valid_words, &WordDict::new());
+        assert!(!verification.matches);
+    }
+
+    #[test]
+    fn reports_recordings_that_dont_replay() {
+        let words = word_db(&["arts", "it"], &[]);
+        let replay = |recording: &GameRecording| {
+            verify_recording(recording, &words.valid_words, &WordDict::new())
+        };
+
+        let mut tampered = recording(&words.valid_words);
+        tampered.final_verification = "tampered".into();
+        let verification = replay(&tampered);
+        assert_eq!(verification.diverged_at, None);
+        assert!(!verification.matches);
+
+        let mut illegal = recording(&words.valid_words);
+        illegal.moves = encode_moves(&[Move::Place {
+            player: 0,
+            tile: 'Z',
+            position: Coordinate { x: 1, y: 3 },
+        }]);
+        let verification = replay(&illegal);
+        assert_eq!(verification.diverged_at, Some(0));
+        assert!(!verification.matches);
+
+        let mut unreadable = recording(&words.valid_words);
+        unreadable.start = "not a savegame".into();
+        let verification = replay(&unreadable);
+        assert_eq!(verification.moves, 0);
+        assert_eq!(verification.diverged_at, Some(0));
+        assert!(!verification.matches);
+    }
+
+    #[test]
+    fn batch_verification_skips_unreadable_lines() {
+        let words = word_db(&["arts", "it"], &[]);
+        let line = serde_json::to_string(&recording(&words.valid_words)).unwrap();
+        let contents = format!("{line}\nnot json\n\n{line}\n");
+
+        let verifications = verify_recordings(&contents, &words.valid_words, &WordDict::new());
+        assert_eq!(verifications.len(), 2);
+        assert!(verifications.iter().all(|v| v.matches));
+    }
+}